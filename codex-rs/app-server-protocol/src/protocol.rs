@@ -94,6 +94,12 @@ client_request_definitions! {
         params: ListConversationsParams,
         response: ListConversationsResponse,
     },
+    /// Search the text of recorded conversations: messages, commands run and
+    /// files touched.
+    SearchConversations {
+        params: SearchConversationsParams,
+        response: SearchConversationsResponse,
+    },
     /// Resume a recorded Codex conversation from a rollout file.
     ResumeConversation {
         params: ResumeConversationParams,
@@ -115,6 +121,10 @@ client_request_definitions! {
         params: InterruptConversationParams,
         response: InterruptConversationResponse,
     },
+    UndoConversation {
+        params: UndoConversationParams,
+        response: UndoConversationResponse,
+    },
     AddConversationListener {
         params: AddConversationListenerParams,
         response: AddConversationSubscriptionResponse,
@@ -290,6 +300,52 @@ pub struct ListConversationsResponse {
     pub next_cursor: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchConversationsParams {
+    /// Whitespace-separated terms that must all occur in a conversation.
+    pub query: String,
+    /// Optional maximum number of results; defaults to a reasonable server-side value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchConversationsResponse {
+    /// Matching conversations, newest first.
+    pub items: Vec<ConversationSearchResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSearchResult {
+    pub conversation_id: ConversationId,
+    pub path: PathBuf,
+    pub preview: String,
+    /// RFC3339 timestamp string for the session start, if available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    pub matches: Vec<ConversationSearchMatch>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSearchMatch {
+    pub kind: ConversationSearchMatchKind,
+    /// Excerpt of the matched text around the first matched term.
+    pub snippet: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum ConversationSearchMatchKind {
+    UserMessage,
+    AgentMessage,
+    Command,
+    FileChange,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct ResumeConversationParams {
@@ -569,6 +625,27 @@ pub struct InterruptConversationResponse {
     pub abort_reason: TurnAbortReason,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoConversationParams {
+    pub conversation_id: ConversationId,
+    /// Number of turns to undo. Defaults to 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turns: Option<usize>,
+    /// Restore the workspace to the snapshot taken before this turn instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restore_to_turn: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoConversationResponse {
+    pub success: bool,
+    /// Turn whose snapshot the workspace was restored to.
+    pub restored_turn: Option<u64>,
+    pub message: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct SendUserMessageResponse {}
@@ -699,6 +776,11 @@ pub struct ExecCommandApprovalParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
     pub parsed_cmd: Vec<ParsedCommand>,
+    /// Directories the sandbox refused to write to, offered as an
+    /// alternative to running without the sandbox. Answer with
+    /// [`ReviewDecision::ApprovedWithWritableRoots`] to accept them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggested_writable_roots: Vec<PathBuf>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -909,6 +991,7 @@ mod tests {
             parsed_cmd: vec![ParsedCommand::Unknown {
                 cmd: "echo hello".to_string(),
             }],
+            suggested_writable_roots: Vec::new(),
         };
         let request = ServerRequest::ExecCommandApproval {
             request_id: RequestId::Integer(7),
@@ -11,6 +11,9 @@ use codex_app_server_protocol::ArchiveConversationParams;
 use codex_app_server_protocol::ArchiveConversationResponse;
 use codex_app_server_protocol::AuthStatusChangeNotification;
 use codex_app_server_protocol::ClientRequest;
+use codex_app_server_protocol::ConversationSearchMatch;
+use codex_app_server_protocol::ConversationSearchMatchKind;
+use codex_app_server_protocol::ConversationSearchResult;
 use codex_app_server_protocol::ConversationSummary;
 use codex_app_server_protocol::ExecCommandApprovalParams;
 use codex_app_server_protocol::ExecCommandApprovalResponse;
@@ -38,6 +41,8 @@ use codex_app_server_protocol::RemoveConversationSubscriptionResponse;
 use codex_app_server_protocol::RequestId;
 use codex_app_server_protocol::Result as JsonRpcResult;
 use codex_app_server_protocol::ResumeConversationParams;
+use codex_app_server_protocol::SearchConversationsParams;
+use codex_app_server_protocol::SearchConversationsResponse;
 use codex_app_server_protocol::SendUserMessageParams;
 use codex_app_server_protocol::SendUserMessageResponse;
 use codex_app_server_protocol::SendUserTurnParams;
@@ -47,6 +52,8 @@ use codex_app_server_protocol::ServerRequestPayload;
 use codex_app_server_protocol::SessionConfiguredNotification;
 use codex_app_server_protocol::SetDefaultModelParams;
 use codex_app_server_protocol::SetDefaultModelResponse;
+use codex_app_server_protocol::UndoConversationParams;
+use codex_app_server_protocol::UndoConversationResponse;
 use codex_app_server_protocol::UserInfoResponse;
 use codex_app_server_protocol::UserSavedConfig;
 use codex_core::AuthManager;
@@ -56,6 +63,7 @@ use codex_core::Cursor as RolloutCursor;
 use codex_core::INTERACTIVE_SESSION_SOURCES;
 use codex_core::NewConversation;
 use codex_core::RolloutRecorder;
+use codex_core::SearchMatchKind;
 use codex_core::SessionMeta;
 use codex_core::auth::CLIENT_ID;
 use codex_core::auth::get_auth_file;
@@ -78,8 +86,10 @@ use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::InputItem as CoreInputItem;
+use codex_core::protocol::McpSamplingApprovalRequestEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::UndoCompletedEvent;
 use codex_login::ServerOptions as LoginServerOptions;
 use codex_login::ShutdownHandle;
 use codex_login::run_login_server;
@@ -129,6 +139,8 @@ pub(crate) struct CodexMessageProcessor {
     active_login: Arc<Mutex<Option<ActiveLogin>>>,
     // Queue of pending interrupt requests per conversation. We reply when TurnAborted arrives.
     pending_interrupts: Arc<Mutex<HashMap<ConversationId, Vec<RequestId>>>>,
+    // Queue of pending undo requests per conversation. We reply when UndoCompleted arrives.
+    pending_undos: Arc<Mutex<HashMap<ConversationId, Vec<RequestId>>>>,
     pending_fuzzy_searches: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
 }
 
@@ -149,6 +161,7 @@ impl CodexMessageProcessor {
             conversation_listeners: HashMap::new(),
             active_login: Arc::new(Mutex::new(None)),
             pending_interrupts: Arc::new(Mutex::new(HashMap::new())),
+            pending_undos: Arc::new(Mutex::new(HashMap::new())),
             pending_fuzzy_searches: Arc::new(Mutex::new(HashMap::new())),
         }
     }
@@ -167,6 +180,9 @@ impl CodexMessageProcessor {
             ClientRequest::ListConversations { request_id, params } => {
                 self.handle_list_conversations(request_id, params).await;
             }
+            ClientRequest::SearchConversations { request_id, params } => {
+                self.handle_search_conversations(request_id, params).await;
+            }
             ClientRequest::ResumeConversation { request_id, params } => {
                 self.handle_resume_conversation(request_id, params).await;
             }
@@ -182,6 +198,9 @@ impl CodexMessageProcessor {
             ClientRequest::InterruptConversation { request_id, params } => {
                 self.interrupt_conversation(request_id, params).await;
             }
+            ClientRequest::UndoConversation { request_id, params } => {
+                self.undo_conversation(request_id, params).await;
+            }
             ClientRequest::AddConversationListener { request_id, params } => {
                 self.add_conversation_listener(request_id, params).await;
             }
@@ -613,13 +632,22 @@ impl CodexMessageProcessor {
             codex_core::protocol::SandboxPolicy::DangerFullAccess => {
                 codex_core::exec::SandboxType::None
             }
-            _ => get_platform_sandbox().unwrap_or(codex_core::exec::SandboxType::None),
+            _ => get_platform_sandbox()
+                .map(|sandbox| {
+                    codex_core::executor::sandbox_type_for_backend(
+                        sandbox,
+                        self.config.linux_sandbox_backend,
+                    )
+                })
+                .unwrap_or(codex_core::exec::SandboxType::None),
         };
         tracing::debug!("Sandbox type: {sandbox_type:?}");
         let codex_linux_sandbox_exe = self.config.codex_linux_sandbox_exe.clone();
         let outgoing = self.outgoing.clone();
         let req_id = request_id;
         let sandbox_cwd = self.config.cwd.clone();
+        let resource_limits = self.config.sandbox_resource_limits;
+        let report_denied_writes = self.config.sandbox_report_denied_writes;
 
         tokio::spawn(async move {
             match codex_core::exec::process_exec_tool_call(
@@ -628,6 +656,8 @@ impl CodexMessageProcessor {
                 &effective_policy,
                 sandbox_cwd.as_path(),
                 &codex_linux_sandbox_exe,
+                &resource_limits,
+                report_denied_writes,
                 None,
             )
             .await
@@ -745,6 +775,69 @@ impl CodexMessageProcessor {
         self.outgoing.send_response(request_id, response).await;
     }
 
+    async fn handle_search_conversations(
+        &self,
+        request_id: RequestId,
+        params: SearchConversationsParams,
+    ) {
+        let SearchConversationsParams { query, limit } = params;
+        let page = match RolloutRecorder::search_conversations(
+            &self.config.codex_home,
+            &query,
+            limit.unwrap_or(25),
+            INTERACTIVE_SESSION_SOURCES,
+        )
+        .await
+        {
+            Ok(page) => page,
+            Err(err) => {
+                let error = JSONRPCErrorError {
+                    code: INTERNAL_ERROR_CODE,
+                    message: format!("failed to search conversations: {err}"),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        let items = page
+            .items
+            .into_iter()
+            .filter_map(|result| {
+                Some(ConversationSearchResult {
+                    conversation_id: result.conversation_id?,
+                    path: result.path,
+                    preview: result.preview.unwrap_or_default(),
+                    timestamp: result.created_at,
+                    matches: result
+                        .matches
+                        .into_iter()
+                        .map(|m| ConversationSearchMatch {
+                            kind: match m.kind {
+                                SearchMatchKind::UserMessage => {
+                                    ConversationSearchMatchKind::UserMessage
+                                }
+                                SearchMatchKind::AgentMessage => {
+                                    ConversationSearchMatchKind::AgentMessage
+                                }
+                                SearchMatchKind::Command => ConversationSearchMatchKind::Command,
+                                SearchMatchKind::FileChange => {
+                                    ConversationSearchMatchKind::FileChange
+                                }
+                            },
+                            snippet: m.snippet,
+                        })
+                        .collect(),
+                })
+            })
+            .collect();
+
+        self.outgoing
+            .send_response(request_id, SearchConversationsResponse { items })
+            .await;
+    }
+
     async fn handle_resume_conversation(
         &self,
         request_id: RequestId,
@@ -1088,6 +1181,42 @@ impl CodexMessageProcessor {
         let _ = conversation.submit(Op::Interrupt).await;
     }
 
+    async fn undo_conversation(&mut self, request_id: RequestId, params: UndoConversationParams) {
+        let UndoConversationParams {
+            conversation_id,
+            turns,
+            restore_to_turn,
+        } = params;
+        let Ok(conversation) = self
+            .conversation_manager
+            .get_conversation(conversation_id)
+            .await
+        else {
+            let error = JSONRPCErrorError {
+                code: INVALID_REQUEST_ERROR_CODE,
+                message: format!("conversation not found: {conversation_id}"),
+                data: None,
+            };
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        };
+
+        let op = match restore_to_turn {
+            Some(turn) => Op::RestoreToTurn { turn },
+            None => Op::Undo {
+                turns: turns.unwrap_or(1),
+            },
+        };
+
+        // Record the pending undo so we can reply when UndoCompleted arrives.
+        {
+            let mut map = self.pending_undos.lock().await;
+            map.entry(conversation_id).or_default().push(request_id);
+        }
+
+        let _ = conversation.submit(op).await;
+    }
+
     async fn add_conversation_listener(
         &mut self,
         request_id: RequestId,
@@ -1114,6 +1243,7 @@ impl CodexMessageProcessor {
             .insert(subscription_id, cancel_tx);
         let outgoing_for_task = self.outgoing.clone();
         let pending_interrupts = self.pending_interrupts.clone();
+        let pending_undos = self.pending_undos.clone();
         tokio::spawn(async move {
             loop {
                 tokio::select! {
@@ -1154,7 +1284,7 @@ impl CodexMessageProcessor {
                         })
                         .await;
 
-                        apply_bespoke_event_handling(event.clone(), conversation_id, conversation.clone(), outgoing_for_task.clone(), pending_interrupts.clone()).await;
+                        apply_bespoke_event_handling(event.clone(), conversation_id, conversation.clone(), outgoing_for_task.clone(), pending_interrupts.clone(), pending_undos.clone()).await;
                     }
                 }
             }
@@ -1255,6 +1385,7 @@ async fn apply_bespoke_event_handling(
     conversation: Arc<CodexConversation>,
     outgoing: Arc<OutgoingMessageSender>,
     pending_interrupts: Arc<Mutex<HashMap<ConversationId, Vec<RequestId>>>>,
+    pending_undos: Arc<Mutex<HashMap<ConversationId, Vec<RequestId>>>>,
 ) {
     let Event { id: event_id, msg } = event;
     match msg {
@@ -1285,6 +1416,7 @@ async fn apply_bespoke_event_handling(
             cwd,
             reason,
             parsed_cmd,
+            suggested_writable_roots,
         }) => {
             let params = ExecCommandApprovalParams {
                 conversation_id,
@@ -1293,6 +1425,7 @@ async fn apply_bespoke_event_handling(
                 cwd,
                 reason,
                 parsed_cmd,
+                suggested_writable_roots,
             };
             let rx = outgoing
                 .send_request(ServerRequestPayload::ExecCommandApproval(params))
@@ -1303,6 +1436,24 @@ async fn apply_bespoke_event_handling(
                 on_exec_approval_response(event_id, rx, conversation).await;
             });
         }
+        EventMsg::McpSamplingApprovalRequest(McpSamplingApprovalRequestEvent {
+            call_id,
+            server,
+            ..
+        }) => {
+            // Clients have no way to approve sampling requests yet, so decline
+            // instead of leaving the MCP server waiting for an answer.
+            info!("declining sampling request from MCP server `{server}`");
+            if let Err(err) = conversation
+                .submit(Op::McpSamplingApproval {
+                    id: call_id,
+                    decision: ReviewDecision::Denied,
+                })
+                .await
+            {
+                error!("failed to decline sampling request: {err}");
+            }
+        }
         // If this is a TurnAborted, reply to any pending interrupt requests.
         EventMsg::TurnAborted(turn_aborted_event) => {
             let pending = {
@@ -1318,6 +1469,28 @@ async fn apply_bespoke_event_handling(
                 }
             }
         }
+        // Undo requests are answered in order, one per UndoCompleted.
+        EventMsg::UndoCompleted(UndoCompletedEvent {
+            success,
+            restored_turn,
+            message,
+        }) => {
+            let request_id = {
+                let mut map = pending_undos.lock().await;
+                match map.get_mut(&conversation_id) {
+                    Some(pending) if !pending.is_empty() => Some(pending.remove(0)),
+                    _ => None,
+                }
+            };
+            if let Some(request_id) = request_id {
+                let response = UndoConversationResponse {
+                    success,
+                    restored_turn,
+                    message,
+                };
+                outgoing.send_response(request_id, response).await;
+            }
+        }
 
         _ => {}
     }
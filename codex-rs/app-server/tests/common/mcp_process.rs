@@ -25,10 +25,12 @@ use codex_app_server_protocol::LoginApiKeyParams;
 use codex_app_server_protocol::NewConversationParams;
 use codex_app_server_protocol::RemoveConversationListenerParams;
 use codex_app_server_protocol::ResumeConversationParams;
+use codex_app_server_protocol::SearchConversationsParams;
 use codex_app_server_protocol::SendUserMessageParams;
 use codex_app_server_protocol::SendUserTurnParams;
 use codex_app_server_protocol::ServerRequest;
 use codex_app_server_protocol::SetDefaultModelParams;
+use codex_app_server_protocol::UndoConversationParams;
 
 use codex_app_server_protocol::JSONRPCError;
 use codex_app_server_protocol::JSONRPCMessage;
@@ -217,6 +219,15 @@ impl McpProcess {
         self.send_request("interruptConversation", params).await
     }
 
+    /// Send an `undoConversation` JSON-RPC request.
+    pub async fn send_undo_conversation_request(
+        &mut self,
+        params: UndoConversationParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("undoConversation", params).await
+    }
+
     /// Send a `getAuthStatus` JSON-RPC request.
     pub async fn send_get_auth_status_request(
         &mut self,
@@ -259,6 +270,15 @@ impl McpProcess {
         self.send_request("listConversations", params).await
     }
 
+    /// Send a `searchConversations` JSON-RPC request.
+    pub async fn send_search_conversations_request(
+        &mut self,
+        params: SearchConversationsParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("searchConversations", params).await
+    }
+
     /// Send a `resumeConversation` JSON-RPC request.
     pub async fn send_resume_conversation_request(
         &mut self,
@@ -315,6 +315,7 @@ async fn test_send_user_turn_changes_approval_policy_behavior() {
             parsed_cmd: vec![ParsedCommand::Unknown {
                 cmd: "python3 -c 'print(42)'".to_string()
             }],
+            suggested_writable_roots: Vec::new(),
         },
         params
     );
@@ -480,7 +481,9 @@ async fn test_send_user_turn_updates_sandbox_and_cwd_between_turns() {
             approval_policy: AskForApproval::Never,
             sandbox_policy: SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![first_cwd.clone()],
+                deny_read: Vec::new(),
                 network_access: false,
+                network_allowlist: Vec::new(),
                 exclude_tmpdir_env_var: false,
                 exclude_slash_tmp: false,
             },
@@ -0,0 +1,157 @@
+use std::path::Path;
+use std::process::Command;
+
+use app_test_support::McpProcess;
+use app_test_support::create_final_assistant_message_sse_response;
+use app_test_support::create_mock_chat_completions_server;
+use app_test_support::to_response;
+use codex_app_server_protocol::AddConversationListenerParams;
+use codex_app_server_protocol::InputItem;
+use codex_app_server_protocol::JSONRPCResponse;
+use codex_app_server_protocol::NewConversationParams;
+use codex_app_server_protocol::NewConversationResponse;
+use codex_app_server_protocol::RequestId;
+use codex_app_server_protocol::SendUserMessageParams;
+use codex_app_server_protocol::UndoConversationParams;
+use codex_app_server_protocol::UndoConversationResponse;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use tokio::time::timeout;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn undo_conversation_restores_the_workspace() -> anyhow::Result<()> {
+    let server =
+        create_mock_chat_completions_server(vec![create_final_assistant_message_sse_response(
+            "Done",
+        )?])
+        .await;
+
+    let tmp = TempDir::new()?;
+    let codex_home = tmp.path().join("codex_home");
+    std::fs::create_dir(&codex_home)?;
+    create_config_toml(&codex_home, &server.uri())?;
+    let working_directory = tmp.path().join("workdir");
+    std::fs::create_dir(&working_directory)?;
+    let status = Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(&working_directory)
+        .status()?;
+    assert!(status.success(), "git init failed");
+    let file = working_directory.join("notes.txt");
+    std::fs::write(&file, "before")?;
+
+    let mut mcp = McpProcess::new(&codex_home).await?;
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize()).await??;
+
+    let new_conv_id = mcp
+        .send_new_conversation_request(NewConversationParams {
+            cwd: Some(working_directory.to_string_lossy().into_owned()),
+            ..Default::default()
+        })
+        .await?;
+    let new_conv_resp: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(new_conv_id)),
+    )
+    .await??;
+    let NewConversationResponse {
+        conversation_id, ..
+    } = to_response::<NewConversationResponse>(new_conv_resp)?;
+
+    let add_listener_id = mcp
+        .send_add_conversation_listener_request(AddConversationListenerParams { conversation_id })
+        .await?;
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(add_listener_id)),
+    )
+    .await??;
+
+    let send_id = mcp
+        .send_send_user_message_request(SendUserMessageParams {
+            conversation_id,
+            items: vec![InputItem::Text {
+                text: "edit the notes".to_string(),
+            }],
+        })
+        .await?;
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(send_id)),
+    )
+    .await??;
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_notification_message("codex/event/task_complete"),
+    )
+    .await??;
+    std::fs::write(&file, "after")?;
+
+    let undo_id = mcp
+        .send_undo_conversation_request(UndoConversationParams {
+            conversation_id,
+            turns: None,
+            restore_to_turn: None,
+        })
+        .await?;
+    let undo_resp: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(undo_id)),
+    )
+    .await??;
+    let undo = to_response::<UndoConversationResponse>(undo_resp)?;
+    assert!(undo.success, "undo failed: {:?}", undo.message);
+    assert_eq!(undo.restored_turn, Some(1));
+    assert_eq!(std::fs::read_to_string(&file)?, "before");
+
+    let undo_id = mcp
+        .send_undo_conversation_request(UndoConversationParams {
+            conversation_id,
+            turns: Some(1),
+            restore_to_turn: None,
+        })
+        .await?;
+    let undo_resp: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(undo_id)),
+    )
+    .await??;
+    assert_eq!(
+        to_response::<UndoConversationResponse>(undo_resp)?,
+        UndoConversationResponse {
+            success: false,
+            restored_turn: None,
+            message: Some("No snapshot available to undo.".to_string()),
+        }
+    );
+
+    Ok(())
+}
+
+fn create_config_toml(codex_home: &Path, server_uri: &str) -> std::io::Result<()> {
+    let config_toml = codex_home.join("config.toml");
+    std::fs::write(
+        config_toml,
+        format!(
+            r#"
+model = "mock-model"
+approval_policy = "never"
+sandbox_mode = "danger-full-access"
+
+model_provider = "mock_provider"
+
+[features]
+ghost_commit = true
+
+[model_providers.mock_provider]
+name = "Mock provider for test"
+base_url = "{server_uri}/v1"
+wire_api = "chat"
+request_max_retries = 0
+stream_max_retries = 0
+"#
+        ),
+    )
+}
@@ -1,6 +1,7 @@
 mod parser;
 mod seek_sequence;
 mod standalone_executable;
+mod three_way;
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -13,7 +14,7 @@ use anyhow::Result;
 pub use parser::Hunk;
 pub use parser::ParseError;
 use parser::ParseError::*;
-use parser::UpdateFileChunk;
+pub use parser::UpdateFileChunk;
 pub use parser::parse_patch;
 use similar::TextDiff;
 use thiserror::Error;
@@ -127,6 +128,9 @@ pub fn maybe_parse_apply_patch(argv: &[String]) -> MaybeApplyPatch {
 pub enum ApplyPatchFileChange {
     Add {
         content: String,
+        /// `Some(true)` to make the file executable, `Some(false)` to clear
+        /// the executable bit, `None` to leave the mode alone.
+        executable: Option<bool>,
     },
     Delete {
         content: String,
@@ -136,6 +140,16 @@ pub enum ApplyPatchFileChange {
         move_path: Option<PathBuf>,
         /// new_content that will result after the unified_diff is applied.
         new_content: String,
+        executable: Option<bool>,
+    },
+    /// The file is created as a copy of `source`.
+    Copy {
+        source: PathBuf,
+    },
+    /// The file is created or overwritten with `contents`.
+    Binary {
+        contents: Vec<u8>,
+        executable: Option<bool>,
     },
 }
 
@@ -167,6 +181,19 @@ pub struct ApplyPatchAction {
 
     /// The working directory that was used to resolve relative paths in the patch.
     pub cwd: PathBuf,
+
+    /// Hunks that only applied with a three-way merge, see
+    /// [`maybe_parse_apply_patch_verified_with_merge_base`].
+    fuzzy_hunks: Vec<FuzzyHunk>,
+}
+
+/// A hunk whose context did not match the file on disk and that was applied
+/// with a three-way merge against the version of the file it was written for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyHunk {
+    pub path: PathBuf,
+    /// 1-based index of the hunk within the file's `*** Update File` section.
+    pub hunk: usize,
 }
 
 impl ApplyPatchAction {
@@ -179,6 +206,11 @@ impl ApplyPatchAction {
         &self.changes
     }
 
+    /// Returns the hunks that were applied with a three-way merge.
+    pub fn fuzzy_hunks(&self) -> &[FuzzyHunk] {
+        &self.fuzzy_hunks
+    }
+
     /// Should be used exclusively for testing. (Not worth the overhead of
     /// creating a feature flag for this.)
     pub fn new_add_for_test(path: &Path, content: String) -> Self {
@@ -198,7 +230,13 @@ impl ApplyPatchAction {
 + {content}
 *** End Patch"#,
         );
-        let changes = HashMap::from([(path.to_path_buf(), ApplyPatchFileChange::Add { content })]);
+        let changes = HashMap::from([(
+            path.to_path_buf(),
+            ApplyPatchFileChange::Add {
+                content,
+                executable: None,
+            },
+        )]);
         #[expect(clippy::expect_used)]
         Self {
             changes,
@@ -207,6 +245,7 @@ impl ApplyPatchAction {
                 .expect("path should have parent")
                 .to_path_buf(),
             patch,
+            fuzzy_hunks: Vec::new(),
         }
     }
 }
@@ -214,6 +253,21 @@ impl ApplyPatchAction {
 /// cwd must be an absolute path so that we can resolve relative paths in the
 /// patch.
 pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApplyPatchVerified {
+    maybe_parse_apply_patch_verified_with_merge_base(argv, cwd, |_| None)
+}
+
+/// Like [`maybe_parse_apply_patch_verified`], but when the context of an
+/// update no longer matches the file, `merge_base` is asked for the version of
+/// the file (by absolute path) the patch was presumably written against. The
+/// update is then applied to that version and merged into the current
+/// contents, like `git merge-file`. The returned action's `patch` is rewritten
+/// so it applies cleanly to the current contents, and the affected hunks are
+/// listed in [`ApplyPatchAction::fuzzy_hunks`].
+pub fn maybe_parse_apply_patch_verified_with_merge_base(
+    argv: &[String],
+    cwd: &Path,
+    merge_base: impl Fn(&Path) -> Option<String>,
+) -> MaybeApplyPatchVerified {
     // Detect a raw patch body passed directly as the command or as the body of a bash -lc
     // script. In these cases, report an explicit error rather than applying the patch.
     match argv {
@@ -252,11 +306,23 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                 })
                 .unwrap_or_else(|| cwd.to_path_buf());
             let mut changes = HashMap::new();
-            for hunk in hunks {
+            let mut fuzzy_hunks = Vec::new();
+            let mut merged_sections = HashMap::new();
+            for (section, hunk) in hunks.into_iter().enumerate() {
                 let path = hunk.resolve_path(&effective_cwd);
                 match hunk {
-                    Hunk::AddFile { contents, .. } => {
-                        changes.insert(path, ApplyPatchFileChange::Add { content: contents });
+                    Hunk::AddFile {
+                        contents,
+                        executable,
+                        ..
+                    } => {
+                        changes.insert(
+                            path,
+                            ApplyPatchFileChange::Add {
+                                content: contents,
+                                executable,
+                            },
+                        );
                     }
                     Hunk::DeleteFile { .. } => {
                         let content = match std::fs::read_to_string(&path) {
@@ -273,12 +339,55 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                         changes.insert(path, ApplyPatchFileChange::Delete { content });
                     }
                     Hunk::UpdateFile {
-                        move_path, chunks, ..
+                        move_path,
+                        executable,
+                        chunks,
+                        ..
                     } => {
+                        let update = if chunks.is_empty() {
+                            // Pure renames and mode changes keep the contents,
+                            // which need not be text.
+                            std::fs::read(&path)
+                                .map(|contents| ApplyPatchFileUpdate {
+                                    unified_diff: String::new(),
+                                    content: String::from_utf8_lossy(&contents).into_owned(),
+                                })
+                                .map_err(|e| {
+                                    ApplyPatchError::IoError(IoError {
+                                        context: format!("Failed to read {}", path.display()),
+                                        source: e,
+                                    })
+                                })
+                        } else {
+                            match unified_diff_from_chunks(&path, &chunks) {
+                                Err(err @ ApplyPatchError::ComputeReplacements(_)) => {
+                                    match merge_base(&path)
+                                        .map(|base| three_way::merge_update(&path, &base, &chunks))
+                                    {
+                                        Some(Ok(merged)) => {
+                                            fuzzy_hunks.extend(
+                                                merged.fuzzy_chunks.into_iter().map(|hunk| {
+                                                    FuzzyHunk {
+                                                        path: path.clone(),
+                                                        hunk,
+                                                    }
+                                                }),
+                                            );
+                                            merged_sections.insert(section, merged.chunks_text);
+                                            Ok(merged.update)
+                                        }
+                                        // Report the mismatch the model can act on rather
+                                        // than why the merge failed.
+                                        Some(Err(_)) | None => Err(err),
+                                    }
+                                }
+                                result => result,
+                            }
+                        };
                         let ApplyPatchFileUpdate {
                             unified_diff,
                             content: contents,
-                        } = match unified_diff_from_chunks(&path, &chunks) {
+                        } = match update {
                             Ok(diff) => diff,
                             Err(e) => {
                                 return MaybeApplyPatchVerified::CorrectnessError(e);
@@ -290,15 +399,49 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                                 unified_diff,
                                 move_path: move_path.map(|p| cwd.join(p)),
                                 new_content: contents,
+                                executable,
+                            },
+                        );
+                    }
+                    Hunk::CopyFile { copy_path, .. } => {
+                        if let Err(e) = std::fs::metadata(&path) {
+                            return MaybeApplyPatchVerified::CorrectnessError(
+                                ApplyPatchError::IoError(IoError {
+                                    context: format!("Failed to read {}", path.display()),
+                                    source: e,
+                                }),
+                            );
+                        }
+                        changes.insert(
+                            effective_cwd.join(copy_path),
+                            ApplyPatchFileChange::Copy { source: path },
+                        );
+                    }
+                    Hunk::BinaryFile {
+                        contents,
+                        executable,
+                        ..
+                    } => {
+                        changes.insert(
+                            path,
+                            ApplyPatchFileChange::Binary {
+                                contents,
+                                executable,
                             },
                         );
                     }
                 }
             }
+            let patch = if merged_sections.is_empty() {
+                patch
+            } else {
+                three_way::replace_section_chunks(&patch, &merged_sections)
+            };
             MaybeApplyPatchVerified::Body(ApplyPatchAction {
                 changes,
                 patch,
                 cwd: effective_cwd,
+                fuzzy_hunks,
             })
         }
         MaybeApplyPatch::ShellParseError(e) => MaybeApplyPatchVerified::ShellParseError(e),
@@ -514,7 +657,8 @@ pub fn apply_hunks(
                 // The file is being added, so it doesn't exist yet.
                 None
             }
-            Hunk::DeleteFile { path } => Some(path.as_path()),
+            Hunk::DeleteFile { path } | Hunk::CopyFile { path, .. } => Some(path.as_path()),
+            Hunk::BinaryFile { .. } => None,
             Hunk::UpdateFile {
                 path, move_path, ..
             } => match move_path {
@@ -575,16 +719,15 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     let mut deleted: Vec<PathBuf> = Vec::new();
     for hunk in hunks {
         match hunk {
-            Hunk::AddFile { path, contents } => {
-                if let Some(parent) = path.parent()
-                    && !parent.as_os_str().is_empty()
-                {
-                    std::fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directories for {}", path.display())
-                    })?;
-                }
+            Hunk::AddFile {
+                path,
+                contents,
+                executable,
+            } => {
+                create_parent_dirs(path)?;
                 std::fs::write(path, contents)
                     .with_context(|| format!("Failed to write file {}", path.display()))?;
+                set_executable(path, *executable)?;
                 added.push(path.clone());
             }
             Hunk::DeleteFile { path } => {
@@ -595,29 +738,77 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
             Hunk::UpdateFile {
                 path,
                 move_path,
+                executable,
+                chunks,
+            } if chunks.is_empty() => {
+                // Pure rename and/or mode change: copying keeps binary
+                // contents and the existing permissions intact.
+                let dest = match move_path {
+                    Some(dest) => {
+                        create_parent_dirs(dest)?;
+                        std::fs::copy(path, dest).with_context(|| {
+                            format!("Failed to move {} to {}", path.display(), dest.display())
+                        })?;
+                        std::fs::remove_file(path).with_context(|| {
+                            format!("Failed to remove original {}", path.display())
+                        })?;
+                        dest
+                    }
+                    None => path,
+                };
+                set_executable(dest, *executable)?;
+                modified.push(dest.clone());
+            }
+            Hunk::UpdateFile {
+                path,
+                move_path,
+                executable,
                 chunks,
             } => {
                 let AppliedPatch { new_contents, .. } =
                     derive_new_contents_from_chunks(path, chunks)?;
                 if let Some(dest) = move_path {
-                    if let Some(parent) = dest.parent()
-                        && !parent.as_os_str().is_empty()
-                    {
-                        std::fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directories for {}", dest.display())
-                        })?;
-                    }
+                    create_parent_dirs(dest)?;
                     std::fs::write(dest, new_contents)
                         .with_context(|| format!("Failed to write file {}", dest.display()))?;
                     std::fs::remove_file(path)
                         .with_context(|| format!("Failed to remove original {}", path.display()))?;
+                    set_executable(dest, *executable)?;
                     modified.push(dest.clone());
                 } else {
                     std::fs::write(path, new_contents)
                         .with_context(|| format!("Failed to write file {}", path.display()))?;
+                    set_executable(path, *executable)?;
                     modified.push(path.clone());
                 }
             }
+            Hunk::CopyFile { path, copy_path } => {
+                create_parent_dirs(copy_path)?;
+                std::fs::copy(path, copy_path).with_context(|| {
+                    format!(
+                        "Failed to copy {} to {}",
+                        path.display(),
+                        copy_path.display()
+                    )
+                })?;
+                added.push(copy_path.clone());
+            }
+            Hunk::BinaryFile {
+                path,
+                contents,
+                executable,
+            } => {
+                let existed = path.exists();
+                create_parent_dirs(path)?;
+                std::fs::write(path, contents)
+                    .with_context(|| format!("Failed to write file {}", path.display()))?;
+                set_executable(path, *executable)?;
+                if existed {
+                    modified.push(path.clone());
+                } else {
+                    added.push(path.clone());
+                }
+            }
         }
     }
     Ok(AffectedPaths {
@@ -627,6 +818,48 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     })
 }
 
+fn create_parent_dirs(path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent).with_context(|| {
+            format!("Failed to create parent directories for {}", path.display())
+        })?;
+    }
+    Ok(())
+}
+
+/// Sets or clears the executable bits of `path` (`chmod +x` / `chmod -x`).
+/// `None` leaves the mode alone.
+#[cfg(unix)]
+fn set_executable(path: &Path, executable: Option<bool>) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let Some(executable) = executable else {
+        return Ok(());
+    };
+    let mut permissions = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read permissions of {}", path.display()))?
+        .permissions();
+    let mode = permissions.mode();
+    let new_mode = if executable {
+        // Grant execute wherever read is granted, like `chmod +x`.
+        mode | ((mode & 0o444) >> 2)
+    } else {
+        mode & !0o111
+    };
+    if new_mode != mode {
+        permissions.set_mode(new_mode);
+        std::fs::set_permissions(path, permissions)
+            .with_context(|| format!("Failed to change mode of {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path, _executable: Option<bool>) -> anyhow::Result<()> {
+    Ok(())
+}
+
 struct AppliedPatch {
     original_contents: String,
     new_contents: String,
@@ -647,26 +880,37 @@ fn derive_new_contents_from_chunks(
             }));
         }
     };
+    let new_contents = apply_chunks_to_contents(&original_contents, path, chunks)?;
+    Ok(AppliedPatch {
+        original_contents,
+        new_contents,
+    })
+}
 
-    let mut original_lines: Vec<String> = original_contents.split('\n').map(String::from).collect();
-
-    // Drop the trailing empty element that results from the final newline so
-    // that line counts match the behaviour of standard `diff`.
-    if original_lines.last().is_some_and(String::is_empty) {
-        original_lines.pop();
-    }
-
+/// Apply the chunks of an `*** Update File` hunk to `original_contents`, the
+/// current contents of the file at `path`, and return the new contents.
+pub fn apply_chunks_to_contents(
+    original_contents: &str,
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+) -> std::result::Result<String, ApplyPatchError> {
+    let original_lines = split_lines(original_contents);
     let replacements = compute_replacements(&original_lines, path, chunks)?;
-    let new_lines = apply_replacements(original_lines, &replacements);
-    let mut new_lines = new_lines;
+    let mut new_lines = apply_replacements(original_lines, &replacements);
     if !new_lines.last().is_some_and(String::is_empty) {
         new_lines.push(String::new());
     }
-    let new_contents = new_lines.join("\n");
-    Ok(AppliedPatch {
-        original_contents,
-        new_contents,
-    })
+    Ok(new_lines.join("\n"))
+}
+
+fn split_lines(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = contents.split('\n').map(String::from).collect();
+    // Drop the trailing empty element that results from the final newline so
+    // that line counts match the behaviour of standard `diff`.
+    if lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    lines
 }
 
 /// Compute a list of replacements needed to transform `original_lines` into the
@@ -879,6 +1123,7 @@ mod tests {
         vec![Hunk::AddFile {
             path: PathBuf::from("foo"),
             contents: "hi\n".to_string(),
+            executable: None,
         }]
     }
 
@@ -940,7 +1185,8 @@ mod tests {
                     hunks,
                     vec![Hunk::AddFile {
                         path: PathBuf::from("foo"),
-                        contents: "hi\n".to_string()
+                        contents: "hi\n".to_string(),
+                        executable: None,
                     }]
                 );
             }
@@ -965,7 +1211,8 @@ mod tests {
                     hunks,
                     vec![Hunk::AddFile {
                         path: PathBuf::from("foo"),
-                        contents: "hi\n".to_string()
+                        contents: "hi\n".to_string(),
+                        executable: None,
                     }]
                 );
             }
@@ -998,7 +1245,8 @@ PATCH"#,
                     hunks,
                     vec![Hunk::AddFile {
                         path: PathBuf::from("foo"),
-                        contents: "hi\n".to_string()
+                        contents: "hi\n".to_string(),
+                        executable: None,
                     }]
                 );
             }
@@ -1176,6 +1424,76 @@ PATCH"#,
         assert_eq!(contents, "line2\n");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_mode_lines_set_and_clear_the_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        let tool = dir.path().join("tool.py");
+        fs::write(&tool, "print()\n").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Add File: {}
+*** Mode: +x
++#!/bin/sh
+*** Update File: {}
+*** Mode: -x"#,
+            script.display(),
+            tool.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o111;
+        assert_ne!(mode(&script), 0);
+        assert_eq!(mode(&tool), 0);
+        assert_eq!(fs::read_to_string(&tool).unwrap(), "print()\n");
+    }
+
+    #[test]
+    fn test_copy_rename_and_binary_hunks() {
+        let dir = tempdir().unwrap();
+        let original = dir.path().join("a.txt");
+        let copy = dir.path().join("nested/b.txt");
+        let blob = dir.path().join("blob.bin");
+        let moved = dir.path().join("moved.bin");
+        fs::write(&original, "same\n").unwrap();
+        fs::write(&blob, [0xff, 0x00]).unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Copy File: {}
+*** Copy to: {}
+*** Update File: {}
+*** Move to: {}
+*** Binary File: {}
++AAEC/w=="#,
+            original.display(),
+            copy.display(),
+            blob.display(),
+            moved.display(),
+            original.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            format!(
+                "Success. Updated the following files:\nA {}\nM {}\nM {}\n",
+                copy.display(),
+                moved.display(),
+                original.display()
+            )
+        );
+        assert_eq!(fs::read_to_string(&copy).unwrap(), "same\n");
+        assert!(!blob.exists());
+        assert_eq!(fs::read(&moved).unwrap(), vec![0xff, 0x00]);
+        assert_eq!(fs::read(&original).unwrap(), vec![0, 1, 2, 255]);
+    }
+
     /// Verify that a single `Update File` hunk with multiple change chunks can update different
     /// parts of a file and that the file is listed only once in the summary.
     #[test]
@@ -1595,14 +1913,62 @@ g
                         .to_string(),
                         move_path: None,
                         new_content: "updated session directory content\n".to_string(),
+                        executable: None,
                     },
                 )]),
                 patch: argv[1].clone(),
                 cwd: session_dir.path().to_path_buf(),
+                fuzzy_hunks: Vec::new(),
             })
         );
     }
 
+    #[test]
+    fn test_stale_context_is_merged_against_the_merge_base() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("source.txt");
+        let base = "one\ntwo\nthree\nfour\nfive\nsix\n";
+        // The file changed after the patch was written.
+        fs::write(&path, "one\nTWO\nthree\nfour\nfive\nsix\n").unwrap();
+        let argv = vec![
+            "apply_patch".to_string(),
+            wrap_patch(&format!(
+                "*** Update File: {}\n@@\n two\n three\n four\n-five\n+5\n six",
+                path.display()
+            )),
+        ];
+
+        assert_matches!(
+            maybe_parse_apply_patch_verified(&argv, dir.path()),
+            MaybeApplyPatchVerified::CorrectnessError(ApplyPatchError::ComputeReplacements(_))
+        );
+        let action =
+            match maybe_parse_apply_patch_verified_with_merge_base(&argv, dir.path(), |p| {
+                (p == path).then(|| base.to_string())
+            }) {
+                MaybeApplyPatchVerified::Body(action) => action,
+                other => panic!("expected a merged patch, got {other:?}"),
+            };
+        assert_eq!(
+            action.fuzzy_hunks(),
+            [FuzzyHunk {
+                path: path.clone(),
+                hunk: 1,
+            }]
+        );
+        let merged = "one\nTWO\nthree\nfour\n5\nsix\n";
+        assert_matches!(
+            action.changes().get(&path),
+            Some(ApplyPatchFileChange::Update { new_content, .. }) if new_content == merged
+        );
+
+        // The rewritten patch applies without the merge base.
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&action.patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), merged);
+    }
+
     #[test]
     fn test_apply_patch_fails_on_write_error() {
         let dir = tempdir().unwrap();
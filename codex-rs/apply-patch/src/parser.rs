@@ -7,14 +7,18 @@
 //! begin_patch: "*** Begin Patch" LF
 //! end_patch: "*** End Patch" LF?
 //!
-//! hunk: add_hunk | delete_hunk | update_hunk
-//! add_hunk: "*** Add File: " filename LF add_line+
+//! hunk: add_hunk | delete_hunk | update_hunk | copy_hunk | binary_hunk
+//! add_hunk: "*** Add File: " filename LF change_mode? add_line+
 //! delete_hunk: "*** Delete File: " filename LF
-//! update_hunk: "*** Update File: " filename LF change_move? change?
+//! update_hunk: "*** Update File: " filename LF change_move? change_mode? change?
+//! copy_hunk: "*** Copy File: " filename LF "*** Copy to: " filename LF
+//! binary_hunk: "*** Binary File: " filename LF change_mode? base64_line*
 //! filename: /(.+)/
 //! add_line: "+" /(.+)/ LF -> line
+//! base64_line: "+" /([A-Za-z0-9+\/=]*)/ LF
 //!
 //! change_move: "*** Move to: " filename LF
+//! change_mode: "*** Mode: " ("+x" | "-x") LF
 //! change: (change_context | change_line)+ eof_line?
 //! change_context: ("@@" | "@@ " /(.+)/) LF
 //! change_line: ("+" | "-" | " ") /(.+)/ LF
@@ -26,14 +30,20 @@ use crate::ApplyPatchArgs;
 use std::path::Path;
 use std::path::PathBuf;
 
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use thiserror::Error;
 
 const BEGIN_PATCH_MARKER: &str = "*** Begin Patch";
-const END_PATCH_MARKER: &str = "*** End Patch";
+pub(crate) const END_PATCH_MARKER: &str = "*** End Patch";
 const ADD_FILE_MARKER: &str = "*** Add File: ";
 const DELETE_FILE_MARKER: &str = "*** Delete File: ";
 const UPDATE_FILE_MARKER: &str = "*** Update File: ";
-const MOVE_TO_MARKER: &str = "*** Move to: ";
+const COPY_FILE_MARKER: &str = "*** Copy File: ";
+const BINARY_FILE_MARKER: &str = "*** Binary File: ";
+pub(crate) const MOVE_TO_MARKER: &str = "*** Move to: ";
+const COPY_TO_MARKER: &str = "*** Copy to: ";
+pub(crate) const MODE_MARKER: &str = "*** Mode: ";
 const EOF_MARKER: &str = "*** End of File";
 const CHANGE_CONTEXT_MARKER: &str = "@@ ";
 const EMPTY_CHANGE_CONTEXT_MARKER: &str = "@@";
@@ -61,6 +71,8 @@ pub enum Hunk {
     AddFile {
         path: PathBuf,
         contents: String,
+        /// `Some(true)` for `*** Mode: +x`, `Some(false)` for `*** Mode: -x`.
+        executable: Option<bool>,
     },
     DeleteFile {
         path: PathBuf,
@@ -68,11 +80,23 @@ pub enum Hunk {
     UpdateFile {
         path: PathBuf,
         move_path: Option<PathBuf>,
+        executable: Option<bool>,
 
         /// Chunks should be in order, i.e. the `change_context` of one chunk
-        /// should occur later in the file than the previous chunk.
+        /// should occur later in the file than the previous chunk. Empty for
+        /// pure renames and mode changes.
         chunks: Vec<UpdateFileChunk>,
     },
+    CopyFile {
+        path: PathBuf,
+        copy_path: PathBuf,
+    },
+    /// Creates or replaces `path` with the decoded base64 payload.
+    BinaryFile {
+        path: PathBuf,
+        contents: Vec<u8>,
+        executable: Option<bool>,
+    },
 }
 
 impl Hunk {
@@ -81,6 +105,8 @@ impl Hunk {
             Hunk::AddFile { path, .. } => cwd.join(path),
             Hunk::DeleteFile { path } => cwd.join(path),
             Hunk::UpdateFile { path, .. } => cwd.join(path),
+            Hunk::CopyFile { path, .. } => cwd.join(path),
+            Hunk::BinaryFile { path, .. } => cwd.join(path),
         }
     }
 }
@@ -103,6 +129,20 @@ pub struct UpdateFileChunk {
     pub is_end_of_file: bool,
 }
 
+/// Whether `line` (with surrounding whitespace removed) starts the section of
+/// a single file within a patch.
+pub(crate) fn is_file_header(line: &str) -> bool {
+    [
+        ADD_FILE_MARKER,
+        DELETE_FILE_MARKER,
+        UPDATE_FILE_MARKER,
+        COPY_FILE_MARKER,
+        BINARY_FILE_MARKER,
+    ]
+    .iter()
+    .any(|marker| line.starts_with(marker))
+}
+
 pub fn parse_patch(patch: &str) -> Result<ApplyPatchArgs, ParseError> {
     let mode = if PARSE_IN_STRICT_MODE {
         ParseMode::Strict
@@ -247,9 +287,10 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
     let first_line = lines[0].trim();
     if let Some(path) = first_line.strip_prefix(ADD_FILE_MARKER) {
         // Add File
+        let (executable, mode_lines) = parse_mode_line(&lines[1..], line_number + 1)?;
         let mut contents = String::new();
-        let mut parsed_lines = 1;
-        for add_line in &lines[1..] {
+        let mut parsed_lines = 1 + mode_lines;
+        for add_line in &lines[parsed_lines..] {
             if let Some(line_to_add) = add_line.strip_prefix('+') {
                 contents.push_str(line_to_add);
                 contents.push('\n');
@@ -262,6 +303,7 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             AddFile {
                 path: PathBuf::from(path),
                 contents,
+                executable,
             },
             parsed_lines,
         ));
@@ -288,6 +330,12 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             parsed_lines += 1;
         }
 
+        // Optional: mode line
+        let (executable, mode_lines) =
+            parse_mode_line(remaining_lines, line_number + parsed_lines)?;
+        remaining_lines = &remaining_lines[mode_lines..];
+        parsed_lines += mode_lines;
+
         let mut chunks = Vec::new();
         // NOTE: we need to know to stop once we reach the next special marker header.
         while !remaining_lines.is_empty() {
@@ -312,7 +360,8 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             remaining_lines = &remaining_lines[chunk_lines..]
         }
 
-        if chunks.is_empty() {
+        // Renames and mode changes do not need to touch the contents.
+        if chunks.is_empty() && move_path.is_none() && executable.is_none() {
             return Err(InvalidHunkError {
                 message: format!("Update file hunk for path '{path}' is empty"),
                 line_number,
@@ -323,20 +372,89 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             UpdateFile {
                 path: PathBuf::from(path),
                 move_path: move_path.map(PathBuf::from),
+                executable,
                 chunks,
             },
             parsed_lines,
         ));
+    } else if let Some(path) = first_line.strip_prefix(COPY_FILE_MARKER) {
+        // Copy File
+        let copy_path = lines
+            .get(1)
+            .and_then(|line| line.trim().strip_prefix(COPY_TO_MARKER))
+            .ok_or_else(|| InvalidHunkError {
+                message: format!(
+                    "Copy file hunk for path '{path}' must be followed by '*** Copy to: {{path}}'"
+                ),
+                line_number: line_number + 1,
+            })?;
+        return Ok((
+            CopyFile {
+                path: PathBuf::from(path),
+                copy_path: PathBuf::from(copy_path),
+            },
+            2,
+        ));
+    } else if let Some(path) = first_line.strip_prefix(BINARY_FILE_MARKER) {
+        // Binary File
+        let (executable, mode_lines) = parse_mode_line(&lines[1..], line_number + 1)?;
+        let mut encoded = String::new();
+        let mut parsed_lines = 1 + mode_lines;
+        for base64_line in &lines[parsed_lines..] {
+            if let Some(data) = base64_line.strip_prefix('+') {
+                encoded.push_str(data.trim());
+                parsed_lines += 1;
+            } else {
+                break;
+            }
+        }
+        let contents = BASE64_STANDARD
+            .decode(&encoded)
+            .map_err(|err| InvalidHunkError {
+                message: format!("Binary file hunk for path '{path}' is not valid base64: {err}"),
+                line_number,
+            })?;
+        return Ok((
+            BinaryFile {
+                path: PathBuf::from(path),
+                contents,
+                executable,
+            },
+            parsed_lines,
+        ));
     }
 
     Err(InvalidHunkError {
         message: format!(
-            "'{first_line}' is not a valid hunk header. Valid hunk headers: '*** Add File: {{path}}', '*** Delete File: {{path}}', '*** Update File: {{path}}'"
+            "'{first_line}' is not a valid hunk header. Valid hunk headers: '*** Add File: {{path}}', '*** Delete File: {{path}}', '*** Update File: {{path}}', '*** Copy File: {{path}}', '*** Binary File: {{path}}'"
         ),
         line_number,
     })
 }
 
+/// Parses an optional `*** Mode: +x` / `*** Mode: -x` line at the start of
+/// `lines`. Returns the requested executable bit and the number of lines
+/// consumed.
+fn parse_mode_line(
+    lines: &[&str],
+    line_number: usize,
+) -> Result<(Option<bool>, usize), ParseError> {
+    let Some(mode) = lines
+        .first()
+        .and_then(|line| line.trim().strip_prefix(MODE_MARKER))
+    else {
+        return Ok((None, 0));
+    };
+    match mode.trim() {
+        "+x" => Ok((Some(true), 1)),
+        "-x" => Ok((Some(false), 1)),
+        other => Err(InvalidHunkError {
+            message: format!("Invalid mode '{other}'. Expected '+x' or '-x'"),
+            line_number,
+        }),
+    }
+}
+
 fn parse_update_file_chunk(
     lines: &[&str],
     line_number: usize,
@@ -486,7 +604,8 @@ fn test_parse_patch() {
         vec![
             AddFile {
                 path: PathBuf::from("path/add.py"),
-                contents: "abc\ndef\n".to_string()
+                contents: "abc\ndef\n".to_string(),
+                executable: None,
             },
             DeleteFile {
                 path: PathBuf::from("path/delete.py")
@@ -494,6 +613,7 @@ fn test_parse_patch() {
             UpdateFile {
                 path: PathBuf::from("path/update.py"),
                 move_path: Some(PathBuf::from("path/update2.py")),
+                executable: None,
                 chunks: vec![UpdateFileChunk {
                     change_context: Some("def f():".to_string()),
                     old_lines: vec!["    pass".to_string()],
@@ -521,6 +641,7 @@ fn test_parse_patch() {
             UpdateFile {
                 path: PathBuf::from("file.py"),
                 move_path: None,
+                executable: None,
                 chunks: vec![UpdateFileChunk {
                     change_context: None,
                     old_lines: vec![],
@@ -530,7 +651,8 @@ fn test_parse_patch() {
             },
             AddFile {
                 path: PathBuf::from("other.py"),
-                contents: "content\n".to_string()
+                contents: "content\n".to_string(),
+                executable: None,
             }
         ]
     );
@@ -551,6 +673,7 @@ fn test_parse_patch() {
         vec![UpdateFile {
             path: PathBuf::from("file2.py"),
             move_path: None,
+            executable: None,
             chunks: vec![UpdateFileChunk {
                 change_context: None,
                 old_lines: vec!["import foo".to_string()],
@@ -561,6 +684,95 @@ fn test_parse_patch() {
     );
 }
 
+#[test]
+fn test_parse_mode_copy_and_binary_hunks() {
+    assert_eq!(
+        parse_patch_text(
+            "*** Begin Patch\n\
+             *** Add File: run.sh\n\
+             *** Mode: +x\n\
+             +#!/bin/sh\n\
+             *** Update File: old.sh\n\
+             *** Move to: new.sh\n\
+             *** Update File: tool.py\n\
+             *** Mode: -x\n\
+             *** Copy File: a.txt\n\
+             *** Copy to: b.txt\n\
+             *** Binary File: logo.bin\n\
+             +AAEC\n\
+             +/w==\n\
+             *** End Patch",
+            ParseMode::Strict
+        )
+        .unwrap()
+        .hunks,
+        vec![
+            AddFile {
+                path: PathBuf::from("run.sh"),
+                contents: "#!/bin/sh\n".to_string(),
+                executable: Some(true),
+            },
+            UpdateFile {
+                path: PathBuf::from("old.sh"),
+                move_path: Some(PathBuf::from("new.sh")),
+                executable: None,
+                chunks: vec![],
+            },
+            UpdateFile {
+                path: PathBuf::from("tool.py"),
+                move_path: None,
+                executable: Some(false),
+                chunks: vec![],
+            },
+            CopyFile {
+                path: PathBuf::from("a.txt"),
+                copy_path: PathBuf::from("b.txt"),
+            },
+            BinaryFile {
+                path: PathBuf::from("logo.bin"),
+                contents: vec![0, 1, 2, 255],
+                executable: None,
+            },
+        ]
+    );
+    assert_eq!(
+        parse_patch_text(
+            "*** Begin Patch\n\
+             *** Update File: run.sh\n\
+             *** Mode: 755\n\
+             *** End Patch",
+            ParseMode::Strict
+        ),
+        Err(InvalidHunkError {
+            message: "Invalid mode '755'. Expected '+x' or '-x'".to_string(),
+            line_number: 3,
+        })
+    );
+    assert_eq!(
+        parse_patch_text(
+            "*** Begin Patch\n\
+             *** Copy File: a.txt\n\
+             *** End Patch",
+            ParseMode::Strict
+        ),
+        Err(InvalidHunkError {
+            message: "Copy file hunk for path 'a.txt' must be followed by '*** Copy to: {path}'"
+                .to_string(),
+            line_number: 3,
+        })
+    );
+    assert!(matches!(
+        parse_patch_text(
+            "*** Begin Patch\n\
+             *** Binary File: logo.bin\n\
+             +not base64!\n\
+             *** End Patch",
+            ParseMode::Strict
+        ),
+        Err(InvalidHunkError { line_number: 2, .. })
+    ));
+}
+
 #[test]
 fn test_parse_patch_lenient() {
     let patch_text = r#"*** Begin Patch
@@ -571,6 +783,7 @@ fn test_parse_patch_lenient() {
     let expected_patch = vec![UpdateFile {
         path: PathBuf::from("file2.py"),
         move_path: None,
+        executable: None,
         chunks: vec![UpdateFileChunk {
             change_context: None,
             old_lines: vec!["import foo".to_string()],
@@ -653,7 +866,8 @@ fn test_parse_one_hunk() {
         parse_one_hunk(&["bad"], 234),
         Err(InvalidHunkError {
             message: "'bad' is not a valid hunk header. \
-            Valid hunk headers: '*** Add File: {path}', '*** Delete File: {path}', '*** Update File: {path}'".to_string(),
+            Valid hunk headers: '*** Add File: {path}', '*** Delete File: {path}', '*** Update File: {path}', \
+            '*** Copy File: {path}', '*** Binary File: {path}'".to_string(),
             line_number: 234
         })
     );
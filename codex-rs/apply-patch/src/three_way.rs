@@ -0,0 +1,179 @@
+//! Fallback for update hunks whose context no longer matches the file on
+//! disk, e.g. because the file changed after the model last read it.
+//!
+//! The hunks are applied to the version of the file the patch was written
+//! against and the result is merged into the current contents with a
+//! three-way merge. The update is then re-expressed as chunks against the
+//! current contents so the patch can be applied by anyone without access to
+//! the older version.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use codex_git_apply::merge_file;
+use similar::ChangeTag;
+use similar::TextDiff;
+
+use crate::ApplyPatchError;
+use crate::ApplyPatchFileUpdate;
+use crate::Hunk;
+use crate::IoError;
+use crate::apply_chunks_to_contents;
+use crate::compute_replacements;
+use crate::parse_patch;
+use crate::parser::END_PATCH_MARKER;
+use crate::parser::MODE_MARKER;
+use crate::parser::MOVE_TO_MARKER;
+use crate::parser::UpdateFileChunk;
+use crate::parser::is_file_header;
+use crate::split_lines;
+
+/// Lines of context around each rewritten chunk.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug)]
+pub(crate) struct MergedUpdate {
+    /// 1-based indices of the chunks whose context was not found in the
+    /// current contents of the file.
+    pub(crate) fuzzy_chunks: Vec<usize>,
+    /// `@@` chunks that turn the current contents into the merged ones.
+    pub(crate) chunks_text: String,
+    pub(crate) update: ApplyPatchFileUpdate,
+}
+
+/// Apply `chunks` to `base` and merge the result into the file at `path`.
+pub(crate) fn merge_update(
+    path: &Path,
+    base: &str,
+    chunks: &[UpdateFileChunk],
+) -> Result<MergedUpdate, ApplyPatchError> {
+    let current = std::fs::read_to_string(path).map_err(|err| {
+        ApplyPatchError::IoError(IoError {
+            context: format!("Failed to read file to update {}", path.display()),
+            source: err,
+        })
+    })?;
+    let theirs = apply_chunks_to_contents(base, path, chunks)?;
+    let merged = merge_file(base, &current, &theirs).map_err(|conflict| {
+        ApplyPatchError::ComputeReplacements(format!(
+            "Failed to merge the patch into {}: {conflict}",
+            path.display()
+        ))
+    })?;
+    if merged == current {
+        return Err(ApplyPatchError::ComputeReplacements(format!(
+            "{} already contains the changes of the patch",
+            path.display()
+        )));
+    }
+
+    let current_lines = split_lines(&current);
+    let fuzzy_chunks = chunks
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| {
+            compute_replacements(&current_lines, path, std::slice::from_ref(*chunk)).is_err()
+        })
+        .map(|(index, _)| index + 1)
+        .collect();
+
+    let mut chunks_text = diff_chunks(&current, &merged);
+    let mut content = apply_chunks_text(&current, path, &chunks_text)?;
+    if content != merged {
+        // The rewritten chunks can be ambiguous (e.g. repeated context), in
+        // which case the whole file is replaced instead.
+        chunks_text = whole_file_chunk(&current, &merged);
+        content = apply_chunks_text(&current, path, &chunks_text)?;
+    }
+    let unified_diff = TextDiff::from_lines(&current, &content)
+        .unified_diff()
+        .context_radius(1)
+        .to_string();
+    Ok(MergedUpdate {
+        fuzzy_chunks,
+        chunks_text,
+        update: ApplyPatchFileUpdate {
+            unified_diff,
+            content,
+        },
+    })
+}
+
+/// Replace the chunks of file sections of `patch` (keyed by the index of the
+/// section) with new chunks, keeping the section headers.
+pub(crate) fn replace_section_chunks(patch: &str, chunks: &HashMap<usize, String>) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut section: Option<usize> = None;
+    let mut sections = 0;
+    let mut in_chunks = false;
+    for line in patch.lines() {
+        let trimmed = line.trim();
+        if is_file_header(trimmed) {
+            section = Some(sections);
+            sections += 1;
+            in_chunks = false;
+        } else if trimmed == END_PATCH_MARKER {
+            section = None;
+        } else if let Some(new_chunks) = section.and_then(|index| chunks.get(&index)) {
+            let is_header = trimmed.starts_with(MOVE_TO_MARKER) || trimmed.starts_with(MODE_MARKER);
+            if !in_chunks && !is_header {
+                lines.extend(new_chunks.lines());
+                in_chunks = true;
+            }
+            if in_chunks {
+                continue;
+            }
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Chunks (in patch syntax) that turn `old` into `new`.
+fn diff_chunks(old: &str, new: &str) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    let mut text = String::new();
+    for group in diff.grouped_ops(CONTEXT_LINES) {
+        text.push_str("@@\n");
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                text.push(match change.tag() {
+                    ChangeTag::Equal => ' ',
+                    ChangeTag::Delete => '-',
+                    ChangeTag::Insert => '+',
+                });
+                let value = change.value();
+                text.push_str(value.strip_suffix('\n').unwrap_or(value));
+                text.push('\n');
+            }
+        }
+    }
+    text
+}
+
+fn whole_file_chunk(old: &str, new: &str) -> String {
+    let mut text = String::from("@@\n");
+    for line in old.lines() {
+        text.push_str(&format!("-{line}\n"));
+    }
+    for line in new.lines() {
+        text.push_str(&format!("+{line}\n"));
+    }
+    text
+}
+
+fn apply_chunks_text(
+    contents: &str,
+    path: &Path,
+    chunks_text: &str,
+) -> Result<String, ApplyPatchError> {
+    let patch =
+        format!("*** Begin Patch\n*** Update File: merged\n{chunks_text}{END_PATCH_MARKER}");
+    match parse_patch(&patch)?.hunks.pop() {
+        Some(Hunk::UpdateFile { chunks, .. }) => apply_chunks_to_contents(contents, path, &chunks),
+        _ => Err(ApplyPatchError::ComputeReplacements(format!(
+            "Failed to rewrite the patch for {}",
+            path.display()
+        ))),
+    }
+}
@@ -0,0 +1,267 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use codex_common::CliConfigOverrides;
+use codex_core::config::find_codex_home;
+use codex_core::executor::allowlist::AllowRule;
+use codex_core::executor::allowlist::CommandAllowlist;
+use codex_core::git_info::resolve_root_git_project_for_trust;
+
+/// Manage commands that Codex may run without asking for approval.
+///
+/// Rules are stored in `~/.codex/approved_commands.toml` and are created when
+/// you pick "always allow" in an approval prompt, or with `add`.
+///
+/// Subcommands:
+/// - `list`   — show persisted rules (with `--json`)
+/// - `add`    — allow a command for the current project or globally
+/// - `revoke` — delete a rule by the id shown in `list`
+#[derive(Debug, clap::Parser)]
+pub struct ApprovalsCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: ApprovalsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ApprovalsSubcommand {
+    /// List persisted command approvals.
+    List(ListArgs),
+
+    /// Always allow a command.
+    Add(AddArgs),
+
+    /// Revoke a persisted command approval.
+    #[clap(visible_alias = "remove")]
+    Revoke(RevokeArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ListArgs {
+    /// Output the rules as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct AddArgs {
+    /// Allow the command in every project instead of only the current one.
+    #[arg(long, conflicts_with = "project")]
+    pub global: bool,
+
+    /// Project directory the rule applies to. Defaults to the git root of the
+    /// current directory (or the current directory outside of git).
+    #[arg(long, value_name = "DIR")]
+    pub project: Option<PathBuf>,
+
+    /// Also allow the command with additional trailing arguments.
+    #[arg(long)]
+    pub prefix: bool,
+
+    /// Treat each argument as a glob (`*` and `?`).
+    #[arg(long)]
+    pub glob: bool,
+
+    /// Command to allow, e.g. `codex approvals add --prefix -- cargo test`.
+    #[arg(trailing_var_arg = true, num_args = 1.., required = true)]
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct RevokeArgs {
+    /// Id of the rule to revoke, as shown by `codex approvals list`.
+    pub id: usize,
+}
+
+impl ApprovalsCli {
+    pub fn run(self) -> Result<()> {
+        let ApprovalsCli {
+            config_overrides,
+            subcommand,
+        } = self;
+
+        // Validate any provided overrides even though they are not currently applied.
+        config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+
+        let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+        match subcommand {
+            ApprovalsSubcommand::List(args) => run_list(&codex_home, args)?,
+            ApprovalsSubcommand::Add(args) => run_add(&codex_home, args)?,
+            ApprovalsSubcommand::Revoke(args) => run_revoke(&codex_home, args)?,
+        }
+
+        Ok(())
+    }
+}
+
+fn load(codex_home: &Path) -> Result<CommandAllowlist> {
+    CommandAllowlist::load(codex_home).with_context(|| {
+        format!(
+            "failed to load {}",
+            CommandAllowlist::path(codex_home).display()
+        )
+    })
+}
+
+fn update<R>(codex_home: &Path, edit: impl FnOnce(&mut CommandAllowlist) -> R) -> Result<R> {
+    CommandAllowlist::update(codex_home, edit).with_context(|| {
+        format!(
+            "failed to write {}",
+            CommandAllowlist::path(codex_home).display()
+        )
+    })
+}
+
+fn format_rule(rule: &AllowRule) -> String {
+    let mut command = shlex::try_join(rule.command.iter().map(String::as_str))
+        .unwrap_or_else(|_| rule.command.join(" "));
+    if rule.prefix {
+        command.push_str(" …");
+    }
+    command
+}
+
+fn run_list(codex_home: &Path, list_args: ListArgs) -> Result<()> {
+    let allowlist = load(codex_home)?;
+
+    if list_args.json {
+        let json_entries: Vec<_> = allowlist
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(idx, rule)| {
+                serde_json::json!({
+                    "id": idx + 1,
+                    "command": rule.command,
+                    "prefix": rule.prefix,
+                    "glob": rule.glob,
+                    "project": rule.project,
+                })
+            })
+            .collect();
+        let output = serde_json::to_string_pretty(&json_entries)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if allowlist.rules.is_empty() {
+        println!(
+            "No persisted command approvals. Try `codex approvals add --prefix -- cargo test`."
+        );
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 4]> = allowlist
+        .rules
+        .iter()
+        .enumerate()
+        .map(|(idx, rule)| {
+            let scope = rule
+                .project
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "global".to_string());
+            let matching = if rule.glob { "glob" } else { "literal" };
+            [
+                (idx + 1).to_string(),
+                scope,
+                matching.to_string(),
+                format_rule(rule),
+            ]
+        })
+        .collect();
+
+    let mut widths = ["Id".len(), "Scope".len(), "Match".len()];
+    for row in &rows {
+        for (i, width) in widths.iter_mut().enumerate() {
+            *width = (*width).max(row[i].len());
+        }
+    }
+
+    println!(
+        "{:<id_w$}  {:<scope_w$}  {:<match_w$}  Command",
+        "Id",
+        "Scope",
+        "Match",
+        id_w = widths[0],
+        scope_w = widths[1],
+        match_w = widths[2],
+    );
+    for row in &rows {
+        println!(
+            "{:<id_w$}  {:<scope_w$}  {:<match_w$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            id_w = widths[0],
+            scope_w = widths[1],
+            match_w = widths[2],
+        );
+    }
+
+    Ok(())
+}
+
+fn run_add(codex_home: &Path, add_args: AddArgs) -> Result<()> {
+    let AddArgs {
+        global,
+        project,
+        prefix,
+        glob,
+        command,
+    } = add_args;
+
+    let project = if global {
+        None
+    } else {
+        let dir = match project {
+            Some(dir) => dir,
+            None => std::env::current_dir().context("failed to resolve current directory")?,
+        };
+        let dir = dir
+            .canonicalize()
+            .with_context(|| format!("failed to resolve {}", dir.display()))?;
+        Some(resolve_root_git_project_for_trust(&dir).unwrap_or(dir))
+    };
+
+    let rule = AllowRule {
+        command,
+        prefix,
+        glob,
+        project,
+    };
+    let description = format_rule(&rule);
+    let scope = match &rule.project {
+        Some(project) => format!("in {}", project.display()),
+        None => "globally".to_string(),
+    };
+
+    if update(codex_home, |allowlist| allowlist.add(rule))? {
+        println!("Always allowing `{description}` {scope}.");
+    } else {
+        println!("`{description}` is already allowed {scope}.");
+    }
+
+    Ok(())
+}
+
+fn run_revoke(codex_home: &Path, revoke_args: RevokeArgs) -> Result<()> {
+    let RevokeArgs { id } = revoke_args;
+
+    let removed = update(codex_home, |allowlist| {
+        id.checked_sub(1).and_then(|index| allowlist.remove(index))
+    })?;
+    let Some(rule) = removed else {
+        bail!("No persisted command approval with id {id}. Run `codex approvals list` to see ids.");
+    };
+
+    println!("Revoked approval for `{}`.", format_rule(&rule));
+    Ok(())
+}
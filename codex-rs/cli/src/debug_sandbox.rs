@@ -8,6 +8,8 @@ use codex_core::landlock::spawn_command_under_linux_sandbox;
 use codex_core::seatbelt::spawn_command_under_seatbelt;
 use codex_core::spawn::StdioPolicy;
 use codex_protocol::config_types::SandboxMode;
+use codex_protocol::protocol::NetworkAllowlistEntry;
+use codex_protocol::protocol::SandboxPolicy;
 
 use crate::LandlockCommand;
 use crate::SeatbeltCommand;
@@ -19,11 +21,13 @@ pub async fn run_command_under_seatbelt(
 ) -> anyhow::Result<()> {
     let SeatbeltCommand {
         full_auto,
+        allow_hosts,
         config_overrides,
         command,
     } = command;
     run_command_under_sandbox(
         full_auto,
+        allow_hosts,
         command,
         config_overrides,
         codex_linux_sandbox_exe,
@@ -38,11 +42,13 @@ pub async fn run_command_under_landlock(
 ) -> anyhow::Result<()> {
     let LandlockCommand {
         full_auto,
+        allow_hosts,
         config_overrides,
         command,
     } = command;
     run_command_under_sandbox(
         full_auto,
+        allow_hosts,
         command,
         config_overrides,
         codex_linux_sandbox_exe,
@@ -58,13 +64,14 @@ enum SandboxType {
 
 async fn run_command_under_sandbox(
     full_auto: bool,
+    allow_hosts: Vec<NetworkAllowlistEntry>,
     command: Vec<String>,
     config_overrides: CliConfigOverrides,
     codex_linux_sandbox_exe: Option<PathBuf>,
     sandbox_type: SandboxType,
 ) -> anyhow::Result<()> {
     let sandbox_mode = create_sandbox_mode(full_auto);
-    let config = Config::load_with_cli_overrides(
+    let mut config = Config::load_with_cli_overrides(
         config_overrides
             .parse_overrides()
             .map_err(anyhow::Error::msg)?,
@@ -75,6 +82,12 @@ async fn run_command_under_sandbox(
         },
     )
     .await?;
+    if let SandboxPolicy::WorkspaceWrite {
+        network_allowlist, ..
+    } = &mut config.sandbox_policy
+    {
+        network_allowlist.extend(allow_hosts);
+    }
 
     // In practice, this should be `std::env::current_dir()` because this CLI
     // does not support `--cwd`, but let's use the config value for consistency.
@@ -110,6 +123,10 @@ async fn run_command_under_sandbox(
                 cwd,
                 &config.sandbox_policy,
                 sandbox_policy_cwd.as_path(),
+                &config.sandbox_resource_limits,
+                config.linux_sandbox_backend,
+                None,
+                false,
                 stdio_policy,
                 env,
             )
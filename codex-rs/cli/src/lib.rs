@@ -4,6 +4,7 @@ pub mod login;
 
 use clap::Parser;
 use codex_common::CliConfigOverrides;
+use codex_protocol::protocol::NetworkAllowlistEntry;
 
 #[derive(Debug, Parser)]
 pub struct SeatbeltCommand {
@@ -11,6 +12,14 @@ pub struct SeatbeltCommand {
     #[arg(long = "full-auto", default_value_t = false)]
     pub full_auto: bool,
 
+    /// Allow network access to HOST (or only HOST:PORT). Requires --full-auto. May be repeated.
+    #[arg(
+        long = "allow-host",
+        value_name = "HOST[:PORT]",
+        requires = "full_auto"
+    )]
+    pub allow_hosts: Vec<NetworkAllowlistEntry>,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 
@@ -25,6 +34,14 @@ pub struct LandlockCommand {
     #[arg(long = "full-auto", default_value_t = false)]
     pub full_auto: bool,
 
+    /// Allow network access to HOST (or only HOST:PORT). Requires --full-auto. May be repeated.
+    #[arg(
+        long = "allow-host",
+        value_name = "HOST[:PORT]",
+        requires = "full_auto"
+    )]
+    pub allow_hosts: Vec<NetworkAllowlistEntry>,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 
@@ -16,6 +16,7 @@ use codex_cli::login::run_logout;
 use codex_cloud_tasks::Cli as CloudTasksCli;
 use codex_common::CliConfigOverrides;
 use codex_exec::Cli as ExecCli;
+use codex_exec::ReviewArgs as ExecReviewArgs;
 use codex_responses_api_proxy::Args as ResponsesApiProxyArgs;
 use codex_tui::AppExitInfo;
 use codex_tui::Cli as TuiCli;
@@ -24,9 +25,13 @@ use owo_colors::OwoColorize;
 use std::path::PathBuf;
 use supports_color::Stream;
 
+mod approvals_cmd;
 mod mcp_cmd;
+mod sessions_cmd;
 
+use crate::approvals_cmd::ApprovalsCli;
 use crate::mcp_cmd::McpCli;
+use crate::sessions_cmd::SessionsCli;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 
@@ -64,6 +69,9 @@ enum Subcommand {
     #[clap(visible_alias = "e")]
     Exec(ExecCli),
 
+    /// Review code changes non-interactively and print the findings as JSON or SARIF.
+    Review(ExecReviewArgs),
+
     /// Manage login.
     Login(LoginCommand),
 
@@ -79,6 +87,9 @@ enum Subcommand {
     /// [experimental] Run the app server.
     AppServer,
 
+    /// List or revoke commands that are always allowed without approval.
+    Approvals(ApprovalsCli),
+
     /// Generate shell completion scripts.
     Completion(CompletionCommand),
 
@@ -93,6 +104,9 @@ enum Subcommand {
     /// Resume a previous interactive session (picker by default; use --last to continue the most recent).
     Resume(ResumeCommand),
 
+    /// Search previously recorded sessions.
+    Sessions(SessionsCli),
+
     /// Internal: generate TypeScript protocol bindings.
     #[clap(hide = true)]
     GenerateTs(GenerateTsCommand),
@@ -353,6 +367,14 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             );
             codex_exec::run_main(exec_cli, codex_linux_sandbox_exe).await?;
         }
+        Some(Subcommand::Review(review_args)) => {
+            let mut exec_cli = ExecCli::review(review_args);
+            prepend_config_flags(
+                &mut exec_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            codex_exec::run_main(exec_cli, codex_linux_sandbox_exe).await?;
+        }
         Some(Subcommand::McpServer) => {
             codex_mcp_server::run_main(codex_linux_sandbox_exe, root_config_overrides).await?;
         }
@@ -361,6 +383,20 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut mcp_cli.config_overrides, root_config_overrides.clone());
             mcp_cli.run().await?;
         }
+        Some(Subcommand::Approvals(mut approvals_cli)) => {
+            prepend_config_flags(
+                &mut approvals_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            approvals_cli.run()?;
+        }
+        Some(Subcommand::Sessions(mut sessions_cli)) => {
+            prepend_config_flags(
+                &mut sessions_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            sessions_cli.run().await?;
+        }
         Some(Subcommand::AppServer) => {
             codex_app_server::run_main(codex_linux_sandbox_exe, root_config_overrides).await?;
         }
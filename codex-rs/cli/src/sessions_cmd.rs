@@ -0,0 +1,186 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use codex_common::CliConfigOverrides;
+use codex_core::RolloutRecorder;
+use codex_core::SearchMatchKind;
+use codex_core::config::find_codex_home;
+use codex_core::find_conversation_path_by_id_str;
+
+/// Inspect recorded sessions.
+///
+/// Subcommands:
+/// - `search` — full-text search over messages, commands and touched files
+/// - `export` — render a session as a Markdown, HTML or JSON transcript
+#[derive(Debug, clap::Parser)]
+pub struct SessionsCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: SessionsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SessionsSubcommand {
+    /// Search recorded sessions for messages, commands and file names.
+    Search(SearchArgs),
+
+    /// Export a session transcript.
+    Export(ExportArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct SearchArgs {
+    /// Words to look for. A session matches when it contains every word
+    /// (case-insensitive).
+    #[arg(num_args = 1.., required = true)]
+    pub query: Vec<String>,
+
+    /// Maximum number of sessions to show.
+    #[arg(long, short = 'n', default_value_t = 20)]
+    pub limit: usize,
+
+    /// Output the results as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    #[value(alias = "markdown")]
+    Md,
+    Html,
+    Json,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ExportArgs {
+    /// Session id (UUID) or path to a rollout file.
+    pub session: String,
+
+    /// Transcript format.
+    #[arg(long, short = 'f', value_enum, default_value_t = ExportFormat::Md)]
+    pub format: ExportFormat,
+
+    /// Write the transcript to this file instead of stdout.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+impl SessionsCli {
+    pub async fn run(self) -> Result<()> {
+        let SessionsCli {
+            config_overrides,
+            subcommand,
+        } = self;
+
+        // Validate any provided overrides even though they are not currently applied.
+        config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+
+        let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+        match subcommand {
+            SessionsSubcommand::Search(args) => run_search(&codex_home, args).await?,
+            SessionsSubcommand::Export(args) => run_export(&codex_home, args).await?,
+        }
+
+        Ok(())
+    }
+}
+
+async fn run_search(codex_home: &Path, search_args: SearchArgs) -> Result<()> {
+    let SearchArgs { query, limit, json } = search_args;
+    let query = query.join(" ");
+
+    let page = RolloutRecorder::search_conversations(codex_home, &query, limit, &[])
+        .await
+        .context("failed to search sessions")?;
+
+    if json {
+        let output = serde_json::to_string_pretty(&page.items)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if page.items.is_empty() {
+        println!("No sessions match `{query}`.");
+        return Ok(());
+    }
+
+    for (idx, item) in page.items.iter().enumerate() {
+        if idx > 0 {
+            println!();
+        }
+        let id = item
+            .conversation_id
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let when = item.created_at.as_deref().unwrap_or("-");
+        println!("{id}  {when}");
+        if let Some(preview) = &item.preview {
+            println!("  {}", preview.lines().next().unwrap_or_default());
+        }
+        println!("  {}", item.path.display());
+        for search_match in &item.matches {
+            let label = match search_match.kind {
+                SearchMatchKind::UserMessage => "user",
+                SearchMatchKind::AgentMessage => "agent",
+                SearchMatchKind::Command => "command",
+                SearchMatchKind::FileChange => "file",
+            };
+            println!("    {label:<7}  {}", search_match.snippet);
+        }
+    }
+
+    if page.reached_scan_cap {
+        println!();
+        println!(
+            "Stopped after scanning {} sessions; older sessions were not searched.",
+            page.num_scanned_files
+        );
+    }
+
+    Ok(())
+}
+
+async fn run_export(codex_home: &Path, export_args: ExportArgs) -> Result<()> {
+    let ExportArgs {
+        session,
+        format,
+        output,
+    } = export_args;
+
+    let candidate = PathBuf::from(&session);
+    let path = if candidate.is_file() {
+        candidate
+    } else {
+        match find_conversation_path_by_id_str(codex_home, &session).await? {
+            Some(path) => path,
+            None => bail!("No recorded session with id {session}."),
+        }
+    };
+
+    let transcript = RolloutRecorder::load_transcript(&path)
+        .await
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let rendered = match format {
+        ExportFormat::Md => transcript.to_markdown(),
+        ExportFormat::Html => transcript.to_html(),
+        ExportFormat::Json => transcript.to_json()? + "\n",
+    };
+
+    match output {
+        Some(output) => {
+            std::fs::write(&output, rendered)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            eprintln!("Wrote transcript to {}", output.display());
+        }
+        None => print!("{rendered}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::executor::allowlist::AllowRule;
+use codex_core::executor::allowlist::CommandAllowlist;
+use predicates::str::contains;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn list_json(codex_home: &Path) -> Result<Value> {
+    let output = codex_command(codex_home)?
+        .args(["approvals", "list", "--json"])
+        .output()?;
+    assert!(output.status.success());
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+#[test]
+fn add_list_and_revoke_update_the_allowlist_file() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let cwd = TempDir::new()?;
+    let cwd_path = cwd.path().canonicalize()?;
+    let other_project = TempDir::new()?;
+    let other_project_path = other_project.path().canonicalize()?;
+
+    // Without `--global` or `--project`, rules are scoped to the current
+    // directory (outside of git, there is no repository root to use).
+    codex_command(codex_home.path())?
+        .current_dir(&cwd_path)
+        .args(["approvals", "add", "--prefix", "--", "cargo", "test"])
+        .assert()
+        .success()
+        .stdout(contains(format!(
+            "Always allowing `cargo test …` in {}.",
+            cwd_path.display()
+        )));
+    codex_command(codex_home.path())?
+        .args([
+            "approvals",
+            "add",
+            "--global",
+            "--glob",
+            "--",
+            "git",
+            "log",
+            "--oneline",
+            "-n*",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Always allowing `git log --oneline '-n*'` globally.",
+        ));
+    codex_command(codex_home.path())?
+        .args(["approvals", "add", "--project"])
+        .arg(other_project.path())
+        .args(["--", "make", "build"])
+        .assert()
+        .success();
+    codex_command(codex_home.path())?
+        .current_dir(&cwd_path)
+        .args(["approvals", "add", "--prefix", "--", "cargo", "test"])
+        .assert()
+        .success()
+        .stdout(contains("`cargo test …` is already allowed"));
+
+    let persisted = std::fs::read_to_string(CommandAllowlist::path(codex_home.path()))?;
+    assert!(
+        persisted.contains("[[rule]]"),
+        "unexpected file: {persisted}"
+    );
+    assert_eq!(
+        CommandAllowlist::load(codex_home.path())?.rules,
+        vec![
+            AllowRule {
+                command: vec!["cargo".to_string(), "test".to_string()],
+                prefix: true,
+                glob: false,
+                project: Some(cwd_path.clone()),
+            },
+            AllowRule {
+                command: vec![
+                    "git".to_string(),
+                    "log".to_string(),
+                    "--oneline".to_string(),
+                    "-n*".to_string(),
+                ],
+                prefix: false,
+                glob: true,
+                project: None,
+            },
+            AllowRule::exact(
+                vec!["make".to_string(), "build".to_string()],
+                Some(other_project_path.clone()),
+            ),
+        ]
+    );
+
+    assert_eq!(
+        list_json(codex_home.path())?,
+        json!([
+            {
+                "id": 1,
+                "command": ["cargo", "test"],
+                "prefix": true,
+                "glob": false,
+                "project": cwd_path,
+            },
+            {
+                "id": 2,
+                "command": ["git", "log", "--oneline", "-n*"],
+                "prefix": false,
+                "glob": true,
+                "project": null,
+            },
+            {
+                "id": 3,
+                "command": ["make", "build"],
+                "prefix": false,
+                "glob": false,
+                "project": other_project_path,
+            },
+        ])
+    );
+    codex_command(codex_home.path())?
+        .args(["approvals", "list"])
+        .assert()
+        .success()
+        .stdout(contains("global"))
+        .stdout(contains("literal"))
+        .stdout(contains("cargo test …"));
+
+    codex_command(codex_home.path())?
+        .args(["approvals", "revoke", "2"])
+        .assert()
+        .success()
+        .stdout(contains("Revoked approval for `git log --oneline '-n*'`."));
+    let rules = CommandAllowlist::load(codex_home.path())?.rules;
+    let commands: Vec<&[String]> = rules.iter().map(|rule| rule.command.as_slice()).collect();
+    assert_eq!(
+        commands,
+        vec![
+            ["cargo".to_string(), "test".to_string()].as_slice(),
+            ["make".to_string(), "build".to_string()].as_slice(),
+        ]
+    );
+
+    codex_command(codex_home.path())?
+        .args(["approvals", "revoke", "3"])
+        .assert()
+        .failure()
+        .stderr(contains("No persisted command approval with id 3."));
+
+    Ok(())
+}
+
+#[test]
+fn list_reads_hand_written_rules() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        CommandAllowlist::path(codex_home.path()),
+        r#"
+[[rule]]
+command = ["cargo", "test"]
+prefix = true
+project = "/home/me/code/my-crate"
+
+[[rule]]
+command = ["git", "log", "--oneline", "-n*"]
+glob = true
+"#,
+    )?;
+
+    assert_eq!(
+        list_json(codex_home.path())?,
+        json!([
+            {
+                "id": 1,
+                "command": ["cargo", "test"],
+                "prefix": true,
+                "glob": false,
+                "project": "/home/me/code/my-crate",
+            },
+            {
+                "id": 2,
+                "command": ["git", "log", "--oneline", "-n*"],
+                "prefix": false,
+                "glob": true,
+                "project": null,
+            },
+        ])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn list_without_rules_suggests_adding_one() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    codex_command(codex_home.path())?
+        .args(["approvals", "list"])
+        .assert()
+        .success()
+        .stdout(contains("No persisted command approvals."));
+    assert_eq!(list_json(codex_home.path())?, json!([]));
+
+    Ok(())
+}
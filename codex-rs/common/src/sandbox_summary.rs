@@ -3,10 +3,12 @@ use codex_core::protocol::SandboxPolicy;
 pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
     match sandbox_policy {
         SandboxPolicy::DangerFullAccess => "danger-full-access".to_string(),
-        SandboxPolicy::ReadOnly => "read-only".to_string(),
+        SandboxPolicy::ReadOnly { .. } => "read-only".to_string(),
         SandboxPolicy::WorkspaceWrite {
             writable_roots,
+            deny_read: _,
             network_access,
+            network_allowlist,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
         } => {
@@ -29,6 +31,10 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
             summary.push_str(&format!(" [{}]", writable_entries.join(", ")));
             if *network_access {
                 summary.push_str(" (network access enabled)");
+            } else if !network_allowlist.is_empty() {
+                let hosts: Vec<String> =
+                    network_allowlist.iter().map(ToString::to_string).collect();
+                summary.push_str(&format!(" (network access to {})", hosts.join(", ")));
             }
             summary
         }
@@ -0,0 +1,518 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::ModelProviderInfo;
+use crate::chat_completions::send_streaming_request;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::model_family::ModelFamily;
+use crate::openai_model_info::get_model_info;
+use crate::openai_tools::create_tools_json_for_anthropic_messages_api;
+use crate::protocol::TokenUsage;
+use bytes::Bytes;
+use codex_otel::otel_event_manager::OtelEventManager;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ResponseItem;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+
+/// `max_tokens` is mandatory in the Messages API; used when the model's
+/// output limit is unknown.
+const DEFAULT_MAX_TOKENS: u64 = 8_192;
+
+/// Implementation for the Anthropic Messages API.
+pub(crate) async fn stream_anthropic_messages(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+    otel_event_manager: &OtelEventManager,
+) -> Result<ResponseStream> {
+    if prompt.output_schema.is_some() {
+        return Err(CodexErr::UnsupportedOperation(
+            "output_schema is not supported for the Anthropic Messages API".to_string(),
+        ));
+    }
+
+    let max_tokens = prompt.max_output_tokens.unwrap_or_else(|| {
+        get_model_info(model_family).map_or(DEFAULT_MAX_TOKENS, |info| info.max_output_tokens)
+    });
+    let mut payload = json!({
+        "model": model_family.slug,
+        "max_tokens": max_tokens,
+        "system": prompt.get_full_instructions(model_family),
+        "messages": build_messages(&prompt.get_formatted_input()),
+        "stream": true,
+    });
+    if let Some(temperature) = prompt.temperature
+        && let Some(obj) = payload.as_object_mut()
+    {
+        obj.insert("temperature".to_string(), json!(temperature));
+    }
+    let tools_json = create_tools_json_for_anthropic_messages_api(&prompt.tools)?;
+    if !tools_json.is_empty()
+        && let Some(obj) = payload.as_object_mut()
+    {
+        obj.insert("tools".to_string(), json!(tools_json));
+    }
+
+    debug!(
+        "POST to {}: {}",
+        provider.get_full_url(&None, &model_family.slug),
+        serde_json::to_string_pretty(&payload).unwrap_or_default()
+    );
+
+    let resp = send_streaming_request(
+        &payload,
+        &model_family.slug,
+        client,
+        provider,
+        otel_event_manager,
+    )
+    .await?;
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+    let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+    tokio::spawn(process_anthropic_sse(
+        stream,
+        tx_event,
+        provider.stream_idle_timeout(),
+        otel_event_manager.clone(),
+    ));
+    Ok(ResponseStream { rx_event })
+}
+
+/// Converts the conversation into Messages API `messages`. Tool calls become
+/// `tool_use` blocks on assistant turns and their outputs `tool_result`
+/// blocks on user turns; consecutive blocks of the same role are merged into
+/// one message as the API expects alternating roles.
+fn build_messages(input: &[ResponseItem]) -> Vec<Value> {
+    let mut messages: Vec<Value> = Vec::new();
+    let mut push = |role: &str, blocks: Vec<Value>| {
+        if blocks.is_empty() {
+            return;
+        }
+        if let Some(last) = messages.last_mut()
+            && last["role"] == role
+            && let Some(content) = last["content"].as_array_mut()
+        {
+            content.extend(blocks);
+            return;
+        }
+        messages.push(json!({ "role": role, "content": blocks }));
+    };
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" {
+                    "assistant"
+                } else {
+                    "user"
+                };
+                let blocks = content
+                    .iter()
+                    .filter_map(|c| match c {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            (!text.is_empty()).then(|| json!({ "type": "text", "text": text }))
+                        }
+                        ContentItem::InputImage { image_url } => Some(image_block(image_url)),
+                    })
+                    .collect();
+                push(role, blocks);
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                let input = serde_json::from_str::<Value>(arguments)
+                    .ok()
+                    .filter(Value::is_object)
+                    .unwrap_or_else(|| json!({}));
+                push(
+                    "assistant",
+                    vec![json!({
+                        "type": "tool_use",
+                        "id": call_id,
+                        "name": name,
+                        "input": input,
+                    })],
+                );
+            }
+            ResponseItem::LocalShellCall {
+                id,
+                call_id,
+                action,
+                ..
+            } => {
+                let id = call_id.clone().or_else(|| id.clone()).unwrap_or_default();
+                push(
+                    "assistant",
+                    vec![json!({
+                        "type": "tool_use",
+                        "id": id,
+                        "name": "local_shell",
+                        "input": action,
+                    })],
+                );
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                push(
+                    "assistant",
+                    vec![json!({
+                        "type": "tool_use",
+                        "id": call_id,
+                        "name": name,
+                        "input": { "input": input },
+                    })],
+                );
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                push(
+                    "user",
+                    vec![json!({
+                        "type": "tool_result",
+                        "tool_use_id": call_id,
+                        "content": output.content,
+                        "is_error": output.success == Some(false),
+                    })],
+                );
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                push(
+                    "user",
+                    vec![json!({
+                        "type": "tool_result",
+                        "tool_use_id": call_id,
+                        "content": output,
+                    })],
+                );
+            }
+            ResponseItem::Reasoning { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::Other => {
+                // Omit these items from the conversation history.
+                continue;
+            }
+        }
+    }
+    messages
+}
+
+/// Images are sent inline when they are data URLs and by reference otherwise.
+fn image_block(image_url: &str) -> Value {
+    if let Some(rest) = image_url.strip_prefix("data:")
+        && let Some((media_type, data)) = rest.split_once(";base64,")
+    {
+        return json!({
+            "type": "image",
+            "source": { "type": "base64", "media_type": media_type, "data": data },
+        });
+    }
+    json!({
+        "type": "image",
+        "source": { "type": "url", "url": image_url },
+    })
+}
+
+/// A content block being streamed, keyed by its `index`.
+enum ContentBlock {
+    Text(String),
+    Thinking(String),
+    ToolUse {
+        id: String,
+        name: String,
+        input_json: String,
+    },
+}
+
+/// SSE processor for the Messages streaming format. Text and thinking deltas
+/// are forwarded as they arrive and every content block is emitted as a
+/// complete [`ResponseItem`] once the server closes it.
+async fn process_anthropic_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    idle_timeout: Duration,
+    otel_event_manager: OtelEventManager,
+) where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+
+    let mut blocks: HashMap<u64, ContentBlock> = HashMap::new();
+    let mut response_id = String::new();
+    let mut token_usage = TokenUsage::default();
+
+    loop {
+        let start = std::time::Instant::now();
+        let response = timeout(idle_timeout, stream.next()).await;
+        let duration = start.elapsed();
+        otel_event_manager.log_sse_event(&response, duration);
+
+        let sse = match response {
+            Ok(Some(Ok(ev))) => ev,
+            Ok(Some(Err(e))) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(e.to_string(), None)))
+                    .await;
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "stream closed before message_stop".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "idle timeout waiting for SSE".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+        };
+
+        let event: Value = match serde_json::from_str(&sse.data) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        trace!("anthropic_messages received SSE event: {event:?}");
+
+        match event["type"].as_str().unwrap_or_default() {
+            "message_start" => {
+                let message = &event["message"];
+                if let Some(id) = message["id"].as_str() {
+                    response_id = id.to_string();
+                }
+                update_token_usage(&mut token_usage, &message["usage"]);
+                let _ = tx_event.send(Ok(ResponseEvent::Created)).await;
+            }
+            "content_block_start" => {
+                let Some(index) = event["index"].as_u64() else {
+                    continue;
+                };
+                let block = &event["content_block"];
+                let block = match block["type"].as_str() {
+                    Some("text") => ContentBlock::Text(String::new()),
+                    Some("thinking") => ContentBlock::Thinking(String::new()),
+                    Some("tool_use") => ContentBlock::ToolUse {
+                        id: block["id"].as_str().unwrap_or_default().to_string(),
+                        name: block["name"].as_str().unwrap_or_default().to_string(),
+                        input_json: String::new(),
+                    },
+                    _ => continue,
+                };
+                blocks.insert(index, block);
+            }
+            "content_block_delta" => {
+                let Some(block) = event["index"].as_u64().and_then(|i| blocks.get_mut(&i)) else {
+                    continue;
+                };
+                let delta = &event["delta"];
+                match (block, delta["type"].as_str()) {
+                    (ContentBlock::Text(text), Some("text_delta")) => {
+                        let chunk = delta["text"].as_str().unwrap_or_default();
+                        text.push_str(chunk);
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::OutputTextDelta(chunk.to_string())))
+                            .await;
+                    }
+                    (ContentBlock::Thinking(text), Some("thinking_delta")) => {
+                        let chunk = delta["thinking"].as_str().unwrap_or_default();
+                        text.push_str(chunk);
+                        let _ = tx_event
+                            .send(Ok(ResponseEvent::ReasoningContentDelta(chunk.to_string())))
+                            .await;
+                    }
+                    (ContentBlock::ToolUse { input_json, .. }, Some("input_json_delta")) => {
+                        input_json.push_str(delta["partial_json"].as_str().unwrap_or_default());
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_stop" => {
+                let Some(block) = event["index"].as_u64().and_then(|i| blocks.remove(&i)) else {
+                    continue;
+                };
+                let item = match block {
+                    ContentBlock::Text(text) => ResponseItem::Message {
+                        id: None,
+                        role: "assistant".to_string(),
+                        content: vec![ContentItem::OutputText { text }],
+                    },
+                    ContentBlock::Thinking(text) => ResponseItem::Reasoning {
+                        id: String::new(),
+                        summary: Vec::new(),
+                        content: Some(vec![ReasoningItemContent::ReasoningText { text }]),
+                        encrypted_content: None,
+                    },
+                    ContentBlock::ToolUse {
+                        id,
+                        name,
+                        input_json,
+                    } => ResponseItem::FunctionCall {
+                        id: None,
+                        name,
+                        // Tools without parameters stream no input at all.
+                        arguments: if input_json.is_empty() {
+                            "{}".to_string()
+                        } else {
+                            input_json
+                        },
+                        call_id: id,
+                    },
+                };
+                let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+            }
+            "message_delta" => {
+                update_token_usage(&mut token_usage, &event["usage"]);
+            }
+            "message_stop" => {
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id,
+                        token_usage: Some(token_usage),
+                    }))
+                    .await;
+                return;
+            }
+            "error" => {
+                let message = event["error"]["message"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+                    .to_string();
+                let _ = tx_event.send(Err(CodexErr::Stream(message, None))).await;
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Folds a Messages API `usage` object into `token_usage`. `message_start`
+/// carries the input counts while `message_delta` carries the cumulative
+/// output count. Cache reads and writes are part of the prompt, so they count
+/// towards `input_tokens`.
+fn update_token_usage(token_usage: &mut TokenUsage, usage: &Value) {
+    let input_keys = [
+        "input_tokens",
+        "cache_read_input_tokens",
+        "cache_creation_input_tokens",
+    ];
+    if input_keys.iter().any(|key| usage.get(key).is_some()) {
+        token_usage.input_tokens = input_keys
+            .iter()
+            .filter_map(|key| usage[key].as_u64())
+            .sum();
+        token_usage.cached_input_tokens = usage["cache_read_input_tokens"].as_u64().unwrap_or(0);
+    }
+    if let Some(output_tokens) = usage["output_tokens"].as_u64() {
+        token_usage.output_tokens = output_tokens;
+    }
+    token_usage.total_tokens = token_usage.input_tokens + token_usage.output_tokens;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn tool_calls_and_outputs_become_alternating_messages() {
+        let input = vec![
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![
+                    ContentItem::InputText {
+                        text: "list files".to_string(),
+                    },
+                    ContentItem::InputImage {
+                        image_url: "data:image/png;base64,aGk=".to_string(),
+                    },
+                ],
+            },
+            ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText {
+                    text: "Sure.".to_string(),
+                }],
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "toolu_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "toolu_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "README.md".to_string(),
+                    success: Some(true),
+                },
+            },
+        ];
+
+        assert_eq!(
+            Value::Array(build_messages(&input)),
+            json!([
+                {
+                    "role": "user",
+                    "content": [
+                        { "type": "text", "text": "list files" },
+                        {
+                            "type": "image",
+                            "source": { "type": "base64", "media_type": "image/png", "data": "aGk=" },
+                        },
+                    ],
+                },
+                {
+                    "role": "assistant",
+                    "content": [
+                        { "type": "text", "text": "Sure." },
+                        {
+                            "type": "tool_use",
+                            "id": "toolu_1",
+                            "name": "shell",
+                            "input": { "command": ["ls"] },
+                        },
+                    ],
+                },
+                {
+                    "role": "user",
+                    "content": [{
+                        "type": "tool_result",
+                        "tool_use_id": "toolu_1",
+                        "content": "README.md",
+                        "is_error": false,
+                    }],
+                },
+            ])
+        );
+    }
+}
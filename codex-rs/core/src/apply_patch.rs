@@ -65,17 +65,20 @@ pub(crate) async fn apply_patch(
                 .request_patch_approval(sub_id.to_owned(), call_id.to_owned(), &action, None, None)
                 .await;
             match rx_approve.await.unwrap_or_default() {
-                ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
+                ReviewDecision::Approved
+                | ReviewDecision::ApprovedForSession
+                | ReviewDecision::ApprovedAlways => {
                     InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
                         action,
                         user_explicitly_approved_this_action: true,
                     })
                 }
-                ReviewDecision::Denied | ReviewDecision::Abort => {
-                    InternalApplyPatchInvocation::Output(Err(FunctionCallError::RespondToModel(
-                        "patch rejected by user".to_string(),
-                    )))
-                }
+                // Patch approvals never offer writable roots.
+                ReviewDecision::ApprovedWithWritableRoots
+                | ReviewDecision::Denied
+                | ReviewDecision::Abort => InternalApplyPatchInvocation::Output(Err(
+                    FunctionCallError::RespondToModel("patch rejected by user".to_string()),
+                )),
             }
         }
         SafetyCheck::Reject { reason } => InternalApplyPatchInvocation::Output(Err(
@@ -91,8 +94,12 @@ pub(crate) fn convert_apply_patch_to_protocol(
     let mut result = HashMap::with_capacity(changes.len());
     for (path, change) in changes {
         let protocol_change = match change {
-            ApplyPatchFileChange::Add { content } => FileChange::Add {
+            ApplyPatchFileChange::Add {
+                content,
+                executable,
+            } => FileChange::Add {
                 content: content.clone(),
+                executable: *executable,
             },
             ApplyPatchFileChange::Delete { content } => FileChange::Delete {
                 content: content.clone(),
@@ -101,9 +108,21 @@ pub(crate) fn convert_apply_patch_to_protocol(
                 unified_diff,
                 move_path,
                 new_content: _new_content,
+                executable,
             } => FileChange::Update {
                 unified_diff: unified_diff.clone(),
                 move_path: move_path.clone(),
+                executable: *executable,
+            },
+            ApplyPatchFileChange::Copy { source } => FileChange::Copy {
+                source: source.clone(),
+            },
+            ApplyPatchFileChange::Binary {
+                contents,
+                executable,
+            } => FileChange::Binary {
+                size: contents.len() as u64,
+                executable: *executable,
             },
         };
         result.insert(path.clone(), protocol_change);
@@ -130,7 +149,8 @@ mod tests {
         assert_eq!(
             got.get(&p),
             Some(&FileChange::Add {
-                content: "hello".to_string()
+                content: "hello".to_string(),
+                executable: None,
             })
         );
     }
@@ -277,47 +277,76 @@ pub(crate) async fn stream_chat_completions(
     }
 
     let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
-    let payload = json!({
+    let mut payload = json!({
         "model": model_family.slug,
         "messages": messages,
         "stream": true,
         "tools": tools_json,
     });
+    if let Some(obj) = payload.as_object_mut() {
+        if let Some(max_tokens) = prompt.max_output_tokens {
+            obj.insert("max_tokens".to_string(), json!(max_tokens));
+        }
+        if let Some(temperature) = prompt.temperature {
+            obj.insert("temperature".to_string(), json!(temperature));
+        }
+    }
 
     debug!(
         "POST to {}: {}",
-        provider.get_full_url(&None),
+        provider.get_full_url(&None, &model_family.slug),
         serde_json::to_string_pretty(&payload).unwrap_or_default()
     );
 
+    let resp = send_streaming_request(
+        &payload,
+        &model_family.slug,
+        client,
+        provider,
+        otel_event_manager,
+    )
+    .await?;
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+    let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+    tokio::spawn(process_chat_sse(
+        stream,
+        tx_event,
+        provider.stream_idle_timeout(),
+        otel_event_manager.clone(),
+    ));
+    Ok(ResponseStream { rx_event })
+}
+
+/// POSTs `payload` to the provider, retrying rate limits, server errors and
+/// transport failures up to `request_max_retries` times. Shared by the wire
+/// APIs that stream server-sent events without the Responses API's extras.
+pub(crate) async fn send_streaming_request(
+    payload: &serde_json::Value,
+    model: &str,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+    otel_event_manager: &OtelEventManager,
+) -> Result<reqwest::Response> {
     let mut attempt = 0;
     let max_retries = provider.request_max_retries();
     loop {
         attempt += 1;
 
-        let req_builder = provider.create_request_builder(client, &None).await?;
+        let req_builder = provider
+            .create_request_builder(client, &None, model)
+            .await?;
 
         let res = otel_event_manager
             .log_request(attempt, || {
                 req_builder
                     .header(reqwest::header::ACCEPT, "text/event-stream")
-                    .json(&payload)
+                    .json(payload)
                     .send()
             })
             .await;
 
         match res {
-            Ok(resp) if resp.status().is_success() => {
-                let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
-                let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
-                tokio::spawn(process_chat_sse(
-                    stream,
-                    tx_event,
-                    provider.stream_idle_timeout(),
-                    otel_event_manager.clone(),
-                ));
-                return Ok(ResponseStream { rx_event });
-            }
+            Ok(resp) if resp.status().is_success() => return Ok(resp),
             Ok(res) => {
                 let status = res.status();
                 if !(status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()) {
@@ -25,6 +25,7 @@ use tracing::debug;
 use tracing::trace;
 use tracing::warn;
 
+use crate::anthropic_messages::stream_anthropic_messages;
 use crate::chat_completions::AggregateStreamExt;
 use crate::chat_completions::stream_chat_completions;
 use crate::client_common::Prompt;
@@ -34,12 +35,17 @@ use crate::client_common::ResponsesApiRequest;
 use crate::client_common::create_reasoning_param_for_request;
 use crate::client_common::create_text_param_for_request;
 use crate::config::Config;
+use crate::config_types::ModelFallback;
 use crate::default_client::create_client;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::UsageLimitReachedError;
+use crate::features::Features;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
+use crate::gemini_generate_content::stream_gemini_generate_content;
 use crate::model_family::ModelFamily;
+use crate::model_family::derive_default_model_family;
+use crate::model_family::find_family_for_model;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
 use crate::openai_model_info::get_model_info;
@@ -120,8 +126,8 @@ impl ModelClient {
         })
     }
 
-    /// Dispatches to either the Responses or Chat implementation depending on
-    /// the provider config.  Public callers always invoke `stream()` – the
+    /// Dispatches to the Responses, Chat, Anthropic Messages or Gemini
+    /// implementation depending on the provider config.  Public callers always invoke `stream()` – the
     /// specialised helpers are private to avoid accidental misuse.
     pub async fn stream(&self, prompt: &Prompt) -> Result<ResponseStream> {
         self.stream_with_task_kind(prompt, TaskKind::Regular).await
@@ -170,6 +176,26 @@ impl ModelClient {
 
                 Ok(ResponseStream { rx_event: rx })
             }
+            WireApi::Anthropic => {
+                stream_anthropic_messages(
+                    prompt,
+                    &self.config.model_family,
+                    &self.client,
+                    &self.provider,
+                    &self.otel_event_manager,
+                )
+                .await
+            }
+            WireApi::Gemini => {
+                stream_gemini_generate_content(
+                    prompt,
+                    &self.config.model_family,
+                    &self.client,
+                    &self.provider,
+                    &self.otel_event_manager,
+                )
+                .await
+            }
         }
     }
 
@@ -200,6 +226,13 @@ impl ModelClient {
             self.summary,
         );
 
+        // Reasoning models reject a sampling temperature.
+        let temperature = if reasoning.is_some() {
+            None
+        } else {
+            prompt.temperature
+        };
+
         let include: Vec<String> = if reasoning.is_some() {
             vec!["reasoning.encrypted_content".to_string()]
         } else {
@@ -247,6 +280,8 @@ impl ModelClient {
             include,
             prompt_cache_key: Some(self.conversation_id.to_string()),
             text,
+            max_output_tokens: prompt.max_output_tokens,
+            temperature,
         };
 
         let mut payload_json = serde_json::to_value(&payload)?;
@@ -292,13 +327,13 @@ impl ModelClient {
 
         trace!(
             "POST to {}: {:?}",
-            self.provider.get_full_url(&auth),
+            self.provider.get_full_url(&auth, &self.config.model),
             serde_json::to_string(payload_json)
         );
 
         let mut req_builder = self
             .provider
-            .create_request_builder(&self.client, &auth)
+            .create_request_builder(&self.client, &auth, &self.config.model)
             .await
             .map_err(StreamAttemptError::Fatal)?;
 
@@ -466,6 +501,50 @@ impl ModelClient {
     pub fn get_auth_manager(&self) -> Option<Arc<AuthManager>> {
         self.auth_manager.clone()
     }
+
+    pub(crate) fn get_features(&self) -> &Features {
+        &self.config.features
+    }
+
+    /// Returns the configured fallbacks that differ from this client's
+    /// provider and model, in order.
+    pub(crate) fn get_model_fallbacks(&self) -> Vec<ModelFallback> {
+        self.config
+            .model_fallbacks
+            .iter()
+            .filter(|fallback| {
+                fallback.model != self.config.model || fallback.model_provider != self.provider
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a client for the same conversation that talks to `fallback`
+    /// instead, keeping the reasoning settings.
+    pub(crate) fn with_model_fallback(&self, fallback: &ModelFallback) -> Self {
+        let model_family = find_family_for_model(&fallback.model)
+            .unwrap_or_else(|| derive_default_model_family(&fallback.model));
+        let mut config = (*self.config).clone();
+        config.model = fallback.model.clone();
+        config.model_provider_id = fallback.model_provider_id.clone();
+        config.model_provider = fallback.model_provider.clone();
+        config.model_context_window = get_model_info(&model_family).map(|info| info.context_window);
+        config.model_family = model_family;
+
+        let otel_event_manager = self
+            .otel_event_manager
+            .with_model(config.model.as_str(), config.model_family.slug.as_str());
+
+        Self::new(
+            Arc::new(config),
+            self.auth_manager.clone(),
+            otel_event_manager,
+            fallback.model_provider.clone(),
+            self.effort,
+            self.summary,
+            self.conversation_id,
+        )
+    }
 }
 
 enum StreamAttemptError {
@@ -41,6 +41,12 @@ pub struct Prompt {
 
     /// Optional the output schema for the model's response.
     pub output_schema: Option<Value>,
+
+    /// Optional cap on the number of tokens the model may generate.
+    pub(crate) max_output_tokens: Option<u64>,
+
+    /// Optional sampling temperature.
+    pub(crate) temperature: Option<f64>,
 }
 
 impl Prompt {
@@ -278,6 +284,10 @@ pub(crate) struct ResponsesApiRequest<'a> {
     pub(crate) prompt_cache_key: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) text: Option<TextControls>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_output_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) temperature: Option<f64>,
 }
 
 pub(crate) mod tools {
@@ -468,6 +478,8 @@ mod tests {
                 verbosity: Some(OpenAiVerbosity::Low),
                 format: None,
             }),
+            max_output_tokens: None,
+            temperature: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
@@ -506,6 +518,8 @@ mod tests {
             include: vec![],
             prompt_cache_key: None,
             text: Some(text_controls),
+            max_output_tokens: None,
+            temperature: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
@@ -542,9 +556,13 @@ mod tests {
             include: vec![],
             prompt_cache_key: None,
             text: None,
+            max_output_tokens: None,
+            temperature: None,
         };
 
         let v = serde_json::to_value(&req).expect("json");
         assert!(v.get("text").is_none());
+        assert!(v.get("max_output_tokens").is_none());
+        assert!(v.get("temperature").is_none());
     }
 }
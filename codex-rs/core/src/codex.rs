@@ -2,6 +2,7 @@ use std::borrow::Cow;
 use std::fmt::Debug;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::OnceLock;
 use std::sync::atomic::AtomicU64;
 
 use crate::AuthManager;
@@ -9,6 +10,7 @@ use crate::client_common::REVIEW_PROMPT;
 use crate::event_mapping::map_response_item_to_event_messages;
 use crate::function_tool::FunctionCallError;
 use crate::review_format::format_review_findings_block;
+use crate::review_target::resolve_review_request;
 use crate::terminal;
 use crate::user_notification::UserNotifier;
 use async_channel::Receiver;
@@ -28,6 +30,10 @@ use futures::future::BoxFuture;
 use futures::prelude::*;
 use futures::stream::FuturesOrdered;
 use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResource;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::PromptMessage;
 use serde_json;
 use serde_json::Value;
 use tokio::sync::Mutex;
@@ -43,9 +49,12 @@ use crate::apply_patch::convert_apply_patch_to_protocol;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
+use crate::command_safety::exec_policy::load_exec_policy;
 use crate::config::Config;
+use crate::config_types::ModelFallback;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::conversation_history::ConversationHistory;
+use crate::dry_run::DryRunOverlay;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
@@ -58,10 +67,16 @@ use crate::exec_command::WriteStdinParams;
 use crate::executor::Executor;
 use crate::executor::ExecutorConfig;
 use crate::executor::normalize_exec_result;
+use crate::features::Features;
+use crate::file_reads::FileReads;
+use crate::file_reads::shell_read_paths;
+use crate::ghost_snapshots::GhostSnapshots;
+use crate::ghost_snapshots::UndoTarget;
 use crate::mcp::auth::compute_auth_statuses;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::model_family::find_family_for_model;
 use crate::openai_model_info::get_model_info;
+use crate::openai_model_info::get_model_pricing;
 use crate::openai_tools::ToolsConfig;
 use crate::openai_tools::ToolsConfigParams;
 use crate::parse_command::parse_command;
@@ -79,6 +94,7 @@ use crate::protocol::EventMsg;
 use crate::protocol::ExecApprovalRequestEvent;
 use crate::protocol::ExecCommandBeginEvent;
 use crate::protocol::ExecCommandEndEvent;
+use crate::protocol::FileChange;
 use crate::protocol::InputItem;
 use crate::protocol::ListCustomPromptsResponseEvent;
 use crate::protocol::Op;
@@ -104,10 +120,14 @@ use crate::state::TaskKind;
 use crate::tasks::CompactTask;
 use crate::tasks::RegularTask;
 use crate::tasks::ReviewTask;
+use crate::tasks::TaskOutcome;
+use crate::token_budget::BudgetStatus;
+use crate::token_budget::check_budget;
 use crate::tools::ToolRouter;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::format_exec_output_str;
 use crate::tools::parallel::ToolCallRuntime;
+use crate::turn_diff_tracker::SessionDiffHistory;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_instructions::UserInstructions;
@@ -124,6 +144,7 @@ use codex_protocol::models::ResponseItem;
 use codex_protocol::protocol::InitialHistory;
 
 pub mod compact;
+mod mcp_sampling;
 use self::compact::build_compacted_history;
 use self::compact::collect_user_messages;
 
@@ -273,6 +294,33 @@ impl TurnContext {
             .map(PathBuf::from)
             .map_or_else(|| self.cwd.clone(), |p| self.cwd.join(p))
     }
+
+    /// Returns a copy of this context whose client talks to `fallback`, with
+    /// the tools rebuilt for the fallback's model family.
+    fn with_model_fallback(&self, fallback: &ModelFallback) -> Self {
+        let client = self.client.with_model_fallback(fallback);
+        let features = if self.is_review_mode {
+            review_features(client.get_features())
+        } else {
+            client.get_features().clone()
+        };
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &client.get_model_family(),
+            features: &features,
+        });
+        Self {
+            client,
+            tools_config,
+            user_instructions: self.user_instructions.clone(),
+            base_instructions: self.base_instructions.clone(),
+            approval_policy: self.approval_policy,
+            sandbox_policy: self.sandbox_policy.clone(),
+            shell_environment_policy: self.shell_environment_policy.clone(),
+            cwd: self.cwd.clone(),
+            is_review_mode: self.is_review_mode,
+            final_output_json_schema: self.final_output_json_schema.clone(),
+        }
+    }
 }
 
 /// Configure the model session.
@@ -364,12 +412,18 @@ impl Session {
         // - load history metadata
         let rollout_fut = RolloutRecorder::new(&config, rollout_params);
 
+        // MCP servers may sample the model once the session exists; the slot
+        // is filled in right after the session is constructed below.
+        let sampling_session = Arc::new(OnceLock::new());
         let mcp_fut = McpConnectionManager::new(
             config.mcp_servers.clone(),
             config
                 .features
                 .enabled(crate::features::Feature::RmcpClient),
             config.mcp_oauth_credentials_store_mode,
+            Some(mcp_sampling::sampling_handler(Arc::clone(
+                &sampling_session,
+            ))),
         );
         let default_shell_fut = shell::default_user_shell();
         let history_meta_fut = crate::message_history::history_metadata(&config);
@@ -440,6 +494,24 @@ impl Session {
             }
         }
 
+        // A broken policy file should not prevent the session from starting;
+        // report it and fall back to the built-in command safety checks.
+        let exec_policy = match config.exec_policy_file.as_deref() {
+            Some(path) => match load_exec_policy(path) {
+                Ok(policy) => Some(Arc::new(policy)),
+                Err(e) => {
+                    let message = format!("Failed to load exec policy: {e:#}");
+                    error!("{message}");
+                    post_session_configured_error_events.push(Event {
+                        id: INITIAL_SUBMIT_ID.to_owned(),
+                        msg: EventMsg::Error(ErrorEvent { message }),
+                    });
+                    None
+                }
+            },
+            None => None,
+        };
+
         let otel_event_manager = OtelEventManager::new(
             conversation_id,
             config.model.as_str(),
@@ -498,11 +570,29 @@ impl Session {
             rollout: Mutex::new(Some(rollout_recorder)),
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
-            executor: Executor::new(ExecutorConfig::new(
-                turn_context.sandbox_policy.clone(),
-                turn_context.cwd.clone(),
-                config.codex_linux_sandbox_exe.clone(),
+            token_budget: config.token_budget.clone(),
+            executor: Executor::new(
+                ExecutorConfig::new(
+                    turn_context.sandbox_policy.clone(),
+                    turn_context.cwd.clone(),
+                    config.codex_linux_sandbox_exe.clone(),
+                )
+                .with_exec_policy(exec_policy)
+                .with_resource_limits(config.sandbox_resource_limits)
+                .with_linux_sandbox_backend(config.linux_sandbox_backend)
+                .with_report_denied_writes(config.sandbox_report_denied_writes),
+            )
+            .with_command_allowlist(config.codex_home.clone()),
+            ghost_snapshots: Mutex::new(GhostSnapshots::new(
+                config
+                    .features
+                    .enabled(crate::features::Feature::GhostCommit),
+                &config.codex_home,
+                conversation_id,
             )),
+            file_reads: Mutex::new(FileReads::default()),
+            diff_history: Mutex::new(SessionDiffHistory::default()),
+            dry_run: config.dry_run.then(|| Mutex::new(DryRunOverlay::default())),
         };
 
         let sess = Arc::new(Session {
@@ -513,6 +603,7 @@ impl Session {
             services,
             next_internal_sub_id: AtomicU64::new(0),
         });
+        let _ = sampling_session.set(Arc::downgrade(&sess));
 
         // Dispatch the SessionConfiguredEvent first and then report any errors.
         // If resuming, include converted initial messages in the payload so UIs can render them immediately.
@@ -566,6 +657,21 @@ impl Session {
                 let rollout_items = conversation_history.get_rollout_items();
                 let persist = matches!(conversation_history, InitialHistory::Forked(_));
 
+                // Snapshots and turn diffs belong to the workspace of the
+                // resumed session.
+                if !persist {
+                    self.services
+                        .ghost_snapshots
+                        .lock()
+                        .await
+                        .replay(&rollout_items);
+                    self.services
+                        .diff_history
+                        .lock()
+                        .await
+                        .replay(&rollout_items);
+                }
+
                 // Always add response items to conversation history
                 let reconstructed_history =
                     self.reconstruct_history_from_rollout(turn_context, &rollout_items);
@@ -603,6 +709,7 @@ impl Session {
         command: Vec<String>,
         cwd: PathBuf,
         reason: Option<String>,
+        suggested_writable_roots: Vec<PathBuf>,
     ) -> ReviewDecision {
         // Add the tx_approve callback to the map before sending the request.
         let (tx_approve, rx_approve) = oneshot::channel();
@@ -630,6 +737,7 @@ impl Session {
                 cwd,
                 reason,
                 parsed_cmd,
+                suggested_writable_roots,
             }),
         };
         self.send_event(event).await;
@@ -793,9 +901,44 @@ impl Session {
                     token_usage,
                     turn_context.client.get_model_context_window(),
                 );
+                if let Some(pricing) = get_model_pricing(&turn_context.client.get_model_family()) {
+                    state.add_estimated_cost(pricing.cost_usd(token_usage));
+                }
             }
         }
         self.send_token_count_event(sub_id).await;
+        self.warn_on_token_budget(sub_id).await;
+    }
+
+    async fn token_budget_status(&self) -> BudgetStatus {
+        let state = self.state.lock().await;
+        let usage = state
+            .token_info
+            .as_ref()
+            .map(|info| info.total_token_usage.clone())
+            .unwrap_or_default();
+        check_budget(
+            &self.services.token_budget,
+            &usage,
+            state.estimated_cost_usd(),
+        )
+    }
+
+    /// Describes the exhausted limit once the session ran out of budget.
+    pub(crate) async fn token_budget_exhausted(&self) -> Option<String> {
+        match self.token_budget_status().await {
+            BudgetStatus::Exhausted(message) => Some(message),
+            BudgetStatus::Within | BudgetStatus::Warning(_) => None,
+        }
+    }
+
+    async fn warn_on_token_budget(&self, sub_id: &str) {
+        let BudgetStatus::Warning(message) = self.token_budget_status().await else {
+            return;
+        };
+        if self.state.lock().await.mark_token_budget_warning_sent() {
+            self.notify_background_event(sub_id, message).await;
+        }
     }
 
     async fn update_rate_limits(&self, sub_id: &str, new_rate_limits: RateLimitSnapshot) {
@@ -869,11 +1012,27 @@ impl Session {
             Some(ApplyPatchCommandContext {
                 user_explicitly_approved_this_action,
                 changes,
+                ..
             }) => {
                 {
                     let mut tracker = turn_diff_tracker.lock().await;
                     tracker.on_patch_begin(&changes);
                 }
+                let touched = changes
+                    .iter()
+                    .flat_map(|(path, change)| {
+                        let move_path = match change {
+                            FileChange::Update {
+                                move_path: Some(dest),
+                                ..
+                            } => Some(dest.clone()),
+                            _ => None,
+                        };
+                        std::iter::once(path.clone()).chain(move_path)
+                    })
+                    .collect();
+                self.record_ghost_snapshot_paths(&sub_id, &cwd, touched)
+                    .await;
 
                 EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
                     call_id,
@@ -881,12 +1040,17 @@ impl Session {
                     changes,
                 })
             }
-            None => EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
-                call_id,
-                command: command_for_display.clone(),
-                cwd,
-                parsed_cmd: parse_command(&command_for_display),
-            }),
+            None => {
+                let parsed_cmd = parse_command(&command_for_display);
+                self.record_file_reads(shell_read_paths(&parsed_cmd, &cwd))
+                    .await;
+                EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+                    call_id,
+                    command: command_for_display.clone(),
+                    cwd,
+                    parsed_cmd,
+                })
+            }
         };
         let event = Event {
             id: sub_id.to_string(),
@@ -901,7 +1065,7 @@ impl Session {
         sub_id: &str,
         call_id: &str,
         output: &ExecToolCallOutput,
-        is_apply_patch: bool,
+        apply_patch: Option<&ApplyPatchCommandContext>,
     ) {
         let ExecToolCallOutput {
             stdout,
@@ -910,6 +1074,7 @@ impl Session {
             duration,
             exit_code,
             timed_out: _,
+            sandbox_violations,
         } = output;
         // Send full stdout/stderr to clients; do not truncate.
         let stdout = stdout.text.clone();
@@ -917,12 +1082,13 @@ impl Session {
         let formatted_output = format_exec_output_str(output);
         let aggregated_output: String = aggregated_output.text.clone();
 
-        let msg = if is_apply_patch {
+        let msg = if let Some(apply_patch) = apply_patch {
             EventMsg::PatchApplyEnd(PatchApplyEndEvent {
                 call_id: call_id.to_string(),
                 stdout,
                 stderr,
                 success: *exit_code == 0,
+                fuzzy_hunks: apply_patch.fuzzy_hunks.clone(),
             })
         } else {
             EventMsg::ExecCommandEnd(ExecCommandEndEvent {
@@ -933,6 +1099,7 @@ impl Session {
                 exit_code: *exit_code,
                 duration: *duration,
                 formatted_output,
+                sandbox_violations: sandbox_violations.clone(),
             })
         };
 
@@ -944,19 +1111,40 @@ impl Session {
 
         // If this is an apply_patch, after we emit the end patch, emit a second event
         // with the full turn diff if there is one.
-        if is_apply_patch {
-            let unified_diff = {
-                let mut tracker = turn_diff_tracker.lock().await;
-                tracker.get_unified_diff()
-            };
-            if let Ok(Some(unified_diff)) = unified_diff {
-                let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
-                let event = Event {
-                    id: sub_id.into(),
-                    msg,
-                };
-                self.send_event(event).await;
+        if let Some(apply_patch) = apply_patch {
+            // The model knows what the files it just patched look like.
+            if *exit_code == 0 {
+                self.record_file_reads(apply_patch.changes.keys().cloned())
+                    .await;
             }
+            self.send_turn_diff(sub_id, &turn_diff_tracker).await;
+        }
+    }
+
+    /// Emit the current diff of the turn and remember it in the session's
+    /// diff history.
+    async fn send_turn_diff(&self, sub_id: &str, turn_diff_tracker: &SharedTurnDiffTracker) {
+        let (turn, unified_diff) = {
+            let mut tracker = turn_diff_tracker.lock().await;
+            (tracker.turn(), tracker.get_unified_diff())
+        };
+        if let Ok(Some(unified_diff)) = unified_diff {
+            let files = self
+                .services
+                .diff_history
+                .lock()
+                .await
+                .record(turn, unified_diff.clone());
+            let msg = EventMsg::TurnDiff(TurnDiffEvent {
+                turn,
+                unified_diff,
+                files,
+            });
+            let event = Event {
+                id: sub_id.to_string(),
+                msg,
+            };
+            self.send_event(event).await;
         }
     }
     /// Runs the exec tool call and emits events for the begin and end of the
@@ -970,7 +1158,6 @@ impl Session {
         approval_policy: AskForApproval,
     ) -> Result<ExecToolCallOutput, ExecError> {
         let PreparedExec { context, request } = prepared;
-        let is_apply_patch = context.apply_patch.is_some();
         let sub_id = context.sub_id.clone();
         let call_id = context.call_id.clone();
 
@@ -991,7 +1178,7 @@ impl Session {
             &sub_id,
             &call_id,
             borrowed,
-            is_apply_patch,
+            context.apply_patch.as_ref(),
         )
         .await;
 
@@ -1261,6 +1448,7 @@ async fn submission_loop(
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items).await {
                     // no current task, spawn a new one
+                    sess.capture_ghost_snapshot(&turn_context, &sub.id).await;
                     sess.spawn_task(Arc::clone(&turn_context), sub.id, items, RegularTask)
                         .await;
                 }
@@ -1354,6 +1542,7 @@ async fn submission_loop(
                     turn_context = Arc::new(fresh_turn_context);
 
                     // no current task, spawn a new one with the per-turn context
+                    sess.capture_ghost_snapshot(&turn_context, &sub.id).await;
                     sess.spawn_task(Arc::clone(&turn_context), sub.id, items, RegularTask)
                         .await;
                 }
@@ -1370,6 +1559,12 @@ async fn submission_loop(
                 }
                 other => sess.notify_approval(&id, other).await,
             },
+            Op::McpSamplingApproval { id, decision } => match decision {
+                ReviewDecision::Abort => {
+                    sess.interrupt_task().await;
+                }
+                other => sess.notify_approval(&id, other).await,
+            },
             Op::AddToHistory { text } => {
                 let id = sess.conversation_id;
                 let config = config.clone();
@@ -1449,10 +1644,64 @@ async fn submission_loop(
                     id: sub_id,
                     msg: EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
                         custom_prompts,
+                        mcp_prompts: sess.services.mcp_connection_manager.list_all_prompts(),
                     }),
                 };
                 sess.send_event(event).await;
             }
+            Op::RunMcpPrompt {
+                server,
+                name,
+                arguments,
+            } => {
+                // Rendering the prompt is a round trip to the server; do it
+                // off the submission loop so other ops are not held up.
+                let sess = sess.clone();
+                let turn_context = Arc::clone(&turn_context);
+                let sub_id = sub.id.clone();
+                tokio::spawn(async move {
+                    let arguments = (!arguments.is_empty()).then(|| serde_json::json!(arguments));
+                    let items = match sess
+                        .services
+                        .mcp_connection_manager
+                        .get_prompt(&server, &name, arguments)
+                        .await
+                    {
+                        Ok(result) => mcp_prompt_messages_to_input_items(result.messages),
+                        Err(e) => {
+                            sess.send_event(Event {
+                                id: sub_id,
+                                msg: EventMsg::Error(ErrorEvent {
+                                    message: format!("Failed to run MCP prompt: {e:#}"),
+                                }),
+                            })
+                            .await;
+                            return;
+                        }
+                    };
+                    if items.is_empty() {
+                        sess.send_event(Event {
+                            id: sub_id,
+                            msg: EventMsg::Error(ErrorEvent {
+                                message: format!(
+                                    "MCP prompt `{server}/{name}` returned no content"
+                                ),
+                            }),
+                        })
+                        .await;
+                        return;
+                    }
+                    turn_context
+                        .client
+                        .get_otel_event_manager()
+                        .user_prompt(&items);
+                    if let Err(items) = sess.inject_input(items).await {
+                        sess.capture_ghost_snapshot(&turn_context, &sub_id).await;
+                        sess.spawn_task(turn_context, sub_id, items, RegularTask)
+                            .await;
+                    }
+                });
+            }
             Op::Compact => {
                 // Attempt to inject input into current task
                 if let Err(items) = sess
@@ -1522,6 +1771,26 @@ async fn submission_loop(
                 };
                 sess.send_event(event).await;
             }
+            Op::Undo { turns } => {
+                sess.undo(turn_context.cwd.clone(), &sub.id, UndoTarget::Turns(turns))
+                    .await;
+            }
+            Op::GetSessionDiff { since_turn } => {
+                let diff = sess.services.diff_history.lock().await.since(since_turn);
+                let event = Event {
+                    id: sub.id.clone(),
+                    msg: EventMsg::SessionDiff(diff),
+                };
+                sess.send_event(event).await;
+            }
+            Op::RestoreToTurn { turn } => {
+                sess.undo(
+                    turn_context.cwd.clone(),
+                    &sub.id,
+                    UndoTarget::BeforeTurn(turn),
+                )
+                .await;
+            }
             Op::Review { review_request } => {
                 spawn_review_thread(
                     sess.clone(),
@@ -1540,6 +1809,16 @@ async fn submission_loop(
     debug!("Agent loop exited");
 }
 
+/// For reviews, disable plan, web_search, view_image regardless of global settings.
+fn review_features(features: &Features) -> Features {
+    let mut review_features = features.clone();
+    review_features.disable(crate::features::Feature::PlanTool);
+    review_features.disable(crate::features::Feature::WebSearchRequest);
+    review_features.disable(crate::features::Feature::ViewImageTool);
+    review_features.disable(crate::features::Feature::StreamableShell);
+    review_features
+}
+
 /// Spawn a review thread using the given prompt.
 async fn spawn_review_thread(
     sess: Arc<Session>,
@@ -1548,18 +1827,25 @@ async fn spawn_review_thread(
     sub_id: String,
     review_request: ReviewRequest,
 ) {
+    let review_request =
+        match resolve_review_request(&parent_turn_context.cwd, review_request).await {
+            Ok(review_request) => review_request,
+            Err(message) => {
+                sess.send_event(Event {
+                    id: sub_id,
+                    msg: EventMsg::Error(ErrorEvent { message }),
+                })
+                .await;
+                return;
+            }
+        };
+
     let model = config.review_model.clone();
     let review_model_family = find_family_for_model(&model)
         .unwrap_or_else(|| parent_turn_context.client.get_model_family());
-    // For reviews, disable plan, web_search, view_image regardless of global settings.
-    let mut review_features = config.features.clone();
-    review_features.disable(crate::features::Feature::PlanTool);
-    review_features.disable(crate::features::Feature::WebSearchRequest);
-    review_features.disable(crate::features::Feature::ViewImageTool);
-    review_features.disable(crate::features::Feature::StreamableShell);
     let tools_config = ToolsConfig::new(&ToolsConfigParams {
         model_family: &review_model_family,
-        features: &review_features,
+        features: &review_features(&config.features),
     });
 
     let base_instructions = REVIEW_PROMPT.to_string();
@@ -1618,6 +1904,7 @@ async fn spawn_review_thread(
 
     // Clone sub_id for the upcoming announcement before moving it into the task.
     let sub_id_for_event = sub_id.clone();
+    sess.services.ghost_snapshots.lock().await.start_turn();
     sess.spawn_task(tc.clone(), sub_id, input, ReviewTask).await;
 
     // Announce entering review mode so UIs can switch modes.
@@ -1647,13 +1934,13 @@ async fn spawn_review_thread(
 /// user_instructions. Emits ExitedReviewMode upon final review message.
 pub(crate) async fn run_task(
     sess: Arc<Session>,
-    turn_context: Arc<TurnContext>,
+    mut turn_context: Arc<TurnContext>,
     sub_id: String,
     input: Vec<InputItem>,
     task_kind: TaskKind,
-) -> Option<String> {
+) -> TaskOutcome {
     if input.is_empty() {
-        return None;
+        return TaskOutcome::Finished(None);
     }
     let event = Event {
         id: sub_id.clone(),
@@ -1681,10 +1968,19 @@ pub(crate) async fn run_task(
     let mut last_agent_message: Option<String> = None;
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
     // many turns, from the perspective of the user, it is a single turn.
-    let turn_diff_tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::new()));
+    let turn = sess.services.ghost_snapshots.lock().await.current_turn();
+    let turn_diff_tracker = Arc::new(tokio::sync::Mutex::new(TurnDiffTracker::for_turn(turn)));
     let mut auto_compact_recently_attempted = false;
+    let mut over_budget = None;
 
     loop {
+        // Stop before sampling again once the session ran out of budget; the
+        // task is then reported as aborted.
+        if let Some(message) = sess.token_budget_exhausted().await {
+            over_budget = Some(message);
+            break;
+        }
+
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
@@ -1730,7 +2026,7 @@ pub(crate) async fn run_task(
             .collect();
         match run_turn(
             Arc::clone(&sess),
-            Arc::clone(&turn_context),
+            &mut turn_context,
             Arc::clone(&turn_diff_tracker),
             sub_id.clone(),
             turn_input,
@@ -1927,7 +2223,10 @@ pub(crate) async fn run_task(
         .await;
     }
 
-    last_agent_message
+    match over_budget {
+        Some(message) => TaskOutcome::OverBudget(message),
+        None => TaskOutcome::Finished(last_agent_message),
+    }
 }
 
 /// Parse the review output; when not valid JSON, build a structured
@@ -1955,39 +2254,45 @@ fn parse_review_output_event(text: &str) -> ReviewOutputEvent {
     }
 }
 
+/// Runs one model turn. When the turn switches to a configured fallback,
+/// `turn_context` is replaced so the rest of the task stays on the fallback.
 async fn run_turn(
     sess: Arc<Session>,
-    turn_context: Arc<TurnContext>,
+    turn_context: &mut Arc<TurnContext>,
     turn_diff_tracker: SharedTurnDiffTracker,
     sub_id: String,
     input: Vec<ResponseItem>,
     task_kind: TaskKind,
 ) -> CodexResult<TurnRunResult> {
     let mcp_tools = sess.services.mcp_connection_manager.list_all_tools();
-    let router = Arc::new(ToolRouter::from_config(
-        &turn_context.tools_config,
-        Some(mcp_tools),
-    ));
+    let tools_config = turn_context
+        .tools_config
+        .clone()
+        .with_mcp_resource_servers(sess.services.mcp_connection_manager.resource_servers());
+    let router = Arc::new(ToolRouter::from_config(&tools_config, Some(mcp_tools)));
 
     let model_supports_parallel = turn_context
         .client
         .get_model_family()
         .supports_parallel_tool_calls;
     let parallel_tool_calls = model_supports_parallel;
-    let prompt = Prompt {
+    let mut prompt = Prompt {
         input,
         tools: router.specs(),
         parallel_tool_calls,
         base_instructions_override: turn_context.base_instructions.clone(),
         output_schema: turn_context.final_output_json_schema.clone(),
+        ..Default::default()
     };
 
+    let mut router = router;
+    let mut fallbacks = turn_context.client.get_model_fallbacks().into_iter();
     let mut retries = 0;
     loop {
         match try_run_turn(
             Arc::clone(&router),
             Arc::clone(&sess),
-            Arc::clone(&turn_context),
+            Arc::clone(turn_context),
             Arc::clone(&turn_diff_tracker),
             &sub_id,
             &prompt,
@@ -2000,7 +2305,7 @@ async fn run_turn(
             Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
             Err(e @ CodexErr::Fatal(_)) => return Err(e),
             Err(e @ CodexErr::ContextWindowExceeded) => {
-                sess.set_total_tokens_full(&sub_id, &turn_context).await;
+                sess.set_total_tokens_full(&sub_id, turn_context).await;
                 return Err(e);
             }
             Err(CodexErr::UsageLimitReached(e)) => {
@@ -2034,6 +2339,30 @@ async fn run_turn(
                     .await;
 
                     tokio::time::sleep(delay).await;
+                } else if let Some(fallback) = fallbacks.next() {
+                    // Continue the turn on the next configured provider/model
+                    // rather than failing it.
+                    let message = format!(
+                        "{e}; switching to model `{}` on provider `{}`",
+                        fallback.model, fallback.model_provider_id
+                    );
+                    warn!("{message}");
+                    sess.notify_stream_error(&sub_id, message).await;
+
+                    *turn_context = Arc::new(turn_context.with_model_fallback(&fallback));
+                    let tools_config = turn_context.tools_config.clone().with_mcp_resource_servers(
+                        sess.services.mcp_connection_manager.resource_servers(),
+                    );
+                    router = Arc::new(ToolRouter::from_config(
+                        &tools_config,
+                        Some(sess.services.mcp_connection_manager.list_all_tools()),
+                    ));
+                    prompt.tools = router.specs();
+                    prompt.parallel_tool_calls = turn_context
+                        .client
+                        .get_model_family()
+                        .supports_parallel_tool_calls;
+                    retries = 0;
                 } else {
                     return Err(e);
                 }
@@ -2257,18 +2586,7 @@ async fn try_run_turn(
 
                 let processed_items: Vec<ProcessedResponseItem> = output.try_collect().await?;
 
-                let unified_diff = {
-                    let mut tracker = turn_diff_tracker.lock().await;
-                    tracker.get_unified_diff()
-                };
-                if let Ok(Some(unified_diff)) = unified_diff {
-                    let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
-                    let event = Event {
-                        id: sub_id.to_string(),
-                        msg,
-                    };
-                    sess.send_event(event).await;
-                }
+                sess.send_turn_diff(sub_id, &turn_diff_tracker).await;
 
                 let result = TurnRunResult {
                     processed_items,
@@ -2374,6 +2692,38 @@ pub(super) fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -
         }
     })
 }
+/// Flattens the messages of a rendered MCP prompt into user input. Text
+/// (including embedded text resources) is joined into a single text item and
+/// images are attached as data URLs; other content kinds are dropped.
+fn mcp_prompt_messages_to_input_items(messages: Vec<PromptMessage>) -> Vec<InputItem> {
+    let mut texts = Vec::new();
+    let mut images = Vec::new();
+    for message in messages {
+        match message.content {
+            ContentBlock::TextContent(text) => texts.push(text.text),
+            ContentBlock::EmbeddedResource(EmbeddedResource {
+                resource: EmbeddedResourceResource::TextResourceContents(resource),
+                ..
+            }) => texts.push(resource.text),
+            ContentBlock::ImageContent(image) => images.push(InputItem::Image {
+                image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+            }),
+            ContentBlock::AudioContent(_)
+            | ContentBlock::ResourceLink(_)
+            | ContentBlock::EmbeddedResource(_) => {}
+        }
+    }
+
+    let mut items = Vec::with_capacity(images.len() + 1);
+    if !texts.is_empty() {
+        items.push(InputItem::Text {
+            text: texts.join("\n\n"),
+        });
+    }
+    items.extend(images);
+    items
+}
+
 fn convert_call_tool_result_to_function_call_output_payload(
     call_tool_result: &CallToolResult,
 ) -> FunctionCallOutputPayload {
@@ -2505,6 +2855,46 @@ mod tests {
     use tokio::time::Duration;
     use tokio::time::sleep;
 
+    #[test]
+    fn mcp_prompt_messages_flatten_to_user_input() {
+        let text = |text: &str| PromptMessage {
+            content: ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text: text.to_string(),
+                r#type: "text".to_string(),
+            }),
+            role: mcp_types::Role::User,
+        };
+        let image = PromptMessage {
+            content: ContentBlock::ImageContent(mcp_types::ImageContent {
+                annotations: None,
+                data: "AAAA".to_string(),
+                mime_type: "image/png".to_string(),
+                r#type: "image".to_string(),
+            }),
+            role: mcp_types::Role::User,
+        };
+
+        let items = mcp_prompt_messages_to_input_items(vec![
+            text("Review this file."),
+            image,
+            text("Focus on error handling."),
+        ]);
+
+        assert_eq!(
+            items,
+            vec![
+                InputItem::Text {
+                    text: "Review this file.\n\nFocus on error handling.".to_string(),
+                },
+                InputItem::Image {
+                    image_url: "data:image/png;base64,AAAA".to_string(),
+                },
+            ]
+        );
+        assert!(mcp_prompt_messages_to_input_items(Vec::new()).is_empty());
+    }
+
     #[test]
     fn reconstruct_history_matches_live_compactions() {
         let (session, turn_context) = make_session_and_context();
@@ -2584,6 +2974,7 @@ mod tests {
             aggregated_output: StreamOutput::new(full),
             duration: StdDuration::from_secs(1),
             timed_out: false,
+            sandbox_violations: Vec::new(),
         };
 
         let out = format_exec_output_str(&exec);
@@ -2633,6 +3024,7 @@ mod tests {
             aggregated_output: StreamOutput::new(full.clone()),
             duration: StdDuration::from_secs(1),
             timed_out: false,
+            sandbox_violations: Vec::new(),
         };
 
         let out = format_exec_output_str(&exec);
@@ -2669,6 +3061,7 @@ mod tests {
             aggregated_output: StreamOutput::new("Command output".to_string()),
             duration: StdDuration::from_secs(1),
             timed_out: true,
+            sandbox_violations: Vec::new(),
         };
 
         let out = format_exec_output_str(&exec);
@@ -2797,11 +3190,20 @@ mod tests {
             rollout: Mutex::new(None),
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            token_budget: config.token_budget.clone(),
             executor: Executor::new(ExecutorConfig::new(
                 turn_context.sandbox_policy.clone(),
                 turn_context.cwd.clone(),
                 None,
             )),
+            ghost_snapshots: Mutex::new(GhostSnapshots::new(
+                false,
+                &config.codex_home,
+                conversation_id,
+            )),
+            file_reads: Mutex::new(FileReads::default()),
+            diff_history: Mutex::new(SessionDiffHistory::default()),
+            dry_run: None,
         };
         let session = Session {
             conversation_id,
@@ -2865,11 +3267,20 @@ mod tests {
             rollout: Mutex::new(None),
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            token_budget: config.token_budget.clone(),
             executor: Executor::new(ExecutorConfig::new(
                 config.sandbox_policy.clone(),
                 config.cwd.clone(),
                 None,
             )),
+            ghost_snapshots: Mutex::new(GhostSnapshots::new(
+                false,
+                &config.codex_home,
+                conversation_id,
+            )),
+            file_reads: Mutex::new(FileReads::default()),
+            diff_history: Mutex::new(SessionDiffHistory::default()),
+            dry_run: None,
         };
         let session = Arc::new(Session {
             conversation_id,
@@ -2897,7 +3308,7 @@ mod tests {
             _ctx: Arc<TurnContext>,
             _sub_id: String,
             _input: Vec<InputItem>,
-        ) -> Option<String> {
+        ) -> TaskOutcome {
             loop {
                 sleep(Duration::from_secs(60)).await;
             }
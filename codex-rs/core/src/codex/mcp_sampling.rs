@@ -0,0 +1,377 @@
+//! Answers `sampling/createMessage` requests from MCP servers.
+//!
+//! Servers typically sample while one of their tools is being called, so
+//! requests are only served while a turn is running: they reuse the turn's
+//! model client (and therefore the session's provider), are gated by the same
+//! approval flow as commands, and their token usage is reported through the
+//! turn's `TokenCount` events. The server's `systemPrompt`, when it sends one,
+//! replaces Codex's base instructions.
+
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::Weak;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use futures::prelude::*;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::CreateMessageResultContent;
+use mcp_types::ModelPreferences;
+use mcp_types::Role;
+use mcp_types::SamplingMessage;
+use mcp_types::SamplingMessageContent;
+use mcp_types::TextContent;
+use tokio::sync::oneshot;
+use tracing::warn;
+
+use super::Session;
+use crate::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::config_types::ModelFallback;
+use crate::mcp_connection_manager::McpSamplingHandler;
+use crate::protocol::AskForApproval;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::McpSamplingApprovalRequestEvent;
+use crate::protocol::ReviewDecision;
+
+/// Stop reason reported to the server when the model finished its answer.
+const END_TURN_STOP_REASON: &str = "endTurn";
+
+/// Builds the handler given to the [`crate::mcp_connection_manager::McpConnectionManager`].
+/// The manager is created before the session, so the session is looked up
+/// through `session` once a request arrives.
+pub(crate) fn sampling_handler(session: Arc<OnceLock<Weak<Session>>>) -> McpSamplingHandler {
+    Arc::new(move |server, params| {
+        let session = session.get().and_then(Weak::upgrade);
+        async move {
+            let Some(session) = session else {
+                bail!("the Codex session is no longer running");
+            };
+            session.handle_mcp_sampling_request(server, params).await
+        }
+        .boxed()
+    })
+}
+
+impl Session {
+    async fn handle_mcp_sampling_request(
+        &self,
+        server: String,
+        params: CreateMessageRequestParams,
+    ) -> Result<CreateMessageResult> {
+        let (sub_id, turn_context) = {
+            let active = self.active_turn.lock().await;
+            active.as_ref().and_then(|at| {
+                at.tasks
+                    .iter()
+                    .next()
+                    .map(|(sub_id, task)| (sub_id.clone(), Arc::clone(&task.turn_context)))
+            })
+        }
+        .ok_or_else(|| anyhow!("sampling is only available while Codex is working on a turn"))?;
+
+        let approved_for_session = self.state.lock().await.is_sampling_approved(&server);
+        if !approved_for_session {
+            if turn_context.approval_policy == AskForApproval::Never {
+                bail!("sampling requires user approval, which the approval policy does not allow");
+            }
+            let call_id = uuid::Uuid::new_v4().to_string();
+            let decision = self
+                .request_mcp_sampling_approval(sub_id.clone(), call_id, server.clone(), &params)
+                .await;
+            match decision {
+                ReviewDecision::Approved => {}
+                ReviewDecision::ApprovedForSession | ReviewDecision::ApprovedAlways => {
+                    self.state.lock().await.approve_sampling_server(server);
+                }
+                // Sampling approvals never offer writable roots.
+                ReviewDecision::ApprovedWithWritableRoots
+                | ReviewDecision::Denied
+                | ReviewDecision::Abort => {
+                    bail!("the user declined the sampling request");
+                }
+            }
+        }
+
+        let prompt = Prompt {
+            input: sampling_messages_to_input(&params.messages)?,
+            base_instructions_override: params.system_prompt.clone(),
+            max_output_tokens: u64::try_from(params.max_tokens).ok().filter(|&n| n > 0),
+            temperature: params.temperature,
+            ..Default::default()
+        };
+        let client = preferred_model(
+            &turn_context.client.get_model(),
+            &turn_context.client.get_model_fallbacks(),
+            params.model_preferences.as_ref(),
+        )
+        .map_or_else(
+            || turn_context.client.clone(),
+            |fallback| turn_context.client.with_model_fallback(fallback),
+        );
+        let mut stream = client.stream(&prompt).await?;
+        let mut text = String::new();
+        loop {
+            let Some(event) = stream.next().await else {
+                bail!("stream closed before response.completed");
+            };
+            match event? {
+                ResponseEvent::OutputItemDone(ResponseItem::Message { role, content, .. })
+                    if role == "assistant" =>
+                {
+                    for item in content {
+                        if let ContentItem::OutputText { text: chunk } = item {
+                            text.push_str(&chunk);
+                        }
+                    }
+                }
+                ResponseEvent::RateLimits(snapshot) => {
+                    self.update_rate_limits(&sub_id, snapshot).await;
+                }
+                ResponseEvent::Completed { token_usage, .. } => {
+                    self.update_token_usage_info(&sub_id, &turn_context, token_usage.as_ref())
+                        .await;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(CreateMessageResult {
+            content: CreateMessageResultContent::TextContent(TextContent {
+                annotations: None,
+                text,
+                r#type: "text".to_string(),
+            }),
+            model: client.get_model(),
+            role: Role::Assistant,
+            stop_reason: Some(END_TURN_STOP_REASON.to_string()),
+        })
+    }
+
+    /// Emit an MCP sampling approval request and await the user's decision.
+    /// The request is keyed by `call_id`, which clients echo back in
+    /// `Op::McpSamplingApproval`.
+    async fn request_mcp_sampling_approval(
+        &self,
+        sub_id: String,
+        call_id: String,
+        server: String,
+        params: &CreateMessageRequestParams,
+    ) -> ReviewDecision {
+        let (tx_approve, rx_approve) = oneshot::channel();
+        let prev_entry = {
+            let mut active = self.active_turn.lock().await;
+            match active.as_mut() {
+                Some(at) => {
+                    let mut ts = at.turn_state.lock().await;
+                    ts.insert_pending_approval(call_id.clone(), tx_approve)
+                }
+                None => None,
+            }
+        };
+        if prev_entry.is_some() {
+            warn!("Overwriting existing pending approval for call_id: {call_id}");
+        }
+
+        let event = Event {
+            id: sub_id,
+            msg: EventMsg::McpSamplingApprovalRequest(McpSamplingApprovalRequestEvent {
+                call_id,
+                server,
+                request: params.clone(),
+            }),
+        };
+        self.send_event(event).await;
+        rx_approve.await.unwrap_or_default()
+    }
+}
+
+/// Picks the model named by the server's `modelPreferences` hints. Codex can
+/// only sample from the session model and the configured `model_fallbacks`, so
+/// each hint, in order, is matched as a substring of those model names. Returns
+/// `None` when the session model should be used.
+fn preferred_model<'a>(
+    current: &str,
+    fallbacks: &'a [ModelFallback],
+    preferences: Option<&ModelPreferences>,
+) -> Option<&'a ModelFallback> {
+    let hints = preferences.and_then(|preferences| preferences.hints.as_ref())?;
+    for hint in hints.iter().filter_map(|hint| hint.name.as_deref()) {
+        if current.contains(hint) {
+            return None;
+        }
+        if let Some(fallback) = fallbacks.iter().find(|f| f.model.contains(hint)) {
+            return Some(fallback);
+        }
+    }
+    None
+}
+
+/// Converts the conversation sent by the server into model input. Images are
+/// only accepted from the user, mirroring what the Responses API supports.
+fn sampling_messages_to_input(messages: &[SamplingMessage]) -> Result<Vec<ResponseItem>> {
+    messages
+        .iter()
+        .map(|message| {
+            let (role, content) = match (&message.role, &message.content) {
+                (Role::User, SamplingMessageContent::TextContent(text)) => (
+                    "user",
+                    ContentItem::InputText {
+                        text: text.text.clone(),
+                    },
+                ),
+                (Role::Assistant, SamplingMessageContent::TextContent(text)) => (
+                    "assistant",
+                    ContentItem::OutputText {
+                        text: text.text.clone(),
+                    },
+                ),
+                (Role::User, SamplingMessageContent::ImageContent(image)) => (
+                    "user",
+                    ContentItem::InputImage {
+                        image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+                    },
+                ),
+                (Role::Assistant, SamplingMessageContent::ImageContent(_)) => {
+                    bail!("image content is only supported in user messages")
+                }
+                (_, SamplingMessageContent::AudioContent(_)) => {
+                    bail!("audio content is not supported")
+                }
+            };
+            Ok(ResponseItem::Message {
+                id: None,
+                role: role.to_string(),
+                content: vec![content],
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::AudioContent;
+    use mcp_types::ImageContent;
+    use mcp_types::ModelHint;
+    use pretty_assertions::assert_eq;
+
+    fn text_message(role: Role, text: &str) -> SamplingMessage {
+        SamplingMessage {
+            content: SamplingMessageContent::TextContent(TextContent {
+                annotations: None,
+                text: text.to_string(),
+                r#type: "text".to_string(),
+            }),
+            role,
+        }
+    }
+
+    #[test]
+    fn sampling_messages_map_to_response_items() {
+        let messages = vec![
+            text_message(Role::User, "What is 2 + 2?"),
+            text_message(Role::Assistant, "4"),
+            SamplingMessage {
+                content: SamplingMessageContent::ImageContent(ImageContent {
+                    annotations: None,
+                    data: "aGk=".to_string(),
+                    mime_type: "image/png".to_string(),
+                    r#type: "image".to_string(),
+                }),
+                role: Role::User,
+            },
+        ];
+
+        let input = sampling_messages_to_input(&messages).expect("convert messages");
+        assert_eq!(
+            input,
+            vec![
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText {
+                        text: "What is 2 + 2?".to_string(),
+                    }],
+                },
+                ResponseItem::Message {
+                    id: None,
+                    role: "assistant".to_string(),
+                    content: vec![ContentItem::OutputText {
+                        text: "4".to_string(),
+                    }],
+                },
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputImage {
+                        image_url: "data:image/png;base64,aGk=".to_string(),
+                    }],
+                },
+            ]
+        );
+    }
+
+    fn fallback(model: &str) -> ModelFallback {
+        ModelFallback {
+            model: model.to_string(),
+            model_provider_id: "openai".to_string(),
+            model_provider: crate::built_in_model_providers()["openai"].clone(),
+        }
+    }
+
+    fn hints(names: &[&str]) -> ModelPreferences {
+        ModelPreferences {
+            cost_priority: None,
+            hints: Some(
+                names
+                    .iter()
+                    .map(|name| ModelHint {
+                        name: Some(name.to_string()),
+                    })
+                    .collect(),
+            ),
+            intelligence_priority: None,
+            speed_priority: None,
+        }
+    }
+
+    #[test]
+    fn preferred_model_follows_hints_in_order() {
+        let fallbacks = vec![fallback("gpt-4.1"), fallback("o4-mini")];
+
+        assert_eq!(preferred_model("gpt-5", &fallbacks, None), None);
+        assert_eq!(
+            preferred_model("gpt-5", &fallbacks, Some(&hints(&["claude", "mini"]))),
+            Some(&fallbacks[1])
+        );
+        assert_eq!(
+            preferred_model("gpt-5", &fallbacks, Some(&hints(&["gpt-5", "mini"]))),
+            None
+        );
+        assert_eq!(
+            preferred_model("gpt-5", &fallbacks, Some(&hints(&["claude"]))),
+            None
+        );
+    }
+
+    #[test]
+    fn audio_content_is_rejected() {
+        let messages = vec![SamplingMessage {
+            content: SamplingMessageContent::AudioContent(AudioContent {
+                annotations: None,
+                data: String::new(),
+                mime_type: "audio/wav".to_string(),
+                r#type: "audio".to_string(),
+            }),
+            role: Role::User,
+        }];
+        assert!(sampling_messages_to_input(&messages).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::Path;
+
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::Policy;
+use codex_execpolicy::PolicyParser;
+use codex_execpolicy::get_default_policy_extended_with;
+
+use crate::bash::parse_bash_lc_plain_commands;
+
+/// Result of checking a proposed command against the user's execpolicy.
+#[derive(Debug, PartialEq)]
+pub(crate) enum ExecPolicyVerdict {
+    /// Every program in the command matched a rule and none of them write
+    /// files, so the command can run without asking.
+    Safe,
+    /// At least one program is forbidden by the policy.
+    Forbidden { reason: String },
+    /// The policy does not vouch for the command; fall back to the built-in
+    /// heuristics.
+    Unverified,
+}
+
+/// Loads the Starlark policy at `path`, layered on top of the default policy
+/// that ships with `codex-execpolicy`.
+pub(crate) fn load_exec_policy(path: &Path) -> anyhow::Result<Policy> {
+    let unparsed_policy = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("failed to read exec policy {}: {e}", path.display()))?;
+    let policy_source = path.to_string_lossy();
+    get_default_policy_extended_with(PolicyParser::new(&policy_source, &unparsed_policy))
+        .map_err(|e| anyhow::anyhow!("failed to parse exec policy {}: {e}", path.display()))
+}
+
+/// Checks `command` against `policy`. A `bash -lc "..."` invocation is split
+/// into its plain commands (see [`parse_bash_lc_plain_commands`]) and each one
+/// must be safe for the whole script to be considered safe.
+pub(crate) fn evaluate_exec_policy(policy: &Policy, command: &[String]) -> ExecPolicyVerdict {
+    let commands = parse_bash_lc_plain_commands(command).unwrap_or_else(|| vec![command.to_vec()]);
+    if commands.is_empty() {
+        return ExecPolicyVerdict::Unverified;
+    }
+
+    let mut all_safe = true;
+    for cmd in &commands {
+        let Some((program, args)) = cmd.split_first() else {
+            return ExecPolicyVerdict::Unverified;
+        };
+        let exec_call = ExecCall {
+            program: program.clone(),
+            args: args.to_vec(),
+        };
+        match policy.check(&exec_call) {
+            Ok(MatchedExec::Forbidden { reason, .. }) => {
+                return ExecPolicyVerdict::Forbidden { reason };
+            }
+            Ok(MatchedExec::Match { exec }) if !exec.might_write_files() => {}
+            Ok(MatchedExec::Match { .. }) | Err(_) => all_safe = false,
+        }
+    }
+
+    if all_safe {
+        ExecPolicyVerdict::Safe
+    } else {
+        ExecPolicyVerdict::Unverified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const USER_POLICY: &str = r#"
+define_program(
+    program="cargo",
+    args=["check"],
+)
+
+forbid_program_regex(
+    regex="^curl$",
+    reason="network access must go through the package registry",
+)
+"#;
+
+    fn vec_str(items: &[&str]) -> Vec<String> {
+        items.iter().map(std::string::ToString::to_string).collect()
+    }
+
+    fn policy() -> Policy {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("team.policy");
+        std::fs::write(&path, USER_POLICY).expect("write policy");
+        load_exec_policy(&path).expect("load policy")
+    }
+
+    #[test]
+    fn user_program_is_safe() {
+        assert_eq!(
+            evaluate_exec_policy(&policy(), &vec_str(&["cargo", "check"])),
+            ExecPolicyVerdict::Safe
+        );
+    }
+
+    #[test]
+    fn default_policy_programs_are_still_known() {
+        assert_eq!(
+            evaluate_exec_policy(&policy(), &vec_str(&["pwd"])),
+            ExecPolicyVerdict::Safe
+        );
+    }
+
+    #[test]
+    fn program_that_writes_files_is_unverified() {
+        assert_eq!(
+            evaluate_exec_policy(&policy(), &vec_str(&["cp", "a.txt", "b.txt"])),
+            ExecPolicyVerdict::Unverified
+        );
+    }
+
+    #[test]
+    fn unknown_program_is_unverified() {
+        assert_eq!(
+            evaluate_exec_policy(&policy(), &vec_str(&["cargo", "publish"])),
+            ExecPolicyVerdict::Unverified
+        );
+    }
+
+    #[test]
+    fn forbidden_program_inside_bash_script() {
+        assert_eq!(
+            evaluate_exec_policy(
+                &policy(),
+                &vec_str(&["bash", "-lc", "pwd && curl https://example.com"])
+            ),
+            ExecPolicyVerdict::Forbidden {
+                reason: "network access must go through the package registry".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_policy_reports_error() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("broken.policy");
+        std::fs::write(&path, "define_program(").expect("write policy");
+        assert!(load_exec_policy(&path).is_err());
+    }
+}
@@ -5,8 +5,11 @@ use crate::config_loader::merge_toml_values;
 use crate::config_profile::ConfigProfile;
 use crate::config_types::DEFAULT_OTEL_ENVIRONMENT;
 use crate::config_types::History;
+use crate::config_types::LinuxSandboxBackend;
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
+use crate::config_types::ModelFallback;
+use crate::config_types::ModelFallbackToml;
 use crate::config_types::Notifications;
 use crate::config_types::OtelConfig;
 use crate::config_types::OtelConfigToml;
@@ -15,6 +18,8 @@ use crate::config_types::ReasoningSummaryFormat;
 use crate::config_types::SandboxWorkspaceWrite;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
+use crate::config_types::TokenBudget;
+use crate::config_types::TokenBudgetToml;
 use crate::config_types::Tui;
 use crate::config_types::UriBasedFileOpener;
 use crate::features::Feature;
@@ -32,6 +37,7 @@ use crate::project_doc::DEFAULT_PROJECT_DOC_FILENAME;
 use crate::project_doc::LOCAL_PROJECT_DOC_FILENAME;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::resource_limits::ResourceLimits;
 use anyhow::Context;
 use codex_app_server_protocol::Tools;
 use codex_app_server_protocol::UserSavedConfig;
@@ -95,11 +101,37 @@ pub struct Config {
     /// Info needed to make an API request to the model.
     pub model_provider: ModelProviderInfo,
 
+    /// Providers/models a turn switches to, in order, once the retries against
+    /// the previous one are exhausted.
+    pub model_fallbacks: Vec<ModelFallback>,
+
+    /// Token and cost limits enforced over the whole session.
+    pub token_budget: TokenBudget,
+
+    /// Keep `apply_patch` edits in memory instead of writing them to disk
+    /// (`codex exec --dry-run`).
+    pub dry_run: bool,
+
     /// Approval policy for executing commands.
     pub approval_policy: AskForApproval,
 
     pub sandbox_policy: SandboxPolicy,
 
+    /// Resource limits applied to every sandboxed command.
+    pub sandbox_resource_limits: ResourceLimits,
+
+    /// How sandboxed commands are isolated on Linux.
+    pub linux_sandbox_backend: LinuxSandboxBackend,
+
+    /// Whether the Linux sandbox watches every write of a command so it can
+    /// report the ones it denied.
+    pub sandbox_report_denied_writes: bool,
+
+    /// Starlark execpolicy consulted (on top of the default policy shipped
+    /// with `codex-execpolicy`) when deciding whether a command can run
+    /// without approval.
+    pub exec_policy_file: Option<PathBuf>,
+
     pub shell_environment_policy: ShellEnvironmentPolicy,
 
     /// When `true`, `AgentReasoning` events emitted by the backend will be
@@ -713,6 +745,14 @@ pub struct ConfigToml {
     /// Provider to use from the model_providers map.
     pub model_provider: Option<String>,
 
+    /// Ordered providers/models to fall back to when a turn exhausts its
+    /// retries against the current one.
+    pub model_fallbacks: Option<Vec<ModelFallbackToml>>,
+
+    /// Token and cost limits for a session. Tasks are aborted once a limit is
+    /// reached.
+    pub token_budget: Option<TokenBudgetToml>,
+
     /// Size of the context window for the model, in tokens.
     pub model_context_window: Option<u64>,
 
@@ -734,6 +774,29 @@ pub struct ConfigToml {
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
+    /// Paths whose contents sandboxed commands and file tools cannot read, in
+    /// addition to the default credential directories. `~/` is resolved
+    /// against the home directory and relative paths against the session cwd.
+    #[serde(default)]
+    pub sandbox_deny_read: Vec<PathBuf>,
+
+    /// Resource limits applied to every sandboxed command (CPU time, address
+    /// space, open files, processes, output file size and, on Linux, a
+    /// cgroup v2 memory limit).
+    #[serde(default)]
+    pub sandbox_resource_limits: ResourceLimits,
+
+    /// Linux sandbox implementation: `landlock` (default) or `namespaces`.
+    pub linux_sandbox_backend: Option<LinuxSandboxBackend>,
+
+    /// Report the writes the Linux sandbox denied, so they can be offered as
+    /// writable roots. Slows down commands that write a lot; off by default.
+    pub sandbox_report_denied_writes: Option<bool>,
+
+    /// Path to a Starlark execpolicy file declaring safe and forbidden
+    /// programs. Relative paths are resolved against the session cwd.
+    pub exec_policy_file: Option<PathBuf>,
+
     /// Optional external command to spawn for end-user notifications.
     #[serde(default)]
     pub notify: Option<Vec<String>>,
@@ -884,21 +947,32 @@ impl ConfigToml {
         let resolved_sandbox_mode = sandbox_mode_override
             .or(self.sandbox_mode)
             .unwrap_or_default();
+        let deny_read = self.sandbox_deny_read.clone();
         match resolved_sandbox_mode {
-            SandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
+            SandboxMode::ReadOnly => SandboxPolicy::ReadOnly { deny_read },
             SandboxMode::WorkspaceWrite => match self.sandbox_workspace_write.as_ref() {
                 Some(SandboxWorkspaceWrite {
                     writable_roots,
                     network_access,
+                    network_allowlist,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots: writable_roots.clone(),
+                    deny_read,
                     network_access: *network_access,
+                    network_allowlist: network_allowlist.clone(),
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
                 },
-                None => SandboxPolicy::new_workspace_write_policy(),
+                None => SandboxPolicy::WorkspaceWrite {
+                    writable_roots: Vec::new(),
+                    deny_read,
+                    network_access: false,
+                    network_allowlist: Vec::new(),
+                    exclude_tmpdir_env_var: false,
+                    exclude_slash_tmp: false,
+                },
             },
             SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
         }
@@ -1084,6 +1158,32 @@ impl Config {
             .or(cfg.model)
             .unwrap_or_else(default_model);
 
+        let model_fallbacks = config_profile
+            .model_fallbacks
+            .or(cfg.model_fallbacks)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|fallback| {
+                let model_provider_id = fallback
+                    .model_provider
+                    .unwrap_or_else(|| model_provider_id.clone());
+                let model_provider = model_providers
+                    .get(&model_provider_id)
+                    .ok_or_else(|| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!("Fallback model provider `{model_provider_id}` not found"),
+                        )
+                    })?
+                    .clone();
+                Ok(ModelFallback {
+                    model: fallback.model.unwrap_or_else(|| model.clone()),
+                    model_provider_id,
+                    model_provider,
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+
         let mut model_family =
             find_family_for_model(&model).unwrap_or_else(|| derive_default_model_family(&model));
 
@@ -1120,6 +1220,18 @@ impl Config {
             Self::get_base_instructions(experimental_instructions_path, &resolved_cwd)?;
         let base_instructions = base_instructions.or(file_base_instructions);
 
+        let exec_policy_file = config_profile
+            .exec_policy_file
+            .as_ref()
+            .or(cfg.exec_policy_file.as_ref())
+            .map(|p| {
+                if p.is_relative() {
+                    resolved_cwd.join(p)
+                } else {
+                    p.clone()
+                }
+            });
+
         // Default review model when not set in config; allow CLI override to take precedence.
         let review_model = override_review_model
             .or(cfg.review_model)
@@ -1143,9 +1255,16 @@ impl Config {
             model_auto_compact_token_limit,
             model_provider_id,
             model_provider,
+            model_fallbacks,
+            token_budget: cfg.token_budget.map(TokenBudget::from).unwrap_or_default(),
+            dry_run: false,
             cwd: resolved_cwd,
             approval_policy,
             sandbox_policy,
+            sandbox_resource_limits: cfg.sandbox_resource_limits,
+            linux_sandbox_backend: cfg.linux_sandbox_backend.unwrap_or_default(),
+            sandbox_report_denied_writes: cfg.sandbox_report_denied_writes.unwrap_or(false),
+            exec_policy_file,
             shell_environment_policy,
             notify: cfg.notify,
             user_instructions,
@@ -1400,6 +1519,7 @@ network_access = false  # This should be ignored.
 
         let sandbox_read_only = r#"
 sandbox_mode = "read-only"
+sandbox_deny_read = ["~/.config/gh", ".env"]
 
 [sandbox_workspace_write]
 network_access = true  # This should be ignored.
@@ -1409,7 +1529,9 @@ network_access = true  # This should be ignored.
             .expect("TOML deserialization should succeed");
         let sandbox_mode_override = None;
         assert_eq!(
-            SandboxPolicy::ReadOnly,
+            SandboxPolicy::ReadOnly {
+                deny_read: vec![PathBuf::from("~/.config/gh"), PathBuf::from(".env")],
+            },
             sandbox_read_only_cfg.derive_sandbox_policy(sandbox_mode_override)
         );
 
@@ -1430,7 +1552,9 @@ exclude_slash_tmp = true
         assert_eq!(
             SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![PathBuf::from("/my/workspace")],
+                deny_read: Vec::new(),
                 network_access: false,
+                network_allowlist: Vec::new(),
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
             },
@@ -1564,6 +1688,83 @@ approve_all = true
         Ok(())
     }
 
+    #[test]
+    fn exec_policy_file_resolves_relative_to_cwd() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cwd = TempDir::new()?;
+        let cfg = ConfigToml {
+            exec_policy_file: Some(PathBuf::from("team.policy")),
+            ..Default::default()
+        };
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(cwd.path().to_path_buf()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(
+            config.exec_policy_file,
+            Some(cwd.path().join("team.policy"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn model_fallbacks_default_to_current_provider_and_model() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+model = "gpt-5-codex"
+
+[[model_fallbacks]]
+model = "gpt-5"
+
+[[model_fallbacks]]
+model_provider = "oss"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+
+        let resolved: Vec<(&str, &str)> = config
+            .model_fallbacks
+            .iter()
+            .map(|f| (f.model.as_str(), f.model_provider_id.as_str()))
+            .collect();
+        assert_eq!(resolved, vec![("gpt-5", "openai"), ("gpt-5-codex", "oss")]);
+        assert_eq!(
+            config.model_fallbacks[1].model_provider,
+            config.model_providers["oss"]
+        );
+
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+[[model_fallbacks]]
+model_provider = "missing"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let err = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .expect_err("unknown fallback provider should be rejected");
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+        Ok(())
+    }
+
     #[test]
     fn config_honors_explicit_file_oauth_store_mode() -> std::io::Result<()> {
         let codex_home = TempDir::new()?;
@@ -2219,8 +2420,15 @@ model_verbosity = "high"
                 model_auto_compact_token_limit: None,
                 model_provider_id: "openai".to_string(),
                 model_provider: fixture.openai_provider.clone(),
+                model_fallbacks: Vec::new(),
+                token_budget: TokenBudget::default(),
+                dry_run: false,
                 approval_policy: AskForApproval::Never,
                 sandbox_policy: SandboxPolicy::new_read_only_policy(),
+                sandbox_resource_limits: ResourceLimits::default(),
+                linux_sandbox_backend: LinuxSandboxBackend::default(),
+                sandbox_report_denied_writes: false,
+                exec_policy_file: None,
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
                 user_instructions: None,
                 notify: None,
@@ -2283,8 +2491,15 @@ model_verbosity = "high"
             model_auto_compact_token_limit: None,
             model_provider_id: "openai-chat-completions".to_string(),
             model_provider: fixture.openai_chat_completions_provider.clone(),
+            model_fallbacks: Vec::new(),
+            token_budget: TokenBudget::default(),
+            dry_run: false,
             approval_policy: AskForApproval::UnlessTrusted,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_resource_limits: ResourceLimits::default(),
+            linux_sandbox_backend: LinuxSandboxBackend::default(),
+            sandbox_report_denied_writes: false,
+            exec_policy_file: None,
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
@@ -2362,8 +2577,15 @@ model_verbosity = "high"
             model_auto_compact_token_limit: None,
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
+            model_fallbacks: Vec::new(),
+            token_budget: TokenBudget::default(),
+            dry_run: false,
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_resource_limits: ResourceLimits::default(),
+            linux_sandbox_backend: LinuxSandboxBackend::default(),
+            sandbox_report_denied_writes: false,
+            exec_policy_file: None,
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
@@ -2427,8 +2649,15 @@ model_verbosity = "high"
             model_auto_compact_token_limit: None,
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
+            model_fallbacks: Vec::new(),
+            token_budget: TokenBudget::default(),
+            dry_run: false,
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_resource_limits: ResourceLimits::default(),
+            linux_sandbox_backend: LinuxSandboxBackend::default(),
+            sandbox_report_denied_writes: false,
+            exec_policy_file: None,
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
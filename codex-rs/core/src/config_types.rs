@@ -13,6 +13,9 @@ use serde::Deserialize;
 use serde::Serialize;
 use serde::de::Error as SerdeError;
 
+use crate::model_provider_info::ModelProviderInfo;
+use crate::protocol::NetworkAllowlistEntry;
+
 pub const DEFAULT_OTEL_ENVIRONMENT: &str = "dev";
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
@@ -242,6 +245,19 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// How sandboxed commands are isolated on Linux.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinuxSandboxBackend {
+    /// Landlock file-system rules plus a seccomp network filter.
+    #[default]
+    Landlock,
+    /// Bubblewrap-style user, mount, PID and network namespaces: commands see
+    /// a read-only root with only their writable roots bound read-write, a
+    /// private `/tmp` and `/dev`, and only their own processes.
+    Namespaces,
+}
+
 // ===== OTEL configuration =====
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
@@ -269,6 +285,78 @@ pub enum OtelExporterKind {
     },
 }
 
+/// Entry of `model_fallbacks` in config.toml: a provider and/or model that a
+/// turn continues on once the retries against the previous one are exhausted.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ModelFallbackToml {
+    /// Key into the `model_providers` map. Defaults to the session's provider.
+    pub model_provider: Option<String>,
+
+    /// Defaults to the session's model.
+    pub model: Option<String>,
+}
+
+/// Effective fallback after the provider has been looked up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelFallback {
+    pub model: String,
+    pub model_provider_id: String,
+    pub model_provider: ModelProviderInfo,
+}
+
+pub const DEFAULT_TOKEN_BUDGET_WARN_PERCENT: u8 = 80;
+
+/// `[token_budget]` table in config.toml. Every limit is optional.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TokenBudgetToml {
+    /// Maximum number of tokens (input + output) the session may use.
+    pub max_total_tokens: Option<u64>,
+
+    /// Maximum number of output tokens (including reasoning) the session may use.
+    pub max_output_tokens: Option<u64>,
+
+    /// Maximum estimated spend in US dollars, based on the per-model prices in
+    /// `openai_model_info.rs`. Usage of models without a known price is free.
+    pub max_cost_usd: Option<f64>,
+
+    /// Percentage of a limit at which a warning is emitted. Defaults to 80.
+    pub warn_at_percent: Option<u8>,
+}
+
+/// Effective token budget of a session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenBudget {
+    pub max_total_tokens: Option<u64>,
+    pub max_output_tokens: Option<u64>,
+    pub max_cost_usd: Option<f64>,
+    pub warn_at_percent: u8,
+}
+
+impl Default for TokenBudget {
+    fn default() -> Self {
+        Self {
+            max_total_tokens: None,
+            max_output_tokens: None,
+            max_cost_usd: None,
+            warn_at_percent: DEFAULT_TOKEN_BUDGET_WARN_PERCENT,
+        }
+    }
+}
+
+impl From<TokenBudgetToml> for TokenBudget {
+    fn from(toml: TokenBudgetToml) -> Self {
+        Self {
+            max_total_tokens: toml.max_total_tokens,
+            max_output_tokens: toml.max_output_tokens,
+            max_cost_usd: toml.max_cost_usd,
+            warn_at_percent: toml
+                .warn_at_percent
+                .unwrap_or(DEFAULT_TOKEN_BUDGET_WARN_PERCENT)
+                .min(100),
+        }
+    }
+}
+
 /// OTEL settings loaded from config.toml. Fields are optional so we can apply defaults.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct OtelConfigToml {
@@ -328,6 +416,10 @@ pub struct SandboxWorkspaceWrite {
     pub writable_roots: Vec<PathBuf>,
     #[serde(default)]
     pub network_access: bool,
+    /// Hosts (`host`, `host:port` or `*.domain`) that stay reachable through a
+    /// filtering proxy when `network_access` is `false`.
+    #[serde(default)]
+    pub network_allowlist: Vec<NetworkAllowlistEntry>,
     #[serde(default)]
     pub exclude_tmpdir_env_var: bool,
     #[serde(default)]
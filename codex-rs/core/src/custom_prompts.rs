@@ -1,4 +1,5 @@
 use codex_protocol::custom_prompts::CustomPrompt;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
@@ -145,6 +146,129 @@ fn parse_frontmatter(content: &str) -> (Option<String>, Option<String>, String)
     (desc, hint, body)
 }
 
+/// Placeholder that stands for all positional arguments, joined by spaces.
+pub const POSITIONAL_ARGUMENTS: &str = "ARGUMENTS";
+
+/// Returns the named placeholders (`$NAME`: upper-case letters, digits and
+/// underscores) in a prompt template, without the leading `$`, de-duplicated
+/// and in order of first appearance. `$ARGUMENTS` and escaped `$$NAME`s are
+/// not included.
+pub fn prompt_argument_names(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (_, placeholder) in scan_placeholders(content) {
+        if let Some(Placeholder::Named(name)) = placeholder
+            && name != POSITIONAL_ARGUMENTS
+            && !names.contains(&name)
+        {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Whether a prompt template uses positional placeholders (`$1`..`$9` or
+/// `$ARGUMENTS`).
+pub fn prompt_has_numeric_placeholders(content: &str) -> bool {
+    scan_placeholders(content)
+        .into_iter()
+        .any(|(_, placeholder)| match placeholder {
+            Some(Placeholder::Positional(_)) => true,
+            Some(Placeholder::Named(name)) => name == POSITIONAL_ARGUMENTS,
+            None => false,
+        })
+}
+
+/// Expands the placeholders in a prompt template: `$NAME` with `named`,
+/// `$1`..`$9` with `positional` (empty when not given) and `$ARGUMENTS` with
+/// all of `positional` joined by spaces. Without `positional`, `$1`..`$9` and
+/// `$ARGUMENTS` are kept verbatim, like `$$` escapes always are.
+///
+/// Returns the named placeholders without a value, in order of first
+/// appearance, as the error.
+pub fn expand_prompt(
+    content: &str,
+    named: &HashMap<String, String>,
+    positional: Option<&[String]>,
+) -> Result<String, Vec<String>> {
+    let mut missing: Vec<String> = Vec::new();
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for (range, placeholder) in scan_placeholders(content) {
+        out.push_str(&content[last..range.start]);
+        last = range.end;
+        match placeholder {
+            None => out.push_str(&content[range]),
+            Some(Placeholder::Positional(idx)) => match positional {
+                Some(positional) => {
+                    out.push_str(positional.get(idx).map(String::as_str).unwrap_or(""));
+                }
+                None => out.push_str(&content[range]),
+            },
+            Some(Placeholder::Named(name)) if name == POSITIONAL_ARGUMENTS => match positional {
+                Some(positional) => out.push_str(&positional.join(" ")),
+                None => out.push_str(&content[range]),
+            },
+            Some(Placeholder::Named(name)) => match named.get(&name) {
+                Some(value) => out.push_str(value),
+                None => {
+                    if !missing.contains(&name) {
+                        missing.push(name);
+                    }
+                }
+            },
+        }
+    }
+    out.push_str(&content[last..]);
+
+    if missing.is_empty() {
+        Ok(out)
+    } else {
+        Err(missing)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Placeholder {
+    Named(String),
+    /// Zero-based index of `$1`..`$9`.
+    Positional(usize),
+}
+
+/// Finds the placeholders in a prompt template: their byte range and what
+/// they refer to. `$$` escapes are reported with `None`.
+fn scan_placeholders(content: &str) -> Vec<(std::ops::Range<usize>, Option<Placeholder>)> {
+    let bytes = content.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'$' {
+            i += 1;
+            continue;
+        }
+        match bytes.get(i + 1) {
+            Some(b'$') => {
+                out.push((i..i + 2, None));
+                i += 2;
+            }
+            Some(d) if (b'1'..=b'9').contains(d) => {
+                out.push((i..i + 2, Some(Placeholder::Positional((d - b'1') as usize))));
+                i += 2;
+            }
+            Some(c) if c.is_ascii_uppercase() => {
+                let end = bytes[i + 1..]
+                    .iter()
+                    .position(|b| !(b.is_ascii_uppercase() || b.is_ascii_digit() || *b == b'_'))
+                    .map_or(bytes.len(), |len| i + 1 + len);
+                let name = content[i + 1..end].to_string();
+                out.push((i..end, Some(Placeholder::Named(name))));
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,4 +347,48 @@ mod tests {
         assert_eq!(hint.as_deref(), Some("[arg]"));
         assert_eq!(body, "First line\r\nSecond line\r\n");
     }
+
+    #[test]
+    fn argument_names_skip_escapes_and_positional() {
+        assert_eq!(
+            prompt_argument_names("Review $FILE for $FOCUS, $FILE again, $1 $ARGUMENTS $$HOME"),
+            vec!["FILE".to_string(), "FOCUS".to_string()]
+        );
+        assert!(prompt_has_numeric_placeholders("Fix $1"));
+        assert!(prompt_has_numeric_placeholders("Fix $ARGUMENTS"));
+        assert!(!prompt_has_numeric_placeholders(
+            "Fix $$1 and $ARGUMENTS_LIST"
+        ));
+    }
+
+    #[test]
+    fn expands_named_and_positional_placeholders() {
+        let named = HashMap::from([("FILE".to_string(), "main.rs".to_string())]);
+        let positional = vec!["error handling".to_string(), "tests".to_string()];
+        assert_eq!(
+            expand_prompt(
+                "Review $FILE: $1 / $ARGUMENTS / [$3] ($$HOME)",
+                &named,
+                Some(positional.as_slice())
+            ),
+            Ok("Review main.rs: error handling / error handling tests / [] ($$HOME)".to_string())
+        );
+    }
+
+    #[test]
+    fn positional_placeholders_are_kept_without_positional_arguments() {
+        let named = HashMap::from([("FILE".to_string(), "main.rs".to_string())]);
+        assert_eq!(
+            expand_prompt("Review $FILE: $1 / $ARGUMENTS", &named, None),
+            Ok("Review main.rs: $1 / $ARGUMENTS".to_string())
+        );
+    }
+
+    #[test]
+    fn expansion_reports_missing_named_arguments() {
+        assert_eq!(
+            expand_prompt("Review $USER on $BRANCH by $USER", &HashMap::new(), None),
+            Err(vec!["USER".to_string(), "BRANCH".to_string()])
+        );
+    }
 }
@@ -0,0 +1,559 @@
+//! `apply_patch` in dry-run mode (`codex exec --dry-run`).
+//!
+//! Patches are applied to an in-memory overlay of the workspace instead of
+//! the files on disk. Later patches and `read_file` calls see the pending
+//! edits, and after every patch the accumulated edits are reported as a single
+//! [`TurnDiffEvent`] that `git apply` accepts, including mode changes and
+//! binary files. Shell commands (which `codex exec --dry-run` runs in a
+//! read-only sandbox) and the `grep_files` and `list_dir` tools still see the
+//! files as they are on disk.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_apply_patch::AffectedPaths;
+use codex_apply_patch::ApplyPatchArgs;
+use codex_apply_patch::Hunk;
+use codex_apply_patch::apply_chunks_to_contents;
+use codex_apply_patch::print_summary;
+
+use crate::codex::Session;
+use crate::function_tool::FunctionCallError;
+use crate::git_info::get_git_repo_root;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::FileChange;
+use crate::protocol::PatchApplyBeginEvent;
+use crate::protocol::PatchApplyEndEvent;
+use crate::protocol::TurnDiffEvent;
+use crate::turn_diff_tracker::ZERO_OID;
+use crate::turn_diff_tracker::diff_stats;
+use crate::turn_diff_tracker::git_blob_sha1_hex_bytes;
+
+const DEV_NULL: &str = "/dev/null";
+
+/// Git's alphabet for the base85 data of binary patches.
+const BASE85_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+#[derive(Debug, Clone, PartialEq)]
+struct OverlayFile {
+    /// Contents on disk when the file was first patched, `None` if it did not
+    /// exist.
+    original: Option<Vec<u8>>,
+    original_executable: bool,
+    /// Contents after the pending edits, `None` if a patch deleted the file.
+    pending: Option<Vec<u8>>,
+    pending_executable: bool,
+}
+
+/// Files touched by patches during a dry run.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DryRunOverlay {
+    files: BTreeMap<PathBuf, OverlayFile>,
+}
+
+impl DryRunOverlay {
+    /// The pending contents of `path` if a patch touched it: `Some(None)` when
+    /// a patch deleted the file.
+    pub(crate) fn pending(&self, path: &Path) -> Option<Option<&[u8]>> {
+        self.files.get(path).map(|file| file.pending.as_deref())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.pending(path) {
+            Some(Some(contents)) => Ok(contents.to_vec()),
+            Some(None) => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} was deleted by an earlier patch", path.display()),
+            )),
+            None => std::fs::read(path),
+        }
+    }
+
+    /// Whether `path` is executable once the pending edits are applied.
+    fn is_executable(&self, path: &Path) -> bool {
+        match self.files.get(path) {
+            Some(file) => file.pending_executable,
+            None => is_executable_on_disk(path),
+        }
+    }
+
+    /// Set the pending contents of `path`; `executable` is `None` to keep the
+    /// current mode.
+    fn write(&mut self, path: PathBuf, contents: Option<Vec<u8>>, executable: Option<bool>) {
+        if let Some(file) = self.files.get_mut(&path) {
+            file.pending = contents;
+            if let Some(executable) = executable {
+                file.pending_executable = executable;
+            }
+            return;
+        }
+        let original = std::fs::read(&path).ok();
+        let original_executable = original.is_some() && is_executable_on_disk(&path);
+        self.files.insert(
+            path,
+            OverlayFile {
+                original,
+                original_executable,
+                pending: contents,
+                pending_executable: executable.unwrap_or(original_executable),
+            },
+        );
+    }
+
+    /// Apply `hunks` (with paths relative to `cwd`). Nothing is changed if any
+    /// hunk fails.
+    fn apply(&mut self, hunks: &[Hunk], cwd: &Path) -> anyhow::Result<AffectedPaths> {
+        let mut next = self.clone();
+        let mut affected = AffectedPaths {
+            added: Vec::new(),
+            modified: Vec::new(),
+            deleted: Vec::new(),
+        };
+        for hunk in hunks {
+            let path = hunk.resolve_path(cwd);
+            match hunk {
+                Hunk::AddFile {
+                    contents,
+                    executable,
+                    ..
+                } => {
+                    next.write(
+                        path.clone(),
+                        Some(contents.clone().into_bytes()),
+                        *executable,
+                    );
+                    affected.added.push(path);
+                }
+                Hunk::DeleteFile { .. } => {
+                    next.read(&path)?;
+                    next.write(path.clone(), None, None);
+                    affected.deleted.push(path);
+                }
+                Hunk::UpdateFile {
+                    move_path,
+                    chunks,
+                    executable,
+                    ..
+                } => {
+                    let contents = next.read(&path)?;
+                    let contents = if chunks.is_empty() {
+                        contents
+                    } else {
+                        let text = String::from_utf8(contents).map_err(|_| {
+                            anyhow::anyhow!("{} is not a text file", path.display())
+                        })?;
+                        apply_chunks_to_contents(&text, &path, chunks)?.into_bytes()
+                    };
+                    let executable = executable.unwrap_or_else(|| next.is_executable(&path));
+                    match move_path {
+                        Some(dest) => {
+                            let dest = cwd.join(dest);
+                            next.write(path, None, None);
+                            next.write(dest.clone(), Some(contents), Some(executable));
+                            affected.modified.push(dest);
+                        }
+                        None => {
+                            next.write(path.clone(), Some(contents), Some(executable));
+                            affected.modified.push(path);
+                        }
+                    }
+                }
+                Hunk::CopyFile { copy_path, .. } => {
+                    let contents = next.read(&path)?;
+                    let executable = next.is_executable(&path);
+                    let dest = cwd.join(copy_path);
+                    next.write(dest.clone(), Some(contents), Some(executable));
+                    affected.added.push(dest);
+                }
+                Hunk::BinaryFile {
+                    contents,
+                    executable,
+                    ..
+                } => {
+                    let existed = next.read(&path).is_ok();
+                    next.write(path.clone(), Some(contents.clone()), *executable);
+                    if existed {
+                        affected.modified.push(path);
+                    } else {
+                        affected.added.push(path);
+                    }
+                }
+            }
+        }
+        *self = next;
+        Ok(affected)
+    }
+
+    /// All pending edits as a git-style unified diff with paths relative to
+    /// `root`.
+    pub(crate) fn unified_diff(&self, root: &Path) -> String {
+        let mut diff = String::new();
+        for (path, file) in &self.files {
+            let mode_changed = file.original.is_some()
+                && file.pending.is_some()
+                && file.original_executable != file.pending_executable;
+            if file.original == file.pending && !mode_changed {
+                continue;
+            }
+            let display = path.strip_prefix(root).unwrap_or(path).display();
+            let old_header = match file.original {
+                Some(_) => format!("a/{display}"),
+                None => DEV_NULL.to_string(),
+            };
+            let new_header = match file.pending {
+                Some(_) => format!("b/{display}"),
+                None => DEV_NULL.to_string(),
+            };
+            let old_mode = git_mode(file.original_executable);
+            let new_mode = git_mode(file.pending_executable);
+            diff.push_str(&format!("diff --git a/{display} b/{display}\n"));
+            match (&file.original, &file.pending) {
+                (None, _) => diff.push_str(&format!("new file mode {new_mode}\n")),
+                (_, None) => diff.push_str(&format!("deleted file mode {old_mode}\n")),
+                _ if mode_changed => {
+                    diff.push_str(&format!("old mode {old_mode}\nnew mode {new_mode}\n"));
+                }
+                _ => {}
+            }
+            if file.original == file.pending {
+                continue;
+            }
+            let old_text = text(file.original.as_deref());
+            let new_text = text(file.pending.as_deref());
+            match (old_text, new_text) {
+                (Some(old), Some(new)) => diff.push_str(
+                    &similar::TextDiff::from_lines(old, new)
+                        .unified_diff()
+                        .context_radius(3)
+                        .header(&old_header, &new_header)
+                        .to_string(),
+                ),
+                _ => {
+                    // `git apply` only takes binary patches with full object
+                    // ids on the index line.
+                    let oid = |contents: Option<&[u8]>| match contents {
+                        Some(contents) => format!("{:x}", git_blob_sha1_hex_bytes(contents)),
+                        None => ZERO_OID.to_string(),
+                    };
+                    let pending = file.pending.as_deref().unwrap_or_default();
+                    diff.push_str(&format!(
+                        "index {}..{}\nGIT binary patch\nliteral {}\n{}\n",
+                        oid(file.original.as_deref()),
+                        oid(file.pending.as_deref()),
+                        pending.len(),
+                        base85_lines(&zlib_stored(pending)),
+                    ));
+                }
+            }
+        }
+        diff
+    }
+}
+
+fn git_mode(executable: bool) -> &'static str {
+    if executable { "100755" } else { "100644" }
+}
+
+#[cfg(unix)]
+fn is_executable_on_disk(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable_on_disk(_path: &Path) -> bool {
+    false
+}
+
+/// `data` wrapped in a zlib stream of uncompressed ("stored") deflate blocks,
+/// which is what a git binary patch carries.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(u8::from(blocks.peek().is_none()));
+        let len = block.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    out.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    out
+}
+
+/// Git's base85 encoding: lines of up to 52 bytes, each prefixed with its
+/// length (`A`-`Z` for 1-26, `a`-`z` for 27-52).
+fn base85_lines(data: &[u8]) -> String {
+    let mut out = String::new();
+    for line in data.chunks(52) {
+        let len = line.len() as u8;
+        out.push(char::from(if len <= 26 {
+            b'A' + len - 1
+        } else {
+            b'a' + len - 27
+        }));
+        for group in line.chunks(4) {
+            let mut word = [0u8; 4];
+            word[..group.len()].copy_from_slice(group);
+            let mut value = u32::from_be_bytes(word);
+            let mut encoded = [0u8; 5];
+            for slot in encoded.iter_mut().rev() {
+                *slot = BASE85_ALPHABET[(value % 85) as usize];
+                value /= 85;
+            }
+            out.extend(encoded.iter().map(|&c| char::from(c)));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Contents as text, treating a missing file as empty. `None` for binary
+/// contents.
+fn text(contents: Option<&[u8]>) -> Option<&str> {
+    match contents {
+        Some(contents) => std::str::from_utf8(contents).ok(),
+        None => Some(""),
+    }
+}
+
+fn file_change(before: Option<&[u8]>, after: Option<&[u8]>) -> Option<FileChange> {
+    let change = match (before, after) {
+        (None, None) => return None,
+        (_, Some(after)) if std::str::from_utf8(after).is_err() => FileChange::Binary {
+            size: after.len() as u64,
+            executable: None,
+        },
+        (None, Some(after)) => FileChange::Add {
+            content: String::from_utf8_lossy(after).into_owned(),
+            executable: None,
+        },
+        (Some(before), None) => FileChange::Delete {
+            content: String::from_utf8_lossy(before).into_owned(),
+        },
+        (Some(before), Some(after)) => FileChange::Update {
+            unified_diff: similar::TextDiff::from_lines(
+                String::from_utf8_lossy(before).as_ref(),
+                String::from_utf8_lossy(after).as_ref(),
+            )
+            .unified_diff()
+            .context_radius(1)
+            .to_string(),
+            move_path: None,
+            executable: None,
+        },
+    };
+    Some(change)
+}
+
+impl Session {
+    /// Apply a patch to the dry-run overlay and report it like a patch that
+    /// was applied on disk.
+    pub(crate) async fn apply_patch_dry_run(
+        &self,
+        sub_id: &str,
+        call_id: &str,
+        args: ApplyPatchArgs,
+        cwd: &Path,
+    ) -> Result<String, FunctionCallError> {
+        let Some(overlay) = &self.services.dry_run else {
+            return Err(FunctionCallError::RespondToModel(
+                "dry-run mode is not enabled".to_string(),
+            ));
+        };
+        let cwd = match &args.workdir {
+            Some(workdir) => cwd.join(workdir),
+            None => cwd.to_path_buf(),
+        };
+
+        let (changes, affected, unified_diff) = {
+            let mut overlay = overlay.lock().await;
+            let before = overlay.clone();
+            let affected = overlay.apply(&args.hunks, &cwd).map_err(|err| {
+                FunctionCallError::RespondToModel(format!("apply_patch verification failed: {err}"))
+            })?;
+            let changes: HashMap<PathBuf, FileChange> = affected
+                .added
+                .iter()
+                .chain(&affected.modified)
+                .chain(&affected.deleted)
+                .filter_map(|path| {
+                    let before = before.read(path).ok();
+                    let after = overlay.pending(path).flatten();
+                    file_change(before.as_deref(), after).map(|change| (path.clone(), change))
+                })
+                .collect();
+            let root = get_git_repo_root(&cwd).unwrap_or_else(|| cwd.clone());
+            (changes, affected, overlay.unified_diff(&root))
+        };
+
+        let mut stdout = Vec::new();
+        if let Err(err) = print_summary(&affected, &mut stdout) {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "apply_patch failed: {err}"
+            )));
+        }
+        let mut stdout = String::from_utf8_lossy(&stdout).into_owned();
+        stdout.push_str(
+            "Dry run: the changes are pending and were not written to disk; \
+             read_file sees them, but shell commands, grep_files and list_dir \
+             still see the original files.\n",
+        );
+
+        for msg in [
+            EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+                call_id: call_id.to_string(),
+                auto_approved: true,
+                changes,
+            }),
+            EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+                call_id: call_id.to_string(),
+                stdout: stdout.clone(),
+                stderr: String::new(),
+                success: true,
+                fuzzy_hunks: Vec::new(),
+            }),
+            // The pending changes span the session rather than one turn.
+            EventMsg::TurnDiff(TurnDiffEvent {
+                turn: 0,
+                files: diff_stats(&unified_diff),
+                unified_diff,
+            }),
+        ] {
+            self.send_event(Event {
+                id: sub_id.to_string(),
+                msg,
+            })
+            .await;
+        }
+        Ok(stdout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_apply_patch::parse_patch;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn apply(overlay: &mut DryRunOverlay, cwd: &Path, patch: &str) -> anyhow::Result<()> {
+        let args = parse_patch(&format!("*** Begin Patch\n{patch}\n*** End Patch"))?;
+        overlay.apply(&args.hunks, cwd).map(|_| ())
+    }
+
+    #[test]
+    fn pending_edits_stay_in_memory_and_accumulate() -> anyhow::Result<()> {
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        std::fs::write(root.join("a.txt"), "one\ntwo\n")?;
+        let mut overlay = DryRunOverlay::default();
+
+        apply(&mut overlay, root, "*** Update File: a.txt\n@@\n-two\n+2")?;
+        // The second patch is written against the pending contents.
+        apply(
+            &mut overlay,
+            root,
+            "*** Update File: a.txt\n@@\n-2\n+II\n*** Add File: b.txt\n+new",
+        )?;
+
+        assert_eq!(std::fs::read_to_string(root.join("a.txt"))?, "one\ntwo\n");
+        assert!(!root.join("b.txt").exists());
+        assert_eq!(
+            overlay.pending(&root.join("a.txt")),
+            Some(Some(b"one\nII\n".as_slice()))
+        );
+        assert_eq!(
+            overlay.unified_diff(root),
+            "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+II\n\
+             diff --git a/b.txt b/b.txt\nnew file mode 100644\n--- /dev/null\n+++ b/b.txt\n@@ -0,0 +1 @@\n+new\n"
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn diff_with_modes_and_binary_files_applies_with_git() -> anyhow::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        use std::process::Command;
+
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        std::fs::write(root.join("run.sh"), "echo hi\n")?;
+        let mut overlay = DryRunOverlay::default();
+
+        apply(
+            &mut overlay,
+            root,
+            "*** Update File: run.sh\n*** Mode: +x\n\
+             *** Add File: tool.sh\n*** Mode: +x\n+#!/bin/sh\n\
+             *** Binary File: blob.bin\n+AJ+Slv8=",
+        )?;
+        let diff = overlay.unified_diff(root);
+        assert!(
+            diff.contains("old mode 100644\nnew mode 100755\n"),
+            "{diff}"
+        );
+        assert!(diff.contains("new file mode 100755\n"), "{diff}");
+        assert!(diff.contains("GIT binary patch\nliteral 5\n"), "{diff}");
+
+        let git = |args: &[&str]| Command::new("git").args(args).current_dir(root).output();
+        let Ok(init) = git(&["init", "-q"]) else {
+            // Git is not installed.
+            return Ok(());
+        };
+        assert!(init.status.success());
+        std::fs::write(root.join("proposed.diff"), &diff)?;
+        let applied = git(&["apply", "proposed.diff"])?;
+        assert!(
+            applied.status.success(),
+            "{}",
+            String::from_utf8_lossy(&applied.stderr)
+        );
+
+        let mode = |name: &str| -> std::io::Result<u32> {
+            Ok(std::fs::metadata(root.join(name))?.permissions().mode() & 0o111)
+        };
+        assert_ne!(mode("run.sh")?, 0);
+        assert_ne!(mode("tool.sh")?, 0);
+        assert_eq!(
+            std::fs::read(root.join("blob.bin"))?,
+            [0, 159, 146, 150, 255]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn failing_patch_leaves_the_overlay_untouched() -> anyhow::Result<()> {
+        let dir = TempDir::new()?;
+        let root = dir.path();
+        std::fs::write(root.join("a.txt"), "one\n")?;
+        let mut overlay = DryRunOverlay::default();
+
+        let result = apply(
+            &mut overlay,
+            root,
+            "*** Delete File: a.txt\n*** Update File: missing.txt\n@@\n-x\n+y",
+        );
+
+        assert!(result.is_err());
+        assert_eq!(overlay.pending(&root.join("a.txt")), None);
+        assert_eq!(overlay.unified_diff(root), "");
+        Ok(())
+    }
+}
@@ -43,13 +43,13 @@ impl EnvironmentContext {
             approval_policy,
             sandbox_mode: match sandbox_policy {
                 Some(SandboxPolicy::DangerFullAccess) => Some(SandboxMode::DangerFullAccess),
-                Some(SandboxPolicy::ReadOnly) => Some(SandboxMode::ReadOnly),
+                Some(SandboxPolicy::ReadOnly { .. }) => Some(SandboxMode::ReadOnly),
                 Some(SandboxPolicy::WorkspaceWrite { .. }) => Some(SandboxMode::WorkspaceWrite),
                 None => None,
             },
             network_access: match sandbox_policy {
                 Some(SandboxPolicy::DangerFullAccess) => Some(NetworkAccess::Enabled),
-                Some(SandboxPolicy::ReadOnly) => Some(NetworkAccess::Restricted),
+                Some(SandboxPolicy::ReadOnly { .. }) => Some(NetworkAccess::Restricted),
                 Some(SandboxPolicy::WorkspaceWrite { network_access, .. }) => {
                     if network_access {
                         Some(NetworkAccess::Enabled)
@@ -183,7 +183,9 @@ mod tests {
     fn workspace_write_policy(writable_roots: Vec<&str>, network_access: bool) -> SandboxPolicy {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: writable_roots.into_iter().map(PathBuf::from).collect(),
+            deny_read: Vec::new(),
             network_access,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
         }
@@ -217,7 +219,7 @@ mod tests {
         let context = EnvironmentContext::new(
             None,
             Some(AskForApproval::Never),
-            Some(SandboxPolicy::ReadOnly),
+            Some(SandboxPolicy::new_read_only_policy()),
             None,
         );
 
@@ -1,4 +1,5 @@
 use crate::exec::ExecToolCallOutput;
+use crate::resource_limits::ResourceLimitKind;
 use crate::token_data::KnownPlan;
 use crate::token_data::PlanType;
 use crate::truncate::truncate_middle;
@@ -39,6 +40,13 @@ pub enum SandboxErr {
     #[error("command timed out")]
     Timeout { output: Box<ExecToolCallOutput> },
 
+    /// Command exceeded one of the configured resource limits
+    #[error("command exceeded the {limit} limit")]
+    ResourceLimit {
+        limit: ResourceLimitKind,
+        output: Box<ExecToolCallOutput>,
+    },
+
     /// Command was killed by a signal
     #[error("command was killed by a signal")]
     Signal(i32),
@@ -311,7 +319,7 @@ pub fn get_error_message_ui(e: &CodexErr) -> String {
     let message = match e {
         CodexErr::Sandbox(SandboxErr::Denied { output }) => {
             let aggregated = output.aggregated_output.text.trim();
-            if !aggregated.is_empty() {
+            let message = if !aggregated.is_empty() {
                 output.aggregated_output.text.clone()
             } else {
                 let stderr = output.stderr.text.trim();
@@ -325,6 +333,17 @@ pub fn get_error_message_ui(e: &CodexErr) -> String {
                         output.exit_code
                     ),
                 }
+            };
+            if output.sandbox_violations.is_empty() {
+                message
+            } else {
+                let violations = output
+                    .sandbox_violations
+                    .iter()
+                    .map(|violation| format!("sandbox denied {violation}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("{}\n{violations}", message.trim_end())
             }
         }
         // Timeouts are not sandbox errors from a UX perspective; present them plainly
@@ -334,6 +353,9 @@ pub fn get_error_message_ui(e: &CodexErr) -> String {
                 output.duration.as_millis()
             )
         }
+        CodexErr::Sandbox(SandboxErr::ResourceLimit { limit, .. }) => {
+            format!("error: command exceeded the {limit} limit")
+        }
         _ => e.to_string(),
     };
 
@@ -345,7 +367,9 @@ mod tests {
     use super::*;
     use crate::exec::StreamOutput;
     use codex_protocol::protocol::RateLimitWindow;
+    use codex_protocol::protocol::SandboxViolation;
     use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
 
     fn rate_limit_snapshot() -> RateLimitSnapshot {
         RateLimitSnapshot {
@@ -384,6 +408,7 @@ mod tests {
             aggregated_output: StreamOutput::new("aggregate detail".to_string()),
             duration: Duration::from_millis(10),
             timed_out: false,
+            sandbox_violations: Vec::new(),
         };
         let err = CodexErr::Sandbox(SandboxErr::Denied {
             output: Box::new(output),
@@ -400,6 +425,7 @@ mod tests {
             aggregated_output: StreamOutput::new(String::new()),
             duration: Duration::from_millis(10),
             timed_out: false,
+            sandbox_violations: Vec::new(),
         };
         let err = CodexErr::Sandbox(SandboxErr::Denied {
             output: Box::new(output),
@@ -416,6 +442,7 @@ mod tests {
             aggregated_output: StreamOutput::new(String::new()),
             duration: Duration::from_millis(8),
             timed_out: false,
+            sandbox_violations: Vec::new(),
         };
         let err = CodexErr::Sandbox(SandboxErr::Denied {
             output: Box::new(output),
@@ -432,6 +459,7 @@ mod tests {
             aggregated_output: StreamOutput::new(String::new()),
             duration: Duration::from_millis(5),
             timed_out: false,
+            sandbox_violations: Vec::new(),
         };
         let err = CodexErr::Sandbox(SandboxErr::Denied {
             output: Box::new(output),
@@ -442,6 +470,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn sandbox_denied_lists_reported_violations() {
+        let output = ExecToolCallOutput {
+            exit_code: 1,
+            stdout: StreamOutput::new(String::new()),
+            stderr: StreamOutput::new(
+                "touch: cannot touch '/etc/foo': Permission denied\n".to_string(),
+            ),
+            aggregated_output: StreamOutput::new(String::new()),
+            duration: Duration::from_millis(5),
+            timed_out: false,
+            sandbox_violations: vec![
+                SandboxViolation::FileWrite {
+                    path: PathBuf::from("/etc/foo"),
+                    syscall: "openat".to_string(),
+                },
+                SandboxViolation::Syscall {
+                    syscall: "connect".to_string(),
+                },
+            ],
+        };
+        let err = CodexErr::Sandbox(SandboxErr::Denied {
+            output: Box::new(output),
+        });
+        assert_eq!(
+            get_error_message_ui(&err),
+            "touch: cannot touch '/etc/foo': Permission denied\n\
+             sandbox denied openat: writing to /etc/foo is not allowed\n\
+             sandbox denied connect: blocked by the sandbox"
+        );
+    }
+
     #[test]
     fn usage_limit_reached_error_formats_free_plan() {
         let err = UsageLimitReachedError {
@@ -15,15 +15,20 @@ use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
 
+use crate::config_types::LinuxSandboxBackend;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
+use crate::landlock::read_violation_report;
 use crate::landlock::spawn_command_under_linux_sandbox;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
 use crate::protocol::ExecOutputStream;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::SandboxViolation;
+use crate::resource_limits::ResourceLimits;
+use crate::resource_limits::exceeded_limit;
 use crate::seatbelt::spawn_command_under_seatbelt;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
@@ -70,6 +75,20 @@ pub enum SandboxType {
 
     /// Only available on Linux.
     LinuxSeccomp,
+
+    /// Only available on Linux. Runs the command in its own user, mount and
+    /// PID namespaces instead of under Landlock.
+    LinuxNamespaces,
+}
+
+impl SandboxType {
+    /// Whether the command runs under `codex-linux-sandbox`.
+    pub fn is_linux(self) -> bool {
+        matches!(
+            self,
+            SandboxType::LinuxSeccomp | SandboxType::LinuxNamespaces
+        )
+    }
 }
 
 #[derive(Clone)]
@@ -79,17 +98,23 @@ pub struct StdoutStream {
     pub tx_event: Sender<Event>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn process_exec_tool_call(
     params: ExecParams,
     sandbox_type: SandboxType,
     sandbox_policy: &SandboxPolicy,
     sandbox_cwd: &Path,
     codex_linux_sandbox_exe: &Option<PathBuf>,
+    resource_limits: &ResourceLimits,
+    report_denied_writes: bool,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
 
     let timeout_duration = params.timeout_duration();
+    #[allow(unused_mut)]
+    let mut oom_killed = false;
+    let mut sandbox_violations = Vec::new();
 
     let raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr> = match sandbox_type
     {
@@ -112,7 +137,7 @@ pub async fn process_exec_tool_call(
             .await?;
             consume_truncated_output(child, timeout_duration, stdout_stream.clone()).await
         }
-        SandboxType::LinuxSeccomp => {
+        SandboxType::LinuxSeccomp | SandboxType::LinuxNamespaces => {
             let ExecParams {
                 command,
                 cwd: command_cwd,
@@ -123,18 +148,43 @@ pub async fn process_exec_tool_call(
             let codex_linux_sandbox_exe = codex_linux_sandbox_exe
                 .as_ref()
                 .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+            // Where `codex-linux-sandbox` writes what the sandbox denied.
+            let violation_report = tempfile::NamedTempFile::new()
+                .map_err(|err| tracing::warn!("failed to create sandbox violation report: {err}"))
+                .ok();
             let child = spawn_command_under_linux_sandbox(
                 codex_linux_sandbox_exe,
                 command,
                 command_cwd,
                 sandbox_policy,
                 sandbox_cwd,
+                resource_limits,
+                if sandbox_type == SandboxType::LinuxNamespaces {
+                    LinuxSandboxBackend::Namespaces
+                } else {
+                    LinuxSandboxBackend::Landlock
+                },
+                violation_report.as_ref().map(tempfile::NamedTempFile::path),
+                report_denied_writes,
                 StdioPolicy::RedirectForShellTool,
                 env,
             )
             .await?;
+            let pid = child.id();
 
-            consume_truncated_output(child, timeout_duration, stdout_stream).await
+            let output = consume_truncated_output(child, timeout_duration, stdout_stream).await;
+            #[cfg(target_os = "linux")]
+            if resource_limits.memory_max_bytes.is_some()
+                && let Some(pid) = pid
+            {
+                oom_killed = crate::resource_limits::take_cgroup_oom_kill(pid);
+            }
+            #[cfg(not(target_os = "linux"))]
+            let _ = pid;
+            if let Some(report) = &violation_report {
+                sandbox_violations = read_violation_report(report.path());
+            }
+            output
         }
     };
     let duration = start.elapsed();
@@ -142,14 +192,16 @@ pub async fn process_exec_tool_call(
         Ok(raw_output) => {
             #[allow(unused_mut)]
             let mut timed_out = raw_output.timed_out;
+            #[allow(unused_mut)]
+            let mut signal = None;
 
             #[cfg(target_family = "unix")]
             {
-                if let Some(signal) = raw_output.exit_status.signal() {
-                    if signal == TIMEOUT_CODE {
+                if let Some(code) = raw_output.exit_status.signal() {
+                    if code == TIMEOUT_CODE {
                         timed_out = true;
                     } else {
-                        return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
+                        signal = Some(code);
                     }
                 }
             }
@@ -169,6 +221,7 @@ pub async fn process_exec_tool_call(
                 aggregated_output,
                 duration,
                 timed_out,
+                sandbox_violations,
             };
 
             if timed_out {
@@ -177,6 +230,22 @@ pub async fn process_exec_tool_call(
                 }));
             }
 
+            let limit = if sandbox_type.is_linux() {
+                exceeded_limit(resource_limits, signal, exit_code, oom_killed)
+            } else {
+                None
+            };
+            if let Some(limit) = limit {
+                return Err(CodexErr::Sandbox(SandboxErr::ResourceLimit {
+                    limit,
+                    output: Box::new(exec_output),
+                }));
+            }
+
+            if let Some(signal) = signal {
+                return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
+            }
+
             if is_likely_sandbox_denied(sandbox_type, &exec_output) {
                 return Err(CodexErr::Sandbox(SandboxErr::Denied {
                     output: Box::new(exec_output),
@@ -202,6 +271,11 @@ fn is_likely_sandbox_denied(sandbox_type: SandboxType, exec_output: &ExecToolCal
         return false;
     }
 
+    // The sandbox told us what it refused; no need to guess.
+    if !exec_output.sandbox_violations.is_empty() {
+        return true;
+    }
+
     // Quick rejects: well-known non-sandbox shell exit codes
     // 2: misuse of shell builtins
     // 126: permission denied
@@ -238,9 +312,7 @@ fn is_likely_sandbox_denied(sandbox_type: SandboxType, exec_output: &ExecToolCal
     #[cfg(unix)]
     {
         const SIGSYS_CODE: i32 = libc::SIGSYS;
-        if sandbox_type == SandboxType::LinuxSeccomp
-            && exec_output.exit_code == EXIT_CODE_SIGNAL_BASE + SIGSYS_CODE
-        {
+        if sandbox_type.is_linux() && exec_output.exit_code == EXIT_CODE_SIGNAL_BASE + SIGSYS_CODE {
             return true;
         }
     }
@@ -293,6 +365,8 @@ pub struct ExecToolCallOutput {
     pub aggregated_output: StreamOutput<String>,
     pub duration: Duration,
     pub timed_out: bool,
+    /// What the sandbox refused to let the command do, when it can tell.
+    pub sandbox_violations: Vec<SandboxViolation>,
 }
 
 async fn exec(
@@ -491,6 +565,7 @@ mod tests {
             aggregated_output: StreamOutput::new(aggregated.to_string()),
             duration: Duration::from_millis(1),
             timed_out: false,
+            sandbox_violations: Vec::new(),
         }
     }
 
@@ -545,4 +620,20 @@ mod tests {
         let output = make_exec_output(exit_code, "", "", "");
         assert!(is_likely_sandbox_denied(SandboxType::LinuxSeccomp, &output));
     }
+
+    #[test]
+    fn sandbox_detection_trusts_reported_violations() {
+        let mut output = make_exec_output(127, "", "", "");
+        output.sandbox_violations = vec![SandboxViolation::FileWrite {
+            path: PathBuf::from("/etc/hosts"),
+            syscall: "openat".to_string(),
+        }];
+        assert!(is_likely_sandbox_denied(SandboxType::LinuxSeccomp, &output));
+
+        output.exit_code = 0;
+        assert!(!is_likely_sandbox_denied(
+            SandboxType::LinuxSeccomp,
+            &output
+        ));
+    }
 }
@@ -0,0 +1,308 @@
+//! Persistent "always allow" rules for shell commands.
+//!
+//! Rules live in `$CODEX_HOME/approved_commands.toml` so users can inspect and
+//! edit them by hand (or through `codex approvals`). Each rule matches a
+//! command's argv element by element, optionally treating the elements as
+//! globs and optionally allowing extra trailing arguments. A rule with a
+//! `project` only applies to commands that run inside that directory:
+//!
+//! ```toml
+//! [[rule]]
+//! command = ["cargo", "test"]
+//! prefix = true
+//! project = "/home/me/code/my-crate"
+//!
+//! [[rule]]
+//! command = ["git", "log", "--oneline", "-n*"]
+//! glob = true
+//! ```
+
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::path::PathBuf;
+
+use fd_lock::RwLock;
+use serde::Deserialize;
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use wildmatch::WildMatch;
+
+pub const APPROVED_COMMANDS_FILE: &str = "approved_commands.toml";
+
+/// Held while the allowlist is read, modified and written back, so that
+/// concurrent Codex processes do not drop each other's changes.
+const APPROVED_COMMANDS_LOCK_FILE: &str = "approved_commands.toml.lock";
+
+/// The full set of persisted rules, in the order they were added.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandAllowlist {
+    #[serde(default, rename = "rule", skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<AllowRule>,
+}
+
+/// A single persisted approval.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllowRule {
+    /// argv to match against, one entry per argument.
+    pub command: Vec<String>,
+
+    /// When true, only the leading arguments have to match and the command
+    /// may carry any number of additional arguments.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prefix: bool,
+
+    /// When true, each entry of `command` is a glob where `*` matches any
+    /// sequence of characters and `?` matches a single character. Otherwise
+    /// entries are compared literally.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub glob: bool,
+
+    /// Restricts the rule to commands whose working directory is inside this
+    /// path. Rules without a project apply everywhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<PathBuf>,
+}
+
+impl AllowRule {
+    /// A rule that matches exactly `command` (no globbing, no extra args).
+    pub fn exact(command: Vec<String>, project: Option<PathBuf>) -> Self {
+        Self {
+            command,
+            prefix: false,
+            glob: false,
+            project,
+        }
+    }
+
+    pub fn matches(&self, command: &[String], cwd: &Path) -> bool {
+        if self.command.is_empty() {
+            return false;
+        }
+        if let Some(project) = &self.project
+            && !cwd.starts_with(project)
+        {
+            return false;
+        }
+        let arity_ok = if self.prefix {
+            command.len() >= self.command.len()
+        } else {
+            command.len() == self.command.len()
+        };
+        arity_ok
+            && self
+                .command
+                .iter()
+                .zip(command)
+                .all(|(pattern, arg)| self.element_matches(pattern, arg))
+    }
+
+    fn element_matches(&self, pattern: &str, arg: &str) -> bool {
+        if self.glob {
+            WildMatch::new(pattern).matches(arg)
+        } else {
+            pattern == arg
+        }
+    }
+}
+
+impl CommandAllowlist {
+    pub fn path(codex_home: &Path) -> PathBuf {
+        codex_home.join(APPROVED_COMMANDS_FILE)
+    }
+
+    /// Reads the allowlist from `codex_home`. A missing file yields an empty
+    /// allowlist.
+    pub fn load(codex_home: &Path) -> std::io::Result<Self> {
+        let contents = match std::fs::read_to_string(Self::path(codex_home)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        toml::from_str(&contents)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Atomically replaces the allowlist file in `codex_home`.
+    pub fn save(&self, codex_home: &Path) -> std::io::Result<()> {
+        let serialized = toml::to_string(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::create_dir_all(codex_home)?;
+        let tmp_file = NamedTempFile::new_in(codex_home)?;
+        std::fs::write(tmp_file.path(), serialized)?;
+        tmp_file
+            .persist(Self::path(codex_home))
+            .map_err(|err| err.error)?;
+        Ok(())
+    }
+
+    /// Loads the allowlist from `codex_home`, applies `edit` and writes the
+    /// result back if `edit` changed it, all under an exclusive file lock.
+    pub fn update<R>(codex_home: &Path, edit: impl FnOnce(&mut Self) -> R) -> std::io::Result<R> {
+        std::fs::create_dir_all(codex_home)?;
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(codex_home.join(APPROVED_COMMANDS_LOCK_FILE))?;
+        let mut lock = RwLock::new(lock_file);
+        let _guard = lock.write()?;
+
+        let original = Self::load(codex_home)?;
+        let mut allowlist = original.clone();
+        let result = edit(&mut allowlist);
+        if allowlist != original {
+            allowlist.save(codex_home)?;
+        }
+        Ok(result)
+    }
+
+    /// Appends `rule` unless an identical rule is already present. Returns
+    /// whether the allowlist changed.
+    pub fn add(&mut self, rule: AllowRule) -> bool {
+        if rule.command.is_empty() || self.rules.contains(&rule) {
+            return false;
+        }
+        self.rules.push(rule);
+        true
+    }
+
+    /// Removes the rule at `index` (zero-based, in listing order).
+    pub fn remove(&mut self, index: usize) -> Option<AllowRule> {
+        (index < self.rules.len()).then(|| self.rules.remove(index))
+    }
+
+    pub fn is_allowed(&self, command: &[String], cwd: &Path) -> bool {
+        self.rules.iter().any(|rule| rule.matches(command, cwd))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn vec_str(items: &[&str]) -> Vec<String> {
+        items.iter().map(std::string::ToString::to_string).collect()
+    }
+
+    #[test]
+    fn exact_rule_requires_identical_argv() {
+        let rule = AllowRule::exact(vec_str(&["cargo", "test"]), None);
+        let cwd = Path::new("/repo");
+        assert!(rule.matches(&vec_str(&["cargo", "test"]), cwd));
+        assert!(!rule.matches(&vec_str(&["cargo", "test", "--all"]), cwd));
+        assert!(!rule.matches(&vec_str(&["cargo"]), cwd));
+    }
+
+    #[test]
+    fn exact_rule_does_not_treat_stars_as_globs() {
+        let rule = AllowRule::exact(vec_str(&["bash", "-lc", "rm *.tmp"]), None);
+        let cwd = Path::new("/repo");
+        assert!(rule.matches(&vec_str(&["bash", "-lc", "rm *.tmp"]), cwd));
+        assert!(!rule.matches(&vec_str(&["bash", "-lc", "rm -rf /.tmp"]), cwd));
+    }
+
+    #[test]
+    fn prefix_and_glob_rules() {
+        let rule = AllowRule {
+            command: vec_str(&["git", "log", "-n*"]),
+            prefix: true,
+            glob: true,
+            project: None,
+        };
+        let cwd = Path::new("/repo");
+        assert!(rule.matches(&vec_str(&["git", "log", "-n5"]), cwd));
+        assert!(rule.matches(&vec_str(&["git", "log", "-n5", "--oneline"]), cwd));
+        assert!(!rule.matches(&vec_str(&["git", "log", "--stat"]), cwd));
+        assert!(!rule.matches(&vec_str(&["git", "log"]), cwd));
+    }
+
+    #[test]
+    fn project_rule_only_applies_inside_project() {
+        let rule = AllowRule::exact(vec_str(&["make"]), Some(PathBuf::from("/repo")));
+        assert!(rule.matches(&vec_str(&["make"]), Path::new("/repo")));
+        assert!(rule.matches(&vec_str(&["make"]), Path::new("/repo/sub")));
+        assert!(!rule.matches(&vec_str(&["make"]), Path::new("/other")));
+        assert!(!rule.matches(&vec_str(&["make"]), Path::new("/repository")));
+    }
+
+    #[test]
+    fn load_save_round_trip() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        assert_eq!(
+            CommandAllowlist::load(codex_home.path()).expect("load missing"),
+            CommandAllowlist::default()
+        );
+
+        let mut allowlist = CommandAllowlist::default();
+        assert!(allowlist.add(AllowRule::exact(
+            vec_str(&["cargo", "check"]),
+            Some(PathBuf::from("/repo")),
+        )));
+        assert!(!allowlist.add(AllowRule::exact(
+            vec_str(&["cargo", "check"]),
+            Some(PathBuf::from("/repo")),
+        )));
+        allowlist.add(AllowRule {
+            command: vec_str(&["ls"]),
+            prefix: true,
+            glob: false,
+            project: None,
+        });
+        allowlist.save(codex_home.path()).expect("save");
+
+        let loaded = CommandAllowlist::load(codex_home.path()).expect("load");
+        assert_eq!(loaded, allowlist);
+
+        let contents =
+            std::fs::read_to_string(CommandAllowlist::path(codex_home.path())).expect("read");
+        assert_eq!(
+            contents,
+            r#"[[rule]]
+command = ["cargo", "check"]
+project = "/repo"
+
+[[rule]]
+command = ["ls"]
+prefix = true
+"#
+        );
+    }
+
+    #[test]
+    fn update_saves_only_changes() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let path = CommandAllowlist::path(codex_home.path());
+
+        let added = CommandAllowlist::update(codex_home.path(), |allowlist| {
+            allowlist.add(AllowRule::exact(vec_str(&["make"]), None))
+        })
+        .expect("update");
+        assert!(added);
+        assert!(
+            CommandAllowlist::load(codex_home.path())
+                .expect("load")
+                .is_allowed(&vec_str(&["make"]), Path::new("/"))
+        );
+
+        std::fs::remove_file(&path).expect("remove");
+        let removed = CommandAllowlist::update(codex_home.path(), |allowlist| allowlist.remove(3))
+            .expect("update");
+        assert_eq!(removed, None);
+        assert!(!path.exists(), "unchanged allowlist should not be written");
+    }
+
+    #[test]
+    fn remove_by_index() {
+        let mut allowlist = CommandAllowlist::default();
+        allowlist.add(AllowRule::exact(vec_str(&["a"]), None));
+        allowlist.add(AllowRule::exact(vec_str(&["b"]), None));
+        assert_eq!(allowlist.remove(5), None);
+        assert_eq!(
+            allowlist.remove(0),
+            Some(AllowRule::exact(vec_str(&["a"]), None))
+        );
+        assert!(allowlist.is_allowed(&vec_str(&["b"]), Path::new("/")));
+        assert!(!allowlist.is_allowed(&vec_str(&["a"]), Path::new("/")));
+    }
+}
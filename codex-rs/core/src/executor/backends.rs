@@ -5,10 +5,24 @@ use async_trait::async_trait;
 
 use crate::CODEX_APPLY_PATCH_ARG1;
 use crate::apply_patch::ApplyPatchExec;
+use crate::config_types::LinuxSandboxBackend;
 use crate::exec::ExecParams;
+use crate::exec::SandboxType;
 use crate::executor::ExecutorConfig;
 use crate::function_tool::FunctionCallError;
 
+/// Maps the platform sandbox chosen for a command onto the Linux sandbox
+/// backend configured for the session. Sandbox selection only decides
+/// whether a command is sandboxed; the backend decides how.
+pub fn sandbox_type_for_backend(sandbox: SandboxType, backend: LinuxSandboxBackend) -> SandboxType {
+    match (sandbox, backend) {
+        (SandboxType::LinuxSeccomp, LinuxSandboxBackend::Namespaces) => {
+            SandboxType::LinuxNamespaces
+        }
+        (sandbox, _) => sandbox,
+    }
+}
+
 pub(crate) enum ExecutionMode {
     Shell,
     ApplyPatch(ApplyPatchExec),
@@ -1,15 +1,42 @@
 use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::SystemTime;
+
+use tracing::warn;
+
+use super::allowlist::AllowRule;
+use super::allowlist::CommandAllowlist;
 
 #[derive(Clone, Debug, Default)]
 /// Thread-safe store of user approvals so repeated commands can reuse
-/// previously granted trust.
+/// previously granted trust. Session approvals live in memory; "always allow"
+/// approvals are persisted to the [`CommandAllowlist`] in `codex_home`.
 pub(crate) struct ApprovalCache {
     inner: Arc<Mutex<HashSet<Vec<String>>>>,
+    codex_home: Option<PathBuf>,
+    allowlist: Arc<Mutex<Option<LoadedAllowlist>>>,
+}
+
+/// The persistent allowlist as last read, along with the modification time
+/// and size of the file it came from (`None` when there was no file).
+#[derive(Debug)]
+struct LoadedAllowlist {
+    stamp: Option<(SystemTime, u64)>,
+    allowlist: CommandAllowlist,
 }
 
 impl ApprovalCache {
+    pub(crate) fn with_allowlist(codex_home: PathBuf) -> Self {
+        Self {
+            inner: Arc::default(),
+            codex_home: Some(codex_home),
+            allowlist: Arc::default(),
+        }
+    }
+
     pub(crate) fn insert(&self, command: Vec<String>) {
         if command.is_empty() {
             return;
@@ -19,9 +46,66 @@ impl ApprovalCache {
         }
     }
 
+    /// Remembers `command` for this session and appends an exact rule for it,
+    /// scoped to `project`, to the persistent allowlist.
+    pub(crate) fn insert_persistent(&self, command: Vec<String>, project: PathBuf) {
+        self.insert(command.clone());
+        let Some(codex_home) = &self.codex_home else {
+            return;
+        };
+        let result = CommandAllowlist::update(codex_home, |allowlist| {
+            allowlist.add(AllowRule::exact(command, Some(project)))
+        });
+        if let Err(e) = result {
+            warn!("failed to persist command approval: {e}");
+        }
+    }
+
     pub(crate) fn snapshot(&self) -> HashSet<Vec<String>> {
         self.inner.lock().map(|g| g.clone()).unwrap_or_default()
     }
+
+    /// Like [`Self::snapshot`], but also includes `command` when a persistent
+    /// allowlist rule covers it for `cwd`. The allowlist is read again
+    /// whenever the file changes, so edits made through `codex approvals`
+    /// apply immediately.
+    pub(crate) fn snapshot_for(&self, command: &[String], cwd: &Path) -> HashSet<Vec<String>> {
+        let mut snapshot = self.snapshot();
+        if let Some(codex_home) = &self.codex_home
+            && self.allowlist_allows(codex_home, command, cwd)
+        {
+            snapshot.insert(command.to_vec());
+        }
+        snapshot
+    }
+
+    fn allowlist_allows(&self, codex_home: &Path, command: &[String], cwd: &Path) -> bool {
+        let stamp = match std::fs::metadata(CommandAllowlist::path(codex_home))
+            .and_then(|metadata| Ok((metadata.modified()?, metadata.len())))
+        {
+            Ok(stamp) => Some(stamp),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                warn!("failed to load command allowlist: {e}");
+                return false;
+            }
+        };
+        let Ok(mut loaded) = self.allowlist.lock() else {
+            return false;
+        };
+        if loaded.as_ref().is_none_or(|loaded| loaded.stamp != stamp) {
+            match CommandAllowlist::load(codex_home) {
+                Ok(allowlist) => *loaded = Some(LoadedAllowlist { stamp, allowlist }),
+                Err(e) => {
+                    warn!("failed to load command allowlist: {e}");
+                    return false;
+                }
+            }
+        }
+        loaded
+            .as_ref()
+            .is_some_and(|loaded| loaded.allowlist.is_allowed(command, cwd))
+    }
 }
 
 #[cfg(test)]
@@ -48,4 +132,42 @@ mod tests {
         let snap2 = cache.snapshot();
         assert_eq!(snap1, snap2);
     }
+
+    #[test]
+    fn persistent_approvals_survive_new_cache() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let project = codex_home.path().join("project");
+        let cmd = vec!["cargo".to_string(), "test".to_string()];
+
+        let cache = ApprovalCache::with_allowlist(codex_home.path().to_path_buf());
+        cache.insert_persistent(cmd.clone(), project.clone());
+        assert!(cache.snapshot().contains(&cmd));
+
+        let fresh = ApprovalCache::with_allowlist(codex_home.path().to_path_buf());
+        assert!(fresh.snapshot().is_empty());
+        assert!(
+            fresh
+                .snapshot_for(&cmd, &project.join("src"))
+                .contains(&cmd)
+        );
+        assert!(!fresh.snapshot_for(&cmd, codex_home.path()).contains(&cmd));
+    }
+
+    #[test]
+    fn allowlist_edits_are_picked_up() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let cmd = vec!["make".to_string()];
+        let cache = ApprovalCache::with_allowlist(codex_home.path().to_path_buf());
+        assert!(!cache.snapshot_for(&cmd, codex_home.path()).contains(&cmd));
+
+        CommandAllowlist::update(codex_home.path(), |allowlist| {
+            allowlist.add(AllowRule::exact(cmd.clone(), None))
+        })
+        .expect("update");
+        assert!(cache.snapshot_for(&cmd, codex_home.path()).contains(&cmd));
+
+        CommandAllowlist::update(codex_home.path(), |allowlist| allowlist.remove(0))
+            .expect("update");
+        assert!(!cache.snapshot_for(&cmd, codex_home.path()).contains(&cmd));
+    }
 }
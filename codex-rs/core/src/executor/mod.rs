@@ -1,13 +1,16 @@
+pub mod allowlist;
 mod backends;
 mod cache;
 mod runner;
 mod sandbox;
 
 pub(crate) use backends::ExecutionMode;
+pub use backends::sandbox_type_for_backend;
 pub(crate) use runner::ExecutionRequest;
 pub(crate) use runner::Executor;
 pub(crate) use runner::ExecutorConfig;
 pub(crate) use runner::normalize_exec_result;
+pub(crate) use runner::sandbox_failure_message;
 
 pub(crate) mod linkers {
     use crate::exec::ExecParams;
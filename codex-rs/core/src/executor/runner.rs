@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::RwLock;
@@ -5,8 +6,10 @@ use std::time::Duration;
 
 use super::backends::ExecutionMode;
 use super::backends::backend_for_mode;
+use super::backends::sandbox_type_for_backend;
 use super::cache::ApprovalCache;
 use crate::codex::Session;
+use crate::config_types::LinuxSandboxBackend;
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::error::get_error_message_ui;
@@ -19,11 +22,16 @@ use crate::exec::process_exec_tool_call;
 use crate::executor::errors::ExecError;
 use crate::executor::sandbox::select_sandbox;
 use crate::function_tool::FunctionCallError;
+use crate::git_info::get_git_repo_root;
+use crate::git_info::resolve_root_git_project_for_trust;
 use crate::protocol::AskForApproval;
 use crate::protocol::ReviewDecision;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::SandboxViolation;
+use crate::resource_limits::ResourceLimits;
 use crate::shell;
 use crate::tools::context::ExecCommandContext;
+use codex_execpolicy::Policy;
 use codex_otel::otel_event_manager::ToolDecisionSource;
 
 #[derive(Clone, Debug)]
@@ -31,6 +39,14 @@ pub(crate) struct ExecutorConfig {
     pub(crate) sandbox_policy: SandboxPolicy,
     pub(crate) sandbox_cwd: PathBuf,
     pub(crate) codex_exe: Option<PathBuf>,
+    pub(crate) exec_policy: Option<Arc<Policy>>,
+    pub(crate) resource_limits: ResourceLimits,
+    pub(crate) linux_sandbox_backend: LinuxSandboxBackend,
+    /// Whether the Linux sandbox reports the writes it denied.
+    pub(crate) report_denied_writes: bool,
+    /// Directories the user allowed writing to for the rest of the session,
+    /// on top of the writable roots of `sandbox_policy`.
+    pub(crate) extra_writable_roots: Vec<PathBuf>,
 }
 
 impl ExecutorConfig {
@@ -43,6 +59,57 @@ impl ExecutorConfig {
             sandbox_policy,
             sandbox_cwd,
             codex_exe,
+            exec_policy: None,
+            resource_limits: ResourceLimits::default(),
+            linux_sandbox_backend: LinuxSandboxBackend::default(),
+            report_denied_writes: false,
+            extra_writable_roots: Vec::new(),
+        }
+    }
+
+    /// Attaches the user's execpolicy so command approval can consult it.
+    pub(crate) fn with_exec_policy(mut self, exec_policy: Option<Arc<Policy>>) -> Self {
+        self.exec_policy = exec_policy;
+        self
+    }
+
+    /// Sets the resource limits applied to sandboxed commands.
+    pub(crate) fn with_resource_limits(mut self, resource_limits: ResourceLimits) -> Self {
+        self.resource_limits = resource_limits;
+        self
+    }
+
+    /// Selects how sandboxed commands are isolated on Linux.
+    pub(crate) fn with_linux_sandbox_backend(mut self, backend: LinuxSandboxBackend) -> Self {
+        self.linux_sandbox_backend = backend;
+        self
+    }
+
+    /// Makes the Linux sandbox report the writes it denied, so they can be
+    /// offered as writable roots.
+    pub(crate) fn with_report_denied_writes(mut self, report_denied_writes: bool) -> Self {
+        self.report_denied_writes = report_denied_writes;
+        self
+    }
+
+    /// Makes `roots` writable for every later command, including after the
+    /// sandbox policy is replaced.
+    fn add_writable_roots(&mut self, roots: &[PathBuf]) {
+        for root in roots {
+            if !self.extra_writable_roots.contains(root) {
+                self.extra_writable_roots.push(root.clone());
+            }
+        }
+        self.apply_extra_writable_roots();
+    }
+
+    fn apply_extra_writable_roots(&mut self) {
+        if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &mut self.sandbox_policy {
+            for root in &self.extra_writable_roots {
+                if !writable_roots.contains(root) {
+                    writable_roots.push(root.clone());
+                }
+            }
         }
     }
 }
@@ -62,12 +129,20 @@ impl Executor {
         }
     }
 
+    /// Backs the approval cache with the persistent command allowlist in
+    /// `codex_home`, enabling "always allow" decisions.
+    pub(crate) fn with_command_allowlist(mut self, codex_home: PathBuf) -> Self {
+        self.approval_cache = ApprovalCache::with_allowlist(codex_home);
+        self
+    }
+
     /// Updates the sandbox policy and working directory used for future
     /// executions without recreating the executor.
     pub(crate) fn update_environment(&self, sandbox_policy: SandboxPolicy, sandbox_cwd: PathBuf) {
         if let Ok(mut cfg) = self.config.write() {
             cfg.sandbox_policy = sandbox_policy;
             cfg.sandbox_cwd = sandbox_cwd;
+            cfg.apply_extra_writable_roots();
         }
     }
 
@@ -108,7 +183,8 @@ impl Executor {
         let sandbox_decision = select_sandbox(
             &request,
             approval_policy,
-            self.approval_cache.snapshot(),
+            self.approval_cache
+                .snapshot_for(&request.approval_command, &request.params.cwd),
             &config,
             session,
             &context.sub_id,
@@ -116,7 +192,9 @@ impl Executor {
             &context.otel_event_manager,
         )
         .await?;
-        if sandbox_decision.record_session_approval {
+        if sandbox_decision.record_persistent_approval {
+            self.record_persistent_approval(&request.approval_command, &config);
+        } else if sandbox_decision.record_session_approval {
             self.approval_cache.insert(request.approval_command.clone());
         }
 
@@ -136,6 +214,11 @@ impl Executor {
             Err(CodexErr::Sandbox(SandboxErr::Timeout { output })) => {
                 Err(CodexErr::Sandbox(SandboxErr::Timeout { output }).into())
             }
+            // Running without the sandbox would also drop the limits, so a
+            // command that hit one is not retried.
+            Err(CodexErr::Sandbox(SandboxErr::ResourceLimit { limit, output })) => {
+                Err(CodexErr::Sandbox(SandboxErr::ResourceLimit { limit, output }).into())
+            }
             Err(CodexErr::Sandbox(error)) => {
                 if sandbox_decision.escalate_on_failure {
                     self.retry_without_sandbox(
@@ -144,9 +227,14 @@ impl Executor {
                         session,
                         context,
                         stdout_stream,
+                        sandbox_decision.initial_sandbox,
                         error,
                     )
                     .await
+                } else if let SandboxErr::Denied { .. } = error {
+                    // Keep the output so the end event reports what the
+                    // sandbox denied.
+                    Err(CodexErr::Sandbox(error).into())
                 } else {
                     let message = sandbox_failure_message(error);
                     Err(ExecError::rejection(message))
@@ -157,7 +245,9 @@ impl Executor {
     }
 
     /// Fallback path invoked when a sandboxed run is denied so the user can
-    /// approve rerunning without isolation.
+    /// approve rerunning without isolation, or with the directories the
+    /// sandbox refused to write to added to the writable roots.
+    #[allow(clippy::too_many_arguments)]
     async fn retry_without_sandbox(
         &self,
         request: &ExecutionRequest,
@@ -165,6 +255,7 @@ impl Executor {
         session: &Session,
         context: &ExecCommandContext,
         stdout_stream: Option<StdoutStream>,
+        sandbox: SandboxType,
         sandbox_error: SandboxErr,
     ) -> Result<ExecToolCallOutput, ExecError> {
         session
@@ -173,13 +264,27 @@ impl Executor {
                 format!("Execution failed: {sandbox_error}"),
             )
             .await;
+        let suggested_writable_roots = suggested_writable_roots(&sandbox_error, config);
+        let reason = if suggested_writable_roots.is_empty() {
+            "command failed; retry without sandbox?".to_string()
+        } else {
+            let roots = suggested_writable_roots
+                .iter()
+                .map(|root| root.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "command failed; allow writing to {roots} in the sandbox, or retry without sandbox?"
+            )
+        };
         let decision = session
             .request_command_approval(
                 context.sub_id.to_string(),
                 context.call_id.to_string(),
                 request.approval_command.clone(),
                 request.params.cwd.clone(),
-                Some("command failed; retry without sandbox?".to_string()),
+                Some(reason),
+                suggested_writable_roots.clone(),
             )
             .await;
 
@@ -190,9 +295,17 @@ impl Executor {
             ToolDecisionSource::User,
         );
         match decision {
-            ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
-                if matches!(decision, ReviewDecision::ApprovedForSession) {
-                    self.approval_cache.insert(request.approval_command.clone());
+            ReviewDecision::Approved
+            | ReviewDecision::ApprovedForSession
+            | ReviewDecision::ApprovedAlways => {
+                match decision {
+                    ReviewDecision::ApprovedForSession => {
+                        self.approval_cache.insert(request.approval_command.clone());
+                    }
+                    ReviewDecision::ApprovedAlways => {
+                        self.record_persistent_approval(&request.approval_command, config);
+                    }
+                    _ => {}
                 }
                 session
                     .notify_background_event(&context.sub_id, "retrying command without sandbox")
@@ -209,12 +322,43 @@ impl Executor {
 
                 Ok(retry_output)
             }
-            ReviewDecision::Denied | ReviewDecision::Abort => {
-                Err(ExecError::rejection("exec command rejected by user"))
+            ReviewDecision::ApprovedWithWritableRoots if !suggested_writable_roots.is_empty() => {
+                let config = match self.config.write() {
+                    Ok(mut cfg) => {
+                        cfg.add_writable_roots(&suggested_writable_roots);
+                        cfg.clone()
+                    }
+                    Err(_) => return Err(ExecError::rejection("executor config poisoned")),
+                };
+                session
+                    .notify_background_event(
+                        &context.sub_id,
+                        "retrying command in the sandbox with the new writable roots",
+                    )
+                    .await;
+
+                let retry_output = self
+                    .spawn(request.params.clone(), sandbox, &config, stdout_stream)
+                    .await?;
+
+                Ok(retry_output)
             }
+            // Nothing to add was suggested, so there is nothing to approve.
+            ReviewDecision::ApprovedWithWritableRoots
+            | ReviewDecision::Denied
+            | ReviewDecision::Abort => Err(ExecError::rejection("exec command rejected by user")),
         }
     }
 
+    /// Persists an "always allow" approval for `command`, scoped to the
+    /// project (git root, or the session cwd outside of a repository).
+    fn record_persistent_approval(&self, command: &[String], config: &ExecutorConfig) {
+        let project = resolve_root_git_project_for_trust(&config.sandbox_cwd)
+            .unwrap_or_else(|| config.sandbox_cwd.clone());
+        self.approval_cache
+            .insert_persistent(command.to_vec(), project);
+    }
+
     async fn spawn(
         &self,
         params: ExecParams,
@@ -224,10 +368,12 @@ impl Executor {
     ) -> Result<ExecToolCallOutput, CodexErr> {
         process_exec_tool_call(
             params,
-            sandbox,
+            sandbox_type_for_backend(sandbox, config.linux_sandbox_backend),
             &config.sandbox_policy,
             &config.sandbox_cwd,
             &config.codex_exe,
+            &config.resource_limits,
+            config.report_denied_writes,
             stdout_stream,
         )
         .await
@@ -253,7 +399,53 @@ fn maybe_translate_shell_command(
     params
 }
 
-fn sandbox_failure_message(error: SandboxErr) -> String {
+/// Directories that would have let the command's denied writes succeed: the
+/// parents of the paths it tried to create or modify. Only workspace-write
+/// policies have writable roots to extend, and only directories inside the
+/// workspace are offered, so a failing command never gets to suggest `$HOME`,
+/// `/etc` or a read-denied credential directory.
+fn suggested_writable_roots(error: &SandboxErr, config: &ExecutorConfig) -> Vec<PathBuf> {
+    let SandboxErr::Denied { output } = error else {
+        return Vec::new();
+    };
+    if !matches!(config.sandbox_policy, SandboxPolicy::WorkspaceWrite { .. }) {
+        return Vec::new();
+    }
+    let workspace = workspace_root(&config.sandbox_cwd);
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for violation in &output.sandbox_violations {
+        let SandboxViolation::FileWrite { path, .. } = violation else {
+            continue;
+        };
+        let Some(parent) = path
+            .parent()
+            .filter(|parent| parent.starts_with(&workspace))
+        else {
+            continue;
+        };
+        if config
+            .sandbox_policy
+            .is_path_read_denied(parent, &config.sandbox_cwd)
+        {
+            continue;
+        }
+        if !roots.iter().any(|root| root == parent) {
+            roots.push(parent.to_path_buf());
+        }
+    }
+    roots
+}
+
+/// The Git repository containing `cwd`, or `cwd` itself outside a repository
+/// or when the repository would cover the home directory.
+fn workspace_root(cwd: &Path) -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    get_git_repo_root(cwd)
+        .filter(|root| !home.as_ref().is_some_and(|home| home.starts_with(root)))
+        .unwrap_or_else(|| cwd.to_path_buf())
+}
+
+pub(crate) fn sandbox_failure_message(error: SandboxErr) -> String {
     let codex_error = CodexErr::Sandbox(error);
     let friendly = get_error_message_ui(&codex_error);
     format!("failed in sandbox: {friendly}")
@@ -293,12 +485,14 @@ pub(crate) fn normalize_exec_result(
             borrowed: Some(output),
             synthetic: None,
         },
-        Err(ExecError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output }))) => {
-            NormalizedExecOutput {
-                borrowed: Some(output.as_ref()),
-                synthetic: None,
-            }
-        }
+        Err(ExecError::Codex(CodexErr::Sandbox(
+            SandboxErr::Timeout { output }
+            | SandboxErr::ResourceLimit { output, .. }
+            | SandboxErr::Denied { output },
+        ))) => NormalizedExecOutput {
+            borrowed: Some(output.as_ref()),
+            synthetic: None,
+        },
         Err(err) => {
             let message = match err {
                 ExecError::Function(FunctionCallError::RespondToModel(msg)) => msg.clone(),
@@ -312,6 +506,7 @@ pub(crate) fn normalize_exec_result(
                 aggregated_output: StreamOutput::new(message),
                 duration: Duration::default(),
                 timed_out: false,
+                sandbox_violations: Vec::new(),
             };
             NormalizedExecOutput {
                 borrowed: None,
@@ -338,6 +533,7 @@ mod tests {
             aggregated_output: StreamOutput::new(text.to_string()),
             duration: Duration::from_millis(123),
             timed_out: false,
+            sandbox_violations: Vec::new(),
         }
     }
 
@@ -372,6 +568,7 @@ mod tests {
             aggregated_output: StreamOutput::new(String::new()),
             duration: Duration::from_millis(10),
             timed_out: false,
+            sandbox_violations: Vec::new(),
         };
         let err = SandboxErr::Denied {
             output: Box::new(output),
@@ -389,6 +586,7 @@ mod tests {
             aggregated_output: StreamOutput::new("aggregate text".to_string()),
             duration: Duration::from_millis(10),
             timed_out: false,
+            sandbox_violations: Vec::new(),
         };
         let err = SandboxErr::Denied {
             output: Box::new(output),
@@ -397,6 +595,42 @@ mod tests {
         assert_eq!(message, "failed in sandbox: aggregate text");
     }
 
+    #[test]
+    fn suggested_writable_roots_stay_inside_the_workspace() {
+        let workspace = tempfile::tempdir().expect("tempdir");
+        let cwd = workspace.path().join("project");
+        let mut output = make_output("");
+        output.sandbox_violations = [
+            cwd.join("build/out.o"),
+            cwd.join("build/lib.a"),
+            workspace.path().join("sibling/file"),
+            PathBuf::from("/etc/hosts"),
+            PathBuf::from("/top-level-file"),
+        ]
+        .into_iter()
+        .map(|path| SandboxViolation::FileWrite {
+            path,
+            syscall: "openat".to_string(),
+        })
+        .collect();
+        let err = SandboxErr::Denied {
+            output: Box::new(output),
+        };
+
+        let cfg = ExecutorConfig::new(
+            SandboxPolicy::new_workspace_write_policy(),
+            cwd.clone(),
+            None,
+        );
+        assert_eq!(
+            suggested_writable_roots(&err, &cfg),
+            vec![cwd.join("build")]
+        );
+
+        let cfg = ExecutorConfig::new(SandboxPolicy::new_read_only_policy(), cwd, None);
+        assert_eq!(suggested_writable_roots(&err, &cfg), Vec::<PathBuf>::new());
+    }
+
     #[test]
     fn normalize_function_error_synthesizes_payload() {
         let err = FunctionCallError::RespondToModel("boom".to_string());
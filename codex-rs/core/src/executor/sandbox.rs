@@ -20,6 +20,7 @@ pub(crate) struct SandboxDecision {
     pub(crate) initial_sandbox: SandboxType,
     pub(crate) escalate_on_failure: bool,
     pub(crate) record_session_approval: bool,
+    pub(crate) record_persistent_approval: bool,
 }
 
 impl SandboxDecision {
@@ -28,6 +29,7 @@ impl SandboxDecision {
             initial_sandbox: sandbox,
             escalate_on_failure,
             record_session_approval: false,
+            record_persistent_approval: false,
         }
     }
 
@@ -36,6 +38,14 @@ impl SandboxDecision {
             initial_sandbox: SandboxType::None,
             escalate_on_failure: false,
             record_session_approval,
+            record_persistent_approval: false,
+        }
+    }
+
+    fn user_override_always() -> Self {
+        Self {
+            record_persistent_approval: true,
+            ..Self::user_override(true)
         }
     }
 }
@@ -106,6 +116,7 @@ async fn select_shell_sandbox(
         &config.sandbox_policy,
         &approved_snapshot,
         request.params.with_escalated_permissions.unwrap_or(false),
+        config.exec_policy.as_deref(),
     );
 
     match safety {
@@ -136,6 +147,7 @@ async fn select_shell_sandbox(
                     request.approval_command.clone(),
                     request.params.cwd.clone(),
                     request.params.justification.clone(),
+                    Vec::new(),
                 )
                 .await;
 
@@ -148,7 +160,12 @@ async fn select_shell_sandbox(
             match decision {
                 ReviewDecision::Approved => Ok(SandboxDecision::user_override(false)),
                 ReviewDecision::ApprovedForSession => Ok(SandboxDecision::user_override(true)),
-                ReviewDecision::Denied | ReviewDecision::Abort => {
+                ReviewDecision::ApprovedAlways => Ok(SandboxDecision::user_override_always()),
+                // No writable roots are suggested before a command runs, so
+                // there is nothing this narrower approval could grant.
+                ReviewDecision::ApprovedWithWritableRoots
+                | ReviewDecision::Denied
+                | ReviewDecision::Abort => {
                     Err(ExecError::rejection("exec command rejected by user"))
                 }
             }
@@ -207,7 +224,11 @@ mod tests {
             action,
             user_explicitly_approved_this_action: true,
         };
-        let cfg = ExecutorConfig::new(SandboxPolicy::ReadOnly, std::env::temp_dir(), None);
+        let cfg = ExecutorConfig::new(
+            SandboxPolicy::new_read_only_policy(),
+            std::env::temp_dir(),
+            None,
+        );
         let request = ExecutionRequest {
             params: ExecParams {
                 command: vec!["apply_patch".into()],
@@ -294,7 +315,11 @@ mod tests {
             action,
             user_explicitly_approved_this_action: false,
         };
-        let cfg = ExecutorConfig::new(SandboxPolicy::ReadOnly, std::env::temp_dir(), None);
+        let cfg = ExecutorConfig::new(
+            SandboxPolicy::new_read_only_policy(),
+            std::env::temp_dir(),
+            None,
+        );
         let request = ExecutionRequest {
             params: ExecParams {
                 command: vec!["apply_patch".into()],
@@ -369,7 +394,11 @@ mod tests {
     #[tokio::test]
     async fn select_shell_escalates_on_failure_with_platform_sandbox() {
         let (session, ctx) = make_session_and_context();
-        let cfg = ExecutorConfig::new(SandboxPolicy::ReadOnly, std::env::temp_dir(), None);
+        let cfg = ExecutorConfig::new(
+            SandboxPolicy::new_read_only_policy(),
+            std::env::temp_dir(),
+            None,
+        );
         let request = ExecutionRequest {
             params: ExecParams {
                 // Unknown command => untrusted but not flagged dangerous
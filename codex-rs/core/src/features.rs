@@ -43,6 +43,8 @@ pub enum Feature {
     WebSearchRequest,
     /// Automatically approve all approval requests from the harness.
     ApproveAll,
+    /// Snapshot the workspace before every turn so it can be undone.
+    GhostCommit,
 }
 
 impl Feature {
@@ -255,4 +257,10 @@ pub const FEATURES: &[FeatureSpec] = &[
         stage: Stage::Experimental,
         default_enabled: false,
     },
+    FeatureSpec {
+        id: Feature::GhostCommit,
+        key: "ghost_commit",
+        stage: Stage::Experimental,
+        default_enabled: false,
+    },
 ];
@@ -0,0 +1,144 @@
+//! The contents of files as the model last saw them.
+//!
+//! When the context of an `apply_patch` update no longer matches a file (for
+//! example because a formatter or the user changed it after the model read
+//! it), the patch is merged against the remembered version instead of being
+//! rejected outright. See
+//! [`codex_apply_patch::maybe_parse_apply_patch_verified_with_merge_base`].
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::parse_command::ParsedCommand;
+
+use crate::codex::Session;
+use crate::parse_command::read_command_path;
+
+/// Number of files remembered per session.
+const MAX_FILES: usize = 64;
+
+/// Larger files are not remembered.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Least recently read files are evicted first.
+#[derive(Debug, Default)]
+pub(crate) struct FileReads {
+    contents: HashMap<PathBuf, String>,
+    order: VecDeque<PathBuf>,
+}
+
+impl FileReads {
+    pub(crate) fn get(&self, path: &Path) -> Option<&str> {
+        self.contents.get(path).map(String::as_str)
+    }
+
+    fn insert(&mut self, path: PathBuf, contents: String) {
+        self.order.retain(|existing| existing != &path);
+        self.order.push_back(path.clone());
+        self.contents.insert(path, contents);
+        while self.order.len() > MAX_FILES {
+            if let Some(evicted) = self.order.pop_front() {
+                self.contents.remove(&evicted);
+            }
+        }
+    }
+
+    fn remove(&mut self, path: &Path) {
+        self.order.retain(|existing| existing != path);
+        self.contents.remove(path);
+    }
+}
+
+impl Session {
+    /// Remember the current contents of `paths` (absolute) as seen by the
+    /// model. Paths that are not readable text files are ignored.
+    pub(crate) async fn record_file_reads(&self, paths: impl IntoIterator<Item = PathBuf>) {
+        let mut read = Vec::new();
+        for path in paths {
+            let contents = match tokio::fs::metadata(&path).await {
+                Ok(metadata) if metadata.is_file() && metadata.len() <= MAX_FILE_BYTES => {
+                    tokio::fs::read_to_string(&path).await.ok()
+                }
+                // The model saw a version we cannot keep; forget the older one.
+                Ok(metadata) if metadata.is_file() => None,
+                _ => continue,
+            };
+            read.push((path, contents));
+        }
+
+        let mut file_reads = self.services.file_reads.lock().await;
+        for (path, contents) in read {
+            match contents {
+                Some(contents) => file_reads.insert(path, contents),
+                None => file_reads.remove(&path),
+            }
+        }
+    }
+}
+
+/// Files a shell command reads, as far as [`ParsedCommand::Read`] tells.
+pub(crate) fn shell_read_paths(parsed: &[ParsedCommand], cwd: &Path) -> Vec<PathBuf> {
+    parsed
+        .iter()
+        .filter_map(|command| match command {
+            ParsedCommand::Read { cmd, .. } => read_command_path(cmd),
+            _ => None,
+        })
+        .map(|path| cwd.join(path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn evicts_the_least_recently_read_file() {
+        let mut file_reads = FileReads::default();
+        for i in 0..MAX_FILES {
+            file_reads.insert(PathBuf::from(format!("/f{i}")), format!("{i}"));
+        }
+        // Reading a file again makes it the most recent one.
+        file_reads.insert(PathBuf::from("/f0"), "0 again".to_string());
+        file_reads.insert(PathBuf::from("/new"), "new".to_string());
+
+        assert_eq!(file_reads.get(Path::new("/f0")), Some("0 again"));
+        assert_eq!(file_reads.get(Path::new("/f1")), None);
+        assert_eq!(file_reads.get(Path::new("/new")), Some("new"));
+    }
+
+    #[test]
+    fn shell_read_paths_resolves_file_operands_against_cwd() {
+        let read = |cmd: &str| ParsedCommand::Read {
+            cmd: cmd.to_string(),
+            name: String::new(),
+        };
+        let parsed = vec![
+            read("sed -n 1,20p src/lib.rs"),
+            read("head -n 50 notes.txt"),
+            read("tail -n +10 log.txt"),
+            read("cat -- -strange-file-name"),
+            read("sed -n 5,9p src/main.rs | nl -ba"),
+            read("nl -s ': ' README.md"),
+            // The path would be relative to `sub`.
+            read("cd sub && cat a.txt"),
+            ParsedCommand::Unknown {
+                cmd: "cargo test".to_string(),
+            },
+        ];
+        assert_eq!(
+            shell_read_paths(&parsed, Path::new("/repo")),
+            vec![
+                PathBuf::from("/repo/src/lib.rs"),
+                PathBuf::from("/repo/notes.txt"),
+                PathBuf::from("/repo/log.txt"),
+                PathBuf::from("/repo/-strange-file-name"),
+                PathBuf::from("/repo/src/main.rs"),
+                PathBuf::from("/repo/README.md"),
+            ]
+        );
+    }
+}
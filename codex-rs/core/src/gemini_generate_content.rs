@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::ModelProviderInfo;
+use crate::chat_completions::send_streaming_request;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::ResponseStream;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::model_family::ModelFamily;
+use crate::openai_tools::create_tools_json_for_gemini_api;
+use crate::protocol::TokenUsage;
+use bytes::Bytes;
+use codex_otel::otel_event_manager::OtelEventManager;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ResponseItem;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::debug;
+use tracing::trace;
+
+/// Implementation for the Gemini generateContent API.
+pub(crate) async fn stream_gemini_generate_content(
+    prompt: &Prompt,
+    model_family: &ModelFamily,
+    client: &reqwest::Client,
+    provider: &ModelProviderInfo,
+    otel_event_manager: &OtelEventManager,
+) -> Result<ResponseStream> {
+    if prompt.output_schema.is_some() {
+        return Err(CodexErr::UnsupportedOperation(
+            "output_schema is not supported for the Gemini API".to_string(),
+        ));
+    }
+
+    let mut payload = json!({
+        "systemInstruction": {
+            "parts": [{ "text": prompt.get_full_instructions(model_family) }],
+        },
+        "contents": build_contents(&prompt.get_formatted_input()),
+    });
+    let mut generation_config = serde_json::Map::new();
+    if let Some(max_tokens) = prompt.max_output_tokens {
+        generation_config.insert("maxOutputTokens".to_string(), json!(max_tokens));
+    }
+    if let Some(temperature) = prompt.temperature {
+        generation_config.insert("temperature".to_string(), json!(temperature));
+    }
+    if !generation_config.is_empty()
+        && let Some(obj) = payload.as_object_mut()
+    {
+        obj.insert(
+            "generationConfig".to_string(),
+            Value::Object(generation_config),
+        );
+    }
+    let tools_json = create_tools_json_for_gemini_api(&prompt.tools)?;
+    if !tools_json.is_empty()
+        && let Some(obj) = payload.as_object_mut()
+    {
+        obj.insert("tools".to_string(), json!(tools_json));
+    }
+
+    debug!(
+        "POST to {}: {}",
+        provider.get_full_url(&None, &model_family.slug),
+        serde_json::to_string_pretty(&payload).unwrap_or_default()
+    );
+
+    let resp = send_streaming_request(
+        &payload,
+        &model_family.slug,
+        client,
+        provider,
+        otel_event_manager,
+    )
+    .await?;
+    let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+    let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
+    tokio::spawn(process_gemini_sse(
+        stream,
+        tx_event,
+        provider.stream_idle_timeout(),
+        otel_event_manager.clone(),
+    ));
+    Ok(ResponseStream { rx_event })
+}
+
+/// Converts the conversation into generateContent `contents`. Gemini matches
+/// function responses to calls by name, so the name of every call is
+/// remembered for its output. Consecutive parts of the same role are merged
+/// into one content entry.
+fn build_contents(input: &[ResponseItem]) -> Vec<Value> {
+    let mut contents: Vec<Value> = Vec::new();
+    let mut push = |role: &str, parts: Vec<Value>| {
+        if parts.is_empty() {
+            return;
+        }
+        if let Some(last) = contents.last_mut()
+            && last["role"] == role
+            && let Some(existing) = last["parts"].as_array_mut()
+        {
+            existing.extend(parts);
+            return;
+        }
+        contents.push(json!({ "role": role, "parts": parts }));
+    };
+    let mut call_names: HashMap<&str, &str> = HashMap::new();
+
+    for item in input {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let role = if role == "assistant" { "model" } else { "user" };
+                let parts = content
+                    .iter()
+                    .filter_map(|c| match c {
+                        ContentItem::InputText { text } | ContentItem::OutputText { text } => {
+                            (!text.is_empty()).then(|| json!({ "text": text }))
+                        }
+                        ContentItem::InputImage { image_url } => Some(image_part(image_url)),
+                    })
+                    .collect();
+                push(role, parts);
+            }
+            ResponseItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => {
+                call_names.insert(call_id, name);
+                let args = serde_json::from_str::<Value>(arguments)
+                    .ok()
+                    .filter(Value::is_object)
+                    .unwrap_or_else(|| json!({}));
+                push(
+                    "model",
+                    vec![json!({ "functionCall": { "name": name, "args": args } })],
+                );
+            }
+            ResponseItem::LocalShellCall {
+                id,
+                call_id,
+                action,
+                ..
+            } => {
+                if let Some(call_id) = call_id.as_deref().or(id.as_deref()) {
+                    call_names.insert(call_id, "local_shell");
+                }
+                push(
+                    "model",
+                    vec![json!({ "functionCall": { "name": "local_shell", "args": action } })],
+                );
+            }
+            ResponseItem::CustomToolCall {
+                call_id,
+                name,
+                input,
+                ..
+            } => {
+                call_names.insert(call_id, name);
+                push(
+                    "model",
+                    vec![json!({ "functionCall": { "name": name, "args": { "input": input } } })],
+                );
+            }
+            ResponseItem::FunctionCallOutput { call_id, output } => {
+                let name = call_names
+                    .get(call_id.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                push(
+                    "user",
+                    vec![json!({
+                        "functionResponse": {
+                            "name": name,
+                            "response": { "content": output.content },
+                        }
+                    })],
+                );
+            }
+            ResponseItem::CustomToolCallOutput { call_id, output } => {
+                let name = call_names
+                    .get(call_id.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                push(
+                    "user",
+                    vec![json!({
+                        "functionResponse": {
+                            "name": name,
+                            "response": { "content": output },
+                        }
+                    })],
+                );
+            }
+            ResponseItem::Reasoning { .. }
+            | ResponseItem::WebSearchCall { .. }
+            | ResponseItem::Other => {
+                // Omit these items from the conversation history.
+                continue;
+            }
+        }
+    }
+    contents
+}
+
+/// Images are sent inline when they are data URLs and by reference otherwise.
+fn image_part(image_url: &str) -> Value {
+    if let Some(rest) = image_url.strip_prefix("data:")
+        && let Some((mime_type, data)) = rest.split_once(";base64,")
+    {
+        return json!({ "inlineData": { "mimeType": mime_type, "data": data } });
+    }
+    json!({ "fileData": { "fileUri": image_url } })
+}
+
+/// SSE processor for the `streamGenerateContent?alt=sse` format. Each event
+/// is a partial `GenerateContentResponse`: text parts are streamed as deltas
+/// and emitted as one assistant message when the stream ends, while function
+/// calls arrive whole and are forwarded immediately.
+async fn process_gemini_sse<S>(
+    stream: S,
+    tx_event: mpsc::Sender<Result<ResponseEvent>>,
+    idle_timeout: Duration,
+    otel_event_manager: OtelEventManager,
+) where
+    S: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut stream = stream.eventsource();
+
+    let mut assistant_text = String::new();
+    let mut reasoning_text = String::new();
+    let mut response_id = String::new();
+    let mut token_usage: Option<TokenUsage> = None;
+    let mut finished = false;
+
+    loop {
+        let start = std::time::Instant::now();
+        let response = timeout(idle_timeout, stream.next()).await;
+        let duration = start.elapsed();
+        otel_event_manager.log_sse_event(&response, duration);
+
+        let sse = match response {
+            Ok(Some(Ok(ev))) => ev,
+            Ok(Some(Err(e))) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(e.to_string(), None)))
+                    .await;
+                return;
+            }
+            Ok(None) if finished => {
+                flush_text(&tx_event, &mut assistant_text, &mut reasoning_text).await;
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id,
+                        token_usage,
+                    }))
+                    .await;
+                return;
+            }
+            Ok(None) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "stream closed before finishReason".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = tx_event
+                    .send(Err(CodexErr::Stream(
+                        "idle timeout waiting for SSE".into(),
+                        None,
+                    )))
+                    .await;
+                return;
+            }
+        };
+
+        let chunk: Value = match serde_json::from_str(&sse.data) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        trace!("gemini received SSE chunk: {chunk:?}");
+
+        if let Some(error) = chunk.get("error") {
+            let message = error["message"]
+                .as_str()
+                .unwrap_or("unknown error")
+                .to_string();
+            let _ = tx_event.send(Err(CodexErr::Stream(message, None))).await;
+            return;
+        }
+
+        if response_id.is_empty()
+            && let Some(id) = chunk["responseId"].as_str()
+        {
+            response_id = id.to_string();
+            let _ = tx_event.send(Ok(ResponseEvent::Created)).await;
+        }
+
+        if let Some(usage) = chunk.get("usageMetadata") {
+            token_usage = Some(token_usage_from_metadata(usage));
+        }
+
+        let candidate = &chunk["candidates"][0];
+        for part in candidate["content"]["parts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            if let Some(text) = part["text"].as_str().filter(|t| !t.is_empty()) {
+                if part["thought"].as_bool() == Some(true) {
+                    reasoning_text.push_str(text);
+                    let _ = tx_event
+                        .send(Ok(ResponseEvent::ReasoningContentDelta(text.to_string())))
+                        .await;
+                } else {
+                    assistant_text.push_str(text);
+                    let _ = tx_event
+                        .send(Ok(ResponseEvent::OutputTextDelta(text.to_string())))
+                        .await;
+                }
+            }
+
+            if let Some(call) = part.get("functionCall") {
+                // Keep the item order the model produced.
+                flush_text(&tx_event, &mut assistant_text, &mut reasoning_text).await;
+                let call_id = call["id"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4()));
+                let item = ResponseItem::FunctionCall {
+                    id: None,
+                    name: call["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: call
+                        .get("args")
+                        .map_or_else(|| "{}".to_string(), Value::to_string),
+                    call_id,
+                };
+                let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+            }
+        }
+
+        if candidate.get("finishReason").is_some() {
+            finished = true;
+        }
+    }
+}
+
+/// Emits the reasoning and assistant text accumulated so far as complete
+/// items.
+async fn flush_text(
+    tx_event: &mpsc::Sender<Result<ResponseEvent>>,
+    assistant_text: &mut String,
+    reasoning_text: &mut String,
+) {
+    if !reasoning_text.is_empty() {
+        let item = ResponseItem::Reasoning {
+            id: String::new(),
+            summary: Vec::new(),
+            content: Some(vec![ReasoningItemContent::ReasoningText {
+                text: std::mem::take(reasoning_text),
+            }]),
+            encrypted_content: None,
+        };
+        let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+    }
+    if !assistant_text.is_empty() {
+        let item = ResponseItem::Message {
+            id: None,
+            role: "assistant".to_string(),
+            content: vec![ContentItem::OutputText {
+                text: std::mem::take(assistant_text),
+            }],
+        };
+        let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
+    }
+}
+
+/// Gemini reports thinking tokens separately from the candidate tokens, while
+/// [`TokenUsage::output_tokens`] includes reasoning.
+fn token_usage_from_metadata(usage: &Value) -> TokenUsage {
+    let count = |key: &str| usage[key].as_u64().unwrap_or(0);
+    let reasoning_output_tokens = count("thoughtsTokenCount");
+    let output_tokens = count("candidatesTokenCount") + reasoning_output_tokens;
+    let input_tokens = count("promptTokenCount");
+    TokenUsage {
+        input_tokens,
+        cached_input_tokens: count("cachedContentTokenCount"),
+        output_tokens,
+        reasoning_output_tokens,
+        total_tokens: usage["totalTokenCount"]
+            .as_u64()
+            .unwrap_or(input_tokens + output_tokens),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn function_responses_are_named_after_their_calls() {
+        let input = vec![
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![ContentItem::InputText {
+                    text: "list files".to_string(),
+                }],
+            },
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["ls"]}"#.to_string(),
+                call_id: "call_1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "call_1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "README.md".to_string(),
+                    success: Some(true),
+                },
+            },
+            ResponseItem::Message {
+                id: None,
+                role: "assistant".to_string(),
+                content: vec![ContentItem::OutputText {
+                    text: "There is a README.".to_string(),
+                }],
+            },
+        ];
+
+        assert_eq!(
+            Value::Array(build_contents(&input)),
+            json!([
+                { "role": "user", "parts": [{ "text": "list files" }] },
+                {
+                    "role": "model",
+                    "parts": [{ "functionCall": { "name": "shell", "args": { "command": ["ls"] } } }],
+                },
+                {
+                    "role": "user",
+                    "parts": [{
+                        "functionResponse": { "name": "shell", "response": { "content": "README.md" } },
+                    }],
+                },
+                { "role": "model", "parts": [{ "text": "There is a README." }] },
+            ])
+        );
+    }
+}
@@ -0,0 +1,473 @@
+//! Workspace snapshots taken before each turn so a session can be rolled back.
+//!
+//! Inside a Git repository snapshots are ghost commits (see
+//! [`codex_git_tooling`]) that are never referenced by a branch. Elsewhere they
+//! are kept in a content-addressed store under `CODEX_HOME` (see
+//! [`file_store`]) that only records the files the agent patches during the
+//! turn, so restoring one leaves every other file alone. Every capture (and
+//! every update of a file-store snapshot) is announced with
+//! [`EventMsg::GhostSnapshot`] and every restore with
+//! [`EventMsg::UndoCompleted`]; both are persisted in the rollout so a resumed
+//! session can undo turns recorded before it was restarted. The turn numbers
+//! allocated here are shared with the session diff history, which drops the
+//! diffs of undone turns along with their snapshots.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::ConversationId;
+
+use codex_git_tooling::CreateGhostCommitOptions;
+use codex_git_tooling::GhostCommit;
+use codex_git_tooling::GitToolingError;
+use codex_git_tooling::create_ghost_commit;
+use codex_git_tooling::restore_ghost_commit;
+use tracing::warn;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::GhostSnapshotEvent;
+use crate::protocol::GhostSnapshotKind;
+use crate::protocol::RolloutItem;
+use crate::protocol::UndoCompletedEvent;
+
+mod file_store;
+
+use file_store::FileSnapshotStore;
+
+/// Number of turns that can be undone.
+const MAX_TRACKED_SNAPSHOTS: usize = 20;
+
+/// Which snapshot an undo request restores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UndoTarget {
+    /// Undo the last `n` turns.
+    Turns(usize),
+    /// Restore the workspace as it was before the given turn started.
+    BeforeTurn(u64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Snapshot {
+    Git(GhostCommit),
+    /// Id of a manifest in the [`FileSnapshotStore`].
+    Files(String),
+}
+
+impl Snapshot {
+    fn id(&self) -> &str {
+        match self {
+            Snapshot::Git(commit) => commit.id(),
+            Snapshot::Files(id) => id,
+        }
+    }
+
+    fn to_event(&self, turn: u64) -> GhostSnapshotEvent {
+        match self {
+            Snapshot::Git(commit) => GhostSnapshotEvent {
+                turn,
+                commit_id: commit.id().to_string(),
+                parent: commit.parent().map(str::to_string),
+                kind: GhostSnapshotKind::Git,
+            },
+            Snapshot::Files(id) => GhostSnapshotEvent {
+                turn,
+                commit_id: id.clone(),
+                parent: None,
+                kind: GhostSnapshotKind::Files,
+            },
+        }
+    }
+
+    fn from_event(event: &GhostSnapshotEvent) -> Self {
+        match event.kind {
+            GhostSnapshotKind::Git => Snapshot::Git(GhostCommit::new(
+                event.commit_id.clone(),
+                event.parent.clone(),
+            )),
+            GhostSnapshotKind::Files => Snapshot::Files(event.commit_id.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TurnSnapshot {
+    turn: u64,
+    snapshot: Snapshot,
+}
+
+/// Snapshots of the current session, oldest first.
+#[derive(Debug)]
+pub(crate) struct GhostSnapshots {
+    enabled: bool,
+    snapshots: Vec<TurnSnapshot>,
+    last_turn: u64,
+    store: FileSnapshotStore,
+    session_id: String,
+}
+
+impl GhostSnapshots {
+    pub(crate) fn new(enabled: bool, codex_home: &Path, session_id: ConversationId) -> Self {
+        Self {
+            enabled,
+            snapshots: Vec::new(),
+            last_turn: 0,
+            store: FileSnapshotStore::new(codex_home),
+            session_id: session_id.to_string(),
+        }
+    }
+
+    /// Rebuild the snapshot stack from the events recorded in a rollout and
+    /// continue the turn numbering where it stopped.
+    pub(crate) fn replay(&mut self, items: &[RolloutItem]) {
+        for item in items {
+            match item {
+                RolloutItem::EventMsg(EventMsg::GhostSnapshot(event)) => {
+                    self.push(event.turn, Snapshot::from_event(event));
+                }
+                RolloutItem::EventMsg(EventMsg::TurnDiff(event)) => {
+                    self.last_turn = self.last_turn.max(event.turn);
+                }
+                RolloutItem::EventMsg(EventMsg::UndoCompleted(UndoCompletedEvent {
+                    success: true,
+                    restored_turn: Some(turn),
+                    ..
+                })) => {
+                    self.snapshots.retain(|snapshot| snapshot.turn < *turn);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Number a new turn. Turns are numbered from 1 whether or not snapshots
+    /// are enabled, so the session diff history can share the numbering.
+    pub(crate) fn start_turn(&mut self) -> u64 {
+        self.last_turn += 1;
+        self.last_turn
+    }
+
+    /// Number of the turn that started last.
+    pub(crate) fn current_turn(&self) -> u64 {
+        self.last_turn
+    }
+
+    /// Add the snapshot taken before `turn`, replacing the previous version
+    /// of it when a file-store snapshot is updated during the turn.
+    fn push(&mut self, turn: u64, snapshot: Snapshot) {
+        self.last_turn = self.last_turn.max(turn);
+        if let Some(last) = self.snapshots.last_mut()
+            && last.turn == turn
+        {
+            last.snapshot = snapshot;
+            return;
+        }
+        self.snapshots.push(TurnSnapshot { turn, snapshot });
+        if self.snapshots.len() > MAX_TRACKED_SNAPSHOTS {
+            self.snapshots.remove(0);
+        }
+    }
+
+    /// Ids of the file-store snapshots this session can still restore.
+    fn file_snapshot_ids(&self) -> Vec<String> {
+        self.snapshots
+            .iter()
+            .filter_map(|snapshot| match &snapshot.snapshot {
+                Snapshot::Files(id) => Some(id.clone()),
+                Snapshot::Git(_) => None,
+            })
+            .collect()
+    }
+
+    /// Index into `snapshots` of the snapshot `target` refers to.
+    fn resolve(&self, target: UndoTarget) -> Result<usize, String> {
+        match target {
+            UndoTarget::Turns(0) => Err("Nothing to undo: asked to undo 0 turns.".to_string()),
+            UndoTarget::Turns(turns) => {
+                let available = self.snapshots.len();
+                available.checked_sub(turns).ok_or_else(|| match available {
+                    0 => "No snapshot available to undo.".to_string(),
+                    1 => format!("Cannot undo {turns} turns: only 1 snapshot is available."),
+                    _ => format!(
+                        "Cannot undo {turns} turns: only {available} snapshots are available."
+                    ),
+                })
+            }
+            UndoTarget::BeforeTurn(turn) => self
+                .snapshots
+                .iter()
+                .position(|snapshot| snapshot.turn == turn)
+                .ok_or_else(|| format!("No snapshot available for turn {turn}.")),
+        }
+    }
+}
+
+impl Session {
+    /// Start a new turn and snapshot the workspace before it runs. Failures
+    /// disable snapshots for the rest of the session.
+    pub(crate) async fn capture_ghost_snapshot(&self, turn_context: &TurnContext, sub_id: &str) {
+        let (turn, store) = {
+            let mut snapshots = self.services.ghost_snapshots.lock().await;
+            let turn = snapshots.start_turn();
+            if !snapshots.enabled {
+                return;
+            }
+            (turn, snapshots.store.clone())
+        };
+
+        let cwd = turn_context.cwd.clone();
+        let result = tokio::task::spawn_blocking(move || capture_snapshot(&cwd, &store)).await;
+        let snapshot = match result {
+            Ok(Ok(snapshot)) => snapshot,
+            Ok(Err(err)) => {
+                warn!("failed to create ghost snapshot: {err}");
+                self.services.ghost_snapshots.lock().await.enabled = false;
+                let message = format!(
+                    "Snapshots disabled after error: {err}. Restart Codex after resolving the issue to re-enable snapshots."
+                );
+                self.notify_background_event(sub_id, message).await;
+                return;
+            }
+            Err(err) => {
+                warn!("ghost snapshot task failed: {err}");
+                return;
+            }
+        };
+
+        self.push_ghost_snapshot(sub_id, turn, snapshot).await;
+    }
+
+    /// Record the pre-images of `paths` in the file-store snapshot of the
+    /// running turn before `apply_patch` changes them.
+    pub(crate) async fn record_ghost_snapshot_paths(
+        &self,
+        sub_id: &str,
+        cwd: &Path,
+        paths: Vec<PathBuf>,
+    ) {
+        let (turn, id, store) = {
+            let snapshots = self.services.ghost_snapshots.lock().await;
+            match snapshots.snapshots.last() {
+                Some(TurnSnapshot {
+                    turn,
+                    snapshot: Snapshot::Files(id),
+                }) if snapshots.enabled && *turn == snapshots.last_turn => {
+                    (*turn, id.clone(), snapshots.store.clone())
+                }
+                _ => return,
+            }
+        };
+
+        let cwd = cwd.to_path_buf();
+        let result = tokio::task::spawn_blocking(move || store.record(&cwd, &id, &paths)).await;
+        match result {
+            Ok(Ok(id)) => {
+                self.push_ghost_snapshot(sub_id, turn, Snapshot::Files(id))
+                    .await;
+            }
+            Ok(Err(err)) => warn!("failed to record paths in snapshot: {err}"),
+            Err(err) => warn!("snapshot task failed: {err}"),
+        }
+    }
+
+    async fn push_ghost_snapshot(&self, sub_id: &str, turn: u64, snapshot: Snapshot) {
+        let event = snapshot.to_event(turn);
+        self.services
+            .ghost_snapshots
+            .lock()
+            .await
+            .push(turn, snapshot);
+        self.sync_snapshot_refs().await;
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::GhostSnapshot(event),
+        })
+        .await;
+    }
+
+    /// Save the file-store snapshots this session still references and drop
+    /// the objects no session needs anymore.
+    async fn sync_snapshot_refs(&self) {
+        let (store, session_id, ids) = {
+            let snapshots = self.services.ghost_snapshots.lock().await;
+            (
+                snapshots.store.clone(),
+                snapshots.session_id.clone(),
+                snapshots.file_snapshot_ids(),
+            )
+        };
+        if ids.is_empty() {
+            return;
+        }
+        let result = tokio::task::spawn_blocking(move || {
+            store.save_refs(&session_id, ids.iter().map(String::as_str))?;
+            store.gc()
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => warn!("failed to clean up snapshots: {err}"),
+            Err(err) => warn!("snapshot cleanup task failed: {err}"),
+        }
+    }
+
+    /// Restore the workspace to the snapshot selected by `target` and drop the
+    /// snapshots of the turns that were undone.
+    pub(crate) async fn undo(&self, cwd: PathBuf, sub_id: &str, target: UndoTarget) {
+        let result = self.try_undo(cwd, target).await;
+        let msg = match result {
+            Ok((turn, snapshot)) => {
+                let short_id: String = snapshot.id().chars().take(8).collect();
+                UndoCompletedEvent {
+                    success: true,
+                    restored_turn: Some(turn),
+                    message: Some(format!(
+                        "Restored workspace to snapshot {short_id} taken before turn {turn}."
+                    )),
+                }
+            }
+            Err(message) => UndoCompletedEvent {
+                success: false,
+                restored_turn: None,
+                message: Some(message),
+            },
+        };
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::UndoCompleted(msg),
+        })
+        .await;
+    }
+
+    async fn try_undo(&self, cwd: PathBuf, target: UndoTarget) -> Result<(u64, Snapshot), String> {
+        if self.active_turn.lock().await.is_some() {
+            return Err("Cannot undo while a turn is running.".to_string());
+        }
+
+        let (undone, store) = {
+            let snapshots = self.services.ghost_snapshots.lock().await;
+            if !snapshots.enabled && snapshots.snapshots.is_empty() {
+                return Err(
+                    "Snapshots are disabled. Enable them with `features.ghost_commit = true`."
+                        .to_string(),
+                );
+            }
+            let index = snapshots.resolve(target)?;
+            (
+                snapshots.snapshots[index..].to_vec(),
+                snapshots.store.clone(),
+            )
+        };
+        let TurnSnapshot { turn, snapshot } = undone[0].clone();
+
+        tokio::task::spawn_blocking(move || match &undone[0].snapshot {
+            Snapshot::Git(commit) => {
+                restore_ghost_commit(&cwd, commit).map_err(|err| err.to_string())
+            }
+            // File-store snapshots only hold the paths their own turn touched,
+            // so every undone turn is reverted, newest first.
+            Snapshot::Files(_) => {
+                undone
+                    .iter()
+                    .rev()
+                    .try_for_each(|undone| match &undone.snapshot {
+                        Snapshot::Files(id) => {
+                            store.restore(&cwd, id).map_err(|err| err.to_string())
+                        }
+                        Snapshot::Git(_) => Ok(()),
+                    })
+            }
+        })
+        .await
+        .map_err(|err| format!("Failed to restore snapshot: {err}"))?
+        .map_err(|err| format!("Failed to restore snapshot: {err}"))?;
+
+        self.services
+            .ghost_snapshots
+            .lock()
+            .await
+            .snapshots
+            .retain(|snapshot| snapshot.turn < turn);
+        self.services.diff_history.lock().await.drop_from(turn);
+        self.sync_snapshot_refs().await;
+        Ok((turn, snapshot))
+    }
+}
+
+/// Snapshot `cwd` as a ghost commit, falling back to the file store when it is
+/// not inside a Git repository (or Git is not installed).
+fn capture_snapshot(cwd: &Path, store: &FileSnapshotStore) -> Result<Snapshot, String> {
+    match create_ghost_commit(&CreateGhostCommitOptions::new(cwd)) {
+        Ok(commit) => Ok(Snapshot::Git(commit)),
+        Err(err) if is_outside_git(&err) => store
+            .begin()
+            .map(Snapshot::Files)
+            .map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn is_outside_git(err: &GitToolingError) -> bool {
+    match err {
+        GitToolingError::NotAGitRepository { .. } => true,
+        GitToolingError::Io(err) => err.kind() == std::io::ErrorKind::NotFound,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn snapshot_event(turn: u64) -> RolloutItem {
+        RolloutItem::EventMsg(EventMsg::GhostSnapshot(GhostSnapshotEvent {
+            turn,
+            commit_id: format!("commit-{turn}"),
+            parent: None,
+            kind: GhostSnapshotKind::Git,
+        }))
+    }
+
+    #[test]
+    fn replay_drops_undone_turns() {
+        let mut snapshots =
+            GhostSnapshots::new(true, Path::new("/tmp/codex-home"), ConversationId::new());
+        snapshots.replay(&[
+            snapshot_event(1),
+            snapshot_event(2),
+            snapshot_event(3),
+            RolloutItem::EventMsg(EventMsg::UndoCompleted(UndoCompletedEvent {
+                success: true,
+                restored_turn: Some(2),
+                message: None,
+            })),
+            snapshot_event(4),
+        ]);
+
+        let turns: Vec<u64> = snapshots.snapshots.iter().map(|s| s.turn).collect();
+        assert_eq!(turns, vec![1, 4]);
+        assert_eq!(snapshots.last_turn, 4);
+    }
+
+    #[test]
+    fn resolve_counts_turns_from_the_end() {
+        let mut snapshots =
+            GhostSnapshots::new(true, Path::new("/tmp/codex-home"), ConversationId::new());
+        snapshots.replay(&[snapshot_event(1), snapshot_event(2), snapshot_event(3)]);
+
+        assert_eq!(snapshots.resolve(UndoTarget::Turns(1)), Ok(2));
+        assert_eq!(snapshots.resolve(UndoTarget::Turns(3)), Ok(0));
+        assert_eq!(
+            snapshots.resolve(UndoTarget::Turns(4)),
+            Err("Cannot undo 4 turns: only 3 snapshots are available.".to_string())
+        );
+        assert_eq!(snapshots.resolve(UndoTarget::BeforeTurn(2)), Ok(1));
+        assert_eq!(
+            snapshots.resolve(UndoTarget::BeforeTurn(7)),
+            Err("No snapshot available for turn 7.".to_string())
+        );
+    }
+}
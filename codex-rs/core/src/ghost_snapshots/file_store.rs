@@ -0,0 +1,443 @@
+//! Content-addressed snapshots for workspaces that are not Git repositories.
+//!
+//! Instead of copying the whole workspace, a snapshot only records the paths
+//! the agent touches during a turn: before `apply_patch` changes a path its
+//! current contents are stored once under `CODEX_HOME/snapshots/objects/<sha1>`,
+//! or the path is marked as absent when the patch creates it. A snapshot is a
+//! manifest listing those pre-images; the manifest is stored as an object too,
+//! so its hash doubles as the snapshot id. Files changed by shell commands are
+//! not recorded and are left alone on restore.
+//!
+//! Each session lists the manifests it still references in
+//! `CODEX_HOME/snapshots/refs/<session id>`; [`FileSnapshotStore::gc`] removes
+//! the objects no session references anymore.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use serde::Serialize;
+use sha1::Digest;
+use sha1::Sha1;
+
+/// Files are hashed and copied in chunks of this size, so recording and
+/// restoring large files does not hold them in memory.
+const COPY_BUFFER_BYTES: usize = 64 * 1024;
+
+/// Objects younger than this are never collected: another session may have
+/// written them without having updated its refs yet.
+const GC_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Refs of sessions that have not been touched for this long are dropped, so
+/// undo is no longer available when such a session is resumed.
+const REFS_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    /// Path relative to the workspace root, or absolute when the file lives
+    /// outside of it.
+    path: PathBuf,
+    /// Object holding the file contents before the turn, `None` when the file
+    /// did not exist and was created by the agent.
+    blob: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    executable: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct FileSnapshotStore {
+    objects: PathBuf,
+    refs: PathBuf,
+}
+
+impl FileSnapshotStore {
+    pub(crate) fn new(codex_home: &Path) -> Self {
+        let root = codex_home.join("snapshots");
+        Self {
+            objects: root.join("objects"),
+            refs: root.join("refs"),
+        }
+    }
+
+    /// Start the snapshot of a new turn and return its id. The snapshot is
+    /// empty until [`FileSnapshotStore::record`] adds the paths the turn
+    /// touches.
+    pub(crate) fn begin(&self) -> io::Result<String> {
+        fs::create_dir_all(&self.objects)?;
+        self.write_manifest(&Manifest::default())
+    }
+
+    /// Add the current state of `paths` to snapshot `id` and return the id of
+    /// the updated snapshot. Paths already recorded keep their first
+    /// pre-image.
+    pub(crate) fn record(
+        &self,
+        workspace: &Path,
+        id: &str,
+        paths: &[PathBuf],
+    ) -> io::Result<String> {
+        let mut manifest = self.read_manifest(id)?;
+        for path in paths {
+            let relative = path.strip_prefix(workspace).unwrap_or(path).to_path_buf();
+            if manifest.files.iter().any(|entry| entry.path == relative) {
+                continue;
+            }
+            let metadata = match fs::symlink_metadata(path) {
+                Ok(metadata) => metadata,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    manifest.files.push(ManifestEntry {
+                        path: relative,
+                        blob: None,
+                        executable: false,
+                    });
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            manifest.files.push(ManifestEntry {
+                path: relative,
+                blob: Some(self.write_object(fs::File::open(path)?)?),
+                executable: is_executable(&metadata),
+            });
+        }
+        manifest.files.sort_by(|a, b| a.path.cmp(&b.path));
+        self.write_manifest(&manifest)
+    }
+
+    /// Put the paths recorded in snapshot `id` back the way they were before
+    /// the turn: recorded files get their contents back and files the agent
+    /// created are removed. Nothing else in `workspace` is touched.
+    pub(crate) fn restore(&self, workspace: &Path, id: &str) -> io::Result<()> {
+        let manifest = self.read_manifest(id)?;
+        for entry in &manifest.files {
+            let path = workspace.join(&entry.path);
+            let Some(blob) = &entry.blob else {
+                match fs::remove_file(&path) {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(err),
+                }
+                continue;
+            };
+            let object = self.object_path(blob)?;
+            let current = fs::File::open(&path).and_then(|file| copy_hashed(file, io::sink()));
+            if current.ok().as_ref() != Some(blob) {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                // Overwrite in place so the file keeps its other permissions.
+                io::copy(&mut fs::File::open(object)?, &mut fs::File::create(&path)?)?;
+            }
+            set_executable(&path, entry.executable)?;
+        }
+        Ok(())
+    }
+
+    /// Record the snapshots `session` still references so [`Self::gc`] keeps
+    /// their objects.
+    pub(crate) fn save_refs<'a>(
+        &self,
+        session: &str,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) -> io::Result<()> {
+        fs::create_dir_all(&self.refs)?;
+        let mut contents = String::new();
+        for id in ids {
+            contents.push_str(id);
+            contents.push('\n');
+        }
+        let mut tmp = tempfile::NamedTempFile::new_in(&self.refs)?;
+        tmp.write_all(contents.as_bytes())?;
+        tmp.persist(self.refs.join(session))
+            .map_err(|err| err.error)?;
+        Ok(())
+    }
+
+    /// Delete the objects no session references anymore, as well as the refs
+    /// of sessions that have not been used for a long time.
+    pub(crate) fn gc(&self) -> io::Result<()> {
+        let mut live = HashSet::new();
+        let refs = match fs::read_dir(&self.refs) {
+            Ok(refs) => refs,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        for entry in refs {
+            let entry = entry?;
+            if older_than(&entry.metadata()?, REFS_MAX_AGE) {
+                fs::remove_file(entry.path())?;
+                continue;
+            }
+            for id in fs::read_to_string(entry.path())?.lines() {
+                // A manifest that cannot be read cannot be restored either.
+                let Ok(manifest) = self.read_manifest(id) else {
+                    continue;
+                };
+                live.insert(id.to_string());
+                live.extend(manifest.files.into_iter().filter_map(|entry| entry.blob));
+            }
+        }
+
+        for entry in fs::read_dir(&self.objects)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if name.to_str().is_some_and(|name| live.contains(name)) {
+                continue;
+            }
+            if older_than(&entry.metadata()?, GC_GRACE_PERIOD) {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_manifest(&self, id: &str) -> io::Result<Manifest> {
+        Ok(serde_json::from_slice(&self.read_object(id)?)?)
+    }
+
+    fn write_manifest(&self, manifest: &Manifest) -> io::Result<String> {
+        self.write_object(serde_json::to_vec(manifest)?.as_slice())
+    }
+
+    /// Store `contents` as an object and return its id. The contents are
+    /// hashed while they are copied, so the id always matches what was
+    /// stored, even if the source changes meanwhile.
+    fn write_object(&self, contents: impl Read) -> io::Result<String> {
+        let mut tmp = tempfile::NamedTempFile::new_in(&self.objects)?;
+        let id = copy_hashed(contents, &mut tmp)?;
+        let path = self.objects.join(&id);
+        if path.exists() {
+            // Refresh the mtime so a concurrent `gc` treats the object as new.
+            fs::File::options()
+                .append(true)
+                .open(&path)?
+                .set_modified(SystemTime::now())?;
+        } else {
+            tmp.persist(&path).map_err(|err| err.error)?;
+        }
+        Ok(id)
+    }
+
+    fn read_object(&self, id: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.object_path(id)?)
+    }
+
+    fn object_path(&self, id: &str) -> io::Result<PathBuf> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid snapshot object id `{id}`"),
+            ));
+        }
+        Ok(self.objects.join(id))
+    }
+}
+
+/// Copy `reader` to `writer` and return the SHA-1 of what was copied, as the
+/// hex string objects are named by.
+fn copy_hashed(mut reader: impl Read, mut writer: impl Write) -> io::Result<String> {
+    let mut hasher = Sha1::new();
+    let mut buffer = vec![0; COPY_BUFFER_BYTES];
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+    }
+    writer.flush()?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn older_than(metadata: &fs::Metadata, age: Duration) -> bool {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed > age)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path, executable: bool) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    let mode = permissions.mode();
+    let new_mode = if executable {
+        // Grant execute wherever read is granted, like `chmod +x`.
+        mode | ((mode & 0o444) >> 2)
+    } else {
+        mode & !0o111
+    };
+    if new_mode != mode {
+        permissions.set_mode(new_mode);
+        fs::set_permissions(path, permissions)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path, _executable: bool) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn restore_only_reverts_recorded_paths() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let workspace = TempDir::new()?;
+        let root = workspace.path();
+        fs::create_dir(root.join("src"))?;
+        fs::write(root.join("src/lib.rs"), "fn original() {}\n")?;
+        fs::write(root.join("README.md"), "readme\n")?;
+
+        let store = FileSnapshotStore::new(codex_home.path());
+        let id = store.begin()?;
+        let id = store.record(
+            root,
+            &id,
+            &[root.join("src/lib.rs"), root.join("src/new.rs")],
+        )?;
+        // A second patch in the same turn keeps the first pre-image.
+        fs::write(root.join("src/lib.rs"), "fn edited() {}\n")?;
+        let id = store.record(root, &id, &[root.join("src/lib.rs")])?;
+        fs::write(root.join("src/new.rs"), "fn new() {}\n")?;
+
+        // Changes the agent did not make through a recorded path survive.
+        fs::write(root.join("README.md"), "edited by the user\n")?;
+        fs::write(root.join("notes.txt"), "created by the user\n")?;
+
+        store.restore(root, &id)?;
+
+        assert_eq!(
+            fs::read_to_string(root.join("src/lib.rs"))?,
+            "fn original() {}\n"
+        );
+        assert!(!root.join("src/new.rs").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("README.md"))?,
+            "edited by the user\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("notes.txt"))?,
+            "created by the user\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn large_files_are_restored() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let workspace = TempDir::new()?;
+        let large = workspace.path().join("large.bin");
+        let original: Vec<u8> = (0..COPY_BUFFER_BYTES * 3 + 1)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        fs::write(&large, &original)?;
+
+        let store = FileSnapshotStore::new(codex_home.path());
+        let id = store.begin()?;
+        let id = store.record(workspace.path(), &id, std::slice::from_ref(&large))?;
+        assert!(
+            store
+                .objects
+                .join(format!("{:x}", Sha1::digest(&original)))
+                .exists()
+        );
+        fs::write(&large, b"truncated")?;
+        store.restore(workspace.path(), &id)?;
+
+        assert_eq!(fs::read(&large)?, original);
+        assert!(store.restore(workspace.path(), "../escape").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn gc_keeps_only_referenced_objects() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let workspace = TempDir::new()?;
+        let file = workspace.path().join("a.txt");
+        let store = FileSnapshotStore::new(codex_home.path());
+
+        fs::write(&file, "kept\n")?;
+        let begin = store.begin()?;
+        let kept = store.record(workspace.path(), &begin, std::slice::from_ref(&file))?;
+        fs::write(&file, "dropped\n")?;
+        let dropped = store.record(workspace.path(), &begin, std::slice::from_ref(&file))?;
+        store.save_refs("session", [kept.as_str()])?;
+
+        // Pretend every object is past the grace period.
+        let old = SystemTime::now() - GC_GRACE_PERIOD * 2;
+        for entry in fs::read_dir(&store.objects)? {
+            fs::File::options()
+                .append(true)
+                .open(entry?.path())?
+                .set_modified(old)?;
+        }
+        store.gc()?;
+
+        let mut remaining: Vec<String> = fs::read_dir(&store.objects)?
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<_>>()?;
+        remaining.sort();
+        let mut expected = vec![kept, format!("{:x}", Sha1::digest(b"kept\n"))];
+        expected.sort();
+        assert_eq!(remaining, expected);
+        assert!(store.read_object(&dropped).is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restore_keeps_the_executable_bit() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let codex_home = TempDir::new()?;
+        let workspace = TempDir::new()?;
+        let script = workspace.path().join("run.sh");
+        fs::write(&script, "#!/bin/sh\n")?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+        let store = FileSnapshotStore::new(codex_home.path());
+        let id = store.begin()?;
+        let id = store.record(workspace.path(), &id, std::slice::from_ref(&script))?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o644))?;
+        store.restore(workspace.path(), &id)?;
+
+        assert_eq!(fs::metadata(&script)?.permissions().mode() & 0o777, 0o755);
+        Ok(())
+    }
+}
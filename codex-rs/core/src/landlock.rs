@@ -1,4 +1,8 @@
+use crate::config_types::LinuxSandboxBackend;
+use crate::network_proxy;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::SandboxViolation;
+use crate::resource_limits::ResourceLimits;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
 use std::collections::HashMap;
@@ -6,26 +10,42 @@ use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
 
-/// Spawn a shell tool command under the Linux Landlock+seccomp sandbox helper
-/// (codex-linux-sandbox).
+/// Spawn a shell tool command under the Linux sandbox helper
+/// (codex-linux-sandbox), which isolates it with Landlock+seccomp or, for
+/// [`LinuxSandboxBackend::Namespaces`], with user/mount/PID namespaces.
 ///
 /// Unlike macOS Seatbelt where we directly embed the policy text, the Linux
 /// helper accepts a list of `--sandbox-permission`/`-s` flags mirroring the
 /// public CLI. We convert the internal [`SandboxPolicy`] representation into
 /// the equivalent CLI options.
+#[allow(clippy::too_many_arguments)]
 pub async fn spawn_command_under_linux_sandbox<P>(
     codex_linux_sandbox_exe: P,
     command: Vec<String>,
     command_cwd: PathBuf,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    resource_limits: &ResourceLimits,
+    backend: LinuxSandboxBackend,
+    violation_report: Option<&Path>,
+    report_denied_writes: bool,
     stdio_policy: StdioPolicy,
-    env: HashMap<String, String>,
+    mut env: HashMap<String, String>,
 ) -> std::io::Result<Child>
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, sandbox_policy_cwd);
+    let network_proxy_port = network_proxy::prepare_sandbox_env(sandbox_policy, &mut env)?;
+    let args = create_linux_sandbox_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+        network_proxy_port,
+        resource_limits,
+        backend,
+        violation_report,
+        report_denied_writes,
+    );
     let arg0 = Some("codex-linux-sandbox");
     spawn_child_async(
         codex_linux_sandbox_exe.as_ref().to_path_buf(),
@@ -40,10 +60,16 @@ where
 }
 
 /// Converts the sandbox policy into the CLI invocation for `codex-linux-sandbox`.
+#[allow(clippy::too_many_arguments)]
 fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    network_proxy_port: Option<u16>,
+    resource_limits: &ResourceLimits,
+    backend: LinuxSandboxBackend,
+    violation_report: Option<&Path>,
+    report_denied_writes: bool,
 ) -> Vec<String> {
     #[expect(clippy::expect_used)]
     let sandbox_policy_cwd = sandbox_policy_cwd
@@ -55,16 +81,50 @@ fn create_linux_sandbox_command_args(
     let sandbox_policy_json =
         serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
 
-    let mut linux_cmd: Vec<String> = vec![
+    let mut linux_cmd: Vec<String> = Vec::new();
+    if backend == LinuxSandboxBackend::Namespaces {
+        linux_cmd.push("--namespaces".to_string());
+    }
+    if let Some(port) = network_proxy_port {
+        linux_cmd.extend(["--network-proxy-port".to_string(), port.to_string()]);
+    }
+    if !resource_limits.is_empty() {
+        #[expect(clippy::expect_used)]
+        let resource_limits_json = serde_json::to_string(resource_limits)
+            .expect("Failed to serialize ResourceLimits to JSON");
+        linux_cmd.extend(["--resource-limits".to_string(), resource_limits_json]);
+    }
+    if let Some(report) = violation_report {
+        linux_cmd.extend([
+            "--violation-report".to_string(),
+            report.to_string_lossy().into_owned(),
+        ]);
+        if report_denied_writes {
+            linux_cmd.push("--report-denied-writes".to_string());
+        }
+    }
+    linux_cmd.extend([
         sandbox_policy_cwd,
         sandbox_policy_json,
         // Separator so that command arguments starting with `-` are not parsed as
         // options of the helper itself.
         "--".to_string(),
-    ];
+    ]);
 
     // Append the original tool command.
     linux_cmd.extend(command);
 
     linux_cmd
 }
+
+/// Reads the violations `codex-linux-sandbox` wrote to its `--violation-report`
+/// file, one JSON object per line. Lines that do not parse are skipped.
+pub(crate) fn read_violation_report(path: &Path) -> Vec<SandboxViolation> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
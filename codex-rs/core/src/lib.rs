@@ -5,6 +5,7 @@
 // the TUI or the tracing stack).
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
+mod anthropic_messages;
 mod apply_patch;
 pub mod auth;
 pub mod bash;
@@ -23,6 +24,7 @@ pub mod config_profile;
 pub mod config_types;
 mod conversation_history;
 pub mod custom_prompts;
+mod dry_run;
 mod environment_context;
 pub mod error;
 pub mod exec;
@@ -30,7 +32,10 @@ mod exec_command;
 pub mod exec_env;
 pub mod executor;
 pub mod features;
+mod file_reads;
 mod flags;
+mod gemini_generate_content;
+mod ghost_snapshots;
 pub mod git_info;
 pub mod landlock;
 pub mod mcp;
@@ -38,7 +43,9 @@ mod mcp_connection_manager;
 mod mcp_tool_call;
 mod message_history;
 mod model_provider_info;
+mod network_proxy;
 pub mod parse_command;
+pub mod resource_limits;
 mod truncate;
 mod unified_exec;
 mod user_instructions;
@@ -50,6 +57,7 @@ pub use model_provider_info::create_oss_provider_with_base_url;
 mod conversation_manager;
 mod event_mapping;
 pub mod review_format;
+mod review_target;
 pub use codex_protocol::protocol::InitialHistory;
 pub use conversation_manager::ConversationManager;
 pub use conversation_manager::NewConversation;
@@ -67,6 +75,7 @@ pub mod seatbelt;
 pub mod shell;
 pub mod spawn;
 pub mod terminal;
+mod token_budget;
 mod tools;
 pub mod turn_diff_tracker;
 pub use rollout::ARCHIVED_SESSIONS_SUBDIR;
@@ -74,11 +83,17 @@ pub use rollout::INTERACTIVE_SESSION_SOURCES;
 pub use rollout::RolloutRecorder;
 pub use rollout::SESSIONS_SUBDIR;
 pub use rollout::SessionMeta;
+pub use rollout::export::Transcript;
+pub use rollout::export::TranscriptEntry;
 pub use rollout::find_conversation_path_by_id_str;
 pub use rollout::list::ConversationItem;
 pub use rollout::list::ConversationsPage;
-pub use rollout::list::get_conversations;
 pub use rollout::list::Cursor;
+pub use rollout::list::get_conversations;
+pub use rollout::search::ConversationSearchPage;
+pub use rollout::search::ConversationSearchResult;
+pub use rollout::search::SearchMatch;
+pub use rollout::search::SearchMatchKind;
 mod function_tool;
 mod state;
 mod tasks;
@@ -5,6 +5,15 @@
 //! helpers to query the available tools across *all* servers and returns them
 //! in a single aggregated map using the fully-qualified tool name
 //! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
+//!
+//! Servers that advertise the `resources` or `prompts` capabilities can also
+//! be browsed through [`McpConnectionManager::list_resources`],
+//! [`McpConnectionManager::read_resource`] and
+//! [`McpConnectionManager::get_prompt`]. Prompts are listed once at startup,
+//! like tools; resources are listed on demand.
+//!
+//! When a [`McpSamplingHandler`] is supplied, Codex advertises the `sampling`
+//! capability and forwards `sampling/createMessage` requests to it.
 
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -17,10 +26,20 @@ use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use codex_mcp_client::McpClient;
+use codex_mcp_client::SamplingHandler;
 use codex_rmcp_client::OAuthCredentialsStoreMode;
 use codex_rmcp_client::RmcpClient;
+use futures::FutureExt;
+use futures::future::BoxFuture;
 use mcp_types::ClientCapabilities;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
 use mcp_types::Implementation;
+use mcp_types::InitializeResult;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::Prompt;
+use mcp_types::ServerCapabilities;
 use mcp_types::Tool;
 
 use serde_json::json;
@@ -51,6 +70,23 @@ const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
 /// spawned successfully.
 pub type ClientStartErrors = HashMap<String, anyhow::Error>;
 
+/// Answers `sampling/createMessage` requests. Receives the name of the server
+/// that sent the request along with its parameters.
+pub(crate) type McpSamplingHandler = Arc<
+    dyn Fn(String, CreateMessageRequestParams) -> BoxFuture<'static, Result<CreateMessageResult>>
+        + Send
+        + Sync,
+>;
+
+/// Binds `handler` to a single server so it can be handed to that server's
+/// client.
+fn sampling_handler_for_server(
+    handler: McpSamplingHandler,
+    server_name: String,
+) -> SamplingHandler {
+    Arc::new(move |params| handler(server_name.clone(), params).boxed())
+}
+
 fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     let mut used_names = HashSet::new();
     let mut qualified_tools = HashMap::new();
@@ -91,6 +127,7 @@ struct ToolInfo {
 
 struct ManagedClient {
     client: McpClientAdapter,
+    capabilities: ServerCapabilities,
     startup_timeout: Duration,
     tool_timeout: Option<Duration>,
 }
@@ -109,15 +146,24 @@ impl McpClientAdapter {
         env: Option<HashMap<String, String>>,
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
-    ) -> Result<Self> {
+        sampling_handler: Option<SamplingHandler>,
+    ) -> Result<(Self, InitializeResult)> {
         if use_rmcp_client {
-            let client = Arc::new(RmcpClient::new_stdio_client(program, args, env).await?);
-            client.initialize(params, Some(startup_timeout)).await?;
-            Ok(McpClientAdapter::Rmcp(client))
+            let mut client = RmcpClient::new_stdio_client(program, args, env).await?;
+            if let Some(handler) = sampling_handler {
+                client = client.with_sampling_handler(handler);
+            }
+            let client = Arc::new(client);
+            let initialize_result = client.initialize(params, Some(startup_timeout)).await?;
+            Ok((McpClientAdapter::Rmcp(client), initialize_result))
         } else {
-            let client = Arc::new(McpClient::new_stdio_client(program, args, env).await?);
-            client.initialize(params, Some(startup_timeout)).await?;
-            Ok(McpClientAdapter::Legacy(client))
+            let mut client = McpClient::new_stdio_client(program, args, env).await?;
+            if let Some(handler) = sampling_handler {
+                client = client.with_sampling_handler(handler);
+            }
+            let client = Arc::new(client);
+            let initialize_result = client.initialize(params, Some(startup_timeout)).await?;
+            Ok((McpClientAdapter::Legacy(client), initialize_result))
         }
     }
 
@@ -128,13 +174,17 @@ impl McpClientAdapter {
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
         store_mode: OAuthCredentialsStoreMode,
-    ) -> Result<Self> {
-        let client = Arc::new(
+        sampling_handler: Option<SamplingHandler>,
+    ) -> Result<(Self, InitializeResult)> {
+        let mut client =
             RmcpClient::new_streamable_http_client(&server_name, &url, bearer_token, store_mode)
-                .await?,
-        );
-        client.initialize(params, Some(startup_timeout)).await?;
-        Ok(McpClientAdapter::Rmcp(client))
+                .await?;
+        if let Some(handler) = sampling_handler {
+            client = client.with_sampling_handler(handler);
+        }
+        let client = Arc::new(client);
+        let initialize_result = client.initialize(params, Some(startup_timeout)).await?;
+        Ok((McpClientAdapter::Rmcp(client), initialize_result))
     }
 
     async fn list_tools(
@@ -159,6 +209,51 @@ impl McpClientAdapter {
             McpClientAdapter::Rmcp(client) => client.call_tool(name, arguments, timeout).await,
         }
     }
+
+    async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::ListResourcesResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.list_resources(params, timeout).await,
+            McpClientAdapter::Rmcp(client) => client.list_resources(params, timeout).await,
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::ReadResourceResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.read_resource(uri, timeout).await,
+            McpClientAdapter::Rmcp(client) => client.read_resource(uri, timeout).await,
+        }
+    }
+
+    async fn list_prompts(
+        &self,
+        params: Option<mcp_types::ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::ListPromptsResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.list_prompts(params, timeout).await,
+            McpClientAdapter::Rmcp(client) => client.list_prompts(params, timeout).await,
+        }
+    }
+
+    async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::GetPromptResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.get_prompt(name, arguments, timeout).await,
+            McpClientAdapter::Rmcp(client) => client.get_prompt(name, arguments, timeout).await,
+        }
+    }
 }
 
 /// A thin wrapper around a set of running [`McpClient`] instances.
@@ -172,6 +267,9 @@ pub(crate) struct McpConnectionManager {
 
     /// Fully qualified tool name -> tool instance.
     tools: HashMap<String, ToolInfo>,
+
+    /// Server-name -> prompts advertised by that server.
+    prompts: HashMap<String, Vec<Prompt>>,
 }
 
 impl McpConnectionManager {
@@ -183,10 +281,14 @@ impl McpConnectionManager {
     ///
     /// Servers that fail to start are reported in `ClientStartErrors`: the
     /// user should be informed about these errors.
+    ///
+    /// * `sampling_handler` – when set, servers may ask Codex to sample the
+    ///   model via `sampling/createMessage`.
     pub async fn new(
         mcp_servers: HashMap<String, McpServerConfig>,
         use_rmcp_client: bool,
         store_mode: OAuthCredentialsStoreMode,
+        sampling_handler: Option<McpSamplingHandler>,
     ) -> Result<(Self, ClientStartErrors)> {
         // Early exit if no servers are configured.
         if mcp_servers.is_empty() {
@@ -222,13 +324,18 @@ impl McpConnectionManager {
                 _ => Ok(None),
             };
 
+            let sampling_handler = sampling_handler
+                .clone()
+                .map(|handler| sampling_handler_for_server(handler, server_name.clone()));
+
             join_set.spawn(async move {
                 let McpServerConfig { transport, .. } = cfg;
                 let params = mcp_types::InitializeRequestParams {
                     capabilities: ClientCapabilities {
                         experimental: None,
                         roots: None,
-                        sampling: None,
+                        // https://modelcontextprotocol.io/specification/2025-06-18/client/sampling#capabilities
+                        sampling: sampling_handler.as_ref().map(|_| json!({})),
                         // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
                         // indicates this should be an empty object.
                         elicitation: Some(json!({})),
@@ -256,6 +363,7 @@ impl McpConnectionManager {
                             env,
                             params,
                             startup_timeout,
+                            sampling_handler,
                         )
                         .await
                     }
@@ -267,11 +375,12 @@ impl McpConnectionManager {
                             params,
                             startup_timeout,
                             store_mode,
+                            sampling_handler,
                         )
                         .await
                     }
                 }
-                .map(|c| (c, startup_timeout));
+                .map(|(c, initialize_result)| (c, initialize_result.capabilities, startup_timeout));
 
                 ((server_name, tool_timeout), client)
             });
@@ -289,11 +398,12 @@ impl McpConnectionManager {
             };
 
             match client_res {
-                Ok((client, startup_timeout)) => {
+                Ok((client, capabilities, startup_timeout)) => {
                     clients.insert(
                         server_name,
                         ManagedClient {
                             client,
+                            capabilities,
                             startup_timeout,
                             tool_timeout: Some(tool_timeout),
                         },
@@ -314,8 +424,16 @@ impl McpConnectionManager {
         };
 
         let tools = qualify_tools(all_tools);
+        let prompts = list_all_prompts(&clients).await;
 
-        Ok((Self { clients, tools }, errors))
+        Ok((
+            Self {
+                clients,
+                tools,
+                prompts,
+            },
+            errors,
+        ))
     }
 
     /// Returns a single map that contains **all** tools. Each key is the
@@ -352,6 +470,83 @@ impl McpConnectionManager {
             .get(tool_name)
             .map(|tool| (tool.server_name.clone(), tool.tool_name.clone()))
     }
+
+    /// Returns the prompts advertised by each server, keyed by server name.
+    pub fn list_all_prompts(&self) -> HashMap<String, Vec<Prompt>> {
+        self.prompts.clone()
+    }
+
+    /// Names of the servers that advertise the `resources` capability, sorted.
+    pub fn resource_servers(&self) -> Vec<String> {
+        let mut servers: Vec<String> = self
+            .clients
+            .iter()
+            .filter(|(_, managed)| managed.capabilities.resources.is_some())
+            .map(|(name, _)| name.clone())
+            .collect();
+        servers.sort();
+        servers
+    }
+
+    /// List one page of resources exposed by `server`.
+    pub async fn list_resources(
+        &self,
+        server: &str,
+        cursor: Option<String>,
+    ) -> Result<mcp_types::ListResourcesResult> {
+        let managed = self.resource_client(server)?;
+        let params = cursor.map(|cursor| ListResourcesRequestParams {
+            cursor: Some(cursor),
+        });
+        managed
+            .client
+            .list_resources(params, managed.tool_timeout)
+            .await
+            .with_context(|| format!("resources/list failed for `{server}`"))
+    }
+
+    /// Read the resource identified by `uri` from `server`.
+    pub async fn read_resource(
+        &self,
+        server: &str,
+        uri: &str,
+    ) -> Result<mcp_types::ReadResourceResult> {
+        let managed = self.resource_client(server)?;
+        managed
+            .client
+            .read_resource(uri.to_string(), managed.tool_timeout)
+            .await
+            .with_context(|| format!("resources/read failed for `{server}` ({uri})"))
+    }
+
+    /// Render the prompt `name` from `server` with the given arguments.
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        name: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> Result<mcp_types::GetPromptResult> {
+        let managed = self
+            .clients
+            .get(server)
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
+        managed
+            .client
+            .get_prompt(name.to_string(), arguments, managed.tool_timeout)
+            .await
+            .with_context(|| format!("prompts/get failed for `{server}/{name}`"))
+    }
+
+    fn resource_client(&self, server: &str) -> Result<&ManagedClient> {
+        let managed = self
+            .clients
+            .get(server)
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
+        if managed.capabilities.resources.is_none() {
+            return Err(anyhow!("MCP server '{server}' does not provide resources"));
+        }
+        Ok(managed)
+    }
 }
 
 fn resolve_bearer_token(
@@ -435,6 +630,67 @@ async fn list_all_tools(clients: &HashMap<String, ManagedClient>) -> Result<Vec<
     Ok(aggregated)
 }
 
+/// Query every server that advertises the `prompts` capability for its
+/// prompts. Servers that fail to respond are skipped.
+async fn list_all_prompts(
+    clients: &HashMap<String, ManagedClient>,
+) -> HashMap<String, Vec<Prompt>> {
+    let mut join_set = JoinSet::new();
+
+    for (server_name, managed_client) in clients {
+        if managed_client.capabilities.prompts.is_none() {
+            continue;
+        }
+        let server_name_cloned = server_name.clone();
+        let client_clone = managed_client.client.clone();
+        let startup_timeout = managed_client.startup_timeout;
+        join_set.spawn(async move {
+            let res = list_prompt_pages(&client_clone, startup_timeout).await;
+            (server_name_cloned, res)
+        });
+    }
+
+    let mut prompts = HashMap::with_capacity(join_set.len());
+    while let Some(join_res) = join_set.join_next().await {
+        let (server_name, list_result) = match join_res {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("Task panic when listing prompts for MCP server: {e:#}");
+                continue;
+            }
+        };
+        match list_result {
+            Ok(server_prompts) => {
+                prompts.insert(server_name, server_prompts);
+            }
+            Err(e) => {
+                warn!("Failed to list prompts for MCP server '{server_name}': {e:#}");
+            }
+        }
+    }
+
+    prompts
+}
+
+/// Fetches every page of a server's prompt listing by following
+/// `nextCursor`. Stops if the server hands back a cursor it already returned.
+async fn list_prompt_pages(client: &McpClientAdapter, timeout: Duration) -> Result<Vec<Prompt>> {
+    let mut prompts = Vec::new();
+    let mut seen_cursors = HashSet::new();
+    let mut cursor = None;
+    loop {
+        let params = cursor.clone().map(|cursor| ListPromptsRequestParams {
+            cursor: Some(cursor),
+        });
+        let page = client.list_prompts(params, Some(timeout)).await?;
+        prompts.extend(page.prompts);
+        match page.next_cursor {
+            Some(next) if seen_cursors.insert(next.clone()) => cursor = Some(next),
+            _ => return Ok(prompts),
+        }
+    }
+}
+
 fn is_valid_mcp_server_name(server_name: &str) -> bool {
     !server_name.is_empty()
         && server_name
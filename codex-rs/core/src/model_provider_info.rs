@@ -21,13 +21,16 @@ const DEFAULT_REQUEST_MAX_RETRIES: u64 = 4;
 const MAX_STREAM_MAX_RETRIES: u64 = 100;
 /// Hard cap for user-configured `request_max_retries`.
 const MAX_REQUEST_MAX_RETRIES: u64 = 100;
+/// Value of the `anthropic-version` header sent to the Anthropic Messages API.
+const ANTHROPIC_API_VERSION: &str = "2023-06-01";
 
 /// Wire protocol that the provider speaks. Most third-party services only
 /// implement the classic OpenAI Chat Completions JSON schema, whereas OpenAI
 /// itself (and a handful of others) additionally expose the more modern
-/// *Responses* API. The two protocols use different request/response shapes
-/// and *cannot* be auto-detected at runtime, therefore each provider entry
-/// must declare which one it expects.
+/// *Responses* API. Anthropic and Google models can also be reached through
+/// their native Messages and generateContent APIs. The protocols use different
+/// request/response shapes and *cannot* be auto-detected at runtime, therefore
+/// each provider entry must declare which one it expects.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum WireApi {
@@ -37,6 +40,12 @@ pub enum WireApi {
     /// Regular Chat Completions compatible with `/v1/chat/completions`.
     #[default]
     Chat,
+
+    /// The Anthropic Messages API at `/v1/messages`.
+    Anthropic,
+
+    /// The Gemini API at `/v1beta/models/{model}:streamGenerateContent`.
+    Gemini,
 }
 
 /// Serializable representation of a provider definition.
@@ -95,12 +104,16 @@ impl ModelProviderInfo {
     ///   • Bearer auth header when an API key is available.
     ///   • Auth token for OAuth.
     ///
+    /// The Anthropic and Gemini wire APIs send the API key in their own
+    /// headers instead, and `model` selects the Gemini endpoint.
+    ///
     /// If the provider declares an `env_key` but the variable is missing/empty, returns an [`Err`] identical to the
     /// one produced by [`ModelProviderInfo::api_key`].
     pub async fn create_request_builder<'a>(
         &'a self,
         client: &'a reqwest::Client,
         auth: &Option<CodexAuth>,
+        model: &str,
     ) -> crate::error::Result<reqwest::RequestBuilder> {
         let effective_auth = match self.api_key() {
             Ok(Some(key)) => Some(CodexAuth::from_api_key(&key)),
@@ -114,12 +127,21 @@ impl ModelProviderInfo {
             }
         };
 
-        let url = self.get_full_url(&effective_auth);
+        let url = self.get_full_url(&effective_auth, model);
 
         let mut builder = client.post(url);
 
+        if self.wire_api == WireApi::Anthropic {
+            builder = builder.header("anthropic-version", ANTHROPIC_API_VERSION);
+        }
+
         if let Some(auth) = effective_auth.as_ref() {
-            builder = builder.bearer_auth(auth.get_token().await?);
+            let token = auth.get_token().await?;
+            builder = match self.wire_api {
+                WireApi::Responses | WireApi::Chat => builder.bearer_auth(token),
+                WireApi::Anthropic => builder.header("x-api-key", token),
+                WireApi::Gemini => builder.header("x-goog-api-key", token),
+            };
         }
 
         Ok(self.apply_http_headers(builder))
@@ -138,17 +160,22 @@ impl ModelProviderInfo {
             })
     }
 
-    pub(crate) fn get_full_url(&self, auth: &Option<CodexAuth>) -> String {
-        let default_base_url = if matches!(
-            auth,
-            Some(CodexAuth {
-                mode: AuthMode::ChatGPT,
-                ..
-            })
-        ) {
-            "https://chatgpt.com/backend-api/codex"
-        } else {
-            "https://api.openai.com/v1"
+    pub(crate) fn get_full_url(&self, auth: &Option<CodexAuth>, model: &str) -> String {
+        let default_base_url = match self.wire_api {
+            WireApi::Anthropic => "https://api.anthropic.com/v1",
+            WireApi::Gemini => "https://generativelanguage.googleapis.com/v1beta",
+            WireApi::Responses | WireApi::Chat
+                if matches!(
+                    auth,
+                    Some(CodexAuth {
+                        mode: AuthMode::ChatGPT,
+                        ..
+                    })
+                ) =>
+            {
+                "https://chatgpt.com/backend-api/codex"
+            }
+            WireApi::Responses | WireApi::Chat => "https://api.openai.com/v1",
         };
         let query_string = self.get_query_string();
         let base_url = self
@@ -159,6 +186,16 @@ impl ModelProviderInfo {
         match self.wire_api {
             WireApi::Responses => format!("{base_url}/responses{query_string}"),
             WireApi::Chat => format!("{base_url}/chat/completions{query_string}"),
+            WireApi::Anthropic => format!("{base_url}/messages{query_string}"),
+            WireApi::Gemini => {
+                // `alt=sse` makes Gemini stream server-sent events instead of
+                // a JSON array.
+                let query_string = match query_string.strip_prefix('?') {
+                    Some(params) => format!("?alt=sse&{params}"),
+                    None => "?alt=sse".to_string(),
+                };
+                format!("{base_url}/models/{model}:streamGenerateContent{query_string}")
+            }
         }
     }
 
@@ -447,6 +484,32 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
         assert_eq!(expected_provider, provider);
     }
 
+    #[test]
+    fn native_wire_api_urls() {
+        let provider_toml = r#"
+name = "Gemini"
+env_key = "GEMINI_API_KEY"
+wire_api = "gemini"
+query_params = { key2 = "value" }
+        "#;
+        let provider: ModelProviderInfo = toml::from_str(provider_toml).unwrap();
+        assert_eq!(provider.wire_api, WireApi::Gemini);
+        assert_eq!(
+            provider.get_full_url(&None, "gemini-2.5-pro"),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-pro:streamGenerateContent?alt=sse&key2=value"
+        );
+
+        let provider = ModelProviderInfo {
+            wire_api: WireApi::Anthropic,
+            query_params: None,
+            ..provider
+        };
+        assert_eq!(
+            provider.get_full_url(&None, "claude-sonnet-4-5"),
+            "https://api.anthropic.com/v1/messages"
+        );
+    }
+
     #[test]
     fn detects_azure_responses_base_urls() {
         fn provider_for(base_url: &str) -> ModelProviderInfo {
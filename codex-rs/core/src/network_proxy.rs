@@ -0,0 +1,354 @@
+//! Local HTTP proxy through which sandboxed commands reach the hosts of a
+//! [`SandboxPolicy`] network allowlist.
+//!
+//! The sandbox only lets commands connect to the proxy. The proxy
+//! checks every `CONNECT host:port` tunnel and plain `http://` request against
+//! the allowlist before dialing out; commands find it through the usual
+//! `HTTP_PROXY`/`HTTPS_PROXY` variables.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tracing::debug;
+use tracing::warn;
+
+use crate::protocol::NetworkAllowlistEntry;
+use crate::protocol::SandboxPolicy;
+
+/// Requests whose head does not fit are rejected.
+const MAX_REQUEST_HEAD_BYTES: usize = 16 * 1024;
+
+const PROXY_ENV_VARS: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
+/// Running proxies by allowlist. They live as long as the process.
+static PROXIES: LazyLock<Mutex<HashMap<Vec<NetworkAllowlistEntry>, SocketAddr>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Start (or reuse) the proxy for the policy's network allowlist and point
+/// the proxy variables of `env` at it. Returns the port the sandbox must let
+/// commands connect to, or `None` when the policy has no allowlist.
+pub(crate) fn prepare_sandbox_env(
+    sandbox_policy: &SandboxPolicy,
+    env: &mut HashMap<String, String>,
+) -> io::Result<Option<u16>> {
+    let allowlist = sandbox_policy.network_allowlist();
+    if allowlist.is_empty() {
+        return Ok(None);
+    }
+    let addr = proxy_for(allowlist)?;
+    let url = format!("http://{addr}");
+    for var in PROXY_ENV_VARS {
+        env.insert(var.to_string(), url.clone());
+    }
+    // Connections that bypass the proxy are blocked by the sandbox anyway.
+    env.remove("NO_PROXY");
+    env.remove("no_proxy");
+    Ok(Some(addr.port()))
+}
+
+fn proxy_for(allowlist: &[NetworkAllowlistEntry]) -> io::Result<SocketAddr> {
+    let mut proxies = PROXIES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(addr) = proxies.get(allowlist) {
+        return Ok(*addr);
+    }
+
+    let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    listener.set_nonblocking(true)?;
+    let addr = listener.local_addr()?;
+    // The proxy gets its own runtime so it outlives the caller's.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let served: Arc<[NetworkAllowlistEntry]> = allowlist.into();
+    std::thread::Builder::new()
+        .name("codex-network-proxy".to_string())
+        .spawn(move || runtime.block_on(serve(listener, served)))?;
+
+    proxies.insert(allowlist.to_vec(), addr);
+    Ok(addr)
+}
+
+async fn serve(listener: std::net::TcpListener, allowlist: Arc<[NetworkAllowlistEntry]>) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(err) => {
+            warn!("failed to start network proxy: {err}");
+            return;
+        }
+    };
+    loop {
+        match listener.accept().await {
+            Ok((client, _)) => {
+                let allowlist = allowlist.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = handle_client(client, &allowlist).await {
+                        debug!("network proxy connection failed: {err}");
+                    }
+                });
+            }
+            Err(err) => warn!("network proxy failed to accept a connection: {err}"),
+        }
+    }
+}
+
+async fn handle_client(
+    mut client: TcpStream,
+    allowlist: &[NetworkAllowlistEntry],
+) -> io::Result<()> {
+    let (head, body) = read_request_head(&mut client).await?;
+    let Some(request) = ProxyRequest::parse(&head) else {
+        return respond(&mut client, "400 Bad Request", "malformed proxy request").await;
+    };
+    let ProxyRequest {
+        host,
+        port,
+        forward_head,
+    } = request;
+    if !allowlist.iter().any(|entry| entry.allows(&host, port)) {
+        let message = format!("{host}:{port} is not in the sandbox network allowlist");
+        return respond(&mut client, "403 Forbidden", &message).await;
+    }
+
+    let mut upstream = match TcpStream::connect((host.as_str(), port)).await {
+        Ok(upstream) => upstream,
+        Err(err) => {
+            let message = format!("failed to connect to {host}:{port}: {err}");
+            return respond(&mut client, "502 Bad Gateway", &message).await;
+        }
+    };
+    match forward_head {
+        Some(head) => upstream.write_all(head.as_bytes()).await?,
+        None => {
+            client
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await?
+        }
+    }
+    upstream.write_all(&body).await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+/// Read up to the blank line ending the request head. Returns the head and
+/// whatever was read past it.
+async fn read_request_head(client: &mut TcpStream) -> io::Result<(String, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            let body = buf.split_off(end + 4);
+            return Ok((String::from_utf8_lossy(&buf).into_owned(), body));
+        }
+        if buf.len() > MAX_REQUEST_HEAD_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "proxy request head too large",
+            ));
+        }
+        let n = client.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+}
+
+async fn respond(client: &mut TcpStream, status: &str, message: &str) -> io::Result<()> {
+    let body = format!("{message}\n");
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    client.write_all(response.as_bytes()).await
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ProxyRequest {
+    host: String,
+    port: u16,
+    /// Head to send upstream for plain HTTP requests; `None` for `CONNECT`
+    /// tunnels.
+    forward_head: Option<String>,
+}
+
+impl ProxyRequest {
+    fn parse(head: &str) -> Option<Self> {
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next()?.split(' ');
+        let (method, target, version) = (
+            request_line.next()?,
+            request_line.next()?,
+            request_line.next()?,
+        );
+
+        if method.eq_ignore_ascii_case("CONNECT") {
+            let (host, port) = split_host_port(target)?;
+            return Some(Self {
+                host,
+                port: port?,
+                forward_head: None,
+            });
+        }
+
+        // HTTPS requests always arrive as `CONNECT` tunnels.
+        let rest = target.strip_prefix("http://")?;
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let authority = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let (host, port) = split_host_port(authority)?;
+
+        // Send the request in origin form and close the upstream connection
+        // after one response so a kept-alive connection cannot be reused for
+        // another host.
+        let mut forward_head = format!("{method} {path} {version}\r\n");
+        for line in lines.filter(|line| !line.is_empty()) {
+            let name = line.split(':').next().unwrap_or_default().trim();
+            if ["connection", "proxy-connection", "proxy-authorization"]
+                .iter()
+                .any(|skipped| name.eq_ignore_ascii_case(skipped))
+            {
+                continue;
+            }
+            forward_head.push_str(line);
+            forward_head.push_str("\r\n");
+        }
+        forward_head.push_str("Connection: close\r\n\r\n");
+
+        Some(Self {
+            host,
+            port: port.unwrap_or(80),
+            forward_head: Some(forward_head),
+        })
+    }
+}
+
+fn split_host_port(authority: &str) -> Option<(String, Option<u16>)> {
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(rest) => {
+            let (host, rest) = rest.split_once(']')?;
+            match rest {
+                "" => (host, None),
+                rest => (host, Some(rest.strip_prefix(':')?)),
+            }
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let port = port.map(str::parse::<u16>).transpose().ok()?;
+    (!host.is_empty()).then(|| (host.to_ascii_lowercase(), port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_connect_and_plain_http_requests() {
+        assert_eq!(
+            ProxyRequest::parse("CONNECT Registry.npmjs.org:443 HTTP/1.1\r\nHost: x\r\n\r\n"),
+            Some(ProxyRequest {
+                host: "registry.npmjs.org".to_string(),
+                port: 443,
+                forward_head: None,
+            })
+        );
+        assert_eq!(
+            ProxyRequest::parse(
+                "GET http://example.com/index.html HTTP/1.1\r\nHost: example.com\r\nProxy-Connection: keep-alive\r\n\r\n"
+            ),
+            Some(ProxyRequest {
+                host: "example.com".to_string(),
+                port: 80,
+                forward_head: Some(
+                    "GET /index.html HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                ),
+            })
+        );
+        // A CONNECT without a port and absolute https:// URLs are rejected.
+        assert_eq!(
+            ProxyRequest::parse("CONNECT example.com HTTP/1.1\r\n\r\n"),
+            None
+        );
+        assert_eq!(
+            ProxyRequest::parse("GET https://example.com/ HTTP/1.1\r\n\r\n"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn tunnels_only_allowlisted_hosts() -> anyhow::Result<()> {
+        let upstream = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let upstream_port = upstream.local_addr()?.port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = upstream.accept().await {
+                let _ = stream.write_all(b"hello from upstream").await;
+            }
+        });
+
+        let allowlist = vec![
+            format!("127.0.0.1:{upstream_port}")
+                .parse()
+                .map_err(anyhow::Error::msg)?,
+        ];
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: Vec::new(),
+            deny_read: Vec::new(),
+            network_access: false,
+            network_allowlist: allowlist,
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+        };
+        let mut env = HashMap::from([("NO_PROXY".to_string(), "*".to_string())]);
+        let port = prepare_sandbox_env(&policy, &mut env)?.expect("allowlist has entries");
+        assert_eq!(
+            env.get("HTTPS_PROXY"),
+            Some(&format!("http://127.0.0.1:{port}"))
+        );
+        assert!(!env.contains_key("NO_PROXY"));
+
+        let mut allowed = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await?;
+        allowed
+            .write_all(format!("CONNECT 127.0.0.1:{upstream_port} HTTP/1.1\r\n\r\n").as_bytes())
+            .await?;
+        let mut response = String::new();
+        allowed.read_to_string(&mut response).await?;
+        assert_eq!(
+            response,
+            "HTTP/1.1 200 Connection Established\r\n\r\nhello from upstream"
+        );
+
+        let mut denied = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).await?;
+        denied
+            .write_all(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        denied.read_to_string(&mut response).await?;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"), "{response}");
+        Ok(())
+    }
+}
@@ -1,10 +1,8 @@
 use crate::model_family::ModelFamily;
+use crate::protocol::TokenUsage;
 
-/// Metadata about a model, particularly OpenAI models.
-/// We may want to consider including details like the pricing for
-/// input tokens, output tokens, etc., though users will need to be able to
-/// override this in config.toml, as this information can get out of date.
-/// Though this would help present more accurate pricing information in the UI.
+/// Metadata about a model, particularly OpenAI models. Pricing lives in
+/// [`ModelPricing`] since only budget enforcement needs it.
 #[derive(Debug)]
 pub(crate) struct ModelInfo {
     /// Size of the context window in tokens. This is the maximum size of the input context.
@@ -75,3 +73,60 @@ pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
         _ => None,
     }
 }
+
+/// Published list prices of a model in US dollars per million tokens. Used to
+/// estimate the spend tracked against `token_budget.max_cost_usd`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ModelPricing {
+    pub(crate) input_per_million: f64,
+    pub(crate) cached_input_per_million: f64,
+    pub(crate) output_per_million: f64,
+}
+
+impl ModelPricing {
+    const fn new(
+        input_per_million: f64,
+        cached_input_per_million: f64,
+        output_per_million: f64,
+    ) -> Self {
+        Self {
+            input_per_million,
+            cached_input_per_million,
+            output_per_million,
+        }
+    }
+
+    /// Estimated cost of `usage` in US dollars. Reasoning tokens are billed as
+    /// output and are already part of `output_tokens`.
+    pub(crate) fn cost_usd(&self, usage: &TokenUsage) -> f64 {
+        let uncached_input = usage.input_tokens.saturating_sub(usage.cached_input_tokens);
+        (uncached_input as f64 * self.input_per_million
+            + usage.cached_input_tokens as f64 * self.cached_input_per_million
+            + usage.output_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Pricing for the models we know about. Returns `None` for local and unknown
+/// models, whose usage is not counted against a cost budget.
+pub(crate) fn get_model_pricing(model_family: &ModelFamily) -> Option<ModelPricing> {
+    let slug = model_family.slug.as_str();
+    match slug {
+        // https://platform.openai.com/docs/pricing
+        "o3" => Some(ModelPricing::new(2.00, 0.50, 8.00)),
+        "o4-mini" => Some(ModelPricing::new(1.10, 0.275, 4.40)),
+        "codex-mini-latest" => Some(ModelPricing::new(1.50, 0.375, 6.00)),
+        "gpt-4.1" | "gpt-4.1-2025-04-14" => Some(ModelPricing::new(2.00, 0.50, 8.00)),
+        "gpt-4o" | "gpt-4o-2024-08-06" | "gpt-4o-2024-11-20" => {
+            Some(ModelPricing::new(2.50, 1.25, 10.00))
+        }
+        "gpt-4o-2024-05-13" => Some(ModelPricing::new(5.00, 5.00, 15.00)),
+        "gpt-3.5-turbo" => Some(ModelPricing::new(0.50, 0.50, 1.50)),
+        _ if slug.starts_with("gpt-5-mini") => Some(ModelPricing::new(0.25, 0.025, 2.00)),
+        _ if slug.starts_with("gpt-5-nano") => Some(ModelPricing::new(0.05, 0.005, 0.40)),
+        _ if slug.starts_with("gpt-5") || slug.starts_with("codex-") => {
+            Some(ModelPricing::new(1.25, 0.125, 10.00))
+        }
+        _ => None,
+    }
+}
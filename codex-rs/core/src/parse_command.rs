@@ -911,6 +911,50 @@ fn simplify_once(commands: &[ParsedCommand]) -> Option<Vec<ParsedCommand>> {
     None
 }
 
+/// Path of the file a [`ParsedCommand::Read`] command line reads, taken from
+/// the operands the reading command treats as files. `None` when the command
+/// changes directory, since the path would not be relative to its `cwd`.
+pub(crate) fn read_command_path(cmd: &str) -> Option<String> {
+    let tokens = normalize_tokens(&shlex_split(cmd)?);
+    let segments = split_on_connectors(&tokens);
+    if segments
+        .iter()
+        .any(|segment| segment.first().is_some_and(|head| head == "cd"))
+    {
+        return None;
+    }
+    segments
+        .iter()
+        .find_map(|segment| read_operand(segment))
+        .cloned()
+}
+
+/// The file operand of a single `cat`/`head`/`tail`/`nl`/`sed -n` command,
+/// skipping flags and the values they take.
+fn read_operand(tokens: &[String]) -> Option<&String> {
+    let (head, tail) = tokens.split_first()?;
+    let operands = match head.as_str() {
+        "cat" => {
+            return match tail {
+                [dashes, path] if dashes == "--" => Some(path),
+                [path] => Some(path),
+                _ => None,
+            };
+        }
+        "sed" => {
+            return tail.get(2).filter(|_| {
+                tail[0] == "-n" && is_valid_sed_n_arg(tail.get(1).map(String::as_str))
+            });
+        }
+        "head" | "tail" => skip_flag_values(tail, &["-n", "-c"]),
+        "nl" => skip_flag_values(tail, &["-s", "-w", "-v", "-i", "-b"]),
+        _ => return None,
+    };
+    operands
+        .into_iter()
+        .find(|operand| !operand.starts_with('-'))
+}
+
 /// Validates that this is a `sed -n 123,123p` command.
 fn is_valid_sed_n_arg(arg: Option<&str>) -> bool {
     let s = match arg {
@@ -0,0 +1,242 @@
+//! Per-command resource limits for sandboxed commands.
+//!
+//! `codex-linux-sandbox` applies the limits to itself right before it execs
+//! the command, so they hold for the command and everything it spawns. When a
+//! command is killed because of one of them, [`exceeded_limit`] tells which so
+//! the failure can be reported as [`crate::error::SandboxErr::ResourceLimit`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Limits applied to every sandboxed command. Unset limits are inherited
+/// from Codex unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// CPU time in seconds (`RLIMIT_CPU`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_time_secs: Option<u64>,
+
+    /// Virtual address space in bytes (`RLIMIT_AS`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_space_bytes: Option<u64>,
+
+    /// Number of open file descriptors (`RLIMIT_NOFILE`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<u64>,
+
+    /// Number of processes (`RLIMIT_NPROC`). The kernel counts every process
+    /// of the user, not only those started by the command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processes: Option<u64>,
+
+    /// Largest file the command may write, in bytes (`RLIMIT_FSIZE`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_file_size_bytes: Option<u64>,
+
+    /// Memory available to the command and its children, in bytes. Enforced
+    /// through a cgroup v2 `memory.max` on Linux, which requires the memory
+    /// controller to be delegated to the cgroup Codex runs in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_max_bytes: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl FromStr for ResourceLimits {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+/// The limit a command was killed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceLimitKind {
+    CpuTime,
+    OutputFileSize,
+    Memory,
+}
+
+impl fmt::Display for ResourceLimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ResourceLimitKind::CpuTime => "CPU time",
+            ResourceLimitKind::OutputFileSize => "output file size",
+            ResourceLimitKind::Memory => "memory",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Works out which configured limit killed the command, if any.
+///
+/// Exceeding CPU time or file size kills the command with `SIGXCPU` or
+/// `SIGXFSZ` (seen directly, or as `128 + signal` when a shell reports it),
+/// and `oom_killed` is set from the `oom_kill` counter of the command's
+/// memory cgroup. The other rlimits only make system calls fail; the command
+/// reports those errors itself, so they are not classified here.
+pub(crate) fn exceeded_limit(
+    limits: &ResourceLimits,
+    signal: Option<i32>,
+    exit_code: i32,
+    oom_killed: bool,
+) -> Option<ResourceLimitKind> {
+    if oom_killed && limits.memory_max_bytes.is_some() && exit_code != 0 {
+        return Some(ResourceLimitKind::Memory);
+    }
+
+    #[cfg(unix)]
+    {
+        const EXIT_CODE_SIGNAL_BASE: i32 = 128; // conventional shell: 128 + signal
+        let signal = signal.or_else(|| {
+            exit_code
+                .checked_sub(EXIT_CODE_SIGNAL_BASE)
+                .filter(|signal| *signal > 0)
+        });
+        match signal {
+            Some(libc::SIGXCPU) if limits.cpu_time_secs.is_some() => {
+                Some(ResourceLimitKind::CpuTime)
+            }
+            Some(libc::SIGXFSZ) if limits.output_file_size_bytes.is_some() => {
+                Some(ResourceLimitKind::OutputFileSize)
+            }
+            _ => None,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal;
+        None
+    }
+}
+
+/// Directory of the cgroup `codex-linux-sandbox` creates for the command it
+/// runs as `pid`. It is a sibling of the cgroup Codex runs in: cgroup v2 does
+/// not let a cgroup that holds processes hand the memory controller down to
+/// its children.
+#[cfg(target_os = "linux")]
+pub fn sandbox_cgroup_dir(pid: u32) -> std::io::Result<std::path::PathBuf> {
+    let cgroups = std::fs::read_to_string("/proc/self/cgroup")?;
+    let own = parse_unified_cgroup(&cgroups).ok_or_else(|| {
+        std::io::Error::other("not running in a cgroup v2 hierarchy (no `0::` entry)")
+    })?;
+    let parent = std::path::Path::new(own)
+        .parent()
+        .unwrap_or(std::path::Path::new("/"));
+    Ok(std::path::Path::new("/sys/fs/cgroup")
+        .join(parent.strip_prefix("/").unwrap_or(parent))
+        .join(format!("codex-sandbox-{pid}")))
+}
+
+/// Returns the cgroup v2 path from the contents of `/proc/<pid>/cgroup`.
+#[cfg(target_os = "linux")]
+fn parse_unified_cgroup(contents: &str) -> Option<&str> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(str::trim)
+}
+
+/// Whether the kernel OOM-killed anything in the cgroup of the sandboxed
+/// command `pid`. Also removes the cgroup, which is empty once the command
+/// and its children have exited.
+#[cfg(target_os = "linux")]
+pub(crate) fn take_cgroup_oom_kill(pid: u32) -> bool {
+    let Ok(dir) = sandbox_cgroup_dir(pid) else {
+        return false;
+    };
+    let oom_killed = std::fs::read_to_string(dir.join("memory.events"))
+        .ok()
+        .and_then(|events| {
+            events
+                .lines()
+                .find_map(|line| line.strip_prefix("oom_kill "))
+                .and_then(|count| count.trim().parse::<u64>().ok())
+        })
+        .is_some_and(|count| count > 0);
+    if let Err(err) = std::fs::remove_dir(&dir) {
+        tracing::debug!("failed to remove sandbox cgroup {}: {err}", dir.display());
+    }
+    oom_killed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_limits_from_json() {
+        let limits: ResourceLimits = r#"{"cpu_time_secs":30,"open_files":64}"#.parse().unwrap();
+        assert_eq!(
+            limits,
+            ResourceLimits {
+                cpu_time_secs: Some(30),
+                open_files: Some(64),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&limits).unwrap(),
+            r#"{"cpu_time_secs":30,"open_files":64}"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn detects_signal_limits() {
+        let limits = ResourceLimits {
+            cpu_time_secs: Some(1),
+            output_file_size_bytes: Some(1024),
+            ..Default::default()
+        };
+        assert_eq!(
+            exceeded_limit(&limits, Some(libc::SIGXCPU), -1, false),
+            Some(ResourceLimitKind::CpuTime)
+        );
+        assert_eq!(
+            exceeded_limit(&limits, None, 128 + libc::SIGXFSZ, false),
+            Some(ResourceLimitKind::OutputFileSize)
+        );
+        assert_eq!(
+            exceeded_limit(&ResourceLimits::default(), Some(libc::SIGXCPU), -1, false),
+            None
+        );
+    }
+
+    #[test]
+    fn ordinary_failures_are_not_resource_limits() {
+        let limits = ResourceLimits {
+            open_files: Some(16),
+            address_space_bytes: Some(1 << 30),
+            memory_max_bytes: Some(1 << 30),
+            ..Default::default()
+        };
+        // Whatever the command printed, a plain failure is not a limit.
+        assert_eq!(exceeded_limit(&limits, None, 1, false), None);
+        assert_eq!(
+            exceeded_limit(&limits, None, 1, true),
+            Some(ResourceLimitKind::Memory)
+        );
+        assert_eq!(exceeded_limit(&limits, None, 0, true), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_unified_cgroup_entry() {
+        let contents = "12:pids:/user.slice\n0::/user.slice/user-1000.slice/session-2.scope\n";
+        assert_eq!(
+            parse_unified_cgroup(contents),
+            Some("/user.slice/user-1000.slice/session-2.scope")
+        );
+        assert_eq!(parse_unified_cgroup("12:pids:/user.slice\n"), None);
+    }
+}
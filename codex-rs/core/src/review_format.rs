@@ -1,4 +1,10 @@
+use std::path::Path;
+
+use serde_json::Value;
+use serde_json::json;
+
 use crate::protocol::ReviewFinding;
+use crate::protocol::ReviewOutputEvent;
 
 // Note: We keep this module UI-agnostic. It returns plain strings that
 // higher layers (e.g., TUI) may style as needed.
@@ -53,3 +59,241 @@ pub fn format_review_findings_block(
 
     lines.join("\n")
 }
+
+/// Severity of a finding in CI output, derived from its priority
+/// (0 is the most urgent).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewSeverity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl ReviewSeverity {
+    /// P0 and P1 are errors, P2 warnings and everything else notes.
+    pub fn from_priority(priority: i32) -> Self {
+        match priority {
+            ..=1 => ReviewSeverity::Error,
+            2 => ReviewSeverity::Warning,
+            _ => ReviewSeverity::Note,
+        }
+    }
+
+    fn sarif_level(self) -> &'static str {
+        match self {
+            ReviewSeverity::Error => "error",
+            ReviewSeverity::Warning => "warning",
+            ReviewSeverity::Note => "note",
+        }
+    }
+
+    fn github_command(self) -> &'static str {
+        match self {
+            ReviewSeverity::Error => "error",
+            ReviewSeverity::Warning => "warning",
+            ReviewSeverity::Note => "notice",
+        }
+    }
+}
+
+/// Priorities the model is asked to use; each gets its own SARIF rule.
+const PRIORITIES: [i32; 4] = [0, 1, 2, 3];
+
+fn rule_id(priority: i32) -> String {
+    format!("codex-review/p{}", priority.clamp(0, 3))
+}
+
+/// Path of the finding relative to `base_dir` when possible, with `/`
+/// separators, since CI tools resolve locations against the checkout.
+fn relative_uri(item: &ReviewFinding, base_dir: &Path) -> String {
+    let path = &item.code_location.absolute_file_path;
+    path.strip_prefix(base_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// 1-based line range; models occasionally report line 0.
+fn line_range(item: &ReviewFinding) -> (u32, u32) {
+    let start = item.code_location.line_range.start.max(1);
+    let end = item.code_location.line_range.end.max(start);
+    (start, end)
+}
+
+/// Render a review as a SARIF 2.1.0 log so code-scanning dashboards can ingest
+/// the findings. Locations are relative to `base_dir` (typically the
+/// repository root).
+pub fn format_review_output_sarif(output: &ReviewOutputEvent, base_dir: &Path) -> Value {
+    let rules: Vec<Value> = PRIORITIES
+        .iter()
+        .map(|&priority| {
+            json!({
+                "id": rule_id(priority),
+                "name": format!("CodexReviewP{priority}"),
+                "shortDescription": { "text": format!("P{priority} issue found by Codex code review") },
+                "defaultConfiguration": {
+                    "level": ReviewSeverity::from_priority(priority).sarif_level(),
+                },
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = output
+        .findings
+        .iter()
+        .map(|finding| {
+            let (start, end) = line_range(finding);
+            json!({
+                "ruleId": rule_id(finding.priority),
+                "level": ReviewSeverity::from_priority(finding.priority).sarif_level(),
+                "message": { "text": format!("{}\n\n{}", finding.title, finding.body.trim()) },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": relative_uri(finding, base_dir) },
+                        "region": { "startLine": start, "endLine": end },
+                    },
+                }],
+                "properties": {
+                    "priority": finding.priority,
+                    "confidence": finding.confidence_score,
+                },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "codex-review",
+                    "informationUri": "https://github.com/openai/codex",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+            "properties": {
+                "overallCorrectness": output.overall_correctness,
+                "overallExplanation": output.overall_explanation,
+            },
+        }],
+    })
+}
+
+/// Format findings as GitHub Actions workflow commands
+/// (`::warning file=...,line=...::message`), one per line, so they show up as
+/// annotations on the pull request. Paths are relative to `base_dir`.
+pub fn format_review_findings_github_annotations(
+    findings: &[ReviewFinding],
+    base_dir: &Path,
+) -> String {
+    findings
+        .iter()
+        .map(|finding| {
+            let (start, end) = line_range(finding);
+            format!(
+                "::{} file={},line={start},endLine={end},title={}::{}",
+                ReviewSeverity::from_priority(finding.priority).github_command(),
+                escape_annotation_property(&relative_uri(finding, base_dir)),
+                escape_annotation_property(&finding.title),
+                escape_annotation_data(finding.body.trim()),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn escape_annotation_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_annotation_property(value: &str) -> String {
+    escape_annotation_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::ReviewCodeLocation;
+    use crate::protocol::ReviewLineRange;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn finding(title: &str, body: &str, priority: i32, start: u32, end: u32) -> ReviewFinding {
+        ReviewFinding {
+            title: title.to_string(),
+            body: body.to_string(),
+            confidence_score: 0.8,
+            priority,
+            code_location: ReviewCodeLocation {
+                absolute_file_path: PathBuf::from("/repo/src/lib.rs"),
+                line_range: ReviewLineRange { start, end },
+            },
+        }
+    }
+
+    #[test]
+    fn sarif_maps_priority_to_rules_and_levels() {
+        let output = ReviewOutputEvent {
+            findings: vec![
+                finding(
+                    "[P1] Off by one",
+                    "The loop skips the last element.\n",
+                    1,
+                    0,
+                    4,
+                ),
+                finding("[P3] Typo", "Misspelled identifier.", 3, 7, 7),
+            ],
+            overall_correctness: "patch is incorrect".to_string(),
+            ..Default::default()
+        };
+
+        let sarif = format_review_output_sarif(&output, Path::new("/repo"));
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let rules = &sarif["runs"][0]["tool"]["driver"]["rules"];
+        assert_eq!(rules[0]["id"], "codex-review/p0");
+        assert_eq!(rules[2]["defaultConfiguration"]["level"], "warning");
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "codex-review/p1");
+        assert_eq!(result["level"], "error");
+        assert_eq!(
+            result["message"]["text"],
+            "[P1] Off by one\n\nThe loop skips the last element."
+        );
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/lib.rs");
+        assert_eq!(location["region"]["startLine"], 1);
+        assert_eq!(location["region"]["endLine"], 4);
+        assert_eq!(sarif["runs"][0]["results"][1]["level"], "note");
+    }
+
+    #[test]
+    fn github_annotations_escape_titles_and_bodies() {
+        let findings = vec![
+            finding(
+                "[P0] Crash: unwrap on None, always",
+                "Line one.\nLine two at 100%.",
+                0,
+                3,
+                5,
+            ),
+            finding("[P2] Slow loop", "Quadratic.", 2, 9, 9),
+        ];
+
+        let annotations = format_review_findings_github_annotations(&findings, Path::new("/repo"));
+
+        assert_eq!(
+            annotations,
+            "::error file=src/lib.rs,line=3,endLine=5,title=[P0] Crash%3A unwrap on None%2C always::Line one.%0ALine two at 100%25.\n\
+             ::warning file=src/lib.rs,line=9,endLine=9,title=[P2] Slow loop::Quadratic."
+        );
+    }
+}
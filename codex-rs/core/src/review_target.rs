@@ -0,0 +1,375 @@
+//! Turns a typed [`ReviewTarget`] into the diff the reviewer looks at.
+
+use std::path::Path;
+use std::time::Duration;
+
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use crate::protocol::ReviewRequest;
+use crate::protocol::ReviewTarget;
+use crate::truncate::truncate_middle;
+
+/// Git's well-known empty tree, used as the base of repositories without
+/// commits.
+const EMPTY_TREE_SHA: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Diffs are computed on large repositories too, so allow more time than the
+/// quick metadata lookups in `git_info`.
+const GIT_DIFF_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Larger diffs are truncated in the middle; the reviewer can still inspect
+/// the omitted part with git.
+const MAX_DIFF_BYTES: usize = 256 * 1024;
+
+/// Fill in the prompt and hint of `request` from its target and append the
+/// diff to review. Requests without a target are returned unchanged.
+pub(crate) async fn resolve_review_request(
+    cwd: &Path,
+    request: ReviewRequest,
+) -> Result<ReviewRequest, String> {
+    let Some(target) = request.target.clone() else {
+        return Ok(request);
+    };
+
+    let (command, diff) = target_diff(cwd, &target, &request.paths).await?;
+    let hint = if request.user_facing_hint.trim().is_empty() {
+        default_hint(&target, &request.paths)
+    } else {
+        request.user_facing_hint.clone()
+    };
+    if diff.trim().is_empty() {
+        return Err(format!("Nothing to review: there are no {hint}."));
+    }
+
+    let instructions = if request.prompt.trim().is_empty() {
+        default_prompt(&target)
+    } else {
+        request.prompt.clone()
+    };
+    let (diff, omitted_tokens) = truncate_middle(&diff, MAX_DIFF_BYTES);
+    let truncation_note = match omitted_tokens {
+        Some(_) => {
+            format!("\n\nThe diff was truncated in the middle; run `{command}` to see all of it.")
+        }
+        None => String::new(),
+    };
+    let prompt = format!(
+        "{instructions}\n\nThe changes to review, as produced by `{command}`:\n\n```diff\n{}\n```{truncation_note}",
+        diff.trim_end()
+    );
+
+    Ok(ReviewRequest {
+        prompt,
+        user_facing_hint: hint,
+        ..request
+    })
+}
+
+fn default_prompt(target: &ReviewTarget) -> String {
+    match target {
+        ReviewTarget::WorkingTree => "Review the current code changes (staged, unstaged, and untracked files) and provide prioritized findings.".to_string(),
+        ReviewTarget::Staged => {
+            "Review the staged code changes and provide prioritized findings.".to_string()
+        }
+        ReviewTarget::BaseBranch { branch } => format!(
+            "Review the code changes that would be merged into the {branch} branch. Provide prioritized, actionable findings."
+        ),
+        ReviewTarget::Commit { sha } => format!(
+            "Review the code changes introduced by commit {sha}. Provide prioritized, actionable findings."
+        ),
+        ReviewTarget::CommitRange { from, to } => format!(
+            "Review the code changes between {from} and {to}. Provide prioritized, actionable findings."
+        ),
+    }
+}
+
+fn default_hint(target: &ReviewTarget, paths: &[String]) -> String {
+    let hint = match target {
+        ReviewTarget::WorkingTree => "current changes".to_string(),
+        ReviewTarget::Staged => "staged changes".to_string(),
+        ReviewTarget::BaseBranch { branch } => format!("changes against '{branch}'"),
+        ReviewTarget::Commit { sha } => {
+            let short: String = sha.chars().take(7).collect();
+            format!("changes in commit {short}")
+        }
+        ReviewTarget::CommitRange { from, to } => format!("changes in {from}..{to}"),
+    };
+    if paths.is_empty() {
+        hint
+    } else {
+        format!("{hint} in {}", paths.join(", "))
+    }
+}
+
+/// Returns the command line shown to the reviewer and the diff it produced.
+async fn target_diff(
+    cwd: &Path,
+    target: &ReviewTarget,
+    paths: &[String],
+) -> Result<(String, String), String> {
+    let args: Vec<String> = match target {
+        ReviewTarget::WorkingTree => {
+            let base = match git(cwd, &["rev-parse", "--verify", "--quiet", "HEAD"]).await {
+                Ok(_) => "HEAD".to_string(),
+                Err(_) => EMPTY_TREE_SHA.to_string(),
+            };
+            vec!["diff".to_string(), base]
+        }
+        ReviewTarget::Staged => vec!["diff".to_string(), "--cached".to_string()],
+        ReviewTarget::BaseBranch { branch } => {
+            let branch = revision(branch)?;
+            let upstream_spec = format!("{branch}@{{upstream}}");
+            let base = git(
+                cwd,
+                &[
+                    "rev-parse",
+                    "--abbrev-ref",
+                    "--symbolic-full-name",
+                    &upstream_spec,
+                ],
+            )
+            .await
+            .map(|upstream| upstream.trim().to_string())
+            .unwrap_or_else(|_| branch.to_string());
+            let merge_base = git(cwd, &["merge-base", "HEAD", &base]).await?;
+            vec!["diff".to_string(), merge_base.trim().to_string()]
+        }
+        ReviewTarget::Commit { sha } => vec![
+            "show".to_string(),
+            "--format=".to_string(),
+            revision(sha)?.to_string(),
+        ],
+        ReviewTarget::CommitRange { from, to } => vec![
+            "diff".to_string(),
+            revision(from)?.to_string(),
+            revision(to)?.to_string(),
+        ],
+    };
+
+    let mut full_args: Vec<&str> = args.iter().map(String::as_str).collect();
+    full_args.extend(["--no-color", "--no-ext-diff", "--no-textconv", "--"]);
+    full_args.extend(paths.iter().map(String::as_str));
+    let mut diff = git(cwd, &full_args).await?;
+
+    if *target == ReviewTarget::WorkingTree {
+        diff.push_str(&untracked_diff(cwd, paths).await?);
+    }
+
+    let mut command = format!("git {}", args.join(" "));
+    if !paths.is_empty() {
+        command.push_str(&format!(" -- {}", paths.join(" ")));
+    }
+    Ok((command, diff))
+}
+
+/// `git diff` output that adds each untracked (and not ignored) file.
+async fn untracked_diff(cwd: &Path, paths: &[String]) -> Result<String, String> {
+    let mut args = vec!["ls-files", "--others", "--exclude-standard", "-z", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    let listing = git(cwd, &args).await?;
+
+    let null_device = if cfg!(windows) { "NUL" } else { "/dev/null" };
+    let mut diff = String::new();
+    for file in listing.split('\0').filter(|file| !file.is_empty()) {
+        // `--no-index` exits with 1 when the files differ, which they always do.
+        let output = run_git(
+            cwd,
+            &[
+                "diff",
+                "--no-color",
+                "--no-ext-diff",
+                "--no-textconv",
+                "--no-index",
+                "--",
+                null_device,
+                file,
+            ],
+        )
+        .await?;
+        diff.push_str(&String::from_utf8_lossy(&output.stdout));
+    }
+    Ok(diff)
+}
+
+/// Reject revisions git would parse as options.
+fn revision(rev: &str) -> Result<&str, String> {
+    let rev = rev.trim();
+    if rev.is_empty() || rev.starts_with('-') {
+        return Err(format!("Invalid git revision `{rev}`."));
+    }
+    Ok(rev)
+}
+
+/// Run git and return its stdout, failing on a non-zero exit status.
+async fn git(cwd: &Path, args: &[&str]) -> Result<String, String> {
+    let output = run_git(cwd, args).await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            stderr.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+async fn run_git(cwd: &Path, args: &[&str]) -> Result<std::process::Output, String> {
+    match timeout(
+        GIT_DIFF_TIMEOUT,
+        Command::new("git").args(args).current_dir(cwd).output(),
+    )
+    .await
+    {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(err)) => Err(format!("Failed to run git: {err}")),
+        Err(_) => Err(format!(
+            "`git {}` timed out after {}s",
+            args.join(" "),
+            GIT_DIFF_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::process::Command as StdCommand;
+    use tempfile::TempDir;
+
+    fn run(cwd: &Path, args: &[&str]) {
+        let status = StdCommand::new("git")
+            .args([
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=test@example.com",
+            ])
+            .args(args)
+            .current_dir(cwd)
+            .status()
+            .expect("run git");
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn repo_with_commit() -> TempDir {
+        let repo = TempDir::new().expect("tempdir");
+        run(repo.path(), &["init", "-q", "--initial-branch=main"]);
+        std::fs::write(repo.path().join("a.txt"), "one\n").expect("write");
+        run(repo.path(), &["add", "a.txt"]);
+        run(repo.path(), &["commit", "-q", "-m", "init"]);
+        repo
+    }
+
+    fn request(target: ReviewTarget, paths: &[&str]) -> ReviewRequest {
+        ReviewRequest {
+            target: Some(target),
+            paths: paths.iter().map(ToString::to_string).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn working_tree_includes_untracked_files() {
+        let repo = repo_with_commit();
+        std::fs::write(repo.path().join("a.txt"), "two\n").expect("write");
+        std::fs::write(repo.path().join("new.txt"), "fresh\n").expect("write");
+
+        let resolved = resolve_review_request(repo.path(), request(ReviewTarget::WorkingTree, &[]))
+            .await
+            .expect("resolve");
+
+        assert_eq!(resolved.user_facing_hint, "current changes");
+        assert!(
+            resolved
+                .prompt
+                .starts_with("Review the current code changes")
+        );
+        assert!(resolved.prompt.contains("as produced by `git diff HEAD`"));
+        assert!(resolved.prompt.contains("+two"));
+        assert!(resolved.prompt.contains("+fresh"));
+    }
+
+    #[tokio::test]
+    async fn staged_and_paths_narrow_the_diff() {
+        let repo = repo_with_commit();
+        std::fs::write(repo.path().join("a.txt"), "staged\n").expect("write");
+        std::fs::write(repo.path().join("b.txt"), "other\n").expect("write");
+        run(repo.path(), &["add", "a.txt", "b.txt"]);
+
+        let resolved = resolve_review_request(
+            repo.path(),
+            ReviewRequest {
+                prompt: "Focus on typos.".to_string(),
+                ..request(ReviewTarget::Staged, &["a.txt"])
+            },
+        )
+        .await
+        .expect("resolve");
+
+        assert_eq!(resolved.user_facing_hint, "staged changes in a.txt");
+        assert!(resolved.prompt.starts_with("Focus on typos."));
+        assert!(resolved.prompt.contains("+staged"));
+        assert!(!resolved.prompt.contains("+other"));
+    }
+
+    #[tokio::test]
+    async fn commit_and_range_targets_use_history() {
+        let repo = repo_with_commit();
+        std::fs::write(repo.path().join("a.txt"), "second\n").expect("write");
+        run(repo.path(), &["commit", "-q", "-am", "second"]);
+
+        let resolved = resolve_review_request(
+            repo.path(),
+            request(
+                ReviewTarget::Commit {
+                    sha: "HEAD".to_string(),
+                },
+                &[],
+            ),
+        )
+        .await
+        .expect("resolve commit");
+        assert!(resolved.prompt.contains("+second"));
+
+        let resolved = resolve_review_request(
+            repo.path(),
+            request(
+                ReviewTarget::CommitRange {
+                    from: "HEAD~1".to_string(),
+                    to: "HEAD".to_string(),
+                },
+                &[],
+            ),
+        )
+        .await
+        .expect("resolve range");
+        assert_eq!(resolved.user_facing_hint, "changes in HEAD~1..HEAD");
+        assert!(resolved.prompt.contains("-one\n+second"));
+    }
+
+    #[tokio::test]
+    async fn rejects_empty_diffs_and_option_like_revisions() {
+        let repo = repo_with_commit();
+
+        let err = resolve_review_request(repo.path(), request(ReviewTarget::Staged, &[]))
+            .await
+            .expect_err("nothing staged");
+        assert_eq!(err, "Nothing to review: there are no staged changes.");
+
+        let err = resolve_review_request(
+            repo.path(),
+            request(
+                ReviewTarget::Commit {
+                    sha: "--output=/tmp/x".to_string(),
+                },
+                &[],
+            ),
+        )
+        .await
+        .expect_err("option-like revision");
+        assert_eq!(err, "Invalid git revision `--output=/tmp/x`.");
+    }
+}
@@ -0,0 +1,649 @@
+//! Rendering of recorded rollouts as human-readable transcripts.
+//!
+//! A [`Transcript`] is built from the [`RolloutItem`]s of a session and can be
+//! rendered as Markdown, as a standalone HTML page, or serialized as JSON.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use codex_protocol::ConversationId;
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::models::ShellToolCallParams;
+use codex_protocol::protocol::InputMessageKind;
+use codex_protocol::protocol::RolloutItem;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::EventMsg;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Transcript {
+    pub conversation_id: Option<ConversationId>,
+    /// RFC3339 timestamp of the session start.
+    pub started_at: Option<String>,
+    pub cwd: Option<PathBuf>,
+    /// Model used for the first turn of the session.
+    pub model: Option<String>,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptEntry {
+    UserMessage {
+        text: String,
+    },
+    AgentMessage {
+        text: String,
+    },
+    /// Reasoning summary shown to the user.
+    Reasoning {
+        text: String,
+    },
+    Exec {
+        command: String,
+        exit_code: Option<i32>,
+        output: Option<String>,
+    },
+    /// An `apply_patch` call, with the patch rendered as a unified diff.
+    Patch {
+        diff: String,
+        output: Option<String>,
+    },
+    ToolCall {
+        name: String,
+        arguments: String,
+        output: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+struct ApplyPatchArgs {
+    input: String,
+}
+
+#[derive(Deserialize)]
+struct ExecOutputJson {
+    output: String,
+    metadata: ExecOutputMetadataJson,
+}
+
+#[derive(Deserialize)]
+struct ExecOutputMetadataJson {
+    exit_code: i32,
+}
+
+impl Transcript {
+    pub fn from_rollout_items(items: &[RolloutItem]) -> Self {
+        let mut transcript = Transcript {
+            conversation_id: None,
+            started_at: None,
+            cwd: None,
+            model: None,
+            entries: Vec::new(),
+        };
+        // Index into `entries` of the call that produced each call id.
+        let mut calls: HashMap<String, usize> = HashMap::new();
+
+        for item in items {
+            match item {
+                RolloutItem::SessionMeta(meta_line) if transcript.conversation_id.is_none() => {
+                    transcript.conversation_id = Some(meta_line.meta.id);
+                    transcript.started_at = Some(meta_line.meta.timestamp.clone());
+                    transcript.cwd = Some(meta_line.meta.cwd.clone());
+                }
+                RolloutItem::TurnContext(turn_context) if transcript.model.is_none() => {
+                    transcript.model = Some(turn_context.model.clone());
+                }
+                RolloutItem::EventMsg(EventMsg::UserMessage(event)) => {
+                    if matches!(event.kind, None | Some(InputMessageKind::Plain)) {
+                        transcript.push(TranscriptEntry::UserMessage {
+                            text: event.message.trim().to_string(),
+                        });
+                    }
+                }
+                RolloutItem::EventMsg(EventMsg::AgentMessage(event)) => {
+                    transcript.push(TranscriptEntry::AgentMessage {
+                        text: event.message.trim().to_string(),
+                    });
+                }
+                RolloutItem::EventMsg(EventMsg::AgentReasoning(event)) => {
+                    transcript.push(TranscriptEntry::Reasoning {
+                        text: event.text.trim().to_string(),
+                    });
+                }
+                RolloutItem::ResponseItem(ResponseItem::LocalShellCall {
+                    call_id,
+                    action: LocalShellAction::Exec(action),
+                    ..
+                }) => {
+                    let index = transcript.push(TranscriptEntry::Exec {
+                        command: command_text(&action.command),
+                        exit_code: None,
+                        output: None,
+                    });
+                    if let Some(call_id) = call_id {
+                        calls.insert(call_id.clone(), index);
+                    }
+                }
+                RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                    name,
+                    arguments,
+                    call_id,
+                    ..
+                }) => {
+                    let entry = match name.as_str() {
+                        "shell" | "container.exec" => {
+                            serde_json::from_str::<ShellToolCallParams>(arguments)
+                                .ok()
+                                .map(|params| TranscriptEntry::Exec {
+                                    command: command_text(&params.command),
+                                    exit_code: None,
+                                    output: None,
+                                })
+                        }
+                        "apply_patch" => serde_json::from_str::<ApplyPatchArgs>(arguments)
+                            .ok()
+                            .map(|args| TranscriptEntry::Patch {
+                                diff: patch_to_diff(&args.input),
+                                output: None,
+                            }),
+                        _ => None,
+                    };
+                    let entry = entry.unwrap_or_else(|| TranscriptEntry::ToolCall {
+                        name: name.clone(),
+                        arguments: arguments.clone(),
+                        output: None,
+                    });
+                    let index = transcript.push(entry);
+                    calls.insert(call_id.clone(), index);
+                }
+                RolloutItem::ResponseItem(ResponseItem::CustomToolCall {
+                    name,
+                    input,
+                    call_id,
+                    ..
+                }) => {
+                    let entry = if name == "apply_patch" {
+                        TranscriptEntry::Patch {
+                            diff: patch_to_diff(input),
+                            output: None,
+                        }
+                    } else {
+                        TranscriptEntry::ToolCall {
+                            name: name.clone(),
+                            arguments: input.clone(),
+                            output: None,
+                        }
+                    };
+                    let index = transcript.push(entry);
+                    calls.insert(call_id.clone(), index);
+                }
+                RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput { call_id, output }) => {
+                    if let Some(index) = calls.get(call_id) {
+                        transcript.set_output(*index, &output.content);
+                    }
+                }
+                RolloutItem::ResponseItem(ResponseItem::CustomToolCallOutput {
+                    call_id,
+                    output,
+                }) => {
+                    if let Some(index) = calls.get(call_id) {
+                        transcript.set_output(*index, output);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        transcript
+    }
+
+    fn push(&mut self, entry: TranscriptEntry) -> usize {
+        self.entries.push(entry);
+        self.entries.len() - 1
+    }
+
+    fn set_output(&mut self, index: usize, content: &str) {
+        let Some(entry) = self.entries.get_mut(index) else {
+            return;
+        };
+        match entry {
+            TranscriptEntry::Exec {
+                exit_code, output, ..
+            } => match serde_json::from_str::<ExecOutputJson>(content) {
+                Ok(parsed) => {
+                    *exit_code = Some(parsed.metadata.exit_code);
+                    *output = Some(parsed.output);
+                }
+                Err(_) => *output = Some(content.to_string()),
+            },
+            TranscriptEntry::Patch { output, .. } => {
+                let content = serde_json::from_str::<ExecOutputJson>(content)
+                    .map(|parsed| parsed.output)
+                    .unwrap_or_else(|_| content.to_string());
+                *output = Some(content);
+            }
+            TranscriptEntry::ToolCall { output, .. } => *output = Some(content.to_string()),
+            TranscriptEntry::UserMessage { .. }
+            | TranscriptEntry::AgentMessage { .. }
+            | TranscriptEntry::Reasoning { .. } => {}
+        }
+    }
+
+    fn title(&self) -> String {
+        match self.conversation_id {
+            Some(id) => format!("Codex session {id}"),
+            None => "Codex session".to_string(),
+        }
+    }
+
+    fn details(&self) -> Vec<(&'static str, String)> {
+        let mut details = Vec::new();
+        if let Some(started_at) = &self.started_at {
+            details.push(("Started", started_at.clone()));
+        }
+        if let Some(model) = &self.model {
+            details.push(("Model", model.clone()));
+        }
+        if let Some(cwd) = &self.cwd {
+            details.push(("Working directory", cwd.display().to_string()));
+        }
+        details
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.title());
+        for (label, value) in self.details() {
+            let _ = writeln!(out, "- {label}: {}", inline_code(&value));
+        }
+
+        for entry in &self.entries {
+            out.push('\n');
+            match entry {
+                TranscriptEntry::UserMessage { text } => {
+                    let _ = write!(out, "## User\n\n{text}\n");
+                }
+                TranscriptEntry::AgentMessage { text } => {
+                    let _ = write!(out, "## Codex\n\n{text}\n");
+                }
+                TranscriptEntry::Reasoning { text } => {
+                    out.push_str("> **Reasoning**\n>\n");
+                    for line in text.lines() {
+                        let _ = writeln!(out, "> {line}");
+                    }
+                }
+                TranscriptEntry::Exec {
+                    command,
+                    exit_code,
+                    output,
+                } => {
+                    let status = exit_code
+                        .map(|code| format!(" (exit code {code})"))
+                        .unwrap_or_default();
+                    let _ = write!(
+                        out,
+                        "### Ran command{status}\n\n{}",
+                        fenced("shell", command)
+                    );
+                    if let Some(output) = output.as_deref().filter(|o| !o.trim().is_empty()) {
+                        let _ = write!(out, "\n{}", fenced("text", output));
+                    }
+                }
+                TranscriptEntry::Patch { diff, output } => {
+                    let _ = write!(out, "### Applied patch\n\n{}", fenced("diff", diff));
+                    if let Some(output) = output.as_deref().filter(|o| !o.trim().is_empty()) {
+                        let _ = write!(out, "\n{}", fenced("text", output));
+                    }
+                }
+                TranscriptEntry::ToolCall {
+                    name,
+                    arguments,
+                    output,
+                } => {
+                    let _ = write!(
+                        out,
+                        "### Called {}\n\n{}",
+                        inline_code(name),
+                        fenced("json", arguments)
+                    );
+                    if let Some(output) = output.as_deref().filter(|o| !o.trim().is_empty()) {
+                        let _ = write!(out, "\n{}", fenced("text", output));
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title());
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+        );
+        let details = self.details();
+        if !details.is_empty() {
+            out.push_str("<dl>\n");
+            for (label, value) in details {
+                let _ = writeln!(
+                    out,
+                    "<dt>{label}</dt><dd><code>{}</code></dd>",
+                    escape_html(&value)
+                );
+            }
+            out.push_str("</dl>\n");
+        }
+
+        for entry in &self.entries {
+            match entry {
+                TranscriptEntry::UserMessage { text } => {
+                    let _ = writeln!(
+                        out,
+                        "<section class=\"user\"><h2>User</h2><div class=\"text\">{}</div></section>",
+                        escape_html(text)
+                    );
+                }
+                TranscriptEntry::AgentMessage { text } => {
+                    let _ = writeln!(
+                        out,
+                        "<section class=\"agent\"><h2>Codex</h2><div class=\"text\">{}</div></section>",
+                        escape_html(text)
+                    );
+                }
+                TranscriptEntry::Reasoning { text } => {
+                    let _ = writeln!(
+                        out,
+                        "<details class=\"reasoning\"><summary>Reasoning</summary><div class=\"text\">{}</div></details>",
+                        escape_html(text)
+                    );
+                }
+                TranscriptEntry::Exec {
+                    command,
+                    exit_code,
+                    output,
+                } => {
+                    let status = match exit_code {
+                        Some(0) => "<span class=\"ok\">exit code 0</span>".to_string(),
+                        Some(code) => format!("<span class=\"failed\">exit code {code}</span>"),
+                        None => String::new(),
+                    };
+                    let _ = write!(
+                        out,
+                        "<section class=\"exec\"><h3>Ran command {status}</h3><pre class=\"command\">$ {}</pre>",
+                        escape_html(command)
+                    );
+                    push_html_output(&mut out, output.as_deref());
+                    out.push_str("</section>\n");
+                }
+                TranscriptEntry::Patch { diff, output } => {
+                    out.push_str(
+                        "<section class=\"patch\"><h3>Applied patch</h3><pre class=\"diff\">",
+                    );
+                    for line in diff.lines() {
+                        let class = match line.chars().next() {
+                            Some('+') if !line.starts_with("+++") => "add",
+                            Some('-') if !line.starts_with("---") => "del",
+                            Some('@') => "hunk",
+                            _ => "ctx",
+                        };
+                        let _ =
+                            writeln!(out, "<span class=\"{class}\">{}</span>", escape_html(line));
+                    }
+                    out.push_str("</pre>");
+                    push_html_output(&mut out, output.as_deref());
+                    out.push_str("</section>\n");
+                }
+                TranscriptEntry::ToolCall {
+                    name,
+                    arguments,
+                    output,
+                } => {
+                    let _ = write!(
+                        out,
+                        "<section class=\"tool\"><h3>Called <code>{}</code></h3><pre>{}</pre>",
+                        escape_html(name),
+                        escape_html(arguments)
+                    );
+                    push_html_output(&mut out, output.as_deref());
+                    out.push_str("</section>\n");
+                }
+            }
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:960px;margin:2em auto;padding:0 1em;line-height:1.5}\
+dl{display:grid;grid-template-columns:max-content auto;gap:.25em 1em}dd{margin:0}\
+section,details{margin:1.5em 0}.text{white-space:pre-wrap}\
+.user{border-left:4px solid #0969da;padding-left:1em}.agent{border-left:4px solid #8250df;padding-left:1em}\
+.reasoning{color:#57606a;font-style:italic}\
+pre{background:#f6f8fa;padding:.75em;overflow-x:auto;white-space:pre-wrap}\
+.add{color:#1a7f37}.del{color:#cf222e}.hunk{color:#0969da}.ok{color:#1a7f37}.failed{color:#cf222e}";
+
+fn push_html_output(out: &mut String, output: Option<&str>) {
+    if let Some(output) = output.filter(|o| !o.trim().is_empty()) {
+        let _ = write!(
+            out,
+            "<details><summary>Output</summary><pre class=\"output\">{}</pre></details>",
+            escape_html(output)
+        );
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Wraps `content` in a code fence long enough not to be closed by any
+/// backtick run inside it.
+fn fenced(lang: &str, content: &str) -> String {
+    let fence = "`".repeat(longest_backtick_run(content).max(2) + 1);
+    let content = content.trim_end_matches('\n');
+    format!("{fence}{lang}\n{content}\n{fence}\n")
+}
+
+fn inline_code(text: &str) -> String {
+    let ticks = "`".repeat(longest_backtick_run(text) + 1);
+    let pad = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{ticks}{pad}{text}{pad}{ticks}")
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// Commands run through `bash -lc` are shown as their script.
+fn command_text(command: &[String]) -> String {
+    match command {
+        [shell, flag, script] if shell.ends_with("sh") && (flag == "-lc" || flag == "-c") => {
+            script.clone()
+        }
+        _ => shlex::try_join(command.iter().map(String::as_str))
+            .unwrap_or_else(|_| command.join(" ")),
+    }
+}
+
+/// Converts an `apply_patch` envelope into a unified diff.
+fn patch_to_diff(patch: &str) -> String {
+    let mut diff = String::new();
+    let mut lines = patch.lines().peekable();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+        if let Some(path) = trimmed.strip_prefix("*** Add File: ") {
+            let _ = write!(diff, "--- /dev/null\n+++ b/{}\n", path.trim());
+        } else if let Some(path) = trimmed.strip_prefix("*** Delete File: ") {
+            let _ = write!(diff, "--- a/{}\n+++ /dev/null\n", path.trim());
+        } else if let Some(path) = trimmed.strip_prefix("*** Update File: ") {
+            let path = path.trim();
+            let new_path = lines
+                .next_if(|next| next.trim_start().starts_with("*** Move to: "))
+                .and_then(|next| next.trim_start().strip_prefix("*** Move to: "))
+                .map(str::trim)
+                .unwrap_or(path);
+            let _ = write!(diff, "--- a/{path}\n+++ b/{new_path}\n");
+        } else if trimmed.starts_with("*** Begin Patch")
+            || trimmed.starts_with("*** End Patch")
+            || trimmed.starts_with("*** End of File")
+        {
+            continue;
+        } else {
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use codex_protocol::protocol::AgentMessageEvent;
+    use codex_protocol::protocol::AgentReasoningEvent;
+    use codex_protocol::protocol::SessionMeta;
+    use codex_protocol::protocol::SessionMetaLine;
+    use codex_protocol::protocol::UserMessageEvent;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn sample_items() -> Vec<RolloutItem> {
+        vec![
+            RolloutItem::SessionMeta(SessionMetaLine {
+                meta: SessionMeta {
+                    timestamp: "2025-01-02T03:04:05Z".to_string(),
+                    cwd: PathBuf::from("/repo"),
+                    ..Default::default()
+                },
+                git: None,
+            }),
+            RolloutItem::EventMsg(EventMsg::UserMessage(UserMessageEvent {
+                message: "fix the <build>".to_string(),
+                kind: Some(InputMessageKind::Plain),
+                images: None,
+            })),
+            RolloutItem::EventMsg(EventMsg::AgentReasoning(AgentReasoningEvent {
+                text: "Looking at the build".to_string(),
+            })),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: json!({ "command": ["bash", "-lc", "cargo build"] }).to_string(),
+                call_id: "call-1".to_string(),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCallOutput {
+                call_id: "call-1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: json!({
+                        "output": "error: missing `;`",
+                        "metadata": { "exit_code": 101, "duration_seconds": 1.5 }
+                    })
+                    .to_string(),
+                    success: Some(false),
+                },
+            }),
+            RolloutItem::ResponseItem(ResponseItem::CustomToolCall {
+                id: None,
+                status: None,
+                call_id: "call-2".to_string(),
+                name: "apply_patch".to_string(),
+                input: "*** Begin Patch\n*** Update File: src/lib.rs\n@@\n-let x = 1\n+let x = 1;\n*** End Patch".to_string(),
+            }),
+            RolloutItem::ResponseItem(ResponseItem::CustomToolCallOutput {
+                call_id: "call-2".to_string(),
+                output: "Success. Updated the following files:\nM src/lib.rs".to_string(),
+            }),
+            RolloutItem::EventMsg(EventMsg::AgentMessage(AgentMessageEvent {
+                message: "Fixed.".to_string(),
+            })),
+        ]
+    }
+
+    #[test]
+    fn transcript_pairs_calls_with_outputs() {
+        let transcript = Transcript::from_rollout_items(&sample_items());
+
+        assert_eq!(
+            transcript.entries,
+            vec![
+                TranscriptEntry::UserMessage {
+                    text: "fix the <build>".to_string()
+                },
+                TranscriptEntry::Reasoning {
+                    text: "Looking at the build".to_string()
+                },
+                TranscriptEntry::Exec {
+                    command: "cargo build".to_string(),
+                    exit_code: Some(101),
+                    output: Some("error: missing `;`".to_string()),
+                },
+                TranscriptEntry::Patch {
+                    diff: "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@\n-let x = 1\n+let x = 1;\n"
+                        .to_string(),
+                    output: Some("Success. Updated the following files:\nM src/lib.rs".to_string()),
+                },
+                TranscriptEntry::AgentMessage {
+                    text: "Fixed.".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn markdown_and_html_render_every_entry() {
+        let transcript = Transcript::from_rollout_items(&sample_items());
+
+        let markdown = transcript.to_markdown();
+        assert!(markdown.contains("- Working directory: `/repo`"));
+        assert!(markdown.contains("## User\n\nfix the <build>\n"));
+        assert!(markdown.contains("> **Reasoning**\n>\n> Looking at the build\n"));
+        assert!(
+            markdown.contains("### Ran command (exit code 101)\n\n```shell\ncargo build\n```\n")
+        );
+        assert!(markdown.contains("```text\nerror: missing `;`\n```\n"));
+        assert!(markdown.contains("```diff\n--- a/src/lib.rs\n"));
+        assert!(markdown.ends_with("## Codex\n\nFixed.\n"));
+
+        let html = transcript.to_html();
+        assert!(html.contains("<div class=\"text\">fix the &lt;build&gt;</div>"));
+        assert!(html.contains("<span class=\"failed\">exit code 101</span>"));
+        assert!(html.contains("<span class=\"add\">+let x = 1;</span>"));
+        assert!(html.ends_with("</html>\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&transcript.to_json().unwrap()).unwrap();
+        assert_eq!(json["entries"][2]["type"], "exec");
+        assert_eq!(json["entries"][2]["exit_code"], 101);
+    }
+
+    #[test]
+    fn fences_outlast_backticks_in_content() {
+        assert_eq!(
+            fenced("text", "a ```b``` c\n"),
+            "````text\na ```b``` c\n````\n"
+        );
+        assert_eq!(inline_code("`x`"), "`` `x` ``");
+    }
+}
@@ -19,10 +19,13 @@ use tracing::info;
 use tracing::warn;
 
 use super::SESSIONS_SUBDIR;
+use super::export::Transcript;
 use super::list::ConversationsPage;
 use super::list::Cursor;
 use super::list::get_conversations;
 use super::policy::is_persisted_response_item;
+use super::search::ConversationSearchPage;
+use super::search::search_conversations;
 use crate::config::Config;
 use crate::default_client::originator;
 use crate::git_info::collect_git_info;
@@ -101,6 +104,23 @@ impl RolloutRecorder {
         get_conversations(codex_home, page_size, cursor, allowed_sources).await
     }
 
+    /// Search the text of recorded conversations under the provided Codex home
+    /// directory, newest first.
+    pub async fn search_conversations(
+        codex_home: &Path,
+        query: &str,
+        limit: usize,
+        allowed_sources: &[SessionSource],
+    ) -> std::io::Result<ConversationSearchPage> {
+        search_conversations(codex_home, query, limit, allowed_sources).await
+    }
+
+    /// Load the rollout file at `path` as a [`Transcript`] for export.
+    pub async fn load_transcript(path: &Path) -> std::io::Result<Transcript> {
+        let history = Self::get_rollout_history(path).await?;
+        Ok(Transcript::from_rollout_items(&history.get_rollout_items()))
+    }
+
     /// Attempt to create a new [`RolloutRecorder`]. If the sessions directory
     /// cannot be created or the rollout file cannot be opened we return the
     /// error so the caller can decide whether to disable persistence.
@@ -0,0 +1,442 @@
+//! Full-text search over recorded rollouts.
+//!
+//! The searchable text of every rollout (user and agent messages, commands
+//! run, files touched by patches) is cached in a file per rollout under
+//! `search_index/` in the Codex home, next to (not inside) `sessions/` so the
+//! index files are never mistaken for rollouts. Entries are keyed by path and
+//! invalidated by file size and modification time, so only new or grown
+//! rollout files are parsed and written again on each search.
+
+use std::collections::HashSet;
+use std::io::{self};
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use codex_protocol::ConversationId;
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::models::ShellToolCallParams;
+use codex_protocol::protocol::InputMessageKind;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::SessionSource;
+use codex_utils_string::take_bytes_at_char_boundary;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::warn;
+
+use super::SESSIONS_SUBDIR;
+use super::list::MAX_SCAN_FILES;
+use super::list::collect_dirs_desc;
+use super::list::collect_files;
+use super::list::parse_timestamp_uuid_from_filename;
+use crate::protocol::EventMsg;
+
+pub const SEARCH_INDEX_DIR: &str = "search_index";
+const SEARCH_INDEX_VERSION: u32 = 2;
+/// Longer messages and commands are only searchable by their beginning.
+const MAX_INDEXED_BYTES_PER_ENTRY: usize = 4 * 1024;
+const MAX_MATCHES_PER_CONVERSATION: usize = 3;
+const SNIPPET_CHARS_BEFORE: usize = 40;
+const SNIPPET_CHARS_AFTER: usize = 80;
+
+/// Conversations matching a search, newest first.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ConversationSearchPage {
+    pub items: Vec<ConversationSearchResult>,
+    /// Number of rollout files considered while searching.
+    pub num_scanned_files: usize,
+    /// True if the scan stopped at the hard cap on files per request.
+    pub reached_scan_cap: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConversationSearchResult {
+    /// Absolute path to the rollout file.
+    pub path: PathBuf,
+    pub conversation_id: Option<ConversationId>,
+    /// First message the user sent in the conversation.
+    pub preview: Option<String>,
+    /// RFC3339 timestamp of the session start, if available.
+    pub created_at: Option<String>,
+    /// RFC3339 timestamp of the last recorded line, if available.
+    pub updated_at: Option<String>,
+    pub matches: Vec<SearchMatch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchMatch {
+    pub kind: SearchMatchKind,
+    /// Whitespace-collapsed excerpt around the first matched term.
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMatchKind {
+    UserMessage,
+    AgentMessage,
+    Command,
+    FileChange,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct IndexedRollout {
+    version: u32,
+    /// The rollout file this was indexed from, with its size and
+    /// modification time at the time.
+    path: PathBuf,
+    len: u64,
+    modified_ms: u64,
+    conversation_id: Option<ConversationId>,
+    source: Option<SessionSource>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    preview: Option<String>,
+    entries: Vec<IndexedText>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedText {
+    kind: SearchMatchKind,
+    text: String,
+}
+
+/// Search recorded conversations for `query`. A conversation matches when
+/// every whitespace-separated term of the query occurs (case-insensitively)
+/// somewhere in its indexed text. At most `limit` conversations are returned.
+pub async fn search_conversations(
+    codex_home: &Path,
+    query: &str,
+    limit: usize,
+    allowed_sources: &[SessionSource],
+) -> io::Result<ConversationSearchPage> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let root = codex_home.join(SESSIONS_SUBDIR);
+    if terms.is_empty() || limit == 0 || !root.exists() {
+        return Ok(ConversationSearchPage::default());
+    }
+
+    let index_dir = codex_home.join(SEARCH_INDEX_DIR);
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    let mut index_files: HashSet<PathBuf> = HashSet::new();
+    let mut items = Vec::new();
+    let mut stopped_early = false;
+
+    let files = rollout_files_newest_first(&root).await?;
+    let reached_scan_cap = files.len() >= MAX_SCAN_FILES;
+    for path in files {
+        if items.len() == limit {
+            stopped_early = true;
+            break;
+        }
+        visited.insert(path.clone());
+        let Ok(metadata) = tokio::fs::metadata(&path).await else {
+            continue;
+        };
+        let len = metadata.len();
+        let modified_ms = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|since_epoch| u64::try_from(since_epoch.as_millis()).unwrap_or(u64::MAX))
+            .unwrap_or_default();
+
+        let index_path = index_file_path(&index_dir, &path);
+        let rollout = match load_indexed_rollout(&index_path).await {
+            Some(cached)
+                if cached.path == path
+                    && cached.len == len
+                    && cached.modified_ms == modified_ms =>
+            {
+                cached
+            }
+            _ => {
+                let Ok(contents) = tokio::fs::read_to_string(&path).await else {
+                    continue;
+                };
+                let mut rollout = index_rollout(&contents);
+                rollout.version = SEARCH_INDEX_VERSION;
+                rollout.path = path.clone();
+                rollout.len = len;
+                rollout.modified_ms = modified_ms;
+                if let Err(err) = save_indexed_rollout(&index_dir, &index_path, &rollout).await {
+                    warn!("failed to write rollout search index: {err}");
+                }
+                rollout
+            }
+        };
+        index_files.insert(index_path);
+
+        if rollout.preview.is_none() {
+            continue;
+        }
+        if !allowed_sources.is_empty()
+            && !rollout
+                .source
+                .is_some_and(|source| allowed_sources.contains(&source))
+        {
+            continue;
+        }
+        if let Some(matches) = match_rollout(&rollout, &terms) {
+            items.push(ConversationSearchResult {
+                path,
+                conversation_id: rollout.conversation_id,
+                preview: rollout.preview,
+                created_at: rollout.created_at,
+                updated_at: rollout.updated_at,
+                matches,
+            });
+        }
+    }
+
+    // Forget deleted rollouts, but only when every file was visited.
+    if !stopped_early && !reached_scan_cap {
+        remove_stale_index_files(&index_dir, &index_files).await;
+    }
+
+    Ok(ConversationSearchPage {
+        items,
+        num_scanned_files: visited.len(),
+        reached_scan_cap,
+    })
+}
+
+/// Rollout files under `root`, newest first, capped at [`MAX_SCAN_FILES`].
+async fn rollout_files_newest_first(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for (_year, year_path) in collect_dirs_desc(root, |s| s.parse::<u16>().ok()).await? {
+        for (_month, month_path) in collect_dirs_desc(&year_path, |s| s.parse::<u8>().ok()).await? {
+            for (_day, day_path) in collect_dirs_desc(&month_path, |s| s.parse::<u8>().ok()).await?
+            {
+                let mut day_files = collect_files(&day_path, |name_str, path| {
+                    if !name_str.starts_with("rollout-") || !name_str.ends_with(".jsonl") {
+                        return None;
+                    }
+                    parse_timestamp_uuid_from_filename(name_str)
+                        .map(|(ts, id)| (ts, id, path.to_path_buf()))
+                })
+                .await?;
+                day_files
+                    .sort_by(|(a_ts, a_id, _), (b_ts, b_id, _)| (b_ts, b_id).cmp(&(a_ts, a_id)));
+                for (_ts, _id, path) in day_files {
+                    files.push(path);
+                    if files.len() >= MAX_SCAN_FILES {
+                        return Ok(files);
+                    }
+                }
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Index file of the rollout at `rollout_path`: its file name, with a
+/// `.json` extension, in `index_dir`.
+fn index_file_path(index_dir: &Path, rollout_path: &Path) -> PathBuf {
+    let mut name = rollout_path.file_stem().unwrap_or_default().to_os_string();
+    name.push(".json");
+    index_dir.join(name)
+}
+
+async fn load_indexed_rollout(path: &Path) -> Option<IndexedRollout> {
+    let contents = tokio::fs::read(path).await.ok()?;
+    serde_json::from_slice::<IndexedRollout>(&contents)
+        .ok()
+        .filter(|rollout| rollout.version == SEARCH_INDEX_VERSION)
+}
+
+async fn save_indexed_rollout(
+    index_dir: &Path,
+    path: &Path,
+    rollout: &IndexedRollout,
+) -> io::Result<()> {
+    let serialized = serde_json::to_vec(rollout).map_err(io::Error::other)?;
+    tokio::fs::create_dir_all(index_dir).await?;
+    // Write to a sibling file first so concurrent searches never see a
+    // partially written index.
+    let tmp_path = path.with_extension(format!("json.{}", std::process::id()));
+    tokio::fs::write(&tmp_path, serialized).await?;
+    tokio::fs::rename(&tmp_path, path).await
+}
+
+/// Removes the index files in `index_dir` other than `keep`.
+async fn remove_stale_index_files(index_dir: &Path, keep: &HashSet<PathBuf>) {
+    let Ok(mut entries) = tokio::fs::read_dir(index_dir).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        // Leave files other searches are still writing alone.
+        if path.extension().is_some_and(|ext| ext == "json") && !keep.contains(&path) {
+            let _ = tokio::fs::remove_file(&path).await;
+        }
+    }
+}
+
+/// Extract the searchable text from the JSONL `contents` of a rollout.
+fn index_rollout(contents: &str) -> IndexedRollout {
+    let mut rollout = IndexedRollout::default();
+    for line in contents.lines() {
+        let Ok(RolloutLine { timestamp, item }) = serde_json::from_str::<RolloutLine>(line) else {
+            continue;
+        };
+        if rollout.created_at.is_none() {
+            rollout.created_at = Some(timestamp.clone());
+        }
+        rollout.updated_at = Some(timestamp);
+
+        match item {
+            RolloutItem::SessionMeta(meta_line) => {
+                rollout.conversation_id = Some(meta_line.meta.id);
+                rollout.source = Some(meta_line.meta.source);
+            }
+            RolloutItem::EventMsg(EventMsg::UserMessage(event)) => {
+                if matches!(event.kind, None | Some(InputMessageKind::Plain)) {
+                    if rollout.preview.is_none() {
+                        rollout.preview = Some(event.message.trim().to_string());
+                    }
+                    rollout.push(SearchMatchKind::UserMessage, event.message);
+                }
+            }
+            RolloutItem::EventMsg(EventMsg::AgentMessage(event)) => {
+                rollout.push(SearchMatchKind::AgentMessage, event.message);
+            }
+            RolloutItem::ResponseItem(ResponseItem::LocalShellCall {
+                action: LocalShellAction::Exec(action),
+                ..
+            }) => {
+                rollout.push(SearchMatchKind::Command, command_text(&action.command));
+            }
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                name, arguments, ..
+            }) => match name.as_str() {
+                "shell" | "container.exec" => {
+                    if let Ok(params) = serde_json::from_str::<ShellToolCallParams>(&arguments) {
+                        rollout.push(SearchMatchKind::Command, command_text(&params.command));
+                    }
+                }
+                "apply_patch" => {
+                    #[derive(Deserialize)]
+                    struct ApplyPatchArgs {
+                        input: String,
+                    }
+                    if let Ok(args) = serde_json::from_str::<ApplyPatchArgs>(&arguments) {
+                        rollout.push_patched_files(&args.input);
+                    }
+                }
+                _ => {}
+            },
+            RolloutItem::ResponseItem(ResponseItem::CustomToolCall { name, input, .. })
+                if name == "apply_patch" =>
+            {
+                rollout.push_patched_files(&input);
+            }
+            _ => {}
+        }
+    }
+    rollout
+}
+
+impl IndexedRollout {
+    fn push(&mut self, kind: SearchMatchKind, text: String) {
+        if !text.trim().is_empty() {
+            let text = take_bytes_at_char_boundary(&text, MAX_INDEXED_BYTES_PER_ENTRY).to_string();
+            self.entries.push(IndexedText { kind, text });
+        }
+    }
+
+    fn push_patched_files(&mut self, patch: &str) {
+        const FILE_MARKERS: [&str; 4] = [
+            "*** Add File: ",
+            "*** Update File: ",
+            "*** Delete File: ",
+            "*** Move to: ",
+        ];
+        for line in patch.lines() {
+            if let Some(path) = FILE_MARKERS
+                .iter()
+                .find_map(|marker| line.trim_start().strip_prefix(marker))
+            {
+                self.push(SearchMatchKind::FileChange, path.trim().to_string());
+            }
+        }
+    }
+}
+
+/// Commands run through `bash -lc` are indexed by their script.
+fn command_text(command: &[String]) -> String {
+    match command {
+        [shell, flag, script] if shell.ends_with("sh") && (flag == "-lc" || flag == "-c") => {
+            script.clone()
+        }
+        _ => shlex::try_join(command.iter().map(String::as_str))
+            .unwrap_or_else(|_| command.join(" ")),
+    }
+}
+
+/// Returns the matches of `rollout` if every term occurs in it.
+fn match_rollout(rollout: &IndexedRollout, terms: &[String]) -> Option<Vec<SearchMatch>> {
+    let lowered: Vec<String> = rollout
+        .entries
+        .iter()
+        .map(|entry| entry.text.to_lowercase())
+        .collect();
+    let all_terms_found = terms
+        .iter()
+        .all(|term| lowered.iter().any(|text| text.contains(term.as_str())));
+    if !all_terms_found {
+        return None;
+    }
+
+    let matches = rollout
+        .entries
+        .iter()
+        .zip(&lowered)
+        .filter_map(|(entry, lower)| {
+            let start = terms
+                .iter()
+                .filter_map(|term| lower.find(term.as_str()))
+                .min()?;
+            Some(SearchMatch {
+                kind: entry.kind,
+                snippet: snippet(&entry.text, lower, start),
+            })
+        })
+        .take(MAX_MATCHES_PER_CONVERSATION)
+        .collect();
+    Some(matches)
+}
+
+/// Excerpt of `text` around byte offset `start` of its lowercased form
+/// `lower`, with whitespace collapsed.
+fn snippet(text: &str, lower: &str, start: usize) -> String {
+    // Lowercasing only rarely changes byte lengths; fall back to the
+    // beginning of the text when offsets do not line up.
+    let start = if lower.len() == text.len() && text.is_char_boundary(start) {
+        start
+    } else {
+        0
+    };
+    let before: Vec<char> = text[..start]
+        .chars()
+        .rev()
+        .take(SNIPPET_CHARS_BEFORE + 1)
+        .collect();
+    let after: Vec<char> = text[start..]
+        .chars()
+        .take(SNIPPET_CHARS_AFTER + 1)
+        .collect();
+
+    let mut excerpt = String::new();
+    if before.len() > SNIPPET_CHARS_BEFORE {
+        excerpt.push('…');
+    }
+    excerpt.extend(before.iter().take(SNIPPET_CHARS_BEFORE).rev());
+    excerpt.extend(after.iter().take(SNIPPET_CHARS_AFTER));
+    if after.len() > SNIPPET_CHARS_AFTER {
+        excerpt.push('…');
+    }
+    excerpt.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -13,11 +13,16 @@ use time::macros::format_description;
 use uuid::Uuid;
 
 use crate::rollout::INTERACTIVE_SESSION_SOURCES;
+use crate::rollout::find_conversation_path_by_id_str;
 use crate::rollout::list::ConversationItem;
 use crate::rollout::list::ConversationsPage;
 use crate::rollout::list::Cursor;
 use crate::rollout::list::get_conversation;
 use crate::rollout::list::get_conversations;
+use crate::rollout::search::SEARCH_INDEX_DIR;
+use crate::rollout::search::SearchMatch;
+use crate::rollout::search::SearchMatchKind;
+use crate::rollout::search::search_conversations;
 use anyhow::Result;
 use codex_protocol::ConversationId;
 use codex_protocol::models::ContentItem;
@@ -925,3 +930,300 @@ async fn test_source_filter_excludes_non_matching_sessions() {
         path.ends_with("rollout-2025-08-01T10-00-00-00000000-0000-0000-0000-00000000004d.jsonl")
     }));
 }
+
+fn session_file_path(root: &Path, ts_str: &str, uuid: Uuid) -> std::path::PathBuf {
+    let (date, _) = ts_str.split_once('T').unwrap();
+    let mut dir = root.join("sessions");
+    for part in date.split('-') {
+        dir.push(part);
+    }
+    dir.join(format!("rollout-{ts_str}-{uuid}.jsonl"))
+}
+
+fn append_rollout_items(path: &Path, items: &[serde_json::Value]) {
+    let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+    for item in items {
+        let line = serde_json::json!({
+            "timestamp": "2025-08-03T10:05:00.000Z",
+            "type": item["type"],
+            "payload": item["payload"],
+        });
+        writeln!(file, "{line}").unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_search_matches_messages_commands_and_files() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let newer = Uuid::from_u128(5);
+    let older = Uuid::from_u128(6);
+    let exec = Uuid::from_u128(7);
+    write_session_file(
+        home,
+        "2025-08-03T10-00-00",
+        newer,
+        0,
+        Some(SessionSource::Cli),
+    )
+    .unwrap();
+    write_session_file(
+        home,
+        "2025-08-02T10-00-00",
+        older,
+        0,
+        Some(SessionSource::Cli),
+    )
+    .unwrap();
+    write_session_file(
+        home,
+        "2025-08-01T10-00-00",
+        exec,
+        0,
+        Some(SessionSource::Exec),
+    )
+    .unwrap();
+
+    let newer_path = session_file_path(home, "2025-08-03T10-00-00", newer);
+    append_rollout_items(
+        &newer_path,
+        &[
+            serde_json::json!({
+                "type": "event_msg",
+                "payload": { "type": "agent_message", "message": "The Flaky test lives in the parser." },
+            }),
+            serde_json::json!({
+                "type": "response_item",
+                "payload": {
+                    "type": "function_call",
+                    "name": "shell",
+                    "arguments": "{\"command\":[\"bash\",\"-lc\",\"cargo test -p parser\"]}",
+                    "call_id": "call-1",
+                },
+            }),
+            serde_json::json!({
+                "type": "response_item",
+                "payload": {
+                    "type": "custom_tool_call",
+                    "name": "apply_patch",
+                    "input": "*** Begin Patch\n*** Update File: src/parser.rs\n@@\n-a\n+b\n*** End Patch",
+                    "call_id": "call-2",
+                },
+            }),
+        ],
+    );
+    let flaky_message = serde_json::json!({
+        "type": "event_msg",
+        "payload": { "type": "agent_message", "message": "No flaky tests here." },
+    });
+    append_rollout_items(
+        &session_file_path(home, "2025-08-02T10-00-00", older),
+        std::slice::from_ref(&flaky_message),
+    );
+    append_rollout_items(
+        &session_file_path(home, "2025-08-01T10-00-00", exec),
+        std::slice::from_ref(&flaky_message),
+    );
+
+    let page = search_conversations(home, "FLAKY parser", 10, INTERACTIVE_SESSION_SOURCES)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 1);
+    let result = &page.items[0];
+    assert_eq!(result.path, newer_path);
+    assert_eq!(
+        result.conversation_id,
+        Some(ConversationId::from_string(&newer.to_string()).unwrap())
+    );
+    assert_eq!(result.preview.as_deref(), Some("Hello from user"));
+    assert_eq!(
+        result.matches,
+        vec![
+            SearchMatch {
+                kind: SearchMatchKind::AgentMessage,
+                snippet: "The Flaky test lives in the parser.".to_string(),
+            },
+            SearchMatch {
+                kind: SearchMatchKind::Command,
+                snippet: "cargo test -p parser".to_string(),
+            },
+            SearchMatch {
+                kind: SearchMatchKind::FileChange,
+                snippet: "src/parser.rs".to_string(),
+            },
+        ]
+    );
+
+    let flaky = search_conversations(home, "flaky", 10, INTERACTIVE_SESSION_SOURCES)
+        .await
+        .unwrap();
+    let ids: Vec<_> = flaky
+        .items
+        .iter()
+        .map(|item| item.conversation_id)
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            Some(ConversationId::from_string(&newer.to_string()).unwrap()),
+            Some(ConversationId::from_string(&older.to_string()).unwrap())
+        ]
+    );
+
+    let all_sources = search_conversations(home, "flaky", 10, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(all_sources.items.len(), 3);
+}
+
+#[tokio::test]
+async fn test_search_index_picks_up_appended_lines() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let id = Uuid::from_u128(8);
+    write_session_file(home, "2025-08-03T10-00-00", id, 0, Some(SessionSource::Cli)).unwrap();
+
+    let page = search_conversations(home, "rustfmt", 10, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert!(page.items.is_empty());
+    let index_files = search_index_files(home);
+    assert_eq!(index_files.len(), 1);
+
+    // Searching again without changes to the rollout leaves its index alone.
+    let indexed_at = fs::metadata(&index_files[0]).unwrap().modified().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    search_conversations(home, "rustfmt", 10, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::metadata(&index_files[0]).unwrap().modified().unwrap(),
+        indexed_at
+    );
+
+    append_rollout_items(
+        &session_file_path(home, "2025-08-03T10-00-00", id),
+        &[serde_json::json!({
+            "type": "response_item",
+            "payload": {
+                "type": "local_shell_call",
+                "call_id": "call-1",
+                "status": "completed",
+                "action": { "type": "exec", "command": ["cargo", "fmt", "--", "--config", "rustfmt.toml"] },
+            },
+        })],
+    );
+
+    let page = search_conversations(home, "rustfmt", 10, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(
+        page.items[0].matches,
+        vec![SearchMatch {
+            kind: SearchMatchKind::Command,
+            snippet: "cargo fmt -- --config rustfmt.toml".to_string(),
+        }]
+    );
+}
+
+fn search_index_files(home: &Path) -> Vec<std::path::PathBuf> {
+    let index_dir = home.join(SEARCH_INDEX_DIR);
+    let mut files: Vec<_> = fs::read_dir(index_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    files
+}
+
+#[tokio::test]
+async fn test_search_index_caps_entries_and_forgets_deleted_rollouts() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let kept = Uuid::from_u128(9);
+    let deleted = Uuid::from_u128(10);
+    write_session_file(
+        home,
+        "2025-08-04T10-00-00",
+        kept,
+        0,
+        Some(SessionSource::Cli),
+    )
+    .unwrap();
+    write_session_file(
+        home,
+        "2025-08-05T10-00-00",
+        deleted,
+        0,
+        Some(SessionSource::Cli),
+    )
+    .unwrap();
+    let long_message = format!("{} needle", "hay ".repeat(4096));
+    append_rollout_items(
+        &session_file_path(home, "2025-08-04T10-00-00", kept),
+        &[serde_json::json!({
+            "type": "event_msg",
+            "payload": { "type": "agent_message", "message": format!("haystack start {long_message}") },
+        })],
+    );
+
+    // Only the beginning of long entries is indexed.
+    let page = search_conversations(home, "haystack", 10, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 1);
+    let page = search_conversations(home, "needle", 10, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    assert!(page.items.is_empty());
+    let index_files = search_index_files(home);
+    assert_eq!(index_files.len(), 2);
+    assert!(
+        index_files
+            .iter()
+            .all(|file| fs::metadata(file).unwrap().len() < 8 * 1024)
+    );
+
+    fs::remove_file(session_file_path(home, "2025-08-05T10-00-00", deleted)).unwrap();
+    search_conversations(home, "needle", 10, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    let remaining = search_index_files(home);
+    assert_eq!(remaining.len(), 1);
+    assert!(remaining[0].to_string_lossy().contains(&kept.to_string()));
+}
+
+#[tokio::test]
+async fn test_find_by_id_ignores_search_index() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let id = Uuid::from_u128(10);
+    write_session_file(home, "2025-08-06T10-00-00", id, 1, Some(SessionSource::Cli)).unwrap();
+    search_conversations(home, "needle", 10, NO_SOURCE_FILTER)
+        .await
+        .unwrap();
+    let index_files = search_index_files(home);
+    assert_eq!(index_files.len(), 1);
+    // Index files written inside sessions/ by older versions must not be
+    // taken for the rollout either.
+    let legacy_index_dir = home.join("sessions").join(SEARCH_INDEX_DIR);
+    fs::create_dir_all(&legacy_index_dir).unwrap();
+    fs::copy(
+        &index_files[0],
+        legacy_index_dir.join(index_files[0].file_name().unwrap()),
+    )
+    .unwrap();
+
+    let found = find_conversation_path_by_id_str(home, &id.to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        found,
+        Some(session_file_path(home, "2025-08-06T10-00-00", id))
+    );
+}
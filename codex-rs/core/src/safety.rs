@@ -5,9 +5,12 @@ use std::path::PathBuf;
 
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
+use codex_execpolicy::Policy;
 
 use crate::exec::SandboxType;
 
+use crate::command_safety::exec_policy::ExecPolicyVerdict;
+use crate::command_safety::exec_policy::evaluate_exec_policy;
 use crate::command_safety::is_dangerous_command::command_might_be_dangerous;
 use crate::command_safety::is_safe_command::is_known_safe_command;
 use crate::protocol::AskForApproval;
@@ -88,14 +91,27 @@ pub fn assess_patch_safety(
 ///
 /// - the user has explicitly approved the command
 /// - the command is on the "known safe" list
+/// - the user's `exec_policy` matches the command and it does not write files
 /// - `DangerFullAccess` was specified and `UnlessTrusted` was not
+///
+/// Commands forbidden by the user's `exec_policy` are always rejected.
 pub fn assess_command_safety(
     command: &[String],
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     approved: &HashSet<Vec<String>>,
     with_escalated_permissions: bool,
+    exec_policy: Option<&Policy>,
 ) -> SafetyCheck {
+    let exec_policy_verdict = exec_policy
+        .map(|policy| evaluate_exec_policy(policy, command))
+        .unwrap_or(ExecPolicyVerdict::Unverified);
+    if let ExecPolicyVerdict::Forbidden { reason } = exec_policy_verdict {
+        return SafetyCheck::Reject {
+            reason: format!("forbidden by exec policy: {reason}"),
+        };
+    }
+
     // Some commands look dangerous. Even if they are run inside a sandbox,
     // unless the user has explicitly approved them, we should ask,
     // or reject if the approval_policy tells us not to ask.
@@ -111,20 +127,23 @@ pub fn assess_command_safety(
     }
 
     // A command is "trusted" because either:
-    // - it belongs to a set of commands we consider "safe" by default, or
+    // - it belongs to a set of commands we consider "safe" by default,
+    // - the user's `exec_policy` declares it safe, or
     // - the user has explicitly approved the command for this session
     //
     // Currently, whether a command is "trusted" is a simple boolean, but we
     // should include more metadata on this command test to indicate whether it
-    // should be run inside a sandbox or not. (This could be something the user
-    // defines as part of `execpolicy`.)
+    // should be run inside a sandbox or not.
     //
     // For example, when `is_known_safe_command(command)` returns `true`, it
     // would probably be fine to run the command in a sandbox, but when
     // `approved.contains(command)` is `true`, the user may have approved it for
     // the session _because_ they know it needs to run outside a sandbox.
 
-    if is_known_safe_command(command) || approved.contains(command) {
+    if is_known_safe_command(command)
+        || exec_policy_verdict == ExecPolicyVerdict::Safe
+        || approved.contains(command)
+    {
         let user_explicitly_approved = approved.contains(command);
         return SafetyCheck::AutoApprove {
             sandbox_type: SandboxType::None,
@@ -156,7 +175,7 @@ pub(crate) fn assess_safety_for_untrusted_command(
             sandbox_type: SandboxType::None,
             user_explicitly_approved: false,
         },
-        (OnRequest, ReadOnly) | (OnRequest, WorkspaceWrite { .. }) => {
+        (OnRequest, ReadOnly { .. }) | (OnRequest, WorkspaceWrite { .. }) => {
             if with_escalated_permissions {
                 SafetyCheck::AskUser
             } else {
@@ -171,9 +190,9 @@ pub(crate) fn assess_safety_for_untrusted_command(
                 }
             }
         }
-        (Never, ReadOnly)
+        (Never, ReadOnly { .. })
         | (Never, WorkspaceWrite { .. })
-        | (OnFailure, ReadOnly)
+        | (OnFailure, ReadOnly { .. })
         | (OnFailure, WorkspaceWrite { .. }) => {
             match get_platform_sandbox() {
                 Some(sandbox_type) => SafetyCheck::AutoApprove {
@@ -218,7 +237,7 @@ fn is_write_patch_constrained_to_writable_paths(
 ) -> bool {
     // Early‑exit if there are no declared writable roots.
     let writable_roots = match sandbox_policy {
-        SandboxPolicy::ReadOnly => {
+        SandboxPolicy::ReadOnly { .. } => {
             return false;
         }
         SandboxPolicy::DangerFullAccess => {
@@ -264,7 +283,10 @@ fn is_write_patch_constrained_to_writable_paths(
 
     for (path, change) in action.changes() {
         match change {
-            ApplyPatchFileChange::Add { .. } | ApplyPatchFileChange::Delete { .. } => {
+            ApplyPatchFileChange::Add { .. }
+            | ApplyPatchFileChange::Delete { .. }
+            | ApplyPatchFileChange::Copy { .. }
+            | ApplyPatchFileChange::Binary { .. } => {
                 if !is_path_writable(path) {
                     return false;
                 }
@@ -308,7 +330,9 @@ mod tests {
         // only `cwd` is writable by default.
         let policy_workspace_only = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
+            deny_read: Vec::new(),
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
         };
@@ -329,7 +353,9 @@ mod tests {
         // outside write should be permitted.
         let policy_with_parent = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![parent],
+            deny_read: Vec::new(),
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
         };
@@ -345,7 +371,7 @@ mod tests {
         // Should not be a trusted command
         let command = vec!["git commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = true;
 
@@ -355,6 +381,7 @@ mod tests {
             &sandbox_policy,
             &approved,
             request_escalated_privileges,
+            None,
         );
 
         assert_eq!(safety_check, SafetyCheck::AskUser);
@@ -364,7 +391,7 @@ mod tests {
     fn dangerous_command_allowed_if_explicitly_approved() {
         let command = vec!["git".to_string(), "reset".to_string(), "--hard".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let mut approved: HashSet<Vec<String>> = HashSet::new();
         approved.insert(command.clone());
         let request_escalated_privileges = false;
@@ -375,6 +402,7 @@ mod tests {
             &sandbox_policy,
             &approved,
             request_escalated_privileges,
+            None,
         );
 
         assert_eq!(
@@ -390,7 +418,7 @@ mod tests {
     fn dangerous_command_not_allowed_if_not_explicitly_approved() {
         let command = vec!["git".to_string(), "reset".to_string(), "--hard".to_string()];
         let approval_policy = AskForApproval::Never;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = false;
 
@@ -400,6 +428,7 @@ mod tests {
             &sandbox_policy,
             &approved,
             request_escalated_privileges,
+            None,
         );
 
         assert_eq!(
@@ -411,11 +440,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn exec_policy_safe_command_is_auto_approved() {
+        let policy = codex_execpolicy::PolicyParser::new(
+            "test.policy",
+            r#"define_program(program="make", args=["lint"])"#,
+        )
+        .parse()
+        .expect("parse policy");
+        let command = vec!["make".to_string(), "lint".to_string()];
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::UnlessTrusted,
+            &SandboxPolicy::new_read_only_policy(),
+            &HashSet::new(),
+            false,
+            Some(&policy),
+        );
+
+        assert_eq!(
+            safety_check,
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None,
+                user_explicitly_approved: false,
+            }
+        );
+    }
+
+    #[test]
+    fn exec_policy_forbidden_command_is_rejected_even_if_approved() {
+        let policy = codex_execpolicy::PolicyParser::new(
+            "test.policy",
+            r#"forbid_program_regex(regex="^terraform$", reason="use the deploy pipeline")"#,
+        )
+        .parse()
+        .expect("parse policy");
+        let command = vec!["terraform".to_string(), "apply".to_string()];
+        let mut approved: HashSet<Vec<String>> = HashSet::new();
+        approved.insert(command.clone());
+
+        let safety_check = assess_command_safety(
+            &command,
+            AskForApproval::OnRequest,
+            &SandboxPolicy::DangerFullAccess,
+            &approved,
+            false,
+            Some(&policy),
+        );
+
+        assert_eq!(
+            safety_check,
+            SafetyCheck::Reject {
+                reason: "forbidden by exec policy: use the deploy pipeline".to_string(),
+            }
+        );
+    }
+
     #[test]
     fn test_request_escalated_privileges_no_sandbox_fallback() {
         let command = vec!["git".to_string(), "commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = false;
 
@@ -425,6 +511,7 @@ mod tests {
             &sandbox_policy,
             &approved,
             request_escalated_privileges,
+            None,
         );
 
         let expected = match get_platform_sandbox() {
@@ -3,6 +3,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
 
+use crate::network_proxy;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use crate::spawn::StdioPolicy;
@@ -24,7 +25,13 @@ pub async fn spawn_command_under_seatbelt(
     stdio_policy: StdioPolicy,
     mut env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
-    let args = create_seatbelt_command_args(command, sandbox_policy, sandbox_policy_cwd);
+    let network_proxy_port = network_proxy::prepare_sandbox_env(sandbox_policy, &mut env)?;
+    let args = create_seatbelt_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+        network_proxy_port,
+    );
     let arg0 = None;
     env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "seatbelt".to_string());
     spawn_child_async(
@@ -43,6 +50,7 @@ fn create_seatbelt_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    network_proxy_port: Option<u16>,
 ) -> Vec<String> {
     let (file_write_policy, extra_cli_args) = {
         if sandbox_policy.has_full_disk_write_access() {
@@ -97,17 +105,22 @@ fn create_seatbelt_command_args(
         }
     };
 
+    let (read_denied_policy, read_denied_cli_args) =
+        create_read_denied_policy(sandbox_policy, sandbox_policy_cwd);
     let file_read_policy = if sandbox_policy.has_full_disk_read_access() {
-        "; allow read-only file operations\n(allow file-read*)"
+        format!("; allow read-only file operations\n(allow file-read*){read_denied_policy}")
     } else {
-        ""
+        String::new()
     };
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
     let network_policy = if sandbox_policy.has_full_network_access() {
-        "(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)"
+        "(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)".to_string()
+    } else if let Some(port) = network_proxy_port {
+        // Only the allowlist proxy is reachable.
+        format!("(allow network-outbound (remote ip \"localhost:{port}\"))\n(allow system-socket)")
     } else {
-        ""
+        String::new()
     };
 
     let full_policy = format!(
@@ -116,14 +129,45 @@ fn create_seatbelt_command_args(
 
     let mut seatbelt_args: Vec<String> = vec!["-p".to_string(), full_policy];
     seatbelt_args.extend(extra_cli_args);
+    seatbelt_args.extend(read_denied_cli_args);
     seatbelt_args.push("--".to_string());
     seatbelt_args.extend(command);
     seatbelt_args
 }
 
+/// Returns the rules (appended after `(allow file-read*)`, which they
+/// override) that keep the contents of read-denied paths unreadable, along
+/// with the parameters they reference.
+fn create_read_denied_policy(
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+) -> (String, Vec<String>) {
+    let read_denied = sandbox_policy.get_read_denied_paths_with_cwd(sandbox_policy_cwd);
+    let denied_names = sandbox_policy.read_denied_file_names();
+    if read_denied.is_empty() && denied_names.is_empty() {
+        return (String::new(), Vec::new());
+    }
+
+    let mut filters: Vec<String> = Vec::new();
+    let mut cli_args: Vec<String> = Vec::new();
+    for (index, path) in read_denied.iter().enumerate() {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        let param = format!("READ_DENIED_{index}");
+        cli_args.push(format!("-D{param}={}", canonical.to_string_lossy()));
+        filters.push(format!("(subpath (param \"{param}\"))"));
+    }
+    // Files with these names are denied wherever they are.
+    for name in denied_names {
+        filters.push(format!("(regex #\"/{}$\")", name.replace('.', "\\.")));
+    }
+    let policy = format!("\n(deny file-read-data\n{}\n)", filters.join(" "));
+    (policy, cli_args)
+}
+
 #[cfg(test)]
 mod tests {
     use super::MACOS_SEATBELT_BASE_POLICY;
+    use super::create_read_denied_policy;
     use super::create_seatbelt_command_args;
     use crate::protocol::SandboxPolicy;
     use pretty_assertions::assert_eq;
@@ -155,7 +199,9 @@ mod tests {
         // does not automatically include defaults TMPDIR or /tmp.
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![root_with_git, root_without_git],
+            deny_read: Vec::new(),
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
         };
@@ -164,7 +210,10 @@ mod tests {
             vec!["/bin/echo".to_string(), "hello".to_string()],
             &policy,
             &cwd,
+            None,
         );
+        // The default read-denied paths depend on `$HOME`.
+        let (read_denied_policy, read_denied_args) = create_read_denied_policy(&policy, &cwd);
 
         // Build the expected policy text using a raw string for readability.
         // Note that the policy includes:
@@ -174,7 +223,7 @@ mod tests {
         let expected_policy = format!(
             r#"{MACOS_SEATBELT_BASE_POLICY}
 ; allow read-only file operations
-(allow file-read*)
+(allow file-read*){read_denied_policy}
 (allow file-write*
 (require-all (subpath (param "WRITABLE_ROOT_0")) (require-not (subpath (param "WRITABLE_ROOT_0_RO_0"))) ) (subpath (param "WRITABLE_ROOT_1")) (subpath (param "WRITABLE_ROOT_2"))
 )
@@ -199,6 +248,7 @@ mod tests {
             format!("-DWRITABLE_ROOT_2={}", cwd.to_string_lossy()),
         ];
 
+        expected_args.extend(read_denied_args);
         expected_args.extend(vec![
             "--".to_string(),
             "/bin/echo".to_string(),
@@ -230,7 +280,9 @@ mod tests {
         // is done properly for cwd.
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
+            deny_read: Vec::new(),
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
         };
@@ -239,7 +291,10 @@ mod tests {
             vec!["/bin/echo".to_string(), "hello".to_string()],
             &policy,
             root_with_git.as_path(),
+            None,
         );
+        let (read_denied_policy, read_denied_args) =
+            create_read_denied_policy(&policy, root_with_git.as_path());
 
         let tmpdir_env_var = std::env::var("TMPDIR")
             .ok()
@@ -261,7 +316,7 @@ mod tests {
         let expected_policy = format!(
             r#"{MACOS_SEATBELT_BASE_POLICY}
 ; allow read-only file operations
-(allow file-read*)
+(allow file-read*){read_denied_policy}
 (allow file-write*
 (require-all (subpath (param "WRITABLE_ROOT_0")) (require-not (subpath (param "WRITABLE_ROOT_0_RO_0"))) ) (subpath (param "WRITABLE_ROOT_1")){tempdir_policy_entry}
 )
@@ -292,6 +347,7 @@ mod tests {
             expected_args.push(format!("-DWRITABLE_ROOT_2={p}"));
         }
 
+        expected_args.extend(read_denied_args);
         expected_args.extend(vec![
             "--".to_string(),
             "/bin/echo".to_string(),
@@ -301,6 +357,30 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn create_seatbelt_args_denies_reading_deny_read_paths() {
+        let tmp = TempDir::new().expect("tempdir");
+        let cwd = tmp.path().canonicalize().expect("canonicalize tempdir");
+        fs::write(cwd.join(".env"), "TOKEN=secret").expect("write .env");
+        let policy = SandboxPolicy::ReadOnly {
+            deny_read: vec![PathBuf::from(".env")],
+        };
+
+        let args = create_seatbelt_command_args(vec!["/bin/true".to_string()], &policy, &cwd, None);
+
+        let denied = policy.get_read_denied_paths_with_cwd(&cwd);
+        let index = denied
+            .iter()
+            .position(|path| path == &cwd.join(".env"))
+            .expect(".env is read-denied");
+        let param = format!("READ_DENIED_{index}");
+        assert!(args.contains(&format!("-D{param}={}", cwd.join(".env").display())));
+        assert!(args[1].contains("(allow file-read*)\n(deny file-read-data\n"));
+        assert!(args[1].contains(&format!("(subpath (param \"{param}\"))")));
+        // `.env` files are denied in every directory, not just the root.
+        assert!(args[1].contains(r#"(regex #"/\.env$")"#));
+    }
+
     struct PopulatedTmp {
         root_with_git: PathBuf,
         root_without_git: PathBuf,
@@ -309,6 +309,7 @@ mod tests {
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
             use crate::protocol::SandboxPolicy;
+            use crate::resource_limits::ResourceLimits;
 
             let temp_home = tempfile::tempdir().unwrap();
             let bashrc_path = temp_home.path().join(".bashrc");
@@ -352,6 +353,8 @@ mod tests {
                 &SandboxPolicy::DangerFullAccess,
                 temp_home.path(),
                 &None,
+                &ResourceLimits::default(),
+                false,
                 None,
             )
             .await
@@ -416,6 +419,7 @@ mod macos_tests {
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
             use crate::protocol::SandboxPolicy;
+            use crate::resource_limits::ResourceLimits;
 
             // create a temp directory with a zshrc file in it
             let temp_home = tempfile::tempdir().unwrap();
@@ -460,6 +464,8 @@ mod macos_tests {
                 &SandboxPolicy::DangerFullAccess,
                 temp_home.path(),
                 &None,
+                &ResourceLimits::default(),
+                false,
                 None,
             )
             .await
@@ -1,7 +1,12 @@
 use crate::RolloutRecorder;
+use crate::config_types::TokenBudget;
+use crate::dry_run::DryRunOverlay;
 use crate::exec_command::ExecSessionManager;
 use crate::executor::Executor;
+use crate::file_reads::FileReads;
+use crate::ghost_snapshots::GhostSnapshots;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::turn_diff_tracker::SessionDiffHistory;
 use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_notification::UserNotifier;
 use tokio::sync::Mutex;
@@ -14,5 +19,11 @@ pub(crate) struct SessionServices {
     pub(crate) rollout: Mutex<Option<RolloutRecorder>>,
     pub(crate) user_shell: crate::shell::Shell,
     pub(crate) show_raw_agent_reasoning: bool,
+    pub(crate) token_budget: TokenBudget,
     pub(crate) executor: Executor,
+    pub(crate) ghost_snapshots: Mutex<GhostSnapshots>,
+    pub(crate) file_reads: Mutex<FileReads>,
+    pub(crate) diff_history: Mutex<SessionDiffHistory>,
+    /// Set when patches are kept in memory instead of being written to disk.
+    pub(crate) dry_run: Option<Mutex<DryRunOverlay>>,
 }
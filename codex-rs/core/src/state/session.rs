@@ -1,5 +1,7 @@
 //! Session-wide mutable state.
 
+use std::collections::HashSet;
+
 use codex_protocol::models::ResponseItem;
 
 use crate::conversation_history::ConversationHistory;
@@ -13,6 +15,11 @@ pub(crate) struct SessionState {
     pub(crate) history: ConversationHistory,
     pub(crate) token_info: Option<TokenUsageInfo>,
     pub(crate) latest_rate_limits: Option<RateLimitSnapshot>,
+    /// MCP servers whose sampling requests the user approved for the session.
+    approved_sampling_servers: HashSet<String>,
+    /// Estimated spend of the session, priced per model as usage comes in.
+    estimated_cost_usd: f64,
+    token_budget_warning_sent: bool,
 }
 
 impl SessionState {
@@ -73,5 +80,29 @@ impl SessionState {
         }
     }
 
+    // Token budget helpers
+    pub(crate) fn add_estimated_cost(&mut self, cost_usd: f64) {
+        self.estimated_cost_usd += cost_usd;
+    }
+
+    pub(crate) fn estimated_cost_usd(&self) -> f64 {
+        self.estimated_cost_usd
+    }
+
+    /// Returns `true` the first time it is called, so the budget warning is
+    /// only sent once per session.
+    pub(crate) fn mark_token_budget_warning_sent(&mut self) -> bool {
+        !std::mem::replace(&mut self.token_budget_warning_sent, true)
+    }
+
+    // MCP sampling approval helpers
+    pub(crate) fn is_sampling_approved(&self, server: &str) -> bool {
+        self.approved_sampling_servers.contains(server)
+    }
+
+    pub(crate) fn approve_sampling_server(&mut self, server: String) {
+        self.approved_sampling_servers.insert(server);
+    }
+
     // Pending input/approval moved to TurnState.
 }
@@ -9,6 +9,7 @@ use tokio::task::AbortHandle;
 use codex_protocol::models::ResponseInputItem;
 use tokio::sync::oneshot;
 
+use crate::codex::TurnContext;
 use crate::protocol::ReviewDecision;
 use crate::tasks::SessionTask;
 
@@ -49,6 +50,7 @@ pub(crate) struct RunningTask {
     pub(crate) handle: AbortHandle,
     pub(crate) kind: TaskKind,
     pub(crate) task: Arc<dyn SessionTask>,
+    pub(crate) turn_context: Arc<TurnContext>,
 }
 
 impl ActiveTurn {
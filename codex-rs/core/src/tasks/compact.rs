@@ -9,6 +9,7 @@ use crate::state::TaskKind;
 
 use super::SessionTask;
 use super::SessionTaskContext;
+use super::TaskOutcome;
 
 #[derive(Clone, Copy, Default)]
 pub(crate) struct CompactTask;
@@ -25,7 +26,9 @@ impl SessionTask for CompactTask {
         ctx: Arc<TurnContext>,
         sub_id: String,
         input: Vec<InputItem>,
-    ) -> Option<String> {
-        compact::run_compact_task(session.clone_session(), ctx, sub_id, input).await
+    ) -> TaskOutcome {
+        TaskOutcome::Finished(
+            compact::run_compact_task(session.clone_session(), ctx, sub_id, input).await,
+        )
     }
 }
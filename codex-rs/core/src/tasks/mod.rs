@@ -9,6 +9,7 @@ use tracing::trace;
 
 use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::InputItem;
@@ -23,6 +24,15 @@ pub(crate) use compact::CompactTask;
 pub(crate) use regular::RegularTask;
 pub(crate) use review::ReviewTask;
 
+/// How a task stopped running.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TaskOutcome {
+    /// The task ran to completion, possibly with a final agent message.
+    Finished(Option<String>),
+    /// The task stopped early because the session's token budget ran out.
+    OverBudget(String),
+}
+
 /// Thin wrapper that exposes the parts of [`Session`] task runners need.
 #[derive(Clone)]
 pub(crate) struct SessionTaskContext {
@@ -49,7 +59,7 @@ pub(crate) trait SessionTask: Send + Sync + 'static {
         ctx: Arc<TurnContext>,
         sub_id: String,
         input: Vec<InputItem>,
-    ) -> Option<String>;
+    ) -> TaskOutcome;
 
     async fn abort(&self, session: Arc<SessionTaskContext>, sub_id: &str) {
         let _ = (session, sub_id);
@@ -75,12 +85,19 @@ impl Session {
             let task_for_run = Arc::clone(&task);
             let sub_clone = sub_id.clone();
             tokio::spawn(async move {
-                let last_agent_message = task_for_run
+                let outcome = task_for_run
                     .run(Arc::clone(&session_ctx), ctx, sub_clone.clone(), input)
                     .await;
                 // Emit completion uniformly from spawn site so all tasks share the same lifecycle.
                 let sess = session_ctx.clone_session();
-                sess.on_task_finished(sub_clone, last_agent_message).await;
+                match outcome {
+                    TaskOutcome::Finished(last_agent_message) => {
+                        sess.on_task_finished(sub_clone, last_agent_message).await
+                    }
+                    TaskOutcome::OverBudget(message) => {
+                        sess.on_task_over_budget(sub_clone, message).await
+                    }
+                }
             })
             .abort_handle()
         };
@@ -89,6 +106,7 @@ impl Session {
             handle,
             kind: task_kind,
             task,
+            turn_context,
         };
         self.register_new_active_task(sub_id, running_task).await;
     }
@@ -104,13 +122,7 @@ impl Session {
         sub_id: String,
         last_agent_message: Option<String>,
     ) {
-        let mut active = self.active_turn.lock().await;
-        if let Some(at) = active.as_mut()
-            && at.remove_task(&sub_id)
-        {
-            *active = None;
-        }
-        drop(active);
+        self.remove_active_task(&sub_id).await;
         let event = Event {
             id: sub_id,
             msg: EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }),
@@ -118,6 +130,32 @@ impl Session {
         self.send_event(event).await;
     }
 
+    /// Ends a task that stopped because the session's token budget ran out.
+    async fn on_task_over_budget(self: &Arc<Self>, sub_id: String, message: String) {
+        self.remove_active_task(&sub_id).await;
+        self.send_event(Event {
+            id: sub_id.clone(),
+            msg: EventMsg::Error(ErrorEvent { message }),
+        })
+        .await;
+        self.send_event(Event {
+            id: sub_id,
+            msg: EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::BudgetExceeded,
+            }),
+        })
+        .await;
+    }
+
+    async fn remove_active_task(&self, sub_id: &str) {
+        let mut active = self.active_turn.lock().await;
+        if let Some(at) = active.as_mut()
+            && at.remove_task(sub_id)
+        {
+            *active = None;
+        }
+    }
+
     async fn register_new_active_task(&self, sub_id: String, task: RunningTask) {
         let mut active = self.active_turn.lock().await;
         let mut turn = ActiveTurn::default();
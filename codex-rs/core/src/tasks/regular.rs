@@ -9,6 +9,7 @@ use crate::state::TaskKind;
 
 use super::SessionTask;
 use super::SessionTaskContext;
+use super::TaskOutcome;
 
 #[derive(Clone, Copy, Default)]
 pub(crate) struct RegularTask;
@@ -25,7 +26,7 @@ impl SessionTask for RegularTask {
         ctx: Arc<TurnContext>,
         sub_id: String,
         input: Vec<InputItem>,
-    ) -> Option<String> {
+    ) -> TaskOutcome {
         let sess = session.clone_session();
         run_task(sess, ctx, sub_id, input, TaskKind::Regular).await
     }
@@ -10,6 +10,7 @@ use crate::state::TaskKind;
 
 use super::SessionTask;
 use super::SessionTaskContext;
+use super::TaskOutcome;
 
 #[derive(Clone, Copy, Default)]
 pub(crate) struct ReviewTask;
@@ -26,7 +27,7 @@ impl SessionTask for ReviewTask {
         ctx: Arc<TurnContext>,
         sub_id: String,
         input: Vec<InputItem>,
-    ) -> Option<String> {
+    ) -> TaskOutcome {
         let sess = session.clone_session();
         run_task(sess, ctx, sub_id, input, TaskKind::Review).await
     }
@@ -0,0 +1,117 @@
+//! Enforcement of the `[token_budget]` limits from config.toml.
+
+use crate::config_types::TokenBudget;
+use crate::protocol::TokenUsage;
+
+/// Result of comparing the usage of a session against its [`TokenBudget`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BudgetStatus {
+    Within,
+    /// The most used limit crossed `warn_at_percent`.
+    Warning(String),
+    /// At least one limit is used up.
+    Exhausted(String),
+}
+
+pub(crate) fn check_budget(
+    budget: &TokenBudget,
+    usage: &TokenUsage,
+    estimated_cost_usd: f64,
+) -> BudgetStatus {
+    let limits = [
+        budget.max_total_tokens.map(|max| {
+            (
+                usage.total_tokens as f64,
+                max as f64,
+                format!("{} of {max} total tokens", usage.total_tokens),
+            )
+        }),
+        budget.max_output_tokens.map(|max| {
+            (
+                usage.output_tokens as f64,
+                max as f64,
+                format!("{} of {max} output tokens", usage.output_tokens),
+            )
+        }),
+        budget.max_cost_usd.map(|max| {
+            (
+                estimated_cost_usd,
+                max,
+                format!("${estimated_cost_usd:.2} of ${max:.2} estimated cost"),
+            )
+        }),
+    ];
+
+    let most_used = limits
+        .into_iter()
+        .flatten()
+        .map(|(used, max, description)| {
+            let fraction = if max > 0.0 { used / max } else { f64::INFINITY };
+            (fraction, description)
+        })
+        .max_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    match most_used {
+        Some((fraction, description)) if fraction >= 1.0 => {
+            BudgetStatus::Exhausted(format!("Token budget exhausted: used {description}."))
+        }
+        Some((fraction, description)) if fraction * 100.0 >= f64::from(budget.warn_at_percent) => {
+            let percent = (fraction * 100.0).floor();
+            BudgetStatus::Warning(format!("Token budget {percent}% used: {description}."))
+        }
+        _ => BudgetStatus::Within,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn usage(total_tokens: u64, output_tokens: u64) -> TokenUsage {
+        TokenUsage {
+            total_tokens,
+            output_tokens,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unlimited_budget_is_never_exhausted() {
+        assert_eq!(
+            check_budget(&TokenBudget::default(), &usage(u64::MAX, u64::MAX), 1e9),
+            BudgetStatus::Within
+        );
+    }
+
+    #[test]
+    fn most_used_limit_decides_the_status() {
+        let budget = TokenBudget {
+            max_total_tokens: Some(10_000),
+            max_output_tokens: Some(1_000),
+            max_cost_usd: Some(2.0),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_budget(&budget, &usage(5_000, 500), 0.5),
+            BudgetStatus::Within
+        );
+        assert_eq!(
+            check_budget(&budget, &usage(5_000, 850), 0.5),
+            BudgetStatus::Warning("Token budget 85% used: 850 of 1000 output tokens.".to_string())
+        );
+        assert_eq!(
+            check_budget(&budget, &usage(5_000, 850), 2.5),
+            BudgetStatus::Exhausted(
+                "Token budget exhausted: used $2.50 of $2.00 estimated cost.".to_string()
+            )
+        );
+        assert_eq!(
+            check_budget(&budget, &usage(10_000, 0), 0.0),
+            BudgetStatus::Exhausted(
+                "Token budget exhausted: used 10000 of 10000 total tokens.".to_string()
+            )
+        );
+    }
+}
@@ -139,8 +139,12 @@ Each operation starts with one of three headers:
 *** Add File: <path> - create a new file. Every following line is a + line (the initial contents).
 *** Delete File: <path> - remove an existing file. Nothing follows.
 *** Update File: <path> - patch an existing file in place (optionally with a rename).
+*** Copy File: <path> - copy an existing file. Must be followed by *** Copy to: <new path>.
+*** Binary File: <path> - create or replace a binary file. Every following line is a + line holding base64-encoded contents.
 
 May be immediately followed by *** Move to: <new path> if you want to rename the file.
+Add, Update and Binary headers may be followed by *** Mode: +x (or *** Mode: -x) to set (or clear) the executable bit; use it instead of running chmod.
+An Update that only renames a file or changes its mode needs no hunks.
 Then one or more “hunks”, each introduced by @@ (optionally followed by a hunk header).
 Within a hunk each line starts with:
 
@@ -166,11 +170,14 @@ The full grammar definition is below:
 Patch := Begin { FileOp } End
 Begin := "*** Begin Patch" NEWLINE
 End := "*** End Patch" NEWLINE
-FileOp := AddFile | DeleteFile | UpdateFile
-AddFile := "*** Add File: " path NEWLINE { "+" line NEWLINE }
+FileOp := AddFile | DeleteFile | UpdateFile | CopyFile | BinaryFile
+AddFile := "*** Add File: " path NEWLINE [ Mode ] { "+" line NEWLINE }
 DeleteFile := "*** Delete File: " path NEWLINE
-UpdateFile := "*** Update File: " path NEWLINE [ MoveTo ] { Hunk }
+UpdateFile := "*** Update File: " path NEWLINE [ MoveTo ] [ Mode ] { Hunk }
+CopyFile := "*** Copy File: " path NEWLINE "*** Copy to: " newPath NEWLINE
+BinaryFile := "*** Binary File: " path NEWLINE [ Mode ] { "+" base64 NEWLINE }
 MoveTo := "*** Move to: " newPath NEWLINE
+Mode := "*** Mode: " ("+x" | "-x") NEWLINE
 Hunk := "@@" [ header ] NEWLINE { HunkLine } [ "*** End of File" NEWLINE ]
 HunkLine := (" " | "-" | "+") text NEWLINE
 
@@ -179,6 +186,10 @@ A full patch can combine several operations:
 *** Begin Patch
 *** Add File: hello.txt
 +Hello world
+*** Add File: scripts/run.sh
+*** Mode: +x
++#!/bin/sh
++python -m app
 *** Update File: src/app.py
 *** Move to: src/main.py
 @@ def greet():
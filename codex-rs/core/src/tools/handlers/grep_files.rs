@@ -75,6 +75,13 @@ impl ToolHandler for GrepFilesHandler {
         let search_path = turn.resolve_path(args.path.clone());
 
         verify_path_exists(&search_path).await?;
+        let is_read_denied = |path: &Path| turn.sandbox_policy.is_path_read_denied(path, &turn.cwd);
+        if is_read_denied(&search_path) {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "searching `{}` is denied by the sandbox policy",
+                search_path.display()
+            )));
+        }
 
         let include = args.include.as_deref().map(str::trim).and_then(|val| {
             if val.is_empty() {
@@ -84,8 +91,15 @@ impl ToolHandler for GrepFilesHandler {
             }
         });
 
-        let search_results =
-            run_rg_search(pattern, include.as_deref(), &search_path, limit, &turn.cwd).await?;
+        let search_results = run_rg_search(
+            pattern,
+            include.as_deref(),
+            &search_path,
+            limit,
+            &turn.cwd,
+            &is_read_denied,
+        )
+        .await?;
 
         if search_results.is_empty() {
             Ok(ToolOutput::Function {
@@ -114,6 +128,7 @@ async fn run_rg_search(
     search_path: &Path,
     limit: usize,
     cwd: &Path,
+    is_read_denied: &(dyn Fn(&Path) -> bool + Sync),
 ) -> Result<Vec<String>, FunctionCallError> {
     let mut command = Command::new("rg");
     command
@@ -142,7 +157,7 @@ async fn run_rg_search(
         })?;
 
     match output.status.code() {
-        Some(0) => Ok(parse_results(&output.stdout, limit)),
+        Some(0) => Ok(parse_results(&output.stdout, limit, is_read_denied)),
         Some(1) => Ok(Vec::new()),
         _ => {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -153,14 +168,19 @@ async fn run_rg_search(
     }
 }
 
-fn parse_results(stdout: &[u8], limit: usize) -> Vec<String> {
+/// Collects up to `limit` matching paths, leaving out read-denied ones.
+fn parse_results(
+    stdout: &[u8],
+    limit: usize,
+    is_read_denied: &dyn Fn(&Path) -> bool,
+) -> Vec<String> {
     let mut results = Vec::new();
     for line in stdout.split(|byte| *byte == b'\n') {
         if line.is_empty() {
             continue;
         }
         if let Ok(text) = std::str::from_utf8(line) {
-            if text.is_empty() {
+            if text.is_empty() || is_read_denied(Path::new(text)) {
                 continue;
             }
             results.push(text.to_string());
@@ -181,7 +201,7 @@ mod tests {
     #[test]
     fn parses_basic_results() {
         let stdout = b"/tmp/file_a.rs\n/tmp/file_b.rs\n";
-        let parsed = parse_results(stdout, 10);
+        let parsed = parse_results(stdout, 10, &|_| false);
         assert_eq!(
             parsed,
             vec!["/tmp/file_a.rs".to_string(), "/tmp/file_b.rs".to_string()]
@@ -191,7 +211,7 @@ mod tests {
     #[test]
     fn parse_truncates_after_limit() {
         let stdout = b"/tmp/file_a.rs\n/tmp/file_b.rs\n/tmp/file_c.rs\n";
-        let parsed = parse_results(stdout, 2);
+        let parsed = parse_results(stdout, 2, &|_| false);
         assert_eq!(
             parsed,
             vec!["/tmp/file_a.rs".to_string(), "/tmp/file_b.rs".to_string()]
@@ -209,7 +229,7 @@ mod tests {
         std::fs::write(dir.join("match_two.txt"), "alpha delta").unwrap();
         std::fs::write(dir.join("other.txt"), "omega").unwrap();
 
-        let results = run_rg_search("alpha", None, dir, 10, dir).await?;
+        let results = run_rg_search("alpha", None, dir, 10, dir, &|_| false).await?;
         assert_eq!(results.len(), 2);
         assert!(results.iter().any(|path| path.ends_with("match_one.txt")));
         assert!(results.iter().any(|path| path.ends_with("match_two.txt")));
@@ -226,7 +246,7 @@ mod tests {
         std::fs::write(dir.join("match_one.rs"), "alpha beta gamma").unwrap();
         std::fs::write(dir.join("match_two.txt"), "alpha delta").unwrap();
 
-        let results = run_rg_search("alpha", Some("*.rs"), dir, 10, dir).await?;
+        let results = run_rg_search("alpha", Some("*.rs"), dir, 10, dir, &|_| false).await?;
         assert_eq!(results.len(), 1);
         assert!(results.iter().all(|path| path.ends_with("match_one.rs")));
         Ok(())
@@ -243,11 +263,21 @@ mod tests {
         std::fs::write(dir.join("two.txt"), "alpha two").unwrap();
         std::fs::write(dir.join("three.txt"), "alpha three").unwrap();
 
-        let results = run_rg_search("alpha", None, dir, 2, dir).await?;
+        let results = run_rg_search("alpha", None, dir, 2, dir, &|_| false).await?;
         assert_eq!(results.len(), 2);
         Ok(())
     }
 
+    #[test]
+    fn parse_skips_read_denied_paths() {
+        let stdout = b"/tmp/file_a.rs\n/tmp/.env\n/tmp/file_b.rs\n";
+        let parsed = parse_results(stdout, 2, &|path| path.ends_with(".env"));
+        assert_eq!(
+            parsed,
+            vec!["/tmp/file_a.rs".to_string(), "/tmp/file_b.rs".to_string()]
+        );
+    }
+
     #[tokio::test]
     async fn run_search_handles_no_matches() -> anyhow::Result<()> {
         if !rg_available() {
@@ -257,7 +287,7 @@ mod tests {
         let dir = temp.path();
         std::fs::write(dir.join("one.txt"), "omega").unwrap();
 
-        let results = run_rg_search("alpha", None, dir, 5, dir).await?;
+        let results = run_rg_search("alpha", None, dir, 5, dir, &|_| false).await?;
         assert!(results.is_empty());
         Ok(())
     }
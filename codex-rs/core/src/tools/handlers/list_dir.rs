@@ -51,7 +51,7 @@ impl ToolHandler for ListDirHandler {
     }
 
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
-        let ToolInvocation { payload, .. } = invocation;
+        let ToolInvocation { payload, turn, .. } = invocation;
 
         let arguments = match payload {
             ToolPayload::Function { arguments } => arguments,
@@ -100,7 +100,14 @@ impl ToolHandler for ListDirHandler {
             ));
         }
 
-        let entries = list_dir_slice(&path, offset, limit, depth).await?;
+        let is_read_denied = |path: &Path| turn.sandbox_policy.is_path_read_denied(path, &turn.cwd);
+        if is_read_denied(&path) {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "listing `{dir_path}` is denied by the sandbox policy"
+            )));
+        }
+
+        let entries = list_dir_slice(&path, offset, limit, depth, &is_read_denied).await?;
         let mut output = Vec::with_capacity(entries.len() + 1);
         output.push(format!("Absolute path: {}", path.display()));
         output.extend(entries);
@@ -116,9 +123,10 @@ async fn list_dir_slice(
     offset: usize,
     limit: usize,
     depth: usize,
+    is_read_denied: &(dyn Fn(&Path) -> bool + Sync),
 ) -> Result<Vec<String>, FunctionCallError> {
     let mut entries = Vec::new();
-    collect_entries(path, Path::new(""), depth, &mut entries).await?;
+    collect_entries(path, Path::new(""), depth, is_read_denied, &mut entries).await?;
 
     if entries.is_empty() {
         return Ok(Vec::new());
@@ -153,6 +161,7 @@ async fn collect_entries(
     dir_path: &Path,
     relative_prefix: &Path,
     depth: usize,
+    is_read_denied: &(dyn Fn(&Path) -> bool + Sync),
     entries: &mut Vec<DirEntry>,
 ) -> Result<(), FunctionCallError> {
     let mut queue = VecDeque::new();
@@ -199,7 +208,11 @@ async fn collect_entries(
         dir_entries.sort_unstable_by(|a, b| a.3.name.cmp(&b.3.name));
 
         for (entry_path, relative_path, kind, dir_entry) in dir_entries {
-            if kind == DirEntryKind::Directory && remaining_depth > 1 {
+            // Read-denied directories are listed but not descended into.
+            if kind == DirEntryKind::Directory
+                && remaining_depth > 1
+                && !is_read_denied(&entry_path)
+            {
                 queue.push_back((entry_path, relative_path, remaining_depth - 1));
             }
             entries.push(dir_entry);
@@ -306,7 +319,7 @@ mod tests {
             symlink(dir_path.join("entry.txt"), &link_path).expect("create symlink");
         }
 
-        let entries = list_dir_slice(dir_path, 1, 20, 3)
+        let entries = list_dir_slice(dir_path, 1, 20, 3, &|_| false)
             .await
             .expect("list directory");
 
@@ -340,7 +353,7 @@ mod tests {
             .await
             .expect("create sub dir");
 
-        let err = list_dir_slice(dir_path, 10, 1, 2)
+        let err = list_dir_slice(dir_path, 10, 1, 2, &|_| false)
             .await
             .expect_err("offset exceeds entries");
         assert_eq!(
@@ -367,7 +380,7 @@ mod tests {
             .await
             .expect("write deeper");
 
-        let entries_depth_one = list_dir_slice(dir_path, 1, 10, 1)
+        let entries_depth_one = list_dir_slice(dir_path, 1, 10, 1, &|_| false)
             .await
             .expect("list depth 1");
         assert_eq!(
@@ -375,7 +388,7 @@ mod tests {
             vec!["nested/".to_string(), "root.txt".to_string(),]
         );
 
-        let entries_depth_two = list_dir_slice(dir_path, 1, 20, 2)
+        let entries_depth_two = list_dir_slice(dir_path, 1, 20, 2, &|_| false)
             .await
             .expect("list depth 2");
         assert_eq!(
@@ -388,7 +401,7 @@ mod tests {
             ]
         );
 
-        let entries_depth_three = list_dir_slice(dir_path, 1, 30, 3)
+        let entries_depth_three = list_dir_slice(dir_path, 1, 30, 3, &|_| false)
             .await
             .expect("list depth 3");
         assert_eq!(
@@ -417,7 +430,7 @@ mod tests {
             .await
             .expect("write gamma");
 
-        let entries = list_dir_slice(dir_path, 2, usize::MAX, 1)
+        let entries = list_dir_slice(dir_path, 2, usize::MAX, 1, &|_| false)
             .await
             .expect("list without overflow");
         assert_eq!(
@@ -438,7 +451,7 @@ mod tests {
                 .expect("write file");
         }
 
-        let entries = list_dir_slice(dir_path, 1, 25, 1)
+        let entries = list_dir_slice(dir_path, 1, 25, 1, &|_| false)
             .await
             .expect("list directory");
         assert_eq!(entries.len(), 26);
@@ -448,6 +461,24 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn does_not_descend_into_read_denied_directories() -> anyhow::Result<()> {
+        let temp = tempdir()?;
+        let dir_path = temp.path();
+        let secrets = dir_path.join("secrets");
+        tokio::fs::create_dir(&secrets).await?;
+        tokio::fs::write(secrets.join("token"), b"token").await?;
+        tokio::fs::write(dir_path.join("notes.txt"), b"notes").await?;
+
+        let entries = list_dir_slice(dir_path, 1, 10, 2, &|path| path == secrets).await?;
+        assert_eq!(
+            entries,
+            vec!["notes.txt".to_string(), "secrets/".to_string()]
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn bfs_truncation() -> anyhow::Result<()> {
         let temp = tempdir()?;
@@ -460,7 +491,7 @@ mod tests {
         tokio::fs::write(nested.join("child.txt"), b"child").await?;
         tokio::fs::write(deeper.join("grandchild.txt"), b"deep").await?;
 
-        let entries_depth_three = list_dir_slice(dir_path, 1, 3, 3).await?;
+        let entries_depth_three = list_dir_slice(dir_path, 1, 3, 3, &|_| false).await?;
         assert_eq!(
             entries_depth_three,
             vec![
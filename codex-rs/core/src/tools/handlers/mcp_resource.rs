@@ -0,0 +1,212 @@
+use async_trait::async_trait;
+use mcp_types::ReadResourceResultContents;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::function_tool::FunctionCallError;
+use crate::mcp_connection_manager::McpConnectionManager;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub(crate) const LIST_MCP_RESOURCES_TOOL_NAME: &str = "list_mcp_resources";
+pub(crate) const READ_MCP_RESOURCE_TOOL_NAME: &str = "read_mcp_resource";
+
+/// Lets the model browse and read resources exposed by connected MCP servers.
+pub struct McpResourceHandler;
+
+#[derive(Deserialize)]
+struct ListMcpResourcesArgs {
+    #[serde(default)]
+    server: Option<String>,
+    #[serde(default)]
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReadMcpResourceArgs {
+    server: String,
+    uri: String,
+}
+
+#[async_trait]
+impl ToolHandler for McpResourceHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{tool_name} handler received unsupported payload"
+                )));
+            }
+        };
+
+        let manager = &session.services.mcp_connection_manager;
+        let output = match tool_name.as_str() {
+            LIST_MCP_RESOURCES_TOOL_NAME => {
+                list_resources(manager, parse_arguments(&arguments)?).await?
+            }
+            READ_MCP_RESOURCE_TOOL_NAME => {
+                read_resource(manager, parse_arguments(&arguments)?).await?
+            }
+            other => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "unsupported MCP resource tool: {other}"
+                )));
+            }
+        };
+
+        Ok(ToolOutput::Function {
+            content: output.to_string(),
+            success: Some(true),
+        })
+    }
+}
+
+fn parse_arguments<T: for<'de> Deserialize<'de>>(arguments: &str) -> Result<T, FunctionCallError> {
+    serde_json::from_str(arguments).map_err(|e| {
+        FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e:?}"))
+    })
+}
+
+async fn list_resources(
+    manager: &McpConnectionManager,
+    args: ListMcpResourcesArgs,
+) -> Result<Value, FunctionCallError> {
+    let ListMcpResourcesArgs { server, cursor } = args;
+    let servers = match server {
+        Some(server) => vec![server],
+        None => {
+            if cursor.is_some() {
+                return Err(FunctionCallError::RespondToModel(
+                    "`cursor` requires `server` to be set".to_string(),
+                ));
+            }
+            manager.resource_servers()
+        }
+    };
+
+    let mut pages = Vec::with_capacity(servers.len());
+    for server in servers {
+        // Report per-server failures inline so one broken server does not hide
+        // the resources of the others.
+        let page = match manager.list_resources(&server, cursor.clone()).await {
+            Ok(result) => json!({
+                "server": server,
+                "resources": result.resources,
+                "nextCursor": result.next_cursor,
+            }),
+            Err(e) => json!({
+                "server": server,
+                "error": format!("{e:#}"),
+            }),
+        };
+        pages.push(page);
+    }
+
+    Ok(json!({ "servers": pages }))
+}
+
+async fn read_resource(
+    manager: &McpConnectionManager,
+    args: ReadMcpResourceArgs,
+) -> Result<Value, FunctionCallError> {
+    let ReadMcpResourceArgs { server, uri } = args;
+    let result = manager
+        .read_resource(&server, &uri)
+        .await
+        .map_err(|e| FunctionCallError::RespondToModel(format!("{e:#}")))?;
+
+    let contents: Vec<Value> = result.contents.into_iter().map(contents_to_json).collect();
+    Ok(json!({
+        "server": server,
+        "uri": uri,
+        "contents": contents,
+    }))
+}
+
+/// Text contents are passed through verbatim; binary blobs are summarized
+/// because base64 data is of no use to the model and bloats the context.
+fn contents_to_json(contents: ReadResourceResultContents) -> Value {
+    match contents {
+        ReadResourceResultContents::TextResourceContents(text) => json!({
+            "uri": text.uri,
+            "mimeType": text.mime_type,
+            "text": text.text,
+        }),
+        ReadResourceResultContents::BlobResourceContents(blob) => json!({
+            "uri": blob.uri,
+            "mimeType": blob.mime_type,
+            "blob": format!("<{} bytes of binary data omitted>", decoded_len(&blob.blob)),
+        }),
+    }
+}
+
+/// Size of the data encoded by `base64`, without decoding it.
+fn decoded_len(base64: &str) -> usize {
+    let base64 = base64.trim_end();
+    let padding = base64.bytes().rev().take_while(|b| *b == b'=').count();
+    (base64.len() * 3 / 4).saturating_sub(padding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::BlobResourceContents;
+    use mcp_types::TextResourceContents;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn blob_contents_are_summarized() {
+        let blob = ReadResourceResultContents::BlobResourceContents(BlobResourceContents {
+            blob: "aGVsbG8=".to_string(),
+            mime_type: Some("application/octet-stream".to_string()),
+            uri: "file:///hello.bin".to_string(),
+        });
+        assert_eq!(
+            contents_to_json(blob),
+            json!({
+                "uri": "file:///hello.bin",
+                "mimeType": "application/octet-stream",
+                "blob": "<5 bytes of binary data omitted>",
+            })
+        );
+
+        let text = ReadResourceResultContents::TextResourceContents(TextResourceContents {
+            mime_type: None,
+            text: "hello".to_string(),
+            uri: "file:///hello.txt".to_string(),
+        });
+        assert_eq!(
+            contents_to_json(text),
+            json!({
+                "uri": "file:///hello.txt",
+                "mimeType": null,
+                "text": "hello",
+            })
+        );
+    }
+
+    #[test]
+    fn decoded_len_accounts_for_padding() {
+        assert_eq!(decoded_len(""), 0);
+        assert_eq!(decoded_len("aGVsbG8="), 5);
+        assert_eq!(decoded_len("aGVsbA=="), 4);
+        assert_eq!(decoded_len("aGVsbG8"), 5);
+        assert_eq!(decoded_len("aGVsbG8h"), 6);
+    }
+}
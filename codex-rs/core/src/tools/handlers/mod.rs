@@ -3,6 +3,7 @@ mod exec_stream;
 mod grep_files;
 mod list_dir;
 mod mcp;
+pub(crate) mod mcp_resource;
 mod plan;
 mod read_file;
 mod shell;
@@ -17,6 +18,7 @@ pub use exec_stream::ExecStreamHandler;
 pub use grep_files::GrepFilesHandler;
 pub use list_dir::ListDirHandler;
 pub use mcp::McpHandler;
+pub use mcp_resource::McpResourceHandler;
 pub use plan::PlanHandler;
 pub use read_file::ReadFileHandler;
 pub use shell::ShellHandler;
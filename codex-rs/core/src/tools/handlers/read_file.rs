@@ -96,7 +96,12 @@ impl ToolHandler for ReadFileHandler {
     }
 
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
-        let ToolInvocation { payload, .. } = invocation;
+        let ToolInvocation {
+            session,
+            turn,
+            payload,
+            ..
+        } = invocation;
 
         let arguments = match payload {
             ToolPayload::Function { arguments } => arguments,
@@ -139,14 +144,42 @@ impl ToolHandler for ReadFileHandler {
                 "file_path must be an absolute path".to_string(),
             ));
         }
+        if turn.sandbox_policy.is_path_read_denied(&path, &turn.cwd) {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "failed to read file: reading `{file_path}` is denied by the sandbox policy"
+            )));
+        }
 
-        let collected = match mode {
-            ReadMode::Slice => slice::read(&path, offset, limit).await?,
-            ReadMode::Indentation => {
-                let indentation = indentation.unwrap_or_default();
+        // In dry-run mode, files edited by earlier patches are read from the
+        // pending edits.
+        let pending = match &session.services.dry_run {
+            Some(overlay) => overlay
+                .lock()
+                .await
+                .pending(&path)
+                .map(|contents| contents.map(<[u8]>::to_vec)),
+            None => None,
+        };
+        let indentation = indentation.unwrap_or_default();
+        let collected = match (pending, mode) {
+            (Some(None), _) => {
+                return Err(FunctionCallError::RespondToModel(
+                    "failed to read file: it was deleted by an earlier patch".to_string(),
+                ));
+            }
+            (Some(Some(contents)), ReadMode::Slice) => {
+                slice::read_lines(contents.as_slice(), offset, limit).await?
+            }
+            (Some(Some(contents)), ReadMode::Indentation) => {
+                indentation::read_block_from(contents.as_slice(), offset, limit, indentation)
+                    .await?
+            }
+            (None, ReadMode::Slice) => slice::read(&path, offset, limit).await?,
+            (None, ReadMode::Indentation) => {
                 indentation::read_block(&path, offset, limit, indentation).await?
             }
         };
+        session.record_file_reads([path]).await;
         Ok(ToolOutput::Function {
             content: collected.join("\n"),
             success: Some(true),
@@ -159,6 +192,7 @@ mod slice {
     use crate::tools::handlers::read_file::format_line;
     use std::path::Path;
     use tokio::fs::File;
+    use tokio::io::AsyncBufRead;
     use tokio::io::AsyncBufReadExt;
     use tokio::io::BufReader;
 
@@ -170,8 +204,14 @@ mod slice {
         let file = File::open(path).await.map_err(|err| {
             FunctionCallError::RespondToModel(format!("failed to read file: {err}"))
         })?;
+        read_lines(BufReader::new(file), offset, limit).await
+    }
 
-        let mut reader = BufReader::new(file);
+    pub async fn read_lines(
+        mut reader: impl AsyncBufRead + Unpin,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<String>, FunctionCallError> {
         let mut collected = Vec::new();
         let mut seen = 0usize;
         let mut buffer = Vec::new();
@@ -231,6 +271,7 @@ mod indentation {
     use std::collections::VecDeque;
     use std::path::Path;
     use tokio::fs::File;
+    use tokio::io::AsyncBufRead;
     use tokio::io::AsyncBufReadExt;
     use tokio::io::BufReader;
 
@@ -239,6 +280,18 @@ mod indentation {
         offset: usize,
         limit: usize,
         options: IndentationArgs,
+    ) -> Result<Vec<String>, FunctionCallError> {
+        let file = File::open(path).await.map_err(|err| {
+            FunctionCallError::RespondToModel(format!("failed to read file: {err}"))
+        })?;
+        read_block_from(BufReader::new(file), offset, limit, options).await
+    }
+
+    pub async fn read_block_from(
+        reader: impl AsyncBufRead + Unpin,
+        offset: usize,
+        limit: usize,
+        options: IndentationArgs,
     ) -> Result<Vec<String>, FunctionCallError> {
         let anchor_line = options.anchor_line.unwrap_or(offset);
         if anchor_line == 0 {
@@ -254,7 +307,7 @@ mod indentation {
             ));
         }
 
-        let collected = collect_file_lines(path).await?;
+        let collected = collect_lines(reader).await?;
         if collected.is_empty() || anchor_line > collected.len() {
             return Err(FunctionCallError::RespondToModel(
                 "anchor_line exceeds file length".to_string(),
@@ -366,12 +419,9 @@ mod indentation {
             .collect())
     }
 
-    async fn collect_file_lines(path: &Path) -> Result<Vec<LineRecord>, FunctionCallError> {
-        let file = File::open(path).await.map_err(|err| {
-            FunctionCallError::RespondToModel(format!("failed to read file: {err}"))
-        })?;
-
-        let mut reader = BufReader::new(file);
+    async fn collect_lines(
+        mut reader: impl AsyncBufRead + Unpin,
+    ) -> Result<Vec<LineRecord>, FunctionCallError> {
         let mut buffer = Vec::new();
         let mut lines = Vec::new();
         let mut number = 0usize;
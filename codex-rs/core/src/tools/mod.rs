@@ -19,13 +19,19 @@ use crate::exec::StdoutStream;
 use crate::executor::ExecutionMode;
 use crate::executor::errors::ExecError;
 use crate::executor::linkers::PreparedExec;
+use crate::executor::sandbox_failure_message;
 use crate::function_tool::FunctionCallError;
 use crate::tools::context::ApplyPatchCommandContext;
 use crate::tools::context::ExecCommandContext;
 use crate::tools::context::SharedTurnDiffTracker;
+use codex_apply_patch::ApplyPatchError;
+use codex_apply_patch::MaybeApplyPatch;
 use codex_apply_patch::MaybeApplyPatchVerified;
+use codex_apply_patch::maybe_parse_apply_patch;
 use codex_apply_patch::maybe_parse_apply_patch_verified;
+use codex_apply_patch::maybe_parse_apply_patch_verified_with_merge_base;
 use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::FuzzyPatchHunk;
 use codex_utils_string::take_bytes_at_char_boundary;
 use codex_utils_string::take_last_bytes_at_char_boundary;
 pub use router::ToolRouter;
@@ -67,8 +73,27 @@ pub(crate) async fn handle_container_exec_with_params(
         )));
     }
 
+    if sess.services.dry_run.is_some()
+        && let MaybeApplyPatch::Body(args) = maybe_parse_apply_patch(&params.command)
+    {
+        return sess
+            .apply_patch_dry_run(&sub_id, &call_id, args, &params.cwd)
+            .await;
+    }
+
     // check if this was a patch, and apply it if so
-    let apply_patch_exec = match maybe_parse_apply_patch_verified(&params.command, &params.cwd) {
+    let verified = match maybe_parse_apply_patch_verified(&params.command, &params.cwd) {
+        // The files may have changed since the model read them; try merging
+        // the patch against the versions it saw.
+        MaybeApplyPatchVerified::CorrectnessError(ApplyPatchError::ComputeReplacements(_)) => {
+            let file_reads = sess.services.file_reads.lock().await;
+            maybe_parse_apply_patch_verified_with_merge_base(&params.command, &params.cwd, |path| {
+                file_reads.get(path).map(str::to_string)
+            })
+        }
+        verified => verified,
+    };
+    let apply_patch_exec = match verified {
         MaybeApplyPatchVerified::Body(changes) => {
             match apply_patch::apply_patch(
                 sess.as_ref(),
@@ -118,6 +143,14 @@ pub(crate) async fn handle_container_exec_with_params(
              }| ApplyPatchCommandContext {
                 user_explicitly_approved_this_action: *user_explicitly_approved_this_action,
                 changes: convert_apply_patch_to_protocol(action),
+                fuzzy_hunks: action
+                    .fuzzy_hunks()
+                    .iter()
+                    .map(|hunk| FuzzyPatchHunk {
+                        path: hunk.path.clone(),
+                        hunk: u32::try_from(hunk.hunk).unwrap_or(u32::MAX),
+                    })
+                    .collect(),
             },
         ),
         tool_name: tool_name.to_string(),
@@ -170,6 +203,15 @@ pub(crate) async fn handle_container_exec_with_params(
         Err(ExecError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output }))) => Err(
             FunctionCallError::RespondToModel(format_exec_output_apply_patch(&output)),
         ),
+        Err(ExecError::Codex(CodexErr::Sandbox(SandboxErr::ResourceLimit { limit, output }))) => {
+            Err(FunctionCallError::RespondToModel(format!(
+                "command exceeded the {limit} limit of the sandbox\n{}",
+                format_exec_output_apply_patch(&output)
+            )))
+        }
+        Err(ExecError::Codex(CodexErr::Sandbox(err @ SandboxErr::Denied { .. }))) => Err(
+            FunctionCallError::RespondToModel(sandbox_failure_message(err)),
+        ),
         Err(ExecError::Codex(err)) => {
             let message = format!("execution error: {err:?}");
             Err(FunctionCallError::RespondToModel(format_exec_output(
@@ -1,5 +1,6 @@
 use crate::client_common::tools::ResponsesApiTool;
 use crate::client_common::tools::ToolSpec;
+use crate::error::CodexErr;
 use crate::features::Feature;
 use crate::features::Features;
 use crate::model_family::ModelFamily;
@@ -7,6 +8,8 @@ use crate::tools::handlers::PLAN_TOOL;
 use crate::tools::handlers::apply_patch::ApplyPatchToolType;
 use crate::tools::handlers::apply_patch::create_apply_patch_freeform_tool;
 use crate::tools::handlers::apply_patch::create_apply_patch_json_tool;
+use crate::tools::handlers::mcp_resource::LIST_MCP_RESOURCES_TOOL_NAME;
+use crate::tools::handlers::mcp_resource::READ_MCP_RESOURCE_TOOL_NAME;
 use crate::tools::registry::ToolRegistryBuilder;
 use serde::Deserialize;
 use serde::Serialize;
@@ -31,6 +34,9 @@ pub(crate) struct ToolsConfig {
     pub include_view_image_tool: bool,
     pub experimental_unified_exec_tool: bool,
     pub experimental_supported_tools: Vec<String>,
+    /// MCP servers that advertise the `resources` capability. When non-empty,
+    /// the `list_mcp_resources` and `read_mcp_resource` tools are exposed.
+    pub mcp_resource_servers: Vec<String>,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -79,8 +85,14 @@ impl ToolsConfig {
             include_view_image_tool,
             experimental_unified_exec_tool,
             experimental_supported_tools: model_family.experimental_supported_tools.clone(),
+            mcp_resource_servers: Vec::new(),
         }
     }
+
+    pub fn with_mcp_resource_servers(mut self, servers: Vec<String>) -> Self {
+        self.mcp_resource_servers = servers;
+        self
+    }
 }
 
 /// Generic JSON‑Schema subset needed for our tool definitions
@@ -257,6 +269,70 @@ fn create_view_image_tool() -> ToolSpec {
     })
 }
 
+fn create_list_mcp_resources_tool(servers: &[String]) -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "server".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Name of the MCP server to list. Omit to list resources from every server."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "cursor".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Pagination cursor returned by a previous call for the same server.".to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: LIST_MCP_RESOURCES_TOOL_NAME.to_string(),
+        description: format!(
+            "Lists resources (files, documents, records, ...) exposed by MCP servers. Servers with resources: {}.",
+            servers.join(", ")
+        ),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: None,
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_read_mcp_resource_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "server".to_string(),
+        JsonSchema::String {
+            description: Some("Name of the MCP server that owns the resource.".to_string()),
+        },
+    );
+    properties.insert(
+        "uri".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "URI of the resource, as returned by list_mcp_resources.".to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: READ_MCP_RESOURCE_TOOL_NAME.to_string(),
+        description: "Reads the contents of a resource exposed by an MCP server.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["server".to_string(), "uri".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
 fn create_test_sync_tool() -> ToolSpec {
     let mut properties = BTreeMap::new();
     properties.insert(
@@ -563,6 +639,101 @@ pub(crate) fn create_tools_json_for_chat_completions_api(
     Ok(tools_json)
 }
 
+/// Returns JSON values that are compatible with tool use in the Anthropic
+/// Messages API:
+/// https://docs.anthropic.com/en/docs/agents-and-tools/tool-use/overview
+pub(crate) fn create_tools_json_for_anthropic_messages_api(
+    tools: &[ToolSpec],
+) -> crate::error::Result<Vec<serde_json::Value>> {
+    let tools_json = function_tools(tools)?
+        .into_iter()
+        .map(|tool| {
+            json!({
+                "name": tool.name,
+                "description": tool.description,
+                "input_schema": tool.parameters,
+            })
+        })
+        .collect();
+    Ok(tools_json)
+}
+
+/// Returns the `tools` array for the Gemini generateContent API, which wraps
+/// all functions in a single `functionDeclarations` entry:
+/// https://ai.google.dev/gemini-api/docs/function-calling
+pub(crate) fn create_tools_json_for_gemini_api(
+    tools: &[ToolSpec],
+) -> crate::error::Result<Vec<serde_json::Value>> {
+    let declarations = function_tools(tools)?
+        .into_iter()
+        .map(|tool| {
+            let mut parameters = serde_json::to_value(&tool.parameters)?;
+            // Gemini accepts an OpenAPI subset of JSON Schema that rejects
+            // `additionalProperties`.
+            strip_additional_properties(&mut parameters);
+            Ok(json!({
+                "name": tool.name,
+                "description": tool.description,
+                "parameters": parameters,
+            }))
+        })
+        .collect::<crate::error::Result<Vec<_>>>()?;
+    if declarations.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(vec![json!({ "functionDeclarations": declarations })])
+}
+
+/// Function tools are the only kind the non-OpenAI wire APIs understand.
+/// The local shell and the freeform `apply_patch` tool are sent as their
+/// function equivalents, whose handlers accept both payloads; other freeform
+/// tools are rejected. Web search runs on OpenAI's side and is dropped.
+fn function_tools(tools: &[ToolSpec]) -> crate::error::Result<Vec<ResponsesApiTool>> {
+    let mut function_tools = Vec::new();
+    for tool in tools {
+        let converted = match tool {
+            ToolSpec::Function(tool) => tool.clone(),
+            ToolSpec::LocalShell {} => as_function_tool(create_shell_tool()),
+            ToolSpec::Freeform(tool) if tool.name == "apply_patch" => {
+                as_function_tool(create_apply_patch_json_tool())
+            }
+            ToolSpec::Freeform(tool) => {
+                return Err(CodexErr::UnsupportedOperation(format!(
+                    "freeform tool `{}` is only supported by the Responses API",
+                    tool.name
+                )));
+            }
+            ToolSpec::WebSearch {} => continue,
+        };
+        function_tools.push(converted);
+    }
+    Ok(function_tools)
+}
+
+fn as_function_tool(spec: ToolSpec) -> ResponsesApiTool {
+    match spec {
+        ToolSpec::Function(tool) => tool,
+        other => unreachable!("expected a function tool, got {other:?}"),
+    }
+}
+
+fn strip_additional_properties(value: &mut JsonValue) {
+    match value {
+        JsonValue::Object(map) => {
+            map.remove("additionalProperties");
+            for v in map.values_mut() {
+                strip_additional_properties(v);
+            }
+        }
+        JsonValue::Array(arr) => {
+            for v in arr.iter_mut() {
+                strip_additional_properties(v);
+            }
+        }
+        _ => {}
+    }
+}
+
 pub(crate) fn mcp_tool_to_openai_tool(
     fully_qualified_name: String,
     tool: mcp_types::Tool,
@@ -723,6 +894,7 @@ pub(crate) fn build_specs(
     use crate::tools::handlers::GrepFilesHandler;
     use crate::tools::handlers::ListDirHandler;
     use crate::tools::handlers::McpHandler;
+    use crate::tools::handlers::McpResourceHandler;
     use crate::tools::handlers::PlanHandler;
     use crate::tools::handlers::ReadFileHandler;
     use crate::tools::handlers::ShellHandler;
@@ -833,6 +1005,17 @@ pub(crate) fn build_specs(
         builder.register_handler("view_image", view_image_handler);
     }
 
+    if !config.mcp_resource_servers.is_empty() {
+        let mcp_resource_handler = Arc::new(McpResourceHandler);
+        builder.push_spec_with_parallel_support(
+            create_list_mcp_resources_tool(&config.mcp_resource_servers),
+            true,
+        );
+        builder.push_spec_with_parallel_support(create_read_mcp_resource_tool(), true);
+        builder.register_handler(LIST_MCP_RESOURCES_TOOL_NAME, mcp_resource_handler.clone());
+        builder.register_handler(READ_MCP_RESOURCE_TOOL_NAME, mcp_resource_handler);
+    }
+
     if let Some(mcp_tools) = mcp_tools {
         let mut entries: Vec<(String, mcp_types::Tool)> = mcp_tools.into_iter().collect();
         entries.sort_by(|a, b| a.0.cmp(&b.0));
@@ -856,6 +1039,7 @@ pub(crate) fn build_specs(
 #[cfg(test)]
 mod tests {
     use crate::client_common::tools::FreeformTool;
+    use crate::client_common::tools::FreeformToolFormat;
     use crate::model_family::find_family_for_model;
     use crate::tools::registry::ConfiguredToolSpec;
     use mcp_types::ToolInputSchema;
@@ -940,6 +1124,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_specs_mcp_resource_tools() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let features = Features::with_defaults();
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            features: &features,
+        });
+        let (tools, _) = build_specs(&config, None).build();
+        assert!(
+            !tools
+                .iter()
+                .any(|tool| tool_name(&tool.spec) == LIST_MCP_RESOURCES_TOOL_NAME)
+        );
+
+        let config = config.with_mcp_resource_servers(vec!["docs".to_string()]);
+        let (tools, _) = build_specs(&config, None).build();
+        let list_tool = find_tool(&tools, LIST_MCP_RESOURCES_TOOL_NAME);
+        assert!(list_tool.supports_parallel_tool_calls);
+        let ToolSpec::Function(ResponsesApiTool { description, .. }) = &list_tool.spec else {
+            panic!("expected function tool");
+        };
+        assert!(description.ends_with("Servers with resources: docs."));
+        assert!(find_tool(&tools, READ_MCP_RESOURCE_TOOL_NAME).supports_parallel_tool_calls);
+    }
+
     #[test]
     #[ignore]
     fn test_parallel_support_flags() {
@@ -1561,4 +1771,64 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_native_wire_api_tools_json() {
+        let tools = vec![
+            create_shell_tool(),
+            ToolSpec::WebSearch {},
+            ToolSpec::Freeform(FreeformTool {
+                name: "apply_patch".to_string(),
+                description: String::new(),
+                format: FreeformToolFormat {
+                    r#type: "grammar".to_string(),
+                    syntax: "lark".to_string(),
+                    definition: String::new(),
+                },
+            }),
+        ];
+
+        let anthropic = create_tools_json_for_anthropic_messages_api(&tools).unwrap();
+        assert_eq!(anthropic.len(), 2);
+        assert_eq!(anthropic[0]["name"], "shell");
+        assert_eq!(anthropic[0]["input_schema"]["type"], "object");
+        assert_eq!(anthropic[0]["input_schema"]["additionalProperties"], false);
+        assert_eq!(anthropic[1]["name"], "apply_patch");
+        assert_eq!(
+            anthropic[1]["input_schema"]["properties"]["input"]["type"],
+            "string"
+        );
+
+        let gemini = create_tools_json_for_gemini_api(&tools).unwrap();
+        assert_eq!(gemini.len(), 1);
+        let declarations = gemini[0]["functionDeclarations"].as_array().unwrap();
+        assert_eq!(declarations.len(), 2);
+        assert_eq!(declarations[0]["name"], "shell");
+        assert_eq!(declarations[0]["parameters"]["type"], "object");
+        assert_eq!(
+            declarations[0]["parameters"].get("additionalProperties"),
+            None
+        );
+        assert_eq!(declarations[1]["name"], "apply_patch");
+
+        let local_shell =
+            create_tools_json_for_anthropic_messages_api(&[ToolSpec::LocalShell {}]).unwrap();
+        assert_eq!(local_shell[0]["name"], "shell");
+
+        let unknown_freeform = ToolSpec::Freeform(FreeformTool {
+            name: "sql".to_string(),
+            description: String::new(),
+            format: FreeformToolFormat {
+                r#type: "grammar".to_string(),
+                syntax: "lark".to_string(),
+                definition: String::new(),
+            },
+        });
+        assert!(create_tools_json_for_gemini_api(&[unknown_freeform]).is_err());
+
+        assert_eq!(
+            create_tools_json_for_gemini_api(&[]).unwrap(),
+            Vec::<JsonValue>::new()
+        );
+    }
 }
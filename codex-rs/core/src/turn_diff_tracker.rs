@@ -10,9 +10,15 @@ use anyhow::anyhow;
 use sha1::digest::Output;
 use uuid::Uuid;
 
+use crate::protocol::EventMsg;
 use crate::protocol::FileChange;
+use crate::protocol::FileDiffStat;
+use crate::protocol::RolloutItem;
+use crate::protocol::SessionDiffEvent;
+use crate::protocol::SessionTurnDiff;
+use crate::protocol::UndoCompletedEvent;
 
-const ZERO_OID: &str = "0000000000000000000000000000000000000000";
+pub(crate) const ZERO_OID: &str = "0000000000000000000000000000000000000000";
 const DEV_NULL: &str = "/dev/null";
 
 struct BaselineFileInfo {
@@ -40,6 +46,8 @@ pub struct TurnDiffTracker {
     temp_name_to_current_path: HashMap<String, PathBuf>,
     /// Cache of known git worktree roots to avoid repeated filesystem walks.
     git_root_cache: Vec<PathBuf>,
+    /// Number of the turn within the session, see [`SessionDiffHistory`].
+    turn: u64,
 }
 
 impl TurnDiffTracker {
@@ -47,6 +55,17 @@ impl TurnDiffTracker {
         Self::default()
     }
 
+    pub fn for_turn(turn: u64) -> Self {
+        Self {
+            turn,
+            ..Self::default()
+        }
+    }
+
+    pub fn turn(&self) -> u64 {
+        self.turn
+    }
+
     /// Front-run apply patch calls to track the starting contents of any modified files.
     /// - Creates an in-memory baseline snapshot for files that already exist on disk when first seen.
     /// - For additions, we intentionally do not create a baseline snapshot so that diffs are proper additions.
@@ -294,8 +313,14 @@ impl TurnDiffTracker {
             None
         };
 
-        // Fast path: identical bytes or both missing.
+        // Fast path: identical bytes or both missing. A `chmod` without
+        // content changes only gets the mode header, like `git diff`.
         if left_bytes == right_bytes.as_deref() {
+            if left_present && baseline_mode != current_mode {
+                aggregated.push_str(&format!("diff --git a/{left_display} b/{right_display}\n"));
+                aggregated.push_str(&format!("old mode {baseline_mode}\n"));
+                aggregated.push_str(&format!("new mode {current_mode}\n"));
+            }
             return aggregated;
         }
 
@@ -369,7 +394,7 @@ impl TurnDiffTracker {
 }
 
 /// Compute the Git SHA-1 blob object ID for the given content (bytes).
-fn git_blob_sha1_hex_bytes(data: &[u8]) -> Output<sha1::Sha1> {
+pub(crate) fn git_blob_sha1_hex_bytes(data: &[u8]) -> Output<sha1::Sha1> {
     // Git blob hash is sha1 of: "blob <len>\0<data>"
     let header = format!("blob {}\0", data.len());
     use sha1::Digest;
@@ -464,9 +489,123 @@ fn is_windows_drive_or_unc_root(p: &std::path::Path) -> bool {
     )
 }
 
+/// Number of turns kept in a [`SessionDiffHistory`].
+const MAX_TRACKED_TURNS: usize = 50;
+
+/// The diffs of the most recent turns of a session that changed files. Turns
+/// are numbered like the session's ghost snapshots.
+#[derive(Debug, Default)]
+pub struct SessionDiffHistory {
+    turns: Vec<SessionTurnDiff>,
+}
+
+impl SessionDiffHistory {
+    /// Rebuild the history from the turn diffs recorded in a rollout.
+    pub(crate) fn replay(&mut self, items: &[RolloutItem]) {
+        for item in items {
+            match item {
+                RolloutItem::EventMsg(EventMsg::TurnDiff(event)) if event.turn > 0 => {
+                    self.record(event.turn, event.unified_diff.clone());
+                }
+                RolloutItem::EventMsg(EventMsg::UndoCompleted(UndoCompletedEvent {
+                    success: true,
+                    restored_turn: Some(turn),
+                    ..
+                })) => self.drop_from(*turn),
+                _ => {}
+            }
+        }
+    }
+
+    /// Record the latest diff of `turn`, replacing the one recorded before,
+    /// and return its per-file stats.
+    pub fn record(&mut self, turn: u64, unified_diff: String) -> Vec<FileDiffStat> {
+        let files = diff_stats(&unified_diff);
+        let entry = SessionTurnDiff {
+            turn,
+            unified_diff,
+            files: files.clone(),
+        };
+        match self.turns.iter_mut().find(|existing| existing.turn == turn) {
+            Some(existing) => *existing = entry,
+            None => {
+                self.turns.push(entry);
+                self.turns.sort_by_key(|existing| existing.turn);
+                if self.turns.len() > MAX_TRACKED_TURNS {
+                    self.turns.remove(0);
+                }
+            }
+        }
+        files
+    }
+
+    /// Forget `turn` and every later turn after they were undone.
+    pub fn drop_from(&mut self, turn: u64) {
+        self.turns.retain(|existing| existing.turn < turn);
+    }
+
+    /// Diffs of the turns starting at `since_turn` (all turns when `None`)
+    /// with per-file totals.
+    pub fn since(&self, since_turn: Option<u64>) -> SessionDiffEvent {
+        let turns: Vec<SessionTurnDiff> = self
+            .turns
+            .iter()
+            .filter(|turn| since_turn.is_none_or(|since| turn.turn >= since))
+            .cloned()
+            .collect();
+        let mut files: Vec<FileDiffStat> = Vec::new();
+        for stat in turns.iter().flat_map(|turn| &turn.files) {
+            match files.iter_mut().find(|file| file.path == stat.path) {
+                Some(file) => {
+                    file.added += stat.added;
+                    file.removed += stat.removed;
+                }
+                None => files.push(stat.clone()),
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        SessionDiffEvent { turns, files }
+    }
+}
+
+/// Lines added and removed per file of a git-style unified diff.
+pub fn diff_stats(unified_diff: &str) -> Vec<FileDiffStat> {
+    let mut files: Vec<FileDiffStat> = Vec::new();
+    let mut in_hunk = false;
+    for line in unified_diff.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            let path = rest.rsplit_once(" b/").map_or(rest, |(_, path)| path);
+            files.push(FileDiffStat {
+                path: PathBuf::from(path),
+                added: 0,
+                removed: 0,
+            });
+            in_hunk = false;
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("@@") {
+            in_hunk = true;
+        } else if !in_hunk {
+            // The `+++` header is unambiguous for paths with spaces.
+            if let Some(path) = line.strip_prefix("+++ b/") {
+                file.path = PathBuf::from(path);
+            }
+        } else if line.starts_with('+') {
+            file.added += 1;
+        } else if line.starts_with('-') {
+            file.removed += 1;
+        }
+    }
+    files
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::TurnDiffEvent;
     use pretty_assertions::assert_eq;
     use tempfile::tempdir;
 
@@ -515,6 +654,7 @@ mod tests {
             file.clone(),
             FileChange::Add {
                 content: "foo\n".to_string(),
+                executable: None,
             },
         )]);
         acc.on_patch_begin(&add_changes);
@@ -545,6 +685,7 @@ index {ZERO_OID}..{right_oid}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: None,
+                executable: None,
             },
         )]);
         acc.on_patch_begin(&update_changes);
@@ -620,6 +761,7 @@ index {left_oid}..{ZERO_OID}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: Some(dest.clone()),
+                executable: None,
             },
         )]);
         acc.on_patch_begin(&mv_changes);
@@ -660,6 +802,7 @@ index {left_oid}..{right_oid}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: Some(dest.clone()),
+                executable: None,
             },
         )]);
         acc.on_patch_begin(&mv_changes);
@@ -682,6 +825,7 @@ index {left_oid}..{right_oid}
             FileChange::Update {
                 unified_diff: "".into(),
                 move_path: Some(dest.clone()),
+                executable: None,
             },
         )]);
         acc.on_patch_begin(&mv);
@@ -722,6 +866,7 @@ index {ZERO_OID}..{right_oid}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: None,
+                executable: None,
             },
         )]);
         acc.on_patch_begin(&update_a);
@@ -784,6 +929,35 @@ index {left_oid_b}..{ZERO_OID}
         assert_eq!(combined, expected);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn mode_change_without_content_change_emits_mode_header() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("run.sh");
+        fs::write(&file, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_patch_begin(&HashMap::from([(
+            file.clone(),
+            FileChange::Update {
+                unified_diff: String::new(),
+                move_path: None,
+                executable: Some(true),
+            },
+        )]));
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let diff = acc.get_unified_diff().unwrap().unwrap();
+        let diff = normalize_diff_for_test(&diff, dir.path());
+        assert_eq!(
+            diff,
+            "diff --git a/<TMP>/run.sh b/<TMP>/run.sh\nold mode 100644\nnew mode 100755\n"
+        );
+    }
+
     #[test]
     fn binary_files_differ_update() {
         let dir = tempdir().unwrap();
@@ -802,6 +976,7 @@ index {left_oid_b}..{ZERO_OID}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: None,
+                executable: None,
             },
         )]);
         acc.on_patch_begin(&update_changes);
@@ -838,6 +1013,7 @@ Binary files differ
             file.clone(),
             FileChange::Add {
                 content: "foo\n".to_string(),
+                executable: None,
             },
         )]);
         acc.on_patch_begin(&add_changes);
@@ -868,6 +1044,7 @@ index {ZERO_OID}..{right_oid}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: None,
+                executable: None,
             },
         )]);
         acc.on_patch_begin(&update_changes);
@@ -893,4 +1070,97 @@ index {ZERO_OID}..{right_oid}
         };
         assert_eq!(combined, expected_combined);
     }
+
+    #[test]
+    fn diff_stats_counts_lines_per_file() {
+        let diff = "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n a\n--- not a header\n+b\n@@ -9 +9,2 @@\n+c\n+d\n\
+diff --git a/my file.txt b/my file.txt\ndeleted file mode 100644\n--- a/my file.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n";
+        assert_eq!(
+            diff_stats(diff),
+            vec![
+                FileDiffStat {
+                    path: PathBuf::from("a.txt"),
+                    added: 3,
+                    removed: 1,
+                },
+                FileDiffStat {
+                    path: PathBuf::from("my file.txt"),
+                    added: 0,
+                    removed: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn session_history_keeps_the_last_diff_of_each_turn() {
+        let diff = |path: &str, added: usize| {
+            let lines: String = (0..added).map(|i| format!("+{i}\n")).collect();
+            format!(
+                "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n@@ -0,0 +1 @@\n{lines}"
+            )
+        };
+        let mut history = SessionDiffHistory::default();
+        history.record(1, diff("a.txt", 1));
+        // Later diffs of a turn are cumulative and replace earlier ones.
+        history.record(1, diff("a.txt", 2));
+        history.record(3, diff("a.txt", 3));
+
+        let all = history.since(None);
+        assert_eq!(
+            all.turns.iter().map(|turn| turn.turn).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(
+            all.files,
+            vec![FileDiffStat {
+                path: PathBuf::from("a.txt"),
+                added: 5,
+                removed: 0,
+            }]
+        );
+        assert_eq!(history.since(Some(2)).turns.len(), 1);
+        assert_eq!(history.since(Some(2)).files[0].added, 3);
+    }
+
+    #[test]
+    fn session_history_replay_drops_undone_turns_and_keeps_the_latest_turns() {
+        let turn_diff = |turn: u64| {
+            RolloutItem::EventMsg(EventMsg::TurnDiff(TurnDiffEvent {
+                turn,
+                unified_diff: format!(
+                    "diff --git a/{turn}.txt b/{turn}.txt\n--- a/{turn}.txt\n+++ b/{turn}.txt\n@@ -0,0 +1 @@\n+{turn}\n"
+                ),
+                files: Vec::new(),
+            }))
+        };
+        let mut history = SessionDiffHistory::default();
+        history.replay(&[
+            turn_diff(1),
+            turn_diff(2),
+            turn_diff(3),
+            RolloutItem::EventMsg(EventMsg::UndoCompleted(UndoCompletedEvent {
+                success: true,
+                restored_turn: Some(2),
+                message: None,
+            })),
+            turn_diff(4),
+        ]);
+        assert_eq!(
+            history
+                .since(None)
+                .turns
+                .iter()
+                .map(|turn| turn.turn)
+                .collect::<Vec<_>>(),
+            vec![1, 4]
+        );
+
+        let many: Vec<RolloutItem> = (1..=MAX_TRACKED_TURNS as u64 + 5).map(turn_diff).collect();
+        let mut history = SessionDiffHistory::default();
+        history.replay(&many);
+        let turns = history.since(None).turns;
+        assert_eq!(turns.len(), MAX_TRACKED_TURNS);
+        assert_eq!(turns[0].turn, 6);
+    }
 }
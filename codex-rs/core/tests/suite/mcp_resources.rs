@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use codex_core::config_types::McpServerConfig;
+use codex_core::config_types::McpServerTransportConfig;
+use codex_core::features::Feature;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::responses;
+use core_test_support::responses::ResponsesRequest;
+use core_test_support::responses::mount_sse_once_match;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use escargot::CargoBuild;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::matchers::any;
+
+const SERVER_NAME: &str = "rmcp";
+
+/// Text of the output the model got back for the tool call `call_id`.
+fn call_output_text(request: &ResponsesRequest, call_id: &str) -> String {
+    let item = request.function_call_output(call_id);
+    match item.get("output") {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Object(output)) => output
+            .get("content")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        other => panic!("unexpected output for {call_id}: {other:?}"),
+    }
+}
+
+fn call_output_json(request: &ResponsesRequest, call_id: &str) -> Value {
+    let text = call_output_text(request, call_id);
+    serde_json::from_str(&text).unwrap_or_else(|err| panic!("{call_id}: {err}: {text}"))
+}
+
+fn resource_uris(page: &Value) -> Vec<&str> {
+    page["resources"]
+        .as_array()
+        .unwrap_or_else(|| panic!("page without resources: {page}"))
+        .iter()
+        .filter_map(|resource| resource["uri"].as_str())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn mcp_resource_tools_list_and_read_server_resources() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = responses::start_mock_server().await;
+
+    let calls = [
+        ("list-all", "list_mcp_resources", json!({})),
+        (
+            "list-next-page",
+            "list_mcp_resources",
+            json!({ "server": SERVER_NAME, "cursor": "page-2" }),
+        ),
+        (
+            "list-unknown-server",
+            "list_mcp_resources",
+            json!({ "server": "missing" }),
+        ),
+        (
+            "read-text",
+            "read_mcp_resource",
+            json!({ "server": SERVER_NAME, "uri": "memo://notes" }),
+        ),
+        (
+            "read-blob",
+            "read_mcp_resource",
+            json!({ "server": SERVER_NAME, "uri": "memo://logo" }),
+        ),
+        (
+            "read-unknown-server",
+            "read_mcp_resource",
+            json!({ "server": "missing", "uri": "memo://notes" }),
+        ),
+    ];
+    let mut first_response = vec![responses::ev_response_created("resp-1")];
+    first_response.extend(calls.iter().map(|(call_id, tool, arguments)| {
+        responses::ev_function_call(call_id, tool, &arguments.to_string())
+    }));
+    first_response.push(responses::ev_completed("resp-1"));
+    let first_mock = mount_sse_once_match(&server, any(), responses::sse(first_response)).await;
+    let second_mock = mount_sse_once_match(
+        &server,
+        any(),
+        responses::sse(vec![
+            responses::ev_assistant_message("msg-1", "done"),
+            responses::ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+
+    let rmcp_test_server_bin = CargoBuild::new()
+        .package("codex-rmcp-client")
+        .bin("test_stdio_server")
+        .run()?
+        .path()
+        .to_string_lossy()
+        .into_owned();
+
+    let fixture = test_codex()
+        .with_config(move |config| {
+            config.features.enable(Feature::RmcpClient);
+            config.mcp_servers.insert(
+                SERVER_NAME.to_string(),
+                McpServerConfig {
+                    transport: McpServerTransportConfig::Stdio {
+                        command: rmcp_test_server_bin,
+                        args: Vec::new(),
+                        env: Some(HashMap::new()),
+                    },
+                    enabled: true,
+                    startup_timeout_sec: Some(Duration::from_secs(10)),
+                    tool_timeout_sec: None,
+                },
+            );
+        })
+        .build(&server)
+        .await?;
+    let session_model = fixture.session_configured.model.clone();
+
+    fixture
+        .codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "look through the rmcp resources".into(),
+            }],
+            final_output_json_schema: None,
+            cwd: fixture.cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: session_model,
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+
+    wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    // The tools are offered because the server advertises resources.
+    let tools = first_mock.single_request().body_json()["tools"].clone();
+    let tool_names: Vec<&str> = tools
+        .as_array()
+        .expect("tools array")
+        .iter()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+    assert!(
+        tool_names.contains(&"list_mcp_resources") && tool_names.contains(&"read_mcp_resource"),
+        "unexpected tools: {tool_names:?}"
+    );
+
+    let request = second_mock.single_request();
+
+    // Without a server, the first page of every resource server is listed.
+    let listed = call_output_json(&request, "list-all");
+    let [page] = listed["servers"].as_array().expect("servers").as_slice() else {
+        panic!("expected a single server page: {listed}");
+    };
+    assert_eq!(page["server"], SERVER_NAME);
+    assert_eq!(resource_uris(page), vec!["memo://notes"]);
+    assert_eq!(page["nextCursor"], "page-2");
+
+    // The cursor fetches the next (and last) page.
+    let listed = call_output_json(&request, "list-next-page");
+    let page = &listed["servers"][0];
+    assert_eq!(resource_uris(page), vec!["memo://logo"]);
+    assert_eq!(page["nextCursor"], Value::Null);
+
+    // Unknown servers are reported in their page instead of failing the call.
+    let listed = call_output_json(&request, "list-unknown-server");
+    let page = &listed["servers"][0];
+    assert_eq!(page["server"], "missing");
+    let error = page["error"].as_str().expect("error for unknown server");
+    assert!(error.contains("missing"), "unexpected error: {error}");
+
+    let read = call_output_json(&request, "read-text");
+    assert_eq!(
+        read,
+        json!({
+            "server": SERVER_NAME,
+            "uri": "memo://notes",
+            "contents": [{
+                "uri": "memo://notes",
+                "mimeType": "text",
+                "text": "hello from the test server",
+            }],
+        })
+    );
+
+    // Binary contents are summarized with their decoded size.
+    let read = call_output_json(&request, "read-blob");
+    assert_eq!(
+        read["contents"],
+        json!([{
+            "uri": "memo://logo",
+            "mimeType": "application/octet-stream",
+            "blob": "<5 bytes of binary data omitted>",
+        }])
+    );
+
+    let error = call_output_text(&request, "read-unknown-server");
+    assert!(error.contains("missing"), "unexpected error: {error}");
+
+    server.verify().await;
+
+    Ok(())
+}
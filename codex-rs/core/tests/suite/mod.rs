@@ -13,8 +13,11 @@ mod grep_files;
 mod json_result;
 mod list_dir;
 mod live_cli;
+mod mcp_resources;
+mod model_fallback;
 mod model_overrides;
 mod model_tools;
+mod native_wire_apis;
 mod otel;
 mod prompt_caching;
 mod read_file;
@@ -25,9 +28,11 @@ mod seatbelt;
 mod shell_serialization;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
+mod token_budget;
 mod tool_harness;
 mod tool_parallelism;
 mod tools;
+mod undo;
 mod unified_exec;
 mod user_notification;
 mod view_image;
@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use codex_core::ModelProviderInfo;
+use codex_core::WireApi;
+use codex_core::config_types::ModelFallback;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event_with_timeout;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn sse_completed(id: &str) -> String {
+    load_sse_fixture_with_id("tests/fixtures/completed_template.json", id)
+}
+
+fn mock_provider(name: &str, server: &MockServer) -> ModelProviderInfo {
+    ModelProviderInfo {
+        name: name.into(),
+        base_url: Some(format!("{}/v1", server.uri())),
+        // Use an existing env var (PATH) to satisfy the auth plumbing without
+        // requiring a real secret.
+        env_key: Some("PATH".into()),
+        env_key_instructions: None,
+        wire_api: WireApi::Responses,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        stream_idle_timeout_ms: Some(2_000),
+        requires_openai_auth: false,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_continues_on_fallback_provider_after_retries_are_exhausted() {
+    skip_if_no_network!();
+
+    let primary = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&primary)
+        .await;
+
+    let fallback = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(sse_completed("resp_fallback"), "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&fallback)
+        .await;
+
+    let primary_provider = mock_provider("primary", &primary);
+    let fallback_provider = mock_provider("fallback", &fallback);
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(move |config| {
+            config.model_provider = primary_provider;
+            config.model_fallbacks = vec![ModelFallback {
+                model: "gpt-5".to_string(),
+                model_provider_id: "fallback".to_string(),
+                model_provider: fallback_provider,
+            }];
+        })
+        .build(&primary)
+        .await
+        .unwrap();
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::StreamError(stream_error) = wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::StreamError(_) | EventMsg::Error(_)),
+        Duration::from_secs(5),
+    )
+    .await
+    else {
+        panic!("expected a stream error announcing the switch");
+    };
+    assert!(
+        stream_error
+            .message
+            .ends_with("switching to model `gpt-5` on provider `fallback`"),
+        "unexpected message: {}",
+        stream_error.message
+    );
+
+    let event = wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::TaskComplete(_) | EventMsg::Error(_)),
+        Duration::from_secs(5),
+    )
+    .await;
+    assert!(
+        matches!(event, EventMsg::TaskComplete(_)),
+        "unexpected event: {event:?}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn task_stays_on_fallback_provider_for_follow_up_turns() {
+    skip_if_no_network!();
+
+    // The primary fails once; the follow-up turn after the tool call must not
+    // go back to it.
+    let primary = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&primary)
+        .await;
+
+    let fallback = MockServer::start().await;
+    let fallback_responses = mount_sse_sequence(
+        &fallback,
+        vec![
+            sse(vec![
+                ev_function_call("call-1", "no_such_tool", "{}"),
+                ev_completed("resp_1"),
+            ]),
+            sse_completed("resp_2"),
+        ],
+    )
+    .await;
+
+    let primary_provider = mock_provider("primary", &primary);
+    let fallback_provider = mock_provider("fallback", &fallback);
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(move |config| {
+            config.model_provider = primary_provider;
+            config.model_fallbacks = vec![ModelFallback {
+                model: "gpt-5".to_string(),
+                model_provider_id: "fallback".to_string(),
+                model_provider: fallback_provider,
+            }];
+        })
+        .build(&primary)
+        .await
+        .unwrap();
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let event = wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::TaskComplete(_) | EventMsg::Error(_)),
+        Duration::from_secs(10),
+    )
+    .await;
+    assert!(
+        matches!(event, EventMsg::TaskComplete(_)),
+        "unexpected event: {event:?}"
+    );
+    assert_eq!(fallback_responses.requests().len(), 2);
+}
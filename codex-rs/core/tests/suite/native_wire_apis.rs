@@ -0,0 +1,357 @@
+//! Streams through the Anthropic Messages and Gemini wire APIs against a mock
+//! server and checks how requests and streamed events are translated.
+
+use std::sync::Arc;
+
+use codex_app_server_protocol::AuthMode;
+use codex_core::ContentItem;
+use codex_core::ModelClient;
+use codex_core::ModelProviderInfo;
+use codex_core::Prompt;
+use codex_core::ResponseEvent;
+use codex_core::ResponseItem;
+use codex_core::WireApi;
+use codex_core::protocol::TokenUsage;
+use codex_otel::otel_event_manager::OtelEventManager;
+use codex_protocol::ConversationId;
+use core_test_support::load_default_config_for_test;
+use core_test_support::skip_if_no_network;
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::header;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+use wiremock::matchers::query_param;
+
+const MODEL: &str = "test-model";
+
+fn existing_env_var() -> &'static str {
+    if cfg!(windows) { "USERNAME" } else { "USER" }
+}
+
+fn sse(events: &[(&str, Value)]) -> String {
+    events
+        .iter()
+        .map(|(event, data)| format!("event: {event}\ndata: {data}\n\n"))
+        .collect()
+}
+
+/// Streams one prompt through `wire_api` and returns the received events and
+/// the JSON body of the request the mock server saw.
+async fn stream_prompt(server: &MockServer, wire_api: WireApi) -> (Vec<ResponseEvent>, Value) {
+    let provider = ModelProviderInfo {
+        name: "mock".into(),
+        base_url: Some(format!("{}/v1", server.uri())),
+        // Reuse an existing environment variable to avoid using unsafe code.
+        env_key: Some(existing_env_var().to_string()),
+        env_key_instructions: None,
+        wire_api,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        stream_idle_timeout_ms: Some(5_000),
+        requires_openai_auth: false,
+    };
+
+    let codex_home = match TempDir::new() {
+        Ok(dir) => dir,
+        Err(e) => panic!("failed to create TempDir: {e}"),
+    };
+    let mut config = load_default_config_for_test(&codex_home);
+    config.model = MODEL.to_string();
+    config.model_family.slug = MODEL.to_string();
+    config.model_provider_id = provider.name.clone();
+    config.model_provider = provider.clone();
+    let effort = config.model_reasoning_effort;
+    let summary = config.model_reasoning_summary;
+    let config = Arc::new(config);
+
+    let conversation_id = ConversationId::new();
+    let otel_event_manager = OtelEventManager::new(
+        conversation_id,
+        config.model.as_str(),
+        config.model_family.slug.as_str(),
+        None,
+        Some("test@test.com".to_string()),
+        Some(AuthMode::ApiKey),
+        false,
+        "test".to_string(),
+    );
+    let client = ModelClient::new(
+        Arc::clone(&config),
+        None,
+        otel_event_manager,
+        provider,
+        effort,
+        summary,
+        conversation_id,
+    );
+
+    let mut prompt = Prompt::default();
+    prompt.input = vec![ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: "list files".to_string(),
+        }],
+    }];
+    prompt.base_instructions_override = Some("be brief".to_string());
+
+    let mut stream = match client.stream(&prompt).await {
+        Ok(s) => s,
+        Err(e) => panic!("stream failed: {e}"),
+    };
+    let mut events = Vec::new();
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(ev) => events.push(ev),
+            Err(e) => panic!("stream event error: {e}"),
+        }
+    }
+
+    let Some(requests) = server.received_requests().await else {
+        panic!("request not made");
+    };
+    match requests[0].body_json() {
+        Ok(body) => (events, body),
+        Err(e) => panic!("invalid json body: {e}"),
+    }
+}
+
+fn assistant_message(text: &str) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "assistant".to_string(),
+        content: vec![ContentItem::OutputText {
+            text: text.to_string(),
+        }],
+    }
+}
+
+/// Compares `(input, cached input, output, reasoning output, total)` tokens.
+fn assert_token_usage(token_usage: &Option<TokenUsage>, expected: (u64, u64, u64, u64, u64)) {
+    let Some(usage) = token_usage else {
+        panic!("expected token usage");
+    };
+    assert_eq!(
+        (
+            usage.input_tokens,
+            usage.cached_input_tokens,
+            usage.output_tokens,
+            usage.reasoning_output_tokens,
+            usage.total_tokens,
+        ),
+        expected
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn anthropic_messages_stream_text_and_tool_use() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    let body = sse(&[
+        (
+            "message_start",
+            json!({
+                "type": "message_start",
+                "message": {
+                    "id": "msg_1",
+                    "usage": { "input_tokens": 10, "cache_read_input_tokens": 5, "output_tokens": 1 },
+                },
+            }),
+        ),
+        (
+            "content_block_start",
+            json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "text", "text": "" } }),
+        ),
+        (
+            "content_block_delta",
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "Listing" } }),
+        ),
+        (
+            "content_block_delta",
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": " files." } }),
+        ),
+        (
+            "content_block_stop",
+            json!({ "type": "content_block_stop", "index": 0 }),
+        ),
+        ("ping", json!({ "type": "ping" })),
+        (
+            "content_block_start",
+            json!({
+                "type": "content_block_start",
+                "index": 1,
+                "content_block": { "type": "tool_use", "id": "toolu_1", "name": "shell", "input": {} },
+            }),
+        ),
+        (
+            "content_block_delta",
+            json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "input_json_delta", "partial_json": "{\"command\":" } }),
+        ),
+        (
+            "content_block_delta",
+            json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "input_json_delta", "partial_json": "[\"ls\"]}" } }),
+        ),
+        (
+            "content_block_stop",
+            json!({ "type": "content_block_stop", "index": 1 }),
+        ),
+        (
+            "message_delta",
+            json!({ "type": "message_delta", "delta": { "stop_reason": "tool_use" }, "usage": { "output_tokens": 7 } }),
+        ),
+        ("message_stop", json!({ "type": "message_stop" })),
+    ]);
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .and(header(
+            "x-api-key",
+            std::env::var(existing_env_var()).unwrap_or_default(),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(body, "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (events, request) = stream_prompt(&server, WireApi::Anthropic).await;
+
+    assert_eq!(request["model"], MODEL);
+    assert_eq!(request["system"], "be brief");
+    assert_eq!(request["stream"], true);
+    assert_eq!(
+        request["messages"],
+        json!([{ "role": "user", "content": [{ "type": "text", "text": "list files" }] }])
+    );
+
+    assert_eq!(events.len(), 6, "unexpected events: {events:?}");
+    assert!(matches!(events[0], ResponseEvent::Created));
+    assert!(matches!(&events[1], ResponseEvent::OutputTextDelta(d) if d == "Listing"));
+    assert!(matches!(&events[2], ResponseEvent::OutputTextDelta(d) if d == " files."));
+    let ResponseEvent::OutputItemDone(message) = &events[3] else {
+        panic!("expected the assistant message, got {:?}", events[3]);
+    };
+    assert_eq!(message, &assistant_message("Listing files."));
+    let ResponseEvent::OutputItemDone(call) = &events[4] else {
+        panic!("expected the tool call, got {:?}", events[4]);
+    };
+    assert_eq!(
+        call,
+        &ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: r#"{"command":["ls"]}"#.to_string(),
+            call_id: "toolu_1".to_string(),
+        }
+    );
+    let ResponseEvent::Completed {
+        response_id,
+        token_usage,
+    } = &events[5]
+    else {
+        panic!("expected completion, got {:?}", events[5]);
+    };
+    assert_eq!(response_id, "msg_1");
+    assert_token_usage(token_usage, (15, 5, 7, 0, 22));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn gemini_stream_text_and_function_call() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+    let chunks = [
+        json!({
+            "responseId": "resp_1",
+            "candidates": [{ "content": { "role": "model", "parts": [{ "text": "Listing" }] } }],
+        }),
+        json!({
+            "responseId": "resp_1",
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        { "text": " files." },
+                        { "functionCall": { "name": "shell", "args": { "command": ["ls"] } } },
+                    ],
+                },
+                "finishReason": "STOP",
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 12,
+                "candidatesTokenCount": 4,
+                "thoughtsTokenCount": 2,
+                "totalTokenCount": 18,
+            },
+        }),
+    ];
+    let body: String = chunks.iter().map(|c| format!("data: {c}\n\n")).collect();
+
+    Mock::given(method("POST"))
+        .and(path(format!("/v1/models/{MODEL}:streamGenerateContent")))
+        .and(query_param("alt", "sse"))
+        .and(header(
+            "x-goog-api-key",
+            std::env::var(existing_env_var()).unwrap_or_default(),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(body, "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (events, request) = stream_prompt(&server, WireApi::Gemini).await;
+
+    assert_eq!(
+        request["systemInstruction"],
+        json!({ "parts": [{ "text": "be brief" }] })
+    );
+    assert_eq!(
+        request["contents"],
+        json!([{ "role": "user", "parts": [{ "text": "list files" }] }])
+    );
+
+    assert_eq!(events.len(), 6, "unexpected events: {events:?}");
+    assert!(matches!(events[0], ResponseEvent::Created));
+    assert!(matches!(&events[1], ResponseEvent::OutputTextDelta(d) if d == "Listing"));
+    assert!(matches!(&events[2], ResponseEvent::OutputTextDelta(d) if d == " files."));
+    let ResponseEvent::OutputItemDone(message) = &events[3] else {
+        panic!("expected the assistant message, got {:?}", events[3]);
+    };
+    assert_eq!(message, &assistant_message("Listing files."));
+    let ResponseEvent::OutputItemDone(ResponseItem::FunctionCall {
+        name, arguments, ..
+    }) = &events[4]
+    else {
+        panic!("expected the function call, got {:?}", events[4]);
+    };
+    assert_eq!(name, "shell");
+    assert_eq!(arguments, r#"{"command":["ls"]}"#);
+    let ResponseEvent::Completed {
+        response_id,
+        token_usage,
+    } = &events[5]
+    else {
+        panic!("expected completion, got {:?}", events[5]);
+    };
+    assert_eq!(response_id, "resp_1");
+    assert_token_usage(token_usage, (12, 0, 6, 2, 18));
+}
@@ -436,7 +436,9 @@ async fn overrides_turn_context_but_keeps_cached_prefix_and_key_constant() {
             approval_policy: Some(AskForApproval::Never),
             sandbox_policy: Some(SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![writable.path().to_path_buf()],
+                deny_read: Vec::new(),
                 network_access: true,
+                network_allowlist: Vec::new(),
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
             }),
@@ -569,7 +571,9 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() {
             approval_policy: AskForApproval::Never,
             sandbox_policy: SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![writable.path().to_path_buf()],
+                deny_read: Vec::new(),
                 network_access: true,
+                network_allowlist: Vec::new(),
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
             },
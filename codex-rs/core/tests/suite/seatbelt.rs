@@ -77,7 +77,9 @@ async fn if_parent_of_repo_is_writable_then_dot_git_folder_is_writable() {
     let test_scenario = create_test_scenario(&tmp);
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: vec![test_scenario.repo_parent.clone()],
+        deny_read: Vec::new(),
         network_access: false,
+        network_allowlist: Vec::new(),
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
     };
@@ -103,7 +105,9 @@ async fn if_git_repo_is_writable_root_then_dot_git_folder_is_read_only() {
     let test_scenario = create_test_scenario(&tmp);
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: vec![test_scenario.repo_root.clone()],
+        deny_read: Vec::new(),
         network_access: false,
+        network_allowlist: Vec::new(),
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
     };
@@ -145,7 +149,7 @@ async fn danger_full_access_allows_all_writes() {
 async fn read_only_forbids_all_writes() {
     let tmp = TempDir::new().expect("should be able to create temp dir");
     let test_scenario = create_test_scenario(&tmp);
-    let policy = SandboxPolicy::ReadOnly;
+    let policy = SandboxPolicy::new_read_only_policy();
 
     test_scenario
         .run_test(
@@ -176,7 +180,7 @@ async fn python_getpwuid_works_under_seatbelt() {
     }
 
     // ReadOnly is sufficient here since we are only exercising user lookup.
-    let policy = SandboxPolicy::ReadOnly;
+    let policy = SandboxPolicy::new_read_only_policy();
     let command_cwd = std::env::current_dir().expect("getcwd");
     let sandbox_cwd = command_cwd.clone();
 
@@ -0,0 +1,115 @@
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::TurnAbortReason;
+use core_test_support::responses;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed_with_tokens;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use wiremock::matchers::any;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn task_is_aborted_once_the_token_budget_is_exhausted() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    // The tool call would normally make the task sample the model again.
+    let first_response = sse(vec![
+        ev_response_created("resp-1"),
+        ev_function_call("call-1", "unknown_tool", "{}"),
+        ev_completed_with_tokens("resp-1", 1_000),
+    ]);
+    responses::mount_sse_once_match(&server, any(), first_response).await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|config| {
+            config.token_budget.max_total_tokens = Some(500);
+        })
+        .build(&server)
+        .await?;
+
+    for text in ["use the tool", "try again"] {
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text { text: text.into() }],
+            })
+            .await?;
+
+        let EventMsg::Error(error) = wait_for_event(&codex, |ev| {
+            matches!(ev, EventMsg::Error(_) | EventMsg::TaskComplete(_))
+        })
+        .await
+        else {
+            panic!("expected the budget error");
+        };
+        assert_eq!(
+            error.message,
+            "Token budget exhausted: used 1000 of 500 total tokens."
+        );
+
+        let EventMsg::TurnAborted(aborted) = wait_for_event(&codex, |ev| {
+            matches!(ev, EventMsg::TurnAborted(_) | EventMsg::TaskComplete(_))
+        })
+        .await
+        else {
+            panic!("expected the task to be aborted");
+        };
+        assert_eq!(aborted.reason, TurnAbortReason::BudgetExceeded);
+    }
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(
+        requests.len(),
+        1,
+        "the model was sampled after the budget ran out"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn task_that_finishes_over_budget_still_completes() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let response = sse(vec![
+        ev_response_created("resp-1"),
+        ev_assistant_message("msg-1", "all done"),
+        ev_completed_with_tokens("resp-1", 1_000),
+    ]);
+    responses::mount_sse_once_match(&server, any(), response).await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|config| {
+            config.token_budget.max_total_tokens = Some(500);
+        })
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "finish up".into(),
+            }],
+        })
+        .await?;
+
+    let EventMsg::TaskComplete(complete) = wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::TurnAborted(_) | EventMsg::TaskComplete(_))
+    })
+    .await
+    else {
+        panic!("expected the task to complete");
+    };
+    assert_eq!(complete.last_agent_message.as_deref(), Some("all done"));
+
+    Ok(())
+}
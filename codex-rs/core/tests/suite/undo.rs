@@ -0,0 +1,203 @@
+use std::fs;
+use std::process::Command;
+
+use codex_core::features::Feature;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::GhostSnapshotKind;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::UndoCompletedEvent;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::responses::ev_apply_patch_function_call;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+
+fn turn_response(id: &str) -> String {
+    sse(vec![
+        ev_response_created(id),
+        ev_assistant_message(&format!("msg-{id}"), "done"),
+        ev_completed(id),
+    ])
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn undo_restores_workspace_snapshots_taken_before_each_turn() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![turn_response("resp-1"), turn_response("resp-2")],
+    )
+    .await;
+
+    let TestCodex { codex, cwd, .. } = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::GhostCommit);
+        })
+        .build(&server)
+        .await?;
+    let status = Command::new("git")
+        .arg("init")
+        .arg("-q")
+        .current_dir(cwd.path())
+        .status()?;
+    assert!(status.success(), "git init failed");
+    let file = cwd.path().join("notes.txt");
+
+    for (turn, contents) in [(1, "v1"), (2, "v2")] {
+        fs::write(&file, contents)?;
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text {
+                    text: format!("turn {turn}"),
+                }],
+            })
+            .await?;
+        let EventMsg::GhostSnapshot(snapshot) = wait_for_event(&codex, |ev| {
+            matches!(ev, EventMsg::GhostSnapshot(_) | EventMsg::TaskComplete(_))
+        })
+        .await
+        else {
+            panic!("expected a snapshot before turn {turn}");
+        };
+        assert_eq!(snapshot.turn, turn);
+        assert_eq!(snapshot.kind, GhostSnapshotKind::Git);
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    }
+    fs::write(&file, "v3")?;
+
+    codex.submit(Op::Undo { turns: 1 }).await?;
+    let EventMsg::UndoCompleted(undo) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::UndoCompleted(_))).await
+    else {
+        unreachable!();
+    };
+    assert!(undo.success, "undo failed: {:?}", undo.message);
+    assert_eq!(undo.restored_turn, Some(2));
+    assert_eq!(fs::read_to_string(&file)?, "v2");
+
+    codex.submit(Op::Undo { turns: 5 }).await?;
+    let EventMsg::UndoCompleted(undo) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::UndoCompleted(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(
+        undo,
+        UndoCompletedEvent {
+            success: false,
+            restored_turn: None,
+            message: Some("Cannot undo 5 turns: only 1 snapshot is available.".to_string()),
+        }
+    );
+
+    codex.submit(Op::RestoreToTurn { turn: 1 }).await?;
+    let EventMsg::UndoCompleted(undo) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::UndoCompleted(_))).await
+    else {
+        unreachable!();
+    };
+    assert!(undo.success, "restore failed: {:?}", undo.message);
+    assert_eq!(undo.restored_turn, Some(1));
+    assert_eq!(fs::read_to_string(&file)?, "v1");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn undo_outside_git_only_reverts_files_the_agent_patched() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let patch = "*** Begin Patch
+*** Update File: notes.txt
+@@
+-before
++after
+*** Add File: created.txt
++new
+*** End Patch";
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_apply_patch_function_call("call-1", patch),
+                ev_completed("resp-1"),
+            ]),
+            turn_response("resp-2"),
+        ],
+    )
+    .await;
+
+    let TestCodex {
+        codex,
+        cwd,
+        session_configured,
+        ..
+    } = test_codex()
+        .with_config(|config| {
+            config.features.enable(Feature::GhostCommit);
+            config.features.enable(Feature::ApplyPatchFreeform);
+        })
+        .build(&server)
+        .await?;
+    let notes = cwd.path().join("notes.txt");
+    fs::write(&notes, "before\n")?;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "edit the notes".to_string(),
+            }],
+            final_output_json_schema: None,
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+    let EventMsg::GhostSnapshot(snapshot) = wait_for_event(&codex, |ev| {
+        matches!(ev, EventMsg::GhostSnapshot(_) | EventMsg::TaskComplete(_))
+    })
+    .await
+    else {
+        panic!("expected a snapshot before the turn");
+    };
+    assert_eq!(snapshot.kind, GhostSnapshotKind::Files);
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let created = cwd.path().join("created.txt");
+    assert_eq!(fs::read_to_string(&notes)?, "after\n");
+    assert_eq!(fs::read_to_string(&created)?, "new\n");
+    // Files the user creates after the turn are not the agent's to remove.
+    let user_file = cwd.path().join("user.txt");
+    fs::write(&user_file, "mine")?;
+
+    codex.submit(Op::Undo { turns: 1 }).await?;
+    let EventMsg::UndoCompleted(undo) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::UndoCompleted(_))).await
+    else {
+        unreachable!();
+    };
+    assert!(undo.success, "undo failed: {:?}", undo.message);
+    assert_eq!(fs::read_to_string(&notes)?, "before\n");
+    assert!(!created.exists());
+    assert_eq!(fs::read_to_string(&user_file)?, "mine");
+
+    Ok(())
+}
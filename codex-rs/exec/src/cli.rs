@@ -52,6 +52,11 @@ pub struct Cli {
     #[arg(long = "skip-git-repo-check", default_value_t = false)]
     pub skip_git_repo_check: bool,
 
+    /// Abort the run once the session has used this many tokens (input +
+    /// output). Overrides `token_budget.max_total_tokens` from config.toml.
+    #[arg(long = "max-tokens-budget", value_name = "TOKENS")]
+    pub max_tokens_budget: Option<u64>,
+
     /// Path to a JSON Schema file describing the model's final response shape.
     #[arg(long = "output-schema", value_name = "FILE")]
     pub output_schema: Option<PathBuf>,
@@ -67,6 +72,15 @@ pub struct Cli {
     #[arg(long = "json", alias = "experimental-json", default_value_t = false)]
     pub json: bool,
 
+    /// With --json, also emit `item.updated` events carrying agent message,
+    /// reasoning and command output deltas as they stream.
+    #[arg(long = "stream-deltas", default_value_t = false, requires = "json")]
+    pub stream_deltas: bool,
+
+    /// Print the JSON Schema of the `--json` events and exit.
+    #[arg(long = "print-event-schema", default_value_t = false)]
+    pub print_event_schema: bool,
+
     /// Whether to include the plan tool in the conversation.
     #[arg(long = "include-plan-tool", default_value_t = false)]
     pub include_plan_tool: bool,
@@ -75,16 +89,37 @@ pub struct Cli {
     #[arg(long = "output-last-message", short = 'o', value_name = "FILE")]
     pub last_message_file: Option<PathBuf>,
 
+    /// Keep the agent's `apply_patch` edits in memory instead of writing them
+    /// to disk, and write them to DIFF_FILE as a single unified diff when the
+    /// run ends. Shell commands run in a read-only sandbox and, like the
+    /// `grep_files` and `list_dir` tools, see the files as they are on disk;
+    /// only `read_file` sees the pending edits.
+    #[arg(long = "dry-run", value_name = "DIFF_FILE")]
+    pub dry_run: Option<PathBuf>,
+
     /// Initial instructions for the agent. If not provided as an argument (or
     /// if `-` is used), instructions are read from stdin.
     #[arg(value_name = "PROMPT", value_hint = clap::ValueHint::Other)]
     pub prompt: Option<String>,
 }
 
+impl Cli {
+    /// A `codex-exec review` invocation with default options, used by the
+    /// top-level `codex review` command.
+    pub fn review(args: ReviewArgs) -> Self {
+        let mut cli = Self::parse_from(["codex-exec"]);
+        cli.command = Some(Command::Review(args));
+        cli
+    }
+}
+
 #[derive(Debug, clap::Subcommand)]
 pub enum Command {
     /// Resume a previous session by id or pick the most recent with --last.
     Resume(ResumeArgs),
+
+    /// Review code changes and print the findings as JSON or SARIF.
+    Review(ReviewArgs),
 }
 
 #[derive(Parser, Debug)]
@@ -103,6 +138,63 @@ pub struct ResumeArgs {
     pub prompt: Option<String>,
 }
 
+#[derive(Parser, Debug, Clone)]
+pub struct ReviewArgs {
+    /// Review only the changes staged in the index. Without a target flag the
+    /// staged, unstaged and untracked changes are reviewed.
+    #[arg(long = "staged", default_value_t = false, conflicts_with_all = ["base", "commit", "range"])]
+    pub staged: bool,
+
+    /// Review the changes since the current branch diverged from BRANCH.
+    #[arg(long = "base", value_name = "BRANCH", conflicts_with_all = ["commit", "range"])]
+    pub base: Option<String>,
+
+    /// Review the changes introduced by a single commit.
+    #[arg(long = "commit", value_name = "SHA", conflicts_with = "range")]
+    pub commit: Option<String>,
+
+    /// Review the changes between two commits, e.g. `origin/main..HEAD`.
+    #[arg(long = "range", value_name = "FROM..TO")]
+    pub range: Option<String>,
+
+    /// Only review changes to these paths.
+    #[arg(long = "path", value_name = "PATH")]
+    pub paths: Vec<String>,
+
+    /// Model used for the review. Defaults to `review_model` from config.toml.
+    #[arg(long = "model", short = 'm')]
+    pub model: Option<String>,
+
+    /// Format of the report written to stdout.
+    #[arg(long = "format", value_enum, default_value_t = ReviewFormat::Json)]
+    pub format: ReviewFormat,
+
+    /// Write the report to FILE instead of stdout.
+    #[arg(long = "output", short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Exit with status 2 when a finding has this priority or a more urgent
+    /// one (0 is the most urgent, 3 the least).
+    #[arg(long = "fail-on-priority", value_name = "PRIORITY")]
+    pub fail_on_priority: Option<i32>,
+
+    /// Additional review instructions. If `-` is used, read them from stdin.
+    #[arg(value_name = "INSTRUCTIONS", value_hint = clap::ValueHint::Other)]
+    pub prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum ReviewFormat {
+    /// The `ReviewOutputEvent` produced by the reviewer.
+    #[default]
+    Json,
+    /// SARIF 2.1.0, for code-scanning dashboards.
+    Sarif,
+    /// GitHub Actions `::warning file=...` annotations.
+    Github,
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
 #[value(rename_all = "kebab-case")]
 pub enum Color {
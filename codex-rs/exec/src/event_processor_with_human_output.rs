@@ -10,6 +10,7 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::FuzzyPatchHunk;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
@@ -20,6 +21,7 @@ use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::UndoCompletedEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_protocol::num_format::format_with_separators;
@@ -221,6 +223,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 aggregated_output,
                 duration,
                 exit_code,
+                sandbox_violations,
                 ..
             }) => {
                 let duration = format!(" in {}", format_duration(duration));
@@ -241,6 +244,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     }
                 }
                 eprintln!("{}", truncated_output.style(self.dimmed));
+                for violation in sandbox_violations {
+                    eprintln!("{}", format!("sandbox denied {violation}").style(self.red));
+                }
             }
             EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
                 call_id: _,
@@ -312,11 +318,15 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 // it's easy to scan in the terminal output.
                 for (path, change) in changes.iter() {
                     match change {
-                        FileChange::Add { content } => {
+                        FileChange::Add {
+                            content,
+                            executable,
+                        } => {
                             let header = format!(
-                                "{} {}",
+                                "{} {}{}",
                                 format_file_change(change),
-                                path.to_string_lossy()
+                                path.to_string_lossy(),
+                                format_mode_change(*executable)
                             );
                             eprintln!("{}", header.style(self.magenta));
                             for line in content.lines() {
@@ -337,16 +347,23 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                         FileChange::Update {
                             unified_diff,
                             move_path,
+                            executable,
                         } => {
                             let header = if let Some(dest) = move_path {
                                 format!(
-                                    "{} {} -> {}",
+                                    "{} {} -> {}{}",
                                     format_file_change(change),
                                     path.to_string_lossy(),
-                                    dest.to_string_lossy()
+                                    dest.to_string_lossy(),
+                                    format_mode_change(*executable)
                                 )
                             } else {
-                                format!("{} {}", format_file_change(change), path.to_string_lossy())
+                                format!(
+                                    "{} {}{}",
+                                    format_file_change(change),
+                                    path.to_string_lossy(),
+                                    format_mode_change(*executable)
+                                )
                             };
                             eprintln!("{}", header.style(self.magenta));
 
@@ -365,6 +382,25 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                                 }
                             }
                         }
+                        FileChange::Copy { source } => {
+                            let header = format!(
+                                "{} {} -> {}",
+                                format_file_change(change),
+                                source.to_string_lossy(),
+                                path.to_string_lossy()
+                            );
+                            eprintln!("{}", header.style(self.magenta));
+                        }
+                        FileChange::Binary { size, executable } => {
+                            let header = format!(
+                                "{} {}{}",
+                                format_file_change(change),
+                                path.to_string_lossy(),
+                                format_mode_change(*executable)
+                            );
+                            eprintln!("{}", header.style(self.magenta));
+                            eprintln!("{}", format!("binary, {size} bytes").style(self.dimmed));
+                        }
                     }
                 }
             }
@@ -373,7 +409,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 stdout,
                 stderr,
                 success,
-                ..
+                fuzzy_hunks,
             }) => {
                 let patch_begin = self.call_id_to_patch.remove(&call_id);
 
@@ -402,8 +438,15 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 for line in output.lines() {
                     eprintln!("{}", line.style(self.dimmed));
                 }
+                for FuzzyPatchHunk { path, hunk } in fuzzy_hunks {
+                    let note = format!(
+                        "hunk {hunk} of {} applied with a three-way merge",
+                        path.display()
+                    );
+                    eprintln!("{}", note.style(self.magenta));
+                }
             }
-            EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => {
+            EventMsg::TurnDiff(TurnDiffEvent { unified_diff, .. }) => {
                 ts_msg!(
                     self,
                     "{}",
@@ -417,6 +460,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::ApplyPatchApprovalRequest(_) => {
                 // Should we exit?
             }
+            EventMsg::McpSamplingApprovalRequest(_) => {
+                // Should we exit?
+            }
             EventMsg::AgentReasoning(agent_reasoning_event) => {
                 if self.show_agent_reasoning {
                     ts_msg!(
@@ -508,12 +554,29 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 TurnAbortReason::ReviewEnded => {
                     ts_msg!(self, "task aborted: review ended");
                 }
+                TurnAbortReason::BudgetExceeded => {
+                    ts_msg!(self, "task aborted: token budget exhausted");
+                    return CodexStatus::InitiateShutdown;
+                }
             },
             EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
             EventMsg::ConversationPath(_) => {}
             EventMsg::UserMessage(_) => {}
             EventMsg::EnteredReviewMode(_) => {}
             EventMsg::ExitedReviewMode(_) => {}
+            EventMsg::GhostSnapshot(_) => {}
+            EventMsg::SessionDiff(_) => {}
+            EventMsg::UndoCompleted(UndoCompletedEvent {
+                success, message, ..
+            }) => {
+                let message = message.unwrap_or_else(|| "undo finished".to_string());
+                if success {
+                    ts_msg!(self, "{}", message.style(self.dimmed));
+                } else {
+                    let prefix = "ERROR:".style(self.red);
+                    ts_msg!(self, "{prefix} {message}");
+                }
+            }
             EventMsg::AgentMessageDelta(_) => {}
             EventMsg::AgentReasoningDelta(_) => {}
             EventMsg::AgentReasoningRawContentDelta(_) => {}
@@ -559,6 +622,16 @@ fn format_file_change(change: &FileChange) -> &'static str {
         FileChange::Update {
             move_path: None, ..
         } => "M",
+        FileChange::Copy { .. } => "C",
+        FileChange::Binary { .. } => "M",
+    }
+}
+
+fn format_mode_change(executable: Option<bool>) -> &'static str {
+    match executable {
+        Some(true) => " (+x)",
+        Some(false) => " (-x)",
+        None => "",
     }
 }
 
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicU64;
 
@@ -8,6 +9,7 @@ use crate::event_processor::handle_last_message;
 use crate::exec_events::AgentMessageItem;
 use crate::exec_events::CommandExecutionItem;
 use crate::exec_events::CommandExecutionStatus;
+use crate::exec_events::EVENT_SCHEMA_VERSION;
 use crate::exec_events::FileChangeItem;
 use crate::exec_events::FileUpdateChange;
 use crate::exec_events::ItemCompletedEvent;
@@ -31,12 +33,16 @@ use crate::exec_events::TurnStartedEvent;
 use crate::exec_events::Usage;
 use crate::exec_events::WebSearchItem;
 use codex_core::config::Config;
+use codex_core::protocol::AgentMessageDeltaEvent;
 use codex_core::protocol::AgentMessageEvent;
+use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecCommandOutputDeltaEvent;
+use codex_core::protocol::ExecOutputStream;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
@@ -45,6 +51,8 @@ use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TaskStartedEvent;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::protocol::TurnAbortedEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_protocol::plan_tool::StepStatus;
 use codex_protocol::plan_tool::UpdatePlanArgs;
@@ -62,12 +70,66 @@ pub struct EventProcessorWithJsonOutput {
     last_total_token_usage: Option<codex_core::protocol::TokenUsage>,
     running_mcp_tool_calls: HashMap<String, RunningMcpToolCall>,
     last_critical_error: Option<ThreadErrorEvent>,
+    /// Emit `item.updated` events for streamed text, see [`Self::with_deltas`].
+    stream_deltas: bool,
+    streaming_agent_message: Option<StreamingText>,
+    streaming_reasoning: Option<StreamingText>,
+    /// Reasoning sections that finished streaming but whose final
+    /// `AgentReasoning` event has not arrived yet.
+    streamed_reasoning: VecDeque<StreamingText>,
 }
 
 #[derive(Debug, Clone)]
 struct RunningCommand {
     command: String,
     item_id: String,
+    stdout: Utf8ChunkDecoder,
+    stderr: Utf8ChunkDecoder,
+}
+
+/// Decodes a byte stream chunk by chunk, holding back a multibyte character
+/// split across chunks until its remaining bytes arrive.
+#[derive(Debug, Clone, Default)]
+struct Utf8ChunkDecoder {
+    incomplete: Vec<u8>,
+}
+
+impl Utf8ChunkDecoder {
+    fn decode(&mut self, chunk: &[u8]) -> String {
+        self.incomplete.extend_from_slice(chunk);
+        let mut decoded = String::new();
+        let mut rest = std::mem::take(&mut self.incomplete);
+        loop {
+            match std::str::from_utf8(&rest) {
+                Ok(valid) => {
+                    decoded.push_str(valid);
+                    return decoded;
+                }
+                Err(err) => {
+                    let valid_up_to = err.valid_up_to();
+                    decoded.push_str(&String::from_utf8_lossy(&rest[..valid_up_to]));
+                    match err.error_len() {
+                        // Invalid bytes are replaced like `from_utf8_lossy` does.
+                        Some(len) => {
+                            decoded.push(char::REPLACEMENT_CHARACTER);
+                            rest.drain(..valid_up_to + len);
+                        }
+                        // The chunk ends in the middle of a character.
+                        None => {
+                            self.incomplete = rest.split_off(valid_up_to);
+                            return decoded;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An agent message or reasoning item whose text is still streaming.
+#[derive(Debug, Clone)]
+struct StreamingText {
+    item_id: String,
 }
 
 #[derive(Debug, Clone)]
@@ -94,15 +156,40 @@ impl EventProcessorWithJsonOutput {
             last_total_token_usage: None,
             running_mcp_tool_calls: HashMap::new(),
             last_critical_error: None,
+            stream_deltas: false,
+            streaming_agent_message: None,
+            streaming_reasoning: None,
+            streamed_reasoning: VecDeque::new(),
         }
     }
 
+    /// Also report agent message, reasoning and command output deltas as
+    /// `item.updated` events while they stream.
+    pub fn with_deltas(mut self, stream_deltas: bool) -> Self {
+        self.stream_deltas = stream_deltas;
+        self
+    }
+
     pub fn collect_thread_events(&mut self, event: &Event) -> Vec<ThreadEvent> {
         match &event.msg {
             EventMsg::SessionConfigured(ev) => self.handle_session_configured(ev),
             EventMsg::AgentMessage(ev) => self.handle_agent_message(ev),
+            EventMsg::AgentMessageDelta(ev) if self.stream_deltas => {
+                self.handle_agent_message_delta(ev)
+            }
             EventMsg::AgentReasoning(ev) => self.handle_reasoning_event(ev),
+            EventMsg::AgentReasoningDelta(ev) if self.stream_deltas => {
+                self.handle_reasoning_delta(ev)
+            }
+            EventMsg::AgentReasoningSectionBreak(_) => {
+                self.streamed_reasoning
+                    .extend(self.streaming_reasoning.take());
+                Vec::new()
+            }
             EventMsg::ExecCommandBegin(ev) => self.handle_exec_command_begin(ev),
+            EventMsg::ExecCommandOutputDelta(ev) if self.stream_deltas => {
+                self.handle_exec_command_output_delta(ev)
+            }
             EventMsg::ExecCommandEnd(ev) => self.handle_exec_command_end(ev),
             EventMsg::McpToolCallBegin(ev) => self.handle_mcp_tool_call_begin(ev),
             EventMsg::McpToolCallEnd(ev) => self.handle_mcp_tool_call_end(ev),
@@ -144,6 +231,7 @@ impl EventProcessorWithJsonOutput {
     fn handle_session_configured(&self, payload: &SessionConfiguredEvent) -> Vec<ThreadEvent> {
         vec![ThreadEvent::ThreadStarted(ThreadStartedEvent {
             thread_id: payload.session_id.to_string(),
+            schema_version: EVENT_SCHEMA_VERSION,
         })]
     }
 
@@ -158,9 +246,13 @@ impl EventProcessorWithJsonOutput {
         vec![ThreadEvent::ItemCompleted(ItemCompletedEvent { item })]
     }
 
-    fn handle_agent_message(&self, payload: &AgentMessageEvent) -> Vec<ThreadEvent> {
+    fn handle_agent_message(&mut self, payload: &AgentMessageEvent) -> Vec<ThreadEvent> {
+        let item_id = match self.streaming_agent_message.take() {
+            Some(streaming) => streaming.item_id,
+            None => self.get_next_item_id(),
+        };
         let item = ThreadItem {
-            id: self.get_next_item_id(),
+            id: item_id,
 
             details: ThreadItemDetails::AgentMessage(AgentMessageItem {
                 text: payload.message.clone(),
@@ -170,9 +262,49 @@ impl EventProcessorWithJsonOutput {
         vec![ThreadEvent::ItemCompleted(ItemCompletedEvent { item })]
     }
 
-    fn handle_reasoning_event(&self, ev: &AgentReasoningEvent) -> Vec<ThreadEvent> {
+    fn handle_agent_message_delta(&mut self, ev: &AgentMessageDeltaEvent) -> Vec<ThreadEvent> {
+        let mut events = Vec::new();
+        let streaming = match &mut self.streaming_agent_message {
+            Some(streaming) => streaming,
+            None => {
+                let item_id = self.get_next_item_id();
+                events.push(ThreadEvent::ItemStarted(ItemStartedEvent {
+                    item: ThreadItem {
+                        id: item_id.clone(),
+                        details: ThreadItemDetails::AgentMessage(AgentMessageItem {
+                            text: String::new(),
+                        }),
+                    },
+                }));
+                self.streaming_agent_message
+                    .insert(StreamingText { item_id })
+            }
+        };
+        // Only the delta is sent; the full text arrives with `item.completed`.
+        events.push(ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item: ThreadItem {
+                id: streaming.item_id.clone(),
+                details: ThreadItemDetails::AgentMessage(AgentMessageItem {
+                    text: String::new(),
+                }),
+            },
+            delta: Some(ev.delta.clone()),
+        }));
+        events
+    }
+
+    fn handle_reasoning_event(&mut self, ev: &AgentReasoningEvent) -> Vec<ThreadEvent> {
+        // Each summary section ends with its own `AgentReasoning`, in order.
+        let item_id = match self
+            .streamed_reasoning
+            .pop_front()
+            .or_else(|| self.streaming_reasoning.take())
+        {
+            Some(streamed) => streamed.item_id,
+            None => self.get_next_item_id(),
+        };
         let item = ThreadItem {
-            id: self.get_next_item_id(),
+            id: item_id,
 
             details: ThreadItemDetails::Reasoning(ReasoningItem {
                 text: ev.text.clone(),
@@ -181,6 +313,35 @@ impl EventProcessorWithJsonOutput {
 
         vec![ThreadEvent::ItemCompleted(ItemCompletedEvent { item })]
     }
+
+    fn handle_reasoning_delta(&mut self, ev: &AgentReasoningDeltaEvent) -> Vec<ThreadEvent> {
+        let mut events = Vec::new();
+        let streaming = match &mut self.streaming_reasoning {
+            Some(streaming) => streaming,
+            None => {
+                let item_id = self.get_next_item_id();
+                events.push(ThreadEvent::ItemStarted(ItemStartedEvent {
+                    item: ThreadItem {
+                        id: item_id.clone(),
+                        details: ThreadItemDetails::Reasoning(ReasoningItem {
+                            text: String::new(),
+                        }),
+                    },
+                }));
+                self.streaming_reasoning.insert(StreamingText { item_id })
+            }
+        };
+        events.push(ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item: ThreadItem {
+                id: streaming.item_id.clone(),
+                details: ThreadItemDetails::Reasoning(ReasoningItem {
+                    text: String::new(),
+                }),
+            },
+            delta: Some(ev.delta.clone()),
+        }));
+        events
+    }
     fn handle_exec_command_begin(&mut self, ev: &ExecCommandBeginEvent) -> Vec<ThreadEvent> {
         let item_id = self.get_next_item_id();
 
@@ -200,6 +361,8 @@ impl EventProcessorWithJsonOutput {
             RunningCommand {
                 command: command_string.clone(),
                 item_id: item_id.clone(),
+                stdout: Utf8ChunkDecoder::default(),
+                stderr: Utf8ChunkDecoder::default(),
             },
         );
 
@@ -216,6 +379,36 @@ impl EventProcessorWithJsonOutput {
         vec![ThreadEvent::ItemStarted(ItemStartedEvent { item })]
     }
 
+    fn handle_exec_command_output_delta(
+        &mut self,
+        ev: &ExecCommandOutputDeltaEvent,
+    ) -> Vec<ThreadEvent> {
+        let Some(running) = self.running_commands.get_mut(&ev.call_id) else {
+            return Vec::new();
+        };
+        let delta = match ev.stream {
+            ExecOutputStream::Stdout => running.stdout.decode(&ev.chunk),
+            ExecOutputStream::Stderr => running.stderr.decode(&ev.chunk),
+        };
+        if delta.is_empty() {
+            return Vec::new();
+        }
+        // Only the delta is sent; the full output arrives with `item.completed`.
+        let item = ThreadItem {
+            id: running.item_id.clone(),
+            details: ThreadItemDetails::CommandExecution(CommandExecutionItem {
+                command: running.command.clone(),
+                aggregated_output: String::new(),
+                exit_code: None,
+                status: CommandExecutionStatus::InProgress,
+            }),
+        };
+        vec![ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item,
+            delta: Some(delta),
+        })]
+    }
+
     fn handle_mcp_tool_call_begin(&mut self, ev: &McpToolCallBeginEvent) -> Vec<ThreadEvent> {
         let item_id = self.get_next_item_id();
         let server = ev.invocation.server.clone();
@@ -285,9 +478,9 @@ impl EventProcessorWithJsonOutput {
 
     fn map_change_kind(&self, kind: &FileChange) -> PatchChangeKind {
         match kind {
-            FileChange::Add { .. } => PatchChangeKind::Add,
+            FileChange::Add { .. } | FileChange::Copy { .. } => PatchChangeKind::Add,
             FileChange::Delete { .. } => PatchChangeKind::Delete,
-            FileChange::Update { .. } => PatchChangeKind::Update,
+            FileChange::Update { .. } | FileChange::Binary { .. } => PatchChangeKind::Update,
         }
     }
 
@@ -321,7 +514,9 @@ impl EventProcessorWithJsonOutput {
     }
 
     fn handle_exec_command_end(&mut self, ev: &ExecCommandEndEvent) -> Vec<ThreadEvent> {
-        let Some(RunningCommand { command, item_id }) = self.running_commands.remove(&ev.call_id)
+        let Some(RunningCommand {
+            command, item_id, ..
+        }) = self.running_commands.remove(&ev.call_id)
         else {
             warn!(
                 call_id = ev.call_id,
@@ -367,7 +562,10 @@ impl EventProcessorWithJsonOutput {
                 id: running.item_id.clone(),
                 details: ThreadItemDetails::TodoList(TodoListItem { items }),
             };
-            return vec![ThreadEvent::ItemUpdated(ItemUpdatedEvent { item })];
+            return vec![ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+                item,
+                delta: None,
+            })];
         }
 
         let item_id = self.get_next_item_id();
@@ -388,6 +586,10 @@ impl EventProcessorWithJsonOutput {
     }
 
     fn handle_task_complete(&mut self) -> Vec<ThreadEvent> {
+        self.streaming_agent_message = None;
+        self.streaming_reasoning = None;
+        self.streamed_reasoning.clear();
+
         let usage = if let Some(u) = &self.last_total_token_usage {
             Usage {
                 input_tokens: u.input_tokens,
@@ -444,13 +646,17 @@ impl EventProcessor for EventProcessorWithJsonOutput {
 
         let Event { msg, .. } = event;
 
-        if let EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) = msg {
-            if let Some(output_file) = self.last_message_path.as_deref() {
-                handle_last_message(last_agent_message.as_deref(), output_file);
+        match msg {
+            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                if let Some(output_file) = self.last_message_path.as_deref() {
+                    handle_last_message(last_agent_message.as_deref(), output_file);
+                }
+                CodexStatus::InitiateShutdown
             }
-            CodexStatus::InitiateShutdown
-        } else {
-            CodexStatus::Running
+            EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::BudgetExceeded,
+            }) => CodexStatus::InitiateShutdown,
+            _ => CodexStatus::Running,
         }
     }
 }
@@ -9,8 +9,11 @@ mod event_processor;
 mod event_processor_with_human_output;
 pub mod event_processor_with_jsonl_output;
 pub mod exec_events;
+mod review;
 
+use anyhow::Context;
 pub use cli::Cli;
+pub use cli::ReviewArgs;
 use codex_core::AuthManager;
 use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use codex_core::ConversationManager;
@@ -34,6 +37,8 @@ use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
 use serde_json::Value;
 use std::io::IsTerminal;
 use std::io::Read;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use supports_color::Stream;
 use tracing::debug;
@@ -65,21 +70,41 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         skip_git_repo_check,
         color,
         last_message_file,
+        dry_run,
         json: json_mode,
+        stream_deltas,
+        print_event_schema,
         sandbox_mode: sandbox_mode_cli_arg,
         prompt,
         output_schema: output_schema_path,
+        max_tokens_budget,
         include_plan_tool,
         config_overrides,
     } = cli;
 
+    if print_event_schema {
+        let schema = serde_json::to_string_pretty(&exec_events::thread_event_schema())?;
+        writeln!(std::io::stdout().lock(), "{schema}")?;
+        return Ok(());
+    }
+
     // Determine the prompt source (parent or subcommand) and read from stdin if needed.
     let prompt_arg = match &command {
         // Allow prompt before the subcommand by falling back to the parent-level prompt
         // when the Resume subcommand did not provide its own prompt.
         Some(ExecCommand::Resume(args)) => args.prompt.clone().or(prompt),
+        // Review instructions are optional, so only read stdin for `-`.
+        Some(ExecCommand::Review(args)) => Some(args.prompt.clone().unwrap_or_default()),
         None => prompt,
     };
+    let review_args = match &command {
+        Some(ExecCommand::Review(args)) => Some(args.clone()),
+        _ => None,
+    };
+    if review_args.is_some() && json_mode {
+        eprintln!("`review` writes its report to stdout and cannot be combined with --json.");
+        std::process::exit(1);
+    }
 
     let prompt = match prompt_arg {
         Some(p) if p != "-" => p,
@@ -147,6 +172,26 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         sandbox_mode_cli_arg.map(Into::<SandboxMode>::into)
     };
 
+    // A dry run must leave the checkout untouched, so shell commands only get
+    // read access.
+    let sandbox_mode = match (&dry_run, sandbox_mode) {
+        (None, sandbox_mode) => sandbox_mode,
+        (Some(_), None | Some(SandboxMode::ReadOnly)) => Some(SandboxMode::ReadOnly),
+        (Some(_), Some(_)) => {
+            let flag = if full_auto {
+                "--full-auto"
+            } else if dangerously_bypass_approvals_and_sandbox {
+                "--dangerously-bypass-approvals-and-sandbox"
+            } else {
+                "--sandbox"
+            };
+            eprintln!(
+                "--dry-run runs commands in a read-only sandbox and cannot be combined with {flag}."
+            );
+            std::process::exit(1);
+        }
+    };
+
     // When using `--oss`, let the bootstrapper pick the model (defaulting to
     // gpt-oss:20b) and ensure it is present locally. Also, force the built‑in
     // `oss` model provider.
@@ -167,7 +212,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     // Load configuration and determine approval policy
     let overrides = ConfigOverrides {
         model,
-        review_model: None,
+        review_model: review_args.as_ref().and_then(|args| args.model.clone()),
         config_profile,
         // Default to never ask for approvals in headless mode. Feature flags can override.
         approval_policy: Some(AskForApproval::Never),
@@ -191,7 +236,11 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     };
 
-    let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+    let mut config = Config::load_with_cli_overrides(cli_kv_overrides, overrides).await?;
+    if let Some(max_tokens_budget) = max_tokens_budget {
+        config.token_budget.max_total_tokens = Some(max_tokens_budget);
+    }
+    config.dry_run = dry_run.is_some();
     let approve_all_enabled = config.features.enabled(Feature::ApproveAll);
 
     let otel = codex_core::otel_init::build_provider(&config, env!("CARGO_PKG_VERSION"));
@@ -219,7 +268,9 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     }
 
     let mut event_processor: Box<dyn EventProcessor> = match json_mode {
-        true => Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone())),
+        true => Box::new(
+            EventProcessorWithJsonOutput::new(last_message_file.clone()).with_deltas(stream_deltas),
+        ),
         _ => Box::new(EventProcessorWithHumanOutput::create_with_ansi(
             stdout_with_ansi,
             &config,
@@ -337,29 +388,47 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     }
 
-    // Send the prompt.
-    let items: Vec<InputItem> = vec![InputItem::Text { text: prompt }];
-    let initial_prompt_task_id = conversation
-        .submit(Op::UserTurn {
-            items,
-            cwd: default_cwd,
-            approval_policy: default_approval_policy,
-            sandbox_policy: default_sandbox_policy,
-            model: default_model,
-            effort: default_effort,
-            summary: default_summary,
-            final_output_json_schema: output_schema,
-        })
-        .await?;
+    // Send the prompt, or start the review.
+    let initial_prompt_task_id = if let Some(args) = &review_args {
+        let review_request = review::review_request(args, prompt)?;
+        conversation.submit(Op::Review { review_request }).await?
+    } else {
+        let items: Vec<InputItem> = vec![InputItem::Text { text: prompt }];
+        conversation
+            .submit(Op::UserTurn {
+                items,
+                cwd: default_cwd.clone(),
+                approval_policy: default_approval_policy,
+                sandbox_policy: default_sandbox_policy,
+                model: default_model,
+                effort: default_effort,
+                summary: default_summary,
+                final_output_json_schema: output_schema,
+            })
+            .await?
+    };
     info!("Sent prompt with event ID: {initial_prompt_task_id}");
 
     // Run the loop until the task is complete.
     // Track whether a fatal error was reported by the server so we can
     // exit with a non-zero status for automation-friendly signaling.
     let mut error_seen = false;
+    let mut review_output = None;
+    // In dry-run mode every turn diff carries all pending edits.
+    let mut dry_run_diff = String::new();
     while let Some(event) = rx.recv().await {
         if matches!(event.msg, EventMsg::Error(_)) {
             error_seen = true;
+            // A review that could not be started never completes a task.
+            if review_args.is_some() {
+                conversation.submit(Op::Shutdown).await?;
+            }
+        }
+        if let EventMsg::ExitedReviewMode(ev) = &event.msg {
+            review_output = ev.review_output.clone();
+        }
+        if let EventMsg::TurnDiff(ev) = &event.msg {
+            dry_run_diff.clone_from(&ev.unified_diff);
         }
         // Auto-approve requests when the approve_all feature is enabled.
         if approve_all_enabled {
@@ -386,6 +455,17 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                         error!("failed to auto-approve patch: {e}");
                     }
                 }
+                EventMsg::McpSamplingApprovalRequest(ev) => {
+                    if let Err(e) = conversation
+                        .submit(Op::McpSamplingApproval {
+                            id: ev.call_id.clone(),
+                            decision: codex_core::protocol::ReviewDecision::Approved,
+                        })
+                        .await
+                    {
+                        error!("failed to auto-approve MCP sampling: {e}");
+                    }
+                }
                 _ => {}
             }
         }
@@ -400,6 +480,25 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             }
         }
     }
+    if let Some(args) = review_args {
+        if error_seen {
+            std::process::exit(1);
+        }
+        let Some(review_output) = review_output else {
+            eprintln!("The review finished without producing a result.");
+            std::process::exit(1);
+        };
+        let exit_code = review::write_review_report(&args, &review_output, &default_cwd)?;
+        if exit_code != 0 {
+            std::process::exit(exit_code);
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = &dry_run {
+        write_dry_run_diff(path, &dry_run_diff)?;
+    }
+
     event_processor.print_final_output();
     if error_seen {
         std::process::exit(1);
@@ -408,6 +507,10 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     Ok(())
 }
 
+fn write_dry_run_diff(path: &Path, diff: &str) -> anyhow::Result<()> {
+    std::fs::write(path, diff).with_context(|| format!("failed to write {}", path.display()))
+}
+
 async fn resolve_resume_path(
     config: &Config,
     args: &crate::cli::ResumeArgs,
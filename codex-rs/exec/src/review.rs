@@ -0,0 +1,83 @@
+//! Headless code review: `codex exec review` / `codex review`.
+
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Context;
+use codex_core::git_info::get_git_repo_root;
+use codex_core::protocol::ReviewOutputEvent;
+use codex_core::protocol::ReviewRequest;
+use codex_core::protocol::ReviewTarget;
+use codex_core::review_format::format_review_findings_github_annotations;
+use codex_core::review_format::format_review_output_sarif;
+
+use crate::cli::ReviewArgs;
+use crate::cli::ReviewFormat;
+
+/// Exit status used when `--fail-on-priority` matched a finding.
+pub(crate) const FINDINGS_EXIT_CODE: i32 = 2;
+
+pub(crate) fn review_request(args: &ReviewArgs, prompt: String) -> anyhow::Result<ReviewRequest> {
+    let target = if args.staged {
+        ReviewTarget::Staged
+    } else if let Some(branch) = &args.base {
+        ReviewTarget::BaseBranch {
+            branch: branch.clone(),
+        }
+    } else if let Some(sha) = &args.commit {
+        ReviewTarget::Commit { sha: sha.clone() }
+    } else if let Some(range) = &args.range {
+        let (from, to) = range
+            .split_once("..")
+            .filter(|(from, to)| !from.is_empty() && !to.is_empty() && !to.starts_with('.'))
+            .with_context(|| format!("--range expects FROM..TO, got `{range}`"))?;
+        ReviewTarget::CommitRange {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    } else {
+        ReviewTarget::WorkingTree
+    };
+
+    Ok(ReviewRequest {
+        prompt,
+        user_facing_hint: String::new(),
+        target: Some(target),
+        paths: args.paths.clone(),
+    })
+}
+
+/// Write the report and return the exit status the run should end with.
+pub(crate) fn write_review_report(
+    args: &ReviewArgs,
+    output: &ReviewOutputEvent,
+    cwd: &Path,
+) -> anyhow::Result<i32> {
+    let base_dir = || get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    let report = match args.format {
+        ReviewFormat::Json => serde_json::to_string_pretty(output)?,
+        ReviewFormat::Sarif => {
+            serde_json::to_string_pretty(&format_review_output_sarif(output, &base_dir()))?
+        }
+        ReviewFormat::Github => {
+            format_review_findings_github_annotations(&output.findings, &base_dir())
+        }
+    };
+
+    match &args.output {
+        Some(path) => std::fs::write(path, format!("{report}\n"))
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            writeln!(stdout, "{report}")?;
+        }
+    }
+
+    let failed = args.fail_on_priority.is_some_and(|threshold| {
+        output
+            .findings
+            .iter()
+            .any(|finding| finding.priority <= threshold)
+    });
+    Ok(if failed { FINDINGS_EXIT_CODE } else { 0 })
+}
@@ -1,10 +1,15 @@
+use codex_core::protocol::AgentMessageDeltaEvent;
 use codex_core::protocol::AgentMessageEvent;
+use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningEvent;
+use codex_core::protocol::AgentReasoningSectionBreakEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecCommandOutputDeltaEvent;
+use codex_core::protocol::ExecOutputStream;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::McpToolCallBeginEvent;
@@ -17,6 +22,7 @@ use codex_exec::event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
 use codex_exec::exec_events::AgentMessageItem;
 use codex_exec::exec_events::CommandExecutionItem;
 use codex_exec::exec_events::CommandExecutionStatus;
+use codex_exec::exec_events::EVENT_SCHEMA_VERSION;
 use codex_exec::exec_events::ItemCompletedEvent;
 use codex_exec::exec_events::ItemStartedEvent;
 use codex_exec::exec_events::ItemUpdatedEvent;
@@ -76,6 +82,7 @@ fn session_configured_produces_thread_started_event() {
         out,
         vec![ThreadEvent::ThreadStarted(ThreadStartedEvent {
             thread_id: "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(),
+            schema_version: EVENT_SCHEMA_VERSION,
         })]
     );
 }
@@ -195,6 +202,7 @@ fn plan_update_emits_todo_list_started_updated_and_completed() {
                     ],
                 }),
             },
+            delta: None,
         })]
     );
 
@@ -535,6 +543,7 @@ fn exec_command_end_success_produces_completed_command_item() {
             exit_code: 0,
             duration: Duration::from_millis(5),
             formatted_output: String::new(),
+            sandbox_violations: Vec::new(),
         }),
     );
     let out_ok = ep.collect_thread_events(&end_ok);
@@ -594,6 +603,7 @@ fn exec_command_end_failure_produces_failed_command_item() {
             exit_code: 1,
             duration: Duration::from_millis(2),
             formatted_output: String::new(),
+            sandbox_violations: Vec::new(),
         }),
     );
     let out_fail = ep.collect_thread_events(&end_fail);
@@ -628,6 +638,7 @@ fn exec_command_end_without_begin_is_ignored() {
             exit_code: 0,
             duration: Duration::from_millis(1),
             formatted_output: String::new(),
+            sandbox_violations: Vec::new(),
         }),
     );
     let out = ep.collect_thread_events(&end_only);
@@ -644,6 +655,7 @@ fn patch_apply_success_produces_item_completed_patchapply() {
         PathBuf::from("a/added.txt"),
         FileChange::Add {
             content: "+hello".to_string(),
+            executable: None,
         },
     );
     changes.insert(
@@ -657,6 +669,7 @@ fn patch_apply_success_produces_item_completed_patchapply() {
         FileChange::Update {
             unified_diff: "--- c/modified.txt\n+++ c/modified.txt\n@@\n-old\n+new\n".to_string(),
             move_path: Some(PathBuf::from("c/renamed.txt")),
+            executable: None,
         },
     );
 
@@ -680,6 +693,7 @@ fn patch_apply_success_produces_item_completed_patchapply() {
             stdout: "applied 3 changes".to_string(),
             stderr: String::new(),
             success: true,
+            fuzzy_hunks: Vec::new(),
         }),
     );
     let out_end = ep.collect_thread_events(&end);
@@ -726,6 +740,7 @@ fn patch_apply_failure_produces_item_completed_patchapply_failed() {
         FileChange::Update {
             unified_diff: "--- file.txt\n+++ file.txt\n@@\n-old\n+new\n".to_string(),
             move_path: None,
+            executable: None,
         },
     );
 
@@ -748,6 +763,7 @@ fn patch_apply_failure_produces_item_completed_patchapply_failed() {
             stdout: String::new(),
             stderr: "failed to apply".to_string(),
             success: false,
+            fuzzy_hunks: Vec::new(),
         }),
     );
     let out_end = ep.collect_thread_events(&end);
@@ -815,3 +831,230 @@ fn task_complete_produces_turn_completed_with_usage() {
         })]
     );
 }
+
+fn agent_message(id: &str, text: &str) -> ThreadItem {
+    ThreadItem {
+        id: id.to_string(),
+        details: ThreadItemDetails::AgentMessage(AgentMessageItem {
+            text: text.to_string(),
+        }),
+    }
+}
+
+fn reasoning(id: &str, text: &str) -> ThreadItem {
+    ThreadItem {
+        id: id.to_string(),
+        details: ThreadItemDetails::Reasoning(ReasoningItem {
+            text: text.to_string(),
+        }),
+    }
+}
+
+#[test]
+fn deltas_are_ignored_without_the_flag() {
+    let mut ep = EventProcessorWithJsonOutput::new(None);
+    let delta = event(
+        "d1",
+        EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+            delta: "Hel".to_string(),
+        }),
+    );
+    assert!(ep.collect_thread_events(&delta).is_empty());
+}
+
+#[test]
+fn agent_message_deltas_stream_into_one_item() {
+    let mut ep = EventProcessorWithJsonOutput::new(None).with_deltas(true);
+    let delta = |text: &str| {
+        event(
+            "d",
+            EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+                delta: text.to_string(),
+            }),
+        )
+    };
+
+    assert_eq!(
+        ep.collect_thread_events(&delta("Hel")),
+        vec![
+            ThreadEvent::ItemStarted(ItemStartedEvent {
+                item: agent_message("item_0", ""),
+            }),
+            ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+                item: agent_message("item_0", ""),
+                delta: Some("Hel".to_string()),
+            }),
+        ]
+    );
+    assert_eq!(
+        ep.collect_thread_events(&delta("lo")),
+        vec![ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item: agent_message("item_0", ""),
+            delta: Some("lo".to_string()),
+        })]
+    );
+
+    let done = event(
+        "m",
+        EventMsg::AgentMessage(AgentMessageEvent {
+            message: "Hello".to_string(),
+        }),
+    );
+    assert_eq!(
+        ep.collect_thread_events(&done),
+        vec![ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: agent_message("item_0", "Hello"),
+        })]
+    );
+
+    // The next message starts a new item.
+    assert_eq!(
+        ep.collect_thread_events(&delta("Bye")),
+        vec![
+            ThreadEvent::ItemStarted(ItemStartedEvent {
+                item: agent_message("item_1", ""),
+            }),
+            ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+                item: agent_message("item_1", ""),
+                delta: Some("Bye".to_string()),
+            }),
+        ]
+    );
+}
+
+#[test]
+fn reasoning_sections_complete_the_items_they_streamed_into() {
+    let mut ep = EventProcessorWithJsonOutput::new(None).with_deltas(true);
+    let delta = |text: &str| {
+        event(
+            "r",
+            EventMsg::AgentReasoningDelta(AgentReasoningDeltaEvent {
+                delta: text.to_string(),
+            }),
+        )
+    };
+    let section_break = event(
+        "r",
+        EventMsg::AgentReasoningSectionBreak(AgentReasoningSectionBreakEvent {}),
+    );
+    let done = |text: &str| {
+        event(
+            "r",
+            EventMsg::AgentReasoning(AgentReasoningEvent {
+                text: text.to_string(),
+            }),
+        )
+    };
+
+    assert_eq!(ep.collect_thread_events(&section_break), Vec::new());
+    assert_eq!(ep.collect_thread_events(&delta("first")).len(), 2);
+    assert_eq!(ep.collect_thread_events(&section_break), Vec::new());
+    assert_eq!(
+        ep.collect_thread_events(&delta("second")),
+        vec![
+            ThreadEvent::ItemStarted(ItemStartedEvent {
+                item: reasoning("item_1", ""),
+            }),
+            ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+                item: reasoning("item_1", ""),
+                delta: Some("second".to_string()),
+            }),
+        ]
+    );
+
+    assert_eq!(
+        ep.collect_thread_events(&done("first")),
+        vec![ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: reasoning("item_0", "first"),
+        })]
+    );
+    assert_eq!(
+        ep.collect_thread_events(&done("second")),
+        vec![ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: reasoning("item_1", "second"),
+        })]
+    );
+}
+
+#[test]
+fn command_output_deltas_update_the_running_command() {
+    let mut ep = EventProcessorWithJsonOutput::new(None).with_deltas(true);
+    let begin = event(
+        "c1",
+        EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+            call_id: "1".to_string(),
+            command: vec!["ls".to_string()],
+            cwd: std::env::current_dir().unwrap(),
+            parsed_cmd: Vec::new(),
+        }),
+    );
+    assert_eq!(ep.collect_thread_events(&begin).len(), 1);
+
+    let output = |stream: ExecOutputStream, chunk: &[u8]| {
+        event(
+            "c2",
+            EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+                call_id: "1".to_string(),
+                stream,
+                chunk: chunk.to_vec(),
+            }),
+        )
+    };
+    let running = || ThreadItem {
+        id: "item_0".to_string(),
+        details: ThreadItemDetails::CommandExecution(CommandExecutionItem {
+            command: "ls".to_string(),
+            aggregated_output: String::new(),
+            exit_code: None,
+            status: CommandExecutionStatus::InProgress,
+        }),
+    };
+
+    assert_eq!(
+        ep.collect_thread_events(&output(ExecOutputStream::Stdout, b"a.txt\n")),
+        vec![ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item: running(),
+            delta: Some("a.txt\n".to_string()),
+        })]
+    );
+    assert_eq!(
+        ep.collect_thread_events(&output(ExecOutputStream::Stdout, b"b.txt\n")),
+        vec![ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item: running(),
+            delta: Some("b.txt\n".to_string()),
+        })]
+    );
+
+    // A character split across chunks is held back until it is complete,
+    // even when the other stream writes in between.
+    let e_acute = "é".as_bytes();
+    assert!(
+        ep.collect_thread_events(&output(ExecOutputStream::Stdout, &e_acute[..1]))
+            .is_empty()
+    );
+    assert_eq!(
+        ep.collect_thread_events(&output(ExecOutputStream::Stderr, b"err\xff")),
+        vec![ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item: running(),
+            delta: Some("err\u{fffd}".to_string()),
+        })]
+    );
+    assert_eq!(
+        ep.collect_thread_events(&output(ExecOutputStream::Stdout, &e_acute[1..])),
+        vec![ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item: running(),
+            delta: Some("é".to_string()),
+        })]
+    );
+
+    // Output of commands that never started is dropped.
+    let stray = event(
+        "c3",
+        EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+            call_id: "unknown".to_string(),
+            stream: ExecOutputStream::Stderr,
+            chunk: b"oops".to_vec(),
+        }),
+    );
+    assert!(ep.collect_thread_events(&stray).is_empty());
+}
@@ -95,6 +95,81 @@ async fn test_apply_patch_tool() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[cfg(not(target_os = "windows"))]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_apply_patch_dry_run_writes_a_diff_instead_of_files() -> anyhow::Result<()> {
+    use core_test_support::skip_if_no_network;
+    use core_test_support::test_codex_exec::test_codex_exec;
+
+    skip_if_no_network!(Ok(()));
+
+    let test = test_codex_exec();
+    let tmp_path = test.cwd_path().to_path_buf();
+    let diff_path = tmp_path.join("proposed.diff");
+    let add_patch = r#"*** Begin Patch
+*** Add File: test.md
++Hello world
+*** End Patch"#;
+    // Only applies if the pending addition is visible.
+    let update_patch = r#"*** Begin Patch
+*** Update File: test.md
+@@
+-Hello world
++Final text
+*** End Patch"#;
+    let response_streams = vec![
+        sse(vec![
+            ev_apply_patch_custom_tool_call("request_0", add_patch),
+            ev_completed("request_0"),
+        ]),
+        sse(vec![
+            ev_apply_patch_function_call("request_1", update_patch),
+            ev_completed("request_1"),
+        ]),
+        sse(vec![ev_completed("request_2")]),
+    ];
+    let server = start_mock_server().await;
+    mount_sse_sequence(&server, response_streams).await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--dry-run")
+        .arg(&diff_path)
+        .arg("foo")
+        .assert()
+        .success();
+
+    assert!(!tmp_path.join("test.md").exists());
+    assert_eq!(
+        fs::read_to_string(&diff_path)?,
+        "diff --git a/test.md b/test.md\nnew file mode 100644\n--- /dev/null\n+++ b/test.md\n@@ -0,0 +1 @@\n+Final text\n"
+    );
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn test_apply_patch_dry_run_rejects_a_writable_sandbox() -> anyhow::Result<()> {
+    use core_test_support::test_codex_exec::test_codex_exec;
+
+    let test = test_codex_exec();
+    let diff_path = test.cwd_path().join("proposed.diff");
+
+    test.cmd()
+        .arg("--skip-git-repo-check")
+        .arg("-s")
+        .arg("danger-full-access")
+        .arg("--dry-run")
+        .arg(&diff_path)
+        .arg("foo")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "--dry-run runs commands in a read-only sandbox and cannot be combined with --sandbox.",
+        ));
+    Ok(())
+}
+
 #[cfg(not(target_os = "windows"))]
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn test_apply_patch_freeform_tool() -> anyhow::Result<()> {
@@ -0,0 +1,231 @@
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_exec::exec_events::AgentMessageItem;
+use codex_exec::exec_events::CommandExecutionItem;
+use codex_exec::exec_events::CommandExecutionStatus;
+use codex_exec::exec_events::EVENT_SCHEMA_VERSION;
+use codex_exec::exec_events::ErrorItem;
+use codex_exec::exec_events::FileChangeItem;
+use codex_exec::exec_events::FileUpdateChange;
+use codex_exec::exec_events::ItemCompletedEvent;
+use codex_exec::exec_events::ItemStartedEvent;
+use codex_exec::exec_events::ItemUpdatedEvent;
+use codex_exec::exec_events::McpToolCallItem;
+use codex_exec::exec_events::McpToolCallStatus;
+use codex_exec::exec_events::PatchApplyStatus;
+use codex_exec::exec_events::PatchChangeKind;
+use codex_exec::exec_events::ReasoningItem;
+use codex_exec::exec_events::ThreadErrorEvent;
+use codex_exec::exec_events::ThreadEvent;
+use codex_exec::exec_events::ThreadItem;
+use codex_exec::exec_events::ThreadItemDetails;
+use codex_exec::exec_events::ThreadStartedEvent;
+use codex_exec::exec_events::TodoItem;
+use codex_exec::exec_events::TodoListItem;
+use codex_exec::exec_events::TurnCompletedEvent;
+use codex_exec::exec_events::TurnFailedEvent;
+use codex_exec::exec_events::TurnStartedEvent;
+use codex_exec::exec_events::Usage;
+use codex_exec::exec_events::WebSearchItem;
+use core_test_support::test_codex_exec::test_codex_exec;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn read_pinned(name: &str) -> String {
+    let path = fixture(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing {}: the JSONL event shape is pinned per EVENT_SCHEMA_VERSION",
+            path.display()
+        )
+    })
+}
+
+fn item(id: &str, details: ThreadItemDetails) -> ThreadItem {
+    ThreadItem {
+        id: id.to_string(),
+        details,
+    }
+}
+
+/// One event of every kind, and every item kind, as written by `--json`.
+fn sample_events() -> Vec<ThreadEvent> {
+    vec![
+        ThreadEvent::ThreadStarted(ThreadStartedEvent {
+            thread_id: "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(),
+            schema_version: EVENT_SCHEMA_VERSION,
+        }),
+        ThreadEvent::TurnStarted(TurnStartedEvent {}),
+        ThreadEvent::ItemStarted(ItemStartedEvent {
+            item: item(
+                "item_0",
+                ThreadItemDetails::CommandExecution(CommandExecutionItem {
+                    command: "ls".to_string(),
+                    aggregated_output: String::new(),
+                    exit_code: None,
+                    status: CommandExecutionStatus::InProgress,
+                }),
+            ),
+        }),
+        ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item: item(
+                "item_0",
+                ThreadItemDetails::CommandExecution(CommandExecutionItem {
+                    command: "ls".to_string(),
+                    aggregated_output: String::new(),
+                    exit_code: None,
+                    status: CommandExecutionStatus::InProgress,
+                }),
+            ),
+            delta: Some("README.md\n".to_string()),
+        }),
+        ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: item(
+                "item_0",
+                ThreadItemDetails::CommandExecution(CommandExecutionItem {
+                    command: "ls".to_string(),
+                    aggregated_output: "README.md\n".to_string(),
+                    exit_code: Some(0),
+                    status: CommandExecutionStatus::Completed,
+                }),
+            ),
+        }),
+        ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: item(
+                "item_1",
+                ThreadItemDetails::Reasoning(ReasoningItem {
+                    text: "thinking".to_string(),
+                }),
+            ),
+        }),
+        ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: item(
+                "item_2",
+                ThreadItemDetails::FileChange(FileChangeItem {
+                    changes: vec![FileUpdateChange {
+                        path: "src/lib.rs".to_string(),
+                        kind: PatchChangeKind::Update,
+                    }],
+                    status: PatchApplyStatus::Completed,
+                }),
+            ),
+        }),
+        ThreadEvent::ItemStarted(ItemStartedEvent {
+            item: item(
+                "item_3",
+                ThreadItemDetails::McpToolCall(McpToolCallItem {
+                    server: "docs".to_string(),
+                    tool: "search".to_string(),
+                    status: McpToolCallStatus::InProgress,
+                }),
+            ),
+        }),
+        ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: item(
+                "item_4",
+                ThreadItemDetails::WebSearch(WebSearchItem {
+                    query: "rust".to_string(),
+                }),
+            ),
+        }),
+        ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item: item(
+                "item_5",
+                ThreadItemDetails::TodoList(TodoListItem {
+                    items: vec![TodoItem {
+                        text: "write tests".to_string(),
+                        completed: true,
+                    }],
+                }),
+            ),
+            delta: None,
+        }),
+        ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: item(
+                "item_6",
+                ThreadItemDetails::Error(ErrorItem {
+                    message: "retrying".to_string(),
+                }),
+            ),
+        }),
+        ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: item(
+                "item_7",
+                ThreadItemDetails::AgentMessage(AgentMessageItem {
+                    text: "done".to_string(),
+                }),
+            ),
+        }),
+        ThreadEvent::TurnCompleted(TurnCompletedEvent {
+            usage: Usage {
+                input_tokens: 10,
+                cached_input_tokens: 2,
+                output_tokens: 5,
+            },
+        }),
+        ThreadEvent::TurnFailed(TurnFailedEvent {
+            error: ThreadErrorEvent {
+                message: "stream disconnected".to_string(),
+            },
+        }),
+        ThreadEvent::Error(ThreadErrorEvent {
+            message: "fatal".to_string(),
+        }),
+    ]
+}
+
+/// The schema printed by `--print-event-schema` must match the one pinned
+/// for the current version. Changing the shape of an event requires bumping
+/// `EVENT_SCHEMA_VERSION` and pinning the new schema.
+#[test]
+fn print_event_schema_matches_pinned_schema() {
+    let output = test_codex_exec()
+        .cmd()
+        .arg("--print-event-schema")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+
+    let printed: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let pinned: Value = serde_json::from_str(&read_pinned(&format!(
+        "thread_event_schema_v{EVENT_SCHEMA_VERSION}.json"
+    )))
+    .unwrap();
+    assert_eq!(
+        printed, pinned,
+        "the JSONL event schema changed; bump EVENT_SCHEMA_VERSION and pin the new schema"
+    );
+}
+
+/// Every event serializes to the pinned wire format and the pinned lines
+/// still deserialize.
+#[test]
+fn events_match_pinned_wire_format() {
+    let pinned = read_pinned(&format!("thread_events_v{EVENT_SCHEMA_VERSION}.jsonl"));
+    let events = sample_events();
+
+    let written: Vec<String> = events
+        .iter()
+        .map(|event| serde_json::to_string(event).unwrap())
+        .collect();
+    assert_eq!(
+        written,
+        pinned.lines().collect::<Vec<_>>(),
+        "the JSONL wire format changed; bump EVENT_SCHEMA_VERSION and pin the new events"
+    );
+
+    let parsed: Vec<ThreadEvent> = pinned
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(parsed, events);
+}
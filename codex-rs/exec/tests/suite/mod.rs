@@ -2,8 +2,10 @@
 mod apply_patch;
 mod approve_all;
 mod auth_env;
+mod event_schema;
 mod originator;
 mod output_schema;
 mod resume;
+mod review;
 mod sandbox;
 mod server_error_exit;
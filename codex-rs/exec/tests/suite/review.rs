@@ -0,0 +1,101 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::path::Path;
+use std::process::Command;
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+use serde_json::Value;
+use wiremock::matchers::any;
+
+fn git(cwd: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=Tester",
+            "-c",
+            "user.email=test@example.com",
+        ])
+        .args(args)
+        .current_dir(cwd)
+        .status()
+        .expect("run git");
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn review_prints_sarif_and_fails_on_priority() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    let cwd = test.cwd_path();
+    git(cwd, &["init", "-q"]);
+    std::fs::write(cwd.join("notes.txt"), "before\n")?;
+    git(cwd, &["add", "notes.txt"]);
+    git(cwd, &["commit", "-q", "-m", "init"]);
+    std::fs::write(cwd.join("notes.txt"), "after\n")?;
+
+    let review = serde_json::json!({
+        "findings": [{
+            "title": "[P1] Notes were rewritten",
+            "body": "The previous contents are lost.",
+            "confidence_score": 0.7,
+            "priority": 1,
+            "code_location": {
+                "absolute_file_path": cwd.join("notes.txt"),
+                "line_range": {"start": 1, "end": 1}
+            }
+        }],
+        "overall_correctness": "patch is incorrect",
+        "overall_explanation": "One regression.",
+        "overall_confidence_score": 0.7
+    });
+    let server = responses::start_mock_server().await;
+    let body = responses::sse(vec![
+        responses::ev_response_created("resp1"),
+        responses::ev_assistant_message("m1", &review.to_string()),
+        responses::ev_completed("resp1"),
+    ]);
+    let response_mock = responses::mount_sse_once_match(&server, any(), body).await;
+
+    let output = test
+        .cmd_with_server(&server)
+        .arg("review")
+        .arg("--format")
+        .arg("sarif")
+        .arg("--fail-on-priority")
+        .arg("1")
+        .output()?;
+    assert_eq!(output.status.code(), Some(2), "{output:?}");
+
+    let sarif: Value = serde_json::from_slice(&output.stdout)?;
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "notes.txt"
+    );
+    assert_eq!(
+        result["message"]["text"],
+        "[P1] Notes were rewritten\n\nThe previous contents are lost."
+    );
+
+    let request = response_mock.single_request().body_json().to_string();
+    assert!(request.contains("as produced by `git diff HEAD`"));
+    assert!(request.contains("-before\\n+after"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn review_without_changes_fails_before_calling_the_model() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    git(test.cwd_path(), &["init", "-q"]);
+
+    test.cmd()
+        .arg("review")
+        .arg("--staged")
+        .assert()
+        .code(1)
+        .stdout("");
+
+    Ok(())
+}
@@ -25,6 +25,7 @@ async fn spawn_command_under_sandbox(
         command_cwd,
         sandbox_policy,
         sandbox_cwd,
+        &ResourceLimits::default(),
         stdio_policy,
         env,
     )
@@ -40,7 +41,9 @@ async fn spawn_command_under_sandbox(
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
+    use codex_core::config_types::LinuxSandboxBackend;
     use codex_core::landlock::spawn_command_under_linux_sandbox;
+    use codex_core::resource_limits::ResourceLimits;
     let codex_linux_sandbox_exe = assert_cmd::cargo::cargo_bin("codex-exec");
     spawn_command_under_linux_sandbox(
         codex_linux_sandbox_exe,
@@ -48,6 +51,10 @@ async fn spawn_command_under_sandbox(
         command_cwd,
         sandbox_policy,
         sandbox_cwd,
+        &ResourceLimits::default(),
+        LinuxSandboxBackend::Landlock,
+        None,
+        false,
         stdio_policy,
         env,
     )
@@ -69,7 +76,9 @@ async fn python_multiprocessing_lock_works_under_sandbox() {
 
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots,
+        deny_read: Vec::new(),
         network_access: false,
+        network_allowlist: Vec::new(),
         exclude_tmpdir_env_var: false,
         exclude_slash_tmp: false,
     };
@@ -129,7 +138,9 @@ async fn sandbox_distinguishes_command_and_policy_cwds() {
     // is under a writable root.
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: vec![],
+        deny_read: Vec::new(),
         network_access: false,
+        network_allowlist: Vec::new(),
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
     };
@@ -269,7 +280,7 @@ fn unix_sock_body() {
 async fn allow_unix_socketpair_recvfrom() {
     run_code_under_sandbox(
         "allow_unix_socketpair_recvfrom",
-        &SandboxPolicy::ReadOnly,
+        &SandboxPolicy::new_read_only_policy(),
         || async { unix_sock_body() },
     )
     .await
@@ -43,3 +43,10 @@ pub fn get_default_policy() -> starlark::Result<Policy> {
     let parser = PolicyParser::new("#default", DEFAULT_POLICY);
     parser.parse()
 }
+
+/// Parses `parser` on top of the default policy so that a user-supplied
+/// policy can add programs and forbidden patterns without having to copy the
+/// shipped rules.
+pub fn get_default_policy_extended_with(parser: PolicyParser) -> starlark::Result<Policy> {
+    PolicyParser::parse_layered(&[PolicyParser::new("#default", DEFAULT_POLICY), parser])
+}
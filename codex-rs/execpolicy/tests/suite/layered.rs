@@ -0,0 +1,82 @@
+extern crate codex_execpolicy;
+
+use codex_execpolicy::ArgType;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::Forbidden;
+use codex_execpolicy::MatchedArg;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::Policy;
+use codex_execpolicy::PolicyParser;
+use codex_execpolicy::Result;
+use codex_execpolicy::ValidExec;
+use codex_execpolicy::get_default_policy_extended_with;
+
+const USER_POLICY: &str = r#"
+define_program(
+    program="cargo",
+    args=["check"],
+)
+
+forbid_program_regex(
+    regex="^shutdown$",
+    reason="shutting down the host is not allowed",
+)
+"#;
+
+#[expect(clippy::expect_used)]
+fn setup() -> Policy {
+    get_default_policy_extended_with(PolicyParser::new("user.policy", USER_POLICY))
+        .expect("failed to load layered policy")
+}
+
+#[test]
+fn test_user_program_is_matched() -> Result<()> {
+    let policy = setup();
+    let cargo_check = ExecCall::new("cargo", &["check"]);
+    assert_eq!(
+        Ok(MatchedExec::Match {
+            exec: ValidExec::new(
+                "cargo",
+                vec![MatchedArg::new(
+                    0,
+                    ArgType::Literal("check".to_string()),
+                    "check"
+                )?],
+                &[]
+            )
+        }),
+        policy.check(&cargo_check)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_default_programs_are_still_matched() {
+    let policy = setup();
+    let pwd = ExecCall::new("pwd", &[]);
+    assert_eq!(
+        Ok(MatchedExec::Match {
+            exec: ValidExec {
+                program: "pwd".into(),
+                ..Default::default()
+            }
+        }),
+        policy.check(&pwd)
+    );
+}
+
+#[test]
+fn test_user_forbidden_program_regex() {
+    let policy = setup();
+    let shutdown = ExecCall::new("shutdown", &["-h", "now"]);
+    assert_eq!(
+        Ok(MatchedExec::Forbidden {
+            cause: Forbidden::Program {
+                program: "shutdown".into(),
+                exec_call: shutdown.clone(),
+            },
+            reason: "shutting down the host is not allowed".into(),
+        }),
+        policy.check(&shutdown)
+    );
+}
@@ -3,6 +3,7 @@ mod bad;
 mod cp;
 mod good;
 mod head;
+mod layered;
 mod literal;
 mod ls;
 mod parse_sed_command;
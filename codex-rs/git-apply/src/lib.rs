@@ -5,6 +5,11 @@ use std::io;
 use std::path::Path;
 use std::path::PathBuf;
 
+mod merge;
+
+pub use merge::MergeConflict;
+pub use merge::merge_file;
+
 #[derive(Debug, Clone)]
 pub struct ApplyGitRequest {
     pub cwd: PathBuf,
@@ -0,0 +1,190 @@
+//! Line-based three-way merge with the semantics of `git merge-file`: the
+//! changes each side made relative to the common base are combined, and
+//! overlapping changes that disagree are reported as conflicts.
+
+use std::fmt;
+use std::ops::Range;
+
+use similar::Algorithm;
+use similar::DiffOp;
+use similar::capture_diff_slices;
+
+/// Returned by [`merge_file`] when both sides changed the same lines
+/// differently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeConflict {
+    /// Number of conflicting regions.
+    pub conflicts: usize,
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.conflicts {
+            1 => write!(f, "1 conflicting change"),
+            n => write!(f, "{n} conflicting changes"),
+        }
+    }
+}
+
+impl std::error::Error for MergeConflict {}
+
+/// Replacement of `base[start..end]` by `other[new]`.
+#[derive(Debug)]
+struct Edit {
+    start: usize,
+    end: usize,
+    new: Range<usize>,
+}
+
+/// Merge the changes `ours` and `theirs` made to `base`. Line endings are kept
+/// exactly as they appear in the inputs.
+pub fn merge_file(base: &str, ours: &str, theirs: &str) -> Result<String, MergeConflict> {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let our_edits = edits(&base, &ours);
+    let their_edits = edits(&base, &theirs);
+
+    let mut merged: Vec<&str> = Vec::new();
+    let mut conflicts = 0;
+    let mut pos = 0;
+    let (mut i, mut j) = (0, 0);
+    while i < our_edits.len() || j < their_edits.len() {
+        // Start a region with whichever edit comes first, then pull in every
+        // edit from either side that overlaps or touches it.
+        let start = match (our_edits.get(i), their_edits.get(j)) {
+            (Some(a), Some(b)) => a.start.min(b.start),
+            (Some(a), None) => a.start,
+            (None, Some(b)) => b.start,
+            (None, None) => break,
+        };
+        let mut end = start;
+        let (first_ours, first_theirs) = (i, j);
+        loop {
+            if let Some(edit) = our_edits.get(i).filter(|edit| edit.start <= end) {
+                end = end.max(edit.end);
+                i += 1;
+            } else if let Some(edit) = their_edits.get(j).filter(|edit| edit.start <= end) {
+                end = end.max(edit.end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        merged.extend_from_slice(&base[pos..start]);
+        let our_region = apply(&base, &ours, start, end, &our_edits[first_ours..i]);
+        let their_region = apply(&base, &theirs, start, end, &their_edits[first_theirs..j]);
+        if first_theirs == j || our_region == their_region {
+            merged.extend(our_region);
+        } else if first_ours == i {
+            merged.extend(their_region);
+        } else {
+            conflicts += 1;
+        }
+        pos = end;
+    }
+    merged.extend_from_slice(&base[pos..]);
+
+    if conflicts > 0 {
+        return Err(MergeConflict { conflicts });
+    }
+    Ok(merged.concat())
+}
+
+fn edits(base: &[&str], other: &[&str]) -> Vec<Edit> {
+    let mut edits: Vec<Edit> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, other) {
+        let (start, end, new) = match op {
+            DiffOp::Equal { .. } => continue,
+            DiffOp::Delete {
+                old_index,
+                old_len,
+                new_index,
+            } => (old_index, old_index + old_len, new_index..new_index),
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                new_len,
+            } => (old_index, old_index, new_index..new_index + new_len),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => (
+                old_index,
+                old_index + old_len,
+                new_index..new_index + new_len,
+            ),
+        };
+        // A deletion directly followed by an insertion is a single change.
+        if let Some(last) = edits.last_mut()
+            && last.end == start
+            && last.new.end == new.start
+        {
+            last.end = end;
+            last.new.end = new.end;
+            continue;
+        }
+        edits.push(Edit { start, end, new });
+    }
+    edits
+}
+
+/// `base[start..end]` with `edits` (all inside that range) applied.
+fn apply<'a>(
+    base: &[&'a str],
+    other: &[&'a str],
+    start: usize,
+    end: usize,
+    edits: &[Edit],
+) -> Vec<&'a str> {
+    let mut lines = Vec::new();
+    let mut pos = start;
+    for edit in edits {
+        lines.extend_from_slice(&base[pos..edit.start]);
+        lines.extend_from_slice(&other[edit.new.clone()]);
+        pos = edit.end;
+    }
+    lines.extend_from_slice(&base[pos..end]);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_changes_to_different_lines() {
+        let base = "a\nb\nc\nd\ne\nf\n";
+        let ours = "a\nB\nc\nd\ne\nf\n";
+        let theirs = "a\nb\nc\nd\nE\nf\ng\n";
+        assert_eq!(
+            merge_file(base, ours, theirs),
+            Ok("a\nB\nc\nd\nE\nf\ng\n".to_string())
+        );
+    }
+
+    #[test]
+    fn identical_changes_are_not_conflicts() {
+        let base = "a\nb\nc\n";
+        let changed = "a\nx\nc\n";
+        assert_eq!(merge_file(base, changed, changed), Ok(changed.to_string()));
+        assert_eq!(merge_file(base, base, changed), Ok(changed.to_string()));
+    }
+
+    #[test]
+    fn overlapping_changes_conflict() {
+        let base = "a\nb\nc\n";
+        assert_eq!(
+            merge_file(base, "a\nx\nc\n", "a\ny\nc\n"),
+            Err(MergeConflict { conflicts: 1 })
+        );
+        // Changes to adjacent lines are treated as overlapping, like Git does.
+        assert_eq!(
+            merge_file(base, "a\nx\nc\n", "a\nb\ny\n"),
+            Err(MergeConflict { conflicts: 1 })
+        );
+    }
+}
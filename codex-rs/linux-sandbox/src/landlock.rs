@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -10,8 +11,10 @@ use codex_core::protocol::SandboxPolicy;
 use landlock::ABI;
 use landlock::Access;
 use landlock::AccessFs;
+use landlock::AccessNet;
 use landlock::CompatLevel;
 use landlock::Compatible;
+use landlock::NetPort;
 use landlock::Ruleset;
 use landlock::RulesetAttr;
 use landlock::RulesetCreatedAttr;
@@ -25,38 +28,201 @@ use seccompiler::SeccompRule;
 use seccompiler::TargetArch;
 use seccompiler::apply_filter;
 
+use crate::namespaces::bring_up_loopback;
+use crate::namespaces::drop_capabilities;
+use crate::namespaces::hide_read_denied_paths;
+use crate::namespaces::make_mounts_private;
+use crate::namespaces::unshare_with_user_namespace;
+use crate::proxy_bridge::ProxyBridge;
+use crate::violations::Supervisor;
+
 /// Apply sandbox policies inside this thread so only the child inherits
 /// them, not the entire CLI process.
 pub(crate) fn apply_sandbox_policy_to_current_thread(
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    proxy_bridge: Option<ProxyBridge>,
+    supervisor: Option<Supervisor>,
 ) -> Result<()> {
-    if !sandbox_policy.has_full_network_access() {
-        install_network_seccomp_filter_on_current_thread()?;
-    }
-
+    let mut writable_roots = Vec::new();
+    let mut read_denied = Vec::new();
     if !sandbox_policy.has_full_disk_write_access() {
-        let writable_roots = sandbox_policy
+        writable_roots = sandbox_policy
             .get_writable_roots_with_cwd(cwd)
             .into_iter()
             .map(|writable_root| writable_root.root)
-            .collect();
-        install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
+            .collect::<Vec<_>>();
+        read_denied = sandbox_policy
+            .get_read_denied_paths_with_cwd(cwd)
+            .into_iter()
+            .chain(sandbox_policy.find_read_denied_files_with_cwd(cwd))
+            .filter_map(|path| path.canonicalize().ok())
+            .collect::<Vec<_>>();
+    }
+
+    // Denied paths are hidden with mounts where possible. Landlock can only
+    // deny reads by leaving whole hierarchies out, which is the fallback for
+    // the paths outside writable roots; inside them it cannot deny reads.
+    let namespaces = enter_namespaces(proxy_bridge, &read_denied)?;
+    match namespaces.hide_error {
+        None => read_denied.clear(),
+        Some(err) => {
+            let canonical_roots = writable_roots
+                .iter()
+                .filter_map(|root| root.canonicalize().ok())
+                .collect::<Vec<_>>();
+            let exposed: Vec<PathBuf>;
+            (exposed, read_denied) = read_denied
+                .into_iter()
+                .partition(|path| canonical_roots.iter().any(|root| path.starts_with(root)));
+            if !exposed.is_empty() {
+                warn_read_denied_paths_exposed(&exposed, &err);
+            }
+        }
+    }
+    apply_network_policy_to_current_thread(
+        sandbox_policy,
+        namespaces.network_proxy_port,
+        supervisor,
+    )?;
+
+    if !sandbox_policy.has_full_disk_write_access() {
+        install_filesystem_landlock_rules_on_current_thread(writable_roots, &read_denied)?;
+    }
+
+    Ok(())
+}
+
+/// What [`enter_namespaces`] set up.
+struct Namespaces {
+    /// Port the allowlist proxy is reachable on.
+    network_proxy_port: Option<u16>,
+    /// Why the paths to hide are still visible, if they are.
+    hide_error: Option<std::io::Error>,
+}
+
+/// Moves the current process into the namespaces Landlock needs help from: a
+/// network namespace whose only way out is `proxy_bridge`, and a mount
+/// namespace where `hidden` is covered up.
+///
+/// Hiding is best effort, the caller falls back to Landlock for the paths it
+/// can deny. The proxy bridge is not.
+///
+/// Must be called while the process is still single-threaded.
+fn enter_namespaces(proxy_bridge: Option<ProxyBridge>, hidden: &[PathBuf]) -> Result<Namespaces> {
+    let mut flags = 0;
+    if proxy_bridge.is_some() {
+        flags |= libc::CLONE_NEWNET;
+    }
+    if !hidden.is_empty() {
+        flags |= libc::CLONE_NEWNS;
+    }
+    if flags == 0 {
+        return Ok(Namespaces {
+            network_proxy_port: None,
+            hide_error: None,
+        });
+    }
+    if let Err(err) = unshare_with_user_namespace(flags) {
+        return match proxy_bridge {
+            Some(_) => Err(err.into()),
+            None => Ok(Namespaces {
+                network_proxy_port: None,
+                hide_error: Some(err),
+            }),
+        };
+    }
+
+    let hide_error = if hidden.is_empty() {
+        None
+    } else {
+        make_mounts_private()
+            .and_then(|()| hide_read_denied_paths(hidden))
+            .err()
+    };
+    let hid = !hidden.is_empty() && hide_error.is_none();
+    let network_proxy_port = match proxy_bridge {
+        Some(proxy_bridge) => {
+            let port = proxy_bridge.port();
+            bring_up_loopback()?;
+            proxy_bridge.listen()?;
+            Some(port)
+        }
+        None => None,
+    };
+    // Without them, the command cannot undo the mounts or touch the network
+    // namespace's configuration.
+    if hid || network_proxy_port.is_some() {
+        drop_capabilities()?;
     }
+    Ok(Namespaces {
+        network_proxy_port,
+        hide_error,
+    })
+}
 
-    // TODO(ragona): Add appropriate restrictions if
-    // `sandbox_policy.has_full_disk_read_access()` is `false`.
+/// Tells whoever reads the command's output that `exposed` stays readable:
+/// the sandbox cannot deny reads inside writable roots without mounts.
+fn warn_read_denied_paths_exposed(exposed: &[PathBuf], err: &std::io::Error) {
+    let paths = exposed
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    eprintln!(
+        "codex-linux-sandbox: warning: {paths} cannot be hidden from this command ({err}) and \
+         remain readable"
+    );
+}
 
+/// Restricts network access of the current thread to what the policy allows:
+/// nothing, or only the allowlist proxy on `network_proxy_port`. With a
+/// `supervisor`, the seccomp filter also reports what it denies.
+pub(crate) fn apply_network_policy_to_current_thread(
+    sandbox_policy: &SandboxPolicy,
+    network_proxy_port: Option<u16>,
+    supervisor: Option<Supervisor>,
+) -> Result<()> {
+    let rules = if sandbox_policy.has_full_network_access() {
+        BTreeMap::new()
+    } else {
+        // Without a proxy to enforce it, an allowlist means no network at all.
+        match allowlist_proxy_port(sandbox_policy, network_proxy_port) {
+            Some(port) => {
+                install_network_landlock_rules_on_current_thread(port)?;
+                network_seccomp_rules(NetworkFilter::TcpOnly)?
+            }
+            None => network_seccomp_rules(NetworkFilter::UnixOnly)?,
+        }
+    };
+    match supervisor {
+        Some(supervisor) => supervisor.install_filter(rules)?,
+        None if !rules.is_empty() => install_seccomp_filter_on_current_thread(rules)?,
+        None => {}
+    }
     Ok(())
 }
 
+/// The proxy port commands may connect to, when the policy has a network
+/// allowlist and a proxy enforcing it.
+pub(crate) fn allowlist_proxy_port(
+    sandbox_policy: &SandboxPolicy,
+    network_proxy_port: Option<u16>,
+) -> Option<u16> {
+    network_proxy_port.filter(|_| !sandbox_policy.network_allowlist().is_empty())
+}
+
 /// Installs Landlock file-system rules on the current thread allowing read
-/// access to the entire file-system while restricting write access to
-/// `/dev/null` and the provided list of `writable_roots`.
+/// access to the entire file-system except `read_denied` while restricting
+/// write access to `/dev/null` and the provided list of `writable_roots`.
+/// None of `read_denied` may be inside a writable root.
 ///
 /// # Errors
 /// Returns [`CodexErr::Sandbox`] variants when the ruleset fails to apply.
-fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathBuf>) -> Result<()> {
+fn install_filesystem_landlock_rules_on_current_thread(
+    writable_roots: Vec<PathBuf>,
+    read_denied: &[PathBuf],
+) -> Result<()> {
     let abi = ABI::V5;
     let access_rw = AccessFs::from_all(abi);
     let access_ro = AccessFs::from_read(abi);
@@ -65,10 +231,23 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
         .set_compatibility(CompatLevel::BestEffort)
         .handle_access(access_rw)?
         .create()?
-        .add_rules(landlock::path_beneath_rules(&["/"], access_ro))?
         .add_rules(landlock::path_beneath_rules(&["/dev/null"], access_rw))?
         .set_no_new_privs(true);
 
+    match ReadableTree::excluding(read_denied) {
+        None => {
+            ruleset = ruleset.add_rules(landlock::path_beneath_rules(&["/"], access_ro))?;
+        }
+        Some(tree) => {
+            ruleset = ruleset
+                .add_rules(landlock::path_beneath_rules(
+                    &tree.ancestors,
+                    AccessFs::ReadDir,
+                ))?
+                .add_rules(landlock::path_beneath_rules(&tree.siblings, access_ro))?;
+        }
+    }
+
     if !writable_roots.is_empty() {
         ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable_roots, access_rw))?;
     }
@@ -82,9 +261,113 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
-/// Installs a seccomp filter that blocks outbound network access except for
-/// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+/// The paths to grant read access to so that everything but a set of denied
+/// paths is readable.
+///
+/// Landlock can only grant access to whole hierarchies, so instead of `/` this
+/// grants every entry next to a denied path or one of its ancestors, while the
+/// ancestors themselves may only be listed. Entries created in those ancestor
+/// directories after the sandbox starts are therefore not readable, which is
+/// why this is only the fallback for when denied paths cannot be hidden with
+/// mounts.
+struct ReadableTree {
+    ancestors: Vec<PathBuf>,
+    siblings: Vec<PathBuf>,
+}
+
+impl ReadableTree {
+    /// Returns `None` when none of `read_denied` exists, i.e. the whole
+    /// file-system may be read.
+    fn excluding(read_denied: &[PathBuf]) -> Option<Self> {
+        // Landlock rules apply to inodes, so resolve symlinks first.
+        let denied: Vec<PathBuf> = read_denied
+            .iter()
+            .filter_map(|path| path.canonicalize().ok())
+            .collect();
+        if denied.is_empty() {
+            return None;
+        }
+        let ancestors: BTreeSet<PathBuf> = denied
+            .iter()
+            .flat_map(|path| path.ancestors().skip(1))
+            .filter(|ancestor| !denied.iter().any(|denied| ancestor.starts_with(denied)))
+            .map(Path::to_path_buf)
+            .collect();
+
+        let mut siblings = Vec::new();
+        for ancestor in &ancestors {
+            let Ok(entries) = std::fs::read_dir(ancestor) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                // A rule on a symlink applies to its target, which must not
+                // be (or contain) a denied path either.
+                let target = if entry.file_type().is_ok_and(|kind| kind.is_symlink()) {
+                    match path.canonicalize() {
+                        Ok(target) => target,
+                        Err(_) => continue,
+                    }
+                } else {
+                    path.clone()
+                };
+                if denied
+                    .iter()
+                    .any(|denied| target.starts_with(denied) || denied.starts_with(&target))
+                {
+                    continue;
+                }
+                siblings.push(path);
+            }
+        }
+
+        Some(Self {
+            ancestors: ancestors.into_iter().collect(),
+            siblings,
+        })
+    }
+}
+
+/// Installs Landlock network rules on the current thread that only allow
+/// connecting to TCP `port`, where the network allowlist proxy listens.
+/// Landlock cannot restrict the address, so this is only safe in a network
+/// namespace where the proxy's bridge is the only listener on `port`.
+///
+/// Unlike the file-system rules these are a hard requirement: on kernels
+/// without Landlock network support (before 6.7) the command fails instead of
+/// running with unrestricted TCP access.
+fn install_network_landlock_rules_on_current_thread(port: u16) -> Result<()> {
+    let abi = ABI::V4;
+    let status = Ruleset::default()
+        .set_compatibility(CompatLevel::HardRequirement)
+        .handle_access(AccessNet::from_all(abi))?
+        .create()?
+        .add_rule(NetPort::new(port, AccessNet::ConnectTcp))?
+        .set_no_new_privs(true)
+        .restrict_self()?;
+
+    if status.ruleset != landlock::RulesetStatus::FullyEnforced {
+        return Err(CodexErr::Sandbox(SandboxErr::LandlockRestrict));
+    }
+
+    Ok(())
+}
+
+/// Which sockets [`network_seccomp_rules`] leaves usable.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NetworkFilter {
+    /// Only AF_UNIX domain sockets.
+    UnixOnly,
+    /// AF_UNIX sockets plus outbound TCP, whose destinations are restricted
+    /// by Landlock.
+    TcpOnly,
+}
+
+/// Seccomp rules that block outbound network access except for AF_UNIX
+/// domain sockets and, with [`NetworkFilter::TcpOnly`], TCP client sockets.
+fn network_seccomp_rules(
+    filter: NetworkFilter,
+) -> std::result::Result<BTreeMap<i64, Vec<SeccompRule>>, SandboxErr> {
     // Build rule map.
     let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
 
@@ -93,25 +376,27 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         rules.insert(nr, vec![]); // empty rule vec = unconditional match
     };
 
-    deny_syscall(libc::SYS_connect);
     deny_syscall(libc::SYS_accept);
     deny_syscall(libc::SYS_accept4);
     deny_syscall(libc::SYS_bind);
     deny_syscall(libc::SYS_listen);
-    deny_syscall(libc::SYS_getpeername);
-    deny_syscall(libc::SYS_getsockname);
-    deny_syscall(libc::SYS_shutdown);
-    deny_syscall(libc::SYS_sendto);
-    deny_syscall(libc::SYS_sendmsg);
-    deny_syscall(libc::SYS_sendmmsg);
-    // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
-    // with their socketpair + child processes for sub-proc management
-    // deny_syscall(libc::SYS_recvfrom);
-    deny_syscall(libc::SYS_recvmsg);
-    deny_syscall(libc::SYS_recvmmsg);
-    deny_syscall(libc::SYS_getsockopt);
-    deny_syscall(libc::SYS_setsockopt);
     deny_syscall(libc::SYS_ptrace);
+    if filter == NetworkFilter::UnixOnly {
+        deny_syscall(libc::SYS_connect);
+        deny_syscall(libc::SYS_getpeername);
+        deny_syscall(libc::SYS_getsockname);
+        deny_syscall(libc::SYS_shutdown);
+        deny_syscall(libc::SYS_sendto);
+        deny_syscall(libc::SYS_sendmsg);
+        deny_syscall(libc::SYS_sendmmsg);
+        // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
+        // with their socketpair + child processes for sub-proc management
+        // deny_syscall(libc::SYS_recvfrom);
+        deny_syscall(libc::SYS_recvmsg);
+        deny_syscall(libc::SYS_recvmmsg);
+        deny_syscall(libc::SYS_getsockopt);
+        deny_syscall(libc::SYS_setsockopt);
+    }
 
     // For `socket` we allow AF_UNIX (arg0 == AF_UNIX) and deny everything else.
     let unix_only_rule = SeccompRule::new(vec![SeccompCondition::new(
@@ -121,13 +406,38 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         libc::AF_UNIX as u64,
     )?])?;
 
-    rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
+    let socket_rules = match filter {
+        NetworkFilter::UnixOnly => vec![unix_only_rule.clone()],
+        NetworkFilter::TcpOnly => tcp_socket_deny_rules()?,
+    };
+    rules.insert(libc::SYS_socket, socket_rules);
     rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
 
+    Ok(rules)
+}
+
+/// Installs a seccomp filter on the current thread that makes the syscalls
+/// matching `rules` fail with `EPERM`.
+pub(crate) fn install_seccomp_filter_on_current_thread(
+    rules: BTreeMap<i64, Vec<SeccompRule>>,
+) -> std::result::Result<(), SandboxErr> {
+    let prog = compile_seccomp_filter(rules, SeccompAction::Errno(libc::EPERM as u32))?;
+
+    apply_filter(&prog)?;
+
+    Ok(())
+}
+
+/// Compiles a filter that returns `action` for the syscalls matching `rules`
+/// and allows everything else.
+pub(crate) fn compile_seccomp_filter(
+    rules: BTreeMap<i64, Vec<SeccompRule>>,
+    action: SeccompAction,
+) -> std::result::Result<BpfProgram, SandboxErr> {
     let filter = SeccompFilter::new(
         rules,
-        SeccompAction::Allow,                     // default – allow
-        SeccompAction::Errno(libc::EPERM as u32), // when rule matches – return EPERM
+        SeccompAction::Allow, // default – allow
+        action,               // when rule matches
         if cfg!(target_arch = "x86_64") {
             TargetArch::x86_64
         } else if cfg!(target_arch = "aarch64") {
@@ -137,9 +447,46 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         },
     )?;
 
-    let prog: BpfProgram = filter.try_into()?;
-
-    apply_filter(&prog)?;
+    Ok(filter.try_into()?)
+}
 
-    Ok(())
+/// Rules denying every `socket` call except AF_UNIX sockets and TCP
+/// AF_INET/AF_INET6 sockets. Landlock only restricts TCP, so UDP, raw and
+/// other stream sockets such as SCTP must not be created at all.
+fn tcp_socket_deny_rules() -> std::result::Result<Vec<SeccompRule>, SandboxErr> {
+    let domain =
+        |op, family: i32| SeccompCondition::new(0, SeccompCmpArgLen::Dword, op, family as u64);
+    let mut rules = vec![SeccompRule::new(vec![
+        domain(SeccompCmpOp::Ne, libc::AF_UNIX)?,
+        domain(SeccompCmpOp::Ne, libc::AF_INET)?,
+        domain(SeccompCmpOp::Ne, libc::AF_INET6)?,
+    ])?];
+    // SOCK_STREAM (1) is the only socket type with neither bit 1 nor bit 2
+    // set; SOCK_NONBLOCK and SOCK_CLOEXEC live in higher bits.
+    for family in [libc::AF_INET, libc::AF_INET6] {
+        for bit in [0b10, 0b100] {
+            rules.push(SeccompRule::new(vec![
+                domain(SeccompCmpOp::Eq, family)?,
+                SeccompCondition::new(
+                    1,
+                    SeccompCmpArgLen::Dword,
+                    SeccompCmpOp::MaskedEq(bit),
+                    bit,
+                )?,
+            ])?);
+        }
+        // Stream sockets of other protocols, such as IPPROTO_SCTP, pass the
+        // type check; only the default protocol (0) and TCP may be asked for.
+        rules.push(SeccompRule::new(vec![
+            domain(SeccompCmpOp::Eq, family)?,
+            SeccompCondition::new(2, SeccompCmpArgLen::Dword, SeccompCmpOp::Ne, 0)?,
+            SeccompCondition::new(
+                2,
+                SeccompCmpArgLen::Dword,
+                SeccompCmpOp::Ne,
+                libc::IPPROTO_TCP as u64,
+            )?,
+        ])?);
+    }
+    Ok(rules)
 }
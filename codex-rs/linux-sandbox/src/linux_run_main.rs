@@ -2,10 +2,43 @@ use clap::Parser;
 use std::ffi::CString;
 use std::path::PathBuf;
 
+use codex_core::resource_limits::ResourceLimits;
+
+use crate::landlock::allowlist_proxy_port;
 use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::namespaces::apply_namespace_sandbox;
+use crate::proxy_bridge::spawn_proxy_bridge;
+use crate::resource_limits::apply_resource_limits_to_current_process;
+use crate::violations::spawn_supervisor;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// Port of the local proxy enforcing the policy's network allowlist.
+    /// The command runs in its own network namespace where connections to
+    /// this port on 127.0.0.1 reach the proxy; there is no other way out.
+    #[arg(long = "network-proxy-port")]
+    pub network_proxy_port: Option<u16>,
+
+    /// Isolate the command in user, mount and PID namespaces instead of
+    /// applying Landlock file-system rules.
+    #[arg(long = "namespaces", default_value_t = false)]
+    pub namespaces: bool,
+
+    /// Resource limits (JSON-encoded `ResourceLimits`) to apply to the
+    /// command before it is executed.
+    #[arg(long = "resource-limits")]
+    pub resource_limits: Option<ResourceLimits>,
+
+    /// File to append the operations the sandbox denies to, as JSON
+    /// `SandboxViolation`s, one per line.
+    #[arg(long = "violation-report")]
+    pub violation_report: Option<PathBuf>,
+
+    /// Also report the writes the sandbox denies. Every write of the command
+    /// then waits for the supervisor, so this is off unless asked for.
+    #[arg(long = "report-denied-writes", default_value_t = false)]
+    pub report_denied_writes: bool,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -19,12 +52,58 @@ pub struct LandlockCommand {
 
 pub fn run_main() -> ! {
     let LandlockCommand {
+        network_proxy_port,
+        namespaces,
+        resource_limits,
+        violation_report,
+        report_denied_writes,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
     } = LandlockCommand::parse();
 
-    if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
+    // Set up limits first: entering the memory cgroup writes to
+    // /sys/fs/cgroup, which Landlock no longer allows afterwards.
+    if let Some(resource_limits) = resource_limits
+        && let Err(e) = apply_resource_limits_to_current_process(&resource_limits)
+    {
+        panic!("error applying resource limits: {e:?}");
+    }
+
+    // The bridge to the network allowlist proxy has to stay in the host's
+    // network namespace, so it is forked off before anything else is set up.
+    let proxy_bridge = allowlist_proxy_port(&sandbox_policy, network_proxy_port).map(|port| {
+        spawn_proxy_bridge(port)
+            .unwrap_or_else(|e| panic!("error starting network proxy bridge: {e:?}"))
+    });
+
+    // The supervisor has to stay outside the sandbox as well.
+    let supervisor = match violation_report {
+        Some(report) => spawn_supervisor(
+            &sandbox_policy,
+            &sandbox_policy_cwd,
+            &report,
+            report_denied_writes,
+        )
+        .unwrap_or_else(|e| panic!("error starting sandbox supervisor: {e:?}")),
+        None => None,
+    };
+
+    if namespaces {
+        if let Err(e) = apply_namespace_sandbox(
+            &sandbox_policy,
+            &sandbox_policy_cwd,
+            proxy_bridge,
+            supervisor,
+        ) {
+            panic!("error setting up namespace sandbox: {e:?}");
+        }
+    } else if let Err(e) = apply_sandbox_policy_to_current_thread(
+        &sandbox_policy,
+        &sandbox_policy_cwd,
+        proxy_bridge,
+        supervisor,
+    ) {
         panic!("error running landlock: {e:?}");
     }
 
@@ -0,0 +1,549 @@
+//! Bubblewrap-style isolation in user, mount, PID and IPC namespaces, plus a
+//! network namespace unless the policy allows full network access.
+//!
+//! The command sees the host file-system read-only with its writable roots
+//! bound back read-write, read-denied paths hidden, a private `/tmp` (when it
+//! may write to `/tmp` at all), a minimal `/dev` and a `/proc` that only
+//! shows its own processes. It runs
+//! without capabilities, so it cannot undo any of these mounts.
+
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::fs::symlink;
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+use codex_core::error::Result;
+use codex_core::protocol::SandboxPolicy;
+
+use crate::landlock::apply_network_policy_to_current_thread;
+use crate::proxy_bridge::ProxyBridge;
+use crate::violations::Supervisor;
+
+/// Host device nodes available in the private `/dev`.
+const DEVICES: [&str; 6] = ["null", "zero", "full", "random", "urandom", "tty"];
+
+/// Moves the current process into new namespaces and returns in the process
+/// that should exec the command. The calling process stays outside and
+/// exits with the command's status once it finishes; it never returns.
+///
+/// Must be called while the process is still single-threaded.
+pub(crate) fn apply_namespace_sandbox(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    proxy_bridge: Option<ProxyBridge>,
+    supervisor: Option<Supervisor>,
+) -> Result<()> {
+    // Commands with a network allowlist reach the proxy through the bridge.
+    let isolate_network = !sandbox_policy.has_full_network_access();
+
+    let mut flags = libc::CLONE_NEWNS | libc::CLONE_NEWPID | libc::CLONE_NEWIPC;
+    if isolate_network {
+        flags |= libc::CLONE_NEWNET;
+    }
+    unshare_with_user_namespace(flags)?;
+
+    // Only children enter the new PID namespace. The first one becomes its
+    // init: it runs the command as its own child and reaps orphans, since the
+    // command would ignore most signals as PID 1.
+    let (status_read, status_write) = pipe()?;
+    let init = check(unsafe { libc::fork() })?;
+    if init != 0 {
+        drop(status_write);
+        drop(proxy_bridge);
+        drop(supervisor);
+        relay_exit_status(init, status_read);
+    }
+    drop(status_read);
+    check(unsafe { libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL) })?;
+
+    if isolate_network {
+        bring_up_loopback()?;
+    }
+    let network_proxy_port = match proxy_bridge {
+        Some(proxy_bridge) => {
+            let port = proxy_bridge.port();
+            proxy_bridge.listen()?;
+            Some(port)
+        }
+        None => None,
+    };
+    let command_cwd = std::env::current_dir()?;
+    set_up_mounts(sandbox_policy, cwd, &command_cwd)?;
+
+    let command = check(unsafe { libc::fork() })?;
+    if command != 0 {
+        drop(supervisor);
+        reap_until_exit(command, status_write);
+    }
+    drop(status_write);
+
+    // The working directory still refers to the mount it was opened on.
+    std::env::set_current_dir(&command_cwd)?;
+    drop_capabilities()?;
+    if network_proxy_port.is_some() {
+        apply_network_policy_to_current_thread(sandbox_policy, network_proxy_port, supervisor)?;
+    } else if let Some(supervisor) = supervisor {
+        supervisor.install_filter(BTreeMap::new())?;
+    }
+    Ok(())
+}
+
+fn set_up_mounts(sandbox_policy: &SandboxPolicy, cwd: &Path, command_cwd: &Path) -> io::Result<()> {
+    make_mounts_private()?;
+
+    if !sandbox_policy.has_full_disk_write_access() {
+        set_up_file_system(sandbox_policy, cwd, command_cwd)?;
+    }
+
+    mount(
+        Some("proc"),
+        Path::new("/proc"),
+        Some("proc"),
+        libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+        None,
+    )
+}
+
+fn set_up_file_system(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    command_cwd: &Path,
+) -> io::Result<()> {
+    let tmp = Path::new("/tmp");
+    let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+    let mut read_denied = sandbox_policy.get_read_denied_paths_with_cwd(cwd);
+    read_denied.extend(sandbox_policy.find_read_denied_files_with_cwd(cwd));
+
+    // Open everything that is bound back in before /tmp and /dev are covered.
+    let roots = writable_roots
+        .into_iter()
+        .filter_map(|root| open_path(&root.root).ok().map(|fd| (root, fd)))
+        .collect::<Vec<_>>();
+    let devices = DEVICES
+        .iter()
+        .filter_map(|name| {
+            open_path(&Path::new("/dev").join(name))
+                .ok()
+                .map(|fd| (name, fd))
+        })
+        .collect::<Vec<_>>();
+    let command_cwd_fd = open_path(command_cwd)?;
+
+    set_mount_attr(
+        Path::new("/"),
+        libc::MOUNT_ATTR_RDONLY | libc::MOUNT_ATTR_NOSUID,
+        0,
+    )?;
+
+    // When the policy lets commands write to /tmp they get a private one
+    // instead; otherwise the host's /tmp stays visible, read-only. Roots
+    // inside /tmp are bound after it is replaced.
+    let tmp_writable = roots.iter().any(|(root, _)| root.root == tmp);
+    let (roots_in_tmp, other_roots): (Vec<_>, Vec<_>) = roots
+        .iter()
+        .filter(|(root, _)| root.root != tmp)
+        .partition(|(root, _)| root.root.starts_with(tmp));
+    for (root, fd) in other_roots {
+        bind_writable_root(&root.root, &root.read_only_subpaths, fd)?;
+    }
+    if tmp_writable {
+        mount_tmpfs(tmp, "mode=1777", libc::MS_NOSUID | libc::MS_NODEV)?;
+    }
+    set_up_dev(&devices)?;
+    for (root, fd) in roots_in_tmp {
+        bind_writable_root(&root.root, &root.read_only_subpaths, fd)?;
+    }
+
+    // Commands can always look at the directory they run in.
+    if !command_cwd.exists() {
+        fs::create_dir_all(command_cwd)?;
+        bind_fd(&command_cwd_fd, command_cwd)?;
+        set_mount_attr(command_cwd, libc::MOUNT_ATTR_RDONLY, 0)?;
+    }
+
+    hide_read_denied_paths(&read_denied)?;
+
+    // The private /dev stays read-only; its device nodes and /dev/shm are
+    // separate mounts and remain writable.
+    mount(
+        None,
+        Path::new("/dev"),
+        None,
+        libc::MS_REMOUNT | libc::MS_BIND | libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NOEXEC,
+        None,
+    )
+}
+
+fn bind_writable_root(root: &Path, read_only_subpaths: &[PathBuf], fd: &OwnedFd) -> io::Result<()> {
+    fs::create_dir_all(root)?;
+    bind_fd(fd, root)?;
+    set_mount_attr(root, 0, libc::MOUNT_ATTR_RDONLY)?;
+    for subpath in read_only_subpaths.iter().filter(|subpath| subpath.exists()) {
+        mount(
+            Some(&subpath.to_string_lossy()),
+            subpath,
+            None,
+            libc::MS_BIND | libc::MS_REC,
+            None,
+        )?;
+        set_mount_attr(subpath, libc::MOUNT_ATTR_RDONLY, 0)?;
+    }
+    Ok(())
+}
+
+/// Replaces /dev with a tmpfs holding only `devices`, the usual `/proc`
+/// symlinks, a private `/dev/shm` and a new `/dev/pts` instance.
+fn set_up_dev(devices: &[(&&str, OwnedFd)]) -> io::Result<()> {
+    let dev = Path::new("/dev");
+    mount_tmpfs(dev, "mode=755", libc::MS_NOSUID | libc::MS_NOEXEC)?;
+    for (name, fd) in devices {
+        let target = dev.join(name);
+        File::create(&target)?;
+        bind_fd(fd, &target)?;
+        set_mount_attr(&target, 0, libc::MOUNT_ATTR_RDONLY)?;
+    }
+    for (name, target) in [
+        ("fd", "/proc/self/fd"),
+        ("stdin", "/proc/self/fd/0"),
+        ("stdout", "/proc/self/fd/1"),
+        ("stderr", "/proc/self/fd/2"),
+        ("ptmx", "pts/ptmx"),
+    ] {
+        symlink(target, dev.join(name))?;
+    }
+
+    let shm = dev.join("shm");
+    fs::create_dir(&shm)?;
+    mount_tmpfs(&shm, "mode=1777", libc::MS_NOSUID | libc::MS_NODEV)?;
+
+    let pts = dev.join("pts");
+    fs::create_dir(&pts)?;
+    mount(
+        Some("devpts"),
+        &pts,
+        Some("devpts"),
+        libc::MS_NOSUID | libc::MS_NOEXEC,
+        Some("newinstance,ptmxmode=0666,mode=620"),
+    )
+}
+
+/// Keeps mount changes in the current mount namespace from propagating back
+/// to the host.
+pub(crate) fn make_mounts_private() -> io::Result<()> {
+    mount(
+        None,
+        Path::new("/"),
+        None,
+        libc::MS_REC | libc::MS_PRIVATE,
+        None,
+    )
+}
+
+/// Covers read-denied directories with an empty, inaccessible tmpfs and
+/// read-denied files with an empty, unreadable file.
+pub(crate) fn hide_read_denied_paths(read_denied: &[PathBuf]) -> io::Result<()> {
+    // The denied file lives on a tmpfs briefly mounted over /tmp, so the
+    // paths to cover are opened before that hides the ones inside /tmp.
+    let targets = read_denied
+        .iter()
+        .filter_map(|path| {
+            let is_dir = fs::metadata(path).ok()?.is_dir();
+            open_path(path).ok().map(|fd| (path, fd, is_dir))
+        })
+        .collect::<Vec<_>>();
+    let scratch = Path::new("/tmp");
+    let needs_file = targets.iter().any(|(_, _, is_dir)| !is_dir);
+    let denied_file = if needs_file {
+        Some(create_denied_file(scratch)?)
+    } else {
+        None
+    };
+
+    for (path, fd, is_dir) in &targets {
+        let target = fd_path(fd);
+        let hidden = match &denied_file {
+            Some(denied_file) if !is_dir => bind_fd(denied_file, &target),
+            _ => mount_tmpfs(
+                &target,
+                "mode=000",
+                libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+            ),
+        };
+        hidden.map_err(|err| with_path(err, "hide", path))?;
+    }
+
+    // Bind mounts keep the file alive once its tmpfs is gone.
+    if denied_file.is_some() {
+        check(unsafe {
+            libc::umount2(
+                c_string(scratch.as_os_str().as_bytes())?.as_ptr(),
+                libc::MNT_DETACH,
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Creates an empty file nobody may read or change on a read-only tmpfs
+/// mounted over `scratch`.
+fn create_denied_file(scratch: &Path) -> io::Result<OwnedFd> {
+    mount_tmpfs(
+        scratch,
+        "mode=700",
+        libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+    )?;
+    let path = scratch.join("denied");
+    File::create(&path)?.set_permissions(fs::Permissions::from_mode(0o000))?;
+    mount(
+        None,
+        scratch,
+        None,
+        libc::MS_REMOUNT | libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+        None,
+    )?;
+    open_path(&path)
+}
+
+/// Waits for the namespace's init process and exits the way the command did.
+fn relay_exit_status(init: libc::pid_t, status_read: OwnedFd) -> ! {
+    let mut status = [0u8; 4];
+    let command_status = File::from(status_read)
+        .read_exact(&mut status)
+        .ok()
+        .map(|()| i32::from_ne_bytes(status));
+    let init_status = wait_for(init);
+    // Init exits without reporting when setting up the sandbox failed.
+    exit_with_status(ExitStatus::from_raw(command_status.unwrap_or(init_status)));
+}
+
+/// Exits the way a child that ended with `status` did.
+pub(crate) fn exit_with_status(status: ExitStatus) -> ! {
+    if let Some(signal) = status.signal() {
+        // Die from the same signal so the caller sees it, without leaving a
+        // core dump behind.
+        unsafe {
+            let no_core = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            libc::setrlimit(libc::RLIMIT_CORE, &no_core);
+            libc::signal(signal, libc::SIG_DFL);
+            libc::kill(libc::getpid(), signal);
+        }
+        std::process::exit(128 + signal);
+    }
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// Runs as PID 1 of the namespace: reaps every process until the command
+/// exits, then reports its raw wait status. Exiting ends all processes the
+/// command left behind.
+fn reap_until_exit(command: libc::pid_t, status_write: OwnedFd) -> ! {
+    loop {
+        let mut status = 0;
+        let pid = unsafe { libc::waitpid(-1, &mut status, 0) };
+        if pid == command {
+            let _ = File::from(status_write).write_all(&status.to_ne_bytes());
+            unsafe { libc::_exit(0) };
+        }
+        if pid == -1 && io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            unsafe { libc::_exit(1) };
+        }
+    }
+}
+
+pub(crate) fn wait_for(pid: libc::pid_t) -> i32 {
+    loop {
+        let mut status = 0;
+        if unsafe { libc::waitpid(pid, &mut status, 0) } == pid {
+            return status;
+        }
+        if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            return 1 << 8;
+        }
+    }
+}
+
+/// Moves the current process into a new user namespace, and the namespaces
+/// in `flags`, keeping its user and group IDs.
+///
+/// Must be called while the process is still single-threaded.
+pub(crate) fn unshare_with_user_namespace(flags: libc::c_int) -> io::Result<()> {
+    let uid = unsafe { libc::getuid() };
+    let gid = unsafe { libc::getgid() };
+    check(unsafe { libc::unshare(libc::CLONE_NEWUSER | flags) })?;
+    fs::write("/proc/self/setgroups", "deny")?;
+    fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
+    Ok(())
+}
+
+/// A new network namespace only has a loopback interface, and it is down.
+pub(crate) fn bring_up_loopback() -> io::Result<()> {
+    let socket =
+        check(unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) })?;
+    let socket = unsafe { OwnedFd::from_raw_fd(socket) };
+    let mut request: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dst, src) in request.ifr_name.iter_mut().zip(b"lo") {
+        *dst = *src as libc::c_char;
+    }
+    check(unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCGIFFLAGS as _, &mut request) })?;
+    unsafe { request.ifr_ifru.ifru_flags |= libc::IFF_UP as libc::c_short };
+    check(unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCSIFFLAGS as _, &request) })?;
+    Ok(())
+}
+
+/// Drops every capability the process holds in the new user namespace, so
+/// the command cannot change the mounts set up for it, even as root.
+pub(crate) fn drop_capabilities() -> io::Result<()> {
+    #[repr(C)]
+    struct CapHeader {
+        version: u32,
+        pid: libc::c_int,
+    }
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct CapData {
+        effective: u32,
+        permitted: u32,
+        inheritable: u32,
+    }
+    const LINUX_CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+
+    let last_cap = fs::read_to_string("/proc/sys/kernel/cap_last_cap")
+        .ok()
+        .and_then(|last| last.trim().parse::<libc::c_ulong>().ok())
+        .unwrap_or(63);
+    for cap in 0..=last_cap {
+        check(unsafe { libc::prctl(libc::PR_CAPBSET_DROP, cap, 0, 0, 0) })?;
+    }
+    check(unsafe {
+        libc::prctl(
+            libc::PR_CAP_AMBIENT,
+            libc::PR_CAP_AMBIENT_CLEAR_ALL,
+            0,
+            0,
+            0,
+        )
+    })?;
+    let header = CapHeader {
+        version: LINUX_CAPABILITY_VERSION_3,
+        pid: 0,
+    };
+    let data = [CapData::default(); 2];
+    check(unsafe { libc::syscall(libc::SYS_capset, &header, data.as_ptr()) } as libc::c_int)?;
+    check(unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) })?;
+    Ok(())
+}
+
+fn mount(
+    source: Option<&str>,
+    target: &Path,
+    fstype: Option<&str>,
+    flags: libc::c_ulong,
+    data: Option<&str>,
+) -> io::Result<()> {
+    let source = source.map(c_string).transpose()?;
+    let target_c = c_string(target.as_os_str().as_bytes())?;
+    let fstype = fstype.map(c_string).transpose()?;
+    let data = data.map(c_string).transpose()?;
+    let as_ptr = |value: &Option<CString>| value.as_ref().map_or(std::ptr::null(), |v| v.as_ptr());
+    check(unsafe {
+        libc::mount(
+            as_ptr(&source),
+            target_c.as_ptr(),
+            as_ptr(&fstype),
+            flags,
+            as_ptr(&data).cast(),
+        )
+    })
+    .map(|_| ())
+    .map_err(|err| with_path(err, "mount", target))
+}
+
+fn mount_tmpfs(target: &Path, options: &str, flags: libc::c_ulong) -> io::Result<()> {
+    mount(Some("tmpfs"), target, Some("tmpfs"), flags, Some(options))
+}
+
+/// Bind-mounts the file or directory behind `fd` (recursively) onto `target`.
+fn bind_fd(fd: &OwnedFd, target: &Path) -> io::Result<()> {
+    let source = fd_path(fd);
+    mount(
+        Some(&source.to_string_lossy()),
+        target,
+        None,
+        libc::MS_BIND | libc::MS_REC,
+        None,
+    )
+}
+
+/// Sets and clears mount attributes of `path` and every mount below it.
+fn set_mount_attr(path: &Path, set: u64, clear: u64) -> io::Result<()> {
+    let path_c = c_string(path.as_os_str().as_bytes())?;
+    let attr = libc::mount_attr {
+        attr_set: set,
+        attr_clr: clear,
+        propagation: 0,
+        userns_fd: 0,
+    };
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_mount_setattr,
+            libc::AT_FDCWD,
+            path_c.as_ptr(),
+            libc::AT_RECURSIVE,
+            &attr,
+            std::mem::size_of::<libc::mount_attr>(),
+        )
+    };
+    check(ret as libc::c_int)
+        .map(|_| ())
+        .map_err(|err| with_path(err, "mount_setattr", path))
+}
+
+/// A path that refers to whatever `fd` refers to, even once something is
+/// mounted over it.
+fn fd_path(fd: &OwnedFd) -> PathBuf {
+    PathBuf::from(format!("/proc/self/fd/{}", fd.as_raw_fd()))
+}
+
+fn open_path(path: &Path) -> io::Result<OwnedFd> {
+    let path = c_string(path.as_os_str().as_bytes())?;
+    let fd = check(unsafe { libc::open(path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) })?;
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+fn pipe() -> io::Result<(OwnedFd, OwnedFd)> {
+    let mut fds = [0; 2];
+    check(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) })?;
+    Ok(unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) })
+}
+
+fn c_string(value: impl AsRef<[u8]>) -> io::Result<CString> {
+    CString::new(value.as_ref()).map_err(io::Error::other)
+}
+
+pub(crate) fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+fn with_path(err: io::Error, operation: &str, path: &Path) -> io::Error {
+    io::Error::new(err.kind(), format!("{operation} {}: {err}", path.display()))
+}
@@ -0,0 +1,142 @@
+//! Connects a command in its own network namespace to the network allowlist
+//! proxy, which listens on the host's loopback interface.
+//!
+//! Landlock can only limit the port a command connects to, not the address,
+//! so commands with an allowlist run in a network namespace that has nothing
+//! but a loopback interface. Before the command leaves the host's network
+//! namespace, a bridge process is forked off that stays behind. Inside the new
+//! namespace the command listens on the proxy's address and hands the
+//! listening socket to the bridge, which accepts the connections made to it
+//! and relays each one to the proxy. That makes the proxy the only endpoint
+//! outside the namespace the command can reach.
+
+use std::io;
+use std::net::Ipv4Addr;
+use std::net::Shutdown;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::os::fd::AsFd;
+use std::os::fd::AsRawFd;
+use std::os::fd::BorrowedFd;
+use std::os::fd::OwnedFd;
+
+use crate::namespaces::check;
+use crate::namespaces::wait_for;
+use crate::violations::pidfd_open;
+use crate::violations::receive_fd;
+use crate::violations::send_fd;
+use crate::violations::socket_pair;
+
+/// The command's end of the connection to its bridge.
+pub(crate) struct ProxyBridge {
+    socket: OwnedFd,
+    port: u16,
+}
+
+/// Forks off the bridge to the proxy listening on `port`. The bridge serves
+/// until the calling process exits.
+///
+/// Must be called while the process is still single-threaded and in the
+/// host's network namespace.
+pub(crate) fn spawn_proxy_bridge(port: u16) -> io::Result<ProxyBridge> {
+    let (bridge_socket, command_socket) = socket_pair()?;
+    let owner = unsafe { libc::getpid() };
+    // Fork twice so the bridge is not a child of the command, which may
+    // wait for all of its children before it exits.
+    let intermediate = check(unsafe { libc::fork() })?;
+    if intermediate == 0 {
+        drop(command_socket);
+        if unsafe { libc::fork() } == 0 {
+            let _ = run_bridge(owner, bridge_socket, port);
+        }
+        unsafe { libc::_exit(0) };
+    }
+    drop(bridge_socket);
+    wait_for(intermediate);
+    Ok(ProxyBridge {
+        socket: command_socket,
+        port,
+    })
+}
+
+impl ProxyBridge {
+    pub(crate) fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Listens on the proxy's address in the current network namespace and
+    /// hands the socket to the bridge.
+    pub(crate) fn listen(self) -> io::Result<()> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, self.port))?;
+        send_fd(&self.socket, &OwnedFd::from(listener))
+    }
+}
+
+fn run_bridge(owner: libc::pid_t, socket: OwnedFd, port: u16) -> io::Result<()> {
+    // Holding on to the command's output would keep its readers waiting.
+    let dev_null = std::fs::File::open("/dev/null")?;
+    for fd in 0..=2 {
+        check(unsafe { libc::dup2(dev_null.as_raw_fd(), fd) })?;
+    }
+    let owner = pidfd_open(owner)?;
+
+    if !wait_readable(socket.as_fd(), owner.as_fd())? {
+        return Ok(());
+    }
+    let Some(listener) = receive_fd(&socket)? else {
+        return Ok(());
+    };
+    drop(socket);
+    let listener = TcpListener::from(listener);
+    listener.set_nonblocking(true)?;
+    while wait_readable(listener.as_fd(), owner.as_fd())? {
+        if let Ok((client, _)) = listener.accept() {
+            std::thread::spawn(move || relay(client, port));
+        }
+    }
+    Ok(())
+}
+
+/// Waits until `fd` is readable and returns `true`, or returns `false` once
+/// the process behind `owner` exited.
+fn wait_readable(fd: BorrowedFd<'_>, owner: BorrowedFd<'_>) -> io::Result<bool> {
+    let mut fds = [
+        libc::pollfd {
+            fd: fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd: owner.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+    loop {
+        if unsafe { libc::poll(fds.as_mut_ptr(), 2, -1) } == -1 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        return Ok(fds[1].revents == 0 && fds[0].revents != 0);
+    }
+}
+
+fn relay(client: TcpStream, port: u16) {
+    let Ok(proxy) = TcpStream::connect((Ipv4Addr::LOCALHOST, port)) else {
+        return;
+    };
+    let (Ok(client_read), Ok(proxy_read)) = (client.try_clone(), proxy.try_clone()) else {
+        return;
+    };
+    let upstream = std::thread::spawn(move || copy(client_read, proxy));
+    copy(proxy_read, client);
+    let _ = upstream.join();
+}
+
+fn copy(mut from: TcpStream, mut to: TcpStream) {
+    let _ = io::copy(&mut from, &mut to);
+    let _ = to.shutdown(Shutdown::Write);
+}
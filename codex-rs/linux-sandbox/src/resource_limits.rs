@@ -0,0 +1,90 @@
+use std::io;
+
+use codex_core::error::CodexErr;
+use codex_core::error::Result;
+use codex_core::resource_limits::ResourceLimits;
+use codex_core::resource_limits::sandbox_cgroup_dir;
+
+/// Apply `limits` to the current process. Called right before `execvp`, so
+/// the command and every process it starts inherit them.
+pub(crate) fn apply_resource_limits_to_current_process(limits: &ResourceLimits) -> Result<()> {
+    // Exceeding the soft CPU limit delivers SIGXCPU; the hard limit one
+    // second later is the SIGKILL backstop for commands that ignore it.
+    let rlimits = [
+        (
+            libc::RLIMIT_CPU,
+            limits.cpu_time_secs,
+            limits.cpu_time_secs.map(|secs| secs.saturating_add(1)),
+        ),
+        (
+            libc::RLIMIT_AS,
+            limits.address_space_bytes,
+            limits.address_space_bytes,
+        ),
+        (libc::RLIMIT_NOFILE, limits.open_files, limits.open_files),
+        (libc::RLIMIT_NPROC, limits.processes, limits.processes),
+        (
+            libc::RLIMIT_FSIZE,
+            limits.output_file_size_bytes,
+            limits.output_file_size_bytes,
+        ),
+    ];
+    for (resource, soft, hard) in rlimits {
+        let (Some(soft), Some(hard)) = (soft, hard) else {
+            continue;
+        };
+        let mut current = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        if unsafe { libc::getrlimit(resource, &mut current) } != 0 {
+            return Err(CodexErr::Io(io::Error::last_os_error()));
+        }
+        // Only the hard limit we inherited can be lowered, never raised.
+        let hard = to_rlim(hard).min(current.rlim_max);
+        let limit = libc::rlimit {
+            rlim_cur: to_rlim(soft).min(hard),
+            rlim_max: hard,
+        };
+        if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+            return Err(CodexErr::Io(io::Error::last_os_error()));
+        }
+    }
+
+    if let Some(memory_max_bytes) = limits.memory_max_bytes {
+        enter_memory_cgroup(memory_max_bytes).map_err(CodexErr::Io)?;
+    }
+
+    Ok(())
+}
+
+fn to_rlim(value: u64) -> libc::rlim_t {
+    libc::rlim_t::try_from(value).unwrap_or(libc::RLIM_INFINITY)
+}
+
+/// Move the current process into a new cgroup whose `memory.max` is
+/// `memory_max_bytes`. Codex reads the cgroup's `memory.events` and removes
+/// it once the command has exited.
+fn enter_memory_cgroup(memory_max_bytes: u64) -> io::Result<()> {
+    let dir = sandbox_cgroup_dir(std::process::id())?;
+    let with_context = |err: io::Error| {
+        io::Error::new(
+            err.kind(),
+            format!(
+                "failed to set up {} for the memory limit; it requires a cgroup v2 \
+                 hierarchy with the memory controller delegated to the user running Codex: {err}",
+                dir.display()
+            ),
+        )
+    };
+
+    std::fs::create_dir(&dir).map_err(with_context)?;
+    std::fs::write(dir.join("memory.max"), memory_max_bytes.to_string()).map_err(with_context)?;
+    // Keep the command from getting around the limit by swapping. The file
+    // is missing when the kernel has no swap accounting, which is fine.
+    match std::fs::write(dir.join("memory.swap.max"), "0") {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(with_context(err)),
+        _ => {}
+    }
+    std::fs::write(dir.join("cgroup.procs"), "0").map_err(with_context)
+}
@@ -0,0 +1,611 @@
+//! Reports what the sandbox denied a command.
+//!
+//! `codex-linux-sandbox` forks before it sets up the sandbox. The child goes
+//! on to become the command and, right before it execs, installs a seccomp
+//! filter that turns the system calls the sandbox blocks into user
+//! notifications (`SECCOMP_RET_USER_NOTIF`) for the parent. The parent stays
+//! outside the sandbox and answers them: blocked calls fail with `EPERM` as
+//! they would without it. Every blocked call is appended to the report file as
+//! a JSON [`SandboxViolation`] per line. Once the command exits, the parent
+//! exits the same way.
+//!
+//! Blocked calls are rare, so watching them costs next to nothing. Denied
+//! writes are only reported when asked for: the filter then also notifies the
+//! parent of every system call that writes to a path, which lets the write
+//! continue so Landlock or the read-only mounts decide about it, and reports
+//! it if it is outside the writable roots. That is a round trip to the parent
+//! per write.
+//!
+//! Processes the command leaves running in the background lose their
+//! supervisor when it exits; the calls it would have answered then fail with
+//! `ENOSYS`.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::fd::RawFd;
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::process::ExitStatusExt;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+use codex_core::error::Result;
+use codex_core::error::SandboxErr;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::SandboxViolation;
+use codex_core::protocol::WritableRoot;
+use seccompiler::SeccompAction;
+use seccompiler::SeccompCmpArgLen;
+use seccompiler::SeccompCmpOp;
+use seccompiler::SeccompCondition;
+use seccompiler::SeccompRule;
+
+use crate::landlock::compile_seccomp_filter;
+use crate::landlock::install_seccomp_filter_on_current_thread;
+use crate::namespaces::check;
+use crate::namespaces::exit_with_status;
+use crate::namespaces::wait_for;
+
+/// Distinct violations reported per command; the first ones are what the
+/// user needs to see.
+const MAX_VIOLATIONS: usize = 64;
+
+// ioctl requests of a seccomp listener, from <linux/seccomp.h>.
+const SECCOMP_IOCTL_NOTIF_RECV: libc::c_ulong = 0xc050_2100;
+const SECCOMP_IOCTL_NOTIF_SEND: libc::c_ulong = 0xc018_2101;
+const SECCOMP_IOCTL_NOTIF_ID_VALID: libc::c_ulong = 0x4008_2102;
+
+/// seccompiler has no action for user notifications, so the filter is
+/// compiled with this action instead and its return value rewritten.
+const NOTIFY_PLACEHOLDER: SeccompAction = SeccompAction::Trace(0x0c0d);
+
+/// The command's end of the connection to its supervisor.
+pub(crate) struct Supervisor {
+    socket: OwnedFd,
+    watch_writes: bool,
+}
+
+/// Forks the supervisor when the policy restricts anything it reports on.
+/// Returns in the child, which sets up the sandbox and execs the command; the
+/// parent supervises it and never returns. Writes are only watched when
+/// `report_denied_writes` is set.
+///
+/// Must be called while the process is still single-threaded.
+pub(crate) fn spawn_supervisor(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    report: &Path,
+    report_denied_writes: bool,
+) -> io::Result<Option<Supervisor>> {
+    let watch_writes = report_denied_writes && !sandbox_policy.has_full_disk_write_access();
+    if !watch_writes && sandbox_policy.has_full_network_access() {
+        return Ok(None);
+    }
+
+    let report = OpenOptions::new().append(true).create(true).open(report)?;
+    let writable_roots = sandbox_policy
+        .get_writable_roots_with_cwd(cwd)
+        .into_iter()
+        .map(|root| WritableRoot {
+            root: canonicalize(root.root),
+            read_only_subpaths: root
+                .read_only_subpaths
+                .into_iter()
+                .map(canonicalize)
+                .collect(),
+        })
+        .collect();
+
+    let (supervisor_socket, command_socket) = socket_pair()?;
+    let supervisor = unsafe { libc::getpid() };
+    let command = check(unsafe { libc::fork() })?;
+    if command != 0 {
+        drop(command_socket);
+        supervise(
+            command,
+            supervisor_socket,
+            Reporter {
+                report,
+                writable_roots,
+                seen: HashSet::new(),
+            },
+        );
+    }
+    drop(supervisor_socket);
+    drop(report);
+
+    // Killing the supervisor, e.g. on timeout, must take the command with it.
+    check(unsafe { libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL) })?;
+    if unsafe { libc::getppid() } != supervisor {
+        unsafe { libc::_exit(1) };
+    }
+    Ok(Some(Supervisor {
+        socket: command_socket,
+        watch_writes,
+    }))
+}
+
+impl Supervisor {
+    /// Installs a seccomp filter on the current thread that fails the system
+    /// calls matching `rules` and, when asked to report denied writes,
+    /// watches writes, reporting both to the supervisor. Falls back to a
+    /// filter that only fails the calls when the supervisor or the kernel
+    /// cannot handle notifications.
+    pub(crate) fn install_filter(self, rules: BTreeMap<i64, Vec<SeccompRule>>) -> Result<()> {
+        let mut ready = [0u8; 1];
+        let ready =
+            unsafe { libc::read(self.socket.as_raw_fd(), ready.as_mut_ptr().cast(), 1) } == 1;
+
+        // The listener is handed over with `sendmsg`, so blocking it has to
+        // wait until afterwards.
+        let mut notify_rules = rules.clone();
+        let handoff_rules: BTreeMap<_, _> = notify_rules
+            .remove_entry(&libc::SYS_sendmsg)
+            .into_iter()
+            .collect();
+        if self.watch_writes {
+            notify_rules.extend(write_rules()?);
+        }
+
+        let listener = if ready && !notify_rules.is_empty() {
+            install_notify_filter(notify_rules).ok()
+        } else {
+            None
+        };
+        let Some(listener) = listener else {
+            if !rules.is_empty() {
+                install_seccomp_filter_on_current_thread(rules)?;
+            }
+            return Ok(());
+        };
+        send_fd(&self.socket, &listener)?;
+        drop(listener);
+
+        if !handoff_rules.is_empty() {
+            install_seccomp_filter_on_current_thread(handoff_rules)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serves the command's notifications until it exits, then exits the way it
+/// did.
+fn supervise(command: libc::pid_t, socket: OwnedFd, mut reporter: Reporter) -> ! {
+    // Without a way to tell when the command exits, it is not supervised.
+    let supervised = pidfd_open(command).ok().and_then(|pidfd| {
+        let ready = unsafe { libc::write(socket.as_raw_fd(), [1u8].as_ptr().cast(), 1) } == 1;
+        if !ready {
+            return None;
+        }
+        let listener = receive_fd(&socket).ok().flatten()?;
+        Some((listener, pidfd))
+    });
+    drop(socket);
+    if let Some((listener, pidfd)) = supervised {
+        serve(&listener, &pidfd, &mut reporter);
+    }
+    exit_with_status(ExitStatus::from_raw(wait_for(command)));
+}
+
+fn serve(listener: &OwnedFd, command: &OwnedFd, reporter: &mut Reporter) {
+    let write_syscalls = write_syscalls();
+    let mut fds = [
+        libc::pollfd {
+            fd: listener.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        libc::pollfd {
+            fd: command.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+    loop {
+        if unsafe { libc::poll(fds.as_mut_ptr(), 2, -1) } == -1 {
+            if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return;
+        }
+        // The command exited, or nothing uses the filter anymore.
+        if fds[1].revents != 0 || fds[0].revents & libc::POLLIN == 0 {
+            return;
+        }
+        answer_notification(listener, &write_syscalls, reporter);
+    }
+}
+
+fn answer_notification(
+    listener: &OwnedFd,
+    write_syscalls: &[WriteSyscall],
+    reporter: &mut Reporter,
+) {
+    let mut notification: libc::seccomp_notif = unsafe { std::mem::zeroed() };
+    // Fails when the calling thread was killed in the meantime.
+    if unsafe {
+        libc::ioctl(
+            listener.as_raw_fd(),
+            SECCOMP_IOCTL_NOTIF_RECV,
+            &mut notification,
+        )
+    } == -1
+    {
+        return;
+    }
+
+    let nr = i64::from(notification.data.nr);
+    let mut response = libc::seccomp_notif_resp {
+        id: notification.id,
+        val: 0,
+        error: 0,
+        flags: 0,
+    };
+    match write_syscalls.iter().find(|syscall| syscall.nr == nr) {
+        Some(syscall) => {
+            response.flags = libc::SECCOMP_USER_NOTIF_FLAG_CONTINUE as u32;
+            let denied = syscall
+                .paths
+                .iter()
+                .filter_map(|(dirfd, path)| {
+                    let dirfd = dirfd.map(|arg| notification.data.args[arg]);
+                    target_path(notification.pid, dirfd, notification.data.args[*path])
+                })
+                .find(|path| reporter.is_denied_write(path));
+            // The memory the path was read from belongs to the calling
+            // thread only while the notification is still pending.
+            if let Some(path) = denied
+                && notification_is_pending(listener, notification.id)
+            {
+                reporter.record(SandboxViolation::FileWrite {
+                    path,
+                    syscall: syscall.name.to_string(),
+                });
+            }
+        }
+        None => {
+            response.error = -libc::EPERM;
+            reporter.record(SandboxViolation::Syscall {
+                syscall: blocked_syscall_name(nr),
+            });
+        }
+    }
+    unsafe { libc::ioctl(listener.as_raw_fd(), SECCOMP_IOCTL_NOTIF_SEND, &response) };
+}
+
+fn notification_is_pending(listener: &OwnedFd, id: u64) -> bool {
+    unsafe { libc::ioctl(listener.as_raw_fd(), SECCOMP_IOCTL_NOTIF_ID_VALID, &id) == 0 }
+}
+
+struct Reporter {
+    report: File,
+    writable_roots: Vec<WritableRoot>,
+    seen: HashSet<SandboxViolation>,
+}
+
+impl Reporter {
+    fn is_denied_write(&self, path: &Path) -> bool {
+        // Device nodes and kernel interfaces are not something to add to
+        // the writable roots.
+        if path.starts_with("/dev") || path.starts_with("/proc") {
+            return false;
+        }
+        // Writing into a directory that does not exist fails anyway.
+        if !path.parent().is_some_and(Path::is_dir) {
+            return false;
+        }
+        !self
+            .writable_roots
+            .iter()
+            .any(|root| root.is_path_writable(path))
+    }
+
+    fn record(&mut self, violation: SandboxViolation) {
+        if self.seen.len() >= MAX_VIOLATIONS || self.seen.contains(&violation) {
+            return;
+        }
+        if let Ok(mut line) = serde_json::to_string(&violation) {
+            line.push('\n');
+            let _ = self.report.write_all(line.as_bytes());
+        }
+        self.seen.insert(violation);
+    }
+}
+
+/// A system call that writes to the paths it is given.
+struct WriteSyscall {
+    nr: i64,
+    name: &'static str,
+    /// Argument indices of the `(dirfd, path)` pairs it writes to. Paths
+    /// without a directory argument are relative to the working directory.
+    paths: &'static [(Option<usize>, usize)],
+    /// Argument index of the `open` flags, for calls that only write with
+    /// some of them.
+    open_flags: Option<u8>,
+}
+
+fn write_syscalls() -> Vec<WriteSyscall> {
+    let syscall = |nr, name, paths| WriteSyscall {
+        nr,
+        name,
+        paths,
+        open_flags: None,
+    };
+    #[allow(unused_mut)]
+    let mut syscalls = vec![
+        WriteSyscall {
+            open_flags: Some(2),
+            ..syscall(libc::SYS_openat, "openat", &[(Some(0), 1)])
+        },
+        syscall(libc::SYS_mkdirat, "mkdirat", &[(Some(0), 1)]),
+        syscall(libc::SYS_mknodat, "mknodat", &[(Some(0), 1)]),
+        syscall(libc::SYS_unlinkat, "unlinkat", &[(Some(0), 1)]),
+        syscall(
+            libc::SYS_renameat2,
+            "renameat2",
+            &[(Some(0), 1), (Some(2), 3)],
+        ),
+        syscall(libc::SYS_linkat, "linkat", &[(Some(2), 3)]),
+        syscall(libc::SYS_symlinkat, "symlinkat", &[(Some(1), 2)]),
+        syscall(libc::SYS_truncate, "truncate", &[(None, 0)]),
+    ];
+    #[cfg(target_arch = "x86_64")]
+    syscalls.extend([
+        WriteSyscall {
+            open_flags: Some(1),
+            ..syscall(libc::SYS_open, "open", &[(None, 0)])
+        },
+        syscall(libc::SYS_creat, "creat", &[(None, 0)]),
+        syscall(libc::SYS_mkdir, "mkdir", &[(None, 0)]),
+        syscall(libc::SYS_mknod, "mknod", &[(None, 0)]),
+        syscall(libc::SYS_unlink, "unlink", &[(None, 0)]),
+        syscall(libc::SYS_rmdir, "rmdir", &[(None, 0)]),
+        syscall(libc::SYS_rename, "rename", &[(None, 0), (None, 1)]),
+        syscall(
+            libc::SYS_renameat,
+            "renameat",
+            &[(Some(0), 1), (Some(2), 3)],
+        ),
+        syscall(libc::SYS_link, "link", &[(None, 1)]),
+        syscall(libc::SYS_symlink, "symlink", &[(None, 1)]),
+    ]);
+    syscalls
+}
+
+/// Seccomp rules matching [`write_syscalls`]; `open` calls only match when
+/// they can write.
+fn write_rules() -> std::result::Result<BTreeMap<i64, Vec<SeccompRule>>, SandboxErr> {
+    let mut rules = BTreeMap::new();
+    for syscall in write_syscalls() {
+        let conditions = match syscall.open_flags {
+            None => Vec::new(),
+            Some(arg) => [libc::O_WRONLY, libc::O_RDWR, libc::O_CREAT, libc::O_TRUNC]
+                .into_iter()
+                .map(|flag| {
+                    SeccompRule::new(vec![SeccompCondition::new(
+                        arg,
+                        SeccompCmpArgLen::Dword,
+                        SeccompCmpOp::MaskedEq(flag as u64),
+                        flag as u64,
+                    )?])
+                })
+                .collect::<std::result::Result<_, _>>()?,
+        };
+        rules.insert(syscall.nr, conditions);
+    }
+    Ok(rules)
+}
+
+/// Names the system calls the network filter blocks.
+fn blocked_syscall_name(nr: i64) -> String {
+    let name = match nr {
+        libc::SYS_accept => "accept",
+        libc::SYS_accept4 => "accept4",
+        libc::SYS_bind => "bind",
+        libc::SYS_listen => "listen",
+        libc::SYS_ptrace => "ptrace",
+        libc::SYS_connect => "connect",
+        libc::SYS_getpeername => "getpeername",
+        libc::SYS_getsockname => "getsockname",
+        libc::SYS_shutdown => "shutdown",
+        libc::SYS_sendto => "sendto",
+        libc::SYS_sendmsg => "sendmsg",
+        libc::SYS_sendmmsg => "sendmmsg",
+        libc::SYS_recvmsg => "recvmsg",
+        libc::SYS_recvmmsg => "recvmmsg",
+        libc::SYS_getsockopt => "getsockopt",
+        libc::SYS_setsockopt => "setsockopt",
+        libc::SYS_socket => "socket",
+        libc::SYS_socketpair => "socketpair",
+        nr => return format!("syscall {nr}"),
+    };
+    name.to_string()
+}
+
+fn install_notify_filter(rules: BTreeMap<i64, Vec<SeccompRule>>) -> Result<OwnedFd> {
+    let mut program = compile_seccomp_filter(rules, NOTIFY_PLACEHOLDER)?;
+    let placeholder = u32::from(NOTIFY_PLACEHOLDER);
+    for instruction in &mut program {
+        if u32::from(instruction.code) == libc::BPF_RET | libc::BPF_K
+            && instruction.k == placeholder
+        {
+            instruction.k = libc::SECCOMP_RET_USER_NOTIF;
+        }
+    }
+
+    check(unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) })?;
+    let program = libc::sock_fprog {
+        len: program.len() as libc::c_ushort,
+        filter: program.as_mut_ptr().cast(),
+    };
+    let listener = unsafe {
+        libc::syscall(
+            libc::SYS_seccomp,
+            libc::SECCOMP_SET_MODE_FILTER,
+            libc::SECCOMP_FILTER_FLAG_NEW_LISTENER,
+            &program,
+        )
+    };
+    let listener = check(listener as libc::c_int)?;
+    Ok(unsafe { OwnedFd::from_raw_fd(listener) })
+}
+
+/// The absolute path a system call of `pid` refers to with the path at
+/// `address`, relative to the directory `dirfd` (or the working directory).
+fn target_path(pid: u32, dirfd: Option<u64>, address: u64) -> Option<PathBuf> {
+    let path = read_c_string(pid, address)?;
+    let path = if path.is_absolute() {
+        path
+    } else {
+        let dir = match dirfd.map(|fd| fd as i32) {
+            None | Some(libc::AT_FDCWD) => format!("/proc/{pid}/cwd"),
+            Some(fd) => format!("/proc/{pid}/fd/{fd}"),
+        };
+        std::fs::read_link(dir).ok()?.join(path)
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    // Resolve symlinks in the directory, but not in the entry written to.
+    match (normalized.parent(), normalized.file_name()) {
+        (Some(parent), Some(name)) => Some(canonicalize(parent.to_path_buf()).join(name)),
+        _ => Some(normalized),
+    }
+}
+
+/// Reads the NUL-terminated string at `address` in the memory of `pid`.
+fn read_c_string(pid: u32, address: u64) -> Option<PathBuf> {
+    let mut bytes = Vec::new();
+    let mut address = usize::try_from(address).ok()?;
+    while bytes.len() < libc::PATH_MAX as usize {
+        // Reads stop short at the end of a mapping, so a chunk may run past
+        // the end of the string.
+        let mut chunk = [0u8; 256];
+        let local = libc::iovec {
+            iov_base: chunk.as_mut_ptr().cast(),
+            iov_len: chunk.len(),
+        };
+        let remote = libc::iovec {
+            iov_base: address as *mut libc::c_void,
+            iov_len: chunk.len(),
+        };
+        let read = unsafe { libc::process_vm_readv(pid as libc::pid_t, &local, 1, &remote, 1, 0) };
+        let read = usize::try_from(read).ok().filter(|read| *read > 0)?;
+        match chunk[..read].iter().position(|byte| *byte == 0) {
+            Some(end) => {
+                bytes.extend_from_slice(&chunk[..end]);
+                return Some(PathBuf::from(OsString::from_vec(bytes)));
+            }
+            None => bytes.extend_from_slice(&chunk[..read]),
+        }
+        address += read;
+    }
+    None
+}
+
+fn canonicalize(path: PathBuf) -> PathBuf {
+    path.canonicalize().unwrap_or(path)
+}
+
+pub(crate) fn pidfd_open(pid: libc::pid_t) -> io::Result<OwnedFd> {
+    let fd = check(unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) } as libc::c_int)?;
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+pub(crate) fn socket_pair() -> io::Result<(OwnedFd, OwnedFd)> {
+    let mut fds = [0; 2];
+    check(unsafe {
+        libc::socketpair(
+            libc::AF_UNIX,
+            libc::SOCK_SEQPACKET | libc::SOCK_CLOEXEC,
+            0,
+            fds.as_mut_ptr(),
+        )
+    })?;
+    Ok(unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) })
+}
+
+/// Control message buffer with room for one file descriptor, aligned for
+/// `cmsghdr`.
+#[repr(C)]
+struct FdMessage {
+    buffer: [u64; 4],
+}
+
+fn message_header(byte: &mut u8, control: &mut FdMessage) -> (libc::msghdr, libc::iovec) {
+    let iov = libc::iovec {
+        iov_base: (byte as *mut u8).cast(),
+        iov_len: 1,
+    };
+    let mut message: libc::msghdr = unsafe { std::mem::zeroed() };
+    message.msg_control = control.buffer.as_mut_ptr().cast();
+    message.msg_controllen = unsafe { libc::CMSG_SPACE(std::mem::size_of::<RawFd>() as u32) } as _;
+    (message, iov)
+}
+
+pub(crate) fn send_fd(socket: &OwnedFd, fd: &OwnedFd) -> io::Result<()> {
+    let mut byte = 0;
+    let mut control = FdMessage { buffer: [0; 4] };
+    let (mut message, mut iov) = message_header(&mut byte, &mut control);
+    message.msg_iov = &mut iov;
+    message.msg_iovlen = 1;
+    unsafe {
+        let header = libc::CMSG_FIRSTHDR(&message);
+        (*header).cmsg_level = libc::SOL_SOCKET;
+        (*header).cmsg_type = libc::SCM_RIGHTS;
+        (*header).cmsg_len = libc::CMSG_LEN(std::mem::size_of::<RawFd>() as u32) as _;
+        std::ptr::write_unaligned(libc::CMSG_DATA(header).cast::<RawFd>(), fd.as_raw_fd());
+    }
+    check(
+        unsafe { libc::sendmsg(socket.as_raw_fd(), &message, libc::MSG_NOSIGNAL) } as libc::c_int,
+    )?;
+    Ok(())
+}
+
+/// Receives a file descriptor sent with [`send_fd`]; `None` when the other
+/// end closed the connection without sending one.
+pub(crate) fn receive_fd(socket: &OwnedFd) -> io::Result<Option<OwnedFd>> {
+    let mut byte = 0;
+    let mut control = FdMessage { buffer: [0; 4] };
+    let (mut message, mut iov) = message_header(&mut byte, &mut control);
+    message.msg_iov = &mut iov;
+    message.msg_iovlen = 1;
+    let received = loop {
+        let received =
+            unsafe { libc::recvmsg(socket.as_raw_fd(), &mut message, libc::MSG_CMSG_CLOEXEC) };
+        if received != -1 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            break check(received as libc::c_int)?;
+        }
+    };
+    if received == 0 {
+        return Ok(None);
+    }
+    unsafe {
+        let header = libc::CMSG_FIRSTHDR(&message);
+        if header.is_null()
+            || (*header).cmsg_level != libc::SOL_SOCKET
+            || (*header).cmsg_type != libc::SCM_RIGHTS
+        {
+            return Ok(None);
+        }
+        let fd = std::ptr::read_unaligned(libc::CMSG_DATA(header).cast::<RawFd>());
+        Ok(Some(OwnedFd::from_raw_fd(fd)))
+    }
+}
@@ -6,6 +6,8 @@
 //!   2. Sending MCP requests and pairing them with their corresponding
 //!      responses.
 //!   3. Offering a convenience helper for the common `tools/list` request.
+//!   4. Answering `sampling/createMessage` requests from the server through an
+//!      optional [`SamplingHandler`].
 //!
 //! The crate hides all JSON‐RPC framing details behind a typed API. Users
 //! interact with the [`ModelContextProtocolRequest`] trait from `mcp-types` to
@@ -13,7 +15,10 @@
 
 use std::collections::HashMap;
 use std::ffi::OsString;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::OnceLock;
 use std::sync::atomic::AtomicI64;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
@@ -23,19 +28,36 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::CreateMessageRequest;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::GetPromptRequest;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
 use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
 use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourcesRequest;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequest;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ReadResourceRequest;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
@@ -56,9 +78,24 @@ use tracing::warn;
 /// client API and the IO tasks.
 const CHANNEL_CAPACITY: usize = 128;
 
+/// JSON-RPC error codes used when answering server-initiated requests.
+const METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+const INVALID_PARAMS_ERROR_CODE: i64 = -32602;
+const INTERNAL_ERROR_CODE: i64 = -32603;
+
 /// Internal representation of a pending request sender.
 type PendingSender = oneshot::Sender<JSONRPCMessage>;
 
+/// Callback that answers `sampling/createMessage` requests sent by the server.
+/// An `Err` is reported back to the server as a JSON-RPC error.
+pub type SamplingHandler = Arc<
+    dyn Fn(
+            CreateMessageRequestParams,
+        ) -> Pin<Box<dyn Future<Output = Result<CreateMessageResult>> + Send>>
+        + Send
+        + Sync,
+>;
+
 /// A running MCP client instance.
 pub struct McpClient {
     /// Retain this child process until the client is dropped. The Tokio runtime
@@ -76,6 +113,10 @@ pub struct McpClient {
 
     /// Monotonically increasing counter used to generate request IDs.
     id_counter: AtomicI64,
+
+    /// Handler for `sampling/createMessage`, shared with the reader task.
+    /// Without one, sampling requests are rejected as unsupported.
+    sampling_handler: Arc<OnceLock<SamplingHandler>>,
 }
 
 impl McpClient {
@@ -111,6 +152,7 @@ impl McpClient {
 
         let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
         let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+        let sampling_handler: Arc<OnceLock<SamplingHandler>> = Arc::new(OnceLock::new());
 
         // Spawn writer task. It listens on the `outgoing_rx` channel and
         // writes messages to the child's STDIN.
@@ -138,9 +180,12 @@ impl McpClient {
         };
 
         // Spawn reader task. It reads line-delimited JSON from the child's
-        // STDOUT and dispatches responses to the pending map.
+        // STDOUT, dispatches responses to the pending map and answers
+        // requests initiated by the server.
         let reader_handle = {
             let pending = pending.clone();
+            let sampling_handler = sampling_handler.clone();
+            let outgoing_tx = outgoing_tx.clone();
             let mut lines = BufReader::new(stdout).lines();
 
             tokio::spawn(async move {
@@ -157,10 +202,8 @@ impl McpClient {
                             // For now we only log server-initiated notifications.
                             info!("<- notification: {}", line);
                         }
-                        Ok(other) => {
-                            // Batch responses and requests are currently not
-                            // expected from the server – log and ignore.
-                            info!("<- unhandled message: {:?}", other);
+                        Ok(JSONRPCMessage::Request(request)) => {
+                            Self::dispatch_request(request, &sampling_handler, &outgoing_tx);
                         }
                         Err(e) => {
                             error!("failed to deserialize JSONRPCMessage: {e}; line = {}", line)
@@ -181,9 +224,20 @@ impl McpClient {
             outgoing_tx,
             pending,
             id_counter: AtomicI64::new(1),
+            sampling_handler,
         })
     }
 
+    /// Answer `sampling/createMessage` requests from the server with
+    /// `handler`. Must be called before [`initialize`](Self::initialize) so
+    /// no request is missed; later calls are ignored.
+    pub fn with_sampling_handler(self, handler: SamplingHandler) -> Self {
+        if self.sampling_handler.set(handler).is_err() {
+            warn!("sampling handler already set; ignoring");
+        }
+        self
+    }
+
     /// Send an arbitrary MCP request and await the typed result.
     ///
     /// If `timeout` is `None` the call waits indefinitely. If `Some(duration)`
@@ -345,6 +399,48 @@ impl McpClient {
         self.send_request::<CallToolRequest>(params, timeout).await
     }
 
+    /// Convenience wrapper around `resources/list`.
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        self.send_request::<ListResourcesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/read`.
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let params = ReadResourceRequestParams { uri };
+        self.send_request::<ReadResourceRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/list`.
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        self.send_request::<ListPromptsRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/get`.
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let params = GetPromptRequestParams { arguments, name };
+        self.send_request::<GetPromptRequest>(params, timeout).await
+    }
+
     /// Internal helper: route a JSON-RPC *response* object to the pending map.
     async fn dispatch_response(
         resp: JSONRPCResponse,
@@ -372,11 +468,80 @@ impl McpClient {
         }
     }
 
-    /// Internal helper: route a JSON-RPC *error* object to the pending map.
-    async fn dispatch_error(
-        err: mcp_types::JSONRPCError,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
+    /// Internal helper: answer a request initiated by the server. Requests are
+    /// handled on their own task so a slow handler does not stall the reader.
+    fn dispatch_request(
+        request: JSONRPCRequest,
+        sampling_handler: &Arc<OnceLock<SamplingHandler>>,
+        outgoing_tx: &mpsc::Sender<JSONRPCMessage>,
     ) {
+        let JSONRPCRequest {
+            id, method, params, ..
+        } = request;
+        let outgoing_tx = outgoing_tx.clone();
+
+        let handler = match sampling_handler.get() {
+            Some(handler) if method == CreateMessageRequest::METHOD => handler.clone(),
+            _ => {
+                info!("<- unsupported request from server: {method}");
+                tokio::spawn(async move {
+                    let error = JSONRPCErrorError {
+                        code: METHOD_NOT_FOUND_ERROR_CODE,
+                        data: None,
+                        message: format!("method not supported: {method}"),
+                    };
+                    Self::send_reply(&outgoing_tx, id, Err(error)).await;
+                });
+                return;
+            }
+        };
+
+        tokio::spawn(async move {
+            let reply = match serde_json::from_value::<CreateMessageRequestParams>(
+                params.unwrap_or_default(),
+            ) {
+                Ok(params) => handler(params)
+                    .await
+                    .map(serde_json::Value::from)
+                    .map_err(|e| JSONRPCErrorError {
+                        code: INTERNAL_ERROR_CODE,
+                        data: None,
+                        message: format!("{e:#}"),
+                    }),
+                Err(e) => Err(JSONRPCErrorError {
+                    code: INVALID_PARAMS_ERROR_CODE,
+                    data: None,
+                    message: format!("invalid sampling/createMessage params: {e}"),
+                }),
+            };
+            Self::send_reply(&outgoing_tx, id, reply).await;
+        });
+    }
+
+    async fn send_reply(
+        outgoing_tx: &mpsc::Sender<JSONRPCMessage>,
+        id: RequestId,
+        reply: std::result::Result<serde_json::Value, JSONRPCErrorError>,
+    ) {
+        let message = match reply {
+            Ok(result) => JSONRPCMessage::Response(JSONRPCResponse {
+                id,
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                result,
+            }),
+            Err(error) => JSONRPCMessage::Error(JSONRPCError {
+                error,
+                id,
+                jsonrpc: JSONRPC_VERSION.to_string(),
+            }),
+        };
+        if outgoing_tx.send(message).await.is_err() {
+            error!("failed to send reply to writer task - channel closed");
+        }
+    }
+
+    /// Internal helper: route a JSON-RPC *error* object to the pending map.
+    async fn dispatch_error(err: JSONRPCError, pending: &Arc<Mutex<HashMap<i64, PendingSender>>>) {
         let id = match err.id {
             RequestId::Integer(i) => i,
             RequestId::String(_) => return, // see comment above
@@ -472,4 +637,58 @@ mod tests {
         assert!(mcp_server_env.contains_key("PATH"));
         assert_eq!(Some(&env_var_new_value), mcp_server_env.get(env_var));
     }
+
+    #[tokio::test]
+    async fn sampling_requests_are_answered_by_the_handler() {
+        use mcp_types::CreateMessageResultContent;
+        use mcp_types::Role;
+        use mcp_types::TextContent;
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let sampling_handler: Arc<OnceLock<SamplingHandler>> = Arc::new(OnceLock::new());
+        let request = |id: i64, method: &str| JSONRPCRequest {
+            id: RequestId::Integer(id),
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: method.to_string(),
+            params: Some(serde_json::json!({ "messages": [], "maxTokens": 16 })),
+        };
+
+        McpClient::dispatch_request(
+            request(1, CreateMessageRequest::METHOD),
+            &sampling_handler,
+            &outgoing_tx,
+        );
+        let Some(JSONRPCMessage::Error(err)) = outgoing_rx.recv().await else {
+            panic!("expected an error without a handler");
+        };
+        assert_eq!(err.error.code, METHOD_NOT_FOUND_ERROR_CODE);
+
+        let handler: SamplingHandler = Arc::new(|params: CreateMessageRequestParams| {
+            Box::pin(async move {
+                Ok(CreateMessageResult {
+                    content: CreateMessageResultContent::TextContent(TextContent {
+                        annotations: None,
+                        text: format!("max {}", params.max_tokens),
+                        r#type: "text".to_string(),
+                    }),
+                    model: "test-model".to_string(),
+                    role: Role::Assistant,
+                    stop_reason: None,
+                })
+            })
+        });
+        assert!(sampling_handler.set(handler).is_ok());
+
+        McpClient::dispatch_request(
+            request(2, CreateMessageRequest::METHOD),
+            &sampling_handler,
+            &outgoing_tx,
+        );
+        let Some(JSONRPCMessage::Response(response)) = outgoing_rx.recv().await else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.id, RequestId::Integer(2));
+        assert_eq!(response.result["content"]["text"], "max 16");
+        assert_eq!(response.result["model"], "test-model");
+    }
 }
@@ -9,6 +9,7 @@ use crate::exec_approval::handle_exec_approval_request;
 use crate::outgoing_message::OutgoingMessageSender;
 use crate::outgoing_message::OutgoingNotificationMeta;
 use crate::patch_approval::handle_patch_approval_request;
+use crate::session_resources::SessionResources;
 use codex_core::CodexConversation;
 use codex_core::ConversationManager;
 use codex_core::NewConversation;
@@ -19,9 +20,12 @@ use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::InputItem;
+use codex_core::protocol::McpSamplingApprovalRequestEvent;
 use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::Submission;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TurnDiffEvent;
 use codex_protocol::ConversationId;
 use mcp_types::CallToolResult;
 use mcp_types::ContentBlock;
@@ -30,8 +34,6 @@ use mcp_types::TextContent;
 use serde_json::json;
 use tokio::sync::Mutex;
 
-pub(crate) const INVALID_PARAMS_ERROR_CODE: i64 = -32602;
-
 /// Run a complete Codex session and stream events back to the client.
 ///
 /// On completion (success or error) the function sends the appropriate
@@ -43,6 +45,7 @@ pub async fn run_codex_tool_session(
     outgoing: Arc<OutgoingMessageSender>,
     conversation_manager: Arc<ConversationManager>,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, ConversationId>>>,
+    session_resources: Arc<SessionResources>,
 ) {
     let NewConversation {
         conversation_id,
@@ -106,9 +109,11 @@ pub async fn run_codex_tool_session(
 
     run_codex_tool_session_inner(
         conversation,
+        conversation_id,
         outgoing,
         id,
         running_requests_id_to_codex_uuid,
+        session_resources,
     )
     .await;
 }
@@ -120,6 +125,7 @@ pub async fn run_codex_tool_session_reply(
     prompt: String,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, ConversationId>>>,
     conversation_id: ConversationId,
+    session_resources: Arc<SessionResources>,
 ) {
     running_requests_id_to_codex_uuid
         .lock()
@@ -142,18 +148,22 @@ pub async fn run_codex_tool_session_reply(
 
     run_codex_tool_session_inner(
         conversation,
+        conversation_id,
         outgoing,
         request_id,
         running_requests_id_to_codex_uuid,
+        session_resources,
     )
     .await;
 }
 
 async fn run_codex_tool_session_inner(
     codex: Arc<CodexConversation>,
+    conversation_id: ConversationId,
     outgoing: Arc<OutgoingMessageSender>,
     request_id: RequestId,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, ConversationId>>>,
+    session_resources: Arc<SessionResources>,
 ) {
     let request_id_str = match &request_id {
         RequestId::String(s) => s.clone(),
@@ -179,6 +189,7 @@ async fn run_codex_tool_session_inner(
                         call_id,
                         reason: _,
                         parsed_cmd,
+                        suggested_writable_roots: _,
                     }) => {
                         handle_exec_approval_request(
                             command,
@@ -244,6 +255,32 @@ async fn run_codex_tool_session_inner(
                             .remove(&request_id);
                         break;
                     }
+                    EventMsg::McpSamplingApprovalRequest(McpSamplingApprovalRequestEvent {
+                        call_id,
+                        server,
+                        ..
+                    }) => {
+                        // There is no elicitation flow for sampling yet, so
+                        // decline rather than leave the server waiting.
+                        tracing::info!("declining sampling request from MCP server `{server}`");
+                        if let Err(err) = codex
+                            .submit(Op::McpSamplingApproval {
+                                id: call_id,
+                                decision: ReviewDecision::Denied,
+                            })
+                            .await
+                        {
+                            tracing::error!("failed to decline sampling request: {err}");
+                        }
+                    }
+                    EventMsg::TurnDiff(TurnDiffEvent { unified_diff, .. }) => {
+                        session_resources
+                            .record_turn_diff(conversation_id, unified_diff, &outgoing)
+                            .await;
+                    }
+                    EventMsg::ShutdownComplete => {
+                        session_resources.forget_session(&conversation_id).await;
+                    }
                     EventMsg::SessionConfigured(_) => {
                         tracing::error!("unexpected SessionConfigured event");
                     }
@@ -273,7 +310,6 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::StreamError(_)
                     | EventMsg::PatchApplyBegin(_)
                     | EventMsg::PatchApplyEnd(_)
-                    | EventMsg::TurnDiff(_)
                     | EventMsg::WebSearchBegin(_)
                     | EventMsg::WebSearchEnd(_)
                     | EventMsg::GetHistoryEntryResponse(_)
@@ -281,10 +317,12 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::TurnAborted(_)
                     | EventMsg::ConversationPath(_)
                     | EventMsg::UserMessage(_)
-                    | EventMsg::ShutdownComplete
                     | EventMsg::ViewImageToolCall(_)
                     | EventMsg::EnteredReviewMode(_)
-                    | EventMsg::ExitedReviewMode(_) => {
+                    | EventMsg::ExitedReviewMode(_)
+                    | EventMsg::GhostSnapshot(_)
+                    | EventMsg::SessionDiff(_)
+                    | EventMsg::UndoCompleted(_) => {
                         // For now, we do not do anything extra for these
                         // events. Note that
                         // send(codex_event_to_notification(&event)) above has
@@ -0,0 +1,178 @@
+//! Exposes the user's custom prompts (`$CODEX_HOME/prompts/*.md`) as MCP
+//! prompts.
+//!
+//! Prompt files use `$NAME` placeholders (upper-case letters, digits and
+//! underscores) for named arguments, `$1`..`$9` for positional arguments and
+//! `$ARGUMENTS` for all positional arguments; `$$` escapes a literal `$`.
+//! Named placeholders become required MCP prompt arguments. Positional
+//! placeholders are filled from an optional `ARGUMENTS` argument that is split
+//! shell-style. Expansion is shared with the TUI
+//! ([`codex_core::custom_prompts::expand_prompt`]).
+
+use std::collections::HashMap;
+
+use codex_core::custom_prompts::POSITIONAL_ARGUMENTS;
+use codex_core::custom_prompts::default_prompts_dir;
+use codex_core::custom_prompts::discover_prompts_in;
+use codex_core::custom_prompts::expand_prompt;
+use codex_core::custom_prompts::prompt_argument_names;
+use codex_core::custom_prompts::prompt_has_numeric_placeholders;
+use codex_protocol::custom_prompts::CustomPrompt;
+use mcp_types::ContentBlock;
+use mcp_types::GetPromptResult;
+use mcp_types::Prompt;
+use mcp_types::PromptArgument;
+use mcp_types::PromptMessage;
+use mcp_types::Role;
+use mcp_types::TextContent;
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum CustomPromptError {
+    NotFound(String),
+    MissingArguments(Vec<String>),
+}
+
+pub(crate) async fn load_custom_prompts() -> Vec<CustomPrompt> {
+    match default_prompts_dir() {
+        Some(dir) => discover_prompts_in(&dir).await,
+        None => Vec::new(),
+    }
+}
+
+pub(crate) fn to_mcp_prompt(prompt: &CustomPrompt) -> Prompt {
+    let mut arguments: Vec<PromptArgument> = prompt_argument_names(&prompt.content)
+        .into_iter()
+        .map(|name| PromptArgument {
+            description: None,
+            name,
+            required: Some(true),
+            title: None,
+        })
+        .collect();
+    if prompt_has_numeric_placeholders(&prompt.content) {
+        arguments.push(PromptArgument {
+            description: Some(
+                prompt
+                    .argument_hint
+                    .clone()
+                    .unwrap_or_else(|| "Space-separated positional arguments".to_string()),
+            ),
+            name: POSITIONAL_ARGUMENTS.to_string(),
+            required: Some(false),
+            title: None,
+        });
+    }
+
+    Prompt {
+        arguments: (!arguments.is_empty()).then_some(arguments),
+        description: prompt.description.clone(),
+        name: prompt.name.clone(),
+        title: None,
+    }
+}
+
+pub(crate) fn render_prompt(
+    prompts: &[CustomPrompt],
+    name: &str,
+    arguments: &HashMap<String, String>,
+) -> Result<GetPromptResult, CustomPromptError> {
+    let prompt = prompts
+        .iter()
+        .find(|prompt| prompt.name == name)
+        .ok_or_else(|| CustomPromptError::NotFound(name.to_string()))?;
+
+    let positional: Vec<String> = arguments
+        .get(POSITIONAL_ARGUMENTS)
+        .map(|args| shlex::Shlex::new(args).collect())
+        .unwrap_or_default();
+    let text = expand_prompt(&prompt.content, arguments, Some(positional.as_slice()))
+        .map_err(CustomPromptError::MissingArguments)?;
+    Ok(GetPromptResult {
+        description: prompt.description.clone(),
+        messages: vec![PromptMessage {
+            content: ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text,
+                r#type: "text".to_string(),
+            }),
+            role: Role::User,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn prompt(content: &str) -> CustomPrompt {
+        CustomPrompt {
+            name: "review".to_string(),
+            path: "/tmp/review.md".into(),
+            content: content.to_string(),
+            description: Some("Review a file".to_string()),
+            argument_hint: None,
+        }
+    }
+
+    #[test]
+    fn named_and_positional_arguments_are_advertised() {
+        let mcp_prompt = to_mcp_prompt(&prompt("Review $FILE for $FOCUS, then $1 ($$HOME)"));
+        let names: Vec<(String, Option<bool>)> = mcp_prompt
+            .arguments
+            .unwrap_or_default()
+            .into_iter()
+            .map(|arg| (arg.name, arg.required))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("FILE".to_string(), Some(true)),
+                ("FOCUS".to_string(), Some(true)),
+                ("ARGUMENTS".to_string(), Some(false)),
+            ]
+        );
+        assert_eq!(mcp_prompt.description, Some("Review a file".to_string()));
+    }
+
+    #[test]
+    fn render_expands_placeholders() {
+        let prompts = vec![prompt("Review $FILE: $1 / $ARGUMENTS ($$HOME)")];
+        let arguments = HashMap::from([
+            ("FILE".to_string(), "main.rs".to_string()),
+            (
+                "ARGUMENTS".to_string(),
+                "\"error handling\" tests".to_string(),
+            ),
+        ]);
+        let result = render_prompt(&prompts, "review", &arguments).expect("render");
+        let [
+            PromptMessage {
+                content: ContentBlock::TextContent(TextContent { text, .. }),
+                role: Role::User,
+            },
+        ] = result.messages.as_slice()
+        else {
+            panic!("expected a single user text message");
+        };
+        assert_eq!(
+            text,
+            "Review main.rs: error handling / error handling tests ($$HOME)"
+        );
+    }
+
+    #[test]
+    fn render_reports_missing_and_unknown() {
+        let prompts = vec![prompt("Review $FILE")];
+        assert_eq!(
+            render_prompt(&prompts, "review", &HashMap::new()).err(),
+            Some(CustomPromptError::MissingArguments(vec![
+                "FILE".to_string()
+            ]))
+        );
+        assert_eq!(
+            render_prompt(&prompts, "nope", &HashMap::new()).err(),
+            Some(CustomPromptError::NotFound("nope".to_string()))
+        );
+    }
+}
@@ -1,2 +1,5 @@
 pub(crate) const INVALID_REQUEST_ERROR_CODE: i64 = -32600;
+pub(crate) const INVALID_PARAMS_ERROR_CODE: i64 = -32602;
 pub(crate) const INTERNAL_ERROR_CODE: i64 = -32603;
+/// MCP-specific error code for unknown resource URIs.
+pub(crate) const RESOURCE_NOT_FOUND_ERROR_CODE: i64 = -32002;
@@ -5,8 +5,18 @@ use crate::codex_tool_config::CodexToolCallParam;
 use crate::codex_tool_config::CodexToolCallReplyParam;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_param;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_reply_param;
+use crate::custom_prompts::CustomPromptError;
+use crate::custom_prompts::load_custom_prompts;
+use crate::custom_prompts::render_prompt;
+use crate::custom_prompts::to_mcp_prompt;
+use crate::error_code::INTERNAL_ERROR_CODE;
+use crate::error_code::INVALID_PARAMS_ERROR_CODE;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
+use crate::error_code::RESOURCE_NOT_FOUND_ERROR_CODE;
 use crate::outgoing_message::OutgoingMessageSender;
+use crate::session_resources::SessionResourceError;
+use crate::session_resources::SessionResources;
+use crate::session_resources::resource_templates;
 use codex_protocol::ConversationId;
 use codex_protocol::protocol::SessionSource;
 
@@ -25,9 +35,13 @@ use mcp_types::JSONRPCErrorError;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourceTemplatesResult;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolRequest;
 use mcp_types::RequestId;
+use mcp_types::ServerCapabilitiesPrompts;
+use mcp_types::ServerCapabilitiesResources;
 use mcp_types::ServerCapabilitiesTools;
 use mcp_types::ServerNotification;
 use mcp_types::TextContent;
@@ -42,6 +56,7 @@ pub(crate) struct MessageProcessor {
     codex_linux_sandbox_exe: Option<PathBuf>,
     conversation_manager: Arc<ConversationManager>,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, ConversationId>>>,
+    session_resources: Arc<SessionResources>,
 }
 
 impl MessageProcessor {
@@ -56,12 +71,14 @@ impl MessageProcessor {
         let auth_manager = AuthManager::shared(config.codex_home.clone(), false);
         let conversation_manager =
             Arc::new(ConversationManager::new(auth_manager, SessionSource::Mcp));
+        let session_resources = Arc::new(SessionResources::new(config.codex_home.clone()));
         Self {
             outgoing,
             initialized: false,
             codex_linux_sandbox_exe,
             conversation_manager,
             running_requests_id_to_codex_uuid: Arc::new(Mutex::new(HashMap::new())),
+            session_resources,
         }
     }
 
@@ -86,25 +103,26 @@ impl MessageProcessor {
                 self.handle_ping(request_id, params).await;
             }
             McpClientRequest::ListResourcesRequest(params) => {
-                self.handle_list_resources(params);
+                self.handle_list_resources(request_id, params).await;
             }
             McpClientRequest::ListResourceTemplatesRequest(params) => {
-                self.handle_list_resource_templates(params);
+                self.handle_list_resource_templates(request_id, params)
+                    .await;
             }
             McpClientRequest::ReadResourceRequest(params) => {
-                self.handle_read_resource(params);
+                self.handle_read_resource(request_id, params).await;
             }
             McpClientRequest::SubscribeRequest(params) => {
-                self.handle_subscribe(params);
+                self.handle_subscribe(request_id, params).await;
             }
             McpClientRequest::UnsubscribeRequest(params) => {
-                self.handle_unsubscribe(params);
+                self.handle_unsubscribe(request_id, params).await;
             }
             McpClientRequest::ListPromptsRequest(params) => {
-                self.handle_list_prompts(params);
+                self.handle_list_prompts(request_id, params).await;
             }
             McpClientRequest::GetPromptRequest(params) => {
-                self.handle_get_prompt(params);
+                self.handle_get_prompt(request_id, params).await;
             }
             McpClientRequest::ListToolsRequest(params) => {
                 self.handle_list_tools(request_id, params).await;
@@ -204,8 +222,13 @@ impl MessageProcessor {
                 completions: None,
                 experimental: None,
                 logging: None,
-                prompts: None,
-                resources: None,
+                prompts: Some(ServerCapabilitiesPrompts {
+                    list_changed: Some(false),
+                }),
+                resources: Some(ServerCapabilitiesResources {
+                    list_changed: Some(false),
+                    subscribe: Some(true),
+                }),
                 tools: Some(ServerCapabilitiesTools {
                     list_changed: Some(true),
                 }),
@@ -242,54 +265,150 @@ impl MessageProcessor {
             .await;
     }
 
-    fn handle_list_resources(
+    async fn handle_list_resources(
         &self,
+        id: RequestId,
         params: <mcp_types::ListResourcesRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/list -> params: {:?}", params);
+        let cursor = params.and_then(|params| params.cursor);
+        match self.session_resources.list(cursor).await {
+            Ok(result) => {
+                self.send_response::<mcp_types::ListResourcesRequest>(id, result)
+                    .await;
+            }
+            Err(err) => self.send_session_resource_error(id, err).await,
+        }
     }
 
-    fn handle_list_resource_templates(
+    async fn handle_list_resource_templates(
         &self,
+        id: RequestId,
         params:
             <mcp_types::ListResourceTemplatesRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/templates/list -> params: {:?}", params);
+        let result = ListResourceTemplatesResult {
+            next_cursor: None,
+            resource_templates: resource_templates(),
+        };
+        self.send_response::<mcp_types::ListResourceTemplatesRequest>(id, result)
+            .await;
     }
 
-    fn handle_read_resource(
+    async fn handle_read_resource(
         &self,
+        id: RequestId,
         params: <mcp_types::ReadResourceRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/read -> params: {:?}", params);
+        match self.session_resources.read(&params.uri).await {
+            Ok(result) => {
+                self.send_response::<mcp_types::ReadResourceRequest>(id, result)
+                    .await;
+            }
+            Err(err) => self.send_session_resource_error(id, err).await,
+        }
     }
 
-    fn handle_subscribe(
+    async fn handle_subscribe(
         &self,
+        id: RequestId,
         params: <mcp_types::SubscribeRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/subscribe -> params: {:?}", params);
+        match self.session_resources.subscribe(&params.uri).await {
+            Ok(()) => {
+                self.send_response::<mcp_types::SubscribeRequest>(id, json!({}))
+                    .await;
+            }
+            Err(err) => self.send_session_resource_error(id, err).await,
+        }
     }
 
-    fn handle_unsubscribe(
+    async fn handle_unsubscribe(
         &self,
+        id: RequestId,
         params: <mcp_types::UnsubscribeRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/unsubscribe -> params: {:?}", params);
+        self.session_resources.unsubscribe(&params.uri).await;
+        self.send_response::<mcp_types::UnsubscribeRequest>(id, json!({}))
+            .await;
+    }
+
+    async fn send_session_resource_error(&self, id: RequestId, err: SessionResourceError) {
+        let (code, message) = match err {
+            SessionResourceError::InvalidParams(message) => (INVALID_PARAMS_ERROR_CODE, message),
+            SessionResourceError::NotFound(message) => (RESOURCE_NOT_FOUND_ERROR_CODE, message),
+            SessionResourceError::Internal(message) => (INTERNAL_ERROR_CODE, message),
+        };
+        let error = JSONRPCErrorError {
+            code,
+            message,
+            data: None,
+        };
+        self.outgoing.send_error(id, error).await;
     }
 
-    fn handle_list_prompts(
+    async fn handle_list_prompts(
         &self,
+        id: RequestId,
         params: <mcp_types::ListPromptsRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("prompts/list -> params: {:?}", params);
+        let prompts = load_custom_prompts().await;
+        let result = ListPromptsResult {
+            next_cursor: None,
+            prompts: prompts.iter().map(to_mcp_prompt).collect(),
+        };
+        self.send_response::<mcp_types::ListPromptsRequest>(id, result)
+            .await;
     }
 
-    fn handle_get_prompt(
+    async fn handle_get_prompt(
         &self,
+        id: RequestId,
         params: <mcp_types::GetPromptRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("prompts/get -> params: {:?}", params);
+        let arguments: HashMap<String, String> = match params.arguments {
+            Some(arguments) => match serde_json::from_value(arguments) {
+                Ok(arguments) => arguments,
+                Err(e) => {
+                    let error = JSONRPCErrorError {
+                        code: INVALID_PARAMS_ERROR_CODE,
+                        message: format!("prompt arguments must be strings: {e}"),
+                        data: None,
+                    };
+                    self.outgoing.send_error(id, error).await;
+                    return;
+                }
+            },
+            None => HashMap::new(),
+        };
+
+        let prompts = load_custom_prompts().await;
+        match render_prompt(&prompts, &params.name, &arguments) {
+            Ok(result) => {
+                self.send_response::<mcp_types::GetPromptRequest>(id, result)
+                    .await;
+            }
+            Err(err) => {
+                let message = match err {
+                    CustomPromptError::NotFound(name) => format!("unknown prompt: {name}"),
+                    CustomPromptError::MissingArguments(missing) => {
+                        format!("missing required arguments: {}", missing.join(", "))
+                    }
+                };
+                let error = JSONRPCErrorError {
+                    code: INVALID_PARAMS_ERROR_CODE,
+                    message,
+                    data: None,
+                };
+                self.outgoing.send_error(id, error).await;
+            }
+        }
     }
 
     async fn handle_list_tools(
@@ -401,6 +520,7 @@ impl MessageProcessor {
         let outgoing = self.outgoing.clone();
         let conversation_manager = self.conversation_manager.clone();
         let running_requests_id_to_codex_uuid = self.running_requests_id_to_codex_uuid.clone();
+        let session_resources = self.session_resources.clone();
 
         // Spawn an async task to handle the Codex session so that we do not
         // block the synchronous message-processing loop.
@@ -413,6 +533,7 @@ impl MessageProcessor {
                 outgoing,
                 conversation_manager,
                 running_requests_id_to_codex_uuid,
+                session_resources,
             )
             .await;
         });
@@ -516,6 +637,7 @@ impl MessageProcessor {
             let outgoing = outgoing.clone();
             let prompt = prompt.clone();
             let running_requests_id_to_codex_uuid = running_requests_id_to_codex_uuid.clone();
+            let session_resources = self.session_resources.clone();
 
             async move {
                 crate::codex_tool_runner::run_codex_tool_session_reply(
@@ -525,6 +647,7 @@ impl MessageProcessor {
                     prompt,
                     running_requests_id_to_codex_uuid,
                     conversation_id,
+                    session_resources,
                 )
                 .await;
             }
@@ -0,0 +1,411 @@
+//! MCP resources backed by Codex sessions.
+//!
+//! Two kinds of resources are exposed:
+//!
+//! - `codex://sessions/<id>`: the recorded rollout (JSONL) of a session, read
+//!   from `$CODEX_HOME/sessions`.
+//! - `codex://sessions/<id>/turn-diff`: the unified diff of the current turn
+//!   for a session running in this server. Clients may subscribe to it and
+//!   receive `notifications/resources/updated` whenever the diff changes.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use codex_core::Cursor as RolloutCursor;
+use codex_core::RolloutRecorder;
+use codex_core::SessionMeta;
+use codex_core::find_conversation_path_by_id_str;
+use codex_protocol::ConversationId;
+use mcp_types::ListResourcesResult;
+use mcp_types::ModelContextProtocolNotification;
+use mcp_types::ReadResourceResult;
+use mcp_types::ReadResourceResultContents;
+use mcp_types::Resource;
+use mcp_types::ResourceTemplate;
+use mcp_types::ResourceUpdatedNotification;
+use mcp_types::ResourceUpdatedNotificationParams;
+use mcp_types::TextResourceContents;
+use tokio::sync::Mutex;
+
+use crate::outgoing_message::OutgoingMessageSender;
+use crate::outgoing_message::OutgoingNotification;
+
+const SESSION_URI_PREFIX: &str = "codex://sessions/";
+const TURN_DIFF_URI_SUFFIX: &str = "/turn-diff";
+const ROLLOUT_MIME_TYPE: &str = "application/x-ndjson";
+const DIFF_MIME_TYPE: &str = "text/x-diff";
+const ROLLOUTS_PAGE_SIZE: usize = 25;
+/// Turn diffs kept for running sessions. When more sessions than this have
+/// reported a diff, the one updated least recently is dropped.
+const MAX_TURN_DIFFS: usize = 32;
+
+/// A resource URI understood by this server.
+#[derive(Debug, PartialEq)]
+pub(crate) enum SessionResourceUri {
+    Rollout(String),
+    TurnDiff(ConversationId),
+}
+
+impl SessionResourceUri {
+    pub(crate) fn parse(uri: &str) -> Option<Self> {
+        let rest = uri.strip_prefix(SESSION_URI_PREFIX)?;
+        if let Some(id) = rest.strip_suffix(TURN_DIFF_URI_SUFFIX) {
+            return ConversationId::from_string(id).ok().map(Self::TurnDiff);
+        }
+        if rest.is_empty() || rest.contains('/') {
+            return None;
+        }
+        Some(Self::Rollout(rest.to_string()))
+    }
+}
+
+pub(crate) fn rollout_uri(id: &str) -> String {
+    format!("{SESSION_URI_PREFIX}{id}")
+}
+
+pub(crate) fn turn_diff_uri(conversation_id: &ConversationId) -> String {
+    format!("{SESSION_URI_PREFIX}{conversation_id}{TURN_DIFF_URI_SUFFIX}")
+}
+
+pub(crate) fn resource_templates() -> Vec<ResourceTemplate> {
+    vec![
+        ResourceTemplate {
+            annotations: None,
+            description: Some("Recorded rollout of a Codex session".to_string()),
+            mime_type: Some(ROLLOUT_MIME_TYPE.to_string()),
+            name: "session".to_string(),
+            title: None,
+            uri_template: format!("{SESSION_URI_PREFIX}{{id}}"),
+        },
+        ResourceTemplate {
+            annotations: None,
+            description: Some("Unified diff of the current turn of a running session".to_string()),
+            mime_type: Some(DIFF_MIME_TYPE.to_string()),
+            name: "turn-diff".to_string(),
+            title: None,
+            uri_template: format!("{SESSION_URI_PREFIX}{{id}}{TURN_DIFF_URI_SUFFIX}"),
+        },
+    ]
+}
+
+/// Errors surfaced to the client as JSON-RPC errors.
+#[derive(Debug)]
+pub(crate) enum SessionResourceError {
+    InvalidParams(String),
+    NotFound(String),
+    Internal(String),
+}
+
+/// Shared state for session resources: the latest turn diff of each running
+/// session and the set of URIs clients have subscribed to.
+pub(crate) struct SessionResources {
+    codex_home: PathBuf,
+    turn_diffs: Mutex<TurnDiffs>,
+    subscriptions: Mutex<HashSet<String>>,
+}
+
+/// Latest diff per session, each tagged with when it was last updated so the
+/// stalest can be evicted.
+#[derive(Default)]
+struct TurnDiffs {
+    diffs: HashMap<ConversationId, (u64, String)>,
+    updates: u64,
+}
+
+impl TurnDiffs {
+    fn insert(&mut self, conversation_id: ConversationId, diff: String) {
+        self.updates += 1;
+        self.diffs.insert(conversation_id, (self.updates, diff));
+        if self.diffs.len() > MAX_TURN_DIFFS
+            && let Some(stalest) = self
+                .diffs
+                .iter()
+                .min_by_key(|(_, (updated, _))| *updated)
+                .map(|(conversation_id, _)| *conversation_id)
+        {
+            self.diffs.remove(&stalest);
+        }
+    }
+}
+
+impl SessionResources {
+    pub(crate) fn new(codex_home: PathBuf) -> Self {
+        Self {
+            codex_home,
+            turn_diffs: Mutex::new(TurnDiffs::default()),
+            subscriptions: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Lists one page of recorded rollouts. The turn diffs of running
+    /// sessions are listed on the first page only.
+    pub(crate) async fn list(
+        &self,
+        cursor: Option<String>,
+    ) -> Result<ListResourcesResult, SessionResourceError> {
+        let rollout_cursor = match &cursor {
+            Some(cursor) => Some(
+                serde_json::from_value::<RolloutCursor>(serde_json::Value::String(cursor.clone()))
+                    .map_err(|_| {
+                        SessionResourceError::InvalidParams(format!("invalid cursor: {cursor}"))
+                    })?,
+            ),
+            None => None,
+        };
+
+        let mut resources = Vec::new();
+        if cursor.is_none() {
+            let mut diffs: Vec<ConversationId> =
+                self.turn_diffs.lock().await.diffs.keys().copied().collect();
+            diffs.sort_by_key(ToString::to_string);
+            resources.extend(diffs.iter().map(|conversation_id| Resource {
+                annotations: None,
+                description: Some("Unified diff of the current turn".to_string()),
+                mime_type: Some(DIFF_MIME_TYPE.to_string()),
+                name: format!("turn-diff-{conversation_id}"),
+                size: None,
+                title: Some(format!("Turn diff for session {conversation_id}")),
+                uri: turn_diff_uri(conversation_id),
+            }));
+        }
+
+        let page = RolloutRecorder::list_conversations(
+            &self.codex_home,
+            ROLLOUTS_PAGE_SIZE,
+            rollout_cursor.as_ref(),
+            &[],
+        )
+        .await
+        .map_err(|e| SessionResourceError::Internal(format!("failed to list sessions: {e}")))?;
+
+        resources.extend(page.items.into_iter().filter_map(|item| {
+            let meta = serde_json::from_value::<SessionMeta>(item.head.first()?.clone()).ok()?;
+            let id = meta.id.to_string();
+            Some(Resource {
+                annotations: None,
+                description: Some(format!("Codex session in {}", meta.cwd.display())),
+                mime_type: Some(ROLLOUT_MIME_TYPE.to_string()),
+                name: format!("session-{id}"),
+                size: None,
+                title: item.created_at.map(|ts| format!("Session started {ts}")),
+                uri: rollout_uri(&id),
+            })
+        }));
+
+        let next_cursor = match page.next_cursor {
+            Some(cursor) => match serde_json::to_value(&cursor) {
+                Ok(serde_json::Value::String(s)) => Some(s),
+                _ => None,
+            },
+            None => None,
+        };
+
+        Ok(ListResourcesResult {
+            next_cursor,
+            resources,
+        })
+    }
+
+    pub(crate) async fn read(&self, uri: &str) -> Result<ReadResourceResult, SessionResourceError> {
+        let not_found = || SessionResourceError::NotFound(format!("resource not found: {uri}"));
+        let (text, mime_type) = match SessionResourceUri::parse(uri).ok_or_else(not_found)? {
+            SessionResourceUri::Rollout(id) => {
+                let path = find_conversation_path_by_id_str(&self.codex_home, &id)
+                    .await
+                    .map_err(|e| {
+                        SessionResourceError::Internal(format!("failed to locate session: {e}"))
+                    })?
+                    .ok_or_else(not_found)?;
+                let text = tokio::fs::read_to_string(&path).await.map_err(|e| {
+                    SessionResourceError::Internal(format!(
+                        "failed to read {}: {e}",
+                        path.display()
+                    ))
+                })?;
+                (text, ROLLOUT_MIME_TYPE)
+            }
+            SessionResourceUri::TurnDiff(conversation_id) => {
+                let diff = self
+                    .turn_diffs
+                    .lock()
+                    .await
+                    .diffs
+                    .get(&conversation_id)
+                    .map(|(_, diff)| diff.clone())
+                    .ok_or_else(not_found)?;
+                (diff, DIFF_MIME_TYPE)
+            }
+        };
+
+        Ok(ReadResourceResult {
+            contents: vec![ReadResourceResultContents::TextResourceContents(
+                TextResourceContents {
+                    mime_type: Some(mime_type.to_string()),
+                    text,
+                    uri: uri.to_string(),
+                },
+            )],
+        })
+    }
+
+    /// Only turn diffs change while the server runs, so they are the only
+    /// resources that can be subscribed to.
+    pub(crate) async fn subscribe(&self, uri: &str) -> Result<(), SessionResourceError> {
+        match SessionResourceUri::parse(uri) {
+            Some(SessionResourceUri::TurnDiff(_)) => {
+                self.subscriptions.lock().await.insert(uri.to_string());
+                Ok(())
+            }
+            _ => Err(SessionResourceError::NotFound(format!(
+                "resource does not support subscriptions: {uri}"
+            ))),
+        }
+    }
+
+    pub(crate) async fn unsubscribe(&self, uri: &str) {
+        self.subscriptions.lock().await.remove(uri);
+    }
+
+    /// Drops the turn diff of a session that has shut down.
+    pub(crate) async fn forget_session(&self, conversation_id: &ConversationId) {
+        self.turn_diffs.lock().await.diffs.remove(conversation_id);
+    }
+
+    /// Stores the latest diff for `conversation_id` and notifies subscribers.
+    pub(crate) async fn record_turn_diff(
+        &self,
+        conversation_id: ConversationId,
+        unified_diff: String,
+        outgoing: &OutgoingMessageSender,
+    ) {
+        self.turn_diffs
+            .lock()
+            .await
+            .insert(conversation_id, unified_diff);
+
+        let uri = turn_diff_uri(&conversation_id);
+        if !self.subscriptions.lock().await.contains(&uri) {
+            return;
+        }
+        let params = match serde_json::to_value(ResourceUpdatedNotificationParams { uri }) {
+            Ok(params) => params,
+            Err(e) => {
+                tracing::warn!("failed to serialize resource update: {e}");
+                return;
+            }
+        };
+        outgoing
+            .send_notification(OutgoingNotification {
+                method: ResourceUpdatedNotification::METHOD.to_string(),
+                params: Some(params),
+            })
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outgoing_message::OutgoingMessage;
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn parses_session_uris() {
+        let conversation_id = ConversationId::new();
+        assert_eq!(
+            SessionResourceUri::parse(&rollout_uri("abc")),
+            Some(SessionResourceUri::Rollout("abc".to_string()))
+        );
+        assert_eq!(
+            SessionResourceUri::parse(&turn_diff_uri(&conversation_id)),
+            Some(SessionResourceUri::TurnDiff(conversation_id))
+        );
+        assert_eq!(SessionResourceUri::parse("codex://sessions/"), None);
+        assert_eq!(SessionResourceUri::parse("codex://sessions/a/b"), None);
+        assert_eq!(SessionResourceUri::parse("file:///tmp/x"), None);
+    }
+
+    #[tokio::test]
+    async fn turn_diff_updates_notify_subscribers() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let resources = SessionResources::new(codex_home.path().to_path_buf());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let outgoing = OutgoingMessageSender::new(tx);
+        let conversation_id = ConversationId::new();
+        let uri = turn_diff_uri(&conversation_id);
+
+        assert!(resources.subscribe(&rollout_uri("abc")).await.is_err());
+        resources.subscribe(&uri).await.expect("subscribe");
+        resources
+            .record_turn_diff(conversation_id, "diff --git a/x b/x".to_string(), &outgoing)
+            .await;
+
+        let Some(OutgoingMessage::Notification(notification)) = rx.recv().await else {
+            panic!("expected a notification");
+        };
+        assert_eq!(notification.method, "notifications/resources/updated");
+        assert_eq!(notification.params, Some(serde_json::json!({ "uri": uri })));
+
+        let read = resources.read(&uri).await.expect("read");
+        assert_eq!(
+            read.contents,
+            vec![ReadResourceResultContents::TextResourceContents(
+                TextResourceContents {
+                    mime_type: Some(DIFF_MIME_TYPE.to_string()),
+                    text: "diff --git a/x b/x".to_string(),
+                    uri: uri.clone(),
+                }
+            )]
+        );
+
+        let listed = resources.list(None).await.expect("list");
+        assert_eq!(
+            listed
+                .resources
+                .iter()
+                .map(|r| r.uri.clone())
+                .collect::<Vec<_>>(),
+            vec![uri.clone()]
+        );
+
+        resources.unsubscribe(&uri).await;
+        resources
+            .record_turn_diff(conversation_id, String::new(), &outgoing)
+            .await;
+        assert!(rx.try_recv().is_err());
+
+        resources.forget_session(&conversation_id).await;
+        assert!(resources.read(&uri).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn invalid_cursor_is_invalid_params() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let resources = SessionResources::new(codex_home.path().to_path_buf());
+        assert!(matches!(
+            resources.list(Some("not a cursor".to_string())).await,
+            Err(SessionResourceError::InvalidParams(_))
+        ));
+    }
+
+    #[test]
+    fn turn_diffs_evict_the_stalest_session() {
+        let mut turn_diffs = TurnDiffs::default();
+        let first = ConversationId::new();
+        turn_diffs.insert(first, "first".to_string());
+        let ids: Vec<ConversationId> = (0..MAX_TURN_DIFFS).map(|_| ConversationId::new()).collect();
+        for id in &ids {
+            turn_diffs.insert(*id, String::new());
+        }
+        assert_eq!(turn_diffs.diffs.len(), MAX_TURN_DIFFS);
+        assert!(!turn_diffs.diffs.contains_key(&first));
+
+        // Updating a session keeps it around.
+        turn_diffs.insert(ids[0], "updated".to_string());
+        turn_diffs.insert(first, "first".to_string());
+        assert!(turn_diffs.diffs.contains_key(&ids[0]));
+        assert!(!turn_diffs.diffs.contains_key(&ids[1]));
+    }
+}
@@ -15,6 +15,7 @@ use codex_mcp_server::CodexToolCallParam;
 
 use mcp_types::CallToolRequestParams;
 use mcp_types::ClientCapabilities;
+use mcp_types::GetPromptRequestParams;
 use mcp_types::Implementation;
 use mcp_types::InitializeRequestParams;
 use mcp_types::JSONRPC_VERSION;
@@ -24,6 +25,7 @@ use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ReadResourceRequestParams;
 use mcp_types::RequestId;
 use pretty_assertions::assert_eq;
 use serde_json::json;
@@ -156,6 +158,13 @@ impl McpProcess {
                 id: RequestId::Integer(request_id),
                 result: json!({
                     "capabilities": {
+                        "prompts": {
+                            "listChanged": false
+                        },
+                        "resources": {
+                            "listChanged": false,
+                            "subscribe": true
+                        },
                         "tools": {
                             "listChanged": true
                         },
@@ -200,6 +209,33 @@ impl McpProcess {
         .await
     }
 
+    pub async fn send_list_prompts_request(&mut self) -> anyhow::Result<i64> {
+        self.send_request(mcp_types::ListPromptsRequest::METHOD, None)
+            .await
+    }
+
+    pub async fn send_get_prompt_request(
+        &mut self,
+        params: GetPromptRequestParams,
+    ) -> anyhow::Result<i64> {
+        self.send_request(
+            mcp_types::GetPromptRequest::METHOD,
+            Some(serde_json::to_value(params)?),
+        )
+        .await
+    }
+
+    pub async fn send_read_resource_request(
+        &mut self,
+        params: ReadResourceRequestParams,
+    ) -> anyhow::Result<i64> {
+        self.send_request(
+            mcp_types::ReadResourceRequest::METHOD,
+            Some(serde_json::to_value(params)?),
+        )
+        .await
+    }
+
     async fn send_request(
         &mut self,
         method: &str,
@@ -1 +1,2 @@
 mod codex_tool;
+mod resources;
@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use mcp_types::GetPromptRequestParams;
+use mcp_types::JSONRPCResponse;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::RequestId;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::time::timeout;
+
+use mcp_test_support::McpProcess;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+const SESSION_ID: &str = "3f941c35-29b3-493b-b0a4-e25800d9aeb0";
+
+#[tokio::test]
+async fn custom_prompts_are_exposed_as_mcp_prompts() -> anyhow::Result<()> {
+    let codex_home = TempDir::new()?;
+    let prompts_dir = codex_home.path().join("prompts");
+    std::fs::create_dir_all(&prompts_dir)?;
+    std::fs::write(
+        prompts_dir.join("review.md"),
+        "---\ndescription: Review a file\n---\nReview $FILE carefully.",
+    )?;
+
+    let mut mcp = McpProcess::new(codex_home.path()).await?;
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize()).await??;
+
+    let list_id = mcp.send_list_prompts_request().await?;
+    let JSONRPCResponse { result, .. } = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(list_id)),
+    )
+    .await??;
+    assert_eq!(
+        result,
+        json!({
+            "prompts": [{
+                "name": "review",
+                "description": "Review a file",
+                "arguments": [{ "name": "FILE", "required": true }],
+            }]
+        })
+    );
+
+    let get_id = mcp
+        .send_get_prompt_request(GetPromptRequestParams {
+            name: "review".to_string(),
+            arguments: Some(json!({ "FILE": "main.rs" })),
+        })
+        .await?;
+    let JSONRPCResponse { result, .. } = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(get_id)),
+    )
+    .await??;
+    assert_eq!(
+        result,
+        json!({
+            "description": "Review a file",
+            "messages": [{
+                "role": "user",
+                "content": { "type": "text", "text": "Review main.rs carefully." },
+            }],
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn recorded_rollouts_are_readable_resources() -> anyhow::Result<()> {
+    let codex_home = TempDir::new()?;
+    let contents = write_rollout(codex_home.path())?;
+
+    let mut mcp = McpProcess::new(codex_home.path()).await?;
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize()).await??;
+
+    let uri = format!("codex://sessions/{SESSION_ID}");
+    let read_id = mcp
+        .send_read_resource_request(ReadResourceRequestParams { uri: uri.clone() })
+        .await?;
+    let JSONRPCResponse { result, .. } = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(read_id)),
+    )
+    .await??;
+    assert_eq!(
+        result,
+        json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": "application/x-ndjson",
+                "text": contents,
+            }]
+        })
+    );
+
+    Ok(())
+}
+
+fn write_rollout(codex_home: &Path) -> std::io::Result<String> {
+    let dir = codex_home
+        .join("sessions")
+        .join("2025")
+        .join("01")
+        .join("02");
+    std::fs::create_dir_all(&dir)?;
+    let meta = json!({
+        "timestamp": "2025-01-02T03:04:05Z",
+        "type": "session_meta",
+        "payload": {
+            "id": SESSION_ID,
+            "timestamp": "2025-01-02T03:04:05Z",
+            "cwd": "/",
+            "originator": "codex",
+            "cli_version": "0.0.0",
+            "instructions": null,
+        }
+    });
+    let contents = format!("{meta}\n");
+    std::fs::write(
+        dir.join(format!("rollout-2025-01-02T03-04-05-{SESSION_ID}.jsonl")),
+        &contents,
+    )?;
+    Ok(contents)
+}
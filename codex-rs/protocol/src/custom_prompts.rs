@@ -9,6 +9,10 @@ use ts_rs::TS;
 /// - Full slash prefix: `"/{PROMPTS_CMD_PREFIX}:"`
 pub const PROMPTS_CMD_PREFIX: &str = "prompts";
 
+/// Namespace for prompts provided by MCP servers. The slash command for
+/// prompt `name` on server `server` is `"/{MCP_PROMPTS_CMD_PREFIX}:server:name"`.
+pub const MCP_PROMPTS_CMD_PREFIX: &str = "mcp";
+
 #[derive(Serialize, Deserialize, Debug, Clone, TS)]
 pub struct CustomPrompt {
     pub name: String,
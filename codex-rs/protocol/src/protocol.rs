@@ -21,6 +21,8 @@ use crate::num_format::format_with_separators;
 use crate::parse_command::ParsedCommand;
 use crate::plan_tool::UpdatePlanArgs;
 use mcp_types::CallToolResult;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::Prompt as McpPrompt;
 use mcp_types::Tool as McpTool;
 use serde::Deserialize;
 use serde::Serialize;
@@ -143,6 +145,14 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Approve a sampling request from an MCP server
+    McpSamplingApproval {
+        /// The `call_id` of the `McpSamplingApprovalRequest` we are approving
+        id: String,
+        /// The user's decision in response to the request.
+        decision: ReviewDecision,
+    },
+
     /// Append an entry to the persistent cross-session message history.
     ///
     /// Note the entry is not guaranteed to be logged if the user has
@@ -166,6 +176,14 @@ pub enum Op {
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
+    /// Fetch a prompt from an MCP server (`prompts/get`) and submit the
+    /// resulting messages as user input, as if the user had typed them.
+    RunMcpPrompt {
+        server: String,
+        name: String,
+        arguments: HashMap<String, String>,
+    },
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
@@ -174,6 +192,25 @@ pub enum Op {
     /// Request a code review from the agent.
     Review { review_request: ReviewRequest },
 
+    /// Restore the workspace to the snapshot taken before the last `turns`
+    /// turns started. The server replies with [`EventMsg::UndoCompleted`].
+    Undo { turns: usize },
+
+    /// Restore the workspace to the snapshot taken before `turn` started, as
+    /// reported by [`EventMsg::GhostSnapshot`]. The server replies with
+    /// [`EventMsg::UndoCompleted`].
+    RestoreToTurn { turn: u64 },
+
+    /// Request the diffs of the turns of this session that changed files,
+    /// starting at `since_turn` (all turns when omitted). Turns are numbered
+    /// like [`EventMsg::GhostSnapshot`]; only the most recent turns are kept
+    /// and undone turns are dropped. The server replies with
+    /// [`EventMsg::SessionDiff`].
+    GetSessionDiff {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since_turn: Option<u64>,
+    },
+
     /// Request to shut down codex instance.
     Shutdown,
 }
@@ -215,9 +252,15 @@ pub enum SandboxPolicy {
     #[serde(rename = "danger-full-access")]
     DangerFullAccess,
 
-    /// Read-only access to the entire file-system.
+    /// Read-only access to the entire file-system, except for the
+    /// read-denied paths (see [`SandboxPolicy::get_read_denied_paths_with_cwd`]).
     #[serde(rename = "read-only")]
-    ReadOnly,
+    ReadOnly {
+        /// Additional paths (beyond the default credential directories) whose
+        /// contents cannot be read from within the sandbox.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read: Vec<PathBuf>,
+    },
 
     /// Same as `ReadOnly` but additionally grants write access to the current
     /// working directory ("workspace").
@@ -228,11 +271,23 @@ pub enum SandboxPolicy {
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         writable_roots: Vec<PathBuf>,
 
+        /// Additional paths (beyond the default credential directories) whose
+        /// contents cannot be read from within the sandbox.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read: Vec<PathBuf>,
+
         /// When set to `true`, outbound network access is allowed. `false` by
         /// default.
         #[serde(default)]
         network_access: bool,
 
+        /// Hosts that commands may still reach when `network_access` is
+        /// `false`. Connections go through a local filtering proxy; everything
+        /// else stays blocked.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        #[ts(type = "Array<string>")]
+        network_allowlist: Vec<NetworkAllowlistEntry>,
+
         /// When set to `true`, will NOT include the per-user `TMPDIR`
         /// environment variable among the default writable roots. Defaults to
         /// `false`.
@@ -246,6 +301,97 @@ pub enum SandboxPolicy {
     },
 }
 
+/// A host, optionally limited to one port, that sandboxed commands may reach
+/// through the network proxy. Written `host`, `host:port`, `*.domain` (any
+/// subdomain of `domain`) or `[ipv6]:port`; without a port every port is
+/// allowed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct NetworkAllowlistEntry {
+    /// Lowercase host name or IP address, possibly starting with `*.`.
+    host: String,
+    port: Option<u16>,
+}
+
+impl NetworkAllowlistEntry {
+    pub fn allows(&self, host: &str, port: u16) -> bool {
+        if self.port.is_some_and(|allowed| allowed != port) {
+            return false;
+        }
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        match self.host.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+            None => host == self.host,
+        }
+    }
+}
+
+impl FromStr for NetworkAllowlistEntry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || format!("invalid network allowlist entry `{s}`, expected HOST or HOST:PORT");
+        let (host, port) = match s.strip_prefix('[') {
+            Some(rest) => {
+                let (host, rest) = rest.split_once(']').ok_or_else(invalid)?;
+                let port = match rest {
+                    "" => None,
+                    rest => Some(rest.strip_prefix(':').ok_or_else(invalid)?),
+                };
+                (host, port)
+            }
+            // A bare IPv6 address has several colons and no port.
+            None if s.matches(':').count() > 1 => (s, None),
+            None => match s.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (s, None),
+            },
+        };
+        let port = port
+            .map(|port| port.parse::<u16>().map_err(|_| invalid()))
+            .transpose()?;
+        let name = host.strip_prefix("*.").unwrap_or(host);
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | ':'));
+        if !valid {
+            return Err(invalid());
+        }
+        Ok(Self {
+            host: host.trim_end_matches('.').to_ascii_lowercase(),
+            port,
+        })
+    }
+}
+
+impl TryFrom<String> for NetworkAllowlistEntry {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for NetworkAllowlistEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.port {
+            Some(port) if self.host.contains(':') => write!(f, "[{}]:{port}", self.host),
+            Some(port) => write!(f, "{}:{port}", self.host),
+            None => f.write_str(&self.host),
+        }
+    }
+}
+
+impl From<NetworkAllowlistEntry> for String {
+    fn from(entry: NetworkAllowlistEntry) -> Self {
+        entry.to_string()
+    }
+}
+
 /// A writable root path accompanied by a list of subpaths that should remain
 /// read‑only even when the root is writable. This is primarily used to ensure
 /// top‑level VCS metadata directories (e.g. `.git`) under a writable root are
@@ -288,7 +434,9 @@ impl FromStr for SandboxPolicy {
 impl SandboxPolicy {
     /// Returns a policy with read-only disk access and no network.
     pub fn new_read_only_policy() -> Self {
-        SandboxPolicy::ReadOnly
+        SandboxPolicy::ReadOnly {
+            deny_read: Vec::new(),
+        }
     }
 
     /// Returns a policy that can read the entire disk, but can only write to
@@ -297,13 +445,16 @@ impl SandboxPolicy {
     pub fn new_workspace_write_policy() -> Self {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
+            deny_read: Vec::new(),
             network_access: false,
+            network_allowlist: Vec::new(),
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
         }
     }
 
-    /// Always returns `true`; restricting read access is not supported.
+    /// Always returns `true`: every mode can read the entire disk apart from
+    /// its read-denied paths.
     pub fn has_full_disk_read_access(&self) -> bool {
         true
     }
@@ -311,7 +462,7 @@ impl SandboxPolicy {
     pub fn has_full_disk_write_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { .. } => false,
         }
     }
@@ -319,23 +470,38 @@ impl SandboxPolicy {
     pub fn has_full_network_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { network_access, .. } => *network_access,
         }
     }
 
+    /// Hosts reachable through the network proxy. Empty unless network access
+    /// is restricted to an allowlist.
+    pub fn network_allowlist(&self) -> &[NetworkAllowlistEntry] {
+        match self {
+            SandboxPolicy::WorkspaceWrite {
+                network_access: false,
+                network_allowlist,
+                ..
+            } => network_allowlist,
+            _ => &[],
+        }
+    }
+
     /// Returns the list of writable roots (tailored to the current working
     /// directory) together with subpaths that should remain read‑only under
     /// each writable root.
     pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<WritableRoot> {
         match self {
             SandboxPolicy::DangerFullAccess => Vec::new(),
-            SandboxPolicy::ReadOnly => Vec::new(),
+            SandboxPolicy::ReadOnly { .. } => Vec::new(),
             SandboxPolicy::WorkspaceWrite {
                 writable_roots,
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
+                deny_read: _,
                 network_access: _,
+                network_allowlist: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -385,8 +551,156 @@ impl SandboxPolicy {
             }
         }
     }
+
+    /// Returns the absolute paths whose contents cannot be read from within
+    /// the sandbox: the default credential directories plus the configured
+    /// `deny_read` entries. Entries starting with `~/` are resolved against
+    /// `$HOME` and relative entries against `cwd`.
+    pub fn get_read_denied_paths_with_cwd(&self, cwd: &Path) -> Vec<PathBuf> {
+        let deny_read = match self {
+            SandboxPolicy::DangerFullAccess => return Vec::new(),
+            SandboxPolicy::ReadOnly { deny_read } => deny_read,
+            SandboxPolicy::WorkspaceWrite { deny_read, .. } => deny_read,
+        };
+        let home = std::env::var_os("HOME")
+            .filter(|home| !home.is_empty())
+            .map(PathBuf::from);
+
+        let mut paths: Vec<PathBuf> = Vec::new();
+        let defaults = DEFAULT_READ_DENIED_PATHS.iter().map(Path::new);
+        for entry in defaults.chain(deny_read.iter().map(PathBuf::as_path)) {
+            let path = match (entry.strip_prefix("~"), &home) {
+                (Ok(rest), Some(home)) => home.join(rest),
+                // Without a home directory, `~` cannot be resolved.
+                (Ok(_), None) => continue,
+                (Err(_), _) => cwd.join(entry),
+            };
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    /// Returns the names of files that cannot be read from within the sandbox
+    /// wherever they are, such as `.env`. Directories with these names stay
+    /// readable.
+    pub fn read_denied_file_names(&self) -> &'static [&'static str] {
+        match self {
+            SandboxPolicy::DangerFullAccess => &[],
+            SandboxPolicy::ReadOnly { .. } | SandboxPolicy::WorkspaceWrite { .. } => {
+                DEFAULT_READ_DENIED_FILE_NAMES
+            }
+        }
+    }
+
+    /// Returns the files under `cwd` and the writable roots whose name makes
+    /// them unreadable from within the sandbox (see
+    /// [`SandboxPolicy::is_path_read_denied`]), for sandboxes that can only
+    /// deny reads of individual paths. `.git` directories are not searched,
+    /// nor is anything past the first [`MAX_READ_DENIED_SCAN_ENTRIES`]
+    /// entries.
+    pub fn find_read_denied_files_with_cwd(&self, cwd: &Path) -> Vec<PathBuf> {
+        let names = self.read_denied_file_names();
+        if names.is_empty() {
+            return Vec::new();
+        }
+        let mut roots = vec![cwd.to_path_buf()];
+        for writable_root in self.get_writable_roots_with_cwd(cwd) {
+            if !roots
+                .iter()
+                .any(|root| writable_root.root.starts_with(root))
+            {
+                roots.retain(|root| !root.starts_with(&writable_root.root));
+                roots.push(writable_root.root);
+            }
+        }
+
+        // Breadth first, so the shallowest files are found before the cap.
+        let mut pending: std::collections::VecDeque<PathBuf> = roots.into();
+        let mut found = Vec::new();
+        let mut scanned = 0;
+        while let Some(dir) = pending.pop_front() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                scanned += 1;
+                if scanned > MAX_READ_DENIED_SCAN_ENTRIES {
+                    tracing::warn!(
+                        "stopped looking for read-denied files after {MAX_READ_DENIED_SCAN_ENTRIES} entries"
+                    );
+                    return found;
+                }
+                let name = entry.file_name();
+                match entry.file_type() {
+                    Ok(kind) if kind.is_dir() => {
+                        if name != ".git" {
+                            pending.push_back(entry.path());
+                        }
+                    }
+                    Ok(_) if names.iter().any(|denied| name == *denied) => {
+                        found.push(entry.path());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        found
+    }
+
+    /// Whether `path` (relative paths are resolved against `cwd`) is, or is
+    /// beneath, a read-denied path, or is a file whose name is always denied
+    /// (such as `.env`) wherever it is. Symlinks are followed when `path`
+    /// exists.
+    pub fn is_path_read_denied(&self, path: &Path, cwd: &Path) -> bool {
+        let names = self.read_denied_file_names();
+        if names.is_empty() {
+            return false;
+        }
+        let denied = self.get_read_denied_paths_with_cwd(cwd);
+        let path = cwd.join(path);
+        let canonical = path.canonicalize().ok();
+        let has_denied_name = |path: &Path| {
+            path.file_name()
+                .is_some_and(|name| names.iter().any(|denied| name == *denied))
+                && !path.is_dir()
+        };
+        if has_denied_name(&path) || canonical.as_deref().is_some_and(has_denied_name) {
+            return true;
+        }
+        denied.iter().any(|denied| {
+            path.starts_with(denied)
+                || canonical.as_ref().is_some_and(|canonical| {
+                    canonical.starts_with(denied.canonicalize().as_deref().unwrap_or(denied))
+                })
+        })
+    }
 }
 
+/// Credential directories and files that are never readable from within the
+/// sandbox. Entries starting with `~/` are relative to the user's home
+/// directory, the others to the working directory.
+const DEFAULT_READ_DENIED_PATHS: &[&str] = &[
+    "~/.ssh",
+    "~/.gnupg",
+    "~/.aws",
+    "~/.azure",
+    "~/.config/gcloud",
+    "~/.kube",
+    "~/.docker/config.json",
+    "~/.netrc",
+    "~/.git-credentials",
+];
+
+/// Names of credential files that are never readable from within the sandbox,
+/// wherever they are.
+const DEFAULT_READ_DENIED_FILE_NAMES: &[&str] = &[".env"];
+
+/// How many directory entries [`SandboxPolicy::find_read_denied_files_with_cwd`]
+/// looks at before giving up.
+const MAX_READ_DENIED_SCAN_ENTRIES: usize = 100_000;
+
 /// User input
 #[non_exhaustive]
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -484,6 +798,9 @@ pub enum EventMsg {
 
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    /// An MCP server asked to sample the model (`sampling/createMessage`).
+    McpSamplingApprovalRequest(McpSamplingApprovalRequestEvent),
+
     BackgroundEvent(BackgroundEventEvent),
 
     /// Notification that a model stream experienced an error or disconnect
@@ -522,6 +839,15 @@ pub enum EventMsg {
 
     /// Exited review mode with an optional final result to apply.
     ExitedReviewMode(ExitedReviewModeEvent),
+
+    /// A snapshot of the workspace was captured before a turn started.
+    GhostSnapshot(GhostSnapshotEvent),
+
+    /// Result of an [`Op::Undo`] or [`Op::RestoreToTurn`].
+    UndoCompleted(UndoCompletedEvent),
+
+    /// Response to [`Op::GetSessionDiff`].
+    SessionDiff(SessionDiffEvent),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -1058,10 +1384,38 @@ pub struct GitInfo {
 }
 
 /// Review request sent to the review session.
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, TS)]
 pub struct ReviewRequest {
+    /// Review instructions. May be empty when `target` is set, in which case a
+    /// default prompt for the target is used.
     pub prompt: String,
+    /// Short description of what is being reviewed, e.g. "current changes".
+    /// May be empty when `target` is set.
     pub user_facing_hint: String,
+    /// Changes to review. When set, core computes the diff with git and
+    /// includes it in the prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<ReviewTarget>,
+    /// Restrict the diff of `target` to these paths (relative to the cwd).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+}
+
+/// The set of changes a review looks at.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReviewTarget {
+    /// Staged, unstaged and untracked changes in the working tree.
+    WorkingTree,
+    /// Only the changes staged in the index.
+    Staged,
+    /// Changes since the current branch diverged from `branch` (or its
+    /// upstream, when it has one).
+    BaseBranch { branch: String },
+    /// Changes introduced by a single commit.
+    Commit { sha: String },
+    /// Changes between two commits, as in `git diff from to`.
+    CommitRange { from: String, to: String },
 }
 
 /// Structured review result produced by a child review session.
@@ -1137,6 +1491,34 @@ pub struct ExecCommandEndEvent {
     pub duration: Duration,
     /// Formatted output from the command, as seen by the model.
     pub formatted_output: String,
+    /// Operations the sandbox refused while the command ran.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sandbox_violations: Vec<SandboxViolation>,
+}
+
+/// An operation the sandbox refused to let a command perform.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SandboxViolation {
+    /// `syscall` tried to create, modify or remove `path`, which is outside
+    /// the writable roots (or inside one of their read-only subpaths).
+    FileWrite { path: PathBuf, syscall: String },
+    /// The sandbox blocked `syscall` outright, mostly because it would use
+    /// the network.
+    Syscall { syscall: String },
+}
+
+impl fmt::Display for SandboxViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SandboxViolation::FileWrite { path, syscall } => {
+                write!(f, "{syscall}: writing to {} is not allowed", path.display())
+            }
+            SandboxViolation::Syscall { syscall } => {
+                write!(f, "{syscall}: blocked by the sandbox")
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -1179,6 +1561,24 @@ pub struct ExecApprovalRequestEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
     pub parsed_cmd: Vec<ParsedCommand>,
+    /// Directories inside the workspace the command failed to write to in
+    /// the sandbox. When set, the user may answer
+    /// [`ReviewDecision::ApprovedWithWritableRoots`] to rerun it in the
+    /// sandbox with these directories writable. Only reported on Linux when
+    /// the opt-in `sandbox_report_denied_writes` setting is enabled; it is
+    /// empty otherwise.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggested_writable_roots: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpSamplingApprovalRequestEvent {
+    /// Identifier for this request; echo it back in `Op::McpSamplingApproval`.
+    pub call_id: String,
+    /// Name of the MCP server, as configured in `mcp_servers`.
+    pub server: String,
+    /// The messages, system prompt and limits requested by the server.
+    pub request: CreateMessageRequestParams,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -1229,11 +1629,54 @@ pub struct PatchApplyEndEvent {
     pub stderr: String,
     /// Whether the patch was applied successfully.
     pub success: bool,
+    /// Hunks whose context did not match the file and that were applied with
+    /// a three-way merge against the last version of the file the model read.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fuzzy_hunks: Vec<FuzzyPatchHunk>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, TS)]
+pub struct FuzzyPatchHunk {
+    pub path: PathBuf,
+    /// 1-based index of the hunk within the file's section of the patch.
+    pub hunk: u32,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct TurnDiffEvent {
+    /// Number of the turn within the session; 0 when the diff does not belong
+    /// to a single turn (dry-run sessions, older rollouts).
+    #[serde(default)]
+    pub turn: u64,
+    pub unified_diff: String,
+    /// Lines added and removed per file, in the order of `unified_diff`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileDiffStat>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, TS)]
+pub struct FileDiffStat {
+    /// Path as it appears in the diff, relative to the repository root.
+    pub path: PathBuf,
+    pub added: u32,
+    pub removed: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SessionDiffEvent {
+    /// Turns that changed files, oldest first.
+    pub turns: Vec<SessionTurnDiff>,
+    /// Lines added and removed per file, summed over `turns`.
+    pub files: Vec<FileDiffStat>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SessionTurnDiff {
+    /// 1-based number of the turn within the session.
+    pub turn: u64,
+    /// Changes made during the turn, as in the turn's last [`TurnDiffEvent`].
     pub unified_diff: String,
+    pub files: Vec<FileDiffStat>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -1279,6 +1722,9 @@ impl fmt::Display for McpAuthStatus {
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct ListCustomPromptsResponseEvent {
     pub custom_prompts: Vec<CustomPrompt>,
+    /// Prompts advertised by connected MCP servers, keyed by server name.
+    #[serde(default)]
+    pub mcp_prompts: HashMap<String, Vec<McpPrompt>>,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize, TS)]
@@ -1319,6 +1765,16 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User has approved this command and wants it remembered in the
+    /// persistent command allowlist under `CODEX_HOME`, scoped to the current
+    /// project, so it is auto-approved in future sessions as well.
+    ApprovedAlways,
+
+    /// User has approved rerunning this command in the sandbox with the
+    /// `suggested_writable_roots` of the request added to the writable roots
+    /// for the remainder of the session.
+    ApprovedWithWritableRoots,
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
@@ -1334,6 +1790,9 @@ pub enum ReviewDecision {
 pub enum FileChange {
     Add {
         content: String,
+        /// `Some(true)` when the file is made executable.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        executable: Option<bool>,
     },
     Delete {
         content: String,
@@ -1341,6 +1800,20 @@ pub enum FileChange {
     Update {
         unified_diff: String,
         move_path: Option<PathBuf>,
+        /// `Some(true)` / `Some(false)` when the executable bit is set or
+        /// cleared, `None` when the mode is unchanged.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        executable: Option<bool>,
+    },
+    /// The file is created as a copy of `source`.
+    Copy {
+        source: PathBuf,
+    },
+    /// The file is created or overwritten with binary contents.
+    Binary {
+        size: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        executable: Option<bool>,
     },
 }
 
@@ -1352,6 +1825,42 @@ pub struct Chunk {
     pub inserted_lines: Vec<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+pub struct GhostSnapshotEvent {
+    /// 1-based number of the turn within the session.
+    pub turn: u64,
+    /// Commit (or file-store manifest) holding the workspace state before the
+    /// turn.
+    pub commit_id: String,
+    /// `HEAD` of the repository when the snapshot was taken.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Where the snapshot is stored.
+    #[serde(default)]
+    pub kind: GhostSnapshotKind,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum GhostSnapshotKind {
+    /// Ghost commit in the workspace's Git repository.
+    #[default]
+    Git,
+    /// Manifest in the content-addressed store under `CODEX_HOME/snapshots`,
+    /// used when the workspace is not a Git repository.
+    Files,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+pub struct UndoCompletedEvent {
+    pub success: bool,
+    /// Turn whose snapshot the workspace was restored to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restored_turn: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct TurnAbortedEvent {
     pub reason: TurnAbortReason,
@@ -1363,6 +1872,8 @@ pub enum TurnAbortReason {
     Interrupted,
     Replaced,
     ReviewEnded,
+    /// The session used up its `token_budget`.
+    BudgetExceeded,
 }
 
 #[cfg(test)]
@@ -1424,4 +1935,148 @@ mod tests {
         assert_eq!(deserialized, event);
         Ok(())
     }
+
+    #[test]
+    fn network_allowlist_entries_match_hosts_and_ports() -> std::result::Result<(), String> {
+        let registry: NetworkAllowlistEntry = "Registry.npmjs.org".parse()?;
+        assert!(registry.allows("registry.npmjs.org", 443));
+        assert!(registry.allows("REGISTRY.npmjs.org.", 80));
+        assert!(!registry.allows("evil-registry.npmjs.org", 443));
+
+        let git: NetworkAllowlistEntry = "git.example.com:22".parse()?;
+        assert!(git.allows("git.example.com", 22));
+        assert!(!git.allows("git.example.com", 443));
+
+        let wildcard: NetworkAllowlistEntry = "*.example.com".parse()?;
+        assert!(wildcard.allows("pkg.example.com", 443));
+        assert!(!wildcard.allows("example.com", 443));
+        assert!(!wildcard.allows("badexample.com", 443));
+
+        let ipv6: NetworkAllowlistEntry = "[::1]:8080".parse()?;
+        assert!(ipv6.allows("::1", 8080));
+        assert_eq!(ipv6.to_string(), "[::1]:8080");
+
+        for invalid in ["", "*", "host:port", "a b", "[::1", "http://example.com"] {
+            assert!(
+                invalid.parse::<NetworkAllowlistEntry>().is_err(),
+                "{invalid}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn network_allowlist_only_applies_without_network_access() -> Result<()> {
+        let policy: SandboxPolicy = serde_json::from_value(json!({
+            "mode": "workspace-write",
+            "network_allowlist": ["crates.io", "*.crates.io:443"],
+        }))?;
+        assert_eq!(
+            policy
+                .network_allowlist()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["crates.io", "*.crates.io:443"]
+        );
+        assert_eq!(
+            serde_json::to_value(&policy)?["network_allowlist"],
+            json!(["crates.io", "*.crates.io:443"])
+        );
+
+        let open: SandboxPolicy = serde_json::from_value(json!({
+            "mode": "workspace-write",
+            "network_access": true,
+            "network_allowlist": ["crates.io"],
+        }))?;
+        assert!(open.network_allowlist().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn read_denied_paths_resolve_against_cwd_and_cover_subpaths() -> Result<()> {
+        let cwd = tempfile::tempdir()?;
+        let policy: SandboxPolicy = serde_json::from_value(json!({
+            "mode": "read-only",
+            "deny_read": [".env", "/etc/shadow"],
+        }))?;
+        assert_eq!(
+            serde_json::to_value(&policy)?["deny_read"],
+            json!([".env", "/etc/shadow"])
+        );
+        assert_eq!(
+            serde_json::to_value(SandboxPolicy::new_read_only_policy())?,
+            json!({ "mode": "read-only" })
+        );
+
+        let denied = policy.get_read_denied_paths_with_cwd(cwd.path());
+        assert!(denied.contains(&cwd.path().join(".env")));
+        assert!(denied.contains(&PathBuf::from("/etc/shadow")));
+        if let Some(home) = std::env::var_os("HOME").filter(|home| !home.is_empty()) {
+            assert!(denied.contains(&PathBuf::from(home).join(".ssh")));
+        }
+
+        assert!(policy.is_path_read_denied(Path::new(".env"), cwd.path()));
+        assert!(policy.is_path_read_denied(&cwd.path().join(".env"), cwd.path()));
+        assert!(!policy.is_path_read_denied(Path::new(".env.example"), cwd.path()));
+        assert!(!policy.is_path_read_denied(Path::new("src/main.rs"), cwd.path()));
+
+        // Symlinks into a denied location are denied too.
+        #[cfg(unix)]
+        {
+            std::fs::write(cwd.path().join(".env"), "TOKEN=secret")?;
+            std::os::unix::fs::symlink(cwd.path().join(".env"), cwd.path().join("link"))?;
+            assert!(policy.is_path_read_denied(Path::new("link"), cwd.path()));
+        }
+
+        assert!(
+            SandboxPolicy::DangerFullAccess
+                .get_read_denied_paths_with_cwd(cwd.path())
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn env_files_are_read_denied_at_any_depth() -> Result<()> {
+        let cwd = tempfile::tempdir()?;
+        let writable = tempfile::tempdir()?;
+        let nested = cwd.path().join("packages/api");
+        std::fs::create_dir_all(&nested)?;
+        std::fs::write(nested.join(".env"), "TOKEN=secret")?;
+        std::fs::write(cwd.path().join(".env"), "TOKEN=secret")?;
+        std::fs::write(writable.path().join(".env"), "TOKEN=secret")?;
+        std::fs::create_dir_all(cwd.path().join(".git"))?;
+        std::fs::write(cwd.path().join(".git/.env"), "")?;
+        // A virtualenv named `.env` stays usable.
+        std::fs::create_dir_all(cwd.path().join("tools/.env/bin"))?;
+
+        let policy = SandboxPolicy::new_read_only_policy();
+        assert!(policy.is_path_read_denied(Path::new("packages/api/.env"), cwd.path()));
+        assert!(policy.is_path_read_denied(Path::new(".env"), cwd.path()));
+        assert!(!policy.is_path_read_denied(Path::new("packages/api/.env.example"), cwd.path()));
+        assert!(!policy.is_path_read_denied(Path::new("tools/.env"), cwd.path()));
+        assert!(!policy.is_path_read_denied(Path::new("tools/.env/bin"), cwd.path()));
+        assert!(
+            !SandboxPolicy::DangerFullAccess
+                .is_path_read_denied(Path::new("packages/api/.env"), cwd.path())
+        );
+
+        let mut found = policy.find_read_denied_files_with_cwd(cwd.path());
+        found.sort();
+        assert_eq!(found, vec![cwd.path().join(".env"), nested.join(".env")]);
+
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![writable.path().to_path_buf()],
+            deny_read: Vec::new(),
+            network_access: false,
+            network_allowlist: Vec::new(),
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        };
+        let found = policy.find_read_denied_files_with_cwd(cwd.path());
+        assert_eq!(found.len(), 3);
+        assert!(found.contains(&writable.path().join(".env")));
+        Ok(())
+    }
 }
@@ -5,11 +5,17 @@ use std::sync::Arc;
 use rmcp::ErrorData as McpError;
 use rmcp::ServiceExt;
 use rmcp::handler::server::ServerHandler;
+use rmcp::model::AnnotateAble;
 use rmcp::model::CallToolRequestParam;
 use rmcp::model::CallToolResult;
 use rmcp::model::JsonObject;
+use rmcp::model::ListResourcesResult;
 use rmcp::model::ListToolsResult;
 use rmcp::model::PaginatedRequestParam;
+use rmcp::model::RawResource;
+use rmcp::model::ReadResourceRequestParam;
+use rmcp::model::ReadResourceResult;
+use rmcp::model::ResourceContents;
 use rmcp::model::ServerCapabilities;
 use rmcp::model::ServerInfo;
 use rmcp::model::Tool;
@@ -17,6 +23,15 @@ use serde::Deserialize;
 use serde_json::json;
 use tokio::task;
 
+/// Cursor of the second page of resources; the first page is served without
+/// one.
+const SECOND_RESOURCE_PAGE: &str = "page-2";
+const NOTES_URI: &str = "memo://notes";
+const NOTES_TEXT: &str = "hello from the test server";
+const LOGO_URI: &str = "memo://logo";
+/// `hello`, base64-encoded.
+const LOGO_BLOB: &str = "aGVsbG8=";
+
 #[derive(Clone)]
 struct TestToolServer {
     tools: Arc<Vec<Tool>>,
@@ -64,6 +79,7 @@ impl ServerHandler for TestToolServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             capabilities: ServerCapabilities::builder()
+                .enable_resources()
                 .enable_tools()
                 .enable_tool_list_changed()
                 .build(),
@@ -85,6 +101,52 @@ impl ServerHandler for TestToolServer {
         }
     }
 
+    /// Serves one text and one binary resource, on a page each.
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        match request.and_then(|request| request.cursor).as_deref() {
+            None => Ok(ListResourcesResult {
+                resources: vec![RawResource::new(NOTES_URI, "notes").no_annotation()],
+                next_cursor: Some(SECOND_RESOURCE_PAGE.to_string()),
+            }),
+            Some(SECOND_RESOURCE_PAGE) => Ok(ListResourcesResult::with_all_items(vec![
+                RawResource::new(LOGO_URI, "logo").no_annotation(),
+            ])),
+            Some(other) => Err(McpError::invalid_params(
+                format!("unknown cursor: {other}"),
+                None,
+            )),
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let contents = match request.uri.as_str() {
+            NOTES_URI => ResourceContents::text(NOTES_TEXT, NOTES_URI),
+            LOGO_URI => ResourceContents::BlobResourceContents {
+                uri: LOGO_URI.to_string(),
+                mime_type: Some("application/octet-stream".to_string()),
+                blob: LOGO_BLOB.to_string(),
+                meta: None,
+            },
+            other => {
+                return Err(McpError::resource_not_found(
+                    format!("unknown resource: {other}"),
+                    None,
+                ));
+            }
+        };
+        Ok(ReadResourceResult {
+            contents: vec![contents],
+        })
+    }
+
     async fn call_tool(
         &self,
         request: CallToolRequestParam,
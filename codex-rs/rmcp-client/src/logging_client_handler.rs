@@ -4,6 +4,9 @@ use rmcp::model::CancelledNotificationParam;
 use rmcp::model::ClientInfo;
 use rmcp::model::CreateElicitationRequestParam;
 use rmcp::model::CreateElicitationResult;
+use rmcp::model::CreateMessageRequestMethod;
+use rmcp::model::CreateMessageRequestParam;
+use rmcp::model::CreateMessageResult;
 use rmcp::model::ElicitationAction;
 use rmcp::model::LoggingLevel;
 use rmcp::model::LoggingMessageNotificationParam;
@@ -16,18 +19,48 @@ use tracing::error;
 use tracing::info;
 use tracing::warn;
 
-#[derive(Debug, Clone)]
+use crate::rmcp_client::SamplingHandler;
+use crate::utils::convert_to_mcp;
+use crate::utils::convert_to_rmcp;
+
+#[derive(Clone)]
 pub(crate) struct LoggingClientHandler {
     client_info: ClientInfo,
+    sampling_handler: Option<SamplingHandler>,
 }
 
 impl LoggingClientHandler {
-    pub(crate) fn new(client_info: ClientInfo) -> Self {
-        Self { client_info }
+    pub(crate) fn new(client_info: ClientInfo, sampling_handler: Option<SamplingHandler>) -> Self {
+        Self {
+            client_info,
+            sampling_handler,
+        }
     }
 }
 
 impl ClientHandler for LoggingClientHandler {
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, rmcp::ErrorData> {
+        let Some(handler) = &self.sampling_handler else {
+            info!("MCP server requested sampling, but no sampling handler is configured.");
+            return Err(rmcp::ErrorData::method_not_found::<
+                CreateMessageRequestMethod,
+            >());
+        };
+        let params = convert_to_mcp(params).map_err(|err| {
+            rmcp::ErrorData::invalid_params(format!("invalid sampling request: {err}"), None)
+        })?;
+        let result = handler(params)
+            .await
+            .map_err(|err| rmcp::ErrorData::internal_error(format!("{err:#}"), None))?;
+        convert_to_rmcp(result).map_err(|err| {
+            rmcp::ErrorData::internal_error(format!("invalid sampling result: {err}"), None)
+        })
+    }
+
     // TODO (CODEX-3571): support elicitations.
     async fn create_elicitation(
         &self,
@@ -406,6 +406,17 @@ impl App {
                         "E X E C".to_string(),
                     ));
                 }
+                ApprovalRequest::McpSampling { prompt, .. } => {
+                    let _ = tui.enter_alt_screen();
+                    let prompt_lines = prompt
+                        .lines()
+                        .map(|line| Line::from(line.to_string()))
+                        .collect();
+                    self.overlay = Some(Overlay::new_static_with_lines(
+                        prompt_lines,
+                        "S A M P L I N G".to_string(),
+                    ));
+                }
             },
             AppEvent::SpawnSubtask {
                 last_n_messages,
@@ -38,6 +38,9 @@ pub(crate) enum ApprovalRequest {
         id: String,
         command: Vec<String>,
         reason: Option<String>,
+        /// Directories the sandbox refused to write to; offered as an
+        /// alternative to running without the sandbox.
+        suggested_writable_roots: Vec<PathBuf>,
     },
     ApplyPatch {
         id: String,
@@ -45,6 +48,12 @@ pub(crate) enum ApprovalRequest {
         cwd: PathBuf,
         changes: HashMap<PathBuf, FileChange>,
     },
+    McpSampling {
+        id: String,
+        server: String,
+        system_prompt: Option<String>,
+        prompt: String,
+    },
 }
 
 /// Modal overlay asking the user to approve or deny one or more requests.
@@ -94,14 +103,21 @@ impl ApprovalOverlay {
         header: Box<dyn Renderable>,
     ) -> (Vec<ApprovalOption>, SelectionViewParams) {
         let (options, title) = match &variant {
-            ApprovalVariant::Exec { .. } => (
-                exec_options(),
+            ApprovalVariant::Exec {
+                suggested_writable_roots,
+                ..
+            } => (
+                exec_options(suggested_writable_roots),
                 "Would you like to run the following command?".to_string(),
             ),
             ApprovalVariant::ApplyPatch { .. } => (
                 patch_options(),
                 "Would you like to make the following edits?".to_string(),
             ),
+            ApprovalVariant::McpSampling { server, .. } => (
+                mcp_sampling_options(),
+                format!("Allow the {server} MCP server to run this prompt with your model?"),
+            ),
         };
 
         let header = Box::new(ColumnRenderable::with([
@@ -145,12 +161,15 @@ impl ApprovalOverlay {
         };
         if let Some(variant) = self.current_variant.as_ref() {
             match (&variant, option.decision) {
-                (ApprovalVariant::Exec { id, command }, decision) => {
+                (ApprovalVariant::Exec { id, command, .. }, decision) => {
                     self.handle_exec_decision(id, command, decision);
                 }
                 (ApprovalVariant::ApplyPatch { id, .. }, decision) => {
                     self.handle_patch_decision(id, decision);
                 }
+                (ApprovalVariant::McpSampling { id, .. }, decision) => {
+                    self.handle_mcp_sampling_decision(id, decision);
+                }
             }
         }
 
@@ -174,6 +193,14 @@ impl ApprovalOverlay {
         }));
     }
 
+    fn handle_mcp_sampling_decision(&self, id: &str, decision: ReviewDecision) {
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::McpSamplingApproval {
+                id: id.to_string(),
+                decision,
+            }));
+    }
+
     fn advance_queue(&mut self) {
         if let Some(next) = self.queue.pop() {
             self.set_current(next);
@@ -233,12 +260,15 @@ impl BottomPaneView for ApprovalOverlay {
             && let Some(variant) = self.current_variant.as_ref()
         {
             match &variant {
-                ApprovalVariant::Exec { id, command } => {
+                ApprovalVariant::Exec { id, command, .. } => {
                     self.handle_exec_decision(id, command, ReviewDecision::Abort);
                 }
                 ApprovalVariant::ApplyPatch { id, .. } => {
                     self.handle_patch_decision(id, ReviewDecision::Abort);
                 }
+                ApprovalVariant::McpSampling { id, .. } => {
+                    self.handle_mcp_sampling_decision(id, ReviewDecision::Abort);
+                }
             }
         }
         self.queue.clear();
@@ -285,6 +315,7 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                 id,
                 command,
                 reason,
+                suggested_writable_roots,
             } => {
                 let mut header: Vec<Line<'static>> = Vec::new();
                 if let Some(reason) = reason
@@ -300,7 +331,11 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                 }
                 header.extend(full_cmd_lines);
                 Self {
-                    variant: ApprovalVariant::Exec { id, command },
+                    variant: ApprovalVariant::Exec {
+                        id,
+                        command,
+                        suggested_writable_roots,
+                    },
                     header: Box::new(Paragraph::new(header).wrap(Wrap { trim: false })),
                 }
             }
@@ -326,14 +361,46 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                     header: Box::new(ColumnRenderable::with(header)),
                 }
             }
+            ApprovalRequest::McpSampling {
+                id,
+                server,
+                system_prompt,
+                prompt,
+            } => {
+                let mut header: Vec<Line<'static>> = Vec::new();
+                if let Some(system_prompt) = system_prompt
+                    && !system_prompt.is_empty()
+                {
+                    header.push(Line::from(vec![
+                        "System prompt: ".into(),
+                        system_prompt.italic(),
+                    ]));
+                    header.push(Line::from(""));
+                }
+                header.extend(prompt.lines().map(|line| Line::from(line.to_string())));
+                Self {
+                    variant: ApprovalVariant::McpSampling { id, server },
+                    header: Box::new(Paragraph::new(header).wrap(Wrap { trim: false })),
+                }
+            }
         }
     }
 }
 
 #[derive(Clone)]
 enum ApprovalVariant {
-    Exec { id: String, command: Vec<String> },
-    ApplyPatch { id: String },
+    Exec {
+        id: String,
+        command: Vec<String>,
+        suggested_writable_roots: Vec<PathBuf>,
+    },
+    ApplyPatch {
+        id: String,
+    },
+    McpSampling {
+        id: String,
+        server: String,
+    },
 }
 
 #[derive(Clone)]
@@ -352,27 +419,47 @@ impl ApprovalOption {
     }
 }
 
-fn exec_options() -> Vec<ApprovalOption> {
-    vec![
-        ApprovalOption {
-            label: "Yes, proceed".to_string(),
-            decision: ReviewDecision::Approved,
+fn exec_options(suggested_writable_roots: &[PathBuf]) -> Vec<ApprovalOption> {
+    let mut options = vec![ApprovalOption {
+        label: "Yes, proceed".to_string(),
+        decision: ReviewDecision::Approved,
+        display_shortcut: None,
+        additional_shortcuts: vec![key_hint::plain(KeyCode::Char('y'))],
+    }];
+    if !suggested_writable_roots.is_empty() {
+        let roots = suggested_writable_roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        options.push(ApprovalOption {
+            label: format!("Yes, allow writing to {roots} and retry in the sandbox"),
+            decision: ReviewDecision::ApprovedWithWritableRoots,
             display_shortcut: None,
-            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('y'))],
-        },
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('w'))],
+        });
+    }
+    options.extend([
         ApprovalOption {
             label: "Yes, and don't ask again for this command".to_string(),
             decision: ReviewDecision::ApprovedForSession,
             display_shortcut: None,
             additional_shortcuts: vec![key_hint::plain(KeyCode::Char('a'))],
         },
+        ApprovalOption {
+            label: "Yes, and always allow this command in this project".to_string(),
+            decision: ReviewDecision::ApprovedAlways,
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('p'))],
+        },
         ApprovalOption {
             label: "No, and tell Codex what to do differently".to_string(),
             decision: ReviewDecision::Abort,
             display_shortcut: Some(key_hint::plain(KeyCode::Esc)),
             additional_shortcuts: vec![key_hint::plain(KeyCode::Char('n'))],
         },
-    ]
+    ]);
+    options
 }
 
 fn patch_options() -> Vec<ApprovalOption> {
@@ -392,6 +479,29 @@ fn patch_options() -> Vec<ApprovalOption> {
     ]
 }
 
+fn mcp_sampling_options() -> Vec<ApprovalOption> {
+    vec![
+        ApprovalOption {
+            label: "Yes, proceed".to_string(),
+            decision: ReviewDecision::Approved,
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('y'))],
+        },
+        ApprovalOption {
+            label: "Yes, and don't ask again for this server".to_string(),
+            decision: ReviewDecision::ApprovedForSession,
+            display_shortcut: None,
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('a'))],
+        },
+        ApprovalOption {
+            label: "No, decline the request".to_string(),
+            decision: ReviewDecision::Denied,
+            display_shortcut: Some(key_hint::plain(KeyCode::Esc)),
+            additional_shortcuts: vec![key_hint::plain(KeyCode::Char('n'))],
+        },
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -404,6 +514,7 @@ mod tests {
             id: "test".to_string(),
             command: vec!["echo".to_string(), "hi".to_string()],
             reason: Some("reason".to_string()),
+            suggested_writable_roots: Vec::new(),
         }
     }
 
@@ -445,6 +556,7 @@ mod tests {
             id: "test".into(),
             command,
             reason: None,
+            suggested_writable_roots: Vec::new(),
         };
 
         let view = ApprovalOverlay::new(exec_request, tx);
@@ -516,4 +628,61 @@ mod tests {
         }
         assert_eq!(decision, Some(ReviewDecision::ApprovedForSession));
     }
+
+    #[test]
+    fn suggested_writable_roots_add_sandbox_retry_option() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let request = ApprovalRequest::Exec {
+            id: "test".to_string(),
+            command: vec!["touch".to_string(), "/opt/cache/stamp".to_string()],
+            reason: None,
+            suggested_writable_roots: vec![PathBuf::from("/opt/cache")],
+        };
+        let mut view = ApprovalOverlay::new(request, tx);
+        assert_eq!(
+            view.options[1].label,
+            "Yes, allow writing to /opt/cache and retry in the sandbox"
+        );
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::NONE));
+
+        let mut decision = None;
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(Op::ExecApproval { decision: d, .. }) = ev {
+                decision = Some(d);
+                break;
+            }
+        }
+        assert_eq!(decision, Some(ReviewDecision::ApprovedWithWritableRoots));
+    }
+
+    #[test]
+    fn mcp_sampling_decline_sends_denied() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let request = ApprovalRequest::McpSampling {
+            id: "call-1".to_string(),
+            server: "docs".to_string(),
+            system_prompt: None,
+            prompt: "user: summarize the page".to_string(),
+        };
+        let mut view = ApprovalOverlay::new(request, tx);
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+
+        assert!(view.is_complete());
+        let mut op = None;
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(op_event @ Op::McpSamplingApproval { .. }) = ev {
+                op = Some(op_event);
+                break;
+            }
+        }
+        assert_eq!(
+            op,
+            Some(Op::McpSamplingApproval {
+                id: "call-1".to_string(),
+                decision: ReviewDecision::Denied,
+            })
+        );
+    }
 }
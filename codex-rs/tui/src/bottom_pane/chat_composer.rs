@@ -18,6 +18,7 @@ use ratatui::widgets::WidgetRef;
 use super::chat_composer_history::ChatComposerHistory;
 use super::command_popup::CommandItem;
 use super::command_popup::CommandPopup;
+use super::command_popup::flatten_mcp_prompts;
 use super::file_search_popup::FileSearchPopup;
 use super::footer::FooterMode;
 use super::footer::FooterProps;
@@ -29,17 +30,22 @@ use super::footer::toggle_shortcut_mode;
 use super::paste_burst::CharDecision;
 use super::paste_burst::PasteBurst;
 use crate::bottom_pane::paste_burst::FlushResult;
+use crate::bottom_pane::prompt_args::PromptExpansionError;
 use crate::bottom_pane::prompt_args::expand_custom_prompt;
 use crate::bottom_pane::prompt_args::expand_if_numeric_with_positional_args;
+use crate::bottom_pane::prompt_args::mcp_prompt_command_with_arg_placeholders;
+use crate::bottom_pane::prompt_args::parse_mcp_prompt_name;
+use crate::bottom_pane::prompt_args::parse_prompt_inputs;
 use crate::bottom_pane::prompt_args::parse_slash_name;
-use crate::bottom_pane::prompt_args::prompt_argument_names;
 use crate::bottom_pane::prompt_args::prompt_command_with_arg_placeholders;
-use crate::bottom_pane::prompt_args::prompt_has_numeric_placeholders;
 use crate::slash_command::SlashCommand;
 use crate::slash_command::built_in_slash_commands;
 use crate::style::user_message_style;
+use codex_core::custom_prompts::prompt_argument_names;
+use codex_core::custom_prompts::prompt_has_numeric_placeholders;
 use codex_protocol::custom_prompts::CustomPrompt;
 use codex_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
+use mcp_types::Prompt as McpPrompt;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
@@ -71,6 +77,11 @@ pub enum InputResult {
         model: Option<String>,
         prompt: String,
     },
+    McpPrompt {
+        server: String,
+        name: String,
+        arguments: HashMap<String, String>,
+    },
     None,
 }
 
@@ -111,6 +122,7 @@ pub(crate) struct ChatComposer {
     // When true, disables paste-burst logic and inserts characters immediately.
     disable_paste_burst: bool,
     custom_prompts: Vec<CustomPrompt>,
+    mcp_prompts: Vec<(String, McpPrompt)>,
     footer_mode: FooterMode,
     footer_hint_override: Option<Vec<(String, String)>>,
     context_window_percent: Option<u8>,
@@ -154,6 +166,7 @@ impl ChatComposer {
             paste_burst: PasteBurst::default(),
             disable_paste_burst: false,
             custom_prompts: Vec::new(),
+            mcp_prompts: Vec::new(),
             footer_mode: FooterMode::ShortcutSummary,
             footer_hint_override: None,
             context_window_percent: None,
@@ -495,6 +508,17 @@ impl ChatComposer {
                                 }
                             }
                         }
+                        CommandItem::McpPrompt(idx) => {
+                            if let Some((server, prompt)) = popup.mcp_prompt(idx) {
+                                let (text, cursor) = mcp_prompt_command_with_arg_placeholders(
+                                    server,
+                                    &prompt.name,
+                                    &mcp_prompt_argument_names(prompt),
+                                );
+                                self.textarea.set_text(&text);
+                                cursor_target = Some(cursor);
+                            }
+                        }
                     }
                     if let Some(pos) = cursor_target {
                         self.textarea.set_cursor(pos);
@@ -548,6 +572,28 @@ impl ChatComposer {
                             }
                             return (InputResult::None, true);
                         }
+                        CommandItem::McpPrompt(idx) => {
+                            if let Some((server, prompt)) = popup.mcp_prompt(idx) {
+                                let arg_names = mcp_prompt_argument_names(prompt);
+                                if arg_names.is_empty() {
+                                    let result = InputResult::McpPrompt {
+                                        server: server.to_string(),
+                                        name: prompt.name.clone(),
+                                        arguments: HashMap::new(),
+                                    };
+                                    self.textarea.set_text("");
+                                    return (result, true);
+                                }
+                                let (text, cursor) = mcp_prompt_command_with_arg_placeholders(
+                                    server,
+                                    &prompt.name,
+                                    &arg_names,
+                                );
+                                self.textarea.set_text(&text);
+                                self.textarea.set_cursor(cursor);
+                            }
+                            return (InputResult::None, true);
+                        }
                     }
                 }
                 // Fallback to default newline handling if no command selected.
@@ -939,6 +985,20 @@ impl ChatComposer {
                     );
                 }
 
+                match parse_mcp_prompt_submission(first_line, &self.mcp_prompts) {
+                    Some(Ok(result)) => {
+                        self.textarea.set_text("");
+                        return (result, true);
+                    }
+                    Some(Err(message)) => {
+                        self.app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+                            history_cell::new_error_event(message),
+                        )));
+                        return (InputResult::None, true);
+                    }
+                    None => {}
+                }
+
                 if let Some((name, rest)) = parse_slash_name(first_line)
                     && rest.is_empty()
                     && let Some((_n, cmd)) = built_in_slash_commands()
@@ -1466,6 +1526,7 @@ impl ChatComposer {
             _ => {
                 if is_editing_slash_command_name {
                     let mut command_popup = CommandPopup::new(self.custom_prompts.clone());
+                    command_popup.set_mcp_prompts(self.mcp_prompts.clone());
                     command_popup.on_composer_text_change(first_line.to_string());
                     self.active_popup = ActivePopup::Command(command_popup);
                 }
@@ -1480,6 +1541,13 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: HashMap<String, Vec<McpPrompt>>) {
+        self.mcp_prompts = flatten_mcp_prompts(prompts);
+        if let ActivePopup::Command(popup) = &mut self.active_popup {
+            popup.set_mcp_prompts(self.mcp_prompts.clone());
+        }
+    }
+
     /// Synchronize `self.file_search_popup` with the current text in the textarea.
     /// Note this is only called when self.active_popup is NOT Command.
     fn sync_file_search_popup(&mut self) {
@@ -1668,6 +1736,61 @@ fn prompt_selection_action(
     }
 }
 
+fn mcp_prompt_argument_names(prompt: &McpPrompt) -> Vec<String> {
+    prompt
+        .arguments
+        .iter()
+        .flatten()
+        .map(|arg| arg.name.clone())
+        .collect()
+}
+
+/// Parses `/mcp:server:prompt KEY=value ...` into an [`InputResult::McpPrompt`].
+/// Returns `None` when the line does not name one of `mcp_prompts`, and an
+/// error message when the arguments are malformed or a required one is empty.
+fn parse_mcp_prompt_submission(
+    line: &str,
+    mcp_prompts: &[(String, McpPrompt)],
+) -> Option<Result<InputResult, String>> {
+    let (command, rest) = parse_slash_name(line)?;
+    let (server, name) = parse_mcp_prompt_name(command)?;
+    let (_, prompt) = mcp_prompts
+        .iter()
+        .find(|(s, p)| s == server && p.name == name)?;
+
+    let arguments = match parse_prompt_inputs(rest) {
+        Ok(arguments) => arguments,
+        Err(error) => {
+            let error = PromptExpansionError::Args {
+                command: format!("/{command}"),
+                error,
+            };
+            return Some(Err(error.user_message()));
+        }
+    };
+    let missing: Vec<String> = prompt
+        .arguments
+        .iter()
+        .flatten()
+        .filter(|arg| arg.required == Some(true))
+        .filter(|arg| arguments.get(&arg.name).is_none_or(String::is_empty))
+        .map(|arg| arg.name.clone())
+        .collect();
+    if !missing.is_empty() {
+        let error = PromptExpansionError::MissingArgs {
+            command: format!("/{command}"),
+            missing,
+        };
+        return Some(Err(error.user_message()));
+    }
+
+    Some(Ok(InputResult::McpPrompt {
+        server: server.to_string(),
+        name: name.to_string(),
+        arguments,
+    }))
+}
+
 /// Parse arguments for the /subtask command.
 /// Format: `/subtask [--last N] [--model MODEL] <prompt>`
 /// Returns (last_n_messages, model, prompt)
@@ -1743,6 +1866,39 @@ mod tests {
     use crate::bottom_pane::textarea::TextArea;
     use tokio::sync::mpsc::unbounded_channel;
 
+    #[test]
+    fn mcp_prompt_submission_parses_arguments() {
+        let prompts = vec![(
+            "docs".to_string(),
+            McpPrompt {
+                arguments: Some(vec![mcp_types::PromptArgument {
+                    description: None,
+                    name: "TOPIC".to_string(),
+                    required: Some(true),
+                    title: None,
+                }]),
+                description: None,
+                name: "summarize".to_string(),
+                title: None,
+            },
+        )];
+
+        assert!(parse_mcp_prompt_submission("/mcp:docs:unknown", &prompts).is_none());
+        assert!(parse_mcp_prompt_submission("/prompts:summarize", &prompts).is_none());
+        assert!(matches!(
+            parse_mcp_prompt_submission("/mcp:docs:summarize TOPIC=\"\"", &prompts),
+            Some(Err(_))
+        ));
+        assert_eq!(
+            parse_mcp_prompt_submission("/mcp:docs:summarize TOPIC=\"error handling\"", &prompts),
+            Some(Ok(InputResult::McpPrompt {
+                server: "docs".to_string(),
+                name: "summarize".to_string(),
+                arguments: HashMap::from([("TOPIC".to_string(), "error handling".to_string())]),
+            }))
+        );
+    }
+
     #[test]
     fn footer_hint_row_is_separated_from_composer() {
         let (tx, _rx) = unbounded_channel::<AppEvent>();
@@ -2347,7 +2503,7 @@ mod tests {
                 Some(CommandItem::Builtin(cmd)) => {
                     assert_eq!(cmd.command(), "model")
                 }
-                Some(CommandItem::UserPrompt(_)) => {
+                Some(CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_)) => {
                     panic!("unexpected prompt selected for '/mo'")
                 }
                 None => panic!("no selected command for '/mo'"),
@@ -12,21 +12,45 @@ use crate::slash_command::SlashCommand;
 use crate::slash_command::built_in_slash_commands;
 use codex_common::fuzzy_match::fuzzy_match;
 use codex_protocol::custom_prompts::CustomPrompt;
+use codex_protocol::custom_prompts::MCP_PROMPTS_CMD_PREFIX;
 use codex_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
+use mcp_types::Prompt as McpPrompt;
+use std::collections::HashMap;
 use std::collections::HashSet;
 
-/// A selectable item in the popup: either a built-in command or a user prompt.
+/// A selectable item in the popup: a built-in command, a user prompt, or a
+/// prompt provided by an MCP server.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum CommandItem {
     Builtin(SlashCommand),
     // Index into `prompts`
     UserPrompt(usize),
+    // Index into `mcp_prompts`
+    McpPrompt(usize),
+}
+
+/// Flattens the per-server prompt lists into `(server, prompt)` pairs sorted
+/// by server and prompt name.
+pub(crate) fn flatten_mcp_prompts(
+    prompts: HashMap<String, Vec<McpPrompt>>,
+) -> Vec<(String, McpPrompt)> {
+    let mut flattened: Vec<(String, McpPrompt)> = prompts
+        .into_iter()
+        .flat_map(|(server, prompts)| {
+            prompts
+                .into_iter()
+                .map(move |prompt| (server.clone(), prompt))
+        })
+        .collect();
+    flattened.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.name.cmp(&b.1.name)));
+    flattened
 }
 
 pub(crate) struct CommandPopup {
     command_filter: String,
     builtins: Vec<(&'static str, SlashCommand)>,
     prompts: Vec<CustomPrompt>,
+    mcp_prompts: Vec<(String, McpPrompt)>,
     state: ScrollState,
 }
 
@@ -41,10 +65,15 @@ impl CommandPopup {
             command_filter: String::new(),
             builtins,
             prompts,
+            mcp_prompts: Vec::new(),
             state: ScrollState::new(),
         }
     }
 
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<(String, McpPrompt)>) {
+        self.mcp_prompts = prompts;
+    }
+
     pub(crate) fn set_prompts(&mut self, mut prompts: Vec<CustomPrompt>) {
         let exclude: HashSet<String> = self
             .builtins
@@ -60,6 +89,12 @@ impl CommandPopup {
         self.prompts.get(idx)
     }
 
+    pub(crate) fn mcp_prompt(&self, idx: usize) -> Option<(&str, &McpPrompt)> {
+        self.mcp_prompts
+            .get(idx)
+            .map(|(server, prompt)| (server.as_str(), prompt))
+    }
+
     /// Update the filter string based on the current composer text. The text
     /// passed in is expected to start with a leading '/'. Everything after the
     /// *first* '/" on the *first* line becomes the active filter that is used
@@ -115,6 +150,10 @@ impl CommandPopup {
             for idx in 0..self.prompts.len() {
                 out.push((CommandItem::UserPrompt(idx), None, 0));
             }
+            // Then MCP prompts, sorted by server and name.
+            for idx in 0..self.mcp_prompts.len() {
+                out.push((CommandItem::McpPrompt(idx), None, 0));
+            }
             return out;
         }
 
@@ -132,23 +171,31 @@ impl CommandPopup {
                 out.push((CommandItem::UserPrompt(idx), Some(indices), score));
             }
         }
+        for (idx, (server, p)) in self.mcp_prompts.iter().enumerate() {
+            let display = format!("{MCP_PROMPTS_CMD_PREFIX}:{server}:{}", p.name);
+            if let Some((indices, score)) = fuzzy_match(&display, filter) {
+                out.push((CommandItem::McpPrompt(idx), Some(indices), score));
+            }
+        }
         // When filtering, sort by ascending score and then by name for stability.
         out.sort_by(|a, b| {
             a.2.cmp(&b.2).then_with(|| {
-                let an = match a.0 {
-                    CommandItem::Builtin(c) => c.command(),
-                    CommandItem::UserPrompt(i) => &self.prompts[i].name,
-                };
-                let bn = match b.0 {
-                    CommandItem::Builtin(c) => c.command(),
-                    CommandItem::UserPrompt(i) => &self.prompts[i].name,
-                };
+                let an = self.item_name(a.0);
+                let bn = self.item_name(b.0);
                 an.cmp(bn)
             })
         });
         out
     }
 
+    fn item_name(&self, item: CommandItem) -> &str {
+        match item {
+            CommandItem::Builtin(c) => c.command(),
+            CommandItem::UserPrompt(i) => &self.prompts[i].name,
+            CommandItem::McpPrompt(i) => &self.mcp_prompts[i].1.name,
+        }
+    }
+
     fn filtered_items(&self) -> Vec<CommandItem> {
         self.filtered().into_iter().map(|(c, _, _)| c).collect()
     }
@@ -168,6 +215,16 @@ impl CommandPopup {
                         format!("/{PROMPTS_CMD_PREFIX}:{}", self.prompts[i].name),
                         "send saved prompt".to_string(),
                     ),
+                    CommandItem::McpPrompt(i) => {
+                        let (server, prompt) = &self.mcp_prompts[i];
+                        (
+                            format!("/{MCP_PROMPTS_CMD_PREFIX}:{server}:{}", prompt.name),
+                            prompt
+                                .description
+                                .clone()
+                                .unwrap_or_else(|| format!("run prompt from {server}")),
+                        )
+                    }
                 };
                 GenericDisplayRow {
                     name,
@@ -234,7 +291,7 @@ mod tests {
         let matches = popup.filtered_items();
         let has_init = matches.iter().any(|item| match item {
             CommandItem::Builtin(cmd) => cmd.command() == "init",
-            CommandItem::UserPrompt(_) => false,
+            CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_) => false,
         });
         assert!(
             has_init,
@@ -252,7 +309,9 @@ mod tests {
         let selected = popup.selected_item();
         match selected {
             Some(CommandItem::Builtin(cmd)) => assert_eq!(cmd.command(), "init"),
-            Some(CommandItem::UserPrompt(_)) => panic!("unexpected prompt selected for '/init'"),
+            Some(CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_)) => {
+                panic!("unexpected prompt selected for '/init'")
+            }
             None => panic!("expected a selected command for exact match"),
         }
     }
@@ -264,7 +323,7 @@ mod tests {
         let matches = popup.filtered_items();
         match matches.first() {
             Some(CommandItem::Builtin(cmd)) => assert_eq!(cmd.command(), "model"),
-            Some(CommandItem::UserPrompt(_)) => {
+            Some(CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_)) => {
                 panic!("unexpected prompt ranked before '/model' for '/mo'")
             }
             None => panic!("expected at least one match for '/mo'"),
@@ -322,4 +381,37 @@ mod tests {
             "prompt with builtin name should be ignored"
         );
     }
+
+    #[test]
+    fn mcp_prompts_are_listed_by_server() {
+        let prompt = |name: &str| McpPrompt {
+            arguments: None,
+            description: None,
+            name: name.to_string(),
+            title: None,
+        };
+        let mut popup = CommandPopup::new(Vec::new());
+        popup.set_mcp_prompts(flatten_mcp_prompts(HashMap::from([
+            ("zeta".to_string(), vec![prompt("review")]),
+            (
+                "docs".to_string(),
+                vec![prompt("summarize"), prompt("explain")],
+            ),
+        ])));
+
+        popup.on_composer_text_change("/mcp:docs".to_string());
+        let names: Vec<String> = popup
+            .filtered_items()
+            .into_iter()
+            .filter_map(|it| match it {
+                CommandItem::McpPrompt(i) => popup
+                    .mcp_prompt(i)
+                    .map(|(server, p)| format!("{server}:{}", p.name)),
+                _ => None,
+            })
+            .collect();
+        assert!(names.contains(&"docs:explain".to_string()));
+        assert!(names.contains(&"docs:summarize".to_string()));
+        assert!(!names.contains(&"zeta:review".to_string()));
+    }
 }
@@ -1,4 +1,5 @@
 //! Bottom pane: shows the ChatComposer or a BottomPaneView, if one is active.
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::app_event_sender::AppEventSender;
@@ -42,6 +43,7 @@ pub(crate) enum CancellationEvent {
 pub(crate) use chat_composer::ChatComposer;
 pub(crate) use chat_composer::InputResult;
 use codex_protocol::custom_prompts::CustomPrompt;
+use mcp_types::Prompt as McpPrompt;
 
 use crate::status_indicator_widget::StatusIndicatorWidget;
 pub(crate) use list_selection_view::SelectionAction;
@@ -386,6 +388,12 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// Update prompts advertised by MCP servers, keyed by server name.
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: HashMap<String, Vec<McpPrompt>>) {
+        self.composer.set_mcp_prompts(prompts);
+        self.request_redraw();
+    }
+
     pub(crate) fn composer_is_empty(&self) -> bool {
         self.composer.is_empty()
     }
@@ -555,6 +563,7 @@ mod tests {
             id: "1".to_string(),
             command: vec!["echo".into(), "ok".into()],
             reason: None,
+            suggested_writable_roots: Vec::new(),
         }
     }
 
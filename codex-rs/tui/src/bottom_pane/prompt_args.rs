@@ -1,15 +1,11 @@
+use codex_core::custom_prompts::expand_prompt;
+use codex_core::custom_prompts::prompt_argument_names;
+use codex_core::custom_prompts::prompt_has_numeric_placeholders;
 use codex_protocol::custom_prompts::CustomPrompt;
+use codex_protocol::custom_prompts::MCP_PROMPTS_CMD_PREFIX;
 use codex_protocol::custom_prompts::PROMPTS_CMD_PREFIX;
-use lazy_static::lazy_static;
-use regex_lite::Regex;
 use shlex::Shlex;
 use std::collections::HashMap;
-use std::collections::HashSet;
-
-lazy_static! {
-    static ref PROMPT_ARG_REGEX: Regex =
-        Regex::new(r"\$[A-Z][A-Z0-9_]*").unwrap_or_else(|_| std::process::abort());
-}
 
 #[derive(Debug)]
 pub enum PromptArgsError {
@@ -81,31 +77,6 @@ pub fn parse_positional_args(rest: &str) -> Vec<String> {
     Shlex::new(rest).collect()
 }
 
-/// Extracts the unique placeholder variable names from a prompt template.
-///
-/// A placeholder is any token that matches the pattern `$[A-Z][A-Z0-9_]*`
-/// (for example `$USER`). The function returns the variable names without
-/// the leading `$`, de-duplicated and in the order of first appearance.
-pub fn prompt_argument_names(content: &str) -> Vec<String> {
-    let mut seen = HashSet::new();
-    let mut names = Vec::new();
-    for m in PROMPT_ARG_REGEX.find_iter(content) {
-        if m.start() > 0 && content.as_bytes()[m.start() - 1] == b'$' {
-            continue;
-        }
-        let name = &content[m.start() + 1..m.end()];
-        // Exclude special positional aggregate token from named args.
-        if name == "ARGUMENTS" {
-            continue;
-        }
-        let name = name.to_string();
-        if seen.insert(name.clone()) {
-            names.push(name);
-        }
-    }
-    names
-}
-
 /// Parses the `key=value` pairs that follow a custom prompt name.
 ///
 /// The input is split using shlex rules, so quoted values are supported
@@ -151,64 +122,24 @@ pub fn expand_custom_prompt(
         Some(prompt) => prompt,
         None => return Ok(None),
     };
-    // If there are named placeholders, expect key=value inputs.
-    let required = prompt_argument_names(&prompt.content);
-    if !required.is_empty() {
+    // If there are named placeholders, expect key=value inputs and leave
+    // `$1`..`$9` and `$ARGUMENTS` as they are; otherwise treat the rest as
+    // positional arguments.
+    let (inputs, positional) = if prompt_argument_names(&prompt.content).is_empty() {
+        (HashMap::new(), Some(parse_positional_args(rest)))
+    } else {
         let inputs = parse_prompt_inputs(rest).map_err(|error| PromptExpansionError::Args {
             command: format!("/{name}"),
             error,
         })?;
-        let missing: Vec<String> = required
-            .into_iter()
-            .filter(|k| !inputs.contains_key(k))
-            .collect();
-        if !missing.is_empty() {
-            return Err(PromptExpansionError::MissingArgs {
-                command: format!("/{name}"),
-                missing,
-            });
-        }
-        let content = &prompt.content;
-        let replaced = PROMPT_ARG_REGEX.replace_all(content, |caps: &regex_lite::Captures<'_>| {
-            if let Some(matched) = caps.get(0)
-                && matched.start() > 0
-                && content.as_bytes()[matched.start() - 1] == b'$'
-            {
-                return matched.as_str().to_string();
-            }
-            let whole = &caps[0];
-            let key = &whole[1..];
-            inputs
-                .get(key)
-                .cloned()
-                .unwrap_or_else(|| whole.to_string())
-        });
-        return Ok(Some(replaced.into_owned()));
-    }
-
-    // Otherwise, treat it as numeric/positional placeholder prompt (or none).
-    let pos_args: Vec<String> = Shlex::new(rest).collect();
-    let expanded = expand_numeric_placeholders(&prompt.content, &pos_args);
-    Ok(Some(expanded))
-}
-
-/// Detect whether `content` contains numeric placeholders ($1..$9) or `$ARGUMENTS`.
-pub fn prompt_has_numeric_placeholders(content: &str) -> bool {
-    if content.contains("$ARGUMENTS") {
-        return true;
-    }
-    let bytes = content.as_bytes();
-    let mut i = 0;
-    while i + 1 < bytes.len() {
-        if bytes[i] == b'$' {
-            let b1 = bytes[i + 1];
-            if (b'1'..=b'9').contains(&b1) {
-                return true;
-            }
-        }
-        i += 1;
-    }
-    false
+        (inputs, None)
+    };
+    expand_prompt(&prompt.content, &inputs, positional.as_deref())
+        .map(Some)
+        .map_err(|missing| PromptExpansionError::MissingArgs {
+            command: format!("/{name}"),
+            missing,
+        })
 }
 
 /// Extract positional arguments from a composer first line like "/name a b" for a given prompt name.
@@ -250,56 +181,39 @@ pub fn expand_if_numeric_with_positional_args(
     if args.is_empty() {
         return None;
     }
-    Some(expand_numeric_placeholders(&prompt.content, &args))
-}
-
-/// Expand `$1..$9` and `$ARGUMENTS` in `content` with values from `args`.
-pub fn expand_numeric_placeholders(content: &str, args: &[String]) -> String {
-    let mut out = String::with_capacity(content.len());
-    let mut i = 0;
-    let mut cached_joined_args: Option<String> = None;
-    while let Some(off) = content[i..].find('$') {
-        let j = i + off;
-        out.push_str(&content[i..j]);
-        let rest = &content[j..];
-        let bytes = rest.as_bytes();
-        if bytes.len() >= 2 {
-            match bytes[1] {
-                b'$' => {
-                    out.push_str("$$");
-                    i = j + 2;
-                    continue;
-                }
-                b'1'..=b'9' => {
-                    let idx = (bytes[1] - b'1') as usize;
-                    if let Some(val) = args.get(idx) {
-                        out.push_str(val);
-                    }
-                    i = j + 2;
-                    continue;
-                }
-                _ => {}
-            }
-        }
-        if rest.len() > "ARGUMENTS".len() && rest[1..].starts_with("ARGUMENTS") {
-            if !args.is_empty() {
-                let joined = cached_joined_args.get_or_insert_with(|| args.join(" "));
-                out.push_str(joined);
-            }
-            i = j + 1 + "ARGUMENTS".len();
-            continue;
-        }
-        out.push('$');
-        i = j + 1;
-    }
-    out.push_str(&content[i..]);
-    out
+    expand_prompt(&prompt.content, &HashMap::new(), Some(args.as_slice())).ok()
 }
 
 /// Constructs a command text for a custom prompt with arguments.
 /// Returns the text and the cursor position (inside the first double quote).
 pub fn prompt_command_with_arg_placeholders(name: &str, args: &[String]) -> (String, usize) {
-    let mut text = format!("/{PROMPTS_CMD_PREFIX}:{name}");
+    command_with_arg_placeholders(format!("/{PROMPTS_CMD_PREFIX}:{name}"), args)
+}
+
+/// Constructs the command text for an MCP server prompt with arguments.
+/// Returns the text and the cursor position (inside the first double quote).
+pub fn mcp_prompt_command_with_arg_placeholders(
+    server: &str,
+    name: &str,
+    args: &[String],
+) -> (String, usize) {
+    command_with_arg_placeholders(format!("/{MCP_PROMPTS_CMD_PREFIX}:{server}:{name}"), args)
+}
+
+/// Splits a slash command name of the form `mcp:server:prompt` into
+/// `(server, prompt)`. Returns `None` for any other command name.
+pub fn parse_mcp_prompt_name(name: &str) -> Option<(&str, &str)> {
+    let rest = name
+        .strip_prefix(MCP_PROMPTS_CMD_PREFIX)?
+        .strip_prefix(':')?;
+    let (server, prompt) = rest.split_once(':')?;
+    if server.is_empty() || prompt.is_empty() {
+        return None;
+    }
+    Some((server, prompt))
+}
+
+fn command_with_arg_placeholders(mut text: String, args: &[String]) -> (String, usize) {
     let mut cursor: usize = text.len();
     for (i, arg) in args.iter().enumerate() {
         text.push_str(format!(" {arg}=\"\"").as_str());
@@ -403,4 +317,59 @@ mod tests {
         let out = expand_custom_prompt("/prompts:my-prompt", &prompts).unwrap();
         assert_eq!(out, Some("literal $$USER".to_string()));
     }
+
+    #[test]
+    fn named_prompts_keep_positional_placeholders() {
+        let prompts = vec![CustomPrompt {
+            name: "my-prompt".to_string(),
+            path: "/tmp/my-prompt.md".to_string().into(),
+            content: "Review $USER changes: $1 $ARGUMENTS".to_string(),
+            description: None,
+            argument_hint: None,
+        }];
+
+        let out = expand_custom_prompt("/prompts:my-prompt USER=Alice", &prompts).unwrap();
+        assert_eq!(out, Some("Review Alice changes: $1 $ARGUMENTS".to_string()));
+    }
+
+    #[test]
+    fn positional_args_only_expand_prompts_without_named_placeholders() {
+        let numeric = CustomPrompt {
+            name: "numeric".to_string(),
+            path: "/tmp/numeric.md".to_string().into(),
+            content: "Fix $1 then $ARGUMENTS".to_string(),
+            description: None,
+            argument_hint: None,
+        };
+        assert_eq!(
+            expand_if_numeric_with_positional_args(&numeric, "/prompts:numeric bug tests"),
+            Some("Fix bug then bug tests".to_string())
+        );
+
+        let named = CustomPrompt {
+            name: "named".to_string(),
+            content: "Fix $1 for $USER".to_string(),
+            ..numeric
+        };
+        assert_eq!(
+            expand_if_numeric_with_positional_args(&named, "/prompts:named bug"),
+            None
+        );
+    }
+
+    #[test]
+    fn mcp_prompt_names() {
+        assert_eq!(
+            parse_mcp_prompt_name("mcp:docs:summarize"),
+            Some(("docs", "summarize"))
+        );
+        assert_eq!(parse_mcp_prompt_name("mcp:docs"), None);
+        assert_eq!(parse_mcp_prompt_name("mcp::summarize"), None);
+        assert_eq!(parse_mcp_prompt_name("prompts:docs:summarize"), None);
+
+        let (text, cursor) =
+            mcp_prompt_command_with_arg_placeholders("docs", "summarize", &["TOPIC".to_string()]);
+        assert_eq!(text, "/mcp:docs:summarize TOPIC=\"\"");
+        assert_eq!(cursor, text.len() - 1);
+    }
 }
@@ -27,28 +27,35 @@ use codex_core::protocol::InputItem;
 use codex_core::protocol::InputMessageKind;
 use codex_core::protocol::ListCustomPromptsResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
+use codex_core::protocol::McpSamplingApprovalRequestEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_core::protocol::ReviewRequest;
+use codex_core::protocol::ReviewTarget;
 use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TokenUsageInfo;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::UndoCompletedEvent;
 use codex_core::protocol::UserMessageEvent;
 use codex_core::protocol::ViewImageToolCallEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_protocol::ConversationId;
+use codex_protocol::custom_prompts::MCP_PROMPTS_CMD_PREFIX;
 use codex_protocol::parse_command::ParsedCommand;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use crossterm::event::KeyModifiers;
+use mcp_types::Role as McpRole;
+use mcp_types::SamplingMessage;
+use mcp_types::SamplingMessageContent;
 use rand::Rng;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Constraint;
@@ -110,16 +117,9 @@ use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
 use codex_file_search::FileMatch;
-use codex_git_tooling::CreateGhostCommitOptions;
-use codex_git_tooling::GhostCommit;
-use codex_git_tooling::GitToolingError;
-use codex_git_tooling::create_ghost_commit;
-use codex_git_tooling::restore_ghost_commit;
 use codex_protocol::plan_tool::UpdatePlanArgs;
 use strum::IntoEnumIterator;
 
-const MAX_TRACKED_GHOST_COMMITS: usize = 20;
-
 // Track information about an in-flight exec command.
 struct RunningCommand {
     command: Vec<String>,
@@ -261,9 +261,6 @@ pub(crate) struct ChatWidget {
     pending_notification: Option<Notification>,
     // Simple review mode flag; used to adjust layout and banners.
     is_review_mode: bool,
-    // List of ghost commits corresponding to each turn.
-    ghost_snapshots: Vec<GhostCommit>,
-    ghost_snapshots_disabled: bool,
     // Whether to add a final message separator after the last message
     needs_final_message_separator: bool,
 
@@ -284,6 +281,27 @@ impl From<String> for UserMessage {
     }
 }
 
+/// Renders the conversation an MCP server wants sampled as `role: text` lines
+/// for the approval prompt.
+fn sampling_messages_to_text(messages: &[SamplingMessage]) -> String {
+    messages
+        .iter()
+        .map(|message| {
+            let role = match message.role {
+                McpRole::User => "user",
+                McpRole::Assistant => "assistant",
+            };
+            let text = match &message.content {
+                SamplingMessageContent::TextContent(text) => text.text.as_str(),
+                SamplingMessageContent::ImageContent(_) => "[image]",
+                SamplingMessageContent::AudioContent(_) => "[audio]",
+            };
+            format!("{role}: {text}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 fn create_initial_user_message(text: String, image_paths: Vec<PathBuf>) -> Option<UserMessage> {
     if text.is_empty() && image_paths.is_empty() {
         None
@@ -549,6 +567,14 @@ impl ChatWidget {
         );
     }
 
+    fn on_mcp_sampling_approval_request(&mut self, ev: McpSamplingApprovalRequestEvent) {
+        let ev2 = ev.clone();
+        self.defer_or_handle(
+            |q| q.push_mcp_sampling_approval(ev),
+            |s| s.handle_mcp_sampling_approval_now(ev2),
+        );
+    }
+
     fn on_exec_command_begin(&mut self, ev: ExecCommandBeginEvent) {
         self.flush_answer_stream_with_separator();
         let ev2 = ev.clone();
@@ -768,6 +794,17 @@ impl ChatWidget {
         // Otherwise, add a failure block.
         if !event.success {
             self.add_to_history(history_cell::new_patch_apply_failure(event.stderr));
+        } else if !event.fuzzy_hunks.is_empty() {
+            let hunks = event
+                .fuzzy_hunks
+                .iter()
+                .map(|hunk| format!("{} (hunk {})", hunk.path.display(), hunk.hunk))
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.add_to_history(history_cell::new_info_event(
+                format!("Merged changes that no longer matched the file: {hunks}"),
+                Some("the file changed after it was last read".to_string()),
+            ));
         }
     }
 
@@ -781,6 +818,7 @@ impl ChatWidget {
             id,
             command: ev.command,
             reason: ev.reason,
+            suggested_writable_roots: ev.suggested_writable_roots,
         };
         self.bottom_pane.push_approval_request(request);
         self.request_redraw();
@@ -807,6 +845,19 @@ impl ChatWidget {
         });
     }
 
+    pub(crate) fn handle_mcp_sampling_approval_now(&mut self, ev: McpSamplingApprovalRequestEvent) {
+        self.flush_answer_stream_with_separator();
+
+        let request = ApprovalRequest::McpSampling {
+            id: ev.call_id,
+            server: ev.server,
+            system_prompt: ev.request.system_prompt,
+            prompt: sampling_messages_to_text(&ev.request.messages),
+        };
+        self.bottom_pane.push_approval_request(request);
+        self.request_redraw();
+    }
+
     pub(crate) fn handle_exec_begin_now(&mut self, ev: ExecCommandBeginEvent) {
         // Ensure the status indicator is visible while the command runs.
         self.running_commands.insert(
@@ -955,8 +1006,6 @@ impl ChatWidget {
             suppress_session_configured_redraw: false,
             pending_notification: None,
             is_review_mode: false,
-            ghost_snapshots: Vec::new(),
-            ghost_snapshots_disabled: true,
             needs_final_message_separator: false,
             last_rendered_width: std::cell::Cell::new(None),
         }
@@ -1020,8 +1069,6 @@ impl ChatWidget {
             suppress_session_configured_redraw: true,
             pending_notification: None,
             is_review_mode: false,
-            ghost_snapshots: Vec::new(),
-            ghost_snapshots_disabled: true,
             needs_final_message_separator: false,
             last_rendered_width: std::cell::Cell::new(None),
         }
@@ -1106,6 +1153,13 @@ impl ChatWidget {
                             prompt,
                         });
                     }
+                    InputResult::McpPrompt {
+                        server,
+                        name,
+                        arguments,
+                    } => {
+                        self.submit_mcp_prompt(server, name, arguments);
+                    }
                     InputResult::None => {}
                 }
             }
@@ -1176,7 +1230,7 @@ impl ChatWidget {
                 self.app_event_tx.send(AppEvent::ExitRequest);
             }
             SlashCommand::Undo => {
-                self.undo_last_snapshot();
+                self.submit_op(Op::Undo { turns: 1 });
             }
             SlashCommand::Diff => {
                 self.add_diff_in_progress();
@@ -1235,6 +1289,7 @@ impl ChatWidget {
                                 PathBuf::from("/tmp/test.txt"),
                                 FileChange::Add {
                                     content: "test".to_string(),
+                                    executable: None,
                                 },
                             ),
                             (
@@ -1242,6 +1297,7 @@ impl ChatWidget {
                                 FileChange::Update {
                                     unified_diff: "+test\n-test2".to_string(),
                                     move_path: None,
+                                    executable: None,
                                 },
                             ),
                         ]),
@@ -1301,8 +1357,6 @@ impl ChatWidget {
             return;
         }
 
-        self.capture_ghost_snapshot();
-
         let mut items: Vec<InputItem> = Vec::new();
 
         if !text.is_empty() {
@@ -1335,55 +1389,26 @@ impl ChatWidget {
         self.needs_final_message_separator = false;
     }
 
-    fn capture_ghost_snapshot(&mut self) {
-        if self.ghost_snapshots_disabled {
-            return;
-        }
-
-        let options = CreateGhostCommitOptions::new(&self.config.cwd);
-        match create_ghost_commit(&options) {
-            Ok(commit) => {
-                self.ghost_snapshots.push(commit);
-                if self.ghost_snapshots.len() > MAX_TRACKED_GHOST_COMMITS {
-                    self.ghost_snapshots.remove(0);
-                }
-            }
-            Err(err) => {
-                self.ghost_snapshots_disabled = true;
-                let (message, hint) = match &err {
-                    GitToolingError::NotAGitRepository { .. } => (
-                        "Snapshots disabled: current directory is not a Git repository."
-                            .to_string(),
-                        None,
-                    ),
-                    _ => (
-                        format!("Snapshots disabled after error: {err}"),
-                        Some(
-                            "Restart Codex after resolving the issue to re-enable snapshots."
-                                .to_string(),
-                        ),
-                    ),
-                };
-                self.add_info_message(message, hint);
-                tracing::warn!("failed to create ghost snapshot: {err}");
-            }
-        }
-    }
-
-    fn undo_last_snapshot(&mut self) {
-        let Some(commit) = self.ghost_snapshots.pop() else {
-            self.add_info_message("No snapshot available to undo.".to_string(), None);
-            return;
-        };
-
-        if let Err(err) = restore_ghost_commit(&self.config.cwd, &commit) {
-            self.add_error_message(format!("Failed to restore snapshot: {err}"));
-            self.ghost_snapshots.push(commit);
-            return;
+    fn submit_mcp_prompt(
+        &mut self,
+        server: String,
+        name: String,
+        arguments: HashMap<String, String>,
+    ) {
+        let mut display = format!("/{MCP_PROMPTS_CMD_PREFIX}:{server}:{name}");
+        let mut sorted_args: Vec<_> = arguments.iter().collect();
+        sorted_args.sort();
+        for (key, value) in sorted_args {
+            display.push_str(&format!(" {key}={value:?}"));
         }
 
-        let short_id: String = commit.id().chars().take(8).collect();
-        self.add_info_message(format!("Restored workspace to snapshot {short_id}"), None);
+        self.submit_op(Op::RunMcpPrompt {
+            server,
+            name,
+            arguments,
+        });
+        self.add_to_history(history_cell::new_user_prompt(display));
+        self.needs_final_message_separator = false;
     }
 
     /// Replay a subset of initial events into the UI to seed the transcript when
@@ -1456,6 +1481,8 @@ impl ChatWidget {
                 TurnAbortReason::ReviewEnded => {
                     self.on_interrupted_turn(ev.reason);
                 }
+                // The error event sent just before already ended the turn.
+                TurnAbortReason::BudgetExceeded => {}
             },
             EventMsg::PlanUpdate(update) => self.on_plan_update(update),
             EventMsg::ExecApprovalRequest(ev) => {
@@ -1465,6 +1492,7 @@ impl ChatWidget {
             EventMsg::ApplyPatchApprovalRequest(ev) => {
                 self.on_apply_patch_approval_request(id.unwrap_or_default(), ev)
             }
+            EventMsg::McpSamplingApprovalRequest(ev) => self.on_mcp_sampling_approval_request(ev),
             EventMsg::ExecCommandBegin(ev) => self.on_exec_command_begin(ev),
             EventMsg::ExecCommandOutputDelta(delta) => self.on_exec_command_output_delta(delta),
             EventMsg::PatchApplyBegin(ev) => self.on_patch_apply_begin(ev),
@@ -1479,7 +1507,9 @@ impl ChatWidget {
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
-            EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
+            EventMsg::TurnDiff(TurnDiffEvent { unified_diff, .. }) => {
+                self.on_turn_diff(unified_diff)
+            }
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 self.on_background_event(message)
             }
@@ -1497,6 +1527,21 @@ impl ChatWidget {
                 self.on_entered_review_mode(review_request)
             }
             EventMsg::ExitedReviewMode(review) => self.on_exited_review_mode(review),
+            EventMsg::GhostSnapshot(_) => {}
+            EventMsg::SessionDiff(_) => {}
+            EventMsg::UndoCompleted(ev) => self.on_undo_completed(ev),
+        }
+    }
+
+    fn on_undo_completed(&mut self, event: UndoCompletedEvent) {
+        let UndoCompletedEvent {
+            success, message, ..
+        } = event;
+        let message = message.unwrap_or_else(|| "Undo finished.".to_string());
+        if success {
+            self.add_info_message(message, None);
+        } else {
+            self.add_error_message(message);
         }
     }
 
@@ -1980,6 +2025,7 @@ impl ChatWidget {
         debug!("received {len} custom prompts");
         // Forward to bottom pane so the slash popup can show them now.
         self.bottom_pane.set_custom_prompts(ev.custom_prompts);
+        self.bottom_pane.set_mcp_prompts(ev.mcp_prompts);
     }
 
     pub(crate) fn open_review_popup(&mut self) {
@@ -2000,16 +2046,14 @@ impl ChatWidget {
 
         items.push(SelectionItem {
             name: "Review uncommitted changes".to_string(),
-            actions: vec![Box::new(
-                move |tx: &AppEventSender| {
-                    tx.send(AppEvent::CodexOp(Op::Review {
-                        review_request: ReviewRequest {
-                            prompt: "Review the current code changes (staged, unstaged, and untracked files) and provide prioritized findings.".to_string(),
-                            user_facing_hint: "current changes".to_string(),
-                        },
-                    }));
-                },
-            )],
+            actions: vec![Box::new(move |tx: &AppEventSender| {
+                tx.send(AppEvent::CodexOp(Op::Review {
+                    review_request: ReviewRequest {
+                        target: Some(ReviewTarget::WorkingTree),
+                        ..Default::default()
+                    },
+                }));
+            })],
             dismiss_on_select: true,
             ..Default::default()
         });
@@ -2058,10 +2102,10 @@ impl ChatWidget {
                 actions: vec![Box::new(move |tx3: &AppEventSender| {
                     tx3.send(AppEvent::CodexOp(Op::Review {
                         review_request: ReviewRequest {
-                            prompt: format!(
-                                "Review the code changes against the base branch '{branch}'. Start by finding the merge diff between the current branch and {branch}'s upstream e.g. (`git merge-base HEAD \"$(git rev-parse --abbrev-ref \"{branch}@{{upstream}}\")\"`), then run `git diff` against that SHA to see what changes we would merge into the {branch} branch. Provide prioritized, actionable findings."
-                            ),
-                            user_facing_hint: format!("changes against '{branch}'"),
+                            target: Some(ReviewTarget::BaseBranch {
+                                branch: branch.clone(),
+                            }),
+                            ..Default::default()
                         },
                     }));
                 })],
@@ -2102,6 +2146,8 @@ impl ChatWidget {
                         review_request: ReviewRequest {
                             prompt,
                             user_facing_hint: hint,
+                            target: Some(ReviewTarget::Commit { sha: sha.clone() }),
+                            paths: Vec::new(),
                         },
                     }));
                 })],
@@ -2136,6 +2182,7 @@ impl ChatWidget {
                     review_request: ReviewRequest {
                         prompt: trimmed.clone(),
                         user_facing_hint: trimmed,
+                        ..Default::default()
                     },
                 }));
             }),
@@ -2326,6 +2373,8 @@ pub(crate) fn show_review_commit_picker_with_entries(
                     review_request: ReviewRequest {
                         prompt,
                         user_facing_hint: hint,
+                        target: Some(ReviewTarget::Commit { sha: sha.clone() }),
+                        paths: Vec::new(),
                     },
                 }));
             })],
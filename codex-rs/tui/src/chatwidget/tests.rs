@@ -150,6 +150,7 @@ fn entered_review_mode_uses_request_hint() {
         msg: EventMsg::EnteredReviewMode(ReviewRequest {
             prompt: "Review the latest changes".to_string(),
             user_facing_hint: "feature branch".to_string(),
+            ..Default::default()
         }),
     });
 
@@ -169,6 +170,7 @@ fn entered_review_mode_defaults_to_current_changes_banner() {
         msg: EventMsg::EnteredReviewMode(ReviewRequest {
             prompt: "Review the current changes".to_string(),
             user_facing_hint: "current changes".to_string(),
+            ..Default::default()
         }),
     });
 
@@ -286,8 +288,6 @@ fn make_chatwidget_manual() -> (
         suppress_session_configured_redraw: false,
         pending_notification: None,
         is_review_mode: false,
-        ghost_snapshots: Vec::new(),
-        ghost_snapshots_disabled: false,
         needs_final_message_separator: false,
         last_rendered_width: std::cell::Cell::new(None),
     };
@@ -394,6 +394,7 @@ fn exec_approval_emits_proposed_command_and_decision_history() {
             "this is a test reason such as one that would be produced by the model".into(),
         ),
         parsed_cmd: vec![],
+        suggested_writable_roots: vec![],
     };
     chat.handle_codex_event(Event {
         id: "sub-short".into(),
@@ -436,6 +437,7 @@ fn exec_approval_decision_truncates_multiline_and_long_commands() {
             "this is a test reason such as one that would be produced by the model".into(),
         ),
         parsed_cmd: vec![],
+        suggested_writable_roots: vec![],
     };
     chat.handle_codex_event(Event {
         id: "sub-multi".into(),
@@ -484,6 +486,7 @@ fn exec_approval_decision_truncates_multiline_and_long_commands() {
         cwd: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
         reason: None,
         parsed_cmd: vec![],
+        suggested_writable_roots: vec![],
     };
     chat.handle_codex_event(Event {
         id: "sub-long".into(),
@@ -537,6 +540,7 @@ fn end_exec(chat: &mut ChatWidget, call_id: &str, stdout: &str, stderr: &str, ex
             exit_code,
             duration: std::time::Duration::from_millis(5),
             formatted_output: aggregated,
+            sandbox_violations: Vec::new(),
         }),
     });
 }
@@ -1354,6 +1358,7 @@ fn approval_modal_exec_snapshot() {
             "this is a test reason such as one that would be produced by the model".into(),
         ),
         parsed_cmd: vec![],
+        suggested_writable_roots: vec![],
     };
     chat.handle_codex_event(Event {
         id: "sub-approve".into(),
@@ -1398,6 +1403,7 @@ fn approval_modal_exec_without_reason_snapshot() {
         cwd: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
         reason: None,
         parsed_cmd: vec![],
+        suggested_writable_roots: vec![],
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-noreason".into(),
@@ -1429,6 +1435,7 @@ fn approval_modal_patch_snapshot() {
         PathBuf::from("README.md"),
         FileChange::Add {
             content: "hello\nworld\n".into(),
+            executable: None,
         },
     );
     let ev = ApplyPatchApprovalRequestEvent {
@@ -1608,6 +1615,7 @@ fn status_widget_and_approval_modal_snapshot() {
             "this is a test reason such as one that would be produced by the model".into(),
         ),
         parsed_cmd: vec![],
+        suggested_writable_roots: vec![],
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-exec".into(),
@@ -1663,6 +1671,7 @@ fn apply_patch_events_emit_history_cells() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            executable: None,
         },
     );
     let ev = ApplyPatchApprovalRequestEvent {
@@ -1703,6 +1712,7 @@ fn apply_patch_events_emit_history_cells() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            executable: None,
         },
     );
     let begin = PatchApplyBeginEvent {
@@ -1728,6 +1738,7 @@ fn apply_patch_events_emit_history_cells() {
         stdout: "ok\n".into(),
         stderr: String::new(),
         success: true,
+        fuzzy_hunks: Vec::new(),
     };
     chat.handle_codex_event(Event {
         id: "s1".into(),
@@ -1749,6 +1760,7 @@ fn apply_patch_manual_approval_adjusts_header() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            executable: None,
         },
     );
     chat.handle_codex_event(Event {
@@ -1767,6 +1779,7 @@ fn apply_patch_manual_approval_adjusts_header() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            executable: None,
         },
     );
     chat.handle_codex_event(Event {
@@ -1796,6 +1809,7 @@ fn apply_patch_manual_flow_snapshot() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            executable: None,
         },
     );
     chat.handle_codex_event(Event {
@@ -1818,6 +1832,7 @@ fn apply_patch_manual_flow_snapshot() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            executable: None,
         },
     );
     chat.handle_codex_event(Event {
@@ -1847,6 +1862,7 @@ fn apply_patch_approval_sends_op_with_submission_id() {
         PathBuf::from("file.rs"),
         FileChange::Add {
             content: "fn main(){}\n".into(),
+            executable: None,
         },
     );
     let ev = ApplyPatchApprovalRequestEvent {
@@ -1884,7 +1900,10 @@ fn apply_patch_full_flow_integration_like() {
     let mut changes = HashMap::new();
     changes.insert(
         PathBuf::from("pkg.rs"),
-        FileChange::Add { content: "".into() },
+        FileChange::Add {
+            content: "".into(),
+            executable: None,
+        },
     );
     chat.handle_codex_event(Event {
         id: "sub-xyz".into(),
@@ -1924,7 +1943,10 @@ fn apply_patch_full_flow_integration_like() {
     let mut changes2 = HashMap::new();
     changes2.insert(
         PathBuf::from("pkg.rs"),
-        FileChange::Add { content: "".into() },
+        FileChange::Add {
+            content: "".into(),
+            executable: None,
+        },
     );
     chat.handle_codex_event(Event {
         id: "sub-xyz".into(),
@@ -1941,6 +1963,7 @@ fn apply_patch_full_flow_integration_like() {
             stdout: String::from("ok"),
             stderr: String::new(),
             success: true,
+            fuzzy_hunks: Vec::new(),
         }),
     });
 }
@@ -1955,7 +1978,10 @@ fn apply_patch_untrusted_shows_approval_modal() {
     let mut changes = HashMap::new();
     changes.insert(
         PathBuf::from("a.rs"),
-        FileChange::Add { content: "".into() },
+        FileChange::Add {
+            content: "".into(),
+            executable: None,
+        },
     );
     chat.handle_codex_event(Event {
         id: "sub-1".into(),
@@ -2003,6 +2029,7 @@ fn apply_patch_request_shows_diff_summary() {
         FileChange::Add {
             // Two lines (no trailing empty line counted)
             content: "line one\nline two\n".into(),
+            executable: None,
         },
     );
     chat.handle_codex_event(Event {
@@ -2296,6 +2323,7 @@ fn chatwidget_exec_and_status_layout_vt100_snapshot() {
             exit_code: 0,
             duration: std::time::Duration::from_millis(16000),
             formatted_output: String::new(),
+            sandbox_violations: Vec::new(),
         }),
     });
     chat.handle_codex_event(Event {
@@ -100,9 +100,10 @@ fn collect_rows(changes: &HashMap<PathBuf, FileChange>) -> Vec<Row> {
     let mut rows: Vec<Row> = Vec::new();
     for (path, change) in changes.iter() {
         let (added, removed) = match change {
-            FileChange::Add { content } => (content.lines().count(), 0),
+            FileChange::Add { content, .. } => (content.lines().count(), 0),
             FileChange::Delete { content } => (0, content.lines().count()),
             FileChange::Update { unified_diff, .. } => calculate_add_remove_from_diff(unified_diff),
+            FileChange::Copy { .. } | FileChange::Binary { .. } => (0, 0),
         };
         let move_path = match change {
             FileChange::Update {
@@ -155,6 +156,7 @@ fn render_changes_block(rows: Vec<Row>, wrap_cols: usize, cwd: &Path) -> Vec<RtL
         let verb = match &row.change {
             FileChange::Add { .. } => "Added",
             FileChange::Delete { .. } => "Deleted",
+            FileChange::Copy { .. } => "Copied",
             _ => "Edited",
         };
         header_spans.push(verb.bold());
@@ -195,7 +197,29 @@ fn render_changes_block(rows: Vec<Row>, wrap_cols: usize, cwd: &Path) -> Vec<RtL
 
 fn render_change(change: &FileChange, out: &mut Vec<RtLine<'static>>, width: usize) {
     match change {
-        FileChange::Add { content } => {
+        FileChange::Add {
+            executable: Some(executable),
+            ..
+        }
+        | FileChange::Update {
+            executable: Some(executable),
+            ..
+        }
+        | FileChange::Binary {
+            executable: Some(executable),
+            ..
+        } => {
+            let mode = if *executable {
+                "mode changed to executable"
+            } else {
+                "mode changed to non-executable"
+            };
+            out.push(RtLine::from(mode.dim()));
+        }
+        _ => {}
+    }
+    match change {
+        FileChange::Add { content, .. } => {
             let line_number_width = line_number_width(content.lines().count());
             for (i, raw) in content.lines().enumerate() {
                 out.extend(push_wrapped_diff_line(
@@ -296,6 +320,14 @@ fn render_change(change: &FileChange, out: &mut Vec<RtLine<'static>>, width: usi
                 }
             }
         }
+        FileChange::Copy { source } => {
+            out.push(RtLine::from(
+                format!("copied from {}", source.display()).dim(),
+            ));
+        }
+        FileChange::Binary { size, .. } => {
+            out.push(RtLine::from(format!("binary file, {size} bytes").dim()));
+        }
     }
 }
 
@@ -484,6 +516,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: None,
+                executable: None,
             },
         );
 
@@ -504,6 +537,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: Some(PathBuf::from("new_name.rs")),
+                executable: None,
             },
         );
 
@@ -524,6 +558,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch_a,
                 move_path: None,
+                executable: None,
             },
         );
 
@@ -532,6 +567,7 @@ mod tests {
             PathBuf::from("b.txt"),
             FileChange::Add {
                 content: "new\n".to_string(),
+                executable: None,
             },
         );
 
@@ -547,6 +583,7 @@ mod tests {
             PathBuf::from("new_file.txt"),
             FileChange::Add {
                 content: "alpha\nbeta\n".to_string(),
+                executable: None,
             },
         );
 
@@ -590,6 +627,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: None,
+                executable: None,
             },
         );
 
@@ -613,6 +651,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: None,
+                executable: None,
             },
         );
 
@@ -640,6 +679,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: None,
+                executable: None,
             },
         );
 
@@ -663,6 +703,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: Some(abs_new),
+                executable: None,
             },
         );
 
@@ -357,6 +357,32 @@ pub fn new_approval_decision_cell(
                 ],
             )
         }
+        ApprovedAlways => {
+            let snippet = Span::from(exec_snippet(&command)).dim();
+            (
+                "✔ ".green(),
+                vec![
+                    "You ".into(),
+                    "approved".bold(),
+                    " codex to always run ".into(),
+                    snippet,
+                    " in this project".bold(),
+                ],
+            )
+        }
+        ApprovedWithWritableRoots => {
+            let snippet = Span::from(exec_snippet(&command)).dim();
+            (
+                "✔ ".green(),
+                vec![
+                    "You ".into(),
+                    "allowed".bold(),
+                    " codex to write to the suggested directories and run ".into(),
+                    snippet,
+                    " in the sandbox".bold(),
+                ],
+            )
+        }
         Denied => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
@@ -6,6 +6,8 @@ use std::sync::Arc;
 use chrono::DateTime;
 use chrono::Utc;
 use codex_core::ConversationItem;
+use codex_core::ConversationSearchPage;
+use codex_core::ConversationSearchResult;
 use codex_core::ConversationsPage;
 use codex_core::Cursor;
 use codex_core::INTERACTIVE_SESSION_SOURCES;
@@ -55,17 +57,33 @@ struct PageLoadRequest {
 
 type PageLoader = Arc<dyn Fn(PageLoadRequest) + Send + Sync>;
 
+#[derive(Clone)]
+struct SearchRequest {
+    codex_home: PathBuf,
+    query: String,
+}
+
+/// Runs a full-text search over the recorded rollouts; results arrive as
+/// [`BackgroundEvent::SearchResults`].
+type SearchLoader = Arc<dyn Fn(SearchRequest) + Send + Sync>;
+
 enum BackgroundEvent {
     PageLoaded {
         request_token: usize,
         search_token: Option<usize>,
         page: std::io::Result<ConversationsPage>,
     },
+    SearchResults {
+        query: String,
+        page: std::io::Result<ConversationSearchPage>,
+    },
 }
 
-/// Interactive session picker that lists recorded rollout files with simple
-/// search and pagination. Shows the first user input as the preview, relative
-/// time (e.g., "5 seconds ago"), and the absolute path.
+/// Interactive session picker that lists recorded rollout files with search
+/// and pagination. Typing filters by the first user input and also runs a
+/// full-text search over messages, commands and touched files. Shows the first
+/// user input as the preview, relative time (e.g., "5 seconds ago"), and the
+/// absolute path.
 pub async fn run_resume_picker(tui: &mut Tui, codex_home: &Path) -> Result<ResumeSelection> {
     let alt = AltScreenGuard::enter(tui);
     let (bg_tx, bg_rx) = mpsc::unbounded_channel();
@@ -89,11 +107,30 @@ pub async fn run_resume_picker(tui: &mut Tui, codex_home: &Path) -> Result<Resum
         });
     });
 
+    let search_tx = bg_tx.clone();
+    let search_loader: SearchLoader = Arc::new(move |request: SearchRequest| {
+        let tx = search_tx.clone();
+        tokio::spawn(async move {
+            let page = RolloutRecorder::search_conversations(
+                &request.codex_home,
+                &request.query,
+                PAGE_SIZE,
+                INTERACTIVE_SESSION_SOURCES,
+            )
+            .await;
+            let _ = tx.send(BackgroundEvent::SearchResults {
+                query: request.query,
+                page,
+            });
+        });
+    });
+
     let mut state = PickerState::new(
         codex_home.to_path_buf(),
         alt.tui.frame_requester(),
         page_loader,
     );
+    state.set_search_loader(search_loader);
     state.load_initial_page().await?;
     state.request_frame();
 
@@ -166,6 +203,10 @@ struct PickerState {
     next_request_token: usize,
     next_search_token: usize,
     page_loader: PageLoader,
+    search_loader: Option<SearchLoader>,
+    /// Full-text matches for the current query, including sessions that have
+    /// not been paged in yet.
+    full_text_rows: Vec<Row>,
     view_rows: Option<usize>,
 }
 
@@ -247,10 +288,16 @@ impl PickerState {
             next_request_token: 0,
             next_search_token: 0,
             page_loader,
+            search_loader: None,
+            full_text_rows: Vec::new(),
             view_rows: None,
         }
     }
 
+    fn set_search_loader(&mut self, search_loader: SearchLoader) {
+        self.search_loader = Some(search_loader);
+    }
+
     fn request_frame(&self) {
         self.requester.schedule_frame();
     }
@@ -363,6 +410,20 @@ impl PickerState {
                 let completed_token = pending.search_token.or(search_token);
                 self.continue_search_if_token_matches(completed_token);
             }
+            BackgroundEvent::SearchResults { query, page } => {
+                if query != self.query {
+                    return Ok(());
+                }
+                match page {
+                    Ok(page) => {
+                        self.full_text_rows = page.items.iter().map(search_result_to_row).collect();
+                        self.apply_filter();
+                    }
+                    Err(err) => {
+                        tracing::warn!("failed to search sessions: {err}");
+                    }
+                }
+            }
         }
         Ok(())
     }
@@ -403,12 +464,26 @@ impl PickerState {
             self.filtered_rows = self.all_rows.clone();
         } else {
             let q = self.query.to_lowercase();
-            self.filtered_rows = self
+            let full_text_paths: HashSet<&PathBuf> =
+                self.full_text_rows.iter().map(|r| &r.path).collect();
+            let mut rows: Vec<Row> = self
                 .all_rows
                 .iter()
-                .filter(|r| r.preview.to_lowercase().contains(&q))
+                .filter(|r| {
+                    r.preview.to_lowercase().contains(&q) || full_text_paths.contains(&r.path)
+                })
                 .cloned()
                 .collect();
+            if !self.full_text_rows.is_empty() {
+                rows.extend(
+                    self.full_text_rows
+                        .iter()
+                        .filter(|r| !self.seen_paths.contains(&r.path))
+                        .cloned(),
+                );
+                rows.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+            }
+            self.filtered_rows = rows;
         }
         if self.selected >= self.filtered_rows.len() {
             self.selected = self.filtered_rows.len().saturating_sub(1);
@@ -426,6 +501,15 @@ impl PickerState {
         }
         self.query = new_query;
         self.selected = 0;
+        self.full_text_rows.clear();
+        if !self.query.is_empty()
+            && let Some(search_loader) = &self.search_loader
+        {
+            search_loader(SearchRequest {
+                codex_home: self.codex_home.clone(),
+                query: self.query.clone(),
+            });
+        }
         self.apply_filter();
         if self.query.is_empty() {
             self.search_state = SearchState::Idle;
@@ -599,6 +683,29 @@ fn head_to_row(item: &ConversationItem) -> Row {
     }
 }
 
+fn search_result_to_row(result: &ConversationSearchResult) -> Row {
+    let created_at = result.created_at.as_deref().and_then(parse_timestamp_str);
+    let updated_at = result
+        .updated_at
+        .as_deref()
+        .and_then(parse_timestamp_str)
+        .or(created_at);
+    let preview = result
+        .preview
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .unwrap_or("(no message yet)")
+        .to_string();
+
+    Row {
+        path: result.path.clone(),
+        preview,
+        created_at,
+        updated_at,
+    }
+}
+
 fn parse_timestamp_str(ts: &str) -> Option<DateTime<Utc>> {
     chrono::DateTime::parse_from_rfc3339(ts)
         .map(|dt| dt.with_timezone(&Utc))
@@ -1404,4 +1511,78 @@ mod tests {
         assert!(!state.search_state.is_active());
         assert!(state.pagination.reached_scan_cap);
     }
+
+    #[test]
+    fn full_text_results_add_rows_for_unloaded_sessions() {
+        let loader: PageLoader = Arc::new(|_| {});
+        let recorded_searches: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let search_sink = recorded_searches.clone();
+        let search_loader: SearchLoader = Arc::new(move |req: SearchRequest| {
+            search_sink.lock().unwrap().push(req.query);
+        });
+
+        let mut state =
+            PickerState::new(PathBuf::from("/tmp"), FrameRequester::test_dummy(), loader);
+        state.set_search_loader(search_loader);
+        state.reset_pagination();
+        state.ingest_page(page(
+            vec![
+                make_item("/tmp/new.jsonl", "2025-01-03T00:00:00Z", "refactor"),
+                make_item("/tmp/mid.jsonl", "2025-01-02T00:00:00Z", "add cargo tests"),
+            ],
+            None,
+            2,
+            false,
+        ));
+
+        state.set_query("cargo".to_string());
+        assert_eq!(
+            *recorded_searches.lock().unwrap(),
+            vec!["cargo".to_string()]
+        );
+
+        let search_result = |path: &str, ts: &str, preview: &str| ConversationSearchResult {
+            path: PathBuf::from(path),
+            conversation_id: None,
+            preview: Some(preview.to_string()),
+            created_at: Some(ts.to_string()),
+            updated_at: Some(ts.to_string()),
+            matches: Vec::new(),
+        };
+        let results = ConversationSearchPage {
+            items: vec![
+                search_result("/tmp/new.jsonl", "2025-01-03T00:00:00Z", "refactor"),
+                search_result("/tmp/old.jsonl", "2025-01-01T00:00:00Z", "fix build"),
+            ],
+            ..Default::default()
+        };
+
+        // Results for a stale query are ignored.
+        state
+            .handle_background_event(BackgroundEvent::SearchResults {
+                query: "carg".to_string(),
+                page: Ok(ConversationSearchPage {
+                    items: results.items.clone(),
+                    ..Default::default()
+                }),
+            })
+            .unwrap();
+        assert_eq!(state.filtered_rows.len(), 1);
+
+        state
+            .handle_background_event(BackgroundEvent::SearchResults {
+                query: "cargo".to_string(),
+                page: Ok(results),
+            })
+            .unwrap();
+        let paths: Vec<_> = state
+            .filtered_rows
+            .iter()
+            .map(|row| row.path.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            paths,
+            vec!["/tmp/new.jsonl", "/tmp/mid.jsonl", "/tmp/old.jsonl"]
+        );
+    }
 }
@@ -70,7 +70,9 @@ fn status_snapshot_includes_reasoning_details() {
     config.model_reasoning_summary = ReasoningSummary::Detailed;
     config.sandbox_policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: Vec::new(),
+        deny_read: Vec::new(),
         network_access: false,
+        network_allowlist: Vec::new(),
         exclude_tmpdir_env_var: false,
         exclude_slash_tmp: false,
     };
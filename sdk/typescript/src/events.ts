@@ -7,6 +7,8 @@ export type ThreadStartedEvent = {
   type: "thread.started";
   /** The identifier of the new thread. Can be used to resume the thread later. */
   thread_id: string;
+  /** Version of the event stream. It changes whenever the shape of any event does. */
+  schema_version: number;
 };
 
 /**
@@ -49,6 +51,8 @@ export type ItemStartedEvent = {
 export type ItemUpdatedEvent = {
   type: "item.updated";
   item: ThreadItem;
+  /** Text appended to the agent message, reasoning or command output since the previous event for this item. `item` then leaves the text empty and `item.completed` carries it in full. */
+  delta?: string;
 };
 
 /** Signals that an item has reached a terminal state—either success or failure. */